@@ -41,13 +41,17 @@ use readingbuddy::GoodreadsMatch;
 use readingbuddy::koreader::UnmatchedSidecar;
 use readingbuddy::providers::ProviderId;
 use readingbuddy::{
-    Book, BookFile, BookImportStats, BookSort, BookTag, CalibreBook, CalibreBookReport,
-    CalibreMatch, CalibreReport, CreatedNote, DeviceBook, DeviceScan, DeviceState, Diagnostic,
-    DiagnosticKind, ErrorClass, FileIdentity, FileImportReport, FileMatch, FileOutcome,
-    FlashcardRow, GoodreadsBookReport, GoodreadsReport, Highlight, ImportReport, KoStatus,
-    MatchCandidate, MatchMethod, MergeReport, NewNoteInput, NoteKind, NoteRecord, NoteSearchHit,
-    OutgoingLink, PullReport, RankedResult, Rating, RatingScale, Reading, SearchOutcome,
-    SearchRequest, Severity, TextOutcome, UnmatchedRow,
+    Backlink, Book, BookFile, BookFilter, BookImportStats, BookPage, BookSort, BookStatus, BookTag,
+    CalibreBook, CalibreBookReport, CalibreMatch, CalibreReport, CardFormat, CardKind, CreatedNote,
+    Device, DeviceBook, DeviceScan, DeviceSource, DeviceState, Diagnostic, DiagnosticKind,
+    ErrorClass, FileIdentity, FileImportReport, FileMatch, FileOutcome, FlashcardRow,
+    GoodreadsBookReport, GoodreadsReport, Grade, Highlight, HighlightSearchHit, ImportReport,
+    KoStatus, LibraryHit, MatchCandidate, MatchMethod, MergeReport, NewNoteInput, NoteKind,
+    NoteRecord, NoteSearchHit, OutgoingLink, PairedDevice, PairingCode, PeriodStats, PluginInstall,
+    PluginRemoval, PluginState, PluginStatus, Promoted, PullReport, PushChange, PushReport,
+    RankedResult, Rating, RatingScale, Reading, SearchOutcome, SearchRequest, Severity, Shelf,
+    ShelfRule, SidecarPayload, StatsGrain, StudyCard, TagCandidate, TextOutcome, UnmatchedRow,
+    Work, WorkHistory, format_day,
 };
 
 /// A path, as far as JSON can carry one. See the module doc.
@@ -97,6 +101,16 @@ pub struct BookDto {
     pub description: Option<String>,
     #[serde(default)]
     pub first_sentence: Option<String>,
+    #[serde(default)]
+    pub series: Option<String>,
+    #[serde(default)]
+    pub series_index: Option<f64>,
+    /// Read-only, like the reading projections below: `attach_edition` and
+    /// `detach_edition` are the writers.
+    #[serde(default)]
+    pub work_id: Option<i64>,
+    #[serde(default)]
+    pub openlibrary_work: Option<String>,
     /// Read-only projections of the **current** reading. Sending them back in a
     /// `save_book` changes nothing: `upsert_book` has ignored these four since
     /// migration `0005`, and `update_progress` is the writer.
@@ -136,6 +150,10 @@ impl From<Book> for BookDto {
             page_count: b.page_count,
             description: b.description,
             first_sentence: b.first_sentence,
+            series: b.series,
+            series_index: b.series_index,
+            work_id: b.work_id,
+            openlibrary_work: b.openlibrary_work,
             current_page: b.current_page,
             finished: b.finished,
             date_started: b.date_started,
@@ -174,6 +192,10 @@ impl From<BookDto> for Book {
             page_count: d.page_count,
             description: d.description,
             first_sentence: d.first_sentence,
+            series: d.series,
+            series_index: d.series_index,
+            work_id: d.work_id,
+            openlibrary_work: d.openlibrary_work,
             current_page: d.current_page,
             finished: d.finished,
             date_started: d.date_started,
@@ -191,6 +213,7 @@ pub enum BookSortDto {
     LastModified,
     Title,
     Progress,
+    Series,
 }
 
 impl From<BookSortDto> for BookSort {
@@ -199,6 +222,112 @@ impl From<BookSortDto> for BookSort {
             BookSortDto::LastModified => BookSort::LastModified,
             BookSortDto::Title => BookSort::Title,
             BookSortDto::Progress => BookSort::Progress,
+            BookSortDto::Series => BookSort::Series,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookStatusDto {
+    Unstarted,
+    Reading,
+    Finished,
+    Abandoned,
+}
+
+impl From<BookStatusDto> for BookStatus {
+    fn from(s: BookStatusDto) -> Self {
+        match s {
+            BookStatusDto::Unstarted => BookStatus::Unstarted,
+            BookStatusDto::Reading => BookStatus::Reading,
+            BookStatusDto::Finished => BookStatus::Finished,
+            BookStatusDto::Abandoned => BookStatus::Abandoned,
+        }
+    }
+}
+
+impl From<BookStatus> for BookStatusDto {
+    fn from(s: BookStatus) -> Self {
+        match s {
+            BookStatus::Unstarted => BookStatusDto::Unstarted,
+            BookStatus::Reading => BookStatusDto::Reading,
+            BookStatus::Finished => BookStatusDto::Finished,
+            BookStatus::Abandoned => BookStatusDto::Abandoned,
+        }
+    }
+}
+
+/// Every field absent is the whole library, so `{}` is a valid filter and an
+/// older client that sends none is unaffected.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookFilterDto {
+    #[serde(default)]
+    pub status: Option<BookStatusDto>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub year_from: Option<i64>,
+    #[serde(default)]
+    pub year_to: Option<i64>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub has_cover: Option<bool>,
+    #[serde(default)]
+    pub has_file: Option<bool>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub series: Option<String>,
+    #[serde(default)]
+    pub min_stars: Option<u8>,
+    #[serde(default)]
+    pub finished_year: Option<i64>,
+    #[serde(default)]
+    pub has_notes: Option<bool>,
+    #[serde(default)]
+    pub shelf: Option<String>,
+}
+
+impl From<BookFilterDto> for BookFilter {
+    fn from(d: BookFilterDto) -> Self {
+        BookFilter {
+            status: d.status.map(Into::into),
+            author: d.author,
+            year_from: d.year_from,
+            year_to: d.year_to,
+            language: d.language,
+            tag: d.tag,
+            has_cover: d.has_cover,
+            has_file: d.has_file,
+            source: d.source,
+            series: d.series,
+            min_stars: d.min_stars,
+            finished_year: d.finished_year,
+            has_notes: d.has_notes,
+            shelf: d.shelf,
+        }
+    }
+}
+
+/// One page of a listing. `total` counts every match, so a client pages until
+/// `offset + books.len()` reaches it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookPageDto {
+    pub books: Vec<BookDto>,
+    pub total: i64,
+    pub offset: i64,
+}
+
+impl From<BookPage> for BookPageDto {
+    fn from(p: BookPage) -> Self {
+        BookPageDto {
+            books: p.books.into_iter().map(Into::into).collect(),
+            total: p.total,
+            offset: p.offset,
         }
     }
 }
@@ -208,9 +337,8 @@ pub struct BookTagDto {
     pub tag: String,
     pub source: String,
     /// The origin's own string, before our normalization. Kept because the
-    /// normalization is ours and the shelf name is theirs — and collections are
-    /// deliberately still deferred, so this is the raw material that design will
-    /// eventually be made against.
+    /// normalization is ours and the shelf name is theirs — and a promoted
+    /// shelf is called by it.
     #[serde(default)]
     pub raw: Option<String>,
 }
@@ -225,6 +353,110 @@ impl From<BookTag> for BookTagDto {
     }
 }
 
+/// A smart shelf's rule. Every field absent matches everything, though a rule
+/// with none set is refused.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShelfRuleDto {
+    #[serde(default)]
+    pub status: Option<BookStatusDto>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub min_stars: Option<u8>,
+    #[serde(default)]
+    pub finished_year: Option<i64>,
+    #[serde(default)]
+    pub has_notes: Option<bool>,
+}
+
+impl From<ShelfRuleDto> for ShelfRule {
+    fn from(d: ShelfRuleDto) -> Self {
+        ShelfRule {
+            status: d.status.map(Into::into),
+            tag: d.tag,
+            author: d.author,
+            min_stars: d.min_stars,
+            finished_year: d.finished_year,
+            has_notes: d.has_notes,
+        }
+    }
+}
+
+impl From<ShelfRule> for ShelfRuleDto {
+    fn from(r: ShelfRule) -> Self {
+        ShelfRuleDto {
+            status: r.status.map(Into::into),
+            tag: r.tag,
+            author: r.author,
+            min_stars: r.min_stars,
+            finished_year: r.finished_year,
+            has_notes: r.has_notes,
+        }
+    }
+}
+
+/// A shelf; `rule` is set for a smart one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShelfDto {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub rule: Option<ShelfRuleDto>,
+    pub books: i64,
+}
+
+impl From<Shelf> for ShelfDto {
+    fn from(s: Shelf) -> Self {
+        ShelfDto {
+            id: s.id,
+            name: s.name,
+            rule: s.rule.map(Into::into),
+            books: s.books,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagCandidateDto {
+    pub source: String,
+    pub tag: String,
+    #[serde(default)]
+    pub raw: Option<String>,
+    pub books: i64,
+    pub shelved: bool,
+}
+
+impl From<TagCandidate> for TagCandidateDto {
+    fn from(t: TagCandidate) -> Self {
+        TagCandidateDto {
+            source: t.source,
+            tag: t.tag,
+            raw: t.raw,
+            books: t.books,
+            shelved: t.shelved,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromotedDto {
+    pub shelf: String,
+    pub created: bool,
+    pub added: usize,
+}
+
+impl From<Promoted> for PromotedDto {
+    fn from(p: Promoted) -> Self {
+        PromotedDto {
+            shelf: p.shelf,
+            created: p.created,
+            added: p.added,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MergeReportDto {
     pub src_existed: bool,
@@ -237,6 +469,8 @@ pub struct MergeReportDto {
     pub device_links_moved: usize,
     pub files_moved: usize,
     #[serde(default)]
+    pub events_moved: usize,
+    #[serde(default)]
     pub orphaned_cover: Option<String>,
 }
 
@@ -252,11 +486,65 @@ impl From<MergeReport> for MergeReportDto {
             flashcards_dropped: r.flashcards_dropped,
             device_links_moved: r.device_links_moved,
             files_moved: r.files_moved,
+            events_moved: r.events_moved,
             orphaned_cover: r.orphaned_cover,
         }
     }
 }
 
+/// A work and every edition of it, oldest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkDto {
+    pub id: i64,
+    pub title: String,
+    #[serde(default)]
+    pub openlibrary_key: Option<String>,
+    pub editions: Vec<BookDto>,
+}
+
+impl From<Work> for WorkDto {
+    fn from(w: Work) -> Self {
+        WorkDto {
+            id: w.id,
+            title: w.title,
+            openlibrary_key: w.openlibrary_key,
+            editions: w.editions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A book read as a work. `has_read` and `read_as` are the engine's answers,
+/// carried rather than left to each client to re-derive from `readings`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkHistoryDto {
+    /// `None` for a book in no work, which is a work of one.
+    #[serde(default)]
+    pub work: Option<WorkDto>,
+    pub editions: Vec<BookDto>,
+    pub readings: Vec<ReadingDto>,
+    #[serde(default)]
+    pub rating: Option<RatingDto>,
+    pub has_read: bool,
+    /// The edition last finished, by id.
+    #[serde(default)]
+    pub read_as: Option<i64>,
+}
+
+impl From<WorkHistory> for WorkHistoryDto {
+    fn from(h: WorkHistory) -> Self {
+        let has_read = h.has_read();
+        let read_as = h.read_as().and_then(|b| b.id);
+        WorkHistoryDto {
+            work: h.work.map(Into::into),
+            editions: h.editions.into_iter().map(Into::into).collect(),
+            readings: h.readings.into_iter().map(Into::into).collect(),
+            rating: h.rating.map(Into::into),
+            has_read,
+            read_as,
+        }
+    }
+}
+
 // ---- readings and highlights ----------------------------------------------
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -278,6 +566,13 @@ pub struct ReadingDto {
     pub ko_percent: Option<f64>,
     #[serde(default)]
     pub ko_rating: Option<i64>,
+    /// When the device last had the book open. Only a stock Kobo reports it.
+    #[serde(default)]
+    pub ko_last_read: Option<i64>,
+    /// The paired reader the device fields last came from, if they came over
+    /// the network.
+    #[serde(default)]
+    pub device_id: Option<i64>,
     pub created_at: i64,
     pub last_modified: i64,
 }
@@ -295,6 +590,8 @@ impl From<Reading> for ReadingDto {
             ko_status: r.ko_status,
             ko_percent: r.ko_percent,
             ko_rating: r.ko_rating,
+            ko_last_read: r.ko_last_read,
+            device_id: r.device_id,
             created_at: r.created_at,
             last_modified: r.last_modified,
         }
@@ -329,6 +626,9 @@ pub struct HighlightDto {
     pub reading_id: Option<i64>,
     pub source: String,
     pub created_at: i64,
+    /// The paired reader that first pushed it. `null` for everything else.
+    #[serde(default)]
+    pub device_id: Option<i64>,
 }
 
 impl From<Highlight> for HighlightDto {
@@ -345,6 +645,85 @@ impl From<Highlight> for HighlightDto {
             reading_id: h.reading_id,
             source: h.source,
             created_at: h.created_at,
+            device_id: h.device_id,
+        }
+    }
+}
+
+/// A highlight a full-text query found. Best first in the list it comes in;
+/// the rank itself stays in the engine, where it means something.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighlightSearchHitDto {
+    pub highlight: HighlightDto,
+    pub snippet: String,
+}
+
+impl From<HighlightSearchHit> for HighlightSearchHitDto {
+    fn from(h: HighlightSearchHit) -> Self {
+        HighlightSearchHitDto {
+            highlight: h.highlight.into(),
+            snippet: h.snippet,
+        }
+    }
+}
+
+// ---- reading statistics ---------------------------------------------------
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsGrainDto {
+    Day,
+    #[default]
+    Month,
+    Year,
+    All,
+}
+
+impl From<StatsGrainDto> for StatsGrain {
+    fn from(g: StatsGrainDto) -> Self {
+        match g {
+            StatsGrainDto::Day => StatsGrain::Day,
+            StatsGrainDto::Month => StatsGrain::Month,
+            StatsGrainDto::Year => StatsGrain::Year,
+            StatsGrainDto::All => StatsGrain::All,
+        }
+    }
+}
+
+/// One period. Dates cross as `YYYY-MM-DD` — a calendar day has no instant,
+/// so unix seconds would pick a timezone for it by accident.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodStatsDto {
+    pub label: String,
+    pub from: String,
+    pub to: String,
+    pub books_finished: usize,
+    pub activity_days: usize,
+    pub highlights: usize,
+    pub notes: usize,
+    pub links: usize,
+    /// `null` is "no source measured this period", never zero.
+    #[serde(default)]
+    pub pages: Option<i64>,
+    #[serde(default)]
+    pub minutes: Option<i64>,
+    pub timed_days: usize,
+}
+
+impl From<PeriodStats> for PeriodStatsDto {
+    fn from(p: PeriodStats) -> Self {
+        PeriodStatsDto {
+            label: p.label,
+            from: format_day(p.from),
+            to: format_day(p.to),
+            books_finished: p.books_finished,
+            activity_days: p.activity_days,
+            highlights: p.highlights,
+            notes: p.notes,
+            links: p.links,
+            pages: p.pages,
+            minutes: p.minutes,
+            timed_days: p.timed_days,
         }
     }
 }
@@ -431,6 +810,9 @@ pub struct NewNoteDto {
     pub title: Option<String>,
     #[serde(default)]
     pub body: String,
+    /// A note template by name; see `readingbuddy::templates`.
+    #[serde(default)]
+    pub template: Option<String>,
 }
 
 impl From<NewNoteDto> for NewNoteInput {
@@ -444,6 +826,7 @@ impl From<NewNoteDto> for NewNoteInput {
             kind: d.kind.into(),
             title: d.title,
             body: d.body,
+            template: d.template,
         }
     }
 }
@@ -483,6 +866,23 @@ impl From<NoteSearchHit> for NoteSearchHitDto {
     }
 }
 
+/// One result of the library search: a note or a highlight, in rank order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LibraryHitDto {
+    Note(NoteSearchHitDto),
+    Highlight(HighlightSearchHitDto),
+}
+
+impl From<LibraryHit> for LibraryHitDto {
+    fn from(h: LibraryHit) -> Self {
+        match h {
+            LibraryHit::Note(n) => LibraryHitDto::Note(n.into()),
+            LibraryHit::Highlight(x) => LibraryHitDto::Highlight(x.into()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OutgoingLinkDto {
     /// The `[[wikilink]]` as written.
@@ -492,6 +892,13 @@ pub struct OutgoingLinkDto {
     /// written, and a client shows it as text rather than dropping it.
     #[serde(default)]
     pub note: Option<NoteDto>,
+    /// The heading, or `^block`, the link points into; `null` for the note.
+    #[serde(default)]
+    pub anchor: Option<String>,
+    /// Whether `note` still has `anchor`. A resolved note without it is a link
+    /// whose *section* dangles: the client opens the note at the top.
+    #[serde(default)]
+    pub anchor_found: bool,
 }
 
 impl From<OutgoingLink> for OutgoingLinkDto {
@@ -499,6 +906,30 @@ impl From<OutgoingLink> for OutgoingLinkDto {
         OutgoingLinkDto {
             target_title: l.target_title,
             note: l.to.map(Into::into),
+            anchor: l.anchor,
+            anchor_found: l.anchor_found,
+        }
+    }
+}
+
+/// A note that links here, and the section of this note it cites.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklinkDto {
+    pub note: NoteDto,
+    /// The heading, or `^block`, of the linked note; `null` for all of it.
+    #[serde(default)]
+    pub anchor: Option<String>,
+    /// False when the linked note no longer has that section.
+    #[serde(default)]
+    pub anchor_found: bool,
+}
+
+impl From<Backlink> for BacklinkDto {
+    fn from(l: Backlink) -> Self {
+        BacklinkDto {
+            note: l.from.into(),
+            anchor: l.anchor,
+            anchor_found: l.anchor_found,
         }
     }
 }
@@ -730,6 +1161,110 @@ impl From<FlashcardRow> for FlashcardDto {
     }
 }
 
+/// What [`Request::ExportFlashcards`](crate::Request::ExportFlashcards) builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardFormatDto {
+    #[default]
+    Tsv,
+    Apkg,
+}
+
+impl From<CardFormatDto> for CardFormat {
+    fn from(f: CardFormatDto) -> Self {
+        match f {
+            CardFormatDto::Tsv => CardFormat::Tsv,
+            CardFormatDto::Apkg => CardFormat::Apkg,
+        }
+    }
+}
+
+// ---- study -----------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GradeDto {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl From<GradeDto> for Grade {
+    fn from(g: GradeDto) -> Self {
+        match g {
+            GradeDto::Again => Grade::Again,
+            GradeDto::Hard => Grade::Hard,
+            GradeDto::Good => Grade::Good,
+            GradeDto::Easy => Grade::Easy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardKindDto {
+    Word,
+    Highlight,
+}
+
+impl From<CardKind> for CardKindDto {
+    fn from(k: CardKind) -> Self {
+        match k {
+            CardKind::Word => CardKindDto::Word,
+            CardKind::Highlight => CardKindDto::Highlight,
+        }
+    }
+}
+
+/// One card to review, with the schedule that made it due. `due_at` and
+/// `last_reviewed_at` are unix seconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StudyCardDto {
+    pub id: i64,
+    pub kind: CardKindDto,
+    pub front: String,
+    #[serde(default)]
+    pub context: Option<String>,
+    pub book_id: i64,
+    pub book_title: String,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    #[serde(default)]
+    pub chapter: Option<String>,
+    #[serde(default)]
+    pub page: Option<i64>,
+    pub ease: f64,
+    pub interval_days: i64,
+    pub repetitions: i64,
+    pub lapses: i64,
+    pub due_at: i64,
+    #[serde(default)]
+    pub last_reviewed_at: Option<i64>,
+}
+
+impl From<StudyCard> for StudyCardDto {
+    fn from(c: StudyCard) -> Self {
+        StudyCardDto {
+            id: c.id,
+            kind: c.kind.into(),
+            front: c.front,
+            context: c.context,
+            book_id: c.book_id,
+            book_title: c.book_title,
+            authors: c.authors,
+            chapter: c.chapter,
+            page: c.page,
+            ease: c.schedule.ease,
+            interval_days: c.schedule.interval_days,
+            repetitions: c.schedule.repetitions,
+            lapses: c.schedule.lapses,
+            due_at: c.due_at,
+            last_reviewed_at: c.last_reviewed_at,
+        }
+    }
+}
+
 // ---- search ----------------------------------------------------------------
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -825,6 +1360,7 @@ impl From<SearchOutcome> for SearchOutcomeDto {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SeverityDto {
+    Info,
     Warning,
     Error,
 }
@@ -832,6 +1368,7 @@ pub enum SeverityDto {
 impl From<Severity> for SeverityDto {
     fn from(s: Severity) -> Self {
         match s {
+            Severity::Info => SeverityDto::Info,
             Severity::Warning => SeverityDto::Warning,
             Severity::Error => SeverityDto::Error,
         }
@@ -933,6 +1470,36 @@ pub enum DiagnosticKindDto {
     CalibreCoverUnreadable {
         path: String,
     },
+    KindleEntrySkipped {
+        entry: usize,
+    },
+    KindleDateUnread {
+        entry: usize,
+    },
+    KoboDatabaseUnreadable {
+        path: String,
+        class: ErrorClassDto,
+    },
+    KoboBookNotIdentified {
+        path: String,
+    },
+    NoteRefreshed {
+        path: String,
+    },
+    NoteIndexed {
+        path: String,
+    },
+    NoteMoved {
+        from: String,
+        to: String,
+    },
+    NoteTombstoned {
+        path: String,
+    },
+    NoteUnreadable {
+        path: String,
+        class: ErrorClassDto,
+    },
 }
 
 impl From<DiagnosticKind> for DiagnosticKindDto {
@@ -992,6 +1559,34 @@ impl From<DiagnosticKind> for DiagnosticKindDto {
             K::CalibreCoverUnreadable { path } => DiagnosticKindDto::CalibreCoverUnreadable {
                 path: path_str(&path),
             },
+            K::KindleEntrySkipped { entry } => DiagnosticKindDto::KindleEntrySkipped { entry },
+            K::KindleDateUnread { entry } => DiagnosticKindDto::KindleDateUnread { entry },
+            K::KoboDatabaseUnreadable { path, class } => {
+                DiagnosticKindDto::KoboDatabaseUnreadable {
+                    path: path_str(&path),
+                    class: class.into(),
+                }
+            }
+            K::KoboBookNotIdentified { path } => DiagnosticKindDto::KoboBookNotIdentified {
+                path: path_str(&path),
+            },
+            K::NoteRefreshed { path } => DiagnosticKindDto::NoteRefreshed {
+                path: path_str(&path),
+            },
+            K::NoteIndexed { path } => DiagnosticKindDto::NoteIndexed {
+                path: path_str(&path),
+            },
+            K::NoteMoved { from, to } => DiagnosticKindDto::NoteMoved {
+                from: path_str(&from),
+                to: path_str(&to),
+            },
+            K::NoteTombstoned { path } => DiagnosticKindDto::NoteTombstoned {
+                path: path_str(&path),
+            },
+            K::NoteUnreadable { path, class } => DiagnosticKindDto::NoteUnreadable {
+                path: path_str(&path),
+                class: class.into(),
+            },
         }
     }
 }
@@ -1059,6 +1654,25 @@ impl From<MatchMethod> for MatchMethodDto {
     }
 }
 
+/// Which of the reader's own records a device row came from. Defaults to
+/// KOReader, which is what every row was before a Kobo's database was read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceSourceDto {
+    #[default]
+    Koreader,
+    Kobo,
+}
+
+impl From<DeviceSource> for DeviceSourceDto {
+    fn from(s: DeviceSource) -> Self {
+        match s {
+            DeviceSource::Koreader => DeviceSourceDto::Koreader,
+            DeviceSource::Kobo => DeviceSourceDto::Kobo,
+        }
+    }
+}
+
 /// The device's own status. `Other` keeps the raw string rather than collapsing
 /// to a known one — a status KOReader grew and we do not model is exactly the
 /// thing worth reporting, and guessing at it would be silent.
@@ -1173,6 +1787,220 @@ impl From<PullReport> for PullReportDto {
     }
 }
 
+/// A sidecar as the reader sends it: the Lua text as a string, or the table
+/// itself as a JSON object. Untagged, because the two are already told apart
+/// by their JSON type and a plugin should not have to say which it sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SidecarPayloadDto {
+    Lua(String),
+    Json(serde_json::Value),
+}
+
+impl From<SidecarPayloadDto> for SidecarPayload {
+    fn from(p: SidecarPayloadDto) -> Self {
+        match p {
+            SidecarPayloadDto::Lua(src) => SidecarPayload::Lua(src),
+            SidecarPayloadDto::Json(json) => SidecarPayload::Json(json),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushChangeDto {
+    pub index: i64,
+    pub highlight_id: i64,
+    pub text: String,
+    #[serde(default)]
+    pub before: Option<String>,
+    pub after: String,
+}
+
+impl From<PushChange> for PushChangeDto {
+    fn from(c: PushChange) -> Self {
+        PushChangeDto {
+            index: c.index,
+            highlight_id: c.highlight_id,
+            text: c.text,
+            before: c.before,
+            after: c.after,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushReportDto {
+    pub sidecar: String,
+    pub book_id: i64,
+    pub dry_run: bool,
+    pub changes: Vec<PushChangeDto>,
+    #[serde(default)]
+    pub backup: Option<String>,
+}
+
+impl From<PushReport> for PushReportDto {
+    fn from(r: PushReport) -> Self {
+        PushReportDto {
+            sidecar: path_str(&r.sidecar),
+            book_id: r.book_id,
+            dry_run: r.dry_run,
+            changes: r.changes.into_iter().map(Into::into).collect(),
+            backup: opt_path(&r.backup),
+        }
+    }
+}
+
+/// readingbuddy's KOReader plugin on a reader, against this build's version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum PluginStateDto {
+    NotInstalled,
+    Current,
+    Older { installed: u32 },
+    Newer { installed: u32 },
+    Foreign { reason: String },
+}
+
+impl From<PluginState> for PluginStateDto {
+    fn from(s: PluginState) -> Self {
+        match s {
+            PluginState::NotInstalled => PluginStateDto::NotInstalled,
+            PluginState::Current => PluginStateDto::Current,
+            PluginState::Older { installed } => PluginStateDto::Older { installed },
+            PluginState::Newer { installed } => PluginStateDto::Newer { installed },
+            PluginState::Foreign { reason } => PluginStateDto::Foreign { reason },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginStatusDto {
+    pub plugin_dir: String,
+    pub bundled: u32,
+    pub state: PluginStateDto,
+}
+
+impl From<PluginStatus> for PluginStatusDto {
+    fn from(s: PluginStatus) -> Self {
+        PluginStatusDto {
+            plugin_dir: path_str(&s.plugin_dir),
+            bundled: s.bundled,
+            state: s.state.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginInstallDto {
+    pub plugin_dir: String,
+    pub version: u32,
+    #[serde(default)]
+    pub replaced: Option<u32>,
+    pub written: Vec<String>,
+    #[serde(default)]
+    pub removed: Vec<String>,
+}
+
+impl From<PluginInstall> for PluginInstallDto {
+    fn from(r: PluginInstall) -> Self {
+        PluginInstallDto {
+            plugin_dir: path_str(&r.plugin_dir),
+            version: r.version,
+            replaced: r.replaced,
+            written: r.written.iter().map(|p| path_str(p)).collect(),
+            removed: r.removed.iter().map(|p| path_str(p)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginRemovalDto {
+    pub plugin_dir: String,
+    #[serde(default)]
+    pub version: Option<u32>,
+    pub removed: Vec<String>,
+    #[serde(default)]
+    pub kept: Vec<String>,
+}
+
+impl From<PluginRemoval> for PluginRemovalDto {
+    fn from(r: PluginRemoval) -> Self {
+        PluginRemovalDto {
+            plugin_dir: path_str(&r.plugin_dir),
+            version: r.version,
+            removed: r.removed.iter().map(|p| path_str(p)).collect(),
+            kept: r.kept.iter().map(|p| path_str(p)).collect(),
+        }
+    }
+}
+
+/// A paired reader, or one waiting to be.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceDto {
+    pub id: i64,
+    pub name: String,
+    pub created_at: i64,
+    /// `null` while its pairing code is still waiting to be traded.
+    #[serde(default)]
+    pub paired_at: Option<i64>,
+    #[serde(default)]
+    pub last_contact: Option<i64>,
+    #[serde(default)]
+    pub revoked_at: Option<i64>,
+    /// Book ids it has pushed, most recent first.
+    #[serde(default)]
+    pub books: Vec<i64>,
+}
+
+impl From<Device> for DeviceDto {
+    fn from(d: Device) -> Self {
+        DeviceDto {
+            id: d.id,
+            name: d.name,
+            created_at: d.created_at,
+            paired_at: d.paired_at,
+            last_contact: d.last_contact,
+            revoked_at: d.revoked_at,
+            books: d.books,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairingCodeDto {
+    pub device_id: i64,
+    pub name: String,
+    pub code: String,
+    pub expires_at: i64,
+}
+
+impl From<PairingCode> for PairingCodeDto {
+    fn from(c: PairingCode) -> Self {
+        PairingCodeDto {
+            device_id: c.device_id,
+            name: c.name,
+            code: c.code,
+            expires_at: c.expires_at,
+        }
+    }
+}
+
+/// What the reader gets for its code. `token` is never shown again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairedDeviceDto {
+    pub device: DeviceDto,
+    pub token: String,
+}
+
+impl From<PairedDevice> for PairedDeviceDto {
+    fn from(p: PairedDevice) -> Self {
+        PairedDeviceDto {
+            device: p.device.into(),
+            token: p.token,
+        }
+    }
+}
+
 /// The four states `docs/decisions.md` names, and no fifth.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "state", rename_all = "snake_case")]
@@ -1215,6 +2043,8 @@ impl From<DeviceState> for DeviceStateDto {
 pub struct DeviceBookDto {
     pub path: String,
     #[serde(default)]
+    pub source: DeviceSourceDto,
+    #[serde(default)]
     pub title: Option<String>,
     #[serde(default)]
     pub authors: Option<String>,
@@ -1235,6 +2065,7 @@ impl From<DeviceBook> for DeviceBookDto {
     fn from(b: DeviceBook) -> Self {
         DeviceBookDto {
             path: path_str(&b.path),
+            source: b.source.into(),
             title: b.title,
             authors: b.authors,
             partial_md5: b.partial_md5,
@@ -1623,6 +2454,10 @@ mod tests {
             page_count: Some(200),
             description: Some("d".into()),
             first_sentence: Some("f".into()),
+            series: Some("s".into()),
+            series_index: Some(2.5),
+            work_id: Some(4),
+            openlibrary_work: Some("/works/OL1W".into()),
             current_page: Some(12),
             finished: true,
             date_started: Some(1),
@@ -1652,6 +2487,10 @@ mod tests {
         assert_eq!(back.page_count, book.page_count);
         assert_eq!(back.description, book.description);
         assert_eq!(back.first_sentence, book.first_sentence);
+        assert_eq!(back.series, book.series);
+        assert_eq!(back.series_index, book.series_index);
+        assert_eq!(back.work_id, book.work_id);
+        assert_eq!(back.openlibrary_work, book.openlibrary_work);
         assert_eq!(back.current_page, book.current_page);
         assert_eq!(back.finished, book.finished);
         assert_eq!(back.date_started, book.date_started);
@@ -63,9 +63,19 @@ pub enum ErrorCode {
     Database,
     /// The platform has no filesystem-notification service to offer.
     Watch,
+    /// A write to the device was refused because the file there changed since
+    /// the last pull. The fix is a pull, never a retry.
+    DeviceChanged,
+    /// The plugin installer will not touch the directory it found: a newer
+    /// version, or not one readingbuddy wrote.
+    PluginRefused,
     /// The request itself was malformed — bad JSON, or a method this build does
     /// not have. Produced by the transport, never by the engine.
     BadRequest,
+    /// The caller may not make this call: a missing or wrong pairing token, a
+    /// spent or expired pairing code, or a method a paired device is not open
+    /// to.
+    Unauthorized,
     /// Anything else, and anything a client's build is too old to name.
     #[serde(other)]
     Internal,
@@ -126,6 +136,9 @@ fn code_for(e: &EngineError) -> ErrorCode {
         EngineError::Io(_) => ErrorCode::Io,
         EngineError::Epub(_) | EngineError::Sidecar(_) => ErrorCode::Parse,
         EngineError::Watch(_) => ErrorCode::Watch,
+        EngineError::DeviceChanged { .. } => ErrorCode::DeviceChanged,
+        EngineError::PluginRefused { .. } => ErrorCode::PluginRefused,
+        EngineError::NotPaired(_) => ErrorCode::Unauthorized,
         EngineError::Timeout { .. } => ErrorCode::Timeout,
         EngineError::Provider { .. } | EngineError::Http(_) => match ErrorClass::from(e) {
             ErrorClass::RateLimited => ErrorCode::RateLimited,
@@ -45,9 +45,11 @@ pub mod protocol;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
 use readingbuddy::{
-    BookSort, CalibreImportOptions, Engine, EngineError, FileImportOptions, GoodreadsImportOptions,
-    NoteKind, NoteRecord, RatingScale,
+    BookQuery, CalibreImportOptions, Engine, EngineError, FileImportOptions,
+    GoodreadsImportOptions, NoteKind, NoteRecord, RatingScale, ShelfRule, StatsRange, parse_day,
 };
 
 pub use dto::*;
@@ -132,9 +134,21 @@ impl Api {
         Ok(self.engine.save_book(&book.into()).await?.into())
     }
 
-    pub async fn list_books(&self, limit: i64, sort: BookSortDto) -> ApiResult<Vec<BookDto>> {
-        let sort: BookSort = sort.into();
-        Ok(map(self.engine.list_books(limit, sort).await?))
+    /// One page of the library, filtered, with the total it was cut from.
+    pub async fn list_books(
+        &self,
+        limit: i64,
+        sort: BookSortDto,
+        filter: BookFilterDto,
+        offset: i64,
+    ) -> ApiResult<BookPageDto> {
+        let query = BookQuery {
+            filter: filter.into(),
+            sort: sort.into(),
+            limit: Some(limit),
+            offset,
+        };
+        Ok(self.engine.query_books(&query).await?.into())
     }
 
     pub async fn get_book(&self, id: i64) -> ApiResult<Option<BookDto>> {
@@ -172,6 +186,64 @@ impl Api {
         Ok(self.engine.merge_books(src, dst).await?.into())
     }
 
+    /// Make `book_id` another edition of `to`'s work. Both stay books.
+    pub async fn attach_edition(&self, book_id: i64, to: i64) -> ApiResult<WorkDto> {
+        Ok(self.engine.attach_edition(book_id, to).await?.into())
+    }
+
+    pub async fn detach_edition(&self, book_id: i64) -> ApiResult<bool> {
+        Ok(self.engine.detach_edition(book_id).await?)
+    }
+
+    /// "Have I read this?", asked of the work: every edition's readings.
+    pub async fn work_history(&self, book_id: i64) -> ApiResult<WorkHistoryDto> {
+        Ok(self.engine.work_history(book_id).await?.into())
+    }
+
+    // ---- shelves -----------------------------------------------------------
+
+    pub async fn list_shelves(&self) -> ApiResult<Vec<ShelfDto>> {
+        Ok(map(self.engine.list_shelves().await?))
+    }
+
+    pub async fn create_shelf(
+        &self,
+        name: &str,
+        rule: Option<ShelfRuleDto>,
+    ) -> ApiResult<ShelfDto> {
+        let rule = rule.map(ShelfRule::from);
+        Ok(self.engine.create_shelf(name, rule.as_ref()).await?.into())
+    }
+
+    pub async fn delete_shelf(&self, name: &str) -> ApiResult<bool> {
+        Ok(self.engine.delete_shelf(name).await?)
+    }
+
+    /// A manual shelf in its order, a smart one by title.
+    pub async fn shelf_books(&self, name: &str) -> ApiResult<Vec<BookDto>> {
+        Ok(map(self.engine.shelf_books(name).await?))
+    }
+
+    pub async fn shelve(&self, name: &str, book_id: i64, position: Option<i64>) -> ApiResult<()> {
+        Ok(self.engine.shelve(name, book_id, position).await?)
+    }
+
+    pub async fn unshelve(&self, name: &str, book_id: i64) -> ApiResult<bool> {
+        Ok(self.engine.unshelve(name, book_id).await?)
+    }
+
+    pub async fn tag_candidates(&self) -> ApiResult<Vec<TagCandidateDto>> {
+        Ok(map(self.engine.tag_candidates().await?))
+    }
+
+    pub async fn promote_tags(
+        &self,
+        source: Option<&str>,
+        tags: &[String],
+    ) -> ApiResult<Vec<PromotedDto>> {
+        Ok(map(self.engine.promote_tags(source, tags).await?))
+    }
+
     // ---- readings ----------------------------------------------------------
 
     pub async fn list_readings(&self, book_id: i64) -> ApiResult<Vec<ReadingDto>> {
@@ -203,6 +275,24 @@ impl Api {
         Ok(self.engine.reread(book_id).await?)
     }
 
+    // ---- reading statistics ------------------------------------------------
+
+    /// `from` and `to` are `YYYY-MM-DD`; anything else is `invalid_input`.
+    pub async fn reading_stats(
+        &self,
+        from: &str,
+        to: &str,
+        grain: StatsGrainDto,
+        book_id: Option<i64>,
+    ) -> ApiResult<Vec<PeriodStatsDto>> {
+        let range = StatsRange {
+            from: parse_day(from)?,
+            to: parse_day(to)?,
+            book_id,
+        };
+        Ok(map(self.engine.reading_stats(&range, grain.into()).await?))
+    }
+
     // ---- highlights --------------------------------------------------------
 
     pub async fn list_highlights(&self, book_id: i64) -> ApiResult<Vec<HighlightDto>> {
@@ -221,6 +311,14 @@ impl Api {
         Ok(self.engine.set_annotation(highlight_id, annotation).await?)
     }
 
+    pub async fn search_highlights(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> ApiResult<Vec<HighlightSearchHitDto>> {
+        Ok(map(self.engine.search_highlights(query, limit).await?))
+    }
+
     // ---- epub and owned files ----------------------------------------------
 
     pub async fn import_epub(&self, path: &Path) -> ApiResult<BookDto> {
@@ -280,6 +378,27 @@ impl Api {
         Ok(self.engine.pull_book_from_sidecar(path).await?.into())
     }
 
+    /// `device` is the paired reader that sent it. It is not a request field —
+    /// a reader does not get to say who it is; [`Api::call_from_device`] fills
+    /// it in from the token it presented.
+    pub async fn pull_sidecar_payload(
+        &self,
+        partial_md5: &str,
+        sidecar: SidecarPayloadDto,
+        origin: Option<&Path>,
+        device: Option<i64>,
+    ) -> ApiResult<PullReportDto> {
+        Ok(self
+            .engine
+            .pull_sidecar_payload(&sidecar.into(), partial_md5, origin, device)
+            .await?
+            .into())
+    }
+
+    pub async fn push_annotations(&self, path: &Path, dry_run: bool) -> ApiResult<PushReportDto> {
+        Ok(self.engine.push_annotations(path, dry_run).await?.into())
+    }
+
     pub async fn sidecar_candidates(&self, path: &Path) -> ApiResult<Vec<MatchCandidateDto>> {
         Ok(map(self.engine.sidecar_candidates(path).await?))
     }
@@ -311,6 +430,40 @@ impl Api {
         Ok(map(self.engine.sync_device(paths).await?))
     }
 
+    pub fn plugin_status(&self, mount: &Path) -> ApiResult<PluginStatusDto> {
+        Ok(self.engine.plugin_status(mount)?.into())
+    }
+
+    pub fn install_plugin(&self, mount: &Path) -> ApiResult<PluginInstallDto> {
+        Ok(self.engine.install_plugin(mount)?.into())
+    }
+
+    pub fn uninstall_plugin(&self, mount: &Path) -> ApiResult<PluginRemovalDto> {
+        Ok(self.engine.uninstall_plugin(mount)?.into())
+    }
+
+    pub async fn mint_pairing_code(&self, name: &str) -> ApiResult<PairingCodeDto> {
+        Ok(self.engine.mint_pairing_code(name).await?.into())
+    }
+
+    pub async fn pair_device(&self, code: &str) -> ApiResult<PairedDeviceDto> {
+        Ok(self.engine.pair_device(code).await?.into())
+    }
+
+    /// For a transport, before it reads a reader's call: the device a token
+    /// belongs to, or `unauthorized`.
+    pub async fn authenticate_device(&self, token: &str) -> ApiResult<DeviceDto> {
+        Ok(self.engine.authenticate_device(token).await?.into())
+    }
+
+    pub async fn list_devices(&self) -> ApiResult<Vec<DeviceDto>> {
+        Ok(map(self.engine.list_devices().await?))
+    }
+
+    pub async fn revoke_device(&self, id: i64) -> ApiResult<()> {
+        Ok(self.engine.revoke_device(id).await?)
+    }
+
     // ---- notes -------------------------------------------------------------
 
     pub async fn create_note(&self, note: NewNoteDto) -> ApiResult<CreatedNoteDto> {
@@ -325,6 +478,10 @@ impl Api {
         Ok(map(self.engine.search_notes(query, limit).await?))
     }
 
+    pub async fn search_library(&self, query: &str, limit: i64) -> ApiResult<Vec<LibraryHitDto>> {
+        Ok(map(self.engine.search_library(query, limit).await?))
+    }
+
     pub async fn get_note(&self, id: i64) -> ApiResult<Option<NoteDto>> {
         Ok(self.engine.get_note(id).await?.map(Into::into))
     }
@@ -371,7 +528,7 @@ impl Api {
         Ok(map(self.engine.outgoing_links(note_id).await?))
     }
 
-    pub async fn backlinks(&self, note_id: i64) -> ApiResult<Vec<NoteDto>> {
+    pub async fn backlinks(&self, note_id: i64) -> ApiResult<Vec<BacklinkDto>> {
         Ok(map(self.engine.backlinks(note_id).await?))
     }
 
@@ -578,8 +735,30 @@ impl Api {
         Ok(map(self.engine.list_flashcards_for_book(book_id).await?))
     }
 
-    pub async fn export_flashcards(&self, include_exported: bool) -> ApiResult<(String, usize)> {
-        Ok(self.engine.export_flashcards(include_exported).await?)
+    pub async fn export_flashcards(
+        &self,
+        include_exported: bool,
+        format: CardFormatDto,
+    ) -> ApiResult<(Vec<u8>, usize)> {
+        Ok(self
+            .engine
+            .export_flashcards(include_exported, format.into())
+            .await?)
+    }
+
+    // ---- study -------------------------------------------------------------
+
+    pub async fn due_cards(&self, limit: usize) -> ApiResult<Vec<StudyCardDto>> {
+        Ok(map(self.engine.due_cards(limit).await?))
+    }
+
+    /// The card as the grade left it — when it is due next.
+    pub async fn grade_card(&self, id: i64, grade: GradeDto) -> ApiResult<StudyCardDto> {
+        Ok(self.engine.grade_card(id, grade.into()).await?.into())
+    }
+
+    pub async fn study_highlight(&self, highlight_id: i64, on: bool) -> ApiResult<bool> {
+        Ok(self.engine.study_highlight(highlight_id, on).await?)
     }
 
     // ---- the two lookups the id-taking methods share -----------------------
@@ -621,6 +800,36 @@ impl Api {
     /// never met, and the two ways of using this crate would stop being the
     /// same code.
     pub async fn dispatch(&self, request: Request) -> ApiResult<Response> {
+        self.route(request, None).await
+    }
+
+    /// A call from a reader on the network, answered only if
+    /// [`Request::open_to_devices`] says a reader may make it.
+    ///
+    /// `device` is what [`Api::authenticate_device`] said about the token the
+    /// reader presented, or `None` if it presented none — and then only
+    /// [`Request::open_before_pairing`] gets through. The check lives here, not
+    /// in a transport, for the reason the allow-list does.
+    pub async fn call_from_device(&self, device: Option<i64>, call: Call) -> Reply {
+        let refused = if !call.request.open_to_devices() {
+            Some("a paired device may not make this call")
+        } else if device.is_none() && !call.request.open_before_pairing() {
+            Some("a pairing token is required")
+        } else {
+            None
+        };
+        if let Some(why) = refused {
+            return Reply::err(call.id, ApiError::new(ErrorCode::Unauthorized, why));
+        }
+        match self.route(call.request, device).await {
+            Ok(response) => Reply::ok(call.id, response),
+            Err(error) => Reply::err(call.id, error),
+        }
+    }
+
+    /// [`Api::dispatch`], carrying the device a call came from to the one
+    /// method that records it.
+    async fn route(&self, request: Request, device: Option<i64>) -> ApiResult<Response> {
         use Request as R;
         Ok(match request {
             R::ApiVersion => {
@@ -639,7 +848,12 @@ impl Api {
             R::LookupIsbn { isbn } => Response::Book(self.lookup_isbn(&isbn).await?),
 
             R::SaveBook { book } => Response::Book(Some(self.save_book(book).await?)),
-            R::ListBooks { limit, sort } => Response::Books(self.list_books(limit, sort).await?),
+            R::ListBooks {
+                limit,
+                sort,
+                filter,
+                offset,
+            } => Response::BookPage(self.list_books(limit, sort, filter, offset).await?),
             R::GetBook { id } => Response::Book(self.get_book(id).await?),
             R::ResolveBooks { selector } => Response::Books(self.resolve_books(&selector).await?),
             R::BookTags { book_id } => Response::BookTags(self.book_tags(book_id).await?),
@@ -652,6 +866,29 @@ impl Api {
             }
             R::FetchCover { book_id } => Response::MaybePath(self.fetch_cover(book_id).await?),
             R::MergeBooks { src, dst } => Response::MergeReport(self.merge_books(src, dst).await?),
+            R::AttachEdition { book_id, to } => {
+                Response::Work(self.attach_edition(book_id, to).await?)
+            }
+            R::DetachEdition { book_id } => Response::Bool(self.detach_edition(book_id).await?),
+            R::WorkHistory { book_id } => Response::WorkHistory(self.work_history(book_id).await?),
+
+            R::ListShelves => Response::Shelves(self.list_shelves().await?),
+            R::CreateShelf { name, rule } => Response::Shelf(self.create_shelf(&name, rule).await?),
+            R::DeleteShelf { name } => Response::Bool(self.delete_shelf(&name).await?),
+            R::ShelfBooks { name } => Response::Books(self.shelf_books(&name).await?),
+            R::Shelve {
+                name,
+                book_id,
+                position,
+            } => {
+                self.shelve(&name, book_id, position).await?;
+                Response::Unit
+            }
+            R::Unshelve { name, book_id } => Response::Bool(self.unshelve(&name, book_id).await?),
+            R::TagCandidates => Response::TagCandidates(self.tag_candidates().await?),
+            R::PromoteTags { source, tags } => {
+                Response::Promoted(self.promote_tags(source.as_deref(), &tags).await?)
+            }
 
             R::ListReadings { book_id } => Response::Readings(self.list_readings(book_id).await?),
             R::GetReading { id } => Response::Reading(self.get_reading(id).await?),
@@ -663,6 +900,13 @@ impl Api {
             } => Response::Book(Some(self.update_progress(book_id, page, finished).await?)),
             R::Reread { book_id } => Response::Id(self.reread(book_id).await?),
 
+            R::ReadingStats {
+                from,
+                to,
+                grain,
+                book_id,
+            } => Response::ReadingStats(self.reading_stats(&from, &to, grain, book_id).await?),
+
             R::ListHighlights { book_id } => {
                 Response::Highlights(self.list_highlights(book_id).await?)
             }
@@ -677,6 +921,9 @@ impl Api {
                     .await?;
                 Response::Unit
             }
+            R::SearchHighlights { query, limit } => {
+                Response::HighlightHits(self.search_highlights(&query, limit).await?)
+            }
 
             R::ImportEpub { path } => {
                 Response::Book(Some(self.import_epub(Path::new(&path)).await?))
@@ -700,6 +947,19 @@ impl Api {
             R::PullBookFromSidecar { path } => {
                 Response::PullReport(self.pull_book_from_sidecar(Path::new(&path)).await?)
             }
+            R::PullSidecarPayload {
+                partial_md5,
+                sidecar,
+                origin,
+            } => Response::PullReport(
+                self.pull_sidecar_payload(
+                    &partial_md5,
+                    sidecar,
+                    origin.as_deref().map(Path::new),
+                    device,
+                )
+                .await?,
+            ),
             R::SidecarCandidates { path } => {
                 Response::Candidates(self.sidecar_candidates(Path::new(&path)).await?)
             }
@@ -707,6 +967,10 @@ impl Api {
                 Response::Text(self.link_sidecar(Path::new(&path), book_id).await?)
             }
 
+            R::PushAnnotations { path, dry_run } => {
+                Response::PushReport(self.push_annotations(Path::new(&path), dry_run).await?)
+            }
+
             R::CandidateMounts => Response::Paths(self.candidate_mounts()),
             R::IsKoreaderMount { path } => Response::Bool(self.is_koreader_mount(Path::new(&path))),
             R::ScanDevice { root } => {
@@ -716,12 +980,33 @@ impl Api {
                 let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
                 Response::PullReports(self.sync_device(&paths).await?)
             }
+            R::PluginStatus { mount } => {
+                Response::PluginStatus(self.plugin_status(Path::new(&mount))?)
+            }
+            R::InstallPlugin { mount } => {
+                Response::PluginInstall(self.install_plugin(Path::new(&mount))?)
+            }
+            R::UninstallPlugin { mount } => {
+                Response::PluginRemoval(self.uninstall_plugin(Path::new(&mount))?)
+            }
+            R::MintPairingCode { name } => {
+                Response::PairingCode(self.mint_pairing_code(&name).await?)
+            }
+            R::PairDevice { code } => Response::PairedDevice(self.pair_device(&code).await?),
+            R::ListDevices => Response::Devices(self.list_devices().await?),
+            R::RevokeDevice { id } => {
+                self.revoke_device(id).await?;
+                Response::Unit
+            }
 
             R::CreateNote { note } => Response::CreatedNote(self.create_note(note).await?),
             R::ListNotes { book_id } => Response::Notes(self.list_notes(book_id).await?),
             R::SearchNotes { query, limit } => {
                 Response::NoteHits(self.search_notes(&query, limit).await?)
             }
+            R::SearchLibrary { query, limit } => {
+                Response::LibraryHits(self.search_library(&query, limit).await?)
+            }
             R::GetNote { id } => Response::Note(self.get_note(id).await?),
             R::NoteForReading { reading_id, kind } => {
                 Response::Note(self.note_for_reading(reading_id, kind).await?)
@@ -741,7 +1026,7 @@ impl Api {
                 Response::Unit
             }
             R::OutgoingLinks { note_id } => Response::Links(self.outgoing_links(note_id).await?),
-            R::Backlinks { note_id } => Response::Notes(self.backlinks(note_id).await?),
+            R::Backlinks { note_id } => Response::Backlinks(self.backlinks(note_id).await?),
 
             R::OpenReflection {
                 book_id,
@@ -855,9 +1140,27 @@ impl Api {
             R::ListFlashcardsForBook { book_id } => {
                 Response::Flashcards(self.list_flashcards_for_book(book_id).await?)
             }
-            R::ExportFlashcards { include_exported } => {
-                let (tsv, count) = self.export_flashcards(include_exported).await?;
-                Response::FlashcardExport { tsv, count }
+            R::ExportFlashcards {
+                include_exported,
+                format,
+            } => {
+                let (bytes, count) = self.export_flashcards(include_exported, format).await?;
+                match format {
+                    CardFormatDto::Tsv => Response::FlashcardExport {
+                        tsv: String::from_utf8_lossy(&bytes).into_owned(),
+                        count,
+                    },
+                    CardFormatDto::Apkg => Response::AnkiPackage {
+                        apkg: STANDARD.encode(bytes),
+                        count,
+                    },
+                }
+            }
+
+            R::DueCards { limit } => Response::StudyCards(self.due_cards(limit).await?),
+            R::GradeCard { id, grade } => Response::StudyCard(self.grade_card(id, grade).await?),
+            R::StudyHighlight { highlight_id, on } => {
+                Response::Bool(self.study_highlight(highlight_id, on).await?)
             }
         })
     }
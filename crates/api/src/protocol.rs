@@ -39,7 +39,10 @@ use crate::error::ApiError;
 /// and a newer client meets [`crate::error::ErrorCode::BadRequest`] on an older
 /// daemon — which is a clear failure rather than a silent misread, and is why
 /// the number can stay still through ordinary growth.
-pub const API_VERSION: u32 = 1;
+///
+/// 2: `list_books` answers with a [`BookPageDto`] — the page and the total it
+/// was cut from — where it used to answer with a bare list.
+pub const API_VERSION: u32 = 2;
 
 /// The build, for a human reading a log. Never branch on it.
 pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -83,6 +86,10 @@ pub enum Request {
         limit: i64,
         #[serde(default)]
         sort: BookSortDto,
+        #[serde(default)]
+        filter: BookFilterDto,
+        #[serde(default)]
+        offset: i64,
     },
     GetBook {
         id: i64,
@@ -106,6 +113,48 @@ pub enum Request {
         src: i64,
         dst: i64,
     },
+    AttachEdition {
+        book_id: i64,
+        to: i64,
+    },
+    DetachEdition {
+        book_id: i64,
+    },
+    WorkHistory {
+        book_id: i64,
+    },
+
+    // ---- shelves ----
+    ListShelves,
+    CreateShelf {
+        name: String,
+        /// Absent for a manual shelf.
+        #[serde(default)]
+        rule: Option<ShelfRuleDto>,
+    },
+    DeleteShelf {
+        name: String,
+    },
+    ShelfBooks {
+        name: String,
+    },
+    Shelve {
+        name: String,
+        book_id: i64,
+        /// 1-based; absent is the end.
+        #[serde(default)]
+        position: Option<i64>,
+    },
+    Unshelve {
+        name: String,
+        book_id: i64,
+    },
+    TagCandidates,
+    PromoteTags {
+        #[serde(default)]
+        source: Option<String>,
+        tags: Vec<String>,
+    },
 
     // ---- readings ----
     ListReadings {
@@ -128,6 +177,17 @@ pub enum Request {
         book_id: i64,
     },
 
+    // ---- reading statistics ----
+    /// Per-period aggregates. `from` and `to` are `YYYY-MM-DD`, both inclusive.
+    ReadingStats {
+        from: String,
+        to: String,
+        #[serde(default)]
+        grain: StatsGrainDto,
+        #[serde(default)]
+        book_id: Option<i64>,
+    },
+
     // ---- highlights ----
     ListHighlights {
         book_id: i64,
@@ -142,6 +202,12 @@ pub enum Request {
         #[serde(default)]
         annotation: Option<String>,
     },
+    /// An FTS5 query over every book's highlights: the passage, both notes on
+    /// it, and the chapter.
+    SearchHighlights {
+        query: String,
+        limit: i64,
+    },
 
     // ---- epub and owned files ----
     ImportEpub {
@@ -180,6 +246,15 @@ pub enum Request {
     PullBookFromSidecar {
         path: String,
     },
+    /// A sidecar the reader sent itself, over the network. Open to a paired
+    /// device; see [`Request::open_to_devices`].
+    PullSidecarPayload {
+        partial_md5: String,
+        sidecar: SidecarPayloadDto,
+        /// The document's path on the reader, for diagnostics.
+        #[serde(default)]
+        origin: Option<String>,
+    },
     SidecarCandidates {
         path: String,
     },
@@ -187,6 +262,13 @@ pub enum Request {
         path: String,
         book_id: i64,
     },
+    /// The desk's annotations into the sidecar's notes. Refused, with
+    /// `device_changed`, when the file moved on since the last pull.
+    PushAnnotations {
+        path: String,
+        #[serde(default)]
+        dry_run: bool,
+    },
 
     // ---- the device ----
     CandidateMounts,
@@ -199,6 +281,30 @@ pub enum Request {
     SyncDevice {
         paths: Vec<String>,
     },
+    /// Never sent on a mount event: installing is always the user's request,
+    /// on a `plugin_dir` they were shown by `plugin_status`.
+    PluginStatus {
+        mount: String,
+    },
+    InstallPlugin {
+        mount: String,
+    },
+    UninstallPlugin {
+        mount: String,
+    },
+    /// A code for the user to type into the reader called `name`.
+    MintPairingCode {
+        name: String,
+    },
+    /// Sent by the reader, with the code it was given. Open to a device that
+    /// is not paired yet — it is how one becomes paired.
+    PairDevice {
+        code: String,
+    },
+    ListDevices,
+    RevokeDevice {
+        id: i64,
+    },
 
     // ---- notes ----
     CreateNote {
@@ -212,6 +318,12 @@ pub enum Request {
         query: String,
         limit: i64,
     },
+    /// Notes and highlights together, ranked against each other. A query that
+    /// is not FTS5 syntax is searched for as plain words.
+    SearchLibrary {
+        query: String,
+        limit: i64,
+    },
     GetNote {
         id: i64,
     },
@@ -353,12 +465,50 @@ pub enum Request {
     ListFlashcardsForBook {
         book_id: i64,
     },
+    /// A TSV comes back as text; an `.apkg` as base64, it being a zip.
     ExportFlashcards {
         #[serde(default)]
         include_exported: bool,
+        #[serde(default)]
+        format: CardFormatDto,
+    },
+
+    // ---- study ----
+    /// Due cards, most overdue first; flashcards are enrolled on the way.
+    DueCards {
+        limit: usize,
+    },
+    GradeCard {
+        id: i64,
+        grade: GradeDto,
+    },
+    /// Opt a highlight into study (`on`), or out again.
+    StudyHighlight {
+        highlight_id: i64,
+        on: bool,
     },
 }
 
+impl Request {
+    /// May a paired reader make this call over the network?
+    ///
+    /// The policy lives here rather than in a transport because it is a fact
+    /// about the vocabulary: a transport that grew its own list would be a
+    /// daemon with logic, and the list would drift from the methods it names.
+    /// Deliberately an allow-list, and deliberately short — a reader on the LAN
+    /// holds a token that a stolen device also holds, and what it can do with
+    /// one is push its own reading, not read or change the library.
+    pub fn open_to_devices(&self) -> bool {
+        self.open_before_pairing() || matches!(self, Request::PullSidecarPayload { .. })
+    }
+
+    /// The part of [`Request::open_to_devices`] a reader may call with no
+    /// token at all: asking what it is talking to, and trading a code for one.
+    pub fn open_before_pairing(&self) -> bool {
+        matches!(self, Request::ApiVersion | Request::PairDevice { .. })
+    }
+}
+
 /// What came back, by shape. See [`Request`] on the size of these variants.
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -381,21 +531,34 @@ pub enum Response {
 
     Book(Option<BookDto>),
     Books(Vec<BookDto>),
+    BookPage(BookPageDto),
     BookTags(Vec<BookTagDto>),
     OpenReadings(Vec<OpenReadingDto>),
     MergeReport(MergeReportDto),
+    Work(WorkDto),
+    WorkHistory(WorkHistoryDto),
+
+    Shelf(ShelfDto),
+    Shelves(Vec<ShelfDto>),
+    TagCandidates(Vec<TagCandidateDto>),
+    Promoted(Vec<PromotedDto>),
 
     Reading(Option<ReadingDto>),
     Readings(Vec<ReadingDto>),
 
     Highlights(Vec<HighlightDto>),
+    HighlightHits(Vec<HighlightSearchHitDto>),
+
+    ReadingStats(Vec<PeriodStatsDto>),
 
     SearchOutcome(SearchOutcomeDto),
 
     Note(Option<NoteDto>),
     Notes(Vec<NoteDto>),
     NoteHits(Vec<NoteSearchHitDto>),
+    LibraryHits(Vec<LibraryHitDto>),
     Links(Vec<OutgoingLinkDto>),
+    Backlinks(Vec<BacklinkDto>),
     CreatedNote(CreatedNoteDto),
 
     Rating(Option<RatingDto>),
@@ -411,8 +574,15 @@ pub enum Response {
     ImportReport(ImportReportDto),
     PullReport(PullReportDto),
     PullReports(Vec<PullReportDto>),
+    PushReport(PushReportDto),
     Candidates(Vec<MatchCandidateDto>),
     DeviceScan(DeviceScanDto),
+    PluginStatus(PluginStatusDto),
+    PluginInstall(PluginInstallDto),
+    PluginRemoval(PluginRemovalDto),
+    PairingCode(PairingCodeDto),
+    PairedDevice(PairedDeviceDto),
+    Devices(Vec<DeviceDto>),
 
     GoodreadsReport(GoodreadsReportDto),
     /// The CSV, plus every honest failure along the way. The payload comes
@@ -432,6 +602,14 @@ pub enum Response {
         tsv: String,
         count: usize,
     },
+    AnkiPackage {
+        /// The `.apkg`, base64.
+        apkg: String,
+        count: usize,
+    },
+
+    StudyCard(StudyCardDto),
+    StudyCards(Vec<StudyCardDto>),
 }
 
 /// A request with an id, as it arrives.
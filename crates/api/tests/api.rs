@@ -13,9 +13,12 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
 use readingbuddy::{Engine, EngineConfig};
 use readingbuddy_api::{
-    Api, ApiError, BookDto, ErrorCode, NewNoteDto, NoteKindDto, Outcome, Request, Response,
+    Api, ApiError, BookDto, BookFilterDto, BookStatusDto, Call, CardKindDto, ErrorCode, NewNoteDto,
+    NoteKindDto, Outcome, Request, Response,
 };
 
 /// A library in a tempdir with an in-memory database, like every other suite
@@ -28,6 +31,7 @@ async fn api() -> (Api, tempfile::TempDir) {
         files_dir: tmp.path().join("files"),
         vault_dir: tmp.path().join("vault"),
         log_dir: tmp.path().join("logs"),
+        templates_dir: tmp.path().join("templates"),
         google_api_key: None,
         calibre_bin_dir: None,
     };
@@ -75,15 +79,21 @@ async fn dispatch_and_the_typed_method_agree() {
         other => panic!("{other:?}"),
     }
 
-    let typed = api.list_books(10, Default::default()).await.unwrap();
+    let typed = api
+        .list_books(10, Default::default(), Default::default(), 0)
+        .await
+        .unwrap();
+    assert_eq!(typed.total, 1);
     match ok(api
         .dispatch(Request::ListBooks {
             limit: 10,
             sort: Default::default(),
+            filter: Default::default(),
+            offset: 0,
         })
         .await)
     {
-        Response::Books(dispatched) => assert_eq!(dispatched, typed),
+        Response::BookPage(dispatched) => assert_eq!(dispatched, typed),
         other => panic!("{other:?}"),
     }
 }
@@ -180,6 +190,7 @@ async fn an_absent_calibre_is_a_status_and_never_an_error() {
         files_dir: tmp.path().join("files"),
         vault_dir: tmp.path().join("vault"),
         log_dir: tmp.path().join("logs"),
+        templates_dir: tmp.path().join("templates"),
         google_api_key: None,
         calibre_bin_dir: Some(empty),
     };
@@ -414,3 +425,403 @@ fn an_import_request_without_only_still_means_the_whole_library() {
         other => panic!("{other:?}"),
     }
 }
+
+/// Dates cross as `YYYY-MM-DD` and a malformed one is the caller's mistake, not
+/// an internal error. A year with no device data has twelve months and no
+/// minutes in any of them — `null`, never `0`.
+#[tokio::test]
+async fn reading_stats_speak_days_and_say_when_they_do_not_know() {
+    let (api, _tmp) = api().await;
+    seed(&api).await;
+
+    let parsed: Request = serde_json::from_str(
+        r#"{"method":"reading_stats","params":{"from":"2026-01-01","to":"2026-12-31"}}"#,
+    )
+    .unwrap();
+    let months = match ok(api.dispatch(parsed).await) {
+        Response::ReadingStats(months) => months,
+        other => panic!("{other:?}"),
+    };
+    assert_eq!(months.len(), 12, "the grain defaults to month");
+    assert_eq!(
+        (months[0].from.as_str(), months[0].to.as_str()),
+        ("2026-01-01", "2026-01-31")
+    );
+    assert!(
+        months
+            .iter()
+            .all(|m| m.minutes.is_none() && m.pages.is_none())
+    );
+
+    let err = api
+        .reading_stats("2026-13-01", "2026-12-31", Default::default(), None)
+        .await
+        .expect_err("there is no thirteenth month");
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+}
+
+/// The library search's hits say which kind they are on the wire, so a client
+/// can render a note and a highlight differently without a second lookup.
+#[tokio::test]
+async fn a_library_hit_names_its_kind_in_json() {
+    let (api, _tmp) = api().await;
+    let book_id = seed(&api).await;
+    api.create_note(NewNoteDto {
+        book_id: Some(book_id),
+        body: "Survival is insufficient.".into(),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+
+    let parsed: Request = serde_json::from_str(
+        r#"{"method":"search_library","params":{"query":"insufficient","limit":5}}"#,
+    )
+    .unwrap();
+    let hits = match ok(api.dispatch(parsed).await) {
+        Response::LibraryHits(hits) => hits,
+        other => panic!("{other:?}"),
+    };
+    assert_eq!(hits, api.search_library("insufficient", 5).await.unwrap());
+    let json = serde_json::to_value(&hits).unwrap();
+    assert_eq!(json[0]["kind"], "note");
+    assert!(
+        json[0]["snippet"]
+            .as_str()
+            .unwrap()
+            .contains(">>insufficient<<")
+    );
+
+    // A stray quote is a word, not an error.
+    assert_eq!(
+        api.search_library("insufficient\"", 5).await.unwrap().len(),
+        1
+    );
+}
+
+/// A listing written before filters existed still parses and still means the
+/// whole library; one that filters gets the page and the count of what matched.
+#[tokio::test]
+async fn a_book_listing_filters_pages_and_counts() {
+    let (api, _tmp) = api().await;
+    let id = seed(&api).await;
+    api.save_book(BookDto {
+        title: Some("Another Book".into()),
+        publish_year: Some(1951),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+
+    let parsed: Request =
+        serde_json::from_str(r#"{"method":"list_books","params":{"limit":1}}"#).unwrap();
+    let page = match ok(api.dispatch(parsed).await) {
+        Response::BookPage(page) => page,
+        other => panic!("{other:?}"),
+    };
+    assert_eq!((page.books.len(), page.total, page.offset), (1, 2, 0));
+
+    let parsed: Request = serde_json::from_str(
+        r#"{"method":"list_books","params":{"limit":10,"filter":{"status":"unstarted","year_to":1960}}}"#,
+    )
+    .unwrap();
+    let page = match ok(api.dispatch(parsed).await) {
+        Response::BookPage(page) => page,
+        other => panic!("{other:?}"),
+    };
+    assert_eq!(page.total, 1);
+    assert_eq!(page.books[0].title.as_deref(), Some("Another Book"));
+    assert_ne!(page.books[0].id, Some(id));
+
+    let err = api
+        .list_books(10, Default::default(), Default::default(), -5)
+        .await
+        .expect_err("a negative offset is the caller's arithmetic");
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+}
+
+/// A shelf over the wire: made, filled, listed through the ordinary filter,
+/// and a smart one's rule round-trips as JSON.
+#[tokio::test]
+async fn shelves_are_made_filled_and_filtered_on() {
+    let (api, _tmp) = api().await;
+    let id = seed(&api).await;
+
+    let made: Request =
+        serde_json::from_str(r#"{"method":"create_shelf","params":{"name":"Desk"}}"#).unwrap();
+    match ok(api.dispatch(made).await) {
+        Response::Shelf(shelf) => assert!(shelf.rule.is_none()),
+        other => panic!("{other:?}"),
+    }
+    let shelve: Request = serde_json::from_str(&format!(
+        r#"{{"method":"shelve","params":{{"name":"desk","book_id":{id}}}}}"#
+    ))
+    .unwrap();
+    assert_eq!(ok(api.dispatch(shelve).await), Response::Unit);
+
+    let page = api
+        .list_books(
+            10,
+            Default::default(),
+            BookFilterDto {
+                shelf: Some("Desk".into()),
+                ..Default::default()
+            },
+            0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.books[0].id, Some(id));
+
+    let smart: Request = serde_json::from_str(
+        r#"{"method":"create_shelf","params":{"name":"Unread","rule":{"status":"unstarted"}}}"#,
+    )
+    .unwrap();
+    match ok(api.dispatch(smart).await) {
+        Response::Shelf(shelf) => {
+            assert_eq!(shelf.rule.unwrap().status, Some(BookStatusDto::Unstarted));
+            assert_eq!(shelf.books, 1);
+        }
+        other => panic!("{other:?}"),
+    }
+    let err = api
+        .shelve("Unread", id, None)
+        .await
+        .expect_err("a smart shelf fills itself");
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+    let err = api.shelf_books("nowhere").await.expect_err("no such shelf");
+    assert_eq!(err.code, ErrorCode::NotFound);
+    assert_eq!(api.list_shelves().await.unwrap().len(), 2);
+}
+
+/// A highlight opted into study is due at once, comes back with where it was
+/// found, and a grade sends it a day away.
+#[tokio::test]
+async fn a_studied_highlight_is_due_graded_and_rescheduled() {
+    let (api, _tmp) = api().await;
+    let push: Request = serde_json::from_str(
+        r#"{"method":"pull_sidecar_payload","params":{
+        "partial_md5":"5f1e0c2b9a8d7e6f5a4b3c2d1e0f9a8b",
+        "sidecar":{"doc_props":{"title":"Kindred"},"annotations":[{"text":"I lost an arm","chapter":"The River","pos0":"/body/p[1]/text().0","datetime":"2026-07-01 10:00:00"}]}
+    }}"#,
+    )
+    .unwrap();
+    let book_id = match ok(api.dispatch(push).await) {
+        Response::PullReport(r) => r.stats.book_id,
+        other => panic!("{other:?}"),
+    };
+    let highlight = api.list_highlights(book_id).await.unwrap()[0].id;
+
+    let opt_in: Request = serde_json::from_str(&format!(
+        r#"{{"method":"study_highlight","params":{{"highlight_id":{highlight},"on":true}}}}"#
+    ))
+    .unwrap();
+    assert_eq!(ok(api.dispatch(opt_in).await), Response::Bool(true));
+    let due = api.due_cards(10).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].kind, CardKindDto::Highlight);
+    assert_eq!(due[0].front, "I lost an arm");
+    assert_eq!(due[0].chapter.as_deref(), Some("The River"));
+
+    let grade: Request = serde_json::from_str(&format!(
+        r#"{{"method":"grade_card","params":{{"id":{},"grade":"good"}}}}"#,
+        due[0].id
+    ))
+    .unwrap();
+    match ok(api.dispatch(grade).await) {
+        Response::StudyCard(card) => {
+            assert_eq!(card.interval_days, 1);
+            assert_eq!(card.due_at, card.last_reviewed_at.unwrap() + 86_400);
+        }
+        other => panic!("{other:?}"),
+    }
+    assert!(api.due_cards(10).await.unwrap().is_empty());
+
+    // No words, so the TSV is empty; the package carries the passage.
+    let tsv: Request =
+        serde_json::from_str(r#"{"method":"export_flashcards","params":{}}"#).unwrap();
+    assert!(matches!(
+        ok(api.dispatch(tsv).await),
+        Response::FlashcardExport { count: 0, .. }
+    ));
+    let apkg: Request =
+        serde_json::from_str(r#"{"method":"export_flashcards","params":{"format":"apkg"}}"#)
+            .unwrap();
+    match ok(api.dispatch(apkg).await) {
+        Response::AnkiPackage { apkg, count } => {
+            assert_eq!(count, 1);
+            assert!(STANDARD.decode(apkg).unwrap().starts_with(b"PK"));
+        }
+        other => panic!("{other:?}"),
+    }
+
+    let err = api
+        .study_highlight(highlight + 100, true)
+        .await
+        .expect_err("no such highlight");
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+/// What a plugin on the reader sends: the sidecar as its Lua text or as the
+/// JSON of the table, with nothing to say which, and the checksum beside it.
+/// Both forms pull the same book in, and both are open to a paired device —
+/// which nothing that reads or changes the library is.
+#[tokio::test]
+async fn a_sidecar_arrives_as_lua_or_as_json_and_is_open_to_devices() {
+    let (api, _dir) = api().await;
+    let lua = r#"{"method":"pull_sidecar_payload","params":{
+        "partial_md5":"5f1e0c2b9a8d7e6f5a4b3c2d1e0f9a8b",
+        "sidecar":"return { [\"doc_props\"] = { [\"title\"] = \"Kindred\" }, [\"annotations\"] = { [1] = { [\"text\"] = \"I lost an arm\", [\"pos0\"] = \"/body/p[1]/text().0\", [\"datetime\"] = \"2026-07-01 10:00:00\" } } }"
+    }}"#;
+    let json = r#"{"method":"pull_sidecar_payload","params":{
+        "partial_md5":"5f1e0c2b9a8d7e6f5a4b3c2d1e0f9a8b",
+        "sidecar":{"doc_props":{"title":"Kindred"},"annotations":[{"text":"I lost an arm","pos0":"/body/p[1]/text().0","datetime":"2026-07-01 10:00:00"}]},
+        "origin":"/mnt/onboard/Books/Kindred.epub"
+    }}"#;
+
+    let mut books = Vec::new();
+    for raw in [lua, json] {
+        let request: Request = serde_json::from_str(raw).expect("parses");
+        assert!(request.open_to_devices());
+        match ok(api.dispatch(request).await) {
+            Response::PullReport(r) => books.push((r.stats.book_id, r.stats.inserted)),
+            other => panic!("{other:?}"),
+        }
+    }
+    let (first, second) = (books[0], books[1]);
+    assert_eq!(first.1, 1);
+    assert_eq!(second, (first.0, 0), "the same book, nothing new");
+
+    for closed in [
+        Request::ListBooks {
+            limit: 10,
+            sort: Default::default(),
+            filter: Default::default(),
+            offset: 0,
+        },
+        Request::DeleteBook { id: 1 },
+    ] {
+        assert!(!closed.open_to_devices(), "{closed:?}");
+    }
+}
+
+/// A reader's call, as the network transport hands it over: a code trades for
+/// a token with no token, a push with one names the reader on what it wrote,
+/// and nothing else gets through either way.
+#[tokio::test]
+async fn a_device_pairs_pushes_and_is_named_on_what_it_pushed() {
+    let (api, _dir) = api().await;
+    let code = api.mint_pairing_code("Libra").await.expect("mint").code;
+
+    let call = |id, raw: &str| Call {
+        id,
+        request: serde_json::from_str(raw).expect("parses"),
+    };
+    let pair = format!(r#"{{"method":"pair_device","params":{{"code":"{code}"}}}}"#);
+    let token = match api.call_from_device(None, call(1, &pair)).await.outcome {
+        Outcome::Ok {
+            response: Response::PairedDevice(p),
+        } => p.token,
+        other => panic!("{other:?}"),
+    };
+    let device = api.authenticate_device(&token).await.expect("paired").id;
+
+    let push = r#"{"method":"pull_sidecar_payload","params":{
+        "partial_md5":"5f1e0c2b9a8d7e6f5a4b3c2d1e0f9a8b",
+        "sidecar":{"doc_props":{"title":"Kindred"},"annotations":[{"text":"I lost an arm","pos0":"/body/p[1]/text().0","datetime":"2026-07-01 10:00:00"}]}
+    }}"#;
+    let refused = api.call_from_device(None, call(2, push)).await;
+    match refused.outcome {
+        Outcome::Error { error } => assert_eq!(error.code, ErrorCode::Unauthorized),
+        other => panic!("{other:?}"),
+    }
+    let book_id = match api
+        .call_from_device(Some(device), call(3, push))
+        .await
+        .outcome
+    {
+        Outcome::Ok {
+            response: Response::PullReport(r),
+        } => r.stats.book_id,
+        other => panic!("{other:?}"),
+    };
+    let highlights = api.list_highlights(book_id).await.expect("highlights");
+    assert_eq!(highlights[0].device_id, Some(device));
+
+    let mint = r#"{"method":"mint_pairing_code","params":{"name":"Mallory"}}"#;
+    let refused = api.call_from_device(Some(device), call(4, mint)).await;
+    assert_eq!(refused.id, 4);
+    assert!(matches!(
+        refused.outcome,
+        Outcome::Error { error } if error.code == ErrorCode::Unauthorized
+    ));
+
+    let devices = api.list_devices().await.expect("list");
+    assert_eq!(devices.len(), 1, "nothing was minted from the reader");
+    assert_eq!(devices[0].books, [book_id]);
+    api.revoke_device(device).await.expect("revoke");
+    let err = api.authenticate_device(&token).await.expect_err("revoked");
+    assert_eq!(err.code, ErrorCode::Unauthorized);
+}
+
+/// Editions grouped over the wire, and the work answering for them: a
+/// translation read is the original read, and the status filter agrees.
+#[tokio::test]
+async fn a_work_is_grouped_and_read_through_the_api() {
+    let (api, _tmp) = api().await;
+    let original = seed(&api).await;
+    let translation = api
+        .save_book(BookDto {
+            title: Some("Stacja Jedenaście".into()),
+            authors: vec!["Emily St. John Mandel".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .id
+        .unwrap();
+    api.update_progress(translation, None, Some(true))
+        .await
+        .unwrap();
+
+    let attach: Request = serde_json::from_str(&format!(
+        r#"{{"method":"attach_edition","params":{{"book_id":{translation},"to":{original}}}}}"#
+    ))
+    .unwrap();
+    match ok(api.dispatch(attach).await) {
+        Response::Work(work) => assert_eq!(work.editions.len(), 2),
+        other => panic!("{other:?}"),
+    }
+    let history: Request = serde_json::from_str(&format!(
+        r#"{{"method":"work_history","params":{{"book_id":{original}}}}}"#
+    ))
+    .unwrap();
+    match ok(api.dispatch(history).await) {
+        Response::WorkHistory(h) => {
+            assert!(h.has_read);
+            assert_eq!(h.read_as, Some(translation));
+        }
+        other => panic!("{other:?}"),
+    }
+    let unstarted = api
+        .list_books(
+            10,
+            Default::default(),
+            BookFilterDto {
+                status: Some(BookStatusDto::Unstarted),
+                ..Default::default()
+            },
+            0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(unstarted.total, 0);
+
+    let detach: Request = serde_json::from_str(&format!(
+        r#"{{"method":"detach_edition","params":{{"book_id":{translation}}}}}"#
+    ))
+    .unwrap();
+    assert_eq!(ok(api.dispatch(detach).await), Response::Bool(true));
+    assert!(!api.work_history(original).await.unwrap().has_read);
+}
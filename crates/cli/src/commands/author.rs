@@ -0,0 +1,41 @@
+//! `author`: everything kept from one author's books, in one report.
+
+use anyhow::Result;
+use readingbuddy::{CorpusOrder, Engine};
+
+use crate::render;
+
+/// Each book with its readings, then its highlights (annotations under them)
+/// and its notes.
+pub async fn run(engine: &Engine, name: &str, by: &str) -> Result<()> {
+    let order: CorpusOrder = by.parse()?;
+    let corpus = engine.author_corpus(name, order).await?;
+    println!(
+        "{} — {} book(s), {} highlight(s), {} note(s)",
+        corpus.name,
+        corpus.books.len(),
+        corpus.highlight_count(),
+        corpus.note_count()
+    );
+    if !corpus.variants.is_empty() {
+        println!("  also as: {}", corpus.variants.join("; "));
+    }
+    for entry in &corpus.books {
+        println!();
+        println!("{}", render::book_line(&entry.book));
+        for (i, r) in entry.readings.iter().enumerate() {
+            println!("  {}", render::reading_line(r, i + 1, entry.readings.len()));
+        }
+        for h in &entry.highlights {
+            let page = h.page.map(|p| format!("p.{p} ")).unwrap_or_default();
+            println!("  {page}“{}”", h.text.trim());
+            if let Some(a) = h.annotation.as_deref().or(h.ko_note.as_deref()) {
+                println!("      — {a}");
+            }
+        }
+        for n in &entry.notes {
+            println!("  note #{} {} [{}]", n.id, n.title, n.kind);
+        }
+    }
+    Ok(())
+}
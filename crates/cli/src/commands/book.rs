@@ -1,7 +1,7 @@
 use std::path::Path;
 
-use anyhow::{Result, bail};
-use readingbuddy::{BookSort, Engine};
+use anyhow::{Context, Result, bail};
+use readingbuddy::{BookFilter, BookQuery, BookSort, BookStatus, Engine};
 
 use super::resolve_one;
 use crate::{prompt, render};
@@ -31,21 +31,139 @@ pub async fn import_epub(engine: &Engine, path: &Path) -> Result<()> {
     Ok(())
 }
 
-pub async fn list(engine: &Engine, limit: i64, sort: &str) -> Result<()> {
-    let sort = match sort {
-        "title" => BookSort::Title,
-        "progress" => BookSort::Progress,
-        "last-modified" | "last_modified" => BookSort::LastModified,
-        other => bail!("unknown sort '{other}' (last-modified | title | progress)"),
-    };
-    let books = engine.list_books(limit, sort).await?;
-    if books.is_empty() {
-        println!("library is empty — try `readingbuddy search` or `readingbuddy epub`");
+#[derive(clap::Args)]
+pub struct ListArgs {
+    #[arg(long, default_value_t = 20)]
+    pub limit: i64,
+    /// Skip this many first — the next page of a large library
+    #[arg(long, default_value_t = 0)]
+    pub offset: i64,
+    /// last-modified | title | progress | series
+    #[arg(long, default_value = "last-modified")]
+    pub sort: String,
+    /// unstarted | reading | finished | abandoned
+    #[arg(long)]
+    pub status: Option<String>,
+    /// Part of an author's name
+    #[arg(long)]
+    pub author: Option<String>,
+    /// Published in or after this year
+    #[arg(long)]
+    pub from_year: Option<i64>,
+    /// Published in or before this year
+    #[arg(long)]
+    pub to_year: Option<i64>,
+    /// Language as stored on the book (en, fr, ...)
+    #[arg(long)]
+    pub lang: Option<String>,
+    /// A Goodreads or calibre shelf
+    #[arg(long)]
+    pub tag: Option<String>,
+    /// true | false
+    #[arg(long)]
+    pub has_cover: Option<bool>,
+    /// true | false: whether the book has an owned file
+    #[arg(long)]
+    pub has_file: Option<bool>,
+    /// goodreads | calibre | koreader | manual
+    #[arg(long)]
+    pub source: Option<String>,
+    /// Part of a series' name
+    #[arg(long)]
+    pub series: Option<String>,
+    /// One of your shelves, manual or smart
+    #[arg(long)]
+    pub shelf: Option<String>,
+}
+
+impl Default for ListArgs {
+    /// What a bare `list` means, for callers that do not go through clap.
+    fn default() -> Self {
+        ListArgs {
+            limit: 20,
+            offset: 0,
+            sort: "last-modified".into(),
+            status: None,
+            author: None,
+            from_year: None,
+            to_year: None,
+            lang: None,
+            tag: None,
+            has_cover: None,
+            has_file: None,
+            source: None,
+            series: None,
+            shelf: None,
+        }
+    }
+}
+
+impl ListArgs {
+    fn to_query(&self) -> Result<BookQuery> {
+        let sort = match self.sort.as_str() {
+            "title" => BookSort::Title,
+            "progress" => BookSort::Progress,
+            "series" => BookSort::Series,
+            "last-modified" | "last_modified" => BookSort::LastModified,
+            other => bail!("unknown sort '{other}' (last-modified | title | progress | series)"),
+        };
+        let status = match self.status.as_deref() {
+            None => None,
+            Some(s) => Some(BookStatus::parse(s).with_context(|| {
+                format!("unknown status '{s}' (unstarted | reading | finished | abandoned)")
+            })?),
+        };
+        Ok(BookQuery {
+            filter: BookFilter {
+                status,
+                author: self.author.clone(),
+                year_from: self.from_year,
+                year_to: self.to_year,
+                language: self.lang.clone(),
+                tag: self.tag.clone(),
+                has_cover: self.has_cover,
+                has_file: self.has_file,
+                source: self.source.clone(),
+                series: self.series.clone(),
+                shelf: self.shelf.clone(),
+                ..Default::default()
+            },
+            sort,
+            limit: Some(self.limit),
+            offset: self.offset,
+        })
+    }
+}
+
+pub async fn list(engine: &Engine, args: &ListArgs) -> Result<()> {
+    let query = args.to_query()?;
+    let page = engine.query_books(&query).await?;
+    if page.total == 0 {
+        if query.filter == BookFilter::default() {
+            println!("library is empty — try `readingbuddy search` or `readingbuddy epub`");
+        } else {
+            println!("no books match");
+        }
         return Ok(());
     }
-    for b in &books {
+    for b in &page.books {
         println!("{}", render::book_line(b));
     }
+    // Only when something was left out: a list that is the whole answer needs
+    // no footer saying so.
+    if page.books.is_empty() {
+        println!("nothing past {} — {} in all", page.offset, page.total);
+    } else if page.books.len() as i64 != page.total {
+        let first = page.offset + 1;
+        let last = page.offset + page.books.len() as i64;
+        match page.next_offset() {
+            Some(next) => println!(
+                "{first}–{last} of {} (--offset {next} for more)",
+                page.total
+            ),
+            None => println!("{first}–{last} of {}", page.total),
+        }
+    }
     Ok(())
 }
 
@@ -66,6 +184,18 @@ pub async fn show(engine: &Engine, selector: &str) -> Result<()> {
         for (i, r) in readings.iter().enumerate() {
             println!("  {}", render::reading_line(r, i + 1, readings.len()));
         }
+        // "Have I read this?" is the work's question: another edition counts.
+        let history = engine.work_history(id).await?;
+        if history.editions.len() > 1 {
+            println!(
+                "  {:<14} {} — `work {id}`",
+                "editions",
+                history.editions.len()
+            );
+            if let Some(read) = history.read_as().filter(|b| b.id != book.id) {
+                println!("  {:<14} {}", "read as", render::book_line(read));
+            }
+        }
     }
     Ok(())
 }
@@ -181,6 +311,7 @@ pub async fn highlights(engine: &Engine, selector: &str) -> Result<()> {
         return Ok(());
     }
     println!("{} — {} highlights\n", book.display_title(), hs.len());
+    let studied = engine.studied_highlights(id).await?;
 
     // Rereads are first-class, so the list is grouped by the read each
     // highlight was captured during — but only when there is more than one read
@@ -189,7 +320,7 @@ pub async fn highlights(engine: &Engine, selector: &str) -> Result<()> {
     let readings = engine.list_readings(id).await?;
     if readings.len() < 2 {
         for h in &hs {
-            print_highlight(h, "  ");
+            print_highlight(h, "  ", &studied);
         }
         return Ok(());
     }
@@ -204,7 +335,7 @@ pub async fn highlights(engine: &Engine, selector: &str) -> Result<()> {
             println!("    (none)");
         }
         for h in mine {
-            print_highlight(h, "    ");
+            print_highlight(h, "    ", &studied);
         }
         println!();
     }
@@ -217,20 +348,25 @@ pub async fn highlights(engine: &Engine, selector: &str) -> Result<()> {
     if !unplaced.is_empty() {
         println!("  not placed in a reading ({})", unplaced.len());
         for h in unplaced {
-            print_highlight(h, "    ");
+            print_highlight(h, "    ", &studied);
         }
     }
     Ok(())
 }
 
-fn print_highlight(h: &readingbuddy::Highlight, indent: &str) {
+fn print_highlight(h: &readingbuddy::Highlight, indent: &str, studied: &[i64]) {
     let page = h.page.map(|p| format!("p.{p} ")).unwrap_or_default();
     let chapter = h
         .chapter
         .as_deref()
         .map(|c| format!("[{c}] "))
         .unwrap_or_default();
-    println!("{indent}{page}{chapter}“{}”", h.text);
+    let deck = if studied.contains(&h.id) {
+        "  [studying]"
+    } else {
+        ""
+    };
+    println!("{indent}#{} {page}{chapter}“{}”{deck}", h.id, h.text);
     if let Some(note) = &h.ko_note {
         println!("{indent}    ↳ {note}");
     }
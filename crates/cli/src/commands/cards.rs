@@ -1,7 +1,9 @@
 use std::path::Path;
 
 use anyhow::Result;
-use readingbuddy::Engine;
+use readingbuddy::{CardFormat, CardKind, Engine, Grade, StudyCard};
+
+use crate::prompt::get_user_input;
 
 pub async fn list(engine: &Engine, all: bool) -> Result<()> {
     let cards = engine.list_flashcards(all).await?;
@@ -20,13 +22,106 @@ pub async fn list(engine: &Engine, all: bool) -> Result<()> {
     Ok(())
 }
 
-pub async fn export(engine: &Engine, out: &Path, all: bool) -> Result<()> {
-    let (tsv, count) = engine.export_flashcards(all).await?;
+pub async fn export(engine: &Engine, out: &Path, format: Option<&str>, all: bool) -> Result<()> {
+    let format = match format {
+        Some(f) => f.parse::<CardFormat>()?,
+        None => CardFormat::for_path(out),
+    };
+    let (bytes, count) = engine.export_flashcards(all, format).await?;
     if count == 0 {
         println!("nothing to export.");
         return Ok(());
     }
-    std::fs::write(out, tsv)?;
-    println!("{count} cards -> {} (Anki: File > Import)", out.display());
+    std::fs::write(out, bytes)?;
+    println!(
+        "{count} cards -> {} ({}; Anki: File > Import)",
+        out.display(),
+        format.as_str()
+    );
+    Ok(())
+}
+
+/// Where a card came from, on one line: book, authors, chapter, page.
+fn provenance(c: &StudyCard) -> String {
+    let mut line = c.book_title.clone();
+    if !c.authors.is_empty() {
+        line.push_str(&format!(" — {}", c.authors.join(", ")));
+    }
+    if let Some(ch) = &c.chapter {
+        line.push_str(&format!(" · {ch}"));
+    }
+    if let Some(p) = c.page {
+        line.push_str(&format!(" · p.{p}"));
+    }
+    line
+}
+
+fn days(n: i64) -> String {
+    if n == 1 {
+        "1 day".into()
+    } else {
+        format!("{n} days")
+    }
+}
+
+/// Review what is due, one card at a time: the prompt and where it is from,
+/// Enter for the rest, then a grade. An empty answer to the grade stops, so
+/// EOF ends the session rather than spinning on it.
+///
+/// No tallies, on the way in or out — see "No task-completion framing" in
+/// `docs/decisions.md`. What is not reviewed today stays due.
+pub async fn review(engine: &Engine, limit: usize) -> Result<()> {
+    let due = engine.due_cards(limit).await?;
+    if due.is_empty() {
+        println!(
+            "nothing due. Words come from single-word highlights; `cards study <highlight id>` \
+             adds a passage."
+        );
+        return Ok(());
+    }
+    println!("Enter shows the rest; 1 again · 2 hard · 3 good · 4 easy · q stops.");
+    for card in &due {
+        println!();
+        match card.kind {
+            CardKind::Word => println!("{}", card.front),
+            CardKind::Highlight => println!("“{}”", card.front),
+        }
+        println!("  {}", provenance(card));
+        get_user_input("")?;
+        if let Some(ctx) = &card.context {
+            println!("  {ctx}");
+        }
+        let grade = loop {
+            let raw = get_user_input("  grade [1-4, q]: ")?;
+            if raw.is_empty() || raw.eq_ignore_ascii_case("q") {
+                println!("\nstopped — the rest stay due.");
+                return Ok(());
+            }
+            match Grade::parse(&raw) {
+                Some(g) => break g,
+                None => println!("  1 again, 2 hard, 3 good, 4 easy — or q to stop"),
+            }
+        };
+        let graded = engine.grade_card(card.id, grade).await?;
+        println!(
+            "  {}: back in {}",
+            grade.as_str(),
+            days(graded.schedule.interval_days)
+        );
+    }
+    println!("\nthat is everything due.");
+    Ok(())
+}
+
+/// Opt a highlight into study, or out of it with `remove`.
+pub async fn study(engine: &Engine, highlight_id: i64, remove: bool) -> Result<()> {
+    let changed = engine.study_highlight(highlight_id, !remove).await?;
+    let msg = match (remove, changed) {
+        (false, true) => "now studying",
+        (false, false) => "already studying",
+        (true, true) => "stopped studying",
+        (true, false) => "was not studying",
+    };
+    println!("{msg} highlight #{highlight_id}");
     Ok(())
 }
@@ -0,0 +1,53 @@
+//! `cite export`.
+//!
+//! The outward half of citing: a reference manager's entries for books, or a
+//! highlight quoted with its page and edition. `cite <note> <highlight>` — a
+//! review pointing at a passage — lives with the review, in `reflect.rs`.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use readingbuddy::{CitationFormat, Engine, QuoteStyle};
+
+use super::resolve_one;
+
+pub struct ExportOpts<'a> {
+    /// Book selectors; none means the whole library.
+    pub books: &'a [String],
+    pub format: &'a str,
+    /// Quote this highlight instead of listing books.
+    pub highlight: Option<i64>,
+    pub style: &'a str,
+    /// Where it goes; stdout when omitted.
+    pub out: Option<&'a Path>,
+}
+
+pub async fn export(engine: &Engine, opts: ExportOpts<'_>) -> Result<()> {
+    let text = match opts.highlight {
+        Some(id) => {
+            let style: QuoteStyle = opts.style.parse()?;
+            engine.quote_highlight(id, style).await? + "\n"
+        }
+        None => {
+            let format: CitationFormat = opts.format.parse()?;
+            let mut ids = Vec::with_capacity(opts.books.len());
+            for selector in opts.books {
+                ids.push(
+                    resolve_one(engine, selector)
+                        .await?
+                        .id
+                        .expect("stored book has id"),
+                );
+            }
+            engine.cite_books(&ids, format).await?
+        }
+    };
+    match opts.out {
+        Some(path) => {
+            std::fs::write(path, &text).with_context(|| format!("writing {}", path.display()))?;
+            println!("-> {}", path.display());
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! `kindle import`.
+//!
+//! A Kindle gives its highlights up in one file, `My Clippings.txt`, and
+//! nowhere else. All the printing lives here — the engine does no terminal I/O.
+
+use std::path::Path;
+
+use anyhow::Result;
+use readingbuddy::kindle::ImportOptions;
+use readingbuddy::{Engine, KindleBookReport, KindleReport};
+
+pub async fn import(engine: &Engine, path: &Path, dry_run: bool, new: bool) -> Result<()> {
+    let report = engine
+        .import_kindle(
+            path,
+            ImportOptions {
+                dry_run,
+                create_ambiguous: new,
+            },
+        )
+        .await?;
+
+    for w in &report.warnings {
+        eprintln!("warning: {w}");
+    }
+    for b in &report.books {
+        println!("{}", book_line(b, &report));
+    }
+
+    // The same two moves `goodreads import` offers, for the same reason: a
+    // title that is only close to one of yours is yours to decide about.
+    for u in &report.unmatched {
+        println!(
+            "unmatched: {} — {} ({} clippings)",
+            u.title,
+            if u.authors.is_empty() {
+                "(unknown author)".to_string()
+            } else {
+                u.authors.join(", ")
+            },
+            u.clippings
+        );
+        for c in &u.candidates {
+            println!(
+                "    maybe #{}: {} ({:.0}%)",
+                c.book_id,
+                c.title,
+                c.score * 100.0
+            );
+        }
+        println!("    it is one of those : readingbuddy merge <new> <kept>, after importing");
+        println!(
+            "    or it is not       : readingbuddy kindle import {} --new",
+            path.display()
+        );
+    }
+
+    println!();
+    if report.dry_run {
+        println!(
+            "{} clippings read from {}, {} books would be created, {} left for you to decide about.",
+            report.entries,
+            report.path.display(),
+            report.created(),
+            report.unmatched.len()
+        );
+        println!(
+            "  nothing was written. do it: readingbuddy kindle import {}",
+            path.display()
+        );
+    } else {
+        println!(
+            "{} clippings read from {}, {} books created, {} matched, {} left for you to decide about.",
+            report.entries,
+            report.path.display(),
+            report.created(),
+            report.books.len() - report.created(),
+            report.unmatched.len()
+        );
+    }
+    Ok(())
+}
+
+fn book_line(b: &KindleBookReport, report: &KindleReport) -> String {
+    let mut parts = Vec::new();
+    if b.inserted > 0 {
+        parts.push(format!("{} new", b.inserted));
+    }
+    if b.updated > 0 {
+        parts.push(format!("{} updated", b.updated));
+    }
+    if b.flashcards > 0 {
+        parts.push(match b.flashcards {
+            1 => "1 flashcard".to_string(),
+            n => format!("{n} flashcards"),
+        });
+    }
+    if parts.is_empty() {
+        parts.push("nothing new".to_string());
+    }
+    let mode = if report.dry_run { " (dry run)" } else { "" };
+    format!(
+        "{}{mode}: {} (matched by {})",
+        b.title,
+        parts.join(", "),
+        b.matched_by
+    )
+}
@@ -1,7 +1,10 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::{Result, bail};
-use readingbuddy::{BookImportStats, DeviceBook, DeviceState, Engine, MatchCandidate};
+use readingbuddy::{
+    ActivityReport, BookImportStats, DeviceBook, DeviceState, Engine, EngineError, MatchCandidate,
+    PluginState,
+};
 
 use super::resolve_one;
 
@@ -131,6 +134,40 @@ pub async fn scan(engine: &Engine, path: Option<&Path>) -> Result<()> {
     Ok(())
 }
 
+/// Fold the device's reading statistics into the activity log.
+pub async fn stats(engine: &Engine, path: Option<&Path>, dry_run: bool) -> Result<()> {
+    let root = resolve_mount(path)?;
+    let report = engine.import_koreader_statistics(&root, dry_run).await?;
+    print_activity(&report, if dry_run { " (dry run)" } else { "" });
+    Ok(())
+}
+
+fn print_activity(report: &ActivityReport, mode: &str) {
+    for b in &report.books {
+        println!(
+            "{}{mode}: {} days, {} min, {} pages ({} new days, {} updated)",
+            b.book_title, b.days, b.minutes, b.pages, b.new_days, b.updated_days
+        );
+    }
+    // The same two moves as an unmatched sidecar, because the fix is the same:
+    // once the sidecar is linked, the next import brings these days across.
+    for u in &report.unmatched {
+        println!(
+            "unmatched{mode}: {} — {} days, {} min ({})",
+            u.title.as_deref().unwrap_or("unknown title"),
+            u.days,
+            u.minutes,
+            u.partial_md5
+        );
+    }
+    if !report.unmatched.is_empty() {
+        println!("    link their sidecars first (`readingbuddy ko sync`), then run this again");
+    }
+    if report.books.is_empty() && report.unmatched.is_empty() {
+        println!("no reading logged in {}.", report.db.display());
+    }
+}
+
 /// Wait for a reader to be plugged in, and scan it when it is. Writes nothing.
 ///
 /// This is the headless half of the mount watcher, and the instrument the wired
@@ -228,6 +265,21 @@ pub async fn sync(engine: &Engine, path: &Path, all: bool, selectors: &[String])
         }
         println!("{}", stats_line(&report.stats, ""));
     }
+
+    // The books just synced are linked now, which is what the statistics join
+    // on — so this is the moment their reading time can come across too. A
+    // reader without the statistics plugin's database is not an error.
+    if readingbuddy::koreader_stats::statistics_db(path).is_some() {
+        let report = engine.import_koreader_statistics(path, false).await?;
+        let days: usize = report
+            .books
+            .iter()
+            .map(|b| b.new_days + b.updated_days)
+            .sum();
+        if days > 0 {
+            println!("reading time: {days} days brought across from the device's statistics");
+        }
+    }
     Ok(())
 }
 
@@ -317,7 +369,133 @@ fn device_line(b: &DeviceBook) -> String {
     )
 }
 
-fn stats_line(s: &BookImportStats, mode: &str) -> String {
+/// Push annotations into a sidecar, or show what a push would change as a
+/// diff of the device's notes.
+pub async fn push(engine: &Engine, path: &Path, dry_run: bool) -> Result<()> {
+    let report = match engine.push_annotations(path, dry_run).await {
+        Ok(report) => report,
+        Err(e @ EngineError::DeviceChanged { .. }) => {
+            bail!(
+                "{e}\n    pull it in : readingbuddy ko pull {}",
+                path.display()
+            )
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if report.changes.is_empty() {
+        println!(
+            "nothing to push — {} already has every annotation.",
+            report.sidecar.display()
+        );
+        return Ok(());
+    }
+    println!("--- {} (on the device)", report.sidecar.display());
+    println!("+++ {} (with your annotations)", report.sidecar.display());
+    for c in &report.changes {
+        println!("@@ [{}] {}", c.index, c.text);
+        if let Some(before) = &c.before {
+            for line in before.lines() {
+                println!("-{line}");
+            }
+        }
+        for line in c.after.lines() {
+            println!("+{line}");
+        }
+    }
+    println!();
+    match &report.backup {
+        Some(backup) => println!(
+            "{} notes pushed; the original is at {}",
+            report.changes.len(),
+            backup.display()
+        ),
+        None => println!(
+            "{} notes would be pushed (dry run) — nothing was written",
+            report.changes.len()
+        ),
+    }
+    Ok(())
+}
+
+pub fn plugin_status(engine: &Engine, path: Option<&Path>) -> Result<()> {
+    let mount = resolve_mount(path)?;
+    let status = engine.plugin_status(&mount)?;
+    println!("{}", status.plugin_dir.display());
+    let bundled = status.bundled;
+    match status.state {
+        PluginState::NotInstalled => {
+            println!("  not installed (this build carries version {bundled})");
+            println!(
+                "  install it : readingbuddy ko plugin install {}",
+                mount.display()
+            );
+        }
+        PluginState::Current => println!("  version {bundled}, up to date"),
+        PluginState::Older { installed } => {
+            println!("  version {installed}; this build carries {bundled}");
+            println!(
+                "  upgrade it : readingbuddy ko plugin install {}",
+                mount.display()
+            );
+        }
+        PluginState::Newer { installed } => {
+            println!("  version {installed}, newer than this build's {bundled} — left as it is")
+        }
+        PluginState::Foreign { reason } => {
+            println!("  not readingbuddy's: {reason}. readingbuddy will not touch it")
+        }
+    }
+    Ok(())
+}
+
+/// Install the plugin, naming every file written — the user is putting code
+/// on their reader, and is owed the list.
+pub fn plugin_install(engine: &Engine, path: Option<&Path>) -> Result<()> {
+    let mount = resolve_mount(path)?;
+    let report = engine.install_plugin(&mount)?;
+    if report.written.is_empty() {
+        println!(
+            "version {} is already installed at {}",
+            report.version,
+            report.plugin_dir.display()
+        );
+        return Ok(());
+    }
+    for p in &report.written {
+        println!("wrote   {}", p.display());
+    }
+    for p in &report.removed {
+        println!("removed {}", p.display());
+    }
+    match report.replaced {
+        Some(old) => println!(
+            "upgraded the plugin from version {old} to {}",
+            report.version
+        ),
+        None => println!("installed version {} of the plugin", report.version),
+    }
+    println!("restart KOReader on the reader to load it.");
+    Ok(())
+}
+
+pub fn plugin_remove(engine: &Engine, path: Option<&Path>) -> Result<()> {
+    let mount = resolve_mount(path)?;
+    let report = engine.uninstall_plugin(&mount)?;
+    let Some(version) = report.version else {
+        println!("not installed: {}", report.plugin_dir.display());
+        return Ok(());
+    };
+    for p in &report.removed {
+        println!("removed {}", p.display());
+    }
+    for p in &report.kept {
+        println!("kept    {} (not ours)", p.display());
+    }
+    println!("removed version {version} of the plugin");
+    Ok(())
+}
+
+pub fn stats_line(s: &BookImportStats, mode: &str) -> String {
     format!(
         "{}{mode}: {} new, {} updated from the device, {} already known, {} flashcard candidates \
          (matched by {})",
@@ -325,7 +503,7 @@ fn stats_line(s: &BookImportStats, mode: &str) -> String {
     )
 }
 
-fn print_candidates(candidates: &[MatchCandidate]) {
+pub fn print_candidates(candidates: &[MatchCandidate]) {
     for c in candidates {
         println!(
             "    maybe #{}: {} ({:.0}%)",
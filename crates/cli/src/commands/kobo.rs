@@ -0,0 +1,46 @@
+//! `kobo import`.
+//!
+//! A stock Kobo keeps everything in one database, `.kobo/KoboReader.sqlite`.
+//! Importing it is `ko import` for that source, so the output reads the same;
+//! a single book is pulled in through `ko sync`, which lists Kobo books next to
+//! KOReader ones.
+
+use std::path::Path;
+
+use anyhow::Result;
+use readingbuddy::Engine;
+use readingbuddy::kobo;
+
+use super::ko::{print_candidates, stats_line};
+
+pub async fn import(engine: &Engine, path: &Path, dry_run: bool) -> Result<()> {
+    let report = engine.import_kobo(path, dry_run).await?;
+    let mode = if dry_run { " (dry run)" } else { "" };
+    let mount = kobo::kobo_db(path)
+        .map(|db| kobo::mount_of(&db))
+        .unwrap_or_else(|| path.to_path_buf());
+
+    for w in &report.warnings {
+        eprintln!("warning: {w}");
+    }
+    for s in &report.imported {
+        println!("{}", stats_line(s, mode));
+    }
+    for u in &report.unmatched {
+        println!(
+            "unmatched{mode}: {} ({})",
+            u.title.as_deref().unwrap_or("unknown title"),
+            u.path.display()
+        );
+        print_candidates(&u.candidates);
+        println!(
+            "    pull it in : readingbuddy ko sync {} --book {}",
+            mount.display(),
+            u.path.display()
+        );
+    }
+    if report.imported.is_empty() && report.unmatched.is_empty() && report.warnings.is_empty() {
+        println!("nothing to import.");
+    }
+    Ok(())
+}
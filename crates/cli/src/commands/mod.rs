@@ -1,13 +1,23 @@
+pub mod author;
 pub mod book;
 pub mod calibre;
 pub mod cards;
+pub mod cite;
 pub mod config;
 pub mod goodreads;
+pub mod kindle;
 pub mod ko;
+pub mod kobo;
 pub mod note;
 pub mod rating;
 pub mod reflect;
 pub mod search;
+pub mod series;
+pub mod shelf;
+pub mod site;
+pub mod stats;
+pub mod vault;
+pub mod work;
 
 use anyhow::{Result, bail};
 use readingbuddy::{Book, Engine, NoteRecord};
@@ -13,6 +13,7 @@ pub struct NoteOpts<'a> {
     pub no_page: bool,
     pub location: Option<String>,
     pub highlight: Option<i64>,
+    pub template: Option<String>,
 }
 
 pub async fn create(engine: &Engine, opts: NoteOpts<'_>) -> Result<()> {
@@ -42,27 +43,35 @@ pub async fn create(engine: &Engine, opts: NoteOpts<'_>) -> Result<()> {
         (None, false) => book.as_ref().and_then(|b| b.current_page),
     };
 
-    let body = match opts.text {
-        Some(t) if !t.trim().is_empty() => t,
-        _ => prompt::edit_in_editor("")?,
+    let mut input = NewNoteInput {
+        book_id: book.as_ref().and_then(|b| b.id),
+        reading_id: None,
+        highlight_id: opts.highlight,
+        page,
+        location: opts.location,
+        kind,
+        title: opts.title,
+        body: String::new(),
+        template: opts.template,
     };
-    if body.trim().is_empty() {
+    match opts.text {
+        // Words on the command line are the note; a template named beside them
+        // is expanded around them by the engine.
+        Some(t) if !t.trim().is_empty() => input.body = t,
+        // Composing: the template is what the editor opens on, so it is
+        // expanded here and whatever comes back is the note.
+        _ => {
+            let start = engine.expand_template(&input).await?.unwrap_or_default();
+            input.body = prompt::edit_in_editor(&start)?;
+            input.template = None;
+        }
+    }
+    if input.body.trim().is_empty() {
         println!("empty note, nothing saved.");
         return Ok(());
     }
 
-    let created = engine
-        .create_note(NewNoteInput {
-            book_id: book.as_ref().and_then(|b| b.id),
-            reading_id: None,
-            highlight_id: opts.highlight,
-            page,
-            location: opts.location,
-            kind,
-            title: opts.title,
-            body,
-        })
-        .await?;
+    let created = engine.create_note(input).await?;
     println!(
         "note #{} “{}” -> {}",
         created.id,
@@ -137,12 +146,21 @@ pub async fn links(engine: &Engine, selector: &str) -> Result<()> {
         println!("  (none — a [[wikilink]] in the body makes one)");
     }
     for link in outgoing {
+        let section = link
+            .anchor
+            .as_deref()
+            .map(|a| format!(" #{a}"))
+            .unwrap_or_default();
         match link.to {
-            Some(t) => println!("  → #{:<4} “{}”", t.id, t.title),
+            Some(t) if !link.anchor_found => println!(
+                "  → #{:<4} “{}”{section}  (no such section — the link lands at the top)",
+                t.id, t.title
+            ),
+            Some(t) => println!("  → #{:<4} “{}”{section}", t.id, t.title),
             // Text, not an error: it resolves itself the moment that note is
             // written, and until then it is a note worth writing.
             None => println!(
-                "  → “{}”  (text — no note by that title yet)",
+                "  → “{}”{section}  (text — no note by that title yet)",
                 link.target_title
             ),
         }
@@ -153,8 +171,16 @@ pub async fn links(engine: &Engine, selector: &str) -> Result<()> {
     if inbound.is_empty() {
         println!("  (nothing links here yet)");
     }
-    for n in inbound {
-        println!("  ← #{:<4} “{}”", n.id, n.title);
+    for link in inbound {
+        let n = &link.from;
+        match (&link.anchor, link.anchor_found) {
+            (Some(a), true) => println!("  ← #{:<4} “{}”  cites #{a}", n.id, n.title),
+            (Some(a), false) => println!(
+                "  ← #{:<4} “{}”  cites #{a}, which this note no longer has",
+                n.id, n.title
+            ),
+            (None, _) => println!("  ← #{:<4} “{}”", n.id, n.title),
+        }
     }
     Ok(())
 }
@@ -5,8 +5,10 @@
 //! body — a public review is a rewrite for a different audience, not a subset of
 //! private thinking — so each is opened and edited on its own.
 
-use anyhow::{Result, bail};
-use readingbuddy::{CreatedNote, Engine, EngineError, NoteKind, Reading};
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use readingbuddy::{CreatedNote, Engine, EngineError, ExportFormat, NoteKind, NoteRecord, Reading};
 
 use super::resolve_one;
 use crate::{prompt, render};
@@ -22,6 +24,12 @@ pub struct ReflectOpts<'a> {
     pub no_edit: bool,
     /// Reviews only: the rating, on the active scale.
     pub rating: Option<f64>,
+    /// Reviews only: export it as a page in this format instead of opening it.
+    pub export: Option<&'a str>,
+    /// Where the export goes; stdout when omitted.
+    pub out: Option<&'a Path>,
+    /// Reviews only: publish it to the site (`Some(true)`) or take it back.
+    pub publish: Option<bool>,
 }
 
 pub async fn reflect(engine: &Engine, opts: ReflectOpts<'_>) -> Result<()> {
@@ -39,6 +47,9 @@ async fn run(engine: &Engine, opts: ReflectOpts<'_>, kind: NoteKind) -> Result<(
     if opts.rating.is_some() && kind != NoteKind::Review {
         bail!("a rating belongs to a review — try `readingbuddy review {book_id} --rating …`");
     }
+    if opts.publish.is_some() && kind != NoteKind::Review {
+        bail!("a reflection is never published — only a review goes on the site");
+    }
 
     let readings = engine.list_readings(book_id).await?;
     let reading_id = match opts.reading {
@@ -46,6 +57,20 @@ async fn run(engine: &Engine, opts: ReflectOpts<'_>, kind: NoteKind) -> Result<(
         None => None,
     };
 
+    if opts.export.is_some() || opts.out.is_some() {
+        if kind != NoteKind::Review {
+            bail!(
+                "only a review exports — a reflection is yours; try `readingbuddy review {book_id} --export html`"
+            );
+        }
+        let format = match (opts.export, opts.out) {
+            (Some(f), _) => f.parse::<ExportFormat>()?,
+            (None, Some(path)) => ExportFormat::for_path(path),
+            (None, None) => unreachable!("checked above"),
+        };
+        return export(engine, &readings, reading_id, format, opts.out).await;
+    }
+
     // `--show` must not write. Opening one creates a reading when the book has
     // none, which is right when the user is sitting down to write and wrong
     // when they only asked to look.
@@ -97,6 +122,18 @@ async fn run(engine: &Engine, opts: ReflectOpts<'_>, kind: NoteKind) -> Result<(
         }
     }
 
+    if let Some(public) = opts.publish {
+        engine.set_review_public(note.id, public).await?;
+        println!(
+            "{} — `readingbuddy site build <dir>` renders the site",
+            if public {
+                "published"
+            } else {
+                "taken off the site"
+            }
+        );
+    }
+
     // Re-read from the vault rather than echoing what the editor returned: the
     // file is the source of the body, and this is where that shows.
     let body = engine.note_body(&record)?;
@@ -112,20 +149,7 @@ async fn show_only(
     reading_id: Option<i64>,
     kind: NoteKind,
 ) -> Result<()> {
-    // The current reading: the open one, else the most recent — the same rule
-    // `Book`'s progress projections follow.
-    let current = reading_id.or_else(|| {
-        readings
-            .iter()
-            .find(|r| r.finished_at.is_none())
-            .or(readings.last())
-            .map(|r| r.id)
-    });
-    let existing = match current {
-        Some(rid) => engine.note_for_reading(rid, kind.as_str()).await?,
-        None => None,
-    };
-    let Some(record) = existing else {
+    let Some(record) = existing_note(engine, readings, reading_id, kind).await? else {
         println!(
             "no {} yet — `readingbuddy {}` opens one",
             kind.as_str(),
@@ -147,6 +171,57 @@ async fn show_only(
     Ok(())
 }
 
+/// The note of this kind on the chosen reading, without creating either.
+async fn existing_note(
+    engine: &Engine,
+    readings: &[Reading],
+    reading_id: Option<i64>,
+    kind: NoteKind,
+) -> Result<Option<NoteRecord>> {
+    // The current reading: the open one, else the most recent — the same rule
+    // `Book`'s progress projections follow.
+    let current = reading_id.or_else(|| {
+        readings
+            .iter()
+            .find(|r| r.finished_at.is_none())
+            .or(readings.last())
+            .map(|r| r.id)
+    });
+    Ok(match current {
+        Some(rid) => engine.note_for_reading(rid, kind.as_str()).await?,
+        None => None,
+    })
+}
+
+/// `review --export`: the review as a page, to a file or stdout. Like
+/// `--show`, it only reads — there is nothing to export from a review not
+/// written yet.
+async fn export(
+    engine: &Engine,
+    readings: &[Reading],
+    reading_id: Option<i64>,
+    format: ExportFormat,
+    out: Option<&Path>,
+) -> Result<()> {
+    let Some(record) = existing_note(engine, readings, reading_id, NoteKind::Review).await? else {
+        bail!("no review yet — `readingbuddy review <book>` opens one");
+    };
+    let page = engine.export_review(record.id, format).await?;
+    match out {
+        Some(path) => {
+            std::fs::write(path, &page).with_context(|| format!("writing {}", path.display()))?;
+            println!(
+                "review #{} → {} ({})",
+                record.id,
+                path.display(),
+                format.as_str()
+            );
+        }
+        None => print!("{page}"),
+    }
+    Ok(())
+}
+
 fn print_note(note: &CreatedNote, body: &str) {
     println!("#{} “{}” -> {}", note.id, note.title, note.file.display());
     print_body(body);
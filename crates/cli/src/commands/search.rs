@@ -1,6 +1,8 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use clap::Args;
-use readingbuddy::{Engine, SearchRequest};
+use readingbuddy::{Engine, LibraryHit, SearchRequest};
 
 use crate::{prompt, render};
 
@@ -35,6 +37,9 @@ pub struct SearchArgs {
     /// Skip cover download on save
     #[arg(long)]
     pub no_cover: bool,
+    /// Search your own notes and highlights instead of the providers
+    #[arg(long, conflicts_with_all = ["title", "author", "publisher", "translator", "lang", "year", "isbn", "pick"])]
+    pub library: bool,
 }
 
 impl SearchArgs {
@@ -54,6 +59,9 @@ impl SearchArgs {
 }
 
 pub async fn run(engine: &Engine, args: SearchArgs) -> Result<()> {
+    if args.library {
+        return library(engine, &args).await;
+    }
     let req = args.to_request();
     if req.is_empty() {
         anyhow::bail!("give me something to search: a query, --title, --author, --isbn, ...");
@@ -102,3 +110,60 @@ pub async fn run(engine: &Engine, args: SearchArgs) -> Result<()> {
     println!("saved {}", render::book_line(&saved));
     Ok(())
 }
+
+/// `search --library`: the text we hold rather than the providers' catalogues.
+///
+/// One ranked list, notes and highlights interleaved, because the engine ranks
+/// them against each other and re-sorting by kind here would undo that.
+async fn library(engine: &Engine, args: &SearchArgs) -> Result<()> {
+    let Some(q) = args.query.as_deref() else {
+        anyhow::bail!("search --library needs a query");
+    };
+    let hits = engine.search_library(q, args.limit.into()).await?;
+    if hits.is_empty() {
+        println!("nothing in your notes or highlights matches '{q}'");
+        return Ok(());
+    }
+
+    // One lookup per book rather than per hit: a good query lands many
+    // highlights in the same book.
+    let mut titles: HashMap<i64, String> = HashMap::new();
+    for id in hits.iter().filter_map(LibraryHit::book_id) {
+        if let std::collections::hash_map::Entry::Vacant(e) = titles.entry(id) {
+            let title = engine.get_book(id).await?;
+            e.insert(title.map_or_else(|| format!("#{id}"), |b| b.display_title().to_string()));
+        }
+    }
+    let book = |id: Option<i64>| {
+        id.and_then(|id| titles.get(&id))
+            .map(|t| format!("  — {t}"))
+            .unwrap_or_default()
+    };
+
+    for hit in &hits {
+        match hit {
+            LibraryHit::Note(n) => {
+                println!(
+                    "note      #{:<4} {}{}",
+                    n.note.id,
+                    n.note.title,
+                    book(n.note.book_id)
+                );
+            }
+            LibraryHit::Highlight(h) => {
+                let page = h
+                    .highlight
+                    .page
+                    .map(|p| format!("p.{p}"))
+                    .unwrap_or_default();
+                println!(
+                    "highlight #{:<4} {page}{}",
+                    h.highlight.id,
+                    book(Some(h.highlight.book_id))
+                );
+            }
+        }
+        println!("          {}", hit.snippet());
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+//! `series`: every series in the library, or one run with where the reader is
+//! in it.
+
+use anyhow::Result;
+use readingbuddy::{Engine, series};
+
+use crate::render;
+
+pub async fn run(engine: &Engine, name: Option<&str>) -> Result<()> {
+    let Some(name) = name else {
+        let all = engine.list_series().await?;
+        if all.is_empty() {
+            println!("no series yet — calibre, Goodreads and OpenLibrary all bring them in");
+        }
+        for s in &all {
+            println!("{}  {}/{} finished", s.name, s.finished, s.books);
+        }
+        return Ok(());
+    };
+
+    let run = engine.series(name).await?;
+    println!("{} — {} book(s)", run.name, run.books.len());
+    let next = run.next_up().and_then(|e| e.book.id);
+    for e in &run.books {
+        let at = e
+            .book
+            .series_index
+            .map(series::format_index)
+            .unwrap_or_else(|| "#?".into());
+        let marker = if e.book.id.is_some() && e.book.id == next {
+            "  ← next"
+        } else {
+            ""
+        };
+        println!(
+            "  {at:<5} {:<10} {}{marker}",
+            e.status.as_str(),
+            render::book_line(&e.book)
+        );
+    }
+    Ok(())
+}
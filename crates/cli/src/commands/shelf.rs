@@ -0,0 +1,182 @@
+//! `shelf`: the user's own shelves, manual and smart, and promoting imported
+//! tags into them.
+//!
+//! A Goodreads shelf or calibre tag is never a shelf by itself — `shelf
+//! promote` with no tags lists what the imports recorded, and the user names
+//! the ones worth keeping.
+
+use anyhow::{Context, Result};
+use readingbuddy::{BookStatus, Engine, Shelf, ShelfRule};
+
+use super::resolve_one;
+use crate::render;
+
+/// The conditions `shelf new` takes; none at all is a manual shelf.
+pub struct RuleOpts<'a> {
+    pub status: Option<&'a str>,
+    pub tag: Option<String>,
+    pub author: Option<String>,
+    pub stars: Option<u8>,
+    pub finished_in: Option<i64>,
+    pub has_notes: Option<bool>,
+}
+
+impl RuleOpts<'_> {
+    fn to_rule(&self) -> Result<Option<ShelfRule>> {
+        let status = match self.status {
+            None => None,
+            Some(s) => Some(BookStatus::parse(s).with_context(|| {
+                format!("unknown status '{s}' (unstarted | reading | finished | abandoned)")
+            })?),
+        };
+        let rule = ShelfRule {
+            status,
+            tag: self.tag.clone(),
+            author: self.author.clone(),
+            min_stars: self.stars,
+            finished_year: self.finished_in,
+            has_notes: self.has_notes,
+        };
+        Ok((!rule.is_empty()).then_some(rule))
+    }
+}
+
+/// A rule the way `shelf new` would be asked for it.
+fn describe(rule: &ShelfRule) -> String {
+    let mut parts = Vec::new();
+    if let Some(s) = rule.status {
+        parts.push(format!("status {}", s.as_str()));
+    }
+    if let Some(t) = &rule.tag {
+        parts.push(format!("tag '{t}'"));
+    }
+    if let Some(a) = &rule.author {
+        parts.push(format!("author '{a}'"));
+    }
+    if let Some(n) = rule.min_stars {
+        parts.push(format!("{n}+ stars"));
+    }
+    if let Some(y) = rule.finished_year {
+        parts.push(format!("finished in {y}"));
+    }
+    match rule.has_notes {
+        Some(true) => parts.push("has notes".into()),
+        Some(false) => parts.push("no notes".into()),
+        None => {}
+    }
+    parts.join(", ")
+}
+
+fn shelf_line(s: &Shelf) -> String {
+    match &s.rule {
+        Some(rule) => format!(
+            "{}  {} book(s)  [smart: {}]",
+            s.name,
+            s.books,
+            describe(rule)
+        ),
+        None => format!("{}  {} book(s)", s.name, s.books),
+    }
+}
+
+pub async fn list(engine: &Engine) -> Result<()> {
+    let shelves = engine.list_shelves().await?;
+    if shelves.is_empty() {
+        println!(
+            "no shelves yet — `readingbuddy shelf new <name>`, or `shelf promote` to keep an \
+             imported one"
+        );
+    }
+    for s in &shelves {
+        println!("{}", shelf_line(s));
+    }
+    Ok(())
+}
+
+pub async fn show(engine: &Engine, name: &str) -> Result<()> {
+    let shelf = engine
+        .shelf(name)
+        .await?
+        .with_context(|| format!("no shelf called '{name}' — see `readingbuddy shelf list`"))?;
+    println!("{}", shelf_line(&shelf));
+    for (i, b) in engine.shelf_books(&shelf.name).await?.iter().enumerate() {
+        if shelf.is_smart() {
+            println!("  {}", render::book_line(b));
+        } else {
+            println!("  {:>3}. {}", i + 1, render::book_line(b));
+        }
+    }
+    Ok(())
+}
+
+pub async fn create(engine: &Engine, name: &str, rule: RuleOpts<'_>) -> Result<()> {
+    let rule = rule.to_rule()?;
+    let shelf = engine.create_shelf(name, rule.as_ref()).await?;
+    println!("made {}", shelf_line(&shelf));
+    Ok(())
+}
+
+pub async fn delete(engine: &Engine, name: &str) -> Result<()> {
+    if engine.delete_shelf(name).await? {
+        println!("deleted shelf '{name}' (its books are untouched)");
+    } else {
+        println!("no shelf called '{name}'");
+    }
+    Ok(())
+}
+
+pub async fn add(engine: &Engine, name: &str, selector: &str, at: Option<i64>) -> Result<()> {
+    let book = resolve_one(engine, selector).await?;
+    let id = book.id.expect("stored book has id");
+    engine.shelve(name, id, at).await?;
+    let position = engine
+        .shelf_books(name)
+        .await?
+        .iter()
+        .position(|b| b.id == Some(id))
+        .map(|i| i + 1)
+        .unwrap_or_default();
+    println!("{} is #{position} on '{name}'", book.display_title());
+    Ok(())
+}
+
+pub async fn remove(engine: &Engine, name: &str, selector: &str) -> Result<()> {
+    let book = resolve_one(engine, selector).await?;
+    let id = book.id.expect("stored book has id");
+    if engine.unshelve(name, id).await? {
+        println!("took {} off '{name}'", book.display_title());
+    } else {
+        println!("{} was not on '{name}'", book.display_title());
+    }
+    Ok(())
+}
+
+/// With no tags, the menu; with tags, the promotion.
+pub async fn promote(engine: &Engine, source: Option<&str>, tags: &[String]) -> Result<()> {
+    if tags.is_empty() {
+        let offered = engine.tag_candidates().await?;
+        if offered.is_empty() {
+            println!("no imported tags — Goodreads and calibre imports bring them in");
+            return Ok(());
+        }
+        println!("tags the imports recorded (`shelf promote <tag>...` to keep some):");
+        for t in offered
+            .iter()
+            .filter(|t| source.is_none_or(|s| s == t.source))
+        {
+            let kept = if t.shelved { "  (shelf exists)" } else { "" };
+            println!(
+                "  {:<10} {:<30} {} book(s){kept}",
+                t.source,
+                t.shelf_name(),
+                t.books
+            );
+        }
+        return Ok(());
+    }
+    for p in engine.promote_tags(source, tags).await? {
+        let verb = if p.created { "made" } else { "added to" };
+        println!("{verb} '{}': {} book(s) new", p.shelf, p.added);
+    }
+    Ok(())
+}
@@ -0,0 +1,26 @@
+//! `site build`.
+//!
+//! The library rendered as plain HTML, for publishing the way a blog is. What
+//! goes on it is decided per review (`review <book> --publish`); a reflection
+//! never does.
+
+use std::path::Path;
+
+use anyhow::Result;
+use readingbuddy::Engine;
+
+pub async fn build(engine: &Engine, out_dir: &Path) -> Result<()> {
+    let report = engine.build_site(out_dir).await?;
+    println!(
+        "{} books, {} published reviews, {} authors, {} tags -> {}",
+        report.books,
+        report.reviews,
+        report.authors,
+        report.tags,
+        report.index.display()
+    );
+    if report.reviews == 0 {
+        println!("    no review is published yet — `readingbuddy review <book> --publish`");
+    }
+    Ok(())
+}
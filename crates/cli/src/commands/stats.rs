@@ -0,0 +1,72 @@
+use anyhow::{Result, bail};
+use readingbuddy::{Engine, PeriodStats, StatsGrain, StatsRange, parse_day};
+
+use super::resolve_one;
+
+pub struct StatsOpts<'a> {
+    pub year: Option<i32>,
+    pub from: Option<&'a str>,
+    pub to: Option<&'a str>,
+    pub by: &'a str,
+    pub book_selector: Option<&'a str>,
+}
+
+/// Per-period reading statistics, as a table.
+pub async fn run(engine: &Engine, opts: StatsOpts<'_>) -> Result<()> {
+    let grain = match opts.by {
+        "day" => StatsGrain::Day,
+        "month" => StatsGrain::Month,
+        "year" => StatsGrain::Year,
+        "all" => StatsGrain::All,
+        other => bail!("unknown period '{other}' (day | month | year | all)"),
+    };
+
+    // --from/--to win over --year; either end left out falls back to the year's.
+    let year = opts
+        .year
+        .unwrap_or_else(|| time::OffsetDateTime::now_utc().year());
+    let from = parse_day(opts.from.unwrap_or(&format!("{year}-01-01")))?;
+    let to = parse_day(opts.to.unwrap_or(&format!("{year}-12-31")))?;
+
+    let book_id = match opts.book_selector {
+        Some(sel) => resolve_one(engine, sel).await?.id,
+        None => None,
+    };
+
+    let periods = engine
+        .reading_stats(&StatsRange { from, to, book_id }, grain)
+        .await?;
+    println!(
+        "{:<23} {:>8} {:>5} {:>10} {:>5} {:>5} {:>7} {:>7}",
+        "period", "finished", "days", "highlights", "notes", "links", "pages", "minutes"
+    );
+    for p in &periods {
+        println!("{}", row(p));
+    }
+    // The dash is the point of the table, so say what it means once.
+    if periods
+        .iter()
+        .any(|p| p.minutes.is_none() || p.pages.is_none())
+    {
+        println!();
+        println!(
+            "— : no source measured this (not zero). `readingbuddy ko stats` brings in KOReader's."
+        );
+    }
+    Ok(())
+}
+
+fn row(p: &PeriodStats) -> String {
+    let known = |v: Option<i64>| v.map_or_else(|| "—".to_string(), |n| n.to_string());
+    format!(
+        "{:<23} {:>8} {:>5} {:>10} {:>5} {:>5} {:>7} {:>7}",
+        p.label,
+        p.books_finished,
+        p.activity_days,
+        p.highlights,
+        p.notes,
+        p.links,
+        known(p.pages),
+        known(p.minutes)
+    )
+}
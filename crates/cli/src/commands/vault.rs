@@ -0,0 +1,102 @@
+//! `vault reindex`, `vault adopt`.
+//!
+//! Disaster recovery: the vault is the notes' home and the database an index
+//! of it, so a lost `database/` is rebuilt from the files. Run it after the
+//! library is back — a book a note names has to exist before the note can be
+//! filed under it — and again after anything else comes back.
+//!
+//! Adoption is the other way in: a reader's own Obsidian vault, copied into
+//! ours once they have seen which book each file would be filed under.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use readingbuddy::Engine;
+
+use super::resolve_one;
+use crate::commands::ko::print_candidates;
+use crate::prompt;
+
+pub async fn reindex(engine: &Engine, dry_run: bool) -> Result<()> {
+    let report = engine.reindex_vault(dry_run).await?;
+    let mode = if dry_run { " (dry run)" } else { "" };
+
+    for d in &report.detached {
+        eprintln!("warning: {}: {}", d.path.display(), d.reason);
+    }
+    for u in &report.unplaced {
+        println!("unplaced{mode}: {} — {}", u.path.display(), u.reason);
+    }
+    println!(
+        "{} files: {} notes rebuilt{mode} ({} on their books), {} already indexed, {} citations, {} unplaced",
+        report.files,
+        report.indexed,
+        report.on_books,
+        report.kept,
+        report.citations,
+        report.unplaced.len()
+    );
+    if !report.unplaced.is_empty() {
+        println!("    bring their books back (an import, `ko sync`), then run this again");
+    }
+    Ok(())
+}
+
+pub async fn adopt(
+    engine: &Engine,
+    root: &Path,
+    books: &[String],
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let mut plan = engine.plan_vault_adoption(root).await?;
+    for assignment in books {
+        let Some((file, selector)) = assignment.rsplit_once('=') else {
+            bail!("--book takes FILE=BOOK, not '{assignment}'");
+        };
+        let proposal = plan
+            .files
+            .iter_mut()
+            .find(|p| p.path == Path::new(file))
+            .with_context(|| format!("{file} is not a markdown file in {}", root.display()))?;
+        if selector == "-" {
+            proposal.choose(None);
+        } else {
+            proposal.choose(Some(&resolve_one(engine, selector).await?));
+        }
+    }
+
+    for p in &plan.files {
+        match (&p.book_title, p.matched_by) {
+            (Some(title), Some(how)) => println!("{} → {title} (by {how})", p.path.display()),
+            _ => println!("{} → no book", p.path.display()),
+        }
+        print_candidates(&p.candidates);
+    }
+    let filed = plan.files.iter().filter(|p| p.book_id.is_some()).count();
+    println!(
+        "{} files: {filed} on a book, {} as free notes",
+        plan.files.len(),
+        plan.files.len() - filed
+    );
+    if dry_run || plan.files.is_empty() {
+        return Ok(());
+    }
+    if !yes && !prompt::confirm(&format!("copy {} files into the vault?", plan.files.len()))? {
+        println!("cancelled.");
+        return Ok(());
+    }
+
+    let report = engine.adopt_vault(&plan).await?;
+    for w in &report.warnings {
+        eprintln!("warning: {w}");
+    }
+    println!(
+        "{} notes adopted, {} already were; {} links resolved, {} waiting for their note",
+        report.adopted.len(),
+        report.already,
+        report.links_resolved,
+        report.links_dangling
+    );
+    Ok(())
+}
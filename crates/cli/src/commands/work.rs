@@ -0,0 +1,69 @@
+//! `work`: a book's editions, and grouping them.
+
+use anyhow::{Result, bail};
+use readingbuddy::Engine;
+
+use super::resolve_one;
+use crate::render;
+
+/// Attach `selector` to `edition_of`'s work, detach it from its own, or —
+/// with neither — show every edition and what was read of them.
+pub async fn run(
+    engine: &Engine,
+    selector: &str,
+    edition_of: Option<&str>,
+    detach: bool,
+) -> Result<()> {
+    let book = resolve_one(engine, selector).await?;
+    let Some(id) = book.id else {
+        bail!("{} is not saved", book.display_title());
+    };
+
+    if detach {
+        if engine.detach_edition(id).await? {
+            println!("{} is a book of its own again", book.display_title());
+        } else {
+            println!("{} was not grouped with anything", book.display_title());
+        }
+        return Ok(());
+    }
+    if let Some(other) = edition_of {
+        let to = resolve_one(engine, other).await?;
+        let to_id = to.id.expect("stored book has id");
+        let work = engine.attach_edition(id, to_id).await?;
+        println!(
+            "{} is now one of {} edition(s) of {}",
+            book.display_title(),
+            work.editions.len(),
+            work.title
+        );
+        return Ok(());
+    }
+
+    let history = engine.work_history(id).await?;
+    match &history.work {
+        Some(w) => {
+            let key = w
+                .openlibrary_key
+                .as_deref()
+                .map(|k| format!("  [{k}]"))
+                .unwrap_or_default();
+            println!("{} — {} edition(s){key}", w.title, history.editions.len());
+        }
+        None => println!(
+            "{} — no other editions (`work {id} --edition-of <book>` to add one)",
+            book.display_title()
+        ),
+    }
+    for e in &history.editions {
+        println!("  {}", render::book_line(e));
+    }
+    match history.read_as() {
+        Some(b) => println!("read, as {}", b.display_title()),
+        None => println!("not read in any edition"),
+    }
+    if let Some(r) = &history.rating {
+        println!("rated {} on the '{}' scale", r.value, r.scale.name);
+    }
+    Ok(())
+}
@@ -51,7 +51,8 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Cmd {
-    /// Search OpenLibrary + Google Books (fielded, merged, ranked)
+    /// Search OpenLibrary + Google Books (fielded, merged, ranked), or with
+    /// --library your own notes and highlights
     Search(commands::search::SearchArgs),
     /// Add a book directly by ISBN
     Add {
@@ -62,16 +63,23 @@ enum Cmd {
     },
     /// Import a local .epub (ISBN lookup + embedded cover)
     Epub { path: PathBuf },
-    /// List the library
-    List {
-        #[arg(long, default_value_t = 20)]
-        limit: i64,
-        /// last-modified | title | progress
-        #[arg(long, default_value = "last-modified")]
-        sort: String,
-    },
+    /// List the library, or the part of it a filter picks out
+    List(commands::book::ListArgs),
     /// Show one book (selector: id, ISBN, or title fragment)
     Show { book: String },
+    /// Every book by one author, with what was highlighted and noted in each
+    Author {
+        /// The author's name, or enough of it to pick out one person
+        name: String,
+        /// published | read
+        #[arg(long, default_value = "published")]
+        by: String,
+    },
+    /// Every series, or one series in order with each book's reading state
+    Series {
+        /// The series' name; omit it to list every series
+        name: Option<String>,
+    },
     /// Remove a book and its cover image
     Rm {
         book: String,
@@ -116,6 +124,10 @@ enum Cmd {
         /// Anchor to an existing highlight by id
         #[arg(long)]
         highlight: Option<i64>,
+        /// Start from this template in the data root's `templates/` (without
+        /// --template, a note composed in $EDITOR starts from `<kind>.md`)
+        #[arg(long)]
+        template: Option<String>,
     },
     /// List notes, or full-text search them
     Notes {
@@ -130,14 +142,23 @@ enum Cmd {
         /// Note selector: id, or part of its title
         note: String,
     },
+    /// The markdown vault the notes live in
+    Vault {
+        #[command(subcommand)]
+        cmd: VaultCmd,
+    },
     /// Open this reading's reflection — private, and it accretes as you read
     Reflect(ReflectArgs),
     /// Open this reading's review — public prose, and the rating lives here
     Review(ReflectArgs),
-    /// Cite a highlight from a note (omit the highlight to list what it cites)
+    /// Cite a highlight from a note (omit the highlight to list what it cites),
+    /// or `cite export` a book or a passage for writing elsewhere
+    #[command(args_conflicts_with_subcommands = true)]
     Cite {
+        #[command(subcommand)]
+        cmd: Option<CiteCmd>,
         /// Note id (see `notes`)
-        note: i64,
+        note: Option<i64>,
         /// Highlight id (see `cite <note>` or `highlights`)
         highlight: Option<i64>,
     },
@@ -158,11 +179,31 @@ enum Cmd {
         #[arg(long)]
         yes: bool,
     },
+    /// Your shelves: manual ones in your order, smart ones kept by a rule
+    Shelf {
+        #[command(subcommand)]
+        cmd: ShelfCmd,
+    },
+    /// A book's other editions: show them, or group and ungroup (nothing moves)
+    Work {
+        book: String,
+        /// Make BOOK another edition of this one's work
+        #[arg(long, value_name = "BOOK")]
+        edition_of: Option<String>,
+        /// Take BOOK out of its work
+        #[arg(long, conflicts_with = "edition_of")]
+        detach: bool,
+    },
     /// Goodreads CSV, in and out (their API is dead; the file is the interface)
     Goodreads {
         #[command(subcommand)]
         cmd: GoodreadsCmd,
     },
+    /// Kindle highlights and notes, from its My Clippings.txt
+    Kindle {
+        #[command(subcommand)]
+        cmd: KindleCmd,
+    },
     /// Calibre, if you have it: format conversion and library import
     Calibre {
         #[command(subcommand)]
@@ -173,7 +214,37 @@ enum Cmd {
         #[command(subcommand)]
         cmd: KoCmd,
     },
-    /// Flashcards captured from single-word highlights
+    /// A stock Kobo's highlights and reading state, from its own database
+    Kobo {
+        #[command(subcommand)]
+        cmd: KoboCmd,
+    },
+    /// Reading statistics per period: finished, active days, highlights,
+    /// notes, links, pages and minutes where known
+    Stats {
+        /// The calendar year to report (default: this one)
+        #[arg(long)]
+        year: Option<i32>,
+        /// First day, YYYY-MM-DD (overrides --year's start)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day, YYYY-MM-DD, inclusive (overrides --year's end)
+        #[arg(long)]
+        to: Option<String>,
+        /// day | month | year | all
+        #[arg(long, default_value = "month")]
+        by: String,
+        /// Only this book (id, ISBN, or title fragment)
+        #[arg(long)]
+        book: Option<String>,
+    },
+    /// Publish the library as a static site: shelf, books, authors, tags and
+    /// the reviews marked public
+    Site {
+        #[command(subcommand)]
+        cmd: SiteCmd,
+    },
+    /// Flashcards captured from single-word highlights, and reviewing them
     Cards {
         #[command(subcommand)]
         cmd: CardsCmd,
@@ -203,6 +274,20 @@ struct ReflectArgs {
     /// Rating on the active scale (`review` only)
     #[arg(long)]
     rating: Option<f64>,
+    /// Write it out as a page to publish: `html` (one self-contained file) or
+    /// `md` (`review` only)
+    #[arg(long, value_name = "FORMAT")]
+    export: Option<String>,
+    /// Where the export goes (default: stdout). Without `--export`, the
+    /// extension picks the format
+    #[arg(long, value_name = "FILE")]
+    out: Option<PathBuf>,
+    /// Put it on the site `site build` renders (`review` only)
+    #[arg(long, conflicts_with = "unpublish")]
+    publish: bool,
+    /// Take it back off the site (`review` only)
+    #[arg(long)]
+    unpublish: bool,
 }
 
 impl<'a> From<&'a ReflectArgs> for commands::reflect::ReflectOpts<'a> {
@@ -213,10 +298,77 @@ impl<'a> From<&'a ReflectArgs> for commands::reflect::ReflectOpts<'a> {
             show: a.show,
             no_edit: a.no_edit,
             rating: a.rating,
+            export: a.export.as_deref(),
+            out: a.out.as_deref(),
+            publish: match (a.publish, a.unpublish) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
         }
     }
 }
 
+#[derive(Subcommand)]
+enum VaultCmd {
+    /// Rebuild notes, links and citations from the vault's markdown, for a lost
+    /// or damaged database. Notes already indexed are kept
+    Reindex {
+        /// Report what would be rebuilt, and what could not be placed, without
+        /// writing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Copy an existing Obsidian vault in, filing each note under the book its
+    /// frontmatter or folders name. The other vault is only read
+    Adopt {
+        /// The Obsidian vault's folder
+        path: PathBuf,
+        /// File a note under this book instead: `Reading/Han.md=pachinko`,
+        /// or `FILE=-` for no book. Repeatable
+        #[arg(long = "book", value_name = "FILE=BOOK")]
+        books: Vec<String>,
+        /// Show what would be filed where, without copying
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CiteCmd {
+    /// Reference entries for books (all of them when none are named), or with
+    /// --highlight a quotation with its page and edition
+    Export {
+        /// Book selectors: id, ISBN, or title fragment
+        books: Vec<String>,
+        /// bibtex | csl-json | ris
+        #[arg(long, default_value = "bibtex")]
+        format: String,
+        /// Quote this highlight instead (see `highlights`)
+        #[arg(long, conflicts_with_all = ["books", "format"])]
+        highlight: Option<i64>,
+        /// chicago | mla | apa, for --highlight
+        #[arg(long, default_value = "chicago", requires = "highlight")]
+        style: String,
+        /// Write to this file instead of stdout
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SiteCmd {
+    /// Render the site into a directory. Files are written over, never
+    /// removed
+    Build {
+        /// Where the site goes (created if missing)
+        out_dir: PathBuf,
+    },
+}
+
 #[derive(Subcommand)]
 enum RatingCmd {
     /// Define (or redefine) a numeric scale
@@ -246,6 +398,56 @@ enum RatingCmd {
     },
 }
 
+#[derive(Subcommand)]
+enum ShelfCmd {
+    /// Every shelf, with how many books are on it
+    List,
+    /// One shelf's books, in its order
+    Show { name: String },
+    /// Make a shelf — smart when any condition is given, manual otherwise
+    New {
+        name: String,
+        /// unstarted | reading | finished | abandoned
+        #[arg(long)]
+        status: Option<String>,
+        /// A Goodreads or calibre shelf
+        #[arg(long)]
+        tag: Option<String>,
+        /// Part of an author's name
+        #[arg(long)]
+        author: Option<String>,
+        /// Rated at least this many stars (0–5, through the Goodreads map)
+        #[arg(long)]
+        stars: Option<u8>,
+        /// Finished in this year
+        #[arg(long, value_name = "YEAR")]
+        finished_in: Option<i64>,
+        /// true | false
+        #[arg(long)]
+        has_notes: Option<bool>,
+    },
+    /// Delete a shelf; its books stay in the library
+    Delete { name: String },
+    /// Put a book on a manual shelf, or move it if it is already there
+    Add {
+        name: String,
+        book: String,
+        /// Position, from 1 (default: the end)
+        #[arg(long)]
+        at: Option<i64>,
+    },
+    /// Take a book off a manual shelf
+    Remove { name: String, book: String },
+    /// Keep imported Goodreads shelves or calibre tags as shelves of your own
+    /// (no tags: list what the imports recorded)
+    Promote {
+        tags: Vec<String>,
+        /// Only this source's tags: goodreads | calibre
+        #[arg(long)]
+        source: Option<String>,
+    },
+}
+
 #[derive(Subcommand)]
 enum GoodreadsCmd {
     /// Import a Goodreads export (My Books > Import and export > Export)
@@ -266,6 +468,20 @@ enum GoodreadsCmd {
     },
 }
 
+#[derive(Subcommand)]
+enum KindleCmd {
+    /// Import My Clippings.txt (the file, or a mounted Kindle)
+    Import {
+        path: PathBuf,
+        /// Report what would change without writing
+        #[arg(long)]
+        dry_run: bool,
+        /// Create a book even for a title that looks like one you already have
+        #[arg(long)]
+        new: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum CalibreCmd {
     /// Say which calibre tools are here, and what they enable
@@ -292,6 +508,17 @@ enum CalibreCmd {
     },
 }
 
+#[derive(Subcommand)]
+enum KoboCmd {
+    /// Import from KoboReader.sqlite (the file, or a mounted Kobo)
+    Import {
+        path: PathBuf,
+        /// Report what would be imported without writing
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum KoCmd {
     /// Import highlights/notes from a sidecar file, .sdr dir, or library root
@@ -316,13 +543,36 @@ enum KoCmd {
         /// Book selector: id, ISBN, or title fragment
         book: String,
     },
+    /// Write your annotations into a sidecar's notes, so they are on the page
+    /// next time. Refused if the device changed it since the last pull
+    Push {
+        /// A sidecar file or its .sdr directory
+        path: PathBuf,
+        /// Show the notes that would change without writing
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Show the state of every book on a mounted reader. Read-only
     Scan {
         /// The mount to scan. Omitted, a mounted KOReader device is looked for
         path: Option<PathBuf>,
     },
+    /// Import time and pages read from KOReader's statistics database
+    Stats {
+        /// A mount, a KOReader install, or the statistics.sqlite3 itself.
+        /// Omitted, a mounted KOReader device is looked for
+        path: Option<PathBuf>,
+        /// Report what would be imported without writing
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Wait for a reader to be plugged in and scan it. Read-only, ctrl-c to stop
     Watch,
+    /// Install, check or remove readingbuddy's own KOReader plugin
+    Plugin {
+        #[command(subcommand)]
+        cmd: PluginCmd,
+    },
     /// Pull books in from a mounted reader
     Sync {
         /// The mount to sync from
@@ -336,6 +586,25 @@ enum KoCmd {
     },
 }
 
+#[derive(Subcommand)]
+enum PluginCmd {
+    /// Install the plugin on a mounted reader, or upgrade an older one
+    Install {
+        /// The mount. Omitted, a mounted KOReader device is looked for
+        path: Option<PathBuf>,
+    },
+    /// Say whether the plugin is there, and which version
+    Status {
+        /// The mount. Omitted, a mounted KOReader device is looked for
+        path: Option<PathBuf>,
+    },
+    /// Remove exactly the files the install wrote
+    Remove {
+        /// The mount. Omitted, a mounted KOReader device is looked for
+        path: Option<PathBuf>,
+    },
+}
+
 #[derive(Subcommand)]
 enum CardsCmd {
     /// List flashcard candidates
@@ -344,15 +613,32 @@ enum CardsCmd {
         #[arg(long)]
         all: bool,
     },
-    /// Export as Anki-importable TSV
+    /// Export for Anki: TSV, or a whole .apkg with studied highlights as
+    /// clozes and the covers
     Export {
-        /// Output file (default: anki.tsv)
+        /// Output file (default: anki.tsv); a .apkg name writes a package
         #[arg(long, default_value = "anki.tsv")]
         out: PathBuf,
+        /// tsv | apkg (default: from the --out extension)
+        #[arg(long)]
+        format: Option<String>,
         /// Re-export cards already marked exported
         #[arg(long)]
         all: bool,
     },
+    /// Review the cards that are due, spaced-repetition style
+    Review {
+        /// At most this many cards in one sitting
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Add a highlight to the review deck (its id is in `highlights`)
+    Study {
+        highlight: i64,
+        /// Take it out of the deck instead, forgetting its schedule
+        #[arg(long)]
+        remove: bool,
+    },
 }
 
 #[tokio::main]
@@ -391,8 +677,10 @@ async fn main() -> Result<()> {
         Cmd::Search(args) => commands::search::run(&engine, args).await?,
         Cmd::Add { isbn, no_cover } => commands::book::add_isbn(&engine, &isbn, no_cover).await?,
         Cmd::Epub { path } => commands::book::import_epub(&engine, &path).await?,
-        Cmd::List { limit, sort } => commands::book::list(&engine, limit, &sort).await?,
+        Cmd::List(args) => commands::book::list(&engine, &args).await?,
         Cmd::Show { book } => commands::book::show(&engine, &book).await?,
+        Cmd::Author { name, by } => commands::author::run(&engine, &name, &by).await?,
+        Cmd::Series { name } => commands::series::run(&engine, name.as_deref()).await?,
         Cmd::Rm { book, yes } => commands::book::remove(&engine, &book, yes).await?,
         Cmd::Progress {
             book,
@@ -409,6 +697,7 @@ async fn main() -> Result<()> {
             no_page,
             location,
             highlight,
+            template,
         } => {
             commands::note::create(
                 &engine,
@@ -421,6 +710,7 @@ async fn main() -> Result<()> {
                     no_page,
                     location,
                     highlight,
+                    template,
                 },
             )
             .await?
@@ -429,9 +719,48 @@ async fn main() -> Result<()> {
             commands::note::list_or_search(&engine, book.as_deref(), search.as_deref()).await?
         }
         Cmd::Links { note } => commands::note::links(&engine, &note).await?,
+        Cmd::Vault { cmd } => match cmd {
+            VaultCmd::Reindex { dry_run } => commands::vault::reindex(&engine, dry_run).await?,
+            VaultCmd::Adopt {
+                path,
+                books,
+                dry_run,
+                yes,
+            } => commands::vault::adopt(&engine, &path, &books, dry_run, yes).await?,
+        },
+        Cmd::Site { cmd } => match cmd {
+            SiteCmd::Build { out_dir } => commands::site::build(&engine, &out_dir).await?,
+        },
         Cmd::Reflect(args) => commands::reflect::reflect(&engine, (&args).into()).await?,
         Cmd::Review(args) => commands::reflect::review(&engine, (&args).into()).await?,
-        Cmd::Cite { note, highlight } => commands::reflect::cite(&engine, note, highlight).await?,
+        Cmd::Cite {
+            cmd:
+                Some(CiteCmd::Export {
+                    books,
+                    format,
+                    highlight,
+                    style,
+                    out,
+                }),
+            ..
+        } => {
+            let opts = commands::cite::ExportOpts {
+                books: &books,
+                format: &format,
+                highlight,
+                style: &style,
+                out: out.as_deref(),
+            };
+            commands::cite::export(&engine, opts).await?
+        }
+        Cmd::Cite {
+            note: Some(note),
+            highlight,
+            ..
+        } => commands::reflect::cite(&engine, note, highlight).await?,
+        Cmd::Cite { .. } => {
+            anyhow::bail!("cite what? `cite <note> [highlight]`, or `cite export [book]...`")
+        }
         Cmd::Rating { cmd } => match cmd {
             RatingCmd::Scale {
                 min,
@@ -448,12 +777,55 @@ async fn main() -> Result<()> {
         },
         Cmd::Highlights { book } => commands::book::highlights(&engine, &book).await?,
         Cmd::Merge { src, dst, yes } => commands::book::merge(&engine, &src, &dst, yes).await?,
+        Cmd::Shelf { cmd } => match cmd {
+            ShelfCmd::List => commands::shelf::list(&engine).await?,
+            ShelfCmd::Show { name } => commands::shelf::show(&engine, &name).await?,
+            ShelfCmd::New {
+                name,
+                status,
+                tag,
+                author,
+                stars,
+                finished_in,
+                has_notes,
+            } => {
+                let rule = commands::shelf::RuleOpts {
+                    status: status.as_deref(),
+                    tag,
+                    author,
+                    stars,
+                    finished_in,
+                    has_notes,
+                };
+                commands::shelf::create(&engine, &name, rule).await?
+            }
+            ShelfCmd::Delete { name } => commands::shelf::delete(&engine, &name).await?,
+            ShelfCmd::Add { name, book, at } => {
+                commands::shelf::add(&engine, &name, &book, at).await?
+            }
+            ShelfCmd::Remove { name, book } => {
+                commands::shelf::remove(&engine, &name, &book).await?
+            }
+            ShelfCmd::Promote { tags, source } => {
+                commands::shelf::promote(&engine, source.as_deref(), &tags).await?
+            }
+        },
+        Cmd::Work {
+            book,
+            edition_of,
+            detach,
+        } => commands::work::run(&engine, &book, edition_of.as_deref(), detach).await?,
         Cmd::Goodreads { cmd } => match cmd {
             GoodreadsCmd::Import { path, dry_run, new } => {
                 commands::goodreads::import(&engine, &path, dry_run, new).await?
             }
             GoodreadsCmd::Export { out } => commands::goodreads::export(&engine, &out).await?,
         },
+        Cmd::Kindle { cmd } => match cmd {
+            KindleCmd::Import { path, dry_run, new } => {
+                commands::kindle::import(&engine, &path, dry_run, new).await?
+            }
+        },
         Cmd::Calibre { cmd } => match cmd {
             CalibreCmd::Status => commands::calibre::status(&engine).await?,
             CalibreCmd::Convert {
@@ -467,21 +839,66 @@ async fn main() -> Result<()> {
                 new,
             } => commands::calibre::import(&engine, library, dry_run, new).await?,
         },
+        Cmd::Kobo { cmd } => match cmd {
+            KoboCmd::Import { path, dry_run } => {
+                commands::kobo::import(&engine, &path, dry_run).await?
+            }
+        },
         Cmd::Ko { cmd } => match cmd {
             KoCmd::Import { path, dry_run } => {
                 commands::ko::import(&engine, &path, dry_run).await?
             }
             KoCmd::Pull { path, new } => commands::ko::pull(&engine, &path, new).await?,
             KoCmd::Link { path, book } => commands::ko::link(&engine, &path, &book).await?,
+            KoCmd::Push { path, dry_run } => commands::ko::push(&engine, &path, dry_run).await?,
             KoCmd::Scan { path } => commands::ko::scan(&engine, path.as_deref()).await?,
+            KoCmd::Stats { path, dry_run } => {
+                commands::ko::stats(&engine, path.as_deref(), dry_run).await?
+            }
             KoCmd::Watch => commands::ko::watch(&engine).await?,
+            KoCmd::Plugin { cmd } => match cmd {
+                PluginCmd::Install { path } => {
+                    commands::ko::plugin_install(&engine, path.as_deref())?
+                }
+                PluginCmd::Status { path } => {
+                    commands::ko::plugin_status(&engine, path.as_deref())?
+                }
+                PluginCmd::Remove { path } => {
+                    commands::ko::plugin_remove(&engine, path.as_deref())?
+                }
+            },
             KoCmd::Sync { path, all, books } => {
                 commands::ko::sync(&engine, &path, all, &books).await?
             }
         },
+        Cmd::Stats {
+            year,
+            from,
+            to,
+            by,
+            book,
+        } => {
+            commands::stats::run(
+                &engine,
+                commands::stats::StatsOpts {
+                    year,
+                    from: from.as_deref(),
+                    to: to.as_deref(),
+                    by: &by,
+                    book_selector: book.as_deref(),
+                },
+            )
+            .await?
+        }
         Cmd::Cards { cmd } => match cmd {
             CardsCmd::List { all } => commands::cards::list(&engine, all).await?,
-            CardsCmd::Export { out, all } => commands::cards::export(&engine, &out, all).await?,
+            CardsCmd::Export { out, format, all } => {
+                commands::cards::export(&engine, &out, format.as_deref(), all).await?
+            }
+            CardsCmd::Review { limit } => commands::cards::review(&engine, limit).await?,
+            CardsCmd::Study { highlight, remove } => {
+                commands::cards::study(&engine, highlight, remove).await?
+            }
         },
         Cmd::Repl => repl::run(&engine).await?,
         Cmd::Config { .. } => unreachable!("handled before engine startup"),
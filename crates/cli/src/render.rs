@@ -1,4 +1,4 @@
-use readingbuddy::{Book, RankedResult, Reading};
+use readingbuddy::{Book, RankedResult, Reading, series};
 
 /// One reading of a book: which of how many, its dates, and where it got to.
 ///
@@ -59,13 +59,25 @@ pub fn book_line(b: &Book) -> String {
         (Some(p), None) => format!("  [p.{p}]"),
         _ => String::new(),
     };
+    let series = series_label(b)
+        .map(|s| format!("  · {s}"))
+        .unwrap_or_default();
     format!(
-        "#{id}  {} — {}{year}{progress}",
+        "#{id}  {} — {}{year}{series}{progress}",
         b.display_title(),
         b.display_authors()
     )
 }
 
+/// `Mistborn #1`, or just the name when the place in it is unknown.
+pub fn series_label(b: &Book) -> Option<String> {
+    let name = b.series.as_deref()?;
+    Some(match b.series_index {
+        Some(i) => format!("{name} {}", series::format_index(i)),
+        None => name.to_string(),
+    })
+}
+
 pub fn book_details(b: &Book) -> String {
     let mut out = String::new();
     let mut push = |label: &str, val: Option<String>| {
@@ -86,6 +98,7 @@ pub fn book_details(b: &Book) -> String {
     push("publisher", b.publisher.clone());
     push("year", b.publish_year.map(|y| y.to_string()));
     push("language", b.language.clone());
+    push("series", series_label(b));
     push("isbn-10", b.isbn_10.clone());
     push("isbn-13", b.isbn_13.clone());
     push("pages", b.page_count.map(|p| p.to_string()));
@@ -20,7 +20,7 @@ pub async fn run(engine: &Engine) -> Result<()> {
         let result = match input.as_str() {
             "s" => search(engine).await,
             "r" => epub(engine).await,
-            "d" => commands::book::list(engine, 20, "last-modified").await,
+            "d" => commands::book::list(engine, &Default::default()).await,
             "n" => note(engine).await,
             "k" => koreader(engine).await,
             "rd" => remove(engine).await,
@@ -58,6 +58,7 @@ async fn search(engine: &Engine) -> Result<()> {
         pick: None,
         no_save: false,
         no_cover: false,
+        library: false,
     };
     commands::search::run(engine, args).await
 }
@@ -84,6 +85,7 @@ async fn note(engine: &Engine) -> Result<()> {
             no_page: false,
             location: None,
             highlight: None,
+            template: None,
         },
     )
     .await
@@ -22,6 +22,11 @@ const SYNTHETIC: &str = concat!(
     "/../engine/tests/fixtures/koreader/synthetic"
 );
 
+const CLIPPINGS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../engine/tests/fixtures/kindle/recorded/My Clippings.txt"
+);
+
 /// A sandboxed invocation of the real binary.
 struct Cli {
     root: tempfile::TempDir,
@@ -205,6 +210,7 @@ fn the_subcommand_set_is_what_we_decided() {
 
     let expected = [
         "add",
+        "author",
         "calibre",
         "cards",
         "cite",
@@ -213,7 +219,9 @@ fn the_subcommand_set_is_what_we_decided() {
         "goodreads",
         "help",
         "highlights",
+        "kindle",
         "ko",
+        "kobo",
         "links",
         "list",
         "merge",
@@ -226,7 +234,13 @@ fn the_subcommand_set_is_what_we_decided() {
         "review",
         "rm",
         "search",
+        "series",
+        "shelf",
         "show",
+        "site",
+        "stats",
+        "vault",
+        "work",
     ];
     assert_eq!(
         found, expected,
@@ -298,6 +312,36 @@ fn pull_then_progress_then_show_agree_about_the_same_book() {
     cli.run(&["show", &id]).has("120").has("A Rated Book");
 }
 
+/// `search --library` searches what we hold, offline, and names the book a
+/// highlight came from — the one thing its snippet cannot say.
+///
+/// The provider flags are refused alongside it rather than ignored: `--isbn`
+/// with `--library` is a question this search cannot answer, and silently
+/// answering a different one is worse than saying so.
+#[test]
+fn library_search_finds_notes_and_highlights_without_the_network() {
+    let cli = Cli::new();
+    let device = cli.root.path().join("device");
+    let sidecar = place(&device, "Gen-Summary.sdr");
+    cli.run(&["ko", "pull", sidecar.to_str().unwrap()]);
+    cli.run(&[
+        "note",
+        "The passage about endings, again.",
+        "--title",
+        "Endings",
+    ]);
+
+    cli.run(&["search", "--library", "passage"])
+        .has("highlight")
+        .has("A Rated Book")
+        .has(">>passage<<")
+        .has("note")
+        .has("Endings");
+
+    let mixed = cli.try_run(&["search", "--library", "passage", "--isbn", "9780000000000"]);
+    assert!(!mixed.ok, "--library with a provider flag must not run");
+}
+
 /// A confirmation prompt reading EOF must decline.
 ///
 /// This is the one that would actually hurt. `prompt::confirm` treats anything
@@ -354,6 +398,228 @@ fn reflect_show_creates_neither_a_note_nor_a_reading() {
     cli.run(&["show", &id]).has("reading 1/1");
 }
 
+/// `review --export` only reads: there is nothing to publish before the review
+/// is written, and a reflection is not for publishing at all.
+#[test]
+fn review_export_writes_a_page_and_refuses_a_reflection() {
+    let cli = Cli::new();
+    let device = cli.root.path().join("device");
+    let sidecar = place(&device, "Unmatched.sdr");
+    cli.run(&["ko", "pull", sidecar.to_str().unwrap()]);
+    let id = cli.run(&["list"]).book_id();
+
+    cli.try_run(&["review", &id, "--export", "html"])
+        .has("no review yet");
+    cli.try_run(&["reflect", &id, "--export", "md"])
+        .has("only a review exports");
+
+    cli.run(&["review", &id, "--no-edit", "--rating", "4"]);
+    cli.run(&["review", &id, "--export", "md"])
+        .has("**Rating:** 4 / 5");
+
+    let page = cli.root.path().join("review.html");
+    cli.run(&["review", &id, "--out", page.to_str().unwrap()])
+        .has("(html)");
+    let html = std::fs::read_to_string(&page).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"), "{html}");
+}
+
+/// `site build` publishes a review only once it is marked, and `--publish` on
+/// a reflection is refused rather than ignored.
+#[test]
+fn site_build_publishes_only_the_marked_review() {
+    let cli = Cli::new();
+    let device = cli.root.path().join("device");
+    let sidecar = place(&device, "Unmatched.sdr");
+    cli.run(&["ko", "pull", sidecar.to_str().unwrap()]);
+    let id = cli.run(&["list"]).book_id();
+    let site = cli.root.path().join("site");
+
+    cli.run(&["review", &id, "--no-edit", "--rating", "4"]);
+    cli.run(&["site", "build", site.to_str().unwrap()])
+        .has("1 books, 0 published reviews")
+        .has("--publish");
+
+    cli.try_run(&["reflect", &id, "--no-edit", "--publish"])
+        .has("never published");
+    cli.run(&["review", &id, "--no-edit", "--publish"])
+        .has("published");
+    cli.run(&["site", "build", site.to_str().unwrap()])
+        .has("1 published reviews");
+    let index = std::fs::read_to_string(site.join("index.html")).unwrap();
+    assert!(index.contains("href=\"books/"), "{index}");
+}
+
+/// `author` pools a person's books under part of their name, and an unknown
+/// name is an error rather than an empty report.
+#[test]
+fn author_reports_every_book_and_what_was_kept() {
+    let cli = Cli::new();
+    let device = cli.root.path().join("device");
+    let sidecar = place(&device, "Unmatched.sdr");
+    cli.run(&["ko", "pull", sidecar.to_str().unwrap()]);
+
+    cli.run(&["author", "writer", "--by", "read"])
+        .has("Ghost Writer — 1 book(s), 1 highlight(s)")
+        .has("Nonexistent Tome")
+        .has("“A passage from a book nobody added.”");
+    let bad = cli.try_run(&["author", "Toni Morrison"]);
+    assert!(!bad.ok);
+    bad.has("no book by an author called");
+    cli.try_run(&["author", "writer", "--by", "mood"])
+        .has("published or read");
+}
+
+/// A Goodreads title carries its series; the list filters and sorts on it and
+/// the series view says which book is next.
+#[test]
+fn series_come_in_from_goodreads_titles() {
+    let cli = Cli::new();
+    let csv = cli.root.path().join("goodreads.csv");
+    std::fs::write(
+        &csv,
+        "Title,Author,Date Read\n\
+         \"The Well of Ascension (Mistborn, #2)\",Brandon Sanderson,\n\
+         \"The Final Empire (Mistborn, #1)\",Brandon Sanderson,2020/01/05\n\
+         \"The Hero of Ages (Mistborn, #3)\",Brandon Sanderson,\n\
+         Pachinko,Min Jin Lee,\n",
+    )
+    .unwrap();
+    cli.run(&["goodreads", "import", csv.to_str().unwrap()]);
+
+    cli.run(&["series"]).has("Mistborn  1/3 finished");
+    let run = cli.run(&["series", "mistborn"]);
+    run.has("Mistborn — 3 book(s)");
+    let next = run
+        .stdout
+        .lines()
+        .find(|l| l.contains("← next"))
+        .expect("a next book");
+    assert!(next.contains("The Well of Ascension"), "{next}");
+
+    let listed = cli.run(&["list", "--series", "mist", "--sort", "series"]);
+    let titles: Vec<&str> = listed
+        .stdout
+        .lines()
+        .map(|l| l.split(" — ").next().unwrap())
+        .collect();
+    assert_eq!(titles.len(), 3, "{}", listed.stdout);
+    assert!(titles[0].ends_with("The Final Empire"), "{titles:?}");
+    assert!(titles[2].ends_with("The Hero of Ages"), "{titles:?}");
+    listed.has("Mistborn #2");
+
+    let bad = cli.try_run(&["series", "Discworld"]);
+    assert!(!bad.ok);
+    bad.has("no series called 'Discworld'");
+}
+
+/// Imported shelves stay tags until promoted; a shelf of our own keeps its
+/// order, and a smart one is a rule the listing filter answers.
+#[test]
+fn shelves_are_kept_in_order_and_promoted_from_tags() {
+    let cli = Cli::new();
+    let csv = cli.root.path().join("goodreads.csv");
+    std::fs::write(
+        &csv,
+        "Title,Author,Date Read,Bookshelves\n\
+         Pachinko,Min Jin Lee,2024/03/01,\"read, korean-lit\"\n\
+         Kindred,Octavia E. Butler,,to-read\n\
+         Human Acts,Han Kang,,\"to-read, korean-lit\"\n",
+    )
+    .unwrap();
+    cli.run(&["goodreads", "import", csv.to_str().unwrap()]);
+    cli.run(&["shelf", "list"]).has("no shelves yet");
+
+    cli.run(&["shelf", "promote"]).has("korean-lit");
+    cli.run(&["shelf", "promote", "korean-lit", "--source", "goodreads"])
+        .has("made 'korean-lit': 2 book(s) new");
+    cli.run(&["shelf", "promote", "korean-lit"])
+        .has("added to 'korean-lit': 0 book(s) new");
+
+    cli.run(&["shelf", "new", "Desk"]);
+    cli.run(&["shelf", "add", "desk", "Kindred"]);
+    cli.run(&["shelf", "add", "desk", "Pachinko", "--at", "1"])
+        .has("Pachinko is #1 on 'desk'");
+    let shown = cli.run(&["shelf", "show", "desk"]);
+    let pachinko = shown.stdout.find("Pachinko").unwrap();
+    let kindred = shown.stdout.find("Kindred").unwrap();
+    assert!(pachinko < kindred, "{}", shown.stdout);
+
+    cli.run(&["shelf", "new", "Done", "--status", "finished"])
+        .has("[smart: status finished]");
+    let listed = cli.run(&["list", "--shelf", "done"]);
+    listed.has("Pachinko");
+    assert!(!listed.stdout.contains("Kindred"), "{}", listed.stdout);
+    cli.run(&["list", "--shelf", "korean-lit", "--status", "unstarted"])
+        .has("Human Acts");
+
+    let bad = cli.try_run(&["shelf", "add", "done", "Kindred"]);
+    assert!(!bad.ok);
+    bad.has("smart shelf");
+}
+
+/// Two editions stay two books; grouping them is what lets reading one answer
+/// for the other.
+#[test]
+fn work_groups_editions_and_reading_one_counts_for_both() {
+    let cli = Cli::new();
+    let csv = cli.root.path().join("goodreads.csv");
+    std::fs::write(
+        &csv,
+        "Title,Author,Date Read\n\
+         Die Verwandlung,Franz Kafka,\n\
+         The Metamorphosis,Franz Kafka,2021/03/02\n",
+    )
+    .unwrap();
+    cli.run(&["goodreads", "import", csv.to_str().unwrap()]);
+
+    cli.run(&["work", "Verwandlung"]).has("no other editions");
+    cli.run(&["work", "Metamorphosis", "--edition-of", "Verwandlung"])
+        .has("is now one of 2 edition(s) of Die Verwandlung");
+    cli.run(&["work", "Verwandlung"])
+        .has("Die Verwandlung — 2 edition(s)")
+        .has("read, as The Metamorphosis");
+    cli.run(&["show", "Verwandlung"])
+        .has("editions")
+        .has("The Metamorphosis");
+    cli.run(&["list"])
+        .has("Die Verwandlung")
+        .has("The Metamorphosis");
+
+    cli.run(&["work", "Metamorphosis", "--detach"])
+        .has("a book of its own again");
+    cli.run(&["work", "Verwandlung"])
+        .has("no other editions")
+        .has("not read in any edition");
+}
+
+/// `cite export` is a subcommand beside `cite <note> <highlight>`, and the two
+/// must not swallow each other's arguments.
+#[test]
+fn cite_export_writes_entries_and_quotes_a_passage() {
+    let cli = Cli::new();
+    let device = cli.root.path().join("device");
+    let sidecar = place(&device, "Unmatched.sdr");
+    cli.run(&["ko", "pull", sidecar.to_str().unwrap()]);
+    let id = cli.run(&["list"]).book_id();
+
+    cli.run(&["cite", "export", &id]).has("@book{");
+    cli.run(&["cite", "export", "--format", "ris"])
+        .has("TY  - BOOK")
+        .has("ER  -");
+    let bad = cli.try_run(&["cite", "export", "--format", "endnote"]);
+    assert!(!bad.ok);
+    bad.has("bibtex, csl-json or ris");
+
+    // The fixture's highlights are the only ones, so the first is id 1.
+    cli.run(&["cite", "export", "--highlight", "1", "--style", "mla"])
+        .has("“");
+
+    // The old shape still parses as a note id.
+    cli.run(&["note", "A thought.", "--title", "Thought"]);
+    cli.run(&["cite", "1"]).has("cites nothing yet");
+}
+
 /// `links` reads the graph in both directions, and says so about the half that
 /// is not there yet.
 ///
@@ -396,6 +662,55 @@ fn links_reads_both_directions_and_prints_a_dangling_target_as_text() {
     missing.has("no note matches");
 }
 
+/// The vault outlives the database: delete `database/` and the notes, and the
+/// link between them, come back from the markdown.
+#[test]
+fn vault_reindex_brings_notes_back_after_the_database_is_lost() {
+    let cli = Cli::new();
+    cli.run(&["note", "Her whole life is [[Han]].", "--title", "Sunja"]);
+    cli.run(&["note", "Grief with no bottom.", "--title", "Han"]);
+    std::fs::remove_dir_all(cli.data_dir().join("database")).unwrap();
+
+    cli.run(&["vault", "reindex", "--dry-run"])
+        .has("2 files: 2 notes rebuilt (dry run)");
+    cli.run(&["notes"]).lacks("Sunja");
+    cli.run(&["vault", "reindex"]).has("0 unplaced");
+    cli.run(&["links", "Sunja"]).has("“Han”");
+}
+
+/// An Obsidian vault is shown filed before it is copied, a proposal can be
+/// overruled from the command line, and the copy lands in our vault.
+#[test]
+fn vault_adopt_previews_then_copies_an_obsidian_vault() {
+    let cli = Cli::new();
+    cli.run(&["kindle", "import", CLIPPINGS]);
+    let theirs = cli.root.path().join("obsidian");
+    std::fs::create_dir_all(theirs.join("Kindred")).unwrap();
+    std::fs::create_dir_all(theirs.join("Inbox")).unwrap();
+    std::fs::write(theirs.join("Kindred/Dana.md"), "Pulled back, again.\n").unwrap();
+    std::fs::write(theirs.join("Inbox/Rufus.md"), "See [[Dana]].\n").unwrap();
+    let root = theirs.to_str().unwrap();
+
+    cli.run(&["vault", "adopt", root, "--dry-run"])
+        .has("Kindred/Dana.md → Kindred (by folder)")
+        .has("Inbox/Rufus.md → no book")
+        .has("2 files: 1 on a book, 1 as free notes");
+    cli.run(&["notes"]).lacks("Dana");
+
+    cli.run(&[
+        "vault",
+        "adopt",
+        root,
+        "--book",
+        "Inbox/Rufus.md=Kindred",
+        "--yes",
+    ])
+    .has("Inbox/Rufus.md → Kindred (by chosen)")
+    .has("2 notes adopted, 0 already were; 1 links resolved");
+    assert!(cli.data_dir().join("vault/kindred/Rufus.md").exists());
+    cli.run(&["links", "Rufus"]).has("“Dana”");
+}
+
 /// Calibre is feature-detected off `PATH`, and the binary finds it there.
 ///
 /// The engine's own suite points `EngineConfig::calibre_bin_dir` at a directory
@@ -496,3 +811,127 @@ fn a_bad_selector_fails_loudly_and_an_underspecified_sync_writes_nothing() {
     vague.has("--all");
     cli.run(&["list"]).has("library is empty");
 }
+
+/// A filtered list says how much it left out, and a filter that matches
+/// nothing is not mistaken for an empty library.
+#[test]
+fn list_filters_and_says_how_many_it_left_out() {
+    let cli = Cli::new();
+    let device = cli.root.path().join("device");
+    let sidecar = place(&device, "Gen-Summary.sdr");
+    cli.run(&["ko", "pull", sidecar.to_str().unwrap()]);
+
+    cli.run(&["list", "--source", "koreader"])
+        .has("A Rated Book");
+    cli.run(&["list", "--status", "abandoned"])
+        .has("no books match");
+    cli.run(&["list", "--offset", "5"])
+        .has("nothing past 5 — 1 in all");
+    let bad = cli.try_run(&["list", "--status", "someday"]);
+    assert!(!bad.ok);
+    bad.has("unknown status");
+}
+
+/// The review loop with stdin at EOF: it shows the first card and where it is
+/// from, and stops at the grade rather than spinning — nothing is graded.
+#[test]
+fn cards_review_shows_what_is_due_and_a_highlight_can_join_the_deck() {
+    let cli = Cli::new();
+    cli.run(&["cards", "review"]).has("nothing due");
+    cli.run(&["kindle", "import", CLIPPINGS]);
+    cli.run(&["cards", "review"])
+        .has("Sehnsucht")
+        .has("Der Process — Franz Kafka · p.12")
+        .has("stopped — the rest stay due")
+        .lacks("My left arm.");
+
+    let listed = cli.run(&["highlights", "Kindred"]);
+    listed.lacks("[studying]");
+    let id = listed
+        .stdout
+        .lines()
+        .find(|l| l.contains("My left arm."))
+        .and_then(|l| l.trim().strip_prefix('#'))
+        .and_then(|l| l.split_whitespace().next())
+        .expect("highlights prints ids")
+        .to_string();
+    cli.run(&["cards", "study", &id]).has("now studying");
+    cli.run(&["cards", "study", &id]).has("already studying");
+    cli.run(&["highlights", "Kindred"]).has("[studying]");
+    // Opted in now, and due at once: the limit reaches it behind the words.
+    cli.run(&["cards", "review", "--limit", "3"])
+        .has("stopped — the rest stay due");
+    cli.run(&["cards", "study", &id, "--remove"])
+        .has("stopped studying");
+    assert!(!cli.try_run(&["cards", "study", "99999"]).ok);
+}
+
+#[test]
+fn cards_export_writes_a_package_when_the_file_name_asks_for_one() {
+    let cli = Cli::new();
+    cli.run(&["kindle", "import", CLIPPINGS]);
+    let deck = cli.root.path().join("deck.apkg");
+    let deck = deck.to_str().unwrap();
+    cli.run(&["cards", "export", "--out", deck]).has("(apkg;");
+    // A zip, with the collection Anki opens inside it.
+    let bytes = std::fs::read(deck).unwrap();
+    assert!(bytes.starts_with(b"PK"));
+    assert!(bytes.windows(16).any(|w| w == b"collection.anki2"));
+    cli.run(&["cards", "export", "--out", deck])
+        .has("nothing to export.");
+
+    let tsv = cli.root.path().join("deck.txt");
+    let tsv = tsv.to_str().unwrap();
+    cli.run(&["cards", "export", "--all", "--format", "tsv", "--out", tsv])
+        .has("(tsv;");
+    assert!(std::fs::read_to_string(tsv).unwrap().contains("Sehnsucht"));
+    assert!(!cli.try_run(&["cards", "export", "--format", "csv"]).ok);
+}
+
+#[test]
+fn kindle_import_previews_then_writes_the_edited_highlight_once() {
+    let cli = Cli::new();
+    let dry = cli.run(&["kindle", "import", CLIPPINGS, "--dry-run"]);
+    dry.has("Kindred (dry run): 1 new")
+        .has("4 books would be created")
+        .has("nothing was written");
+    assert!(dry.stderr.contains("clipping 5:"), "{}", dry.stderr);
+    cli.run(&["list"]).has("library is empty");
+
+    cli.run(&["kindle", "import", CLIPPINGS])
+        .has("Der Process: 2 new, 1 flashcard (matched by new)");
+    cli.run(&["highlights", "Kindred"]).has("My left arm.");
+    cli.run(&["kindle", "import", CLIPPINGS])
+        .has("Kindred: nothing new (matched by title)");
+}
+
+/// `ko plugin` lists every file it writes, refuses a volume that is not a
+/// KOReader install, and removes what it wrote.
+#[test]
+fn ko_plugin_installs_names_its_files_and_removes_them() {
+    let cli = Cli::new();
+    let plain = cli.root.path().join("usb-stick");
+    std::fs::create_dir_all(&plain).unwrap();
+    let refused = cli.try_run(&["ko", "plugin", "install", plain.to_str().unwrap()]);
+    assert!(!refused.ok);
+    refused.has("not a KOReader install");
+
+    let mount = cli.root.path().join("mount");
+    let ko = mount.join("koreader");
+    std::fs::create_dir_all(ko.join("frontend")).unwrap();
+    std::fs::create_dir_all(ko.join("plugins")).unwrap();
+    std::fs::write(ko.join("reader.lua"), "-- entry point\n").unwrap();
+    let mount = mount.to_str().unwrap();
+
+    cli.run(&["ko", "plugin", "status", mount])
+        .has("not installed");
+    cli.run(&["ko", "plugin", "install", mount])
+        .has("wrote   ")
+        .has("readingbuddy.koplugin/main.lua")
+        .has("installed version 1");
+    cli.run(&["ko", "plugin", "status", mount])
+        .has("up to date");
+    cli.run(&["ko", "plugin", "remove", mount])
+        .has("removed version 1");
+    assert!(!ko.join("plugins/readingbuddy.koplugin").exists());
+}
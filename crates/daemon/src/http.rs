@@ -0,0 +1,646 @@
+//! The reader's transport: one HTTP endpoint on the LAN, for a KOReader plugin
+//! that has no unix socket to talk to.
+//!
+//! The same rule as [`crate::server`] holds here, and harder: **nothing in
+//! this file names an API method.** What a reader may call, with a token or
+//! without one, is [`Api::call_from_device`]'s to decide; who a token belongs
+//! to is [`Api::authenticate_device`]'s. This file asks both questions and
+//! carries the answers.
+//!
+//! ## Why HTTP after all, and why by hand
+//!
+//! `server.rs` explains why the local transport is not HTTP, and all of it
+//! still holds for the local transport. A reader on the network is the case it
+//! did not cover: KOReader ships an HTTP client and a JSON encoder, and no way
+//! to hold a socket open and speak lines. So the reader gets HTTP — but only
+//! the slice of HTTP/1.1 that one `POST` with a `Content-Length` needs, which
+//! is a request line, some headers and a body. A server crate would be a
+//! router and a middleware stack around those three things.
+//!
+//! ```text
+//! curl -H "Authorization: Bearer $TOKEN" \
+//!   --data '{"id":1,"request":{"method":"pull_sidecar_payload","params":{…}}}' \
+//!   http://laptop.local:8765/v1/call
+//! ```
+//!
+//! The body is a [`Call`] and the answer is a [`Reply`], exactly as on the
+//! socket. The status line is for the HTTP client; the reply is for the caller.
+//! One request per connection, and `Connection: close` says so.
+
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use readingbuddy_api::{Api, ApiError, Call, ErrorCode, Outcome, Reply};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+
+use crate::server::{Line, MAX_LINE, read_line_capped};
+
+/// The one path there is. Versioned in the path rather than in a header
+/// because a plugin built against it names it once, in a string.
+pub const PATH: &str = "/v1/call";
+
+/// The longest request line or header line read before the connection is
+/// refused. Nothing a real client sends comes near it.
+const MAX_HEADER_LINE: usize = 8 * 1024;
+
+/// How many header lines before the request is refused. The same reason as
+/// [`MAX_HEADER_LINE`], for a client that sends many short ones.
+const MAX_HEADERS: usize = 64;
+
+/// The most a caller with no token may send. What it is allowed to send — a
+/// pairing code, a version check — fits many times over; a stranger on the
+/// LAN does not get to make us buffer [`MAX_LINE`].
+const MAX_UNPAIRED_BODY: usize = 4 * 1024;
+
+/// How many connections are served at once. A reader makes one at a time; a
+/// peer holding this many open is not a reader, and the next connection is
+/// closed unanswered rather than given a task and a buffer.
+const MAX_CONNECTIONS: usize = 64;
+
+/// How long a client has to send its request, once connected.
+///
+/// The caps above bound what a stranger can make us hold; these bound for how
+/// long. Without them a peer sending a byte a minute keeps its task and its
+/// buffer forever, and [`MAX_CONNECTIONS`] of those is the whole listener.
+#[derive(Debug, Clone, Copy)]
+struct Deadlines {
+    /// The request line and every header.
+    head: Duration,
+    /// The body, counted from the end of the head. Longer, because a paired
+    /// reader's sidecar may be megabytes over a weak wifi link.
+    body: Duration,
+}
+
+const DEADLINES: Deadlines = Deadlines {
+    head: Duration::from_secs(10),
+    body: Duration::from_secs(30),
+};
+
+/// Accept until `shutdown` resolves.
+///
+/// The shape of [`crate::server::serve`], for the same reason: a test binds
+/// `127.0.0.1:0` itself and posts to whatever port it got.
+pub async fn serve<S>(api: Api, listener: TcpListener, shutdown: S) -> anyhow::Result<()>
+where
+    S: std::future::Future<Output = ()>,
+{
+    serve_with(api, listener, shutdown, DEADLINES).await
+}
+
+/// [`serve`], with deadlines a test can make short.
+async fn serve_with<S>(
+    api: Api,
+    listener: TcpListener,
+    shutdown: S,
+    deadlines: Deadlines,
+) -> anyhow::Result<()>
+where
+    S: std::future::Future<Output = ()>,
+{
+    let api = Arc::new(api);
+    let slots = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            biased;
+            () = &mut shutdown => return Ok(()),
+            accepted = listener.accept() => {
+                match accepted {
+                    Ok((stream, peer)) => {
+                        let Ok(slot) = Arc::clone(&slots).try_acquire_owned() else {
+                            tracing::warn!(%peer, "too many device connections; closed one");
+                            continue;
+                        };
+                        let api = Arc::clone(&api);
+                        tokio::spawn(async move {
+                            if let Err(e) = handle(&api, stream, deadlines).await {
+                                tracing::warn!(%peer, error = %e, "device connection ended badly");
+                            }
+                            drop(slot);
+                        });
+                    }
+                    Err(e) => tracing::warn!(error = %e, "accept failed"),
+                }
+            }
+        }
+    }
+}
+
+/// A status line and the reply that explains it.
+struct Answer {
+    status: u16,
+    reason: &'static str,
+    reply: Reply,
+}
+
+impl Answer {
+    fn refuse(status: u16, reason: &'static str, error: ApiError) -> Answer {
+        Answer {
+            status,
+            reason,
+            reply: Reply::err(0, error),
+        }
+    }
+}
+
+/// One connection, one request, one answer.
+async fn handle(api: &Api, stream: TcpStream, deadlines: Deadlines) -> io::Result<()> {
+    let (rx, mut tx) = stream.into_split();
+    let mut reader = BufReader::new(rx);
+    let answer = match read_request(&mut reader, api, deadlines).await? {
+        Ok((device, body)) => match serde_json::from_slice::<Call>(&body) {
+            Ok(call) => {
+                let reply = api.call_from_device(device, call).await;
+                let (status, reason) = match &reply.outcome {
+                    Outcome::Error { error } if error.code == ErrorCode::Unauthorized => {
+                        match device {
+                            None => (401, "Unauthorized"),
+                            Some(_) => (403, "Forbidden"),
+                        }
+                    }
+                    _ => (200, "OK"),
+                };
+                Answer {
+                    status,
+                    reason,
+                    reply,
+                }
+            }
+            Err(e) => Answer::refuse(400, "Bad Request", ApiError::bad_request(e.to_string())),
+        },
+        Err(refused) => refused,
+    };
+    let body = answer.reply.to_line();
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        answer.status,
+        answer.reason,
+        body.len()
+    );
+    tx.write_all(head.as_bytes()).await?;
+    tx.write_all(body.as_bytes()).await?;
+    tx.flush().await?;
+    tx.shutdown().await
+}
+
+/// What the head of a request said, before anything is decided on it.
+struct Head {
+    method: String,
+    target: String,
+    length: Option<usize>,
+    bearer: Option<String>,
+    chunked: bool,
+}
+
+/// The request line and headers, checked in the order that costs a stranger
+/// least: path and method, then the token, and only then is the body read —
+/// all of it for a paired device, [`MAX_UNPAIRED_BODY`] for anyone else. Each
+/// half has its [`Deadlines`]; missing one is a 408.
+async fn read_request<R>(
+    reader: &mut R,
+    api: &Api,
+    deadlines: Deadlines,
+) -> io::Result<Result<(Option<i64>, Vec<u8>), Answer>>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    let too_slow = || {
+        Answer::refuse(
+            408,
+            "Request Timeout",
+            ApiError::bad_request("the request took too long to arrive"),
+        )
+    };
+    let head = match timeout(deadlines.head, read_head(reader)).await {
+        Ok(head) => head?,
+        Err(_) => return Ok(Err(too_slow())),
+    };
+    let Head {
+        method,
+        target,
+        length,
+        bearer,
+        chunked,
+    } = match head {
+        Ok(head) => head,
+        Err(refused) => return Ok(Err(refused)),
+    };
+
+    if target != PATH {
+        return Ok(Err(Answer::refuse(
+            404,
+            "Not Found",
+            ApiError::bad_request(format!("nothing at {target}; the endpoint is {PATH}")),
+        )));
+    }
+    if method != "POST" {
+        return Ok(Err(Answer::refuse(
+            405,
+            "Method Not Allowed",
+            ApiError::bad_request("only POST"),
+        )));
+    }
+    let device = match bearer {
+        Some(token) => match api.authenticate_device(&token).await {
+            Ok(device) => Some(device.id),
+            Err(error) => return Ok(Err(Answer::refuse(401, "Unauthorized", error))),
+        },
+        None => None,
+    };
+    let length = match length {
+        Some(n) if !chunked => n,
+        _ => {
+            return Ok(Err(Answer::refuse(
+                411,
+                "Length Required",
+                ApiError::bad_request("send the body with a Content-Length"),
+            )));
+        }
+    };
+    let cap = if device.is_some() {
+        MAX_LINE
+    } else {
+        MAX_UNPAIRED_BODY
+    };
+    if length > cap {
+        return Ok(Err(Answer::refuse(
+            413,
+            "Content Too Large",
+            ApiError::bad_request(format!("request exceeded {cap} bytes")),
+        )));
+    }
+    let mut body = vec![0; length];
+    match timeout(deadlines.body, reader.read_exact(&mut body)).await {
+        Ok(read) => read?,
+        Err(_) => return Ok(Err(too_slow())),
+    };
+    Ok(Ok((device, body)))
+}
+
+/// Read the request line and headers, refusing a head past the caps.
+async fn read_head<R>(reader: &mut R) -> io::Result<Result<Head, Answer>>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    let too_large = || {
+        Answer::refuse(
+            431,
+            "Request Header Fields Too Large",
+            ApiError::bad_request("request head too large"),
+        )
+    };
+    let mut line = Vec::new();
+    match read_line_capped(reader, &mut line, MAX_HEADER_LINE).await? {
+        Line::Read => {}
+        Line::TooLong => return Ok(Err(too_large())),
+        Line::Eof => return Err(io::ErrorKind::UnexpectedEof.into()),
+    }
+    let request_line = String::from_utf8_lossy(&line).trim_end().to_owned();
+    let mut parts = request_line.split(' ');
+    let mut head = Head {
+        method: parts.next().unwrap_or("").to_owned(),
+        target: parts.next().unwrap_or("").to_owned(),
+        length: None,
+        bearer: None,
+        chunked: false,
+    };
+
+    let mut headers = 0;
+    loop {
+        line.clear();
+        match read_line_capped(reader, &mut line, MAX_HEADER_LINE).await? {
+            Line::Read => {}
+            Line::TooLong => return Ok(Err(too_large())),
+            Line::Eof => return Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+        let header = String::from_utf8_lossy(&line);
+        let header = header.trim_end();
+        if header.is_empty() {
+            return Ok(Ok(head));
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Ok(Err(too_large()));
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            head.length = value.parse::<usize>().ok();
+        } else if name.eq_ignore_ascii_case("authorization") {
+            // A present header that is not a bearer token is a wrong token,
+            // not a missing one.
+            head.bearer = Some(value.strip_prefix("Bearer ").unwrap_or("").to_owned());
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            head.chunked = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::net::SocketAddr;
+    use std::path::Path;
+
+    use readingbuddy::{Engine, EngineConfig};
+    use readingbuddy_api::Response;
+
+    const FIXTURES: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../engine/tests/fixtures/koreader/synthetic"
+    );
+
+    /// Serve, and hand back the address, a code minted for a reader called
+    /// "Libra", and a shutdown switch.
+    async fn spawn(root: &Path) -> (SocketAddr, String, tokio::sync::oneshot::Sender<()>) {
+        spawn_with(root, DEADLINES).await
+    }
+
+    async fn spawn_with(
+        root: &Path,
+        deadlines: Deadlines,
+    ) -> (SocketAddr, String, tokio::sync::oneshot::Sender<()>) {
+        let config = EngineConfig {
+            db_url: "sqlite::memory:".into(),
+            images_dir: root.join("images"),
+            files_dir: root.join("files"),
+            vault_dir: root.join("vault"),
+            log_dir: root.join("logs"),
+            templates_dir: root.join("templates"),
+            google_api_key: None,
+            calibre_bin_dir: None,
+        };
+        let api = Api::new(Arc::new(Engine::open(config).await.expect("engine")));
+        let code = api.mint_pairing_code("Libra").await.unwrap().code;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            serve_with(
+                api,
+                listener,
+                async {
+                    stop_rx.await.ok();
+                },
+                deadlines,
+            )
+            .await
+            .expect("serve");
+        });
+        (addr, code, stop_tx)
+    }
+
+    /// The smallest HTTP client that is still one: a request out, the status
+    /// and the body back.
+    async fn post(addr: SocketAddr, path: &str, token: Option<&str>, body: &str) -> (u16, Reply) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let auth = token
+            .map(|t| format!("Authorization: Bearer {t}\r\n"))
+            .unwrap_or_default();
+        let request = format!(
+            "POST {path} HTTP/1.1\r\nHost: {addr}\r\n{auth}Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).await.unwrap();
+        let (head, body) = raw.split_once("\r\n\r\n").expect("a head and a body");
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (
+            status,
+            serde_json::from_str(body.trim_end()).expect("a reply"),
+        )
+    }
+
+    /// What a reader does first: trade its code for a token, with no token.
+    async fn pair(addr: SocketAddr, code: &str) -> String {
+        let body = serde_json::json!({
+            "id": 1,
+            "request": { "method": "pair_device", "params": { "code": code } },
+        })
+        .to_string();
+        let (status, reply) = post(addr, PATH, None, &body).await;
+        assert_eq!(status, 200);
+        match reply.outcome {
+            Outcome::Ok {
+                response: Response::PairedDevice(p),
+            } => p.token,
+            other => panic!("{other:?}"),
+        }
+    }
+
+    fn push(id: u64, sidecar: &str, md5: &str) -> String {
+        let src = std::fs::read_to_string(Path::new(FIXTURES).join(sidecar)).unwrap();
+        serde_json::json!({
+            "id": id,
+            "request": {
+                "method": "pull_sidecar_payload",
+                "params": { "partial_md5": md5, "sidecar": src },
+            },
+        })
+        .to_string()
+    }
+
+    /// A real fixture sidecar, posted as the plugin would, comes back as the
+    /// report a wired pull gives — and posting it again changes nothing.
+    #[tokio::test]
+    async fn a_posted_sidecar_comes_back_as_a_pull_report() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (addr, code, _stop) = spawn(tmp.path()).await;
+        let token = pair(addr, &code).await;
+        let body = push(
+            3,
+            "Pachinko.sdr/metadata.epub.lua",
+            "0d6ba6c47caf63b8b3d1a2b3c4d5e6f7",
+        );
+
+        let (status, reply) = post(addr, PATH, Some(&token), &body).await;
+        assert_eq!(status, 200);
+        assert_eq!(reply.id, 3);
+        let first = match reply.outcome {
+            Outcome::Ok {
+                response: Response::PullReport(r),
+            } => r,
+            other => panic!("{other:?}"),
+        };
+        assert!(first.stats.inserted > 0);
+        assert_eq!(first.stats.book_title, "Pachinko");
+
+        let (_, reply) = post(addr, PATH, Some(&token), &body).await;
+        match reply.outcome {
+            Outcome::Ok {
+                response: Response::PullReport(r),
+            } => {
+                assert_eq!(r.stats.book_id, first.stats.book_id);
+                assert_eq!(r.stats.inserted, 0);
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+
+    /// No token and a wrong token are refused, a spent code does not pair
+    /// twice, and a good token opens the push but not the library.
+    #[tokio::test]
+    async fn without_a_paired_token_nothing_is_pulled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (addr, code, _stop) = spawn(tmp.path()).await;
+        let body = push(
+            1,
+            "The-Trial.sdr/metadata.epub.lua",
+            "33333333333333333333333333333333",
+        );
+        for token in [None, Some("not-a-token"), Some("")] {
+            let (status, reply) = post(addr, PATH, token, &body).await;
+            assert_eq!(status, 401, "{token:?}");
+            match reply.outcome {
+                Outcome::Error { error } => assert_eq!(error.code, ErrorCode::Unauthorized),
+                other => panic!("{other:?}"),
+            }
+        }
+
+        let token = pair(addr, &code).await;
+        let again = serde_json::json!({
+            "id": 1,
+            "request": { "method": "pair_device", "params": { "code": code } },
+        })
+        .to_string();
+        let (status, _) = post(addr, PATH, None, &again).await;
+        assert_eq!(status, 401, "a code is spent by its first trade");
+
+        let list = r#"{"id":2,"request":{"method":"list_books","params":{"limit":10}}}"#;
+        let (status, reply) = post(addr, PATH, Some(&token), list).await;
+        assert_eq!(status, 403, "the token opens the push, not the library");
+        assert_eq!(reply.id, 2);
+        match reply.outcome {
+            Outcome::Error { error } => assert_eq!(error.code, ErrorCode::Unauthorized),
+            other => panic!("{other:?}"),
+        }
+    }
+
+    /// The rest of HTTP gets a status that says what was wrong, and a body the
+    /// API's own clients can read.
+    #[tokio::test]
+    async fn anything_but_a_post_to_the_endpoint_is_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (addr, code, _stop) = spawn(tmp.path()).await;
+
+        let (status, _) = post(addr, "/", None, "{}").await;
+        assert_eq!(status, 404);
+        let (status, reply) = post(addr, PATH, None, "{not json").await;
+        assert_eq!(status, 400);
+        match reply.outcome {
+            Outcome::Error { error } => assert_eq!(error.code, ErrorCode::BadRequest),
+            other => panic!("{other:?}"),
+        }
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET {PATH} HTTP/1.1\r\nHost: x\r\n\r\n").as_bytes())
+            .await
+            .unwrap();
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).await.unwrap();
+        assert!(raw.starts_with("HTTP/1.1 405 "), "{raw}");
+
+        // Past the cap for a stranger long before the cap for a reader. Only
+        // the head is sent: a close with the body still unread would be a
+        // reset, as `server.rs`'s overlong-line test explains.
+        let oversized = |auth: String, length: usize| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let head = format!("POST {PATH} HTTP/1.1\r\n{auth}Content-Length: {length}\r\n\r\n");
+            stream.write_all(head.as_bytes()).await.unwrap();
+            let mut raw = String::new();
+            stream.read_to_string(&mut raw).await.unwrap();
+            raw
+        };
+        let raw = oversized(String::new(), MAX_UNPAIRED_BODY + 1).await;
+        assert!(raw.starts_with("HTTP/1.1 413 "), "{raw}");
+        let token = pair(addr, &code).await;
+        let raw = oversized(format!("Authorization: Bearer {token}\r\n"), MAX_LINE + 1).await;
+        assert!(raw.starts_with("HTTP/1.1 413 "), "{raw}");
+    }
+
+    /// A client that never finishes its head is answered 408 and let go,
+    /// rather than holding its task open for as long as it likes.
+    #[tokio::test]
+    async fn a_head_that_never_ends_is_timed_out() {
+        let tmp = tempfile::tempdir().unwrap();
+        let deadlines = Deadlines {
+            head: Duration::from_millis(200),
+            body: Duration::from_millis(200),
+        };
+        let (addr, _code, _stop) = spawn_with(tmp.path(), deadlines).await;
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("POST {PATH} HTTP/1.1\r\nHost: x\r\n").as_bytes())
+            .await
+            .unwrap();
+        let mut raw = String::new();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut raw))
+            .await
+            .expect("the server hung up")
+            .unwrap();
+        assert!(raw.starts_with("HTTP/1.1 408 "), "{raw}");
+
+        // A head that arrives, and a body that does not.
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let head = format!("POST {PATH} HTTP/1.1\r\nContent-Length: 10\r\n\r\n{{");
+        stream.write_all(head.as_bytes()).await.unwrap();
+        let mut raw = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut raw))
+            .await
+            .expect("the server hung up")
+            .ok();
+        assert!(raw.starts_with(b"HTTP/1.1 408 "), "{raw:?}");
+    }
+
+    /// Past [`MAX_CONNECTIONS`] open at once, the next is closed unanswered;
+    /// once one of them goes, a reader gets through again.
+    #[tokio::test]
+    async fn connections_past_the_cap_are_closed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (addr, _code, _stop) = spawn(tmp.path()).await;
+        let mut idle = Vec::new();
+        for _ in 0..MAX_CONNECTIONS {
+            idle.push(TcpStream::connect(addr).await.unwrap());
+        }
+        let mut extra = TcpStream::connect(addr).await.unwrap();
+        let mut raw = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), extra.read_to_end(&mut raw))
+            .await
+            .expect("the extra connection was held, not closed")
+            .ok();
+        assert!(raw.is_empty(), "{raw:?}");
+
+        drop(idle);
+        // The dropped sockets' tasks see EOF and give their slots back.
+        for _ in 0..50 {
+            let body = serde_json::json!({
+                "id": 1,
+                "request": { "method": "api_version" },
+            })
+            .to_string();
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let request = format!(
+                "POST {PATH} HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(request.as_bytes()).await.ok();
+            let mut raw = String::new();
+            stream.read_to_string(&mut raw).await.ok();
+            if raw.starts_with("HTTP/1.1 200 ") {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("no slot came back");
+    }
+}
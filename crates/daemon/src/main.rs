@@ -15,17 +15,25 @@
 //! Unix only, and that is the scope rather than a gap: the daemon exists for a
 //! Tauri app and a menu-bar companion on the user's own machine, and the
 //! platforms `device.rs` knows how to find a reader on are macOS and Linux.
+//!
+//! `--listen` adds the one exception to "this machine only": an HTTP endpoint
+//! a KOReader plugin on the LAN can pair with and push its sidecars to, and
+//! open to nothing else. Off unless asked for; see [`http`]. Pairing codes are
+//! minted over the socket (`mint_pairing_code`), so only this machine's user
+//! can let a reader in.
 
 #[cfg(not(unix))]
 compile_error!("readingbuddyd is a unix-socket daemon; there is no Windows transport yet");
 
+mod http;
 mod server;
 
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use clap::Parser;
-use readingbuddy::{Engine, EngineConfig};
+use readingbuddy::{Engine, EngineConfig, Severity, VaultWatcher};
 use readingbuddy_api::Api;
 
 #[derive(Parser, Debug)]
@@ -44,6 +52,11 @@ struct Cli {
     #[arg(long)]
     socket: Option<PathBuf>,
 
+    /// Also listen for paired readers on the network, e.g. `0.0.0.0:8765`.
+    /// Off by default: a port on the LAN is a decision, not a default.
+    #[arg(long)]
+    listen: Option<SocketAddr>,
+
     /// Google Books API key. The engine takes it from `GOOGLE_BOOKS_API_KEY`
     /// too; the daemon deliberately does **not** read the CLI's config file,
     /// which is the CLI's to own.
@@ -76,8 +89,17 @@ async fn main() -> anyhow::Result<()> {
         .clone()
         .unwrap_or_else(|| cli.data_dir.join("readingbuddyd.sock"));
 
-    let engine = Engine::open(config).await?;
-    let api = Api::new(Arc::new(engine));
+    let engine = Arc::new(Engine::open(config).await?);
+    // The one thing the daemon does besides transport, and it is a log line:
+    // a note edited in Obsidian while only the daemon is up is re-indexed by
+    // the engine, and the operator sees what changed.
+    match engine.watch_vault() {
+        Ok(vault) => {
+            tokio::spawn(log_vault_changes(engine.clone(), vault));
+        }
+        Err(e) => tracing::warn!(error = %e, "not watching the vault"),
+    }
+    let api = Api::new(engine);
 
     let listener = server::bind(&socket).await?;
     // After `bind`, so a refusal to start does not delete the socket of the
@@ -85,7 +107,34 @@ async fn main() -> anyhow::Result<()> {
     let _guard = server::SocketGuard(socket.clone());
     tracing::info!(socket = %socket.display(), api = readingbuddy_api::API_VERSION, "listening");
 
-    server::serve(api, listener, shutdown()).await
+    let Some(addr) = cli.listen else {
+        return server::serve(api, listener, shutdown()).await;
+    };
+    let devices = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!(addr = %devices.local_addr()?, path = http::PATH, "listening for readers");
+    // One signal, two listeners: whichever stops first stops the other, so the
+    // socket guard runs either way.
+    tokio::select! {
+        r = server::serve(api.clone(), listener, shutdown()) => r,
+        r = http::serve(api, devices, std::future::pending()) => r,
+    }
+}
+
+/// Hand each settled batch of vault paths to the engine and log its report.
+async fn log_vault_changes(engine: Arc<Engine>, mut vault: VaultWatcher) {
+    while let Some(paths) = vault.next().await {
+        match engine.refresh_vault_paths(&paths).await {
+            Ok(changed) => {
+                for d in changed {
+                    match d.severity {
+                        Severity::Info => tracing::info!(kind = ?d.kind, "{d}"),
+                        _ => tracing::warn!(kind = ?d.kind, "{d}"),
+                    }
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "vault refresh failed"),
+        }
+    }
 }
 
 /// Ctrl-C or SIGTERM. Both, because a supervisor sends the second and a
@@ -185,7 +185,7 @@ async fn handle(api: &Api, stream: UnixStream) -> io::Result<()> {
     }
 }
 
-enum Line {
+pub(crate) enum Line {
     Read,
     /// The peer closed with nothing pending.
     Eof,
@@ -205,7 +205,11 @@ enum Line {
 /// Bytes are handed over as read, terminator included, and a truncated final
 /// line with no newline is still delivered — a client that hangs up
 /// mid-message gets its last call answered rather than dropped.
-async fn read_line_capped<R>(reader: &mut R, buf: &mut Vec<u8>, max: usize) -> io::Result<Line>
+pub(crate) async fn read_line_capped<R>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max: usize,
+) -> io::Result<Line>
 where
     R: tokio::io::AsyncBufRead + Unpin,
 {
@@ -255,6 +259,7 @@ mod tests {
             files_dir: root.join("files"),
             vault_dir: root.join("vault"),
             log_dir: root.join("logs"),
+            templates_dir: root.join("templates"),
             google_api_key: None,
             calibre_bin_dir: None,
         };
@@ -337,6 +342,8 @@ mod tests {
             .call(Request::ListBooks {
                 limit: 10,
                 sort: Default::default(),
+                filter: Default::default(),
+                offset: 0,
             })
             .await;
         assert_eq!(reply.id, 2);
@@ -0,0 +1,9 @@
+-- Installed by readingbuddy over USB, and removed by it the same way:
+-- `readingbuddy ko plugin remove`, or the Device screen. Edits made here are
+-- replaced on the next upgrade.
+return {
+    name = "readingbuddy",
+    fullname = "readingbuddy",
+    description = "Keeps this reader's highlights and notes in step with readingbuddy.",
+    version = 1,
+}
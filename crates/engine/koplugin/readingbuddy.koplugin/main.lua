@@ -0,0 +1,47 @@
+-- readingbuddy's half of the link, on the reader.
+--
+-- It fails closed: anything that goes wrong in here is logged and dropped.
+-- Nothing this plugin does is worth a reader that will not open a book, so
+-- every entry point KOReader calls is wrapped in `pcall`, and nothing here
+-- runs on the path that turns a page.
+
+local WidgetContainer = require("ui/widget/container/widgetcontainer")
+local logger = require("logger")
+
+local ReadingBuddy = WidgetContainer:extend{
+    name = "readingbuddy",
+    is_doc_only = false,
+}
+
+local function guarded(what, fn)
+    local ok, err = pcall(fn)
+    if not ok then
+        logger.warn("readingbuddy:", what, "failed:", err)
+    end
+end
+
+function ReadingBuddy:init()
+    guarded("init", function()
+        self.ui.menu:registerToMainMenu(self)
+    end)
+end
+
+function ReadingBuddy:addToMainMenu(menu_items)
+    menu_items.readingbuddy = {
+        text = "readingbuddy",
+        sorting_hint = "tools",
+        keep_menu_open = true,
+        callback = function()
+            guarded("menu", function()
+                local InfoMessage = require("ui/widget/infomessage")
+                local UIManager = require("ui/uimanager")
+                UIManager:show(InfoMessage:new{
+                    text = "readingbuddy is installed. Plug the reader in and "
+                        .. "pull from the Device screen to bring your highlights across.",
+                })
+            end)
+        end,
+    }
+end
+
+return ReadingBuddy
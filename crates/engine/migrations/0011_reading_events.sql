@@ -0,0 +1,35 @@
+-- The source-agnostic activity log: one row per book, per day, per source.
+--
+-- Reading-time data is KOReader-only today, and a view built directly on
+-- `statistics.sqlite3` would open to blanks for anyone whose library came from
+-- a Goodreads CSV. So no source is consumed in its own shape: each one is a
+-- *filler* of this table, and the aggregates read only this table. The first
+-- filler is KOReader's `page_stat_data`; highlights, notes and a local reading
+-- source can fill it later without a view or a query changing.
+--
+-- `minutes` and `pages` are nullable on purpose. A filler that knows you were
+-- in the book on a day but not for how long writes NULL, and NULL is "unknown",
+-- never zero — zero is a claim.
+--
+-- `source_key` says *which* instance of a source wrote the row. For KOReader it
+-- is the device file's `partial_md5`: two files linked to one book (an epub and
+-- its re-download) each have their own statistics, and keying on the book alone
+-- would let the second import overwrite the first day's minutes instead of
+-- standing beside them. `''` rather than NULL for sources with no instance,
+-- because NULLs are distinct in a UNIQUE constraint and would defeat it.
+CREATE TABLE reading_events (
+    id            INTEGER PRIMARY KEY,
+    book_id       INTEGER NOT NULL REFERENCES books(id) ON DELETE CASCADE,
+    reading_id    INTEGER REFERENCES readings(id) ON DELETE SET NULL,
+    day           TEXT NOT NULL,              -- YYYY-MM-DD
+    minutes       INTEGER,                    -- NULL = unknown
+    pages         INTEGER,                    -- NULL = unknown
+    source        TEXT NOT NULL,              -- koreader | ...
+    source_key    TEXT NOT NULL DEFAULT '',   -- koreader: the partial_md5
+    confidence    TEXT NOT NULL,              -- measured | inferred
+    created_at    INTEGER NOT NULL,
+    last_modified INTEGER NOT NULL,
+    UNIQUE (book_id, day, source, source_key)
+);
+CREATE INDEX idx_reading_events_day ON reading_events(day);
+CREATE INDEX idx_reading_events_reading ON reading_events(reading_id);
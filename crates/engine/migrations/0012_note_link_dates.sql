@@ -0,0 +1,20 @@
+-- When each wikilink edge was first written.
+--
+-- The reading statistics count "links written" per month, and `note_links` had
+-- no date of its own: an edge's only clock was its note's, and a reflection is
+-- opened empty and written for weeks afterwards, so every link in it would have
+-- been dated to the day it was opened.
+--
+-- `set_note_links` used to delete a note's edges and rewrite them, which would
+-- re-date every edge on every save. It now keeps the edges the body still
+-- writes and drops only the ones it no longer does, so `created_at` survives
+-- for exactly as long as the link does.
+--
+-- Back-filled from the linking note's own `created_at`. That is the earliest
+-- the edge can have been written, not when it was — but it is the only date
+-- there is, and a NULL here would drop every existing link out of every period
+-- rather than put it in the likeliest one.
+ALTER TABLE note_links ADD COLUMN created_at INTEGER;
+
+UPDATE note_links
+   SET created_at = (SELECT n.created_at FROM notes n WHERE n.id = note_links.from_note);
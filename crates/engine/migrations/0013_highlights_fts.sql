@@ -0,0 +1,52 @@
+-- Full-text search over highlights.
+--
+-- `notes_fts` (0001) has been the only index, so the words most worth finding
+-- again — the ones you highlighted, and what you wrote beside them — could
+-- only be listed a book at a time.
+--
+-- **External content, kept by triggers**, which is the opposite of what
+-- `notes_fts` does and for the reason `notes_fts` gives: a note's body lives on
+-- disk and only the notes engine can see it change, so it maintains its own
+-- copy. A highlight's text lives in this database, and every path that changes
+-- it is a statement against `highlights` — `insert_highlight`, the device
+-- refresh, `set_annotation`, a merge deleting a duplicate, a book deleted out
+-- from under its highlights by `ON DELETE CASCADE`. Triggers see all of them;
+-- a call in each Rust method would see only the ones someone remembered.
+--
+-- Four columns. `annotation` is ours and `ko_note` is the device's, and both
+-- are the reader's own words: leaving the device note out would make a note
+-- typed on the Kobo or KOReader unfindable until it had been copied across.
+-- `chapter` is there because "that passage in the chapter about the ship" is
+-- how a highlight is remembered.
+--
+-- The update trigger is narrowed to those columns. `merge_books` rewrites
+-- `book_id` and `identity_hash`, and attribution rewrites `reading_id`, on
+-- every import; re-indexing the text each time would be pure churn.
+CREATE VIRTUAL TABLE highlights_fts USING fts5(
+    text, annotation, ko_note, chapter,
+    content='highlights', content_rowid='id',
+    tokenize='porter unicode61'
+);
+
+CREATE TRIGGER highlights_fts_insert AFTER INSERT ON highlights BEGIN
+    INSERT INTO highlights_fts (rowid, text, annotation, ko_note, chapter)
+    VALUES (new.id, new.text, new.annotation, new.ko_note, new.chapter);
+END;
+
+-- An external-content table is told what it is deleting: the old values have
+-- to be the ones it indexed, or the tokens they left behind are never removed.
+CREATE TRIGGER highlights_fts_delete AFTER DELETE ON highlights BEGIN
+    INSERT INTO highlights_fts (highlights_fts, rowid, text, annotation, ko_note, chapter)
+    VALUES ('delete', old.id, old.text, old.annotation, old.ko_note, old.chapter);
+END;
+
+CREATE TRIGGER highlights_fts_update AFTER UPDATE OF text, annotation, ko_note, chapter
+ON highlights BEGIN
+    INSERT INTO highlights_fts (highlights_fts, rowid, text, annotation, ko_note, chapter)
+    VALUES ('delete', old.id, old.text, old.annotation, old.ko_note, old.chapter);
+    INSERT INTO highlights_fts (rowid, text, annotation, ko_note, chapter)
+    VALUES (new.id, new.text, new.annotation, new.ko_note, new.chapter);
+END;
+
+-- Every highlight imported before this migration.
+INSERT INTO highlights_fts (highlights_fts) VALUES ('rebuild');
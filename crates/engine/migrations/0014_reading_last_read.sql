@@ -0,0 +1,8 @@
+-- When the device last had the book open, as the device says.
+--
+-- Part of the device-owned mirror beside `ko_status`/`ko_percent`/`ko_rating`,
+-- and refreshed the same way: straight assignment from whatever the reader
+-- reports. Nickel (a stock Kobo) keeps it as `content.DateLastRead`; a KOReader
+-- sidecar has nothing equivalent, so it stays NULL for KOReader-only books.
+-- Unix seconds, UTC, like every other instant in this schema.
+ALTER TABLE readings ADD COLUMN ko_last_read INTEGER;
@@ -0,0 +1,42 @@
+-- Readers paired for wireless sync, and which of them said what.
+--
+-- A row is born *pending*: a short `pairing_code` the user reads off this
+-- machine and types into the reader, good until `code_expires_at`. The reader
+-- trades it for a long-lived token, and the row becomes *paired* — the code is
+-- cleared so it cannot be traded twice, and only the token's SHA-256 is kept.
+-- The token itself is shown once, to the reader that asked, and never stored:
+-- a copy of this database is not a copy of every reader's credentials.
+--
+-- Revoking clears `token_hash` and stamps `revoked_at` rather than deleting the
+-- row, because the rows below still say which reader they came from and a
+-- revoked reader is still the one that made them.
+CREATE TABLE devices (
+    id              INTEGER PRIMARY KEY,
+    name            TEXT NOT NULL,
+    pairing_code    TEXT UNIQUE,              -- while pending
+    code_expires_at INTEGER,                  -- while pending
+    token_hash      TEXT UNIQUE,              -- once paired; hex SHA-256
+    created_at      INTEGER NOT NULL,
+    paired_at       INTEGER,
+    last_contact    INTEGER,
+    revoked_at      INTEGER
+);
+
+-- Which books each reader has pushed, and when it last did. One row per pair,
+-- refreshed in place — the history is the highlights and readings themselves.
+CREATE TABLE device_books_reported (
+    device_id     INTEGER NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    book_id       INTEGER NOT NULL REFERENCES books(id) ON DELETE CASCADE,
+    last_reported INTEGER NOT NULL,
+    PRIMARY KEY (device_id, book_id)
+);
+CREATE INDEX idx_device_books_reported_book ON device_books_reported(book_id);
+
+-- Provenance for a two-reader household. NULL is "not from a paired reader":
+-- a wired pull, a Kobo, a Kindle, or anything before this migration.
+--
+-- A highlight keeps the reader that first reported it; a reading's device
+-- mirror (`ko_status`/`ko_percent`/`ko_rating`) is whoever reported last, so its
+-- `device_id` moves with it.
+ALTER TABLE highlights ADD COLUMN device_id INTEGER REFERENCES devices(id) ON DELETE SET NULL;
+ALTER TABLE readings ADD COLUMN device_id INTEGER REFERENCES devices(id) ON DELETE SET NULL;
@@ -0,0 +1,16 @@
+-- A note whose file left the vault.
+--
+-- The vault is the notes' home and the database is an index of it, so a file
+-- deleted or moved in Obsidian has to leave the index too. It is *tombstoned*
+-- rather than deleted, because a move is seen by the vault watcher as two
+-- separate things — a path that vanished and a path that appeared — and only
+-- the first may have arrived yet. Deleting the row on the first half would
+-- cascade its citations away and re-number the note, and the second half would
+-- then index a stranger with the same words.
+--
+-- While `missing_since` is set the row is out of the index: its `notes_fts` row
+-- is removed, links *to* it dangle again (so they re-resolve toward any live
+-- note of the same title), and listings skip it. Its own outgoing edges stay,
+-- with their dates, for the day it comes back — which is a rename, or the file
+-- restored from Obsidian's `.trash`. Unix seconds, like every other instant.
+ALTER TABLE notes ADD COLUMN missing_since INTEGER;
@@ -0,0 +1,43 @@
+-- Links into a section of a note, not only to the note.
+--
+-- `[[Han#Origins]]` and `[[Han#^grief]]` used to be recorded as a plain edge to
+-- `Han`: the part after `#` was dropped on extraction, and "what links here"
+-- could only ever open a note at its top. The anchor now rides on the edge —
+-- the last heading of a `#A#B` path, or `^id` for a block — with `''` for a
+-- link to the whole note. It joins the primary key, because `[[Han#Origins]]`
+-- and `[[Han#Ending]]` in one body are two links, to two places.
+--
+-- `note_anchors` is the other end: every heading and `^block` id a note's body
+-- has, rewritten whenever its links are. An anchored edge resolves when its
+-- note resolves **and** the note still has the anchor — a join at read time,
+-- not a flag on the edge, so a heading deleted from the target makes every
+-- link into it dangle without touching the notes that link there.
+--
+-- SQLite cannot change a primary key in place, so `note_links` is rebuilt with
+-- its rows, dates and indexes. Existing edges become whole-note links; the
+-- next save of each linking note (or `vault reindex`) restores its anchors.
+CREATE TABLE note_links_new (
+    from_note    INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    to_note      INTEGER REFERENCES notes(id) ON DELETE SET NULL,
+    target_title TEXT NOT NULL,
+    anchor       TEXT NOT NULL DEFAULT '',
+    created_at   INTEGER,
+    PRIMARY KEY (from_note, target_title, anchor)
+);
+
+INSERT INTO note_links_new (rowid, from_note, to_note, target_title, created_at)
+     SELECT rowid, from_note, to_note, target_title, created_at FROM note_links;
+
+DROP TABLE note_links;
+ALTER TABLE note_links_new RENAME TO note_links;
+
+CREATE INDEX idx_note_links_to     ON note_links(to_note);
+CREATE INDEX idx_note_links_target ON note_links(target_title COLLATE NOCASE);
+
+-- Headings as written (compared `NOCASE`, as Obsidian does); blocks with their
+-- `^`, so the two can never be mistaken for each other.
+CREATE TABLE note_anchors (
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    anchor  TEXT NOT NULL COLLATE NOCASE,
+    PRIMARY KEY (note_id, anchor)
+);
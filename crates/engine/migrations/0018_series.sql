@@ -0,0 +1,28 @@
+-- Series, and where a book sits in one.
+--
+-- Calibre carries `series` and `series_index`, a Goodreads title embeds
+-- "(Saga, #3)", and an OpenLibrary edition lists its series — and all of it was
+-- dropped on the way in, because `books` had nowhere to put it.
+--
+-- A table of its own rather than a text column on `books`: the run is the thing
+-- a series view lists, and "every book in Saga" wants one name to join on, not
+-- a `LIKE` over free text that calibre spells one way and Goodreads another.
+-- `NOCASE` is what makes those two spellings one row.
+CREATE TABLE series (
+    id   INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
+-- One series per book, merged the way every other book column is: a record
+-- that names a series sets it, and one that names none leaves it alone. A book
+-- in two (a Discworld novel that is also a Rincewind one) is in whichever its
+-- source names first, which every source we read means as the main one.
+--
+-- `position` is REAL because calibre's `series_index` is: novellas sit at 2.5.
+-- NULL is "in the series, place unknown", which is not the same as first.
+CREATE TABLE book_series (
+    book_id   INTEGER PRIMARY KEY REFERENCES books(id) ON DELETE CASCADE,
+    series_id INTEGER NOT NULL REFERENCES series(id) ON DELETE CASCADE,
+    position  REAL
+);
+CREATE INDEX idx_book_series_series ON book_series(series_id, position);
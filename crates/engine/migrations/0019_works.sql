@@ -0,0 +1,25 @@
+-- Works: the book above its editions.
+--
+-- A translation, a paperback and an ebook of one novel are three `books` rows,
+-- and they should stay three — each has its own ISBN, page count and cover, and
+-- a highlight's page means nothing on the wrong one. `merge_books` is the wrong
+-- tool for that: it makes them one row and the distinction is gone. What was
+-- missing is the layer that says they are one *work*, so "have I read this?",
+-- the author corpus and a rating can answer for the novel rather than for
+-- whichever edition happened to be asked about.
+--
+-- `openlibrary_key` is the `/works/OL…W` key, the one identifier any source
+-- gives a work. It is nullable: a work the user put together by hand has none,
+-- and a provider hit that does carry one fills it in later.
+CREATE TABLE works (
+    id              INTEGER PRIMARY KEY,
+    title           TEXT NOT NULL DEFAULT '',
+    openlibrary_key TEXT UNIQUE,
+    created_at      INTEGER NOT NULL
+);
+
+-- One work per edition, or none: a book nobody has grouped is its own work,
+-- and every roll-up treats a NULL here as "just this row". `SET NULL` rather
+-- than a cascade, because deleting a work must never delete a book.
+ALTER TABLE books ADD COLUMN work_id INTEGER REFERENCES works(id) ON DELETE SET NULL;
+CREATE INDEX idx_books_work ON books(work_id);
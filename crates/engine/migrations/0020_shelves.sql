@@ -0,0 +1,40 @@
+-- Shelves: collections of our own, decided rather than inherited.
+--
+-- `docs/decisions.md` deferred collections because three systems minting them
+-- is a merge problem with no good default — a Goodreads shelf, a calibre tag
+-- and a KOReader collection called "favourites" are not obviously one thing.
+-- The answer is not to merge them. A shelf here is the user's, made here; what
+-- the other systems said stays in `book_tags` as the provenance it always was,
+-- and a tag becomes a shelf only when the user picks it (`promote_tags`), as a
+-- copy taken once. Re-importing never touches a shelf.
+--
+-- Two kinds in one table, because a listing filter, the CLI and the API all
+-- want "the shelf called X" without caring which:
+--
+-- * `manual` — the books in `shelf_books`, in the order the user put them.
+-- * `smart`  — `rule`, a saved listing filter as JSON, evaluated every time it
+--   is read. A smart shelf has no `shelf_books` rows; a book is on it because
+--   of what it is now, not because of what it was when the rule was written.
+CREATE TABLE shelves (
+    id            INTEGER PRIMARY KEY,
+    name          TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    kind          TEXT NOT NULL CHECK (kind IN ('manual', 'smart')),
+    rule          TEXT,
+    created_at    INTEGER NOT NULL,
+    last_modified INTEGER NOT NULL,
+    CHECK ((kind = 'smart') = (rule IS NOT NULL))
+);
+
+-- `position` orders a manual shelf and is only ever ordered by, so the gap a
+-- book leaves when it is deleted (the cascade) is invisible. Every writer
+-- renumbers the shelf 1..n before it places a book, so "put it third" means
+-- third as shown. `merge_books` moves a book's memberships before the cascade
+-- can take them.
+CREATE TABLE shelf_books (
+    shelf_id INTEGER NOT NULL REFERENCES shelves(id) ON DELETE CASCADE,
+    book_id  INTEGER NOT NULL REFERENCES books(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    added_at INTEGER NOT NULL,
+    PRIMARY KEY (shelf_id, book_id)
+);
+CREATE INDEX idx_shelf_books_book ON shelf_books(book_id);
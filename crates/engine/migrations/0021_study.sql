@@ -0,0 +1,45 @@
+-- Study: spaced-repetition review of flashcards and opted-in highlights.
+--
+-- `flashcards` stays what it was — a word lifted from a single-word highlight,
+-- and the queue the Anki export drains. A study card is the schedule hung off
+-- one: how easy it has been, when it is next due. Keeping the schedule apart
+-- means exporting a word to Anki and reviewing it here do not step on each
+-- other, and a highlight can be studied without pretending to be a word.
+--
+-- A card is one of two things, never both:
+--
+-- * `flashcard_id` — every flashcard is studyable. Rows are made lazily, the
+--   first time the due queue is read after the flashcard appears, so importers
+--   do not have to know this table exists.
+-- * `highlight_id` — a passage the user chose to learn. Highlights are opt-in:
+--   most are worth keeping, few are worth drilling.
+--
+-- Both cascade: the card goes with the thing it quizzes. `merge_books` moves a
+-- dropped duplicate highlight's card onto the survivor first.
+CREATE TABLE study_cards (
+    id               INTEGER PRIMARY KEY,
+    flashcard_id     INTEGER UNIQUE REFERENCES flashcards(id) ON DELETE CASCADE,
+    highlight_id     INTEGER UNIQUE REFERENCES highlights(id) ON DELETE CASCADE,
+    ease             REAL NOT NULL DEFAULT 2.5,
+    interval_days    INTEGER NOT NULL DEFAULT 0,
+    repetitions      INTEGER NOT NULL DEFAULT 0,
+    lapses           INTEGER NOT NULL DEFAULT 0,
+    due_at           INTEGER NOT NULL,
+    last_reviewed_at INTEGER,
+    created_at       INTEGER NOT NULL,
+    CHECK ((flashcard_id IS NULL) <> (highlight_id IS NULL))
+);
+CREATE INDEX idx_study_cards_due ON study_cards(due_at);
+
+-- Every grade given, with the schedule it produced. The card row holds only
+-- the latest state; this is what a different scheduler would be refitted
+-- from, and what "reviewed today" counts.
+CREATE TABLE study_log (
+    id            INTEGER PRIMARY KEY,
+    card_id       INTEGER NOT NULL REFERENCES study_cards(id) ON DELETE CASCADE,
+    grade         TEXT NOT NULL CHECK (grade IN ('again', 'hard', 'good', 'easy')),
+    reviewed_at   INTEGER NOT NULL,
+    interval_days INTEGER NOT NULL,
+    ease          REAL NOT NULL
+);
+CREATE INDEX idx_study_log_card ON study_log(card_id);
@@ -0,0 +1,587 @@
+//! The Anki package: an `.apkg` Anki opens as it is.
+//!
+//! The TSV export is three columns and a book title; this is the structure
+//! that file loses. A package is a zip of `collection.anki2` — an SQLite
+//! database in Anki's legacy (schema 11) layout, which every Anki since 2.1
+//! still imports — a `media` map, and the media files under their numbers.
+//!
+//! Two note types, both put in a `readingbuddy` deck:
+//!
+//! * **readingbuddy** — a word: `Word`, `Context` (the sentence it was in),
+//!   `Book`, `Author`, `Page` and `Cover`. One card, word on the front.
+//! * **readingbuddy cloze** — a studied highlight. Each sentence of the passage
+//!   is its own deletion, so a passage of three sentences is three cards, each
+//!   asking for one with the other two around it. The reader's note on the
+//!   passage is on the back.
+//!
+//! The cover is the book's own image file, named by its content so that two
+//! packages — or two books sharing a cover — never give Anki two copies.
+//!
+//! **Re-exporting updates.** Anki matches an imported note to one it has by
+//! GUID and, the notetype being the same, overwrites its fields when the
+//! incoming note is newer. So the GUID is a hash of what the source is, not of
+//! where it is stored — the book's title and the word; the book's title, the
+//! device's creation time and the text of a passage — because a merge, a vault
+//! reindex or a rebuilt database gives the same card a new row. The note type
+//! ids are constants and every note is stamped with the export time, so a
+//! changed context or note arrives changed and a card is not doubled. Retitling
+//! a book, or correcting a passage's text, does make a new note: that is the
+//! identity, and there is nothing steadier to hash. Cards leave as new cards:
+//! Anki keeps its own schedule for the ones it already has, and ours stays
+//! ours.
+
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde_json::{Value, json};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use zip::write::SimpleFileOptions;
+
+use crate::error::{EngineError, Result};
+use crate::export::{escape, image_mime};
+use crate::storage::{AnkiSource, CardKind};
+
+/// The note type ids. Fixed, because Anki recognises a notetype it already
+/// has by id, and a new id on every export would be a new notetype each time.
+const WORD_MODEL: i64 = 1_718_000_000_101;
+const CLOZE_MODEL: i64 = 1_718_000_000_102;
+const DECK: i64 = 1_718_000_000_100;
+const DECK_NAME: &str = "readingbuddy";
+
+/// Anki's field separator inside `notes.flds`.
+const FIELD_SEP: char = '\u{1f}';
+
+/// The schema-11 collection: Anki's own `CREATE` statements, minus nothing.
+const SCHEMA: &str = r#"
+CREATE TABLE col (
+    id integer primary key, crt integer not null, mod integer not null,
+    scm integer not null, ver integer not null, dty integer not null,
+    usn integer not null, ls integer not null, conf text not null,
+    models text not null, decks text not null, dconf text not null,
+    tags text not null
+);
+CREATE TABLE notes (
+    id integer primary key, guid text not null, mid integer not null,
+    mod integer not null, usn integer not null, tags text not null,
+    flds text not null, sfld integer not null, csum integer not null,
+    flags integer not null, data text not null
+);
+CREATE TABLE cards (
+    id integer primary key, nid integer not null, did integer not null,
+    ord integer not null, mod integer not null, usn integer not null,
+    type integer not null, queue integer not null, due integer not null,
+    ivl integer not null, factor integer not null, reps integer not null,
+    lapses integer not null, left integer not null, odue integer not null,
+    odid integer not null, flags integer not null, data text not null
+);
+CREATE TABLE revlog (
+    id integer primary key, cid integer not null, usn integer not null,
+    ease integer not null, ivl integer not null, lastIvl integer not null,
+    factor integer not null, time integer not null, type integer not null
+);
+CREATE TABLE graves (
+    usn integer not null, oid integer not null, type integer not null
+);
+CREATE INDEX ix_notes_usn on notes (usn);
+CREATE INDEX ix_cards_usn on cards (usn);
+CREATE INDEX ix_revlog_usn on revlog (usn);
+CREATE INDEX ix_cards_nid on cards (nid);
+CREATE INDEX ix_cards_sched on cards (did, queue, due);
+CREATE INDEX ix_revlog_cid on revlog (cid);
+CREATE INDEX ix_notes_csum on notes (csum);
+"#;
+
+const CSS: &str = ".card { font-family: Georgia, serif; font-size: 20px; text-align: center; }
+.word { font-size: 32px; }
+.passage { text-align: left; line-height: 1.5; }
+.source { margin-top: 1em; font-size: 14px; color: #888; }
+.context { font-style: italic; }
+.cover img { max-height: 160px; margin-top: 1em; }
+.cloze { font-weight: bold; }";
+
+const SOURCE: &str = "<div class=\"source\">{{Book}}{{#Author}} — {{Author}}{{/Author}}\
+                      {{#Page}} · p.{{Page}}{{/Page}}</div>";
+
+/// One note, ready for the `notes` table, and how many cards it makes.
+#[derive(Debug)]
+struct Note {
+    guid: String,
+    model: i64,
+    fields: Vec<String>,
+    sort_field: String,
+    cards: usize,
+}
+
+/// Build the package for `sources`, stamped `now` (unix seconds).
+pub(crate) async fn package(sources: &[AnkiSource], now: i64) -> Result<Vec<u8>> {
+    let mut media: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut notes: Vec<Note> = Vec::with_capacity(sources.len());
+    for s in sources {
+        let cover = cover(s, &mut media);
+        let n = note(s, cover.as_deref());
+        // Two copies of one book holding the same word are one card to Anki;
+        // a second note under its GUID would also be a second row under its id.
+        if notes.iter().all(|m| m.guid != n.guid) {
+            notes.push(n);
+        }
+    }
+    let collection = collection(&notes, now).await?;
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let opts = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file("collection.anki2", opts).map_err(zip_err)?;
+    zip.write_all(&collection)?;
+    let map: serde_json::Map<String, Value> = media
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| (i.to_string(), Value::from(name.as_str())))
+        .collect();
+    zip.start_file("media", opts).map_err(zip_err)?;
+    zip.write_all(serde_json::to_string(&map)?.as_bytes())?;
+    for (i, (_, bytes)) in media.iter().enumerate() {
+        zip.start_file(i.to_string(), opts).map_err(zip_err)?;
+        zip.write_all(bytes)?;
+    }
+    Ok(zip.finish().map_err(zip_err)?.into_inner())
+}
+
+fn zip_err(e: zip::result::ZipError) -> EngineError {
+    EngineError::Io(std::io::Error::other(e))
+}
+
+/// The source's book cover as a media file name, reading it into `media` the
+/// first time it is met. A cover gone from disk, or of a type Anki cannot
+/// show, leaves the field empty — it is decoration.
+fn cover(s: &AnkiSource, media: &mut Vec<(String, Vec<u8>)>) -> Option<String> {
+    let path = PathBuf::from(s.cover_path.as_deref()?);
+    image_mime(&path)?;
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let bytes = std::fs::read(&path).ok()?;
+    let name = format!("readingbuddy-{}.{ext}", &hex(&Sha256::digest(&bytes))[..16]);
+    if !media.iter().any(|(n, _)| *n == name) {
+        media.push((name.clone(), bytes));
+    }
+    Some(name)
+}
+
+fn note(s: &AnkiSource, cover: Option<&str>) -> Note {
+    let author = escape(&s.authors.join(", "));
+    let page = s.page.map(|p| p.to_string()).unwrap_or_default();
+    let cover = cover
+        .map(|name| format!("<img src=\"{}\">", escape(name)))
+        .unwrap_or_default();
+    let context = escape(s.context.as_deref().unwrap_or("").trim());
+    let book = escape(&s.book_title);
+    // The title as a reader would compare it, so respacing it is no new book.
+    let title = s
+        .book_title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let title = title.to_lowercase();
+    match s.kind {
+        CardKind::Word => Note {
+            guid: guid(&format!("word:{title}:{}", s.anchor)),
+            model: WORD_MODEL,
+            fields: vec![escape(&s.front), context, book, author, page, cover],
+            sort_field: s.front.clone(),
+            cards: 1,
+        },
+        CardKind::Highlight => {
+            let (text, cards) = cloze(&s.front);
+            Note {
+                guid: guid(&format!("highlight:{title}:{}", s.anchor)),
+                model: CLOZE_MODEL,
+                fields: vec![text, context, book, author, page, cover],
+                sort_field: s.front.split_whitespace().collect::<Vec<_>>().join(" "),
+                cards,
+            }
+        }
+    }
+}
+
+/// The passage with each sentence a deletion of its own: `{{c1::…}} {{c2::…}}`.
+/// Returns the field and how many deletions — one card each.
+fn cloze(passage: &str) -> (String, usize) {
+    let parts = sentences(passage);
+    let text = parts
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            // `::` would start a hint and `}}` end the deletion early.
+            let body = escape(s).replace(':', "&#58;").replace('}', "&#125;");
+            format!("{{{{c{}::{body}}}}}", i + 1)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    (text, parts.len().max(1))
+}
+
+/// Split after `.`, `!`, `?` or `…` where whitespace follows. A passage with
+/// no such break is one sentence.
+fn sentences(passage: &str) -> Vec<String> {
+    let words: Vec<&str> = passage.split_whitespace().collect();
+    let mut out = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for w in words {
+        current.push(w);
+        let end = w.trim_end_matches(['"', '\'', '”', '’', ')', '»']);
+        if end.ends_with(['.', '!', '?', '…']) {
+            out.push(current.join(" "));
+            current.clear();
+        }
+    }
+    if !current.is_empty() {
+        out.push(current.join(" "));
+    }
+    out
+}
+
+/// A GUID that is the same on every export of the same source.
+fn guid(key: &str) -> String {
+    hex(&Sha256::digest(format!("readingbuddy:{key}")))[..16].to_string()
+}
+
+/// A positive id that fits Anki's (and JavaScript's) 53 bits, from `key`.
+/// Deterministic so that two exports of one library are the same file.
+fn stable_id(key: &str) -> i64 {
+    let d = Sha256::digest(format!("readingbuddy:id:{key}"));
+    let n = u64::from_be_bytes(d[..8].try_into().expect("a SHA-256 has 8 bytes"));
+    ((n >> 12) | 1) as i64
+}
+
+/// Anki's duplicate checksum: the first 8 hex digits of the SHA-1 of the
+/// sort field's text.
+fn checksum(text: &str) -> i64 {
+    let d = Sha1::digest(text.as_bytes());
+    i64::from(u32::from_be_bytes(
+        d[..4].try_into().expect("a SHA-1 has 4 bytes"),
+    ))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// A scratch file for the collection, removed when dropped. SQLite writes a
+/// file and Anki reads one; there is no in-memory path between them.
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new() -> Scratch {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Scratch(std::env::temp_dir().join(format!(
+            "rb-anki-{}-{}.anki2",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        )))
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.0).ok();
+    }
+}
+
+/// `collection.anki2`'s bytes: the schema, one `col` row carrying the note
+/// types and deck as JSON, and the notes and their cards.
+async fn collection(notes: &[Note], now: i64) -> Result<Vec<u8>> {
+    let scratch = Scratch::new();
+    std::fs::remove_file(&scratch.0).ok();
+    let opts = SqliteConnectOptions::new()
+        .filename(&scratch.0)
+        .create_if_missing(true)
+        // Anki opens the one file; a WAL beside it would be left behind.
+        .journal_mode(SqliteJournalMode::Delete);
+    // One connection, so that closing the pool below has the file complete
+    // and unlocked before it is read back.
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await?;
+    sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+
+    let ms = now * 1000;
+    let mut tx = pool.begin().await?;
+    sqlx::query("INSERT INTO col VALUES (1, ?, ?, ?, 11, 0, 0, 0, ?, ?, ?, ?, '{}')")
+        .bind(now)
+        .bind(ms)
+        .bind(ms)
+        .bind(conf().to_string())
+        .bind(models(now).to_string())
+        .bind(decks(now).to_string())
+        .bind(deck_conf().to_string())
+        .execute(&mut *tx)
+        .await?;
+
+    for (pos, n) in notes.iter().enumerate() {
+        let nid = stable_id(&n.guid);
+        sqlx::query("INSERT INTO notes VALUES (?, ?, ?, ?, -1, ' readingbuddy ', ?, ?, ?, 0, '')")
+            .bind(nid)
+            .bind(&n.guid)
+            .bind(n.model)
+            .bind(now)
+            .bind(n.fields.join(&FIELD_SEP.to_string()))
+            .bind(&n.sort_field)
+            .bind(checksum(&n.sort_field))
+            .execute(&mut *tx)
+            .await?;
+        for ord in 0..n.cards {
+            sqlx::query(
+                "INSERT INTO cards VALUES (?, ?, ?, ?, ?, -1, 0, 0, ?, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            )
+            .bind(stable_id(&format!("{}:{ord}", n.guid)))
+            .bind(nid)
+            .bind(DECK)
+            .bind(ord as i64)
+            .bind(now)
+            .bind(pos as i64 + 1)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+    tx.commit().await?;
+    pool.close().await;
+    Ok(std::fs::read(&scratch.0)?)
+}
+
+fn field(name: &str, ord: usize) -> Value {
+    json!({
+        "name": name, "ord": ord, "sticky": false, "rtl": false,
+        "font": "Arial", "size": 20, "media": [],
+    })
+}
+
+fn template(name: &str, qfmt: &str, afmt: &str) -> Value {
+    json!({
+        "name": name, "ord": 0, "qfmt": qfmt, "afmt": afmt,
+        "did": null, "bqfmt": "", "bafmt": "",
+    })
+}
+
+fn model(id: i64, name: &str, kind: u8, fields: &[&str], tmpl: Value, now: i64) -> Value {
+    json!({
+        "id": id, "name": name, "type": kind, "mod": now, "usn": -1,
+        "sortf": 0, "did": DECK, "tmpls": [tmpl],
+        "flds": fields.iter().enumerate().map(|(i, f)| field(f, i)).collect::<Vec<_>>(),
+        "css": CSS,
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\
+                     \\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\
+                     \\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "latexsvg": false,
+        // The word card exists when field 0 does; a cloze note's cards are its
+        // deletions, which Anki counts itself.
+        "req": if kind == 0 { json!([[0, "any", [0]]]) } else { json!([]) },
+        "tags": [], "vers": [],
+    })
+}
+
+fn models(now: i64) -> Value {
+    let word = template(
+        "Word",
+        &format!("<div class=\"word\">{{{{Word}}}}</div>\n{SOURCE}"),
+        "{{FrontSide}}\n<hr id=\"answer\">\n<div class=\"context\">{{Context}}</div>\n\
+         {{#Cover}}<div class=\"cover\">{{Cover}}</div>{{/Cover}}",
+    );
+    let cloze = template(
+        "Cloze",
+        &format!("<div class=\"passage\">{{{{cloze:Text}}}}</div>\n{SOURCE}"),
+        &format!(
+            "<div class=\"passage\">{{{{cloze:Text}}}}</div>\n{SOURCE}\n\
+             {{{{#Note}}}}<hr id=\"answer\"><div class=\"context\">{{{{Note}}}}</div>{{{{/Note}}}}\n\
+             {{{{#Cover}}}}<div class=\"cover\">{{{{Cover}}}}</div>{{{{/Cover}}}}"
+        ),
+    );
+    json!({
+        WORD_MODEL.to_string(): model(
+            WORD_MODEL,
+            "readingbuddy",
+            0,
+            &["Word", "Context", "Book", "Author", "Page", "Cover"],
+            word,
+            now,
+        ),
+        CLOZE_MODEL.to_string(): model(
+            CLOZE_MODEL,
+            "readingbuddy cloze",
+            1,
+            &["Text", "Note", "Book", "Author", "Page", "Cover"],
+            cloze,
+            now,
+        ),
+    })
+}
+
+fn deck(id: i64, name: &str, now: i64) -> Value {
+    json!({
+        "id": id, "name": name, "desc": "", "mod": now, "usn": -1,
+        "collapsed": false, "browserCollapsed": false, "dyn": 0, "conf": 1,
+        "extendNew": 0, "extendRev": 0,
+        "newToday": [0, 0], "revToday": [0, 0], "lrnToday": [0, 0], "timeToday": [0, 0],
+    })
+}
+
+fn decks(now: i64) -> Value {
+    json!({
+        "1": deck(1, "Default", now),
+        DECK.to_string(): deck(DECK, DECK_NAME, now),
+    })
+}
+
+fn conf() -> Value {
+    json!({
+        "activeDecks": [1], "curDeck": 1, "newSpread": 0, "collapseTime": 1200,
+        "timeLim": 0, "estTimes": true, "dueCounts": true, "curModel": WORD_MODEL,
+        "nextPos": 1, "sortType": "noteFld", "sortBackwards": false, "addToCur": true,
+    })
+}
+
+/// Anki's stock options group. The importer keeps the reader's own for a deck
+/// it already has.
+fn deck_conf() -> Value {
+    json!({
+        "1": {
+            "id": 1, "name": "Default", "mod": 0, "usn": 0, "maxTaken": 60,
+            "autoplay": true, "timer": 0, "replayq": true,
+            "new": {
+                "bury": true, "delays": [1, 10], "initialFactor": 2500,
+                "ints": [1, 4, 7], "order": 1, "perDay": 20, "separate": true,
+            },
+            "lapse": {
+                "delays": [10], "leechAction": 0, "leechFails": 8, "minInt": 1, "mult": 0,
+            },
+            "rev": {
+                "bury": true, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1,
+                "maxIvl": 36500, "minSpace": 1, "perDay": 100,
+            },
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::ConnectOptions;
+    use std::io::Read;
+
+    fn source(kind: CardKind, id: i64, front: &str, anchor: &str) -> AnkiSource {
+        AnkiSource {
+            kind,
+            id,
+            front: front.into(),
+            context: Some("a <b>sentence</b>".into()),
+            anchor: anchor.into(),
+            book_title: "Der Process".into(),
+            authors: vec!["Franz Kafka".into()],
+            page: Some(12),
+            cover_path: None,
+        }
+    }
+
+    fn unzip(apkg: &[u8]) -> std::collections::BTreeMap<String, Vec<u8>> {
+        let mut zip = zip::ZipArchive::new(Cursor::new(apkg)).unwrap();
+        (0..zip.len())
+            .map(|i| {
+                let mut f = zip.by_index(i).unwrap();
+                let mut bytes = Vec::new();
+                f.read_to_end(&mut bytes).unwrap();
+                (f.name().to_string(), bytes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_passage_is_one_deletion_per_sentence_and_cannot_break_out() {
+        assert_eq!(
+            sentences("He waited. Nobody came! Why?  And then"),
+            ["He waited.", "Nobody came!", "Why?", "And then"]
+        );
+        assert_eq!(sentences("“Quoted.” Next"), ["“Quoted.”", "Next"]);
+        let (text, cards) = cloze("Go on: a}} b. <i>c</i>");
+        assert_eq!(cards, 2);
+        assert_eq!(
+            text,
+            "{{c1::Go on&#58; a&#125;&#125; b.}} {{c2::&lt;i&gt;c&lt;/i&gt;}}"
+        );
+    }
+
+    #[test]
+    fn a_guid_depends_on_the_source_and_nothing_else() {
+        let a = note(&source(CardKind::Word, 3, "Sehnsucht", "Sehnsucht"), None);
+        // Another row — a merge or a rebuild renumbers — is the same card.
+        let b = note(&source(CardKind::Word, 9, "Sehnsucht", "Sehnsucht"), None);
+        let c = note(&source(CardKind::Highlight, 3, "Sehnsucht", ""), None);
+        let mut elsewhere = source(CardKind::Word, 3, "Sehnsucht", "Sehnsucht");
+        elsewhere.book_title = "Das Schloss".into();
+        assert_eq!(a.guid, b.guid);
+        assert_ne!(a.guid, c.guid);
+        assert_ne!(a.guid, note(&elsewhere, None).guid);
+        assert_eq!(a.fields[1], "a &lt;b&gt;sentence&lt;/b&gt;");
+        // What Anki's `fieldChecksum` gives for the same word.
+        assert_eq!(checksum("Sehnsucht"), 3_068_762_177);
+    }
+
+    #[tokio::test]
+    async fn the_package_is_a_collection_anki_can_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let cover = dir.path().join("cover.jpg");
+        std::fs::write(&cover, b"\xff\xd8not really a jpeg").unwrap();
+        let mut word = source(CardKind::Word, 1, "Sehnsucht", "Sehnsucht");
+        word.cover_path = Some(cover.display().to_string());
+        let mut passage = source(CardKind::Highlight, 7, "One. Two.", "2024-03-01");
+        passage.cover_path = word.cover_path.clone();
+
+        let apkg = package(&[word, passage], 1_700_000_000).await.unwrap();
+        let files = unzip(&apkg);
+        let media: Value = serde_json::from_slice(&files["media"]).unwrap();
+        let name = media["0"].as_str().unwrap();
+        assert!(name.starts_with("readingbuddy-") && name.ends_with(".jpg"));
+        // Both notes show the one cover, so it is packed once.
+        assert_eq!(media.as_object().unwrap().len(), 1);
+        assert_eq!(files["0"], b"\xff\xd8not really a jpeg");
+
+        let db = dir.path().join("collection.anki2");
+        std::fs::write(&db, &files["collection.anki2"]).unwrap();
+        let mut conn = SqliteConnectOptions::new()
+            .filename(&db)
+            .connect()
+            .await
+            .unwrap();
+        let (ver, models): (i64, String) = sqlx::query_as("SELECT ver, models FROM col")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(ver, 11);
+        let models: Value = serde_json::from_str(&models).unwrap();
+        assert_eq!(models[WORD_MODEL.to_string()]["name"], "readingbuddy");
+        assert_eq!(models[CLOZE_MODEL.to_string()]["type"], 1);
+
+        let notes: Vec<(i64, String)> = sqlx::query_as("SELECT mid, flds FROM notes ORDER BY mid")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        let fields: Vec<&str> = notes[0].1.split(FIELD_SEP).collect();
+        assert_eq!(
+            fields[..5],
+            [
+                "Sehnsucht",
+                "a &lt;b&gt;sentence&lt;/b&gt;",
+                "Der Process",
+                "Franz Kafka",
+                "12"
+            ]
+        );
+        assert_eq!(fields[5], format!("<img src=\"{name}\">"));
+        assert!(notes[1].1.starts_with("{{c1::One.}} {{c2::Two.}}"));
+        let cards: i64 = sqlx::query_scalar("SELECT count(*) FROM cards")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(cards, 3);
+    }
+}
@@ -0,0 +1,231 @@
+//! One author across every book of theirs: the corpus view.
+//!
+//! Everything else reads one book at a time — KOReader keeps a sidecar per
+//! file and [`Storage::list_highlights`] is per book — so "what have I marked
+//! in everything she wrote?" had no answer. This pools the books, and with each
+//! its readings, highlights (annotations ride on them) and notes.
+//!
+//! **Name variants** are pooled by `matching::author_key`: the same person
+//! arrives as `Min Jin Lee` from calibre and `Lee, Min Jin` from an epub, and
+//! sorted tokens make both one key. What it cannot do is expand an initial —
+//! `J. R. R. Tolkien` and `John Ronald Reuel Tolkien` stay two — which is the
+//! same line the matcher draws, for the same reason.
+//!
+//! **Editions** of one work are one entry: a translation and the original are
+//! one book by this author, so the earliest published stands for the work —
+//! the original, usually — and the others' readings, highlights and notes are
+//! pooled into it.
+//!
+//! A query that is not a whole name (`lee`) matches every author whose name
+//! contains all its words; when that is more than one person it says who,
+//! rather than pooling two people's books.
+
+use crate::book::Book;
+use crate::error::{EngineError, Result};
+use crate::matching::author_key;
+use crate::storage::{BookSort, Highlight, NoteRecord, Reading, Storage};
+
+/// How an author's books are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CorpusOrder {
+    /// By publication year: the order they were written in.
+    #[default]
+    Published,
+    /// By when they were first read here: the order they were met in.
+    Read,
+}
+
+impl CorpusOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorpusOrder::Published => "published",
+            CorpusOrder::Read => "read",
+        }
+    }
+}
+
+impl std::str::FromStr for CorpusOrder {
+    type Err = EngineError;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "published" | "year" => Ok(CorpusOrder::Published),
+            "read" => Ok(CorpusOrder::Read),
+            other => Err(EngineError::InvalidInput(format!(
+                "unknown order: {other} (published or read)"
+            ))),
+        }
+    }
+}
+
+/// Every book by one author, and what was kept from each.
+#[derive(Debug, Clone)]
+pub struct AuthorCorpus {
+    /// The name as most of the books write it.
+    pub name: String,
+    /// The other spellings pooled in with it.
+    pub variants: Vec<String>,
+    pub books: Vec<CorpusBook>,
+}
+
+impl AuthorCorpus {
+    pub fn highlight_count(&self) -> usize {
+        self.books.iter().map(|b| b.highlights.len()).sum()
+    }
+
+    pub fn note_count(&self) -> usize {
+        self.books.iter().map(|b| b.notes.len()).sum()
+    }
+}
+
+/// One book of the corpus.
+#[derive(Debug, Clone)]
+pub struct CorpusBook {
+    pub book: Book,
+    /// The work's other editions by this author. Their readings, highlights
+    /// and notes are in the lists below with `book`'s own.
+    pub editions: Vec<Book>,
+    pub readings: Vec<Reading>,
+    pub highlights: Vec<Highlight>,
+    /// Every note on the book, reflections included: this is the reader's own
+    /// view, not a published one.
+    pub notes: Vec<NoteRecord>,
+}
+
+impl CorpusBook {
+    /// When this book was first read, if it has been.
+    pub fn first_read(&self) -> Option<i64> {
+        self.readings
+            .iter()
+            .filter_map(|r| r.started_at.or(r.finished_at))
+            .min()
+    }
+}
+
+pub(crate) async fn author_corpus(
+    storage: &Storage,
+    name: &str,
+    order: CorpusOrder,
+) -> Result<AuthorCorpus> {
+    let query = author_key(name);
+    if query.is_empty() {
+        return Err(EngineError::InvalidInput("an author needs a name".into()));
+    }
+    let library = storage.list_books(i64::MAX, BookSort::Title).await?;
+
+    // A whole name first; failing that, every name with all of the query's
+    // words in it.
+    let whole = |key: &str| key == query;
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let partial = |key: &str| {
+        let have: Vec<&str> = key.split_whitespace().collect();
+        words.iter().all(|w| have.contains(w))
+    };
+    let key = match keys_matching(&library, whole).into_iter().next() {
+        Some(k) => k,
+        None => {
+            let found = keys_matching(&library, partial);
+            match found.len() {
+                0 => {
+                    return Err(EngineError::NotFound(format!(
+                        "no book by an author called '{name}'"
+                    )));
+                }
+                1 => found.into_iter().next().expect("one key"),
+                _ => {
+                    let names: Vec<String> = found
+                        .iter()
+                        .map(|k| spellings(&library, k).remove(0))
+                        .collect();
+                    return Err(EngineError::InvalidInput(format!(
+                        "'{name}' could be {} — give more of the name",
+                        names.join(", ")
+                    )));
+                }
+            }
+        }
+    };
+
+    let mut variants = spellings(&library, &key);
+    let name = variants.remove(0);
+    let mut books = Vec::new();
+    for book in library {
+        if !book.authors.iter().any(|a| author_key(a) == key) {
+            continue;
+        }
+        let id = book.id.expect("stored book has id");
+        let readings = storage.list_readings(id).await?;
+        let highlights = storage.list_highlights(id).await?;
+        let notes = storage.list_notes(Some(id)).await?;
+        let same_work =
+            |b: &&mut CorpusBook| book.work_id.is_some() && b.book.work_id == book.work_id;
+        match books.iter_mut().find(same_work) {
+            Some(entry) => {
+                entry.readings.extend(readings);
+                entry.highlights.extend(highlights);
+                entry.notes.extend(notes);
+                entry.editions.push(book);
+            }
+            None => books.push(CorpusBook {
+                book,
+                editions: Vec::new(),
+                readings,
+                highlights,
+                notes,
+            }),
+        }
+    }
+    for b in &mut books {
+        let year = |b: &Book| (b.publish_year.is_none(), b.publish_year);
+        if let Some(i) = (0..b.editions.len()).min_by_key(|&i| year(&b.editions[i]))
+            && year(&b.editions[i]) < year(&b.book)
+        {
+            std::mem::swap(&mut b.book, &mut b.editions[i]);
+        }
+        b.readings
+            .sort_by_key(|r| (r.started_at.unwrap_or(r.created_at), r.id));
+    }
+    // Unknowns last either way, and the title order `list_books` gave them is
+    // kept among equals.
+    match order {
+        CorpusOrder::Published => {
+            books.sort_by_key(|b| (b.book.publish_year.is_none(), b.book.publish_year))
+        }
+        CorpusOrder::Read => books.sort_by_key(|b| (b.first_read().is_none(), b.first_read())),
+    }
+    Ok(AuthorCorpus {
+        name,
+        variants,
+        books,
+    })
+}
+
+/// The distinct author keys in the library that `matches` accepts, in the
+/// order first met.
+fn keys_matching(library: &[Book], matches: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for a in library.iter().flat_map(|b| &b.authors) {
+        let k = author_key(a);
+        if matches(&k) && !keys.contains(&k) {
+            keys.push(k);
+        }
+    }
+    keys
+}
+
+/// Every way the library spells the author with this key, the most used
+/// first.
+fn spellings(library: &[Book], key: &str) -> Vec<String> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for a in library.iter().flat_map(|b| &b.authors) {
+        if author_key(a) != key {
+            continue;
+        }
+        match counts.iter_mut().find(|(s, _)| s == a) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((a.clone(), 1)),
+        }
+    }
+    // Stable, so a tie goes to the spelling met first.
+    counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+    counts.into_iter().map(|(s, _)| s).collect()
+}
@@ -21,6 +21,24 @@ pub struct Book {
     pub page_count: Option<i64>,
     pub description: Option<String>,
     pub first_sentence: Option<String>,
+    /// The series this book is in. Lives in `book_series` (migration `0018`)
+    /// rather than on `books`, and merges the way the columns do: a record that
+    /// names a series sets it, one that names none leaves it alone.
+    pub series: Option<String>,
+    /// Where in [`Book::series`] it sits. Fractional because calibre's is —
+    /// a novella between the second and third books is 2.5.
+    pub series_index: Option<f64>,
+    /// The work this edition belongs to (migration `0019`), when it has been
+    /// grouped with others. Read-only here: [`Storage::attach_edition`] and
+    /// [`Storage::detach_edition`] are the writers, and an upsert ignores it.
+    ///
+    /// [`Storage::attach_edition`]: crate::Storage::attach_edition
+    /// [`Storage::detach_edition`]: crate::Storage::detach_edition
+    pub work_id: Option<i64>,
+    /// OpenLibrary's `/works/…` key. A provider that knows it sets it, and the
+    /// upsert files the book under the work with that key — creating it the
+    /// first time — unless the book is already in one.
+    pub openlibrary_work: Option<String>,
     /// Reading state, as a **read-only projection of the current reading** —
     /// the open one if there is one, else the most recent. Since migration
     /// `0005` these are not `books` columns and
@@ -287,6 +287,9 @@ pub struct CalibreBook {
     /// file that was opened whose `partial_md5` the sidecar carries.
     pub formats: Vec<PathBuf>,
     pub series: Option<String>,
+    /// Where in `series` the book sits. Calibre writes a `series_index` of 1.0
+    /// on every book, series or not, so it is only kept alongside a series.
+    pub series_index: Option<f64>,
     /// Calibre's `timestamp` — when the book was added to *that* library.
     pub added: Option<i64>,
 }
@@ -385,6 +388,8 @@ struct RawRow {
     tags: Option<StringOrList>,
     #[serde(default)]
     series: Option<String>,
+    #[serde(default)]
+    series_index: Option<f64>,
 }
 
 /// Parse `calibredb list --for-machine` output.
@@ -437,6 +442,11 @@ fn row_to_book(r: RawRow) -> CalibreBook {
             .formats
             .map(|f| f.values().into_iter().map(PathBuf::from).collect())
             .unwrap_or_default(),
+        series_index: r
+            .series
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+            .and(r.series_index),
         series: r.series.filter(|s| !s.trim().is_empty()),
         added: r.timestamp.as_deref().and_then(unix_of),
     }
@@ -817,6 +827,8 @@ async fn apply(
         isbn_10: cb.isbn_10.clone(),
         isbn_13: cb.isbn_13.clone(),
         description: cb.description.clone(),
+        series: cb.series.clone(),
+        series_index: cb.series_index,
         ..Default::default()
     };
     let created = book_id.is_none();
@@ -1001,6 +1013,7 @@ mod tests {
         );
         assert_eq!(p.tags, vec!["fiction", "korean-lit"]);
         assert_eq!(p.series.as_deref(), Some("Saga"));
+        assert_eq!(p.series_index, Some(1.0));
         assert_eq!(p.formats.len(), 2, "every format, not just the epub");
         assert!(p.cover.is_some());
     }
@@ -1031,6 +1044,7 @@ mod tests {
         assert_eq!(bare.title.as_deref(), Some("Untitled Draft"));
         assert!(bare.authors.is_empty());
         assert_eq!(bare.series, None);
+        assert_eq!(bare.series_index, None, "calibre's 1.0 is not a place");
         assert_eq!(bare.publisher, None);
         assert_eq!(bare.isbn_10, None);
         assert_eq!(bare.isbn_13, None);
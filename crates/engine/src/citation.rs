@@ -0,0 +1,559 @@
+//! Citing a book, and quoting a highlight from it, for writing done elsewhere.
+//!
+//! Not to be confused with a review's *citations* — the highlights a note
+//! points at ([`crate::Engine::cite`]). This module is the outward direction:
+//! a reference manager's entry for a book, or a quotation with its page, ready
+//! to paste into a paper.
+//!
+//! Three entry formats, one per tool family: **BibTeX** (LaTeX, and anything
+//! that imports `.bib`), **CSL-JSON** (Zotero, pandoc's citeproc) and **RIS**
+//! (EndNote, Mendeley). Three quotation styles: **Chicago** (a note), **MLA**
+//! and **APA** (in-text, followed by the reference). A quotation is written
+//! with `*italics*` for the title — it is pasted into markdown far more often
+//! than anywhere else, and a word processor takes the asterisks as a hint.
+//!
+//! The edition is the ISBN we hold: a page number means nothing without it,
+//! so every quotation ends with one when the book has one.
+//!
+//! Names are stored as written ("Min Jin Lee"). The family name is taken to be
+//! the last word, unless the name is already "Family, Given" — which is wrong
+//! for some names, and why the output is text to check rather than a file to
+//! trust.
+
+use std::collections::HashSet;
+
+use serde_json::{Value, json};
+
+use crate::book::Book;
+use crate::error::{EngineError, Result};
+use crate::storage::Highlight;
+
+/// A reference-manager entry format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CitationFormat {
+    #[default]
+    Bibtex,
+    CslJson,
+    Ris,
+}
+
+impl std::str::FromStr for CitationFormat {
+    type Err = EngineError;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "bibtex" | "bib" => Ok(CitationFormat::Bibtex),
+            "csl" | "csl-json" | "json" => Ok(CitationFormat::CslJson),
+            "ris" => Ok(CitationFormat::Ris),
+            other => Err(EngineError::InvalidInput(format!(
+                "unknown citation format: {other} (bibtex, csl-json or ris)"
+            ))),
+        }
+    }
+}
+
+/// How a quotation is attributed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteStyle {
+    #[default]
+    Chicago,
+    Mla,
+    Apa,
+}
+
+impl QuoteStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuoteStyle::Chicago => "chicago",
+            QuoteStyle::Mla => "mla",
+            QuoteStyle::Apa => "apa",
+        }
+    }
+
+    /// The next style round, for a key that cycles them.
+    pub fn next(self) -> QuoteStyle {
+        match self {
+            QuoteStyle::Chicago => QuoteStyle::Mla,
+            QuoteStyle::Mla => QuoteStyle::Apa,
+            QuoteStyle::Apa => QuoteStyle::Chicago,
+        }
+    }
+}
+
+impl std::str::FromStr for QuoteStyle {
+    type Err = EngineError;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "chicago" => Ok(QuoteStyle::Chicago),
+            "mla" => Ok(QuoteStyle::Mla),
+            "apa" => Ok(QuoteStyle::Apa),
+            other => Err(EngineError::InvalidInput(format!(
+                "unknown quotation style: {other} (chicago, mla or apa)"
+            ))),
+        }
+    }
+}
+
+/// Entries for these books, as one file's worth: BibTeX and RIS entries one
+/// after another, CSL-JSON as the array citeproc reads.
+pub fn entries(books: &[Book], format: CitationFormat) -> String {
+    match format {
+        CitationFormat::CslJson => {
+            let items: Vec<Value> = books
+                .iter()
+                .zip(keys(books))
+                .map(|(b, k)| csl(b, &k))
+                .collect();
+            serde_json::to_string_pretty(&items).expect("json values serialize") + "\n"
+        }
+        CitationFormat::Bibtex => books
+            .iter()
+            .zip(keys(books))
+            .map(|(b, k)| bibtex(b, &k))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        CitationFormat::Ris => books.iter().map(ris).collect::<Vec<_>>().join("\n"),
+    }
+}
+
+/// Each book's [`cite_key`], made unique within the file: two editions of one
+/// book share author, year and title word, and a reference manager keeps only
+/// one entry per key. The first keeps its key; later ones take `b`, `c`, … the
+/// way a bibliography tells two works of one year apart.
+fn keys(books: &[Book]) -> Vec<String> {
+    let mut used = HashSet::new();
+    books
+        .iter()
+        .map(|book| {
+            let key = cite_key(book);
+            let key = std::iter::once(key.clone())
+                .chain(('b'..='z').map(|c| format!("{key}{c}")))
+                .chain((2..).map(|n| format!("{key}-{n}")))
+                .find(|k| !used.contains(k))
+                .expect("an unused key");
+            used.insert(key.clone());
+            key
+        })
+        .collect()
+}
+
+/// A highlight quoted in `style`: the passage, where it is, and the book it
+/// is from.
+pub fn quotation(book: &Book, h: &Highlight, style: QuoteStyle) -> String {
+    let text = h.text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let title = format!("*{}*", book.display_title());
+    let year = book.publish_year.map(|y| y.to_string());
+    let publisher = book.publisher.as_deref();
+    let isbn = book
+        .any_isbn()
+        .map(|i| format!(" ISBN {i}."))
+        .unwrap_or_default();
+    // A page when there is one; a chapter is the only other place to point.
+    let locator = h.page.map(|p| p.to_string()).or_else(|| {
+        h.chapter
+            .as_deref()
+            .filter(|c| !c.trim().is_empty())
+            .map(|c| format!("ch. “{}”", c.trim()))
+    });
+    match style {
+        QuoteStyle::Chicago => {
+            let mut note = format!("“{text}” ");
+            if !book.authors.is_empty() {
+                note.push_str(&format!("{}, ", listed(&book.authors, " and ")));
+            }
+            note.push_str(&title);
+            if !book.translators.is_empty() {
+                note.push_str(&format!(", trans. {}", listed(&book.translators, " and ")));
+            }
+            let imprint: Vec<&str> = [publisher, year.as_deref()].into_iter().flatten().collect();
+            if !imprint.is_empty() {
+                note.push_str(&format!(" ({})", imprint.join(", ")));
+            }
+            if let Some(l) = &locator {
+                note.push_str(&format!(", {l}"));
+            }
+            format!("{note}.{isbn}")
+        }
+        QuoteStyle::Mla => {
+            let who = mla_short(&book.authors);
+            let cite = [who.clone(), locator.clone()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let mut out = match cite.is_empty() {
+                true => format!("“{text}”"),
+                false => format!("“{text}” ({cite})."),
+            };
+            out.push_str("\n\n");
+            if let Some(first) = book.authors.first() {
+                out.push_str(&inverted(first));
+                match book.authors.len() {
+                    1 => {}
+                    2 => out.push_str(&format!(", and {}", book.authors[1])),
+                    _ => out.push_str(", et al"),
+                }
+                out.push_str(". ");
+            }
+            out.push_str(&format!("{title}. "));
+            if !book.translators.is_empty() {
+                out.push_str(&format!(
+                    "Translated by {}, ",
+                    listed(&book.translators, " and ")
+                ));
+            }
+            let imprint: Vec<&str> = [publisher, year.as_deref()].into_iter().flatten().collect();
+            if !imprint.is_empty() {
+                out.push_str(&format!("{}.", imprint.join(", ")));
+            }
+            format!("{}{isbn}", out.trim_end())
+        }
+        QuoteStyle::Apa => {
+            let who = match book.authors.len() {
+                0 => None,
+                1 => Some(family(&book.authors[0])),
+                2 => Some(format!(
+                    "{} & {}",
+                    family(&book.authors[0]),
+                    family(&book.authors[1])
+                )),
+                _ => Some(format!("{} et al.", family(&book.authors[0]))),
+            };
+            let when = year.clone().unwrap_or_else(|| "n.d.".into());
+            let cite = [
+                who,
+                Some(when.clone()),
+                locator.map(|l| match h.page {
+                    Some(_) => format!("p. {l}"),
+                    None => l,
+                }),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(", ");
+            let mut out = format!("“{text}” ({cite}).\n\n");
+            if !book.authors.is_empty() {
+                let names: Vec<String> = book.authors.iter().map(|a| apa_name(a)).collect();
+                out.push_str(&format!("{} ", listed(&names, " & ")));
+            }
+            out.push_str(&format!("({when}). {title}"));
+            if !book.translators.is_empty() {
+                out.push_str(&format!(" ({}, Trans.)", listed(&book.translators, " & ")));
+            }
+            out.push('.');
+            if let Some(p) = publisher {
+                out.push_str(&format!(" {p}."));
+            }
+            format!("{out}{isbn}")
+        }
+    }
+}
+
+/// `Lee2017Pachinko`-style, ASCII only: what a `\cite{}` can name.
+pub fn cite_key(book: &Book) -> String {
+    let ascii = |s: &str| -> String { s.chars().filter(char::is_ascii_alphanumeric).collect() };
+    let who = book
+        .authors
+        .first()
+        .map(|a| ascii(&family(a)))
+        .unwrap_or_default();
+    let word = book
+        .display_title()
+        .split_whitespace()
+        .map(ascii)
+        .find(|w| w.len() > 3 || !matches!(w.to_lowercase().as_str(), "a" | "an" | "the"))
+        .unwrap_or_default();
+    let key = format!(
+        "{}{}{}",
+        who.to_lowercase(),
+        book.publish_year.map(|y| y.to_string()).unwrap_or_default(),
+        word.to_lowercase()
+    );
+    match (key.is_empty(), book.id) {
+        (true, Some(id)) => format!("book{id}"),
+        (true, None) => "book".to_string(),
+        (false, _) => key,
+    }
+}
+
+fn bibtex(book: &Book, key: &str) -> String {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    if !book.authors.is_empty() {
+        let names: Vec<String> = book.authors.iter().map(|a| inverted(a)).collect();
+        fields.push(("author", names.join(" and ")));
+    }
+    // biblatex's field; plain BibTeX styles skip a field they do not know.
+    if !book.translators.is_empty() {
+        let names: Vec<String> = book.translators.iter().map(|a| inverted(a)).collect();
+        fields.push(("translator", names.join(" and ")));
+    }
+    fields.push(("title", book.display_title().to_string()));
+    if let Some(p) = &book.publisher {
+        fields.push(("publisher", p.clone()));
+    }
+    if let Some(y) = book.publish_year {
+        fields.push(("year", y.to_string()));
+    }
+    if let Some(i) = book.any_isbn() {
+        fields.push(("isbn", i.to_string()));
+    }
+    if let Some(l) = &book.language {
+        fields.push(("language", l.clone()));
+    }
+    if let Some(n) = book.page_count {
+        fields.push(("pagetotal", n.to_string()));
+    }
+    let mut out = format!("@book{{{key},\n");
+    for (k, v) in fields {
+        out.push_str(&format!("  {k:<10} = {{{}}},\n", bibtex_escape(&v)));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn csl(book: &Book, key: &str) -> Value {
+    let names = |list: &[String]| -> Vec<Value> {
+        list.iter()
+            .map(|n| json!({ "family": family(n), "given": given(n) }))
+            .collect()
+    };
+    let mut item = json!({
+        "id": key,
+        "type": "book",
+        "title": book.display_title(),
+    });
+    let obj = item.as_object_mut().expect("a json object");
+    if !book.authors.is_empty() {
+        obj.insert("author".into(), Value::Array(names(&book.authors)));
+    }
+    if !book.translators.is_empty() {
+        obj.insert("translator".into(), Value::Array(names(&book.translators)));
+    }
+    if let Some(p) = &book.publisher {
+        obj.insert("publisher".into(), json!(p));
+    }
+    if let Some(y) = book.publish_year {
+        obj.insert("issued".into(), json!({ "date-parts": [[y]] }));
+    }
+    if let Some(i) = book.any_isbn() {
+        obj.insert("ISBN".into(), json!(i));
+    }
+    if let Some(l) = &book.language {
+        obj.insert("language".into(), json!(l));
+    }
+    if let Some(n) = book.page_count {
+        obj.insert("number-of-pages".into(), json!(n));
+    }
+    item
+}
+
+fn ris(book: &Book) -> String {
+    let mut lines = vec![("TY", "BOOK".to_string())];
+    lines.extend(book.authors.iter().map(|a| ("AU", inverted(a))));
+    // `A4` is RIS's subsidiary author, which is where every manager reads a
+    // translator from.
+    lines.extend(book.translators.iter().map(|t| ("A4", inverted(t))));
+    lines.push(("TI", book.display_title().to_string()));
+    if let Some(p) = &book.publisher {
+        lines.push(("PB", p.clone()));
+    }
+    if let Some(y) = book.publish_year {
+        lines.push(("PY", y.to_string()));
+    }
+    if let Some(i) = book.any_isbn() {
+        lines.push(("SN", i.to_string()));
+    }
+    if let Some(l) = &book.language {
+        lines.push(("LA", l.clone()));
+    }
+    lines.push(("ER", String::new()));
+    lines
+        .into_iter()
+        .map(|(tag, v)| format!("{tag}  - {v}").trim_end().to_string() + "\n")
+        .collect()
+}
+
+/// The family name: before the comma of "Family, Given", else the last word.
+fn family(name: &str) -> String {
+    match name.split_once(',') {
+        Some((f, _)) => f.trim().to_string(),
+        None => name.split_whitespace().last().unwrap_or(name).to_string(),
+    }
+}
+
+fn given(name: &str) -> String {
+    match name.split_once(',') {
+        Some((_, g)) => g.trim().to_string(),
+        None => {
+            let words: Vec<&str> = name.split_whitespace().collect();
+            words[..words.len().saturating_sub(1)].join(" ")
+        }
+    }
+}
+
+/// "Family, Given" — how a reference list sorts.
+fn inverted(name: &str) -> String {
+    match given(name) {
+        g if g.is_empty() => family(name),
+        g => format!("{}, {g}", family(name)),
+    }
+}
+
+/// "Lee, M. J."
+fn apa_name(name: &str) -> String {
+    let initials: Vec<String> = given(name)
+        .split_whitespace()
+        .filter_map(|w| w.chars().next())
+        .map(|c| format!("{c}."))
+        .collect();
+    match initials.is_empty() {
+        true => family(name),
+        false => format!("{}, {}", family(name), initials.join(" ")),
+    }
+}
+
+/// MLA's in-text name: one family name, two joined, or the first *et al.*
+fn mla_short(authors: &[String]) -> Option<String> {
+    match authors {
+        [] => None,
+        [one] => Some(family(one)),
+        [a, b] => Some(format!("{} and {}", family(a), family(b))),
+        [a, ..] => Some(format!("{} et al.", family(a))),
+    }
+}
+
+/// "A", "A and B", "A, B and C" (with `and` as given).
+fn listed(names: &[String], and: &str) -> String {
+    match names {
+        [] => String::new(),
+        [one] => one.clone(),
+        [init @ .., last] => format!("{}{and}{last}", init.join(", ")),
+    }
+}
+
+fn bibtex_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pachinko() -> Book {
+        Book {
+            id: Some(1),
+            title: Some("Pachinko".into()),
+            authors: vec!["Min Jin Lee".into()],
+            publisher: Some("Grand Central".into()),
+            publish_year: Some(2017),
+            isbn_13: Some("9781455563937".into()),
+            ..Default::default()
+        }
+    }
+
+    fn passage() -> Highlight {
+        Highlight {
+            id: 7,
+            book_id: 1,
+            text: "History has failed us,\nbut no matter.".into(),
+            chapter: Some("Book I".into()),
+            page: Some(3),
+            ko_note: None,
+            annotation: None,
+            ko_datetime: None,
+            reading_id: None,
+            source: "koreader".into(),
+            created_at: 0,
+            device_id: None,
+        }
+    }
+
+    #[test]
+    fn a_book_is_an_entry_in_each_format() {
+        let bib = entries(&[pachinko()], CitationFormat::Bibtex);
+        assert!(bib.starts_with("@book{lee2017pachinko,\n"), "{bib}");
+        assert!(bib.contains("author     = {Lee, Min Jin},"), "{bib}");
+        assert!(bib.contains("isbn       = {9781455563937},"), "{bib}");
+
+        let csl: Value = serde_json::from_str(&entries(&[pachinko()], CitationFormat::CslJson))
+            .expect("valid json");
+        assert_eq!(csl[0]["author"][0]["family"], "Lee");
+        assert_eq!(csl[0]["author"][0]["given"], "Min Jin");
+        assert_eq!(csl[0]["issued"]["date-parts"][0][0], 2017);
+
+        let ris = entries(&[pachinko()], CitationFormat::Ris);
+        assert!(ris.starts_with("TY  - BOOK\nAU  - Lee, Min Jin\n"), "{ris}");
+        assert!(ris.ends_with("ER  -\n"), "{ris}");
+    }
+
+    #[test]
+    fn two_editions_get_their_own_keys() {
+        let paperback = Book {
+            id: Some(2),
+            isbn_13: Some("9781455563920".into()),
+            ..pachinko()
+        };
+        let books = [pachinko(), paperback];
+
+        let bib = entries(&books, CitationFormat::Bibtex);
+        assert!(bib.contains("@book{lee2017pachinko,\n"), "{bib}");
+        assert!(bib.contains("@book{lee2017pachinkob,\n"), "{bib}");
+
+        let csl: Value =
+            serde_json::from_str(&entries(&books, CitationFormat::CslJson)).expect("valid json");
+        assert_eq!(csl[0]["id"], "lee2017pachinko");
+        assert_eq!(csl[1]["id"], "lee2017pachinkob");
+    }
+
+    #[test]
+    fn a_highlight_is_quoted_with_its_page_and_edition() {
+        let q = quotation(&pachinko(), &passage(), QuoteStyle::Chicago);
+        assert_eq!(
+            q,
+            "“History has failed us, but no matter.” Min Jin Lee, *Pachinko* \
+             (Grand Central, 2017), 3. ISBN 9781455563937."
+        );
+        let q = quotation(&pachinko(), &passage(), QuoteStyle::Mla);
+        assert!(
+            q.starts_with("“History has failed us, but no matter.” (Lee 3)."),
+            "{q}"
+        );
+        assert!(
+            q.contains("Lee, Min Jin. *Pachinko*. Grand Central, 2017."),
+            "{q}"
+        );
+        let q = quotation(&pachinko(), &passage(), QuoteStyle::Apa);
+        assert!(q.contains("(Lee, 2017, p. 3)."), "{q}");
+        assert!(
+            q.contains("Lee, M. J. (2017). *Pachinko*. Grand Central."),
+            "{q}"
+        );
+        assert!(q.ends_with("ISBN 9781455563937."), "{q}");
+    }
+
+    #[test]
+    fn a_pageless_highlight_points_at_its_chapter() {
+        let mut h = passage();
+        h.page = None;
+        let q = quotation(&pachinko(), &h, QuoteStyle::Chicago);
+        assert!(q.contains("2017), ch. “Book I”."), "{q}");
+    }
+
+    #[test]
+    fn bibtex_special_characters_are_escaped() {
+        let mut b = pachinko();
+        b.title = Some("Salt & Pepper_50%".into());
+        let bib = entries(&[b], CitationFormat::Bibtex);
+        assert!(bib.contains("{Salt \\& Pepper\\_50\\%}"), "{bib}");
+    }
+}
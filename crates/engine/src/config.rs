@@ -22,6 +22,12 @@ pub struct EngineConfig {
     /// gitignored, and it moves with `--data-dir` — so a sandbox run writes its
     /// logs into the sandbox instead of the user's home.
     pub log_dir: PathBuf,
+    /// The reader's note templates (`<kind>.md`, `<tag>/<kind>.md`, and any
+    /// others chosen by name); see [`crate::templates`].
+    ///
+    /// Under the data root, not in the vault: a template is not a note, and a
+    /// file of `{{placeholders}}` would be indexed, linked and searched as one.
+    pub templates_dir: PathBuf,
     /// Optional Google Books API key (keyless works at lower quota).
     pub google_api_key: Option<String>,
     /// A directory to look for calibre's command line tools in, **before**
@@ -46,6 +52,7 @@ impl EngineConfig {
             files_dir: root.join("database/files"),
             vault_dir: root.join("vault"),
             log_dir: root.join("logs"),
+            templates_dir: root.join("templates"),
             google_api_key: std::env::var("GOOGLE_BOOKS_API_KEY").ok(),
             calibre_bin_dir: None,
         }
@@ -65,6 +72,7 @@ mod tests {
         assert_eq!(c.vault_dir, PathBuf::from("/tmp/rb/vault"));
         // Logs must follow --data-dir, or a sandbox run scribbles in $HOME.
         assert_eq!(c.log_dir, PathBuf::from("/tmp/rb/logs"));
+        assert_eq!(c.templates_dir, PathBuf::from("/tmp/rb/templates"));
     }
 
     #[test]
@@ -1,4 +1,5 @@
-//! Reading a mounted KOReader device.
+//! Reading a mounted KOReader device — or a stock Kobo's own database, which
+//! sits on the same kind of volume and fills the same screen.
 //!
 //! Two verbs, and the difference between them is the whole module: [`scan_device`]
 //! is read-only and answers *what is the state of each book on this device*;
@@ -18,6 +19,7 @@ use std::path::{Path, PathBuf};
 
 use crate::diagnostic::Diagnostic;
 use crate::error::{EngineError, Result};
+use crate::kobo::{self, KoboBook};
 use crate::koreader::{
     self, KoSidecar, KoStatus, MatchCandidate, MatchMethod, PullReport, find_sidecars,
     parse_sidecar,
@@ -109,24 +111,29 @@ pub fn mount_roots() -> Vec<PathBuf> {
 
 /// Would we offer this path as a mounted reader?
 ///
-/// [`is_koreader_mount`] plus the symlink rule, in one place because the watcher
-/// and the lister must agree: a volume one of them announces and the other never
-/// lists is a device the user is told about and cannot open.
+/// [`is_koreader_mount`] or a Kobo's own database, plus the symlink rule, in one
+/// place because the watcher and the lister must agree: a volume one of them
+/// announces and the other never lists is a device the user is told about and
+/// cannot open.
+///
+/// Deliberately wider than [`is_koreader_mount`], which stays the gate for
+/// *writing* to a volume: a stock Kobo can be read, but there is no KOReader on
+/// it to install a plugin into.
 pub fn offers_reader(path: &Path) -> bool {
     // `/Volumes/Macintosh HD` is a symlink to `/` on macOS. Following it would
     // offer the boot disk as a removable device.
     let is_link = std::fs::symlink_metadata(path)
         .map(|m| m.file_type().is_symlink())
         .unwrap_or(false);
-    !is_link && is_koreader_mount(path)
+    !is_link && (is_koreader_mount(path) || kobo::is_kobo_mount(path))
 }
 
-/// Mounted volumes that hold a KOReader install.
+/// Mounted volumes that hold a reader we can read: a KOReader install, or a
+/// stock Kobo.
 ///
 /// Filtered rather than listed: an unfiltered list of `/Volumes` is something
 /// the caller could produce itself, and every caller would then have to
-/// re-apply [`is_koreader_mount`] — including the one that is about to write to
-/// the volume.
+/// re-apply [`offers_reader`].
 pub fn candidate_mounts() -> Vec<PathBuf> {
     let mut found = Vec::new();
     for root in mount_roots() {
@@ -190,10 +197,32 @@ impl DeviceState {
     }
 }
 
+/// Which of the reader's own records a device row was read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeviceSource {
+    /// A KOReader sidecar. `DeviceBook::path` is the `metadata.*.lua`.
+    #[default]
+    Koreader,
+    /// A stock Kobo's `KoboReader.sqlite`. `DeviceBook::path` is the book's
+    /// file on the mount — see [`kobo::locate`].
+    Kobo,
+}
+
+impl DeviceSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeviceSource::Koreader => "koreader",
+            DeviceSource::Kobo => "kobo",
+        }
+    }
+}
+
 /// One book as it exists on the device.
 #[derive(Debug, Clone)]
 pub struct DeviceBook {
+    /// What a sync is handed to bring this book across. Unique per row.
     pub path: PathBuf,
+    pub source: DeviceSource,
     pub title: Option<String>,
     pub authors: Option<String>,
     pub partial_md5: Option<String>,
@@ -249,7 +278,10 @@ impl DeviceScan {
 /// book on it. Read-only.
 ///
 /// Never fails on one bad file: an unreadable or unparsable sidecar becomes a
-/// row in [`DeviceState::Unreadable`], not an error.
+/// row in [`DeviceState::Unreadable`], not an error. A Kobo's own database is
+/// read too when the root is a Kobo mount, and its books listed after the
+/// sidecars'; a database that will not open is a warning, and the sidecars
+/// still list.
 #[tracing::instrument(skip(storage), fields(root = %root.display()))]
 pub async fn scan_device(storage: &Storage, root: &Path) -> Result<DeviceScan> {
     // One token for the whole scan, stamped onto every cache row it touches.
@@ -262,7 +294,8 @@ pub async fn scan_device(storage: &Storage, root: &Path) -> Result<DeviceScan> {
     };
 
     let sidecars = find_sidecars(root)?;
-    if sidecars.is_empty() {
+    let kobo_db = kobo::kobo_db(root).filter(|_| root.is_dir());
+    if sidecars.is_empty() && kobo_db.is_none() {
         tracing::warn!(root = %root.display(), "no KOReader sidecars found");
         scan.warnings
             .push(Diagnostic::no_sidecars_found(root.to_path_buf()));
@@ -276,6 +309,10 @@ pub async fn scan_device(storage: &Storage, root: &Path) -> Result<DeviceScan> {
     }
     (scan.parsed, scan.cached) = counts;
 
+    if let Some(db) = kobo_db {
+        scan_kobo(storage, &db, &mut scan).await?;
+    }
+
     // A sidecar deleted from the device leaves a cache row that will never be
     // seen again. Scoped to this root, so unplugging one device does not forget
     // another.
@@ -363,6 +400,7 @@ async fn scan_one(
     let ko_status = facts.ko_status.as_deref().map(KoStatus::from);
     let mut book = DeviceBook {
         path: path.to_path_buf(),
+        source: DeviceSource::Koreader,
         title: facts.title.clone(),
         authors: facts.authors.clone(),
         partial_md5: facts.partial_md5.clone(),
@@ -441,11 +479,79 @@ async fn scan_one(
     Ok(book)
 }
 
+/// The Kobo half of a scan: every book Nickel has opened, with the state the
+/// sidecar rows get. No cache — the whole database is two queries, which is
+/// less work than the `stat` calls the sidecar cache exists to save.
+async fn scan_kobo(storage: &Storage, db: &Path, scan: &mut DeviceScan) -> Result<()> {
+    let books = match kobo::read_library(db).await {
+        Ok(books) => books,
+        Err(err) => {
+            tracing::warn!(path = %db.display(), error = %err, "Kobo database unreadable");
+            scan.warnings
+                .push(Diagnostic::kobo_unreadable(db.to_path_buf(), &err));
+            return Ok(());
+        }
+    };
+    for kb in books {
+        scan.books.push(scan_kobo_book(storage, kb).await?);
+    }
+    Ok(())
+}
+
+async fn scan_kobo_book(storage: &Storage, kb: KoboBook) -> Result<DeviceBook> {
+    let md5 = kb.partial_md5();
+    let mut book = DeviceBook {
+        path: kb.path.clone(),
+        source: DeviceSource::Kobo,
+        title: kb.title.clone(),
+        authors: (!kb.authors.is_empty()).then(|| kb.authors.join("\n")),
+        partial_md5: md5.clone(),
+        book_id: None,
+        matched_by: None,
+        state: DeviceState::Unchanged,
+        ko_percent: kb.percent,
+        ko_status: kb.status.clone(),
+    };
+    let Some((matched, matched_by)) = kobo::match_book(storage, &kb, md5.as_deref()).await? else {
+        book.state = DeviceState::New {
+            candidates: kobo::match_candidates(storage, &kb).await?,
+        };
+        return Ok(book);
+    };
+    let Some(book_id) = matched.id else {
+        tracing::error!(title = %matched.display_title(), "matched book has no id");
+        book.state = DeviceState::New {
+            candidates: Vec::new(),
+        };
+        return Ok(book);
+    };
+    book.book_id = Some(book_id);
+    book.matched_by = Some(matched_by);
+
+    let mut new_highlights = 0;
+    let mut refreshed = 0;
+    for h in &kb.highlights {
+        if !storage.highlight_exists(book_id, h).await? {
+            new_highlights += 1;
+        } else if storage.device_fields_differ(book_id, h).await? {
+            refreshed += 1;
+        }
+    }
+    if new_highlights > 0 || refreshed > 0 {
+        book.state = DeviceState::Updated {
+            new_highlights,
+            refreshed,
+        };
+    }
+    Ok(book)
+}
+
 /// A row for a file we could not read. Everything but the path is unknown, by
 /// definition — that is what makes it unreadable.
 fn unreadable(path: &Path, diagnostic: Diagnostic) -> DeviceBook {
     DeviceBook {
         path: path.to_path_buf(),
+        source: DeviceSource::Koreader,
         title: None,
         authors: None,
         partial_md5: None,
@@ -530,10 +636,18 @@ fn nanos(t: std::time::SystemTime) -> i64 {
 /// A sidecar that fails to read or parse is an **error** here, unlike in a
 /// scan. A scan is a view and degrades; a sync is something the user asked for
 /// by name, and a silent success with no book is worse than a refusal.
+///
+/// A Kobo row's path is the book's file rather than a sidecar, and goes to
+/// [`kobo::pull`]; a directory still means its sidecars only, so pointing a
+/// sync at a Kobo's mount does not create every book Nickel ever opened.
 #[tracing::instrument(skip_all, fields(paths = paths.len()))]
 pub async fn sync_device(storage: &Storage, paths: &[PathBuf]) -> Result<Vec<PullReport>> {
     let mut out = Vec::new();
     for path in paths {
+        if !path.is_dir() && !koreader::is_sidecar_file(path) && kobo::locate(path).is_some() {
+            out.push(kobo::pull(storage, path).await?);
+            continue;
+        }
         for sidecar in find_sidecars(path)? {
             out.push(sync_one(storage, &sidecar).await?);
         }
@@ -629,10 +743,21 @@ mod tests {
     #[test]
     fn every_offered_mount_is_one_we_would_accept() {
         for m in candidate_mounts() {
-            assert!(is_koreader_mount(&m), "{} was offered", m.display());
+            assert!(offers_reader(&m), "{} was offered", m.display());
         }
     }
 
+    /// A stock Kobo is offered as a reader, and is still not somewhere a
+    /// plugin could be installed.
+    #[test]
+    fn a_stock_kobo_is_offered_but_not_writable() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join(".kobo")).unwrap();
+        std::fs::write(tmp.path().join(kobo::KOBO_DB), b"").unwrap();
+        assert!(offers_reader(tmp.path()));
+        assert!(!is_koreader_mount(tmp.path()));
+    }
+
     #[test]
     fn a_mount_is_recognised_by_its_contents_not_its_name() {
         let tmp = tempfile::tempdir().unwrap();
@@ -21,6 +21,10 @@ use crate::providers::ProviderId;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Severity {
+    /// Nothing went wrong: the engine changed something by itself that the user
+    /// did not ask for in so many words, and should hear about. The vault
+    /// watcher is the one source today.
+    Info,
     /// The operation continued and returned partial results.
     Warning,
     /// The operation could not proceed.
@@ -190,6 +194,68 @@ pub enum DiagnosticKind {
     CalibreCoverUnreadable {
         path: PathBuf,
     },
+
+    // ---- kindle ------------------------------------------------------------
+    /// A `My Clippings.txt` entry that could not be read, or whose text the
+    /// Kindle withheld. `entry` is 1-based, counted in separators, which is
+    /// how the reader will have to find it.
+    KindleEntrySkipped {
+        entry: usize,
+    },
+    /// The entry imported, but its `Added on` was in a form we do not read, so
+    /// it is stored undated.
+    KindleDateUnread {
+        entry: usize,
+    },
+
+    // ---- kobo --------------------------------------------------------------
+    /// `.kobo/KoboReader.sqlite` was there but could not be opened or read —
+    /// most often because the Kobo was still writing to it when it was
+    /// mounted. A device scan goes on to list the KOReader books beside it.
+    KoboDatabaseUnreadable {
+        path: PathBuf,
+        class: ErrorClass,
+    },
+    /// A Kobo book whose file is not on the device (archived, or a store book
+    /// never downloaded), so there is no `partial_md5` to key a
+    /// `device_books` link on. The Kobo twin of
+    /// [`DiagnosticKind::SidecarNotIdentified`]: the pull happens, and the next
+    /// one has only the title to go on.
+    KoboBookNotIdentified {
+        path: PathBuf,
+    },
+
+    // ---- the vault watcher -------------------------------------------------
+    //
+    // Every `path` here is relative to `vault_dir`, as `notes.file_path` is:
+    // the vault is the user's own folder, and its absolute location is noise in
+    // a status line.
+    /// A note edited outside readingbuddy, re-read into the index.
+    NoteRefreshed {
+        path: PathBuf,
+    },
+    /// A markdown file that appeared in the vault and was not a note of ours
+    /// before. Indexed as it stands — nothing is written back into it.
+    NoteIndexed {
+        path: PathBuf,
+    },
+    /// A note moved (or renamed) outside readingbuddy. Same row, new path, so
+    /// its citations and the links into it survive the move.
+    NoteMoved {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// A note whose file left the vault, taken out of the index (migration
+    /// `0016`).
+    NoteTombstoned {
+        path: PathBuf,
+    },
+    /// A markdown file that could not be read — most often a half-synced file
+    /// or one that is not UTF-8. Its row, if it has one, is left as it was.
+    NoteUnreadable {
+        path: PathBuf,
+        class: ErrorClass,
+    },
 }
 
 /// One degradation, carried in-band on a partly-successful result.
@@ -243,6 +309,26 @@ impl Diagnostic {
         }
     }
 
+    pub fn kobo_unreadable(path: PathBuf, err: &EngineError) -> Self {
+        Diagnostic {
+            kind: DiagnosticKind::KoboDatabaseUnreadable {
+                path,
+                class: ErrorClass::from(err),
+            },
+            severity: Severity::Warning,
+            detail: err.to_string(),
+        }
+    }
+
+    pub fn kobo_not_identified(path: PathBuf) -> Self {
+        Diagnostic {
+            kind: DiagnosticKind::KoboBookNotIdentified { path },
+            severity: Severity::Warning,
+            detail: "its file is not on the device; a second pull has only the title to go on"
+                .to_string(),
+        }
+    }
+
     pub fn no_sidecars_found(path: PathBuf) -> Self {
         Diagnostic {
             kind: DiagnosticKind::NoSidecarsFound { path },
@@ -271,6 +357,49 @@ impl Diagnostic {
         }
     }
 
+    pub fn note_refreshed(path: PathBuf) -> Self {
+        Diagnostic {
+            kind: DiagnosticKind::NoteRefreshed { path },
+            severity: Severity::Info,
+            detail: "edited outside readingbuddy; re-indexed".to_string(),
+        }
+    }
+
+    pub fn note_indexed(path: PathBuf) -> Self {
+        Diagnostic {
+            kind: DiagnosticKind::NoteIndexed { path },
+            severity: Severity::Info,
+            detail: "new in the vault; indexed".to_string(),
+        }
+    }
+
+    pub fn note_moved(from: PathBuf, to: PathBuf) -> Self {
+        Diagnostic {
+            detail: format!("moved from {}", from.display()),
+            kind: DiagnosticKind::NoteMoved { from, to },
+            severity: Severity::Info,
+        }
+    }
+
+    pub fn note_tombstoned(path: PathBuf) -> Self {
+        Diagnostic {
+            kind: DiagnosticKind::NoteTombstoned { path },
+            severity: Severity::Info,
+            detail: "gone from the vault; taken out of the index".to_string(),
+        }
+    }
+
+    pub fn note_unreadable(path: PathBuf, err: &EngineError) -> Self {
+        Diagnostic {
+            kind: DiagnosticKind::NoteUnreadable {
+                path,
+                class: ErrorClass::from(err),
+            },
+            severity: Severity::Warning,
+            detail: err.to_string(),
+        }
+    }
+
     /// The provider this diagnostic is about, if any.
     pub fn provider(&self) -> Option<ProviderId> {
         match self.kind {
@@ -339,6 +468,21 @@ impl fmt::Display for Diagnostic {
             DiagnosticKind::CalibreCoverUnreadable { path } => {
                 write!(f, "{}: {}", path.display(), self.detail)
             }
+            DiagnosticKind::KindleEntrySkipped { entry }
+            | DiagnosticKind::KindleDateUnread { entry } => {
+                write!(f, "clipping {entry}: {}", self.detail)
+            }
+            DiagnosticKind::KoboDatabaseUnreadable { path, .. }
+            | DiagnosticKind::KoboBookNotIdentified { path } => {
+                write!(f, "{}: {}", path.display(), self.detail)
+            }
+            DiagnosticKind::NoteRefreshed { path }
+            | DiagnosticKind::NoteIndexed { path }
+            | DiagnosticKind::NoteMoved { to: path, .. }
+            | DiagnosticKind::NoteTombstoned { path }
+            | DiagnosticKind::NoteUnreadable { path, .. } => {
+                write!(f, "{}: {}", path.display(), self.detail)
+            }
         }
     }
 }
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 use crate::providers::ProviderId;
@@ -37,6 +38,29 @@ pub enum EngineError {
     Epub(String),
     #[error("koreader sidecar error: {0}")]
     Sidecar(String),
+    /// A push was refused: the sidecar on the device is not the file we last
+    /// pulled from.
+    ///
+    /// Its own variant because the answer is always the same and is never
+    /// "try again": pull, look at what the device did, then push. Writing
+    /// anyway is exactly the outcome the check exists to rule out.
+    #[error("{} changed on the device since the last pull ({reason}) — pull it first", .path.display())]
+    DeviceChanged { path: PathBuf, reason: String },
+    /// The plugin installer will not touch this directory: it is a newer
+    /// version than this build carries, or not one readingbuddy wrote.
+    ///
+    /// Its own variant because it is an answer, not a fault: the reader is
+    /// fine, the install is simply not ours to change, and a frontend says so
+    /// rather than offering a retry.
+    #[error("will not touch {}: {reason}", .dir.display())]
+    PluginRefused { dir: PathBuf, reason: String },
+    /// A pairing code that is wrong, spent or expired, or a token that is not
+    /// — or is no longer — a paired reader's.
+    ///
+    /// Its own variant because the reader on the other end must tell "pair
+    /// again" apart from every fault it could usefully retry.
+    #[error("not paired: {0}")]
+    NotPaired(String),
     /// Calibre is not installed, or not the half of it this feature needs.
     ///
     /// Its own variant because **absent is a first-class answer here**, not a
@@ -0,0 +1,418 @@
+//! A review, published: one page that stands on its own outside the vault.
+//!
+//! Everything on it is read at export time, which is what keeps it honest. The
+//! passages are the review's [citations](crate::Engine::cite), looked up in the
+//! highlights table rather than copied into the body when they were cited, so
+//! a highlight corrected on the device since is exported corrected. The rating
+//! is the raw value on the scale it was given on, never the Goodreads mapping.
+//!
+//! Two formats. **HTML** is one self-contained file — the cover inlined as a
+//! `data:` URI, the styling in a `<style>` block — because it is meant to be
+//! mailed, uploaded or opened from anywhere. **Markdown** is for pasting into
+//! something that renders its own; it names the cover by path and leaves the
+//! rest to whatever shows it.
+//!
+//! A `[[wikilink]]` in the body is the vault's, and means nothing to a reader
+//! of the page: it is written out as its alias, or its title. An embed
+//! (`![[…]]`) is dropped.
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use pulldown_cmark::{Event, Options, Parser, html};
+use regex::{Captures, Regex};
+use time::OffsetDateTime;
+
+use crate::book::Book;
+use crate::error::{EngineError, Result};
+use crate::notes::NoteKind;
+use crate::storage::{Highlight, NoteRecord, Rating, Reading, Storage, format_day};
+
+/// What a review is exported as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Html,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Markdown => "md",
+        }
+    }
+
+    /// The format a file name asks for: `.md` / `.markdown` is Markdown, and
+    /// anything else HTML, which is the one that needs nothing beside it.
+    pub fn for_path(path: &Path) -> ExportFormat {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown") => {
+                ExportFormat::Markdown
+            }
+            _ => ExportFormat::Html,
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = EngineError;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "html" | "htm" => Ok(ExportFormat::Html),
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            other => Err(EngineError::InvalidInput(format!(
+                "unknown export format: {other} (html or md)"
+            ))),
+        }
+    }
+}
+
+/// A cover image, read off disk for inlining.
+#[derive(Debug)]
+struct Cover {
+    path: PathBuf,
+    mime: &'static str,
+    bytes: Vec<u8>,
+}
+
+impl Cover {
+    /// The book's cover, when it has one a browser can show. One gone from
+    /// disk leaves the page without it; it is decoration, and the review is
+    /// still the review.
+    fn of(book: &Book) -> Option<Cover> {
+        let path = PathBuf::from(book.cover_path.as_deref()?);
+        let mime = image_mime(&path)?;
+        let bytes = std::fs::read(&path).ok()?;
+        Some(Cover { path, mime, bytes })
+    }
+}
+
+/// Everything one review page shows.
+#[derive(Debug)]
+pub(crate) struct ReviewPage {
+    title: String,
+    book: Option<Book>,
+    reading: Option<Reading>,
+    rating: Option<Rating>,
+    body: String,
+    citations: Vec<Highlight>,
+}
+
+impl ReviewPage {
+    /// Gather a review's page. `body` is the note's, frontmatter off.
+    pub(crate) async fn gather(
+        storage: &Storage,
+        note: &NoteRecord,
+        body: String,
+    ) -> Result<ReviewPage> {
+        if note.kind != NoteKind::Review.as_str() {
+            return Err(EngineError::InvalidInput(format!(
+                "note id {} is a {}, not a review — only a review is written to be read",
+                note.id, note.kind
+            )));
+        }
+        let book = match note.book_id {
+            Some(id) => storage.get_book(id).await?,
+            None => None,
+        };
+        let reading = match note.reading_id {
+            Some(id) => storage.get_reading(id).await?,
+            None => None,
+        };
+        Ok(ReviewPage {
+            title: note.title.clone(),
+            book,
+            reading,
+            rating: storage.review_rating(note.id).await?,
+            body,
+            citations: storage.citations_for(note.id).await?,
+        })
+    }
+
+    pub(crate) fn render(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Html => self.html(),
+            ExportFormat::Markdown => self.markdown(),
+        }
+    }
+
+    fn cover(&self) -> Option<Cover> {
+        self.book.as_ref().and_then(Cover::of)
+    }
+
+    fn heading(&self) -> &str {
+        self.book
+            .as_ref()
+            .map_or(self.title.as_str(), |b| b.display_title())
+    }
+
+    fn byline(&self) -> Option<String> {
+        self.book
+            .as_ref()
+            .filter(|b| !b.authors.is_empty())
+            .map(|b| b.authors.join(", "))
+    }
+
+    fn rating_line(&self) -> Option<String> {
+        self.rating
+            .as_ref()
+            .map(|r| format!("{} / {}", r.value, r.scale.max))
+    }
+
+    fn read_line(&self) -> Option<String> {
+        self.reading.as_ref().and_then(reading_dates)
+    }
+
+    fn markdown(&self) -> String {
+        let mut out = format!("# {}\n", self.heading());
+        if let Some(by) = self.byline() {
+            out.push_str(&format!("\n*by {by}*\n"));
+        }
+        if let Some(c) = self.cover() {
+            out.push_str(&format!(
+                "\n![Cover of {}](<{}>)\n",
+                self.heading(),
+                c.path.display()
+            ));
+        }
+        let facts: Vec<String> = [
+            self.rating_line().map(|r| format!("**Rating:** {r}")),
+            self.read_line().map(|r| format!("**Read:** {r}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !facts.is_empty() {
+            out.push_str(&format!("\n{}\n", facts.join("  \n")));
+        }
+        let body = unlink(&self.body);
+        if !body.trim().is_empty() {
+            out.push_str(&format!("\n{}\n", body.trim()));
+        }
+        if !self.citations.is_empty() {
+            out.push_str("\n## Passages\n");
+            for h in &self.citations {
+                out.push('\n');
+                for line in h.text.trim().lines() {
+                    out.push_str(&format!("> {line}\n"));
+                }
+                if let Some(w) = whereabouts(h) {
+                    out.push_str(&format!(">\n> — {w}\n"));
+                }
+            }
+        }
+        out
+    }
+
+    fn html(&self) -> String {
+        let mut head = String::new();
+        if let Some(c) = self.cover() {
+            head.push_str(&format!(
+                "<img class=\"cover\" src=\"data:{};base64,{}\" alt=\"Cover of {}\">\n",
+                c.mime,
+                STANDARD.encode(&c.bytes),
+                escape(self.heading())
+            ));
+        }
+        head.push_str(&format!("<h1>{}</h1>\n", escape(self.heading())));
+        if let Some(by) = self.byline() {
+            head.push_str(&format!("<p class=\"byline\">by {}</p>\n", escape(&by)));
+        }
+        head.push_str(&self.facts_html());
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<article>\n\
+             <header>\n{head}</header>\n{}</article>\n</body>\n</html>\n",
+            escape(&self.title),
+            self.review_html()
+        )
+    }
+
+    /// The rating and the reading's dates, as one line.
+    pub(crate) fn facts_html(&self) -> String {
+        let facts: Vec<String> = [
+            self.rating_line()
+                .map(|r| format!("<span class=\"rating\">{}</span>", escape(&r))),
+            self.read_line()
+                .map(|r| format!("<span class=\"read\">read {}</span>", escape(&r))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if facts.is_empty() {
+            String::new()
+        } else {
+            format!("<p class=\"facts\">{}</p>\n", facts.join(" · "))
+        }
+    }
+
+    /// The review itself — body, then its passages — without the page around
+    /// it. The site's book page is this under the book's own header.
+    ///
+    /// Raw HTML in the body is written out as text, not passed through: the
+    /// page is published, and a `<script>` pasted into a note is not the
+    /// reader deciding to run it on everyone who opens the site.
+    pub(crate) fn review_html(&self) -> String {
+        let mut out = String::new();
+        let body = unlink(&self.body);
+        let events = Parser::new_ext(&body, Options::ENABLE_TABLES).map(|e| match e {
+            Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+            e => e,
+        });
+        html::push_html(&mut out, events);
+        if !self.citations.is_empty() {
+            out.push_str("<section class=\"passages\">\n<h2>Passages</h2>\n");
+            for h in &self.citations {
+                let text: Vec<String> = h.text.trim().lines().map(escape).collect();
+                out.push_str(&format!("<blockquote>\n<p>{}</p>\n", text.join("<br>\n")));
+                if let Some(w) = whereabouts(h) {
+                    out.push_str(&format!("<footer>{}</footer>\n", escape(&w)));
+                }
+                out.push_str("</blockquote>\n");
+            }
+            out.push_str("</section>\n");
+        }
+        out
+    }
+}
+
+/// Enough to read comfortably on a phone and print cleanly; nothing fetched.
+pub(crate) const STYLE: &str = "
+body { max-width: 40rem; margin: 2rem auto; padding: 0 1rem;
+       font: 1.05rem/1.6 Georgia, 'Times New Roman', serif; color: #222; }
+header { overflow: hidden; margin-bottom: 1.5rem; }
+.cover { float: right; max-width: 8rem; margin: 0 0 1rem 1rem; }
+h1 { margin-bottom: 0.2rem; }
+.byline, .facts { margin: 0.2rem 0; color: #555; }
+blockquote { margin: 1rem 0; padding-left: 1rem; border-left: 3px solid #ccc; }
+blockquote footer { font-size: 0.9rem; color: #666; }
+";
+
+/// `2026-01-01 – 2026-02-03`, or `since 2026-01-01` while it is open.
+pub(crate) fn reading_dates(r: &Reading) -> Option<String> {
+    match (r.started_at.and_then(day), r.finished_at.and_then(day)) {
+        (Some(s), Some(f)) => Some(format!("{s} – {f}")),
+        (None, Some(f)) => Some(format!("finished {f}")),
+        (Some(s), None) => Some(format!("since {s}")),
+        (None, None) => None,
+    }
+}
+
+/// `p. 40 · Chapter 3`: where in the book a passage is.
+fn whereabouts(h: &Highlight) -> Option<String> {
+    let parts: Vec<String> = [
+        h.page.map(|p| format!("p. {p}")),
+        h.chapter.clone().filter(|c| !c.trim().is_empty()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+/// The body with its `[[wikilinks]]` written out as the words they show.
+fn unlink(body: &str) -> String {
+    let re =
+        Regex::new(r"(!)?\[\[([^\]\|#]*)(#[^\]\|]*)?(?:\|([^\]]*))?\]\]").expect("static regex");
+    re.replace_all(body, |c: &Captures| {
+        if c.get(1).is_some() {
+            return String::new();
+        }
+        if let Some(alias) = c.get(4) {
+            return alias.as_str().trim().to_string();
+        }
+        let target = c[2].trim();
+        if target.is_empty() {
+            // `[[#Heading]]`, a link within the note: the heading's words.
+            c.get(3)
+                .map(|a| a.as_str().trim_start_matches(['#', '^']).trim().to_string())
+                .unwrap_or_default()
+        } else {
+            target.to_string()
+        }
+    })
+    .into_owned()
+}
+
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub(crate) fn day(at: i64) -> Option<String> {
+    OffsetDateTime::from_unix_timestamp(at)
+        .ok()
+        .map(|t| format_day(t.date()))
+}
+
+/// The image types a browser shows inline, by extension — the same ones the
+/// cover downloader and the epub extractor write.
+pub(crate) fn image_mime(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    Some(match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wikilinks_read_as_their_words() {
+        assert_eq!(
+            unlink("Like [[Han]], [[Sunja|her]], [[Han#Origins]] and [[#Ending]].![[map.png]]"),
+            "Like Han, her, Han and Ending."
+        );
+    }
+
+    #[test]
+    fn formats_parse_and_follow_the_file_name() {
+        assert_eq!(
+            "MD".parse::<ExportFormat>().unwrap(),
+            ExportFormat::Markdown
+        );
+        assert_eq!("html".parse::<ExportFormat>().unwrap(), ExportFormat::Html);
+        assert!("pdf".parse::<ExportFormat>().is_err());
+        assert_eq!(
+            ExportFormat::for_path(Path::new("out/review.markdown")),
+            ExportFormat::Markdown
+        );
+        assert_eq!(
+            ExportFormat::for_path(Path::new("review")),
+            ExportFormat::Html
+        );
+    }
+
+    #[test]
+    fn html_escapes_what_the_reader_typed() {
+        let page = ReviewPage {
+            title: "Review: <b>".into(),
+            book: None,
+            reading: None,
+            rating: None,
+            body: "**fine** & <i>dandy</i>\n\n<script>alert(1)</script>\n".into(),
+            citations: Vec::new(),
+        };
+        let html = page.html();
+        assert!(html.contains("<title>Review: &lt;b&gt;</title>"), "{html}");
+        assert!(html.contains("<strong>fine</strong>"), "{html}");
+        assert!(html.contains("&amp; &lt;i&gt;dandy&lt;/i&gt;"), "{html}");
+        assert!(
+            html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"),
+            "{html}"
+        );
+        assert!(!html.contains("<script>"), "{html}");
+    }
+}
@@ -1,5 +1,51 @@
+use std::path::Path;
+
+use crate::error::{EngineError, Result};
 use crate::storage::FlashcardRow;
 
+/// What the Anki export is written as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CardFormat {
+    /// Three tab-separated columns for Anki's text importer: word, context,
+    /// book title. Words only.
+    #[default]
+    Tsv,
+    /// A package Anki opens as-is — note types, covers, and a GUID per note
+    /// so a second import updates the first. See [`crate::anki`].
+    Apkg,
+}
+
+impl CardFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CardFormat::Tsv => "tsv",
+            CardFormat::Apkg => "apkg",
+        }
+    }
+
+    /// The format a file name asks for: `.apkg` is a package, and anything
+    /// else the TSV it has always been.
+    pub fn for_path(path: &Path) -> CardFormat {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("apkg") => CardFormat::Apkg,
+            _ => CardFormat::Tsv,
+        }
+    }
+}
+
+impl std::str::FromStr for CardFormat {
+    type Err = EngineError;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "tsv" | "txt" => Ok(CardFormat::Tsv),
+            "apkg" | "anki" => Ok(CardFormat::Apkg),
+            other => Err(EngineError::InvalidInput(format!(
+                "unknown card format: {other} (tsv or apkg)"
+            ))),
+        }
+    }
+}
+
 /// If a highlight is a single word (after trimming edge punctuation and
 /// quotes), return the cleaned word — it's a flashcard candidate.
 pub fn single_word(text: &str) -> Option<String> {
@@ -48,6 +94,19 @@ mod tests {
         assert_eq!(single_word("..."), None);
     }
 
+    #[test]
+    fn card_formats_parse_and_follow_the_file_name() {
+        assert_eq!("APKG".parse::<CardFormat>().unwrap(), CardFormat::Apkg);
+        assert_eq!("tsv".parse::<CardFormat>().unwrap(), CardFormat::Tsv);
+        assert!("csv".parse::<CardFormat>().is_err());
+        assert_eq!(
+            CardFormat::for_path(Path::new("deck.apkg")),
+            CardFormat::Apkg
+        );
+        assert_eq!(CardFormat::for_path(Path::new("anki.tsv")), CardFormat::Tsv);
+        assert_eq!(CardFormat::for_path(Path::new("anki")), CardFormat::Tsv);
+    }
+
     #[test]
     fn tsv_escapes_separators() {
         let cards = vec![FlashcardRow {
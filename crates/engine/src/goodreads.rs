@@ -38,6 +38,7 @@ use crate::error::{EngineError, Result};
 use crate::koreader::{self, MatchCandidate};
 use crate::matching::Query;
 use crate::notes::{NewNoteInput, NoteKind};
+use crate::series::split_title;
 use crate::storage::{STATUS_FINISHED, STATUS_READING, Storage};
 use crate::{Engine, storage};
 
@@ -84,7 +85,11 @@ pub struct GoodreadsRow {
     /// the line the user has to go and look at.
     pub row: usize,
     pub external_id: Option<String>,
+    /// The title with its series suffix taken off: Goodreads has no series
+    /// column and writes `The Final Empire (Mistborn, #1)` instead.
     pub title: Option<String>,
+    pub series: Option<String>,
+    pub series_index: Option<f64>,
     pub authors: Vec<String>,
     pub isbn_10: Option<String>,
     pub isbn_13: Option<String>,
@@ -257,10 +262,21 @@ pub fn parse_csv(path: &Path) -> Result<Vec<GoodreadsRow>> {
             isbn_10 = isbn_10.or_else(|| isbn_13.take());
         }
 
+        let (title, series) = match get("title").and_then(cell) {
+            Some(t) => {
+                let (title, series) = split_title(&t);
+                (Some(title), series)
+            }
+            None => (None, None),
+        };
+        let (series, series_index) = series.map_or((None, None), |(s, i)| (Some(s), i));
+
         out.push(GoodreadsRow {
             row: n + 1,
             external_id: get("book id").and_then(cell),
-            title: get("title").and_then(cell),
+            title,
+            series,
+            series_index,
             authors,
             isbn_10,
             isbn_13,
@@ -544,6 +560,8 @@ async fn create_book(storage: &Storage, row: &GoodreadsRow) -> Result<i64> {
             page_count: row.page_count,
             isbn_10: row.isbn_10.clone(),
             isbn_13: row.isbn_13.clone(),
+            series: row.series.clone(),
+            series_index: row.series_index,
             ..Default::default()
         })
         .await?;
@@ -616,6 +634,25 @@ async fn apply(
     if let Some(id) = &row.external_id {
         storage.link_external_id(SOURCE, id, book_id).await?;
     }
+    // The one book field a matched row still has to offer: the title is ours
+    // by now, but nothing else we read knew the series folded into it.
+    let known = storage.get_book(book_id).await?;
+    let same = known.is_some_and(|b| {
+        b.series.as_deref().map(str::to_lowercase) == row.series.as_deref().map(str::to_lowercase)
+            && (row.series_index.is_none() || b.series_index == row.series_index)
+    });
+    if row.series.is_some() && !same {
+        storage
+            .enrich_book(
+                book_id,
+                &Book {
+                    series: row.series.clone(),
+                    series_index: row.series_index,
+                    ..Default::default()
+                },
+            )
+            .await?;
+    }
     let tags: Vec<(String, String)> = row
         .shelves
         .iter()
@@ -1199,4 +1236,23 @@ mod tests {
         assert_eq!(r.shelves, vec!["korean-lit", "favourites"]);
         assert_eq!(r.review.as_deref(), Some("First line.\nSecond line."));
     }
+
+    /// Goodreads has no series column; the series rides in the title, and the
+    /// title we store is the one on the cover.
+    #[test]
+    fn a_series_comes_out_of_the_title() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("series.csv");
+        std::fs::write(
+            &path,
+            "Title,Author\n\"The Final Empire (Mistborn, #1)\",Brandon Sanderson\nPachinko,Min Jin Lee\n",
+        )
+        .unwrap();
+        let rows = parse_csv(&path).unwrap();
+        assert_eq!(rows[0].title.as_deref(), Some("The Final Empire"));
+        assert_eq!(rows[0].series.as_deref(), Some("Mistborn"));
+        assert_eq!(rows[0].series_index, Some(1.0));
+        assert_eq!(rows[1].title.as_deref(), Some("Pachinko"));
+        assert_eq!(rows[1].series, None);
+    }
 }
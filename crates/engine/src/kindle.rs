@@ -0,0 +1,1119 @@
+//! Kindle's `My Clippings.txt`.
+//!
+//! The one file a Kindle will give up its highlights in: plain text, appended
+//! to on every highlight, note and bookmark, never rewritten. Each entry is
+//!
+//! ```text
+//! Title (Author)
+//! - Your Highlight on page 12 | Location 178-180 | Added on Monday, March 5, 2018 10:41:11 PM
+//!
+//! the highlighted text
+//! ==========
+//! ```
+//!
+//! and almost every part of that is worse than it looks. Four things here are
+//! decisions rather than mechanics:
+//!
+//! * **The header line is localized.** `Ihre Markierung auf Seite 12 |
+//!   Position 178-180 | Hinzugefügt am Montag, 5. März 2018 22:41:11` is the
+//!   same entry from a German Kindle. Nothing in the header is parsed by its
+//!   position: the kind is found by keyword, the location and page by the word
+//!   in front of the number, and the date by picking out a year, a month name,
+//!   a day and a time from whatever words surround them. A locale this does not
+//!   know loses its date, not its highlights — see [`parse_added`].
+//! * **An edit is a second entry.** Dragging a highlight's bounds appends the
+//!   new text and keeps the old. [`fold`] keeps the latest of each, and
+//!   [`Storage::supersede_highlight`] rewrites a copy an earlier import already
+//!   stored, so the edit lands on the row the reader may have annotated rather
+//!   than beside it.
+//! * **A note is its own entry.** It is attached to the highlight whose range
+//!   it falls in, as that highlight's device note (`ko_note`) — which is what
+//!   it is on the Kindle too. A note with no highlight under it is kept as a
+//!   highlight with no text, because dropping the reader's words is the one
+//!   outcome that cannot be undone.
+//! * **Books are matched, never keyed.** A clipping carries a title and an
+//!   author and nothing else — no ISBN, no file hash. Matching is the shared
+//!   [`crate::matching`] scorer through [`koreader::scores_for`], with the same
+//!   bands every other importer uses; a title that is only *close* to a book
+//!   already here is left for the reader to decide about.
+//!
+//! Identity is [`NewHighlight::identity_hash`], unchanged: the `Added on` stamp
+//! stands where KOReader's `datetime` does (it is the one thing the Kindle never
+//! rewrites), the location where the xpointer does. Re-importing the same file,
+//! or a longer copy of it, adds nothing twice.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::book::Book;
+use crate::diagnostic::{Diagnostic, DiagnosticKind, Severity};
+use crate::error::{EngineError, Result};
+use crate::flashcards::single_word;
+use crate::koreader::{self, MatchCandidate};
+use crate::matching::Query;
+use crate::storage::{NewHighlight, Storage};
+
+/// The `highlights.source` this importer writes.
+pub const SOURCE: &str = "kindle";
+
+/// Where the file lives on a mounted Kindle.
+pub const CLIPPINGS_FILE: &str = "documents/My Clippings.txt";
+
+/// The line between entries. Ten `=`, alone on a line.
+const SEPARATOR: &str = "==========";
+
+// ---- the entry -------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClippingKind {
+    Highlight,
+    Note,
+    /// A position with no words. Parsed so it can be counted; there is nothing
+    /// in it to store.
+    Bookmark,
+}
+
+/// Where in the book an entry sits.
+///
+/// Kindle locations for a Kindle book, pages for a PDF — which has no
+/// locations at all. Kept apart because a location and a page with the same
+/// number are nowhere near each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Location(i64, i64),
+    Page(i64, i64),
+}
+
+impl Anchor {
+    fn unit(&self) -> &'static str {
+        match self {
+            Anchor::Location(..) => "loc",
+            Anchor::Page(..) => "page",
+        }
+    }
+
+    fn range(&self) -> (i64, i64) {
+        match *self {
+            Anchor::Location(a, b) | Anchor::Page(a, b) => (a, b),
+        }
+    }
+
+    fn same_unit(&self, other: &Anchor) -> bool {
+        self.unit() == other.unit()
+    }
+
+    fn overlaps(&self, other: &Anchor) -> bool {
+        let ((a0, a1), (b0, b1)) = (self.range(), other.range());
+        self.same_unit(other) && a0 <= b1 && b0 <= a1
+    }
+}
+
+/// One entry of the file, as the Kindle wrote it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clipping {
+    /// 1-based position in the file, so a diagnostic names the entry the
+    /// reader has to go and look at.
+    pub entry: usize,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub kind: ClippingKind,
+    /// The printed page, when the book has them.
+    pub page: Option<i64>,
+    pub anchor: Option<Anchor>,
+    /// `Added on`, as `YYYY-MM-DD HH:MM:SS` — KOReader's own format, so the
+    /// rest of the engine reads it like any other annotation stamp. `None`
+    /// when the header's date could not be read.
+    pub added: Option<String>,
+    pub text: String,
+}
+
+/// Everything the file held, and what could not be read of it.
+#[derive(Debug, Default)]
+pub struct Clippings {
+    /// Entries in the file, including the ones skipped.
+    pub entries: usize,
+    pub clippings: Vec<Clipping>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+// ---- parsing ---------------------------------------------------------------
+
+/// Header keywords per kind, lowercased. English, German, French, Spanish,
+/// Italian, Portuguese, Dutch and Japanese — the Kindle UI languages seen in
+/// the wild. Bookmark first: nothing in the other two lists is inside it, and
+/// the reverse is not true of every language.
+const KINDS: &[(ClippingKind, &[&str])] = &[
+    (
+        ClippingKind::Bookmark,
+        &[
+            "bookmark",
+            "lesezeichen",
+            "signet",
+            "marcador",
+            "segnalibro",
+            "bladwijzer",
+            "ブックマーク",
+        ],
+    ),
+    (
+        ClippingKind::Highlight,
+        &[
+            "highlight",
+            "markierung",
+            "surlignement",
+            "subrayado",
+            "evidenziazione",
+            "destaque",
+            "markering",
+            "ハイライト",
+        ],
+    ),
+    (
+        ClippingKind::Note,
+        &["note", "notiz", "nota", "notitie", "メモ"],
+    ),
+];
+
+const LOCATION_WORDS: &[&str] = &[
+    "location",
+    "loc.",
+    "position",
+    "posición",
+    "posizione",
+    "posição",
+    "emplacement",
+    "locatie",
+    "位置no.",
+];
+
+const PAGE_WORDS: &[&str] = &["page", "seite", "página", "pagina"];
+
+/// Month names in the same languages, full forms only — the header never
+/// abbreviates.
+const MONTHS: &[(u8, &[&str])] = &[
+    (
+        1,
+        &[
+            "january", "januar", "janvier", "enero", "gennaio", "janeiro", "januari",
+        ],
+    ),
+    (
+        2,
+        &[
+            "february",
+            "februar",
+            "février",
+            "febrero",
+            "febbraio",
+            "fevereiro",
+            "februari",
+        ],
+    ),
+    (3, &["march", "märz", "mars", "marzo", "março", "maart"]),
+    (4, &["april", "avril", "abril", "aprile"]),
+    (5, &["may", "mai", "mayo", "maggio", "maio", "mei"]),
+    (6, &["june", "juni", "juin", "junio", "giugno", "junho"]),
+    (7, &["july", "juli", "juillet", "julio", "luglio", "julho"]),
+    (8, &["august", "août", "agosto", "augustus"]),
+    (
+        9,
+        &[
+            "september",
+            "septembre",
+            "septiembre",
+            "settembre",
+            "setembro",
+        ],
+    ),
+    (
+        10,
+        &[
+            "october", "oktober", "octobre", "octubre", "ottobre", "outubro",
+        ],
+    ),
+    (11, &["november", "novembre", "noviembre", "novembro"]),
+    (
+        12,
+        &[
+            "december",
+            "dezember",
+            "décembre",
+            "diciembre",
+            "dicembre",
+            "dezembro",
+        ],
+    ),
+];
+
+/// Read a whole clippings file. Never fails on content: an entry that cannot
+/// be read is a warning, and the rest of the file still imports — one mangled
+/// header must not cost the other two thousand highlights.
+pub fn parse_clippings(src: &str) -> Clippings {
+    let mut out = Clippings::default();
+    let mut lines: Vec<&str> = Vec::new();
+    let mut entry = 0;
+    for line in src.lines().chain(std::iter::once(SEPARATOR)) {
+        if line.trim_end() != SEPARATOR {
+            lines.push(line);
+            continue;
+        }
+        if lines
+            .iter()
+            .all(|l| l.trim().trim_start_matches('\u{feff}').is_empty())
+        {
+            lines.clear();
+            continue;
+        }
+        entry += 1;
+        match parse_entry(entry, &lines) {
+            Ok(c) => out.clippings.push(c),
+            Err(why) => out.warnings.push(entry_skipped(entry, why)),
+        }
+        lines.clear();
+    }
+    out.entries = entry;
+    for c in &out.clippings {
+        if c.added.is_none() && c.kind != ClippingKind::Bookmark {
+            out.warnings.push(Diagnostic {
+                kind: DiagnosticKind::KindleDateUnread { entry: c.entry },
+                severity: Severity::Warning,
+                detail: "the Added on date was not readable; imported without one".into(),
+            });
+        }
+    }
+    out
+}
+
+fn parse_entry(entry: usize, lines: &[&str]) -> std::result::Result<Clipping, &'static str> {
+    // Some firmware writes a byte-order mark at the start of *every* entry,
+    // not just the file, so it comes off every title line.
+    let mut lines = lines
+        .iter()
+        .map(|l| l.trim_start_matches('\u{feff}').trim_end())
+        .skip_while(|l| l.trim().is_empty());
+    let title_line = lines.next().ok_or("no title line")?;
+    let header = lines.next().ok_or("no header line")?;
+    let header = header
+        .trim()
+        .strip_prefix('-')
+        .ok_or("the second line is not a clipping header")?
+        .trim();
+    let text = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+
+    let lower = header.to_lowercase();
+    let kind = KINDS
+        .iter()
+        .find(|(_, words)| words.iter().any(|w| lower.contains(w)))
+        .map(|(k, _)| *k)
+        .ok_or("not a highlight, note or bookmark")?;
+
+    let (title, authors) = split_title(title_line);
+    if title.is_empty() {
+        return Err("no title, so there is nothing to match on");
+    }
+    // The clipping limit: once a publisher's share of a book has been
+    // clipped, the Kindle writes this placeholder in place of the text.
+    if text.starts_with('<') && text.ends_with('>') {
+        return Err("the Kindle withheld the text (clipping limit)");
+    }
+    if kind == ClippingKind::Highlight && text.is_empty() {
+        return Err("a highlight with no text");
+    }
+
+    let mut page = None;
+    let mut location = None;
+    let segments: Vec<&str> = header.split('|').collect();
+    for seg in &segments {
+        let seg = seg.to_lowercase();
+        if let Some(at) = LOCATION_WORDS
+            .iter()
+            .find_map(|w| seg.find(w).map(|i| i + w.len()))
+        {
+            location = location.or_else(|| number_range(&seg[at..]));
+        } else if let Some(at) = seg.find("ページ") {
+            // Japanese puts the number first: `12ページ`.
+            page = page.or_else(|| trailing_number(&seg[..at]));
+        } else if let Some(at) = PAGE_WORDS
+            .iter()
+            .find_map(|w| seg.find(w).map(|i| i + w.len()))
+        {
+            page = page.or_else(|| number_range(&seg[at..]).map(|(p, _)| p));
+        }
+    }
+    let anchor = location
+        .map(|(a, b)| Anchor::Location(a, b))
+        .or_else(|| page.map(|p| Anchor::Page(p, p)));
+
+    Ok(Clipping {
+        entry,
+        title,
+        authors,
+        kind,
+        page,
+        anchor,
+        added: segments.last().and_then(|s| parse_added(s)),
+        text,
+    })
+}
+
+/// `Title (Author)` into its halves.
+///
+/// The author is the **last** bracketed group, and only when it ends the
+/// line: `Dune (Dune Chronicles, Book 1) (Frank Herbert)` is a title with a
+/// series in it. Authors are `;`-separated, and a single `Last, First` is
+/// turned round — the matcher would agree either way, but a book created from
+/// a clipping should not be filed under a surname-first name.
+fn split_title(line: &str) -> (String, Vec<String>) {
+    let line = line.trim();
+    let Some(open) = line.strip_suffix(')').and_then(|l| l.rfind('(')) else {
+        return (line.to_string(), Vec::new());
+    };
+    let title = line[..open].trim();
+    let authors = &line[open + 1..line.len() - 1];
+    if title.is_empty() {
+        return (line.to_string(), Vec::new());
+    }
+    let authors = authors
+        .split(';')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(|a| match a.split_once(',') {
+            Some((last, first)) if !first.contains(',') => {
+                format!("{} {}", first.trim(), last.trim())
+            }
+            _ => a.to_string(),
+        })
+        .collect();
+    (title.to_string(), authors)
+}
+
+/// The first `N` or `N-M` in `s`.
+///
+/// Older firmware abbreviates the end of a range to the digits that changed —
+/// `Loc. 178-80` is 178 to 180 — so a shorter end borrows the start's leading
+/// digits.
+fn number_range(s: &str) -> Option<(i64, i64)> {
+    let start_at = s.find(|c: char| c.is_ascii_digit())?;
+    let rest = &s[start_at..];
+    let start_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let start_str = &rest[..start_len];
+    let start: i64 = start_str.parse().ok()?;
+    let rest = rest[start_len..].trim_start();
+    let Some(rest) = rest.strip_prefix(['-', '–']) else {
+        return Some((start, start));
+    };
+    let rest = rest.trim_start();
+    let end_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let end_str = &rest[..end_len];
+    if end_str.is_empty() {
+        return Some((start, start));
+    }
+    let end: i64 = if end_str.len() < start_str.len() {
+        format!("{}{end_str}", &start_str[..start_str.len() - end_str.len()])
+            .parse()
+            .ok()?
+    } else {
+        end_str.parse().ok()?
+    };
+    Some((start, end.max(start)))
+}
+
+fn trailing_number(s: &str) -> Option<i64> {
+    let digits: String = s
+        .chars()
+        .rev()
+        .skip_while(|c| c.is_whitespace())
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.chars().rev().collect::<String>().parse().ok()
+}
+
+/// The `Added on` date, in any of the header's languages, as
+/// `YYYY-MM-DD HH:MM:SS`.
+///
+/// Not a format string per locale: there are more Kindle locales than anyone
+/// has a list of, and they differ in word order, in the words around the date
+/// and in whether the day has a full stop after it. What they agree on is that
+/// a date is a four-digit year, a month *name*, a one- or two-digit day and a
+/// `H:MM:SS` time, so that is what is picked out, and every other word is
+/// ignored. Japanese writes `2018年3月5日` and is read by its markers.
+///
+/// `None` when any part is missing or the date does not exist. The highlight
+/// still imports; it just has no stamp to tell it from the same words
+/// highlighted at the same place on another day.
+pub fn parse_added(s: &str) -> Option<String> {
+    let lower = s.to_lowercase();
+    let (mut year, mut month, mut day) = (None, None, None);
+    let mut time = None;
+    let mut pm = lower.contains("午後");
+    let mut am = false;
+
+    // Each marker searched for after the one before it: `作成日` has a 日 of
+    // its own ahead of the date.
+    if let Some(y) = lower.find('年')
+        && let Some(m) = lower[y..].find('月').map(|i| y + i)
+        && let Some(d) = lower[m..].find('日').map(|i| m + i)
+    {
+        year = trailing_number(&lower[..y]);
+        month = trailing_number(&lower[..m]).and_then(|m| u8::try_from(m).ok());
+        day = trailing_number(&lower[..d]).and_then(|d| u8::try_from(d).ok());
+    }
+
+    for token in lower.split(|c: char| c.is_whitespace() || c == ',') {
+        let token = token.trim_matches(|c: char| c == '.' || c == ',');
+        if token.is_empty() {
+            continue;
+        }
+        if let Some(t) = token
+            .rsplit(|c: char| !(c.is_ascii_digit() || c == ':'))
+            .next()
+            && t.contains(':')
+        {
+            time = parse_time(t).or(time);
+            continue;
+        }
+        match token.replace('.', "").as_str() {
+            "pm" => pm = true,
+            "am" => am = true,
+            _ => {}
+        }
+        if let Some((n, _)) = MONTHS.iter().find(|(_, names)| names.contains(&token)) {
+            month = month.or(Some(*n));
+        } else if token.chars().all(|c| c.is_ascii_digit()) {
+            match token.len() {
+                4 => year = year.or(token.parse().ok()),
+                1 | 2 => day = day.or(token.parse().ok()),
+                _ => {}
+            }
+        }
+    }
+
+    let (h, mi, sec) = time?;
+    let h = match (pm, am) {
+        (true, _) if h < 12 => h + 12,
+        (_, true) if h == 12 => 0,
+        _ => h,
+    };
+    let date = time::Date::from_calendar_date(
+        i32::try_from(year?).ok()?,
+        time::Month::try_from(month?).ok()?,
+        day?,
+    )
+    .ok()?;
+    (h < 24 && mi < 60 && sec < 60).then(|| {
+        format!(
+            "{:04}-{:02}-{:02} {h:02}:{mi:02}:{sec:02}",
+            date.year(),
+            u8::from(date.month()),
+            date.day()
+        )
+    })
+}
+
+fn parse_time(t: &str) -> Option<(u8, u8, u8)> {
+    let mut parts = t.split(':').map(|p| p.parse::<u8>().ok());
+    let h = parts.next()??;
+    let m = parts.next()??;
+    let s = parts.next().flatten().unwrap_or(0);
+    Some((h, m, s))
+}
+
+fn entry_skipped(entry: usize, why: &str) -> Diagnostic {
+    Diagnostic {
+        kind: DiagnosticKind::KindleEntrySkipped { entry },
+        severity: Severity::Warning,
+        detail: why.to_string(),
+    }
+}
+
+// ---- folding ---------------------------------------------------------------
+
+/// One highlight as it should be stored, and the earlier versions of it the
+/// Kindle kept in the file.
+#[derive(Debug)]
+struct Folded {
+    highlight: NewHighlight,
+    /// Latest first. Any of these an earlier import stored is rewritten to
+    /// `highlight` rather than left beside it.
+    replaces: Vec<NewHighlight>,
+}
+
+/// Is `new` an edit of `old`? The ranges overlap and the two share an end or
+/// one text holds the other — which is what dragging either handle does.
+/// Two different sentences on either side of one location overlap too, and
+/// share neither, which is why overlap alone is not enough.
+fn is_edit_of(new: &Clipping, old: &Clipping) -> bool {
+    let (Some(a), Some(b)) = (new.anchor, old.anchor) else {
+        return false;
+    };
+    if !a.overlaps(&b) {
+        return false;
+    }
+    let ((a0, a1), (b0, b1)) = (a.range(), b.range());
+    a0 == b0 || a1 == b1 || new.text.contains(&old.text) || old.text.contains(&new.text)
+}
+
+fn to_highlight(c: &Clipping, note: Option<String>) -> NewHighlight {
+    let (pos0, pos1) = match c.anchor {
+        Some(a) => {
+            let (start, end) = a.range();
+            (
+                Some(format!("kindle:{}={start}", a.unit())),
+                Some(format!("kindle:{}={end}", a.unit())),
+            )
+        }
+        None => (None, None),
+    };
+    NewHighlight {
+        text: c.text.clone(),
+        chapter: None,
+        page: c.page,
+        pos0,
+        pos1,
+        ko_datetime: c.added.clone(),
+        ko_datetime_updated: None,
+        color: None,
+        note,
+        source: SOURCE.to_string(),
+    }
+}
+
+/// One book's entries, in file order, into what should be stored.
+fn fold(clippings: &[&Clipping]) -> (Vec<Folded>, usize) {
+    // Highlights, latest version of each. Position in `kept` is file order of
+    // the *latest* edit, which is the order the reader last touched them.
+    let mut kept: Vec<(&Clipping, Vec<&Clipping>)> = Vec::new();
+    for c in clippings
+        .iter()
+        .filter(|c| c.kind == ClippingKind::Highlight)
+    {
+        let mut older = Vec::new();
+        if let Some(i) = kept.iter().rposition(|(k, _)| is_edit_of(c, k)) {
+            let (k, mut before) = kept.remove(i);
+            older.push(k);
+            older.append(&mut before);
+        }
+        kept.push((c, older));
+    }
+
+    // Notes onto the highlight they sit in: the one ending at the note's
+    // location if there is one (that is where the Kindle puts it), else any
+    // that covers it, latest first. A later note at the same spot is an edit.
+    let mut notes: Vec<BTreeMap<(i64, &'static str), &Clipping>> =
+        vec![BTreeMap::new(); kept.len()];
+    let mut orphans: Vec<(&Clipping, Vec<&Clipping>)> = Vec::new();
+    for n in clippings.iter().filter(|c| c.kind == ClippingKind::Note) {
+        let Some(at) = n.anchor else {
+            orphans.push((n, Vec::new()));
+            continue;
+        };
+        let (spot, _) = at.range();
+        let covering = |k: &Clipping, exact: bool| {
+            k.anchor.is_some_and(|a| {
+                let (a0, a1) = a.range();
+                a.same_unit(&at)
+                    && if exact {
+                        a1 == spot
+                    } else {
+                        a0 <= spot && spot <= a1
+                    }
+            })
+        };
+        let home = kept
+            .iter()
+            .rposition(|(k, _)| covering(k, true))
+            .or_else(|| kept.iter().rposition(|(k, _)| covering(k, false)));
+        match home {
+            Some(i) => {
+                notes[i].insert((spot, at.unit()), n);
+            }
+            None => match orphans
+                .iter()
+                .position(|(o, _)| o.anchor.is_some_and(|a| a == at))
+            {
+                Some(i) => {
+                    let (o, mut before) = orphans.remove(i);
+                    let mut older = vec![o];
+                    older.append(&mut before);
+                    orphans.push((n, older));
+                }
+                None => orphans.push((n, Vec::new())),
+            },
+        }
+    }
+
+    let mut out: Vec<Folded> = kept
+        .iter()
+        .zip(&notes)
+        .map(|((c, older), notes)| {
+            let note = (!notes.is_empty()).then(|| {
+                notes
+                    .values()
+                    .map(|n| n.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            });
+            Folded {
+                highlight: to_highlight(c, note.clone()),
+                replaces: older
+                    .iter()
+                    .map(|o| to_highlight(o, note.clone()))
+                    .collect(),
+            }
+        })
+        .collect();
+    // A note with nothing highlighted under it: a highlight with no text
+    // carrying the note, so the words are searchable and nothing is lost.
+    for (n, older) in orphans {
+        let as_note = |c: &Clipping| {
+            let mut h = to_highlight(c, Some(c.text.clone()));
+            h.text = String::new();
+            h
+        };
+        out.push(Folded {
+            highlight: as_note(n),
+            replaces: older.into_iter().map(as_note).collect(),
+        });
+    }
+
+    let bookmarks = clippings
+        .iter()
+        .filter(|c| c.kind == ClippingKind::Bookmark)
+        .count();
+    (out, bookmarks)
+}
+
+// ---- the report ------------------------------------------------------------
+
+/// How a book in the file found its book here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KindleMatch {
+    /// The shared matcher was sure enough on title and author.
+    Title,
+    /// Nothing matched and nothing was close, so the book was created from
+    /// the clippings' title and author.
+    New,
+}
+
+impl std::fmt::Display for KindleMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KindleMatch::Title => write!(f, "title"),
+            KindleMatch::New => write!(f, "new"),
+        }
+    }
+}
+
+/// One book's share of the file. The counts mean what
+/// [`BookImportStats`](crate::BookImportStats)'s do.
+#[derive(Debug)]
+pub struct KindleBookReport {
+    /// `None` in a dry run for a book that does not exist yet.
+    pub book_id: Option<i64>,
+    pub title: String,
+    pub matched_by: KindleMatch,
+    pub inserted: usize,
+    /// Stored highlights the file disagreed with: a note added or edited, or
+    /// the highlight itself re-dragged on the device.
+    pub updated: usize,
+    pub skipped: usize,
+    pub flashcards: usize,
+    /// Bookmarks in the file for this book. Counted, never stored.
+    pub bookmarks: usize,
+}
+
+/// A book we will not guess about. The same shape as
+/// [`UnmatchedRow`](crate::UnmatchedRow): the title, and what it probably is.
+#[derive(Debug)]
+pub struct UnmatchedClippings {
+    pub title: String,
+    pub authors: Vec<String>,
+    /// Highlights and notes in the file for it.
+    pub clippings: usize,
+    pub candidates: Vec<MatchCandidate>,
+}
+
+#[derive(Debug, Default)]
+pub struct KindleReport {
+    pub dry_run: bool,
+    /// The file that was read, so a caller given a mount can say which.
+    pub path: PathBuf,
+    /// Entries in the file, whatever became of them.
+    pub entries: usize,
+    pub books: Vec<KindleBookReport>,
+    pub unmatched: Vec<UnmatchedClippings>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+impl KindleReport {
+    pub fn created(&self) -> usize {
+        self.books
+            .iter()
+            .filter(|b| b.matched_by == KindleMatch::New)
+            .count()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportOptions {
+    pub dry_run: bool,
+    /// Create a book for a title that landed in the candidate band instead of
+    /// reporting it — `goodreads import --new`, for the same reason.
+    pub create_ambiguous: bool,
+}
+
+// ---- import ----------------------------------------------------------------
+
+/// The clippings file a path points at: the file itself, or a Kindle mount
+/// holding one.
+pub fn clippings_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+    let on_mount = path.join(CLIPPINGS_FILE);
+    on_mount.is_file().then_some(on_mount)
+}
+
+/// What clippings are grouped by: the title and authors as the Kindle wrote
+/// them.
+type BookKey<'a> = (&'a str, &'a [String]);
+
+/// Import a `My Clippings.txt`. Idempotent: a second run over the same file —
+/// or over the same file with more appended — adds nothing twice.
+pub async fn import(storage: &Storage, path: &Path, opts: ImportOptions) -> Result<KindleReport> {
+    let Some(file) = clippings_file(path) else {
+        return Err(EngineError::NotFound(format!(
+            "no Kindle clippings at {} (looked for {CLIPPINGS_FILE})",
+            path.display()
+        )));
+    };
+    // Lossy rather than `read_to_string`: one stray byte in a title the Kindle
+    // copied out of a badly-encoded book should cost that title, not the file.
+    let src = String::from_utf8_lossy(&std::fs::read(&file)?).into_owned();
+    let parsed = parse_clippings(&src);
+    let mut report = KindleReport {
+        dry_run: opts.dry_run,
+        path: file,
+        entries: parsed.entries,
+        warnings: parsed.warnings,
+        ..Default::default()
+    };
+
+    // By book, in the order each first appears.
+    let mut books: Vec<(BookKey, Vec<&Clipping>)> = Vec::new();
+    for c in &parsed.clippings {
+        let key = (c.title.as_str(), c.authors.as_slice());
+        match books.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => v.push(c),
+            None => books.push((key, vec![c])),
+        }
+    }
+
+    for ((title, authors), clippings) in books {
+        let (folded, bookmarks) = fold(&clippings);
+        if folded.is_empty() && bookmarks > 0 {
+            // Only bookmarks: nothing to store, and not worth creating a book
+            // or asking the reader about.
+            continue;
+        }
+
+        let scored = koreader::scores_for(storage, &Query::new(Some(title), authors)).await?;
+        let (book_id, matched_by) = match scored.first() {
+            Some(s) if s.can_auto => (s.book.id, KindleMatch::Title),
+            _ => {
+                let candidates = koreader::band(scored);
+                if !candidates.is_empty() && !opts.create_ambiguous {
+                    report.unmatched.push(UnmatchedClippings {
+                        title: title.to_string(),
+                        authors: authors.to_vec(),
+                        clippings: clippings.len() - bookmarks,
+                        candidates,
+                    });
+                    continue;
+                }
+                (None, KindleMatch::New)
+            }
+        };
+
+        let mut book = KindleBookReport {
+            book_id,
+            title: title.to_string(),
+            matched_by,
+            inserted: 0,
+            updated: 0,
+            skipped: 0,
+            flashcards: 0,
+            bookmarks,
+        };
+        if opts.dry_run {
+            preview(storage, &folded, &mut book).await?;
+        } else {
+            let id = match book_id {
+                Some(id) => id,
+                None => {
+                    storage
+                        .upsert_book(&Book {
+                            title: Some(title.to_string()),
+                            authors: authors.to_vec(),
+                            ..Default::default()
+                        })
+                        .await?
+                }
+            };
+            book.book_id = Some(id);
+            apply(storage, id, &folded, &mut book).await?;
+        }
+        tracing::info!(
+            book_id = book.book_id,
+            inserted = book.inserted,
+            updated = book.updated,
+            skipped = book.skipped,
+            matched_by = %book.matched_by,
+            dry_run = opts.dry_run,
+            "imported clippings"
+        );
+        report.books.push(book);
+    }
+    Ok(report)
+}
+
+/// The read-only half, for a dry run. It answers what [`apply`] would and
+/// writes nothing.
+async fn preview(storage: &Storage, folded: &[Folded], book: &mut KindleBookReport) -> Result<()> {
+    let Some(book_id) = book.book_id else {
+        book.inserted = folded.len();
+        book.flashcards = folded
+            .iter()
+            .filter(|f| single_word(&f.highlight.text).is_some())
+            .count();
+        return Ok(());
+    };
+    for f in folded {
+        let h = &f.highlight;
+        if storage.highlight_exists(book_id, h).await? {
+            if storage.device_fields_differ(book_id, h).await? {
+                book.updated += 1;
+            } else {
+                book.skipped += 1;
+            }
+            continue;
+        }
+        let mut superseded = false;
+        for old in &f.replaces {
+            if storage.highlight_exists(book_id, old).await? {
+                superseded = true;
+                break;
+            }
+        }
+        if superseded {
+            book.updated += 1;
+        } else {
+            book.inserted += 1;
+            if single_word(&h.text).is_some() {
+                book.flashcards += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The single place the supersede/insert/refresh/skip decision is made.
+async fn apply(
+    storage: &Storage,
+    book_id: i64,
+    folded: &[Folded],
+    book: &mut KindleBookReport,
+) -> Result<()> {
+    for f in folded {
+        let h = &f.highlight;
+        let mut superseded = false;
+        for old in &f.replaces {
+            if storage.supersede_highlight(book_id, old, h).await? {
+                superseded = true;
+                break;
+            }
+        }
+        if superseded {
+            book.updated += 1;
+            continue;
+        }
+        match storage.insert_highlight(book_id, h).await? {
+            None => {
+                if storage.refresh_device_fields(book_id, h).await? {
+                    book.updated += 1;
+                } else {
+                    book.skipped += 1;
+                }
+            }
+            Some(highlight_id) => {
+                book.inserted += 1;
+                if let Some(word) = single_word(&h.text)
+                    && storage
+                        .insert_flashcard(book_id, Some(highlight_id), &word, h.note.as_deref())
+                        .await?
+                {
+                    book.flashcards += 1;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn one(src: &str) -> Clipping {
+        let parsed = parse_clippings(src);
+        assert_eq!(parsed.clippings.len(), 1, "{:?}", parsed.warnings);
+        parsed.clippings.into_iter().next().unwrap()
+    }
+
+    /// The same highlight from five Kindles set to five languages reads as the
+    /// same entry.
+    #[test]
+    fn the_header_reads_in_every_language_the_kindle_writes() {
+        let headers = [
+            "- Your Highlight on page 12 | Location 178-180 | Added on Monday, March 5, 2018 10:41:11 PM",
+            "- Ihre Markierung auf Seite 12 | Position 178-180 | Hinzugefügt am Montag, 5. März 2018 22:41:11",
+            "- Votre surlignement sur la page 12 | emplacement 178-180 | Ajouté le lundi 5 mars 2018 22:41:11",
+            "- Tu subrayado en la página 12 | posición 178-180 | Añadido el lunes, 5 de marzo de 2018 22:41:11",
+            "- La tua evidenziazione a pagina 12 | posizione 178-180 | Aggiunto il lunedì 5 marzo 2018 22:41:11",
+            "- 12ページ|位置No. 178-180のハイライト |作成日: 2018年3月5日月曜日 22:41:11",
+        ];
+        for header in headers {
+            let c = one(&format!(
+                "The Left Hand of Darkness (Le Guin, Ursula K.)\n{header}\n\nLight is the left hand of darkness\n==========\n"
+            ));
+            assert_eq!(c.kind, ClippingKind::Highlight, "{header}");
+            assert_eq!(c.page, Some(12), "{header}");
+            assert_eq!(c.anchor, Some(Anchor::Location(178, 180)), "{header}");
+            assert_eq!(c.added.as_deref(), Some("2018-03-05 22:41:11"), "{header}");
+            assert_eq!(c.title, "The Left Hand of Darkness");
+            assert_eq!(c.authors, vec!["Ursula K. Le Guin".to_string()]);
+        }
+    }
+
+    #[test]
+    fn old_firmware_abbreviates_the_end_of_a_range() {
+        let c = one(
+            "Kindred (Octavia E. Butler)\r\n- Highlight Loc. 1178-82  | Added on Tuesday, April 3, 2012, 12:05 AM\r\n\r\nI lost an arm\r\n==========\r\n",
+        );
+        assert_eq!(c.anchor, Some(Anchor::Location(1178, 1182)));
+        assert_eq!(c.added.as_deref(), Some("2012-04-03 00:05:00"));
+        assert_eq!(c.text, "I lost an arm");
+    }
+
+    #[test]
+    fn kinds_series_and_byte_order_marks() {
+        let src = "\u{feff}Dune (Dune Chronicles, Book 1) (Frank Herbert)\n\
+                   - Your Bookmark on Location 500 | Added on Monday, March 5, 2018 10:41:11 PM\n\n\n\
+                   ==========\n\
+                   \u{feff}Dune (Dune Chronicles, Book 1) (Frank Herbert)\n\
+                   - Your Note on Location 501 | Added on Monday, March 5, 2018 10:42:00 PM\n\n\
+                   the spice\n\
+                   ==========\n";
+        let parsed = parse_clippings(src);
+        assert_eq!(parsed.entries, 2);
+        let kinds: Vec<_> = parsed.clippings.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec![ClippingKind::Bookmark, ClippingKind::Note]);
+        assert_eq!(parsed.clippings[0].title, "Dune (Dune Chronicles, Book 1)");
+        assert_eq!(
+            parsed.clippings[0].authors,
+            vec!["Frank Herbert".to_string()]
+        );
+    }
+
+    /// A bad entry costs itself, and says which one it was.
+    #[test]
+    fn an_unreadable_entry_is_a_warning_not_a_failure() {
+        let src = "Kindred (Octavia E. Butler)\n- Your Highlight on Location 10 | Added on Monday, March 5, 2018 10:41:11 PM\n\n<You have reached the clipping limit for this item>\n==========\n\
+                   Kindred (Octavia E. Butler)\nnot a header\n\nwords\n==========\n\
+                   Kindred (Octavia E. Butler)\n- Your Highlight on Location 12 | Added on someday\n\nwords\n==========\n";
+        let parsed = parse_clippings(src);
+        assert_eq!(parsed.entries, 3);
+        assert_eq!(parsed.clippings.len(), 1);
+        let kinds: Vec<_> = parsed.warnings.iter().map(|w| w.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiagnosticKind::KindleEntrySkipped { entry: 1 },
+                DiagnosticKind::KindleEntrySkipped { entry: 2 },
+                DiagnosticKind::KindleDateUnread { entry: 3 },
+            ]
+        );
+    }
+
+    /// Dragging a highlight's end appends a second entry. One survives, the
+    /// latest, carrying the earlier as what it replaces; the note lands on it;
+    /// a neighbouring sentence sharing a location is left alone.
+    #[test]
+    fn an_edit_folds_into_one_highlight_and_the_note_lands_on_it() {
+        let entry = |kind: &str, loc: &str, minute: u8, text: &str| {
+            format!(
+                "Kindred (Octavia E. Butler)\n- Your {kind} on Location {loc} | Added on Monday, March 5, 2018 10:{minute:02}:00 PM\n\n{text}\n==========\n"
+            )
+        };
+        let src = [
+            entry("Highlight", "178-179", 1, "I lost an arm"),
+            entry("Highlight", "180-182", 2, "on my last trip home"),
+            entry("Highlight", "178-180", 3, "I lost an arm on my last trip"),
+            entry("Note", "180", 4, "first line of the book"),
+            entry("Note", "180", 5, "the first line of the book"),
+            entry("Note", "900", 6, "check this later"),
+            entry("Bookmark", "950", 7, ""),
+        ]
+        .concat();
+        let parsed = parse_clippings(&src);
+        let refs: Vec<&Clipping> = parsed.clippings.iter().collect();
+        let (folded, bookmarks) = fold(&refs);
+        assert_eq!(bookmarks, 1);
+
+        let texts: Vec<&str> = folded.iter().map(|f| f.highlight.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["on my last trip home", "I lost an arm on my last trip", ""]
+        );
+        let edited = &folded[1];
+        assert_eq!(edited.replaces.len(), 1);
+        assert_eq!(edited.replaces[0].text, "I lost an arm");
+        assert_eq!(
+            edited.highlight.note.as_deref(),
+            Some("the first line of the book"),
+            "a later note at the same spot is an edit of it"
+        );
+        assert_eq!(edited.highlight.pos0.as_deref(), Some("kindle:loc=178"));
+        assert_eq!(
+            edited.highlight.ko_datetime.as_deref(),
+            Some("2018-03-05 22:03:00")
+        );
+        // The neighbour overlaps at 180 but shares no end and no words.
+        assert!(folded[0].replaces.is_empty());
+        assert_eq!(folded[0].highlight.note, None);
+
+        let orphan = &folded[2];
+        assert_eq!(orphan.highlight.note.as_deref(), Some("check this later"));
+    }
+
+    #[test]
+    fn a_date_that_does_not_exist_is_no_date() {
+        assert_eq!(
+            parse_added("Added on Friday, February 30, 2018 10:41:11 PM"),
+            None
+        );
+        assert_eq!(parse_added("Added on Monday, March 5, 2018"), None);
+        assert_eq!(
+            parse_added("Adicionado: segunda-feira, 5 de março de 2018 22:41:11"),
+            Some("2018-03-05 22:41:11".into())
+        );
+        assert_eq!(
+            parse_added("Added on Monday, 5 March 2018 12:10:00 AM"),
+            Some("2018-03-05 00:10:00".into())
+        );
+    }
+}
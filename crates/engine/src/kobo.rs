@@ -0,0 +1,557 @@
+//! A stock Kobo's own database: `.kobo/KoboReader.sqlite`.
+//!
+//! A Kobo without KOReader keeps everything in one SQLite file that Nickel, its
+//! reading app, owns: `content` has a row per book (`ContentType = 6`) and per
+//! chapter (`9`), with reading state on the book row; `Bookmark` has a row per
+//! highlight, note and dog-ear, keyed to the book by `VolumeID`. This module
+//! reads the two and writes what it finds through the same rules a sidecar
+//! gets — it is [`crate::koreader`]'s sibling, not a new way of doing things:
+//!
+//! * **Highlights are device-owned**, exactly as a sidecar's are:
+//!   [`koreader::write_highlights`] makes the insert/refresh/skip decision,
+//!   `Annotation` is the device note (`ko_note`), and the reader's own
+//!   `annotation` is never touched. Identity is the creation time, the
+//!   chapter-qualified start of the range and the text — none of which Nickel
+//!   lets you edit.
+//! * **Books are linked by the file**, through `device_books`, on the same
+//!   `partial_md5` a sidecar carries. A sideloaded book's `ContentID` is its
+//!   path under `/mnt/onboard`, a store book's file is `.kobo/kepub/<ContentID>`;
+//!   either way the file is on the mount, and hashing it means a book read in
+//!   KOReader on Monday and in Nickel on Tuesday is one book here. Then ISBN,
+//!   then the shared title matcher, in [`koreader::match_book`]'s order.
+//! * **Reading state** is `ReadStatus` (1 reading, 2 finished), `___PercentRead`
+//!   and `DateLastRead`, mirrored onto the reading as the sidecar's status and
+//!   percent are, plus `readings.ko_last_read` (migration `0014`), which a
+//!   sidecar has no equivalent for.
+//!
+//! The database is opened **read-only** and never written: Nickel may have it
+//! open, and a Kobo that finds its database changed under it rebuilds it.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use sqlx::Row;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use crate::book::{Book, normalize_isbn};
+use crate::diagnostic::Diagnostic;
+use crate::error::{EngineError, Result};
+use crate::koreader::{
+    self, BookImportStats, ImportReport, KoStatus, MatchCandidate, MatchMethod, PullReport,
+    UnmatchedSidecar,
+};
+use crate::matching::Query;
+use crate::partial_md5::partial_md5;
+use crate::storage::{LinkedBy, NewHighlight, Storage, ko_datetime_to_unix};
+
+/// The `highlights.source` and `readings.source` this importer writes.
+pub const SOURCE: &str = "kobo";
+
+/// Where Nickel keeps its database, relative to the mount.
+pub const KOBO_DB: &str = ".kobo/KoboReader.sqlite";
+
+/// Where a store book's file lives, relative to the mount. Named by its
+/// `ContentID`, with no extension.
+const KEPUB_DIR: &str = ".kobo/kepub";
+
+/// The prefix on a sideloaded book's `ContentID`: the mount, as the Kobo sees
+/// it from the inside.
+const ONBOARD: &str = "file:///mnt/onboard/";
+
+/// One book Nickel has opened, with everything it holds about it.
+#[derive(Debug, Clone)]
+pub struct KoboBook {
+    pub content_id: String,
+    /// The book's file on the mount — where it is, or where it would be for a
+    /// store book that is not downloaded. Also the key a device row is pulled
+    /// by: see [`locate`].
+    pub path: PathBuf,
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub isbn: Option<String>,
+    pub language: Option<String>,
+    pub status: Option<KoStatus>,
+    /// `0.0..=1.0`, like a sidecar's `percent_finished`.
+    pub percent: Option<f64>,
+    /// `DateLastRead`, unix seconds.
+    pub last_read: Option<i64>,
+    pub highlights: Vec<NewHighlight>,
+}
+
+impl KoboBook {
+    /// The file's `partial_md5`, when the file is on the device.
+    pub fn partial_md5(&self) -> Option<String> {
+        self.path
+            .is_file()
+            .then(|| partial_md5(&self.path).ok())
+            .flatten()
+    }
+}
+
+/// The database a path points at: the file itself, or a Kobo mount holding
+/// one.
+pub fn kobo_db(path: &Path) -> Option<PathBuf> {
+    if path.is_file() && path.file_name().is_some_and(|n| n == "KoboReader.sqlite") {
+        return Some(path.to_path_buf());
+    }
+    let on_mount = path.join(KOBO_DB);
+    on_mount.is_file().then_some(on_mount)
+}
+
+/// Does this volume hold a Kobo's own database?
+pub fn is_kobo_mount(path: &Path) -> bool {
+    path.join(KOBO_DB).is_file()
+}
+
+/// The mount a database sits in: two levels up from `.kobo/KoboReader.sqlite`.
+pub fn mount_of(db: &Path) -> PathBuf {
+    db.parent()
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// Where a book's file is on the mount.
+pub fn book_path(mount: &Path, content_id: &str) -> PathBuf {
+    match content_id.strip_prefix(ONBOARD) {
+        Some(rel) => mount.join(rel),
+        None => mount.join(KEPUB_DIR).join(content_id),
+    }
+}
+
+/// The other direction: which Kobo, and which of its books, a path is.
+///
+/// A device row's `path` is the book's file — it has to be *something* unique
+/// per book, and a sidecar row's is the sidecar. This is how a sync gets from
+/// one back to the database without a second key travelling alongside.
+pub fn locate(path: &Path) -> Option<(PathBuf, String)> {
+    let mount = path.ancestors().skip(1).find(|a| is_kobo_mount(a))?;
+    let rel = path.strip_prefix(mount).ok()?;
+    let content_id = match rel.strip_prefix(KEPUB_DIR) {
+        Ok(id) => id.to_string_lossy().into_owned(),
+        Err(_) => format!("{ONBOARD}{}", rel.to_string_lossy()),
+    };
+    Some((mount.join(KOBO_DB), content_id))
+}
+
+// ---- reading the database --------------------------------------------------
+
+/// Every book Nickel has opened, with its highlights.
+///
+/// "Opened" is the same line a sidecar draws — KOReader writes one the first
+/// time a book is opened — so a device screen lists the books you have read
+/// rather than every sample the store ever pushed. A row counts when it has a
+/// read status, a last-read date, or anything in `Bookmark`.
+pub async fn read_library(db: &Path) -> Result<Vec<KoboBook>> {
+    let opts = SqliteConnectOptions::new().filename(db).read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await?;
+
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name IN ('content', 'Bookmark')",
+    )
+    .fetch_all(&pool)
+    .await?;
+    if tables.len() < 2 {
+        pool.close().await;
+        return Err(EngineError::InvalidInput(format!(
+            "{} is not a Kobo database (no content/Bookmark tables)",
+            db.display()
+        )));
+    }
+
+    // `NULLIF(…, '')` throughout: Nickel writes an empty string as often as a
+    // NULL for "none", and the two mean the same.
+    let books = sqlx::query(
+        "SELECT c.ContentID AS content_id, NULLIF(c.Title, '') AS title,
+                NULLIF(c.Attribution, '') AS authors, NULLIF(c.ISBN, '') AS isbn,
+                NULLIF(c.Language, '') AS language, c.ReadStatus AS read_status,
+                c.___PercentRead AS percent, NULLIF(c.DateLastRead, '') AS last_read
+           FROM content c
+          WHERE c.ContentType = 6
+            AND (c.ReadStatus > 0 OR NULLIF(c.DateLastRead, '') IS NOT NULL
+                 OR EXISTS (SELECT 1 FROM Bookmark b WHERE b.VolumeID = c.ContentID))
+          ORDER BY c.ContentID",
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    // Dog-ears are `Bookmark` rows with neither text nor annotation: a place,
+    // not a highlight, and nothing to store. Hidden rows are ones the reader
+    // deleted and Nickel has not yet purged.
+    let marks = sqlx::query(
+        "SELECT b.VolumeID AS volume, b.ContentID AS chapter_id, b.Text AS text,
+                NULLIF(trim(b.Annotation), '') AS note,
+                b.StartContainerPath AS start_path, b.EndContainerPath AS end_path,
+                b.DateCreated AS created, b.DateModified AS modified,
+                NULLIF(ch.Title, '') AS chapter
+           FROM Bookmark b
+           LEFT JOIN content ch ON ch.ContentID = b.ContentID
+          WHERE (b.Hidden IS NULL OR b.Hidden <> 'true')
+            AND (NULLIF(trim(b.Text), '') IS NOT NULL
+                 OR NULLIF(trim(b.Annotation), '') IS NOT NULL)
+          ORDER BY b.VolumeID, b.DateCreated, b.BookmarkID",
+    )
+    .fetch_all(&pool)
+    .await?;
+    pool.close().await;
+
+    let mut highlights: BTreeMap<String, Vec<NewHighlight>> = BTreeMap::new();
+    for row in marks {
+        let volume: String = row.try_get("volume")?;
+        let chapter_id: Option<String> = row.try_get("chapter_id")?;
+        let chapter_id = chapter_id.unwrap_or_default();
+        let at = |p: Option<String>| p.map(|p| format!("kobo:{chapter_id}#{p}"));
+        let text: Option<String> = row.try_get("text")?;
+        highlights.entry(volume).or_default().push(NewHighlight {
+            text: text.map(|t| t.trim().to_string()).unwrap_or_default(),
+            chapter: row.try_get("chapter")?,
+            page: None,
+            pos0: at(row.try_get("start_path")?),
+            pos1: at(row.try_get("end_path")?),
+            ko_datetime: row
+                .try_get::<Option<String>, _>("created")?
+                .and_then(|d| kobo_datetime(&d)),
+            ko_datetime_updated: row
+                .try_get::<Option<String>, _>("modified")?
+                .and_then(|d| kobo_datetime(&d)),
+            color: None,
+            note: row.try_get("note")?,
+            source: SOURCE.to_string(),
+        });
+    }
+
+    let mount = mount_of(db);
+    let mut out = Vec::with_capacity(books.len());
+    for row in books {
+        let content_id: String = row.try_get("content_id")?;
+        let status = match row.try_get::<Option<i64>, _>("read_status")? {
+            Some(1) => Some(KoStatus::Reading),
+            Some(2) => Some(KoStatus::Complete),
+            _ => None,
+        };
+        // A finished book reads 0% on some firmware — Nickel resets it on the
+        // way to "finished" — and 100% is what it means.
+        let percent = match (&status, row.try_get::<Option<i64>, _>("percent")?) {
+            (Some(KoStatus::Complete), _) => Some(1.0),
+            (_, Some(p)) if p > 0 => Some(p.min(100) as f64 / 100.0),
+            _ => None,
+        };
+        let authors: Option<String> = row.try_get("authors")?;
+        out.push(KoboBook {
+            path: book_path(&mount, &content_id),
+            title: row.try_get("title")?,
+            authors: split_attribution(authors.as_deref()),
+            isbn: row.try_get("isbn")?,
+            language: row.try_get("language")?,
+            status,
+            percent,
+            last_read: row
+                .try_get::<Option<String>, _>("last_read")?
+                .and_then(|d| kobo_datetime(&d))
+                .and_then(|d| ko_datetime_to_unix(&d)),
+            highlights: highlights.remove(&content_id).unwrap_or_default(),
+            content_id,
+        });
+    }
+    Ok(out)
+}
+
+/// Nickel's timestamps — `2018-03-05T22:41:11Z`, `2018-03-05T22:41:11.000`,
+/// depending on firmware — as KOReader's `YYYY-MM-DD HH:MM:SS`, so attribution
+/// and every other reader of `ko_datetime` treats them alike. Both are UTC.
+fn kobo_datetime(s: &str) -> Option<String> {
+    let s = s.trim();
+    let head = s.get(..19)?.replacen('T', " ", 1);
+    ko_datetime_to_unix(&head).map(|_| head)
+}
+
+/// `Attribution` is one string. Nickel joins several authors with `&` or `,` —
+/// but a comma is also how a surname-first name is written, so it only splits
+/// when every piece is more than one word.
+fn split_attribution(raw: Option<&str>) -> Vec<String> {
+    let Some(raw) = raw else {
+        return Vec::new();
+    };
+    let pieces: Vec<&str> = raw
+        .split(['&', ';'])
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .collect();
+    pieces
+        .into_iter()
+        .flat_map(|p| {
+            let parts: Vec<&str> = p.split(',').map(str::trim).collect();
+            if parts.len() > 1 && parts.iter().all(|a| a.split_whitespace().count() > 1) {
+                parts
+            } else {
+                vec![p]
+            }
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+// ---- matching --------------------------------------------------------------
+
+/// [`koreader::match_book`]'s order, on what a Kobo knows: the file's
+/// `partial_md5` through `device_books`, then `content.ISBN`, then the shared
+/// matcher on title and `Attribution`.
+pub async fn match_book(
+    storage: &Storage,
+    kb: &KoboBook,
+    md5: Option<&str>,
+) -> Result<Option<(Book, MatchMethod)>> {
+    if let Some(md5) = md5
+        && let Some(book) = storage.find_book_by_partial_md5(md5).await?
+    {
+        return Ok(Some((book, MatchMethod::Md5)));
+    }
+    if let Some(isbn) = kb.isbn.as_deref().and_then(normalize_isbn)
+        && let Some(book) = storage.find_book_by_isbn(&isbn).await?
+    {
+        return Ok(Some((book, MatchMethod::Isbn)));
+    }
+    let mut scored =
+        koreader::scores_for(storage, &Query::new(kb.title.as_deref(), &kb.authors)).await?;
+    if scored.first().is_some_and(|s| s.can_auto) {
+        return Ok(Some((scored.remove(0).book, MatchMethod::Title)));
+    }
+    Ok(None)
+}
+
+/// Library books in the ambiguous band for this Kobo book, best first.
+pub async fn match_candidates(storage: &Storage, kb: &KoboBook) -> Result<Vec<MatchCandidate>> {
+    let scored =
+        koreader::scores_for(storage, &Query::new(kb.title.as_deref(), &kb.authors)).await?;
+    Ok(koreader::band(scored))
+}
+
+// ---- import ----------------------------------------------------------------
+
+/// Import every book in a Kobo's database that matches one here. The
+/// [`koreader::import`] of this source: idempotent, and an unmatched book is
+/// reported with its candidates rather than created.
+#[tracing::instrument(skip(storage), fields(path = %path.display()))]
+pub async fn import(storage: &Storage, path: &Path, dry_run: bool) -> Result<ImportReport> {
+    let Some(db) = kobo_db(path) else {
+        return Err(EngineError::NotFound(format!(
+            "no Kobo database at {} (looked for {KOBO_DB})",
+            path.display()
+        )));
+    };
+    let mut report = ImportReport::default();
+    for kb in read_library(&db).await? {
+        let md5 = kb.partial_md5();
+        let Some((book, matched_by)) = match_book(storage, &kb, md5.as_deref()).await? else {
+            let candidates = match_candidates(storage, &kb).await?;
+            report.unmatched.push(UnmatchedSidecar {
+                path: kb.path,
+                title: kb.title,
+                partial_md5: md5,
+                candidates,
+            });
+            continue;
+        };
+        let Some(book_id) = book.id else {
+            tracing::error!(title = %book.display_title(), "matched book has no id; skipping");
+            continue;
+        };
+        if !dry_run && let Some(md5) = &md5 {
+            storage
+                .link_device_book(md5, book_id, LinkedBy::Auto)
+                .await?;
+        }
+        let title = book.display_title().to_string();
+        report
+            .imported
+            .push(import_into(storage, book_id, title, matched_by, &kb, dry_run).await?);
+    }
+    Ok(report)
+}
+
+/// Pull one Kobo book in, creating it when nothing here matches. `path` is a
+/// device row's path — the book's file on the mount; see [`locate`].
+///
+/// The twin of [`koreader::import_book_from_sidecar`], and an error rather
+/// than a warning when the book is not there, for the same reason: the user
+/// asked for this one by name.
+pub async fn pull(storage: &Storage, path: &Path) -> Result<PullReport> {
+    let Some((db, content_id)) = locate(path) else {
+        return Err(EngineError::NotFound(format!(
+            "{} is not on a Kobo (no {KOBO_DB} above it)",
+            path.display()
+        )));
+    };
+    let kb = read_library(&db)
+        .await?
+        .into_iter()
+        .find(|b| b.content_id == content_id)
+        .ok_or_else(|| EngineError::NotFound(format!("{content_id} in {}", db.display())))?;
+
+    let mut warnings = Vec::new();
+    let md5 = kb.partial_md5();
+    if md5.is_none() {
+        tracing::warn!(path = %path.display(), "Kobo book has no file on the device; cannot key a link");
+        warnings.push(Diagnostic::kobo_not_identified(kb.path.clone()));
+    }
+    let (book_id, matched_by) = match match_book(storage, &kb, md5.as_deref()).await? {
+        Some((book, method)) => (
+            book.id
+                .ok_or_else(|| EngineError::Other("matched book has no id".into()))?,
+            method,
+        ),
+        None => (
+            storage.upsert_book(&book_from(&kb)).await?,
+            MatchMethod::New,
+        ),
+    };
+    if let Some(md5) = &md5 {
+        storage
+            .link_device_book(md5, book_id, LinkedBy::Auto)
+            .await?;
+    }
+    let title = storage
+        .get_book(book_id)
+        .await?
+        .map(|b| b.display_title().to_string())
+        .unwrap_or_default();
+    let stats = import_into(storage, book_id, title, matched_by, &kb, false).await?;
+    Ok(PullReport { stats, warnings })
+}
+
+/// The book a Kobo describes, as far as it knows. Offline, like a sidecar
+/// pull: no provider enrichment, and an ISBN only when Nickel has a valid one.
+fn book_from(kb: &KoboBook) -> Book {
+    let isbn = kb.isbn.as_deref().and_then(normalize_isbn);
+    Book {
+        title: kb.title.clone(),
+        authors: kb.authors.clone(),
+        language: kb.language.clone(),
+        isbn_13: isbn.clone().filter(|i| i.len() == 13),
+        isbn_10: isbn.filter(|i| i.len() == 10),
+        ..Default::default()
+    }
+}
+
+async fn import_into(
+    storage: &Storage,
+    book_id: i64,
+    book_title: String,
+    matched_by: MatchMethod,
+    kb: &KoboBook,
+    dry_run: bool,
+) -> Result<BookImportStats> {
+    let mut stats = BookImportStats {
+        book_id,
+        book_title,
+        inserted: 0,
+        updated: 0,
+        skipped: 0,
+        flashcards: 0,
+        matched_by,
+        percent_finished: kb.percent,
+        status: kb.status.clone(),
+        // Nickel keeps star ratings in a table of their own, keyed to the
+        // store; a sideloaded book has none to read.
+        rating: None,
+    };
+    koreader::write_highlights(storage, book_id, &kb.highlights, dry_run, &mut stats).await?;
+    if !dry_run {
+        persist_device_state(storage, book_id, kb).await?;
+    }
+    tracing::info!(
+        book_id,
+        inserted = stats.inserted,
+        updated = stats.updated,
+        skipped = stats.skipped,
+        matched_by = %stats.matched_by,
+        status = stats.status.as_ref().map(|s| s.to_string()),
+        percent_finished = stats.percent_finished,
+        dry_run,
+        "imported kobo book"
+    );
+    Ok(stats)
+}
+
+/// `koreader::persist_device_state`'s rules, for a Kobo. A reading opens only
+/// when the device has state to put on it, starting at the first highlight;
+/// finished on the device closes it. Unlike a sidecar's, the mirror here never
+/// includes a rating, so the one the reading has is left alone rather than
+/// cleared.
+async fn persist_device_state(storage: &Storage, book_id: i64, kb: &KoboBook) -> Result<()> {
+    if kb.status.is_none() && kb.percent.is_none() && kb.last_read.is_none() {
+        return Ok(());
+    }
+    let started = kb
+        .highlights
+        .iter()
+        .filter_map(|h| h.ko_datetime.as_deref())
+        .filter_map(ko_datetime_to_unix)
+        .min();
+    storage.ensure_reading(book_id, started, SOURCE).await?;
+    let rating = storage.device_rating(book_id).await?;
+    storage
+        .set_device_state(book_id, kb.status.as_ref(), kb.percent, rating)
+        .await?;
+    storage.set_device_last_read(book_id, kb.last_read).await?;
+    if kb.status == Some(KoStatus::Complete) {
+        storage.finish_reading(book_id).await?;
+    }
+    storage.attribute_highlights(book_id).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_content_id_and_a_path_are_the_same_book_both_ways() {
+        let mount = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(mount.path().join(".kobo")).unwrap();
+        std::fs::write(mount.path().join(KOBO_DB), b"").unwrap();
+
+        for id in [
+            "file:///mnt/onboard/Books/Kindred.kepub.epub",
+            "0b7c1f4e-2a39-4c1b-9f0e-6d2a1b7e8c11",
+        ] {
+            let path = book_path(mount.path(), id);
+            let (db, back) = locate(&path).expect("on the mount");
+            assert_eq!(back, id);
+            assert_eq!(db, mount.path().join(KOBO_DB));
+        }
+        assert_eq!(locate(Path::new("/nowhere/Books/x.epub")), None);
+    }
+
+    #[test]
+    fn nickel_timestamps_read_as_koreader_ones() {
+        assert_eq!(
+            kobo_datetime("2018-03-05T22:41:11Z").as_deref(),
+            Some("2018-03-05 22:41:11")
+        );
+        assert_eq!(
+            kobo_datetime("2018-03-05T22:41:11.000").as_deref(),
+            Some("2018-03-05 22:41:11")
+        );
+        assert_eq!(kobo_datetime("yesterday"), None);
+    }
+
+    #[test]
+    fn attribution_splits_on_joins_but_not_on_a_surname_comma() {
+        assert_eq!(
+            split_attribution(Some("Neil Gaiman & Terry Pratchett")),
+            vec!["Neil Gaiman", "Terry Pratchett"]
+        );
+        assert_eq!(
+            split_attribution(Some("Neil Gaiman, Terry Pratchett")),
+            vec!["Neil Gaiman", "Terry Pratchett"]
+        );
+        assert_eq!(
+            split_attribution(Some("Butler, Octavia E.")),
+            vec!["Butler, Octavia E."]
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Installing readingbuddy's own KOReader plugin onto a mounted reader, and
+//! taking it off again.
+//!
+//! `docs/decisions.md` makes the rules non-negotiable, and each has one place
+//! here:
+//!
+//! - **a real KOReader install only** — [`crate::device::koreader_dir`] is the
+//!   gate, the same predicate the device screen uses to find the books;
+//! - **only inside our own directory** — every path written or removed is
+//!   `plugins/readingbuddy.koplugin/<name>` with `<name>` a bare file name, and
+//!   a symlinked plugin directory is refused rather than followed;
+//! - **create-only, upgrade replaces only ours, never downgrade** — a
+//!   directory without our manifest is someone else's, and a newer version is
+//!   left as it is;
+//! - **exact uninstall** — the manifest lists what we wrote, and removal takes
+//!   exactly that; anything else found in the directory stays, and is reported.
+//!
+//! Never called from a scan or a mount event: installing is always something
+//! the user asked for, on a path they were shown.
+//!
+//! The plugin's Lua is committed under `crates/engine/koplugin/` and compiled
+//! in, so the version installed is always the one this build was tested with.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::device::koreader_dir;
+use crate::error::{EngineError, Result};
+
+/// The directory name KOReader loads a plugin from, under `plugins/`.
+pub const PLUGIN_DIR: &str = "readingbuddy.koplugin";
+
+/// The version of the plugin this build carries. `_meta.lua` says the same,
+/// and a test holds the two together.
+pub const PLUGIN_VERSION: u32 = 1;
+
+/// What we write into the plugin directory beside the plugin, and the only
+/// thing that marks the directory as ours.
+const MANIFEST: &str = "readingbuddy.manifest";
+
+/// The plugin, file by file.
+const FILES: [(&str, &str); 2] = [
+    (
+        "_meta.lua",
+        include_str!("../koplugin/readingbuddy.koplugin/_meta.lua"),
+    ),
+    (
+        "main.lua",
+        include_str!("../koplugin/readingbuddy.koplugin/main.lua"),
+    ),
+];
+
+/// What is in a reader's plugin directory, compared with what this build
+/// would install.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginState {
+    NotInstalled,
+    /// The version this build carries.
+    Current,
+    /// An earlier version; an install upgrades it.
+    Older {
+        installed: u32,
+    },
+    /// A later version than this build knows, from a newer readingbuddy. Left
+    /// alone: a downgrade is never what the user meant.
+    Newer {
+        installed: u32,
+    },
+    /// A `readingbuddy.koplugin` we did not write — no manifest, one we cannot
+    /// read, or a symlink. Never touched.
+    Foreign {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginStatus {
+    /// Where the plugin is, or would go. Shown before any install.
+    pub plugin_dir: PathBuf,
+    pub bundled: u32,
+    pub state: PluginState,
+}
+
+/// What an install did, file by file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginInstall {
+    pub plugin_dir: PathBuf,
+    pub version: u32,
+    /// The version this replaced, if it was an upgrade.
+    pub replaced: Option<u32>,
+    /// Every file written, the manifest included. Empty when the current
+    /// version was already there.
+    pub written: Vec<PathBuf>,
+    /// Files of the older version the new one no longer has.
+    pub removed: Vec<PathBuf>,
+}
+
+/// What an uninstall did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginRemoval {
+    pub plugin_dir: PathBuf,
+    /// The version removed; `None` when there was nothing to remove.
+    pub version: Option<u32>,
+    pub removed: Vec<PathBuf>,
+    /// Files in the plugin directory that we did not write, and so left there
+    /// along with the directory itself.
+    pub kept: Vec<PathBuf>,
+}
+
+/// The plugin directory on this mount, or why there cannot be one.
+fn plugin_dir(mount: &Path) -> Result<PathBuf> {
+    let Some(ko) = koreader_dir(mount) else {
+        return Err(EngineError::InvalidInput(format!(
+            "{} is not a KOReader install, so there is nowhere to put the plugin",
+            mount.display()
+        )));
+    };
+    Ok(ko.join("plugins").join(PLUGIN_DIR))
+}
+
+/// Compare what is installed on `mount` with what this build carries.
+pub fn status(mount: &Path) -> Result<PluginStatus> {
+    let dir = plugin_dir(mount)?;
+    let state = match read_manifest(&dir)? {
+        Installed::Absent => PluginState::NotInstalled,
+        Installed::Foreign(reason) => PluginState::Foreign { reason },
+        Installed::Ours { version, .. } => match version.cmp(&PLUGIN_VERSION) {
+            std::cmp::Ordering::Equal => PluginState::Current,
+            std::cmp::Ordering::Less => PluginState::Older { installed: version },
+            std::cmp::Ordering::Greater => PluginState::Newer { installed: version },
+        },
+    };
+    Ok(PluginStatus {
+        plugin_dir: dir,
+        bundled: PLUGIN_VERSION,
+        state,
+    })
+}
+
+/// Install, or upgrade, the plugin on `mount`.
+///
+/// Only files that differ from this build's are written, so the current
+/// version already there is a no-op — and one with a file missing or edited is
+/// put right. A newer version, or a directory that is not ours, is refused
+/// with [`EngineError::PluginRefused`] and nothing written.
+pub fn install(mount: &Path) -> Result<PluginInstall> {
+    let dir = plugin_dir(mount)?;
+    let (replaced, old_files) = match read_manifest(&dir)? {
+        Installed::Absent => (None, Vec::new()),
+        Installed::Foreign(reason) => return Err(refused(&dir, reason)),
+        Installed::Ours { version, .. } if version > PLUGIN_VERSION => {
+            return Err(refused(
+                &dir,
+                format!("version {version} is installed, newer than this build's {PLUGIN_VERSION}"),
+            ));
+        }
+        Installed::Ours { version, files } => {
+            ((version != PLUGIN_VERSION).then_some(version), files)
+        }
+    };
+
+    std::fs::create_dir_all(&dir)?;
+    let new: BTreeSet<&str> = FILES.iter().map(|(name, _)| *name).collect();
+    let mut listed = new.clone();
+    listed.extend(old_files.iter().map(String::as_str));
+
+    // The manifest goes first, listing every file that may be in the directory
+    // by the end: a copy interrupted part-way leaves a directory that is still
+    // visibly ours and still exactly removable, and the next install, finding
+    // the files that differ, finishes the job.
+    let mut manifest_written = write_if_changed(&dir, MANIFEST, &manifest(&listed))?;
+    let mut written = Vec::new();
+    for (name, body) in FILES {
+        if write_if_changed(&dir, name, body)? {
+            written.push(dir.join(name));
+        }
+    }
+
+    let mut removed = Vec::new();
+    for name in old_files.iter().filter(|n| !new.contains(n.as_str())) {
+        let path = dir.join(name);
+        if remove_file(&path)? {
+            removed.push(path);
+        }
+    }
+
+    manifest_written |= write_if_changed(&dir, MANIFEST, &manifest(&new))?;
+    if manifest_written {
+        written.push(dir.join(MANIFEST));
+    }
+
+    Ok(PluginInstall {
+        plugin_dir: dir,
+        version: PLUGIN_VERSION,
+        replaced,
+        written,
+        removed,
+    })
+}
+
+/// Remove the plugin from `mount`: the files the manifest lists, the manifest,
+/// then the directory if that leaves it empty.
+pub fn uninstall(mount: &Path) -> Result<PluginRemoval> {
+    let dir = plugin_dir(mount)?;
+    let (version, files) = match read_manifest(&dir)? {
+        Installed::Absent => {
+            return Ok(PluginRemoval {
+                plugin_dir: dir,
+                version: None,
+                removed: Vec::new(),
+                kept: Vec::new(),
+            });
+        }
+        Installed::Foreign(reason) => return Err(refused(&dir, reason)),
+        Installed::Ours { version, files } => (version, files),
+    };
+
+    let mut removed = Vec::new();
+    for name in files.iter().map(String::as_str).chain([MANIFEST]) {
+        let path = dir.join(name);
+        if remove_file(&path)? {
+            removed.push(path);
+        }
+    }
+
+    let mut kept: Vec<PathBuf> = std::fs::read_dir(&dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    kept.sort();
+    if kept.is_empty() {
+        std::fs::remove_dir(&dir)?;
+    }
+
+    Ok(PluginRemoval {
+        plugin_dir: dir,
+        version: Some(version),
+        removed,
+        kept,
+    })
+}
+
+enum Installed {
+    Absent,
+    Foreign(String),
+    Ours { version: u32, files: Vec<String> },
+}
+
+fn read_manifest(dir: &Path) -> Result<Installed> {
+    let meta = match std::fs::symlink_metadata(dir) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Installed::Absent),
+        Err(e) => return Err(e.into()),
+    };
+    if meta.file_type().is_symlink() {
+        return Ok(Installed::Foreign("it is a symlink".into()));
+    }
+    if !meta.is_dir() {
+        return Ok(Installed::Foreign("it is not a directory".into()));
+    }
+    let src = match std::fs::read_to_string(dir.join(MANIFEST)) {
+        Ok(src) => src,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Installed::Foreign(format!(
+                "it has no {MANIFEST}, so readingbuddy did not install it"
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    Ok(parse_manifest(&src).map_or_else(
+        || Installed::Foreign(format!("its {MANIFEST} is not one readingbuddy wrote")),
+        |(version, files)| Installed::Ours { version, files },
+    ))
+}
+
+/// `version N`, then one bare file name per line; `#` lines are comments.
+///
+/// A name with a separator in it, or `..`, makes the whole manifest unreadable
+/// rather than being skipped: uninstall removes what this lists, and a list
+/// that points outside the directory is not one we wrote.
+fn parse_manifest(src: &str) -> Option<(u32, Vec<String>)> {
+    let mut lines = src
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'));
+    let version = lines.next()?.strip_prefix("version ")?.parse().ok()?;
+    let mut files = Vec::new();
+    for name in lines {
+        let bare = !name.contains(['/', '\\']) && name != ".." && name != "." && name != MANIFEST;
+        if !bare {
+            return None;
+        }
+        files.push(name.to_string());
+    }
+    Some((version, files))
+}
+
+fn manifest(files: &BTreeSet<&str>) -> String {
+    let mut out = String::from(
+        "# Written by readingbuddy. It lists the files it installed here, and\n\
+         # `readingbuddy ko plugin remove` removes exactly those.\n",
+    );
+    out.push_str(&format!("version {PLUGIN_VERSION}\n"));
+    for name in files {
+        out.push_str(name);
+        out.push('\n');
+    }
+    out
+}
+
+/// Write one file unless it already says exactly this; `true` if it was
+/// written.
+///
+/// Through a temporary file and a rename, so a reader unplugged mid-copy has
+/// the old file or the new one, never half of either.
+fn write_if_changed(dir: &Path, name: &str, body: &str) -> Result<bool> {
+    let path = dir.join(name);
+    if std::fs::read(&path).ok().as_deref() == Some(body.as_bytes()) {
+        return Ok(false);
+    }
+    let tmp = dir.join(format!("{name}.readingbuddy-tmp"));
+    std::fs::write(&tmp, body)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(true)
+}
+
+/// Remove one file, if it is there. A file already gone is not an error: the
+/// manifest says what we wrote, not what survives.
+fn remove_file(path: &Path) -> Result<bool> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn refused(dir: &Path, reason: String) -> EngineError {
+    EngineError::PluginRefused {
+        dir: dir.to_path_buf(),
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::koreader::{eval_sidecar, get_int, get_str, sandbox};
+
+    /// `_meta.lua` is what KOReader shows, and `PLUGIN_VERSION` is what the
+    /// downgrade check compares. Two numbers for one fact, so they are held
+    /// together here.
+    #[test]
+    fn the_bundled_meta_names_the_plugin_and_its_version() {
+        let lua = sandbox().unwrap();
+        let meta = eval_sidecar(&lua, FILES[0].1).unwrap();
+        assert_eq!(get_str(&meta, "name").as_deref(), Some("readingbuddy"));
+        assert_eq!(get_int(&meta, "version"), Some(i64::from(PLUGIN_VERSION)));
+    }
+
+    #[test]
+    fn the_manifest_round_trips_and_refuses_paths_outside_the_directory() {
+        let files: BTreeSet<&str> = FILES.iter().map(|(name, _)| *name).collect();
+        let (version, files) = parse_manifest(&manifest(&files)).unwrap();
+        assert_eq!(version, PLUGIN_VERSION);
+        assert_eq!(files, ["_meta.lua", "main.lua"]);
+
+        for hostile in ["version 1\n../../reader.lua\n", "version 1\nsub/main.lua\n"] {
+            assert_eq!(parse_manifest(hostile), None, "{hostile:?}");
+        }
+        assert_eq!(parse_manifest("main.lua\n"), None, "no version line");
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use mlua::{Lua, LuaOptions, StdLib, Table, Value};
+use mlua::{Lua, LuaOptions, LuaSerdeExt, StdLib, Table, Value};
 
 use crate::book::Book;
 use crate::diagnostic::Diagnostic;
@@ -153,6 +153,62 @@ pub struct KoStats {
 /// (2024+) flat `annotations` array and the legacy `highlight`+`bookmarks`
 /// page-keyed layout.
 pub fn parse_sidecar(src: &str) -> Result<KoSidecar> {
+    let lua = sandbox()?;
+    let root = eval_sidecar(&lua, src)?;
+    parse_root(&root)
+}
+
+/// The same sidecar, arrived as JSON — what KOReader's own `json.encode` makes
+/// of the table, which is how a plugin on the reader sends it without writing
+/// a Lua serialiser of its own.
+///
+/// Converted into a table in the same sandbox and walked by the same code, so
+/// the two forms cannot drift apart. An array becomes a sequence from `1`, as
+/// it was in the Lua; an object's numeric keys arrive as strings and are read
+/// back as the integers they were.
+pub fn parse_sidecar_json(json: &serde_json::Value) -> Result<KoSidecar> {
+    let lua = sandbox()?;
+    let value = lua
+        .to_value(json)
+        .map_err(|e| EngineError::Sidecar(format!("json: {e}")))?;
+    let Value::Table(root) = value else {
+        return Err(EngineError::Sidecar("sidecar JSON is not an object".into()));
+    };
+    parse_root(&root)
+}
+
+fn parse_root(root: &Table) -> Result<KoSidecar> {
+    let mut sidecar = KoSidecar {
+        partial_md5: get_str(root, "partial_md5_checksum"),
+        doc_pages: get_int(root, "doc_pages"),
+        percent_finished: get_f64(root, "percent_finished"),
+        // `summary`, `stats` and `percent_finished` are DocSettings *root*
+        // keys, written by subsystems that never look at the annotations
+        // layout. Reading them before the layout dispatch below is what makes a
+        // legacy sidecar carry them too — pinned by `Gen-Summary-Legacy`.
+        summary: get_table(root, "summary").map(|t| parse_summary(&t)),
+        stats: get_table(root, "stats").map(|t| parse_stats(&t)),
+        ..Default::default()
+    };
+    if let Some(props) = get_table(root, "doc_props") {
+        sidecar.title = get_str(&props, "title");
+        sidecar.authors = get_str(&props, "authors");
+        sidecar.language = get_str(&props, "language");
+    }
+
+    if let Some(annotations) = get_table(root, "annotations") {
+        sidecar.highlights = parse_annotations(&annotations)?;
+    } else if let Some(highlight) = get_table(root, "highlight") {
+        let notes_by_datetime = get_table(root, "bookmarks")
+            .map(|b| bookmark_notes(&b))
+            .unwrap_or_default();
+        sidecar.highlights = parse_legacy(&highlight, &notes_by_datetime)?;
+    }
+    Ok(sidecar)
+}
+
+/// The VM a sidecar is evaluated in: no stdlib, and an instruction budget.
+pub(crate) fn sandbox() -> Result<Lua> {
     let lua = Lua::new_with(StdLib::NONE, LuaOptions::default())
         .map_err(|e| EngineError::Sidecar(format!("lua init: {e}")))?;
 
@@ -173,6 +229,11 @@ pub fn parse_sidecar(src: &str) -> Result<KoSidecar> {
         },
     );
 
+    Ok(lua)
+}
+
+/// Evaluate a sidecar chunk in [`sandbox`]'s VM and return its root table.
+pub(crate) fn eval_sidecar(lua: &Lua, src: &str) -> Result<Table> {
     let value: Value = lua
         .load(src)
         .eval()
@@ -182,44 +243,17 @@ pub fn parse_sidecar(src: &str) -> Result<KoSidecar> {
             "sidecar did not return a table".into(),
         ));
     };
-
-    let mut sidecar = KoSidecar {
-        partial_md5: get_str(&root, "partial_md5_checksum"),
-        doc_pages: get_int(&root, "doc_pages"),
-        percent_finished: get_f64(&root, "percent_finished"),
-        // `summary`, `stats` and `percent_finished` are DocSettings *root*
-        // keys, written by subsystems that never look at the annotations
-        // layout. Reading them before the layout dispatch below is what makes a
-        // legacy sidecar carry them too — pinned by `Gen-Summary-Legacy`.
-        summary: get_table(&root, "summary").map(|t| parse_summary(&t)),
-        stats: get_table(&root, "stats").map(|t| parse_stats(&t)),
-        ..Default::default()
-    };
-    if let Some(props) = get_table(&root, "doc_props") {
-        sidecar.title = get_str(&props, "title");
-        sidecar.authors = get_str(&props, "authors");
-        sidecar.language = get_str(&props, "language");
-    }
-
-    if let Some(annotations) = get_table(&root, "annotations") {
-        sidecar.highlights = parse_annotations(&annotations)?;
-    } else if let Some(highlight) = get_table(&root, "highlight") {
-        let notes_by_datetime = get_table(&root, "bookmarks")
-            .map(|b| bookmark_notes(&b))
-            .unwrap_or_default();
-        sidecar.highlights = parse_legacy(&highlight, &notes_by_datetime)?;
-    }
-    Ok(sidecar)
+    Ok(root)
 }
 
-fn get_str(t: &Table, key: &str) -> Option<String> {
+pub(crate) fn get_str(t: &Table, key: &str) -> Option<String> {
     t.get::<Option<String>>(key)
         .ok()
         .flatten()
         .filter(|s| !s.is_empty())
 }
 
-fn get_int(t: &Table, key: &str) -> Option<i64> {
+pub(crate) fn get_int(t: &Table, key: &str) -> Option<i64> {
     t.get::<Option<i64>>(key).ok().flatten()
 }
 
@@ -229,7 +263,7 @@ fn get_f64(t: &Table, key: &str) -> Option<f64> {
     t.get::<Option<f64>>(key).ok().flatten()
 }
 
-fn get_table(t: &Table, key: &str) -> Option<Table> {
+pub(crate) fn get_table(t: &Table, key: &str) -> Option<Table> {
     t.get::<Option<Table>>(key).ok().flatten()
 }
 
@@ -256,7 +290,7 @@ fn parse_stats(t: &Table) -> KoStats {
     }
 }
 
-fn entry_to_highlight(item: &Table, page: Option<i64>) -> Option<NewHighlight> {
+pub(crate) fn entry_to_highlight(item: &Table, page: Option<i64>) -> Option<NewHighlight> {
     let text = get_str(item, "text")?;
     // Modern `annotations` mixes highlights and plain bookmarks; a real
     // highlight always carries a pos0 xpointer.
@@ -800,7 +834,45 @@ async fn import_into(
         status,
         rating: summary.and_then(|s| s.rating),
     };
-    for h in &sc.highlights {
+    write_highlights(storage, book_id, &sc.highlights, dry_run, &mut stats).await?;
+    if !dry_run {
+        persist_device_state(storage, book_id, sc, &stats).await?;
+    }
+
+    // `summary.note` is the user's review — private reading, the same class
+    // as highlight text and note bodies. It is deliberately absent from
+    // every field here and must never rise above `trace!`. Status, rating
+    // and progress are device state, not prose, and are fine to log.
+    tracing::info!(
+        book_id,
+        inserted = stats.inserted,
+        updated = stats.updated,
+        skipped = stats.skipped,
+        flashcards = stats.flashcards,
+        matched_by = %stats.matched_by,
+        status = stats.status.as_ref().map(|s| s.to_string()),
+        rating = stats.rating,
+        percent_finished = stats.percent_finished,
+        dry_run,
+        "imported sidecar"
+    );
+    Ok(stats)
+}
+
+/// The insert/refresh/skip decision for one book's device highlights, counted
+/// into `stats`.
+///
+/// `pub(crate)` for [`crate::kobo`]: a Kobo's highlights are device-owned by
+/// exactly the rules a sidecar's are, and a second copy of this loop would be
+/// a second answer to what `updated` means.
+pub(crate) async fn write_highlights(
+    storage: &Storage,
+    book_id: i64,
+    highlights: &[NewHighlight],
+    dry_run: bool,
+    stats: &mut BookImportStats,
+) -> Result<()> {
+    for h in highlights {
         if dry_run {
             if storage.highlight_exists(book_id, h).await? {
                 // A preview that reported a device edit as "already known"
@@ -844,28 +916,7 @@ async fn import_into(
             }
         }
     }
-    if !dry_run {
-        persist_device_state(storage, book_id, sc, &stats).await?;
-    }
-
-    // `summary.note` is the user's review — private reading, the same class
-    // as highlight text and note bodies. It is deliberately absent from
-    // every field here and must never rise above `trace!`. Status, rating
-    // and progress are device state, not prose, and are fine to log.
-    tracing::info!(
-        book_id,
-        inserted = stats.inserted,
-        updated = stats.updated,
-        skipped = stats.skipped,
-        flashcards = stats.flashcards,
-        matched_by = %stats.matched_by,
-        status = stats.status.as_ref().map(|s| s.to_string()),
-        rating = stats.rating,
-        percent_finished = stats.percent_finished,
-        dry_run,
-        "imported sidecar"
-    );
-    Ok(stats)
+    Ok(())
 }
 
 /// Mirror the sidecar's reading state onto a reading, and attribute the
@@ -957,7 +1008,67 @@ pub async fn import_book_from_sidecar(storage: &Storage, sidecar: &Path) -> Resu
     // book, so an unreadable or unparsable sidecar is an error here.
     let src = std::fs::read_to_string(sidecar)?;
     let sc = parse_sidecar(&src)?;
+    pull_parsed(storage, &sc, sidecar).await
+}
+
+/// A sidecar that arrived over the network rather than off a mounted volume.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SidecarPayload {
+    /// The file as KOReader wrote it: `return { ... }`.
+    Lua(String),
+    /// The same table through `json.encode`.
+    Json(serde_json::Value),
+}
+
+/// Pull a book in from a sidecar sent by the reader itself — the wireless
+/// twin of [`import_book_from_sidecar`], and the same path from the parse on.
+///
+/// `partial_md5` is the document's, from the reader: a sidecar written before
+/// the book was ever opened to the end may not carry one yet, and it is the
+/// key every later pull de-duplicates on, so it is required here rather than
+/// degraded to a warning. When the sidecar does carry one, the two must agree.
+/// `origin` is where the reader said the document is, for the diagnostics; the
+/// checksum stands in when it said nothing. `device` is the paired reader that
+/// sent it, if one did, and its name goes on what the pull wrote.
+pub async fn pull_payload(
+    storage: &Storage,
+    payload: &SidecarPayload,
+    partial_md5: &str,
+    origin: Option<&Path>,
+    device: Option<i64>,
+) -> Result<PullReport> {
+    let md5 = partial_md5.trim().to_ascii_lowercase();
+    if md5.len() != 32 || !md5.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(EngineError::InvalidInput(format!(
+            "{partial_md5:?} is not a partial MD5"
+        )));
+    }
+    let mut sc = match payload {
+        SidecarPayload::Lua(src) => parse_sidecar(src)?,
+        SidecarPayload::Json(json) => parse_sidecar_json(json)?,
+    };
+    match &sc.partial_md5 {
+        Some(own) if !own.eq_ignore_ascii_case(&md5) => {
+            return Err(EngineError::InvalidInput(format!(
+                "the sidecar is for {own}, not {md5}"
+            )));
+        }
+        _ => sc.partial_md5 = Some(md5.clone()),
+    }
+    let origin = origin.map_or_else(|| PathBuf::from(&md5), Path::to_path_buf);
+    let report = pull_parsed(storage, &sc, &origin).await?;
+    if let Some(device) = device {
+        let stats = &report.stats;
+        let carried_state =
+            stats.status.is_some() || stats.percent_finished.is_some() || stats.rating.is_some();
+        storage
+            .record_device_report(device, stats.book_id, &sc.highlights, carried_state)
+            .await?;
+    }
+    Ok(report)
+}
 
+async fn pull_parsed(storage: &Storage, sc: &KoSidecar, sidecar: &Path) -> Result<PullReport> {
     let mut warnings = Vec::new();
     let (book_id, matched_by) = match sc.partial_md5.as_deref() {
         Some(md5) => match storage.find_book_by_partial_md5(md5).await? {
@@ -968,7 +1079,7 @@ pub async fn import_book_from_sidecar(storage: &Storage, sidecar: &Path) -> Resu
                 (id, MatchMethod::Md5)
             }
             None => {
-                let id = storage.upsert_book(&book_from_sidecar(&sc)).await?;
+                let id = storage.upsert_book(&book_from_sidecar(sc)).await?;
                 storage.link_device_book(md5, id, LinkedBy::Auto).await?;
                 (id, MatchMethod::New)
             }
@@ -984,7 +1095,7 @@ pub async fn import_book_from_sidecar(storage: &Storage, sidecar: &Path) -> Resu
             );
             warnings.push(Diagnostic::sidecar_not_identified(sidecar.to_path_buf()));
             (
-                storage.upsert_book(&book_from_sidecar(&sc)).await?,
+                storage.upsert_book(&book_from_sidecar(sc)).await?,
                 MatchMethod::New,
             )
         }
@@ -1000,7 +1111,7 @@ pub async fn import_book_from_sidecar(storage: &Storage, sidecar: &Path) -> Resu
         book_title,
         matched_by,
     };
-    let stats = import_into(storage, target, &sc, sidecar, false, &mut warnings).await?;
+    let stats = import_into(storage, target, sc, sidecar, false, &mut warnings).await?;
     Ok(PullReport { stats, warnings })
 }
 
@@ -0,0 +1,505 @@
+//! Pushing the reader's own annotations back into a KOReader sidecar.
+//!
+//! The only place the engine writes to a mounted reader, and written to that
+//! standard. An annotation made here (`highlights.annotation`) goes into the
+//! matching entry's `note` in the sidecar's `annotations` table, so it is on
+//! the page the next time the book is opened.
+//!
+//! **Nothing is clobbered.** KOReader owns the file and rewrites it on every
+//! page turn, so a push first checks that the file is still the one the last
+//! pull saw: every highlight in it is one we hold, every device note is the
+//! `last_seen_ko_note` we recorded, and the reading state is what
+//! `readings.ko_*` mirrors. Any difference is [`EngineError::DeviceChanged`] —
+//! pull, look, push again — and the file is not touched. The file's size and
+//! mtime are checked once more immediately before the write, which is the
+//! `sidecar_seen` rule applied to the window between reading and writing.
+//!
+//! **The file is rewritten whole, the way KOReader writes it** — `dump.lua`'s
+//! layout: keys sorted, four-space indent, `%q` strings, numbers as `%.14g`.
+//! Editing the text in place would mean parsing Lua string syntax by hand; the
+//! evaluated table is exact, and KOReader's own next flush rewrites the file
+//! from its table anyway. Comments inside the table do not survive, which no
+//! real sidecar has. See `docs/koreader-format.md` §3.
+//!
+//! The original is copied aside first, and `annotations_externally_modified`
+//! is set so KOReader reconciles rather than trusting its in-memory copy.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use mlua::{Table, Value};
+use time::OffsetDateTime;
+
+use crate::error::{EngineError, Result};
+use crate::koreader;
+use crate::storage::{NewHighlight, Storage};
+
+/// How deep a sidecar's tables may nest before the writer refuses it. A real
+/// one is four deep; anything past this is a cycle or not a sidecar.
+const MAX_DEPTH: usize = 64;
+
+/// One note the push writes, or would.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushChange {
+    /// The entry's index in the sidecar's `annotations` table.
+    pub index: i64,
+    pub highlight_id: i64,
+    /// The highlighted passage, for the caller to show which one this is.
+    pub text: String,
+    /// The device's note before the push.
+    pub before: Option<String>,
+    /// Our annotation, which replaces it.
+    pub after: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PushReport {
+    pub sidecar: PathBuf,
+    pub book_id: i64,
+    pub dry_run: bool,
+    /// In the sidecar's own order. Empty when the device already says what we
+    /// do, in which case nothing was written.
+    pub changes: Vec<PushChange>,
+    /// Where the original went. `None` on a dry run or when nothing changed.
+    pub backup: Option<PathBuf>,
+}
+
+/// Write this book's annotations into its sidecar. `path` is the
+/// `metadata.*.lua` or its `.sdr` directory, and the sidecar must already be
+/// linked to a book here — a push goes into a file a pull has read.
+#[tracing::instrument(skip(storage), fields(path = %path.display()))]
+pub async fn push(storage: &Storage, path: &Path, dry_run: bool) -> Result<PushReport> {
+    let sidecar = one_sidecar(path)?;
+    let stamp = file_stamp(&sidecar)?;
+    let src = std::fs::read_to_string(&sidecar)?;
+    let sc = koreader::parse_sidecar(&src)?;
+
+    let not_linked = || {
+        EngineError::NotFound(format!(
+            "{} is not linked to a book here — pull or link it first",
+            sidecar.display()
+        ))
+    };
+    let md5 = sc.partial_md5.as_deref().ok_or_else(not_linked)?;
+    let book_id = storage
+        .find_book_by_partial_md5(md5)
+        .await?
+        .and_then(|b| b.id)
+        .ok_or_else(not_linked)?;
+    let changed = |reason: String| EngineError::DeviceChanged {
+        path: sidecar.clone(),
+        reason,
+    };
+
+    let summary = sc.summary.as_ref();
+    if storage
+        .device_state_differs(
+            book_id,
+            summary.and_then(|s| s.status.as_ref()),
+            sc.percent_finished,
+            summary.and_then(|s| s.rating),
+        )
+        .await?
+    {
+        return Err(changed("read further, or its status changed".into()));
+    }
+
+    let Some(entries) = annotation_entries(&src)? else {
+        if sc.highlights.is_empty() {
+            return Ok(report(sidecar, book_id, dry_run, Vec::new()));
+        }
+        return Err(EngineError::InvalidInput(format!(
+            "{} is in KOReader's pre-2024 layout; open the book once in a current \
+             KOReader to convert it, then pull and push again",
+            sidecar.display()
+        )));
+    };
+
+    let mut changes = Vec::new();
+    for (index, h) in entries {
+        let Some((stored, last_seen)) = storage.device_highlight(book_id, &h).await? else {
+            return Err(changed(format!(
+                "“{}” was highlighted there",
+                excerpt(&h.text)
+            )));
+        };
+        if h.note != last_seen || storage.device_fields_differ(book_id, &h).await? {
+            return Err(changed(format!("“{}” was edited there", excerpt(&h.text))));
+        }
+        let Some(ours) = stored
+            .annotation
+            .as_deref()
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+        else {
+            continue;
+        };
+        if h.note.as_deref() == Some(ours) {
+            continue;
+        }
+        changes.push(PushChange {
+            index,
+            highlight_id: stored.id,
+            text: h.text,
+            before: h.note,
+            after: ours.to_string(),
+        });
+    }
+
+    let mut out = report(sidecar, book_id, dry_run, changes);
+    if dry_run || out.changes.is_empty() {
+        return Ok(out);
+    }
+    let bytes = rewrite(&src, &out.changes)?;
+
+    if file_stamp(&out.sidecar)? != stamp {
+        return Err(EngineError::DeviceChanged {
+            path: out.sidecar.clone(),
+            reason: "written to while we were reading it".into(),
+        });
+    }
+    let backup = backup_path(&out.sidecar)?;
+    std::fs::copy(&out.sidecar, &backup)?;
+    let tmp = out.sidecar.with_extension("lua.readingbuddy-tmp");
+    std::fs::write(&tmp, &bytes)?;
+    std::fs::rename(&tmp, &out.sidecar)?;
+
+    for c in &out.changes {
+        storage.record_pushed_note(c.highlight_id, &c.after).await?;
+    }
+    tracing::info!(
+        book_id,
+        notes = out.changes.len(),
+        backup = %backup.display(),
+        "pushed annotations to sidecar"
+    );
+    out.backup = Some(backup);
+    Ok(out)
+}
+
+/// Every highlight in the `annotations` table, with its index, in the file's
+/// order. `None` when the file has no `annotations` table.
+///
+/// Synchronous and separate from [`rewrite`], which evaluates the file a
+/// second time: a Lua value cannot be held across an `.await`, and the checks
+/// in between are queries.
+fn annotation_entries(src: &str) -> Result<Option<Vec<(i64, NewHighlight)>>> {
+    let lua = koreader::sandbox()?;
+    let root = koreader::eval_sidecar(&lua, src)?;
+    let Some(annotations) = koreader::get_table(&root, "annotations") else {
+        return Ok(None);
+    };
+    let mut entries = Vec::new();
+    for pair in annotations.pairs::<i64, Table>() {
+        let (idx, item) =
+            pair.map_err(|e| EngineError::Sidecar(format!("annotation entry: {e}")))?;
+        if let Some(h) = koreader::entry_to_highlight(&item, None) {
+            entries.push((idx, h));
+        }
+    }
+    entries.sort_by_key(|(idx, _)| *idx);
+    Ok(Some(entries))
+}
+
+/// The sidecar with `changes` applied, as KOReader would write it: each
+/// entry's `note` and `datetime_updated`, and the root's
+/// `annotations_externally_modified`.
+fn rewrite(src: &str, changes: &[PushChange]) -> Result<Vec<u8>> {
+    let lua = koreader::sandbox()?;
+    let root = koreader::eval_sidecar(&lua, src)?;
+    let annotations = koreader::get_table(&root, "annotations")
+        .ok_or_else(|| EngineError::Sidecar("annotations vanished on re-read".into()))?;
+    let now = ko_now()?;
+    for c in changes {
+        let item: Table = annotations.get(c.index).map_err(lua_err)?;
+        item.set("note", c.after.as_str()).map_err(lua_err)?;
+        item.set("datetime_updated", now.as_str())
+            .map_err(lua_err)?;
+    }
+    root.set("annotations_externally_modified", true)
+        .map_err(lua_err)?;
+
+    let mut bytes = header(src).into_bytes();
+    bytes.extend_from_slice(b"return ");
+    dump(&Value::Table(root), 0, &mut bytes)?;
+    bytes.push(b'\n');
+    Ok(bytes)
+}
+
+fn report(sidecar: PathBuf, book_id: i64, dry_run: bool, changes: Vec<PushChange>) -> PushReport {
+    PushReport {
+        sidecar,
+        book_id,
+        dry_run,
+        changes,
+        backup: None,
+    }
+}
+
+/// The one sidecar a path names. A push writes a file, so "every sidecar under
+/// this directory" is not an answer it can act on.
+fn one_sidecar(path: &Path) -> Result<PathBuf> {
+    let mut found = koreader::find_sidecars(path)?;
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => Err(EngineError::NotFound(format!(
+            "no KOReader sidecar at {}",
+            path.display()
+        ))),
+        n => Err(EngineError::InvalidInput(format!(
+            "{} holds {n} sidecars; name the metadata.*.lua to push into",
+            path.display()
+        ))),
+    }
+}
+
+fn file_stamp(path: &Path) -> Result<(u64, Option<SystemTime>)> {
+    let meta = std::fs::metadata(path)?;
+    Ok((meta.len(), meta.modified().ok()))
+}
+
+/// `metadata.epub.lua.20260105211408.bak`, beside the file. Not `.old`, which
+/// is KOReader's own and is overwritten on its next flush; and not ending in
+/// `.lua`, so the walker never mistakes it for a sidecar.
+fn backup_path(sidecar: &Path) -> Result<PathBuf> {
+    let stamp = OffsetDateTime::now_utc().format(time::macros::format_description!(
+        "[year][month][day][hour][minute][second]"
+    ))?;
+    let name = sidecar
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut backup = sidecar.with_file_name(format!("{name}.{stamp}.bak"));
+    let mut n = 1;
+    while backup.exists() {
+        n += 1;
+        backup = sidecar.with_file_name(format!("{name}.{stamp}-{n}.bak"));
+    }
+    Ok(backup)
+}
+
+/// KOReader's `os.date("%Y-%m-%d %H:%M:%S")`, read as UTC for the reason
+/// `ko_datetime_to_unix` gives.
+fn ko_now() -> Result<String> {
+    Ok(
+        OffsetDateTime::now_utc().format(time::macros::format_description!(
+            "[year]-[month]-[day] [hour]:[minute]:[second]"
+        ))?,
+    )
+}
+
+fn excerpt(text: &str) -> String {
+    const MAX: usize = 40;
+    match text.char_indices().nth(MAX) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+fn lua_err(e: mlua::Error) -> EngineError {
+    EngineError::Sidecar(format!("lua: {e}"))
+}
+
+/// The comment lines above `return`, kept as they were. KOReader writes the
+/// on-device path there, and nothing reads it, but it is not ours to drop.
+fn header(src: &str) -> String {
+    let mut out = String::new();
+    for line in src.split_inclusive('\n') {
+        if !line.trim_start().starts_with("--") {
+            break;
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+// ---- dump.lua --------------------------------------------------------------
+
+/// KOReader's `dump()`: `{`, one `[key] = value,` per line at four spaces a
+/// level, keys sorted numbers-first, `}` at the parent's indent.
+fn dump(value: &Value, depth: usize, out: &mut Vec<u8>) -> Result<()> {
+    match value {
+        Value::Boolean(b) => out.extend_from_slice(if *b { b"true" } else { b"false" }),
+        Value::Integer(i) => out.extend_from_slice(i.to_string().as_bytes()),
+        Value::Number(n) => out.extend_from_slice(lua_number(*n).as_bytes()),
+        Value::String(s) => quote(&s.as_bytes(), out),
+        Value::Table(t) => {
+            if depth >= MAX_DEPTH {
+                return Err(EngineError::Sidecar(
+                    "sidecar nests too deeply to write back".into(),
+                ));
+            }
+            let mut pairs = Vec::new();
+            for pair in t.pairs::<Value, Value>() {
+                pairs.push(pair.map_err(lua_err)?);
+            }
+            pairs.sort_by(|(a, _), (b, _)| KeyOrder::of(a).cmp(&KeyOrder::of(b)));
+            out.extend_from_slice(b"{\n");
+            for (k, v) in &pairs {
+                indent(depth + 1, out);
+                out.push(b'[');
+                dump(k, depth + 1, out)?;
+                out.extend_from_slice(b"] = ");
+                dump(v, depth + 1, out)?;
+                out.extend_from_slice(b",\n");
+            }
+            indent(depth, out);
+            out.push(b'}');
+        }
+        other => {
+            return Err(EngineError::Sidecar(format!(
+                "cannot write a {} back into a sidecar",
+                other.type_name()
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn indent(depth: usize, out: &mut Vec<u8>) {
+    out.extend(std::iter::repeat_n(b' ', depth * 4));
+}
+
+/// Lua 5.1's `%q`, which is what LuaJIT — and so KOReader — writes: only the
+/// quote, the backslash, newline, carriage return and NUL are escaped, and a
+/// newline as a backslash before a real one.
+fn quote(s: &[u8], out: &mut Vec<u8>) {
+    out.push(b'"');
+    for &b in s {
+        match b {
+            b'"' => out.extend_from_slice(b"\\\""),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b'\n' => out.extend_from_slice(b"\\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            0 => out.extend_from_slice(b"\\000"),
+            _ => out.push(b),
+        }
+    }
+    out.push(b'"');
+}
+
+/// `tostring` on a LuaJIT number: `%.14g`. A whole float prints without a
+/// point, which is how a finished book's `percent_finished` comes to be `1`.
+fn lua_number(n: f64) -> String {
+    if n.is_nan() {
+        return "nan".into();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { "inf" } else { "-inf" }.into();
+    }
+    if n == 0.0 {
+        return if n.is_sign_negative() { "-0" } else { "0" }.into();
+    }
+    let sci = format!("{n:.13e}");
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    if (-4..14).contains(&exp) {
+        let decimals = (13 - exp).max(0) as usize;
+        trim_zeros(format!("{n:.decimals$}"))
+    } else {
+        let sign = if exp < 0 { '-' } else { '+' };
+        format!(
+            "{}e{sign}{:02}",
+            trim_zeros(mantissa.to_string()),
+            exp.abs()
+        )
+    }
+}
+
+fn trim_zeros(s: String) -> String {
+    if !s.contains('.') {
+        return s;
+    }
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// `orderedPairs`' sort: numbers before strings, each in its natural order.
+enum KeyOrder {
+    Number(f64),
+    Str(Vec<u8>),
+    Other,
+}
+
+impl KeyOrder {
+    fn of(v: &Value) -> Self {
+        match v {
+            Value::Integer(i) => KeyOrder::Number(*i as f64),
+            Value::Number(n) => KeyOrder::Number(*n),
+            Value::String(s) => KeyOrder::Str(s.as_bytes().to_vec()),
+            _ => KeyOrder::Other,
+        }
+    }
+
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+        match (self, other) {
+            (KeyOrder::Number(a), KeyOrder::Number(b)) => a.total_cmp(b),
+            (KeyOrder::Str(a), KeyOrder::Str(b)) => a.cmp(b),
+            (KeyOrder::Number(_), _) => Ordering::Less,
+            (_, KeyOrder::Number(_)) => Ordering::Greater,
+            (KeyOrder::Str(_), _) => Ordering::Less,
+            (_, KeyOrder::Str(_)) => Ordering::Greater,
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dumped(src: &str) -> String {
+        let lua = koreader::sandbox().unwrap();
+        let root = koreader::eval_sidecar(&lua, src).unwrap();
+        let mut out = Vec::new();
+        dump(&Value::Table(root), 0, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// A file already in KOReader's layout comes back byte for byte, which is
+    /// what makes a push's diff only the notes it changed.
+    #[test]
+    fn a_koreader_written_table_round_trips_exactly() {
+        let src = r#"{
+    [1] = {
+        ["chapter"] = "Chapter Ten",
+        ["datetime"] = "2026-07-27 09:18:26",
+        ["note"] = "veils over self expression\
+\
+",
+        ["pageno"] = 235,
+        ["text"] = "“Do you think I imagine",
+    },
+    [3] = {
+        ["text"] = "a \"quoted\" back\\slash",
+    },
+    ["doc_pages"] = 312,
+    ["empty"] = {
+    },
+    ["percent_finished"] = 0.99770326136886,
+    ["summary"] = {
+        ["status"] = "complete",
+    },
+    ["whole"] = 1,
+    ["yes"] = true,
+}"#;
+        assert_eq!(dumped(&format!("return {src}")), src);
+    }
+
+    #[test]
+    fn numbers_print_as_luajit_prints_them() {
+        assert_eq!(lua_number(1.0), "1");
+        assert_eq!(lua_number(0.2265625), "0.2265625");
+        assert_eq!(lua_number(0.1 + 0.2), "0.3");
+        assert_eq!(lua_number(1e20), "1e+20");
+        assert_eq!(lua_number(0.00001), "1e-05");
+        assert_eq!(lua_number(-12.5), "-12.5");
+    }
+
+    #[test]
+    fn the_header_comment_is_kept_and_nothing_else() {
+        let src = "-- /mnt/us/Book.sdr/metadata.epub.lua\nreturn {\n    -- inside\n}\n";
+        assert_eq!(header(src), "-- /mnt/us/Book.sdr/metadata.epub.lua\n");
+        assert_eq!(header("return {}"), "");
+    }
+}
@@ -0,0 +1,489 @@
+//! KOReader's reading statistics: `settings/statistics.sqlite3`.
+//!
+//! The statistics plugin logs every page turn — `page_stat_data(id_book, page,
+//! start_time, duration, total_pages)` — against a `book` row whose `md5` is
+//! the same `partialMD5` a sidecar carries as `partial_md5_checksum`. That is
+//! the join: `book.md5` → `device_books.partial_md5` → our book. No title
+//! matching happens here. A device file we have never linked is reported, not
+//! guessed at; linking it is `ko pull`/`ko link`'s decision, and the next
+//! import picks its days up.
+//!
+//! What lands is one `reading_events` row per book per day, `source =
+//! 'koreader'`, `confidence = 'measured'`. The device database is opened
+//! **read-only** and never written: it belongs to the reader, which may have it
+//! open.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use sqlx::Row;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use crate::device::koreader_dir;
+use crate::error::{EngineError, Result};
+use crate::storage::{CONFIDENCE_MEASURED, NewReadingEvent, Storage};
+
+/// Where the plugin keeps its database, relative to the KOReader install.
+pub const STATISTICS_DB: &str = "settings/statistics.sqlite3";
+
+/// The `reading_events.source` this importer writes.
+pub const SOURCE: &str = "koreader";
+
+/// What one import did, or would do.
+#[derive(Debug, Default)]
+pub struct ActivityReport {
+    /// The database that was read, so a caller given a mount can say which.
+    pub db: PathBuf,
+    pub books: Vec<BookActivity>,
+    pub unmatched: Vec<UnmatchedActivity>,
+}
+
+/// One linked device file's days.
+#[derive(Debug)]
+pub struct BookActivity {
+    pub book_id: i64,
+    pub book_title: String,
+    pub partial_md5: String,
+    /// Days the device has any reading logged for.
+    pub days: usize,
+    /// Days not in the log before this import.
+    pub new_days: usize,
+    /// Days already in the log whose minutes or pages the device now reports
+    /// differently — it kept counting after the last import.
+    pub updated_days: usize,
+    /// Totals over every day, as the device reports them now.
+    pub minutes: i64,
+    pub pages: i64,
+}
+
+/// A book the device has statistics for, but whose file is linked to nothing
+/// in the library.
+#[derive(Debug)]
+pub struct UnmatchedActivity {
+    pub title: Option<String>,
+    pub authors: Option<String>,
+    pub partial_md5: String,
+    pub days: usize,
+    pub minutes: i64,
+}
+
+/// One device file, one day, as the plugin logged it.
+struct DeviceDay {
+    day: String,
+    seconds: i64,
+    pages: i64,
+}
+
+struct DeviceBook {
+    title: Option<String>,
+    authors: Option<String>,
+    days: Vec<DeviceDay>,
+}
+
+/// The statistics database a path points at: the file itself, a KOReader
+/// install directory, or a mount holding one.
+pub fn statistics_db(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+    let in_install = path.join(STATISTICS_DB);
+    if in_install.is_file() {
+        return Some(in_install);
+    }
+    koreader_dir(path)
+        .map(|dir| dir.join(STATISTICS_DB))
+        .filter(|db| db.is_file())
+}
+
+/// Seconds to whole minutes, to the nearest.
+///
+/// Rounded rather than truncated: the plugin discards page views shorter than
+/// its minimum anyway, and truncation would turn a steady run of 50-second
+/// days into a reader who never read.
+fn minutes(seconds: i64) -> i64 {
+    (seconds + 30) / 60
+}
+
+/// Fold the device's statistics into `reading_events`. Idempotent: a second
+/// run over the same database changes nothing.
+#[tracing::instrument(skip(storage), fields(path = %path.display()))]
+pub async fn import(storage: &Storage, path: &Path, dry_run: bool) -> Result<ActivityReport> {
+    let Some(db) = statistics_db(path) else {
+        return Err(EngineError::NotFound(format!(
+            "no KOReader statistics database at {} (looked for {STATISTICS_DB})",
+            path.display()
+        )));
+    };
+    let device = read_device_days(&db).await?;
+
+    let mut report = ActivityReport {
+        db,
+        ..Default::default()
+    };
+    for (md5, dev) in device {
+        let seconds: i64 = dev.days.iter().map(|d| d.seconds).sum();
+        let Some(book) = storage.find_book_by_partial_md5(&md5).await? else {
+            report.unmatched.push(UnmatchedActivity {
+                title: dev.title,
+                authors: dev.authors,
+                partial_md5: md5,
+                days: dev.days.len(),
+                minutes: minutes(seconds),
+            });
+            continue;
+        };
+        let Some(book_id) = book.id else { continue };
+
+        let mut stats = BookActivity {
+            book_id,
+            book_title: book.display_title().to_string(),
+            partial_md5: md5.clone(),
+            days: dev.days.len(),
+            new_days: 0,
+            updated_days: 0,
+            minutes: minutes(seconds),
+            pages: dev.days.iter().map(|d| d.pages).sum(),
+        };
+        if !dry_run {
+            storage
+                .drop_reading_events_elsewhere(SOURCE, &md5, book_id)
+                .await?;
+        }
+        for d in &dev.days {
+            let ev = NewReadingEvent {
+                book_id,
+                day: &d.day,
+                minutes: Some(minutes(d.seconds)),
+                pages: Some(d.pages),
+                source: SOURCE,
+                source_key: &md5,
+                confidence: CONFIDENCE_MEASURED,
+            };
+            match storage.find_reading_event(&ev).await? {
+                None => stats.new_days += 1,
+                Some(old) if old.minutes != ev.minutes || old.pages != ev.pages => {
+                    stats.updated_days += 1
+                }
+                Some(_) => continue,
+            }
+            if !dry_run {
+                storage.put_reading_event(&ev).await?;
+            }
+        }
+        if !dry_run {
+            storage.attribute_reading_events(book_id).await?;
+        }
+        report.books.push(stats);
+    }
+    Ok(report)
+}
+
+/// Per-file, per-day totals out of the plugin's database.
+///
+/// Grouped on `md5` rather than `book.id`: the plugin keys `book` on title,
+/// authors *and* md5, so editing a book's metadata on the device starts a
+/// second row for the same file, and both rows' pages are that file's reading.
+///
+/// Pages are `count(DISTINCT page)`, which is what the plugin's own calendar
+/// shows — a page reread on the same day was still one page of progress. The
+/// day is the importing machine's local date, again as the plugin computes it:
+/// `start_time` is true unix time, and "what did I read on Tuesday" is asked in
+/// the reader's own timezone, not UTC's.
+async fn read_device_days(db: &Path) -> Result<BTreeMap<String, DeviceBook>> {
+    let opts = SqliteConnectOptions::new().filename(db).read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await?;
+
+    // `page_stat_data` since the plugin's 2020 schema; before it, the same
+    // columns were a table called `page_stat`, which is now a view over the
+    // new one and must not be read in its place.
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table'
+          AND name IN ('book', 'page_stat_data', 'page_stat')",
+    )
+    .fetch_all(&pool)
+    .await?;
+    let has = |t: &str| tables.iter().any(|n| n == t);
+    let log = if !has("book") {
+        None
+    } else if has("page_stat_data") {
+        Some("page_stat_data")
+    } else if has("page_stat") {
+        Some("page_stat")
+    } else {
+        None
+    };
+    let Some(log) = log else {
+        pool.close().await;
+        return Err(EngineError::InvalidInput(format!(
+            "{} is not a KOReader statistics database (no book/page_stat_data tables)",
+            db.display()
+        )));
+    };
+
+    let sql = format!(
+        "SELECT b.md5 AS md5, MAX(b.title) AS title, MAX(b.authors) AS authors,
+                date(p.start_time, 'unixepoch', 'localtime') AS day,
+                SUM(p.duration) AS seconds, COUNT(DISTINCT p.page) AS pages
+           FROM {log} p JOIN book b ON b.id = p.id_book
+          WHERE b.md5 IS NOT NULL AND b.md5 <> ''
+          GROUP BY b.md5, day
+          ORDER BY b.md5, day"
+    );
+    let rows = sqlx::query(&sql).fetch_all(&pool).await?;
+    pool.close().await;
+
+    let mut out: BTreeMap<String, DeviceBook> = BTreeMap::new();
+    for row in rows {
+        let md5: String = row.try_get("md5")?;
+        let entry = out.entry(md5).or_insert_with(|| DeviceBook {
+            title: None,
+            authors: None,
+            days: Vec::new(),
+        });
+        // The plugin writes "N/A" for a document with no title or author.
+        let known = |s: Option<String>| s.filter(|s| !s.is_empty() && s != "N/A");
+        entry.title = entry.title.take().or(known(row.try_get("title")?));
+        entry.authors = entry.authors.take().or(known(row.try_get("authors")?));
+        entry.days.push(DeviceDay {
+            day: row.try_get("day")?,
+            seconds: row.try_get::<Option<i64>, _>("seconds")?.unwrap_or(0),
+            pages: row.try_get("pages")?,
+        });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::Book;
+    use crate::storage::LinkedBy;
+
+    /// 2026-03-01 12:00:00 UTC. Midday, so the local date is the same one in
+    /// any timezone a test machine is plausibly in.
+    const MARCH_1_NOON: i64 = 1_772_366_400;
+    const DAY: i64 = 86_400;
+
+    /// A statistics database in the plugin's current shape.
+    async fn device_db(dir: &Path) -> PathBuf {
+        let path = dir.join("statistics.sqlite3");
+        let opts = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(opts)
+            .await
+            .unwrap();
+        for stmt in [
+            "CREATE TABLE book (id integer PRIMARY KEY autoincrement, title text, authors text,
+                notes integer, last_open integer, highlights integer, pages integer,
+                series text, language text, md5 text, total_read_time integer,
+                total_read_pages integer)",
+            "CREATE TABLE page_stat_data (id_book integer, page integer NOT NULL DEFAULT 0,
+                start_time integer NOT NULL DEFAULT 0, duration integer NOT NULL DEFAULT 0,
+                total_pages integer NOT NULL DEFAULT 0,
+                UNIQUE (id_book, page, start_time))",
+            "CREATE VIEW page_stat AS SELECT * FROM page_stat_data",
+            "INSERT INTO book (id, title, authors, md5) VALUES
+                (1, 'Pachinko', 'Min Jin Lee', 'aaaa'),
+                (2, 'Kokoro', 'Natsume Sōseki', 'bbbb')",
+        ] {
+            sqlx::query(stmt).execute(&pool).await.unwrap();
+        }
+        // Pachinko: day one, pages 1-3 with page 2 read twice; day three, one page.
+        for (page, start, duration) in [
+            (1, MARCH_1_NOON, 60),
+            (2, MARCH_1_NOON + 60, 90),
+            (2, MARCH_1_NOON + 600, 30),
+            (3, MARCH_1_NOON + 700, 120),
+            (4, MARCH_1_NOON + 2 * DAY, 45),
+        ] {
+            log(&pool, 1, page, start, duration).await;
+        }
+        log(&pool, 2, 10, MARCH_1_NOON, 600).await;
+        pool.close().await;
+        path
+    }
+
+    async fn log(pool: &sqlx::SqlitePool, book: i64, page: i64, start: i64, duration: i64) {
+        sqlx::query(
+            "INSERT INTO page_stat_data (id_book, page, start_time, duration, total_pages)
+             VALUES (?, ?, ?, ?, 300)",
+        )
+        .bind(book)
+        .bind(page)
+        .bind(start)
+        .bind(duration)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn linked(s: &Storage, title: &str, md5: &str) -> i64 {
+        let id = s
+            .upsert_book(&Book {
+                title: Some(title.into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        s.link_device_book(md5, id, LinkedBy::Auto).await.unwrap();
+        id
+    }
+
+    /// The join is the partial MD5 alone; pages are distinct pages per day and
+    /// minutes the rounded sum of the durations.
+    #[tokio::test]
+    async fn device_days_land_on_the_linked_book() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = device_db(tmp.path()).await;
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let pachinko = linked(&s, "Pachinko", "aaaa").await;
+
+        let report = import(&s, &db, false).await.unwrap();
+        assert_eq!(report.books.len(), 1);
+        let b = &report.books[0];
+        assert_eq!((b.book_id, b.days, b.new_days), (pachinko, 2, 2));
+
+        let events = s.list_reading_events(pachinko).await.unwrap();
+        let days: Vec<(&str, Option<i64>, Option<i64>)> = events
+            .iter()
+            .map(|e| (e.day.as_str(), e.minutes, e.pages))
+            .collect();
+        assert_eq!(
+            days,
+            vec![
+                ("2026-03-01", Some(5), Some(3)),
+                ("2026-03-03", Some(1), Some(1)),
+            ]
+        );
+        assert!(events.iter().all(|e| e.source == "koreader"
+            && e.source_key == "aaaa"
+            && e.confidence == CONFIDENCE_MEASURED));
+    }
+
+    /// A file we never linked is reported with what the device knows about it,
+    /// and nothing is written for it.
+    #[tokio::test]
+    async fn an_unlinked_device_file_is_reported_not_guessed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = device_db(tmp.path()).await;
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        // Same title, no link: title matching is not this importer's job.
+        let kokoro = s
+            .upsert_book(&Book {
+                title: Some("Kokoro".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let report = import(&s, &db, false).await.unwrap();
+        assert!(report.books.is_empty());
+        assert_eq!(report.unmatched.len(), 2);
+        let k = report
+            .unmatched
+            .iter()
+            .find(|u| u.partial_md5 == "bbbb")
+            .unwrap();
+        assert_eq!(k.title.as_deref(), Some("Kokoro"));
+        assert_eq!((k.days, k.minutes), (1, 10));
+        assert!(s.list_reading_events(kokoro).await.unwrap().is_empty());
+    }
+
+    /// Plugging the reader in twice changes nothing; a dry run never writes.
+    #[tokio::test]
+    async fn a_second_import_is_a_no_op_and_a_dry_run_writes_nothing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = device_db(tmp.path()).await;
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let pachinko = linked(&s, "Pachinko", "aaaa").await;
+
+        let dry = import(&s, &db, true).await.unwrap();
+        assert_eq!(dry.books[0].new_days, 2);
+        assert!(s.list_reading_events(pachinko).await.unwrap().is_empty());
+
+        import(&s, &db, false).await.unwrap();
+        let again = import(&s, &db, false).await.unwrap();
+        assert_eq!(
+            (again.books[0].new_days, again.books[0].updated_days),
+            (0, 0)
+        );
+        assert_eq!(s.list_reading_events(pachinko).await.unwrap().len(), 2);
+    }
+
+    /// More reading on a day already imported overwrites it.
+    #[tokio::test]
+    async fn a_day_the_device_kept_counting_is_updated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = device_db(tmp.path()).await;
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let pachinko = linked(&s, "Pachinko", "aaaa").await;
+        import(&s, &db, false).await.unwrap();
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(SqliteConnectOptions::new().filename(&db))
+            .await
+            .unwrap();
+        log(&pool, 1, 5, MARCH_1_NOON + 2 * DAY + 100, 600).await;
+        pool.close().await;
+
+        let report = import(&s, &db, false).await.unwrap();
+        assert_eq!(
+            (report.books[0].new_days, report.books[0].updated_days),
+            (0, 1)
+        );
+        let last = s
+            .list_reading_events(pachinko)
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        assert_eq!((last.minutes, last.pages), (Some(11), Some(2)));
+    }
+
+    /// A mount, an install directory and the file itself all find the database;
+    /// a database that is something else is refused rather than read as empty.
+    #[tokio::test]
+    async fn the_database_is_found_from_a_mount_and_a_stranger_is_refused() {
+        let mount = tempfile::tempdir().unwrap();
+        let install = mount.path().join("koreader");
+        std::fs::create_dir_all(install.join("frontend")).unwrap();
+        std::fs::create_dir_all(install.join("plugins")).unwrap();
+        std::fs::create_dir_all(install.join("settings")).unwrap();
+        std::fs::write(install.join("reader.lua"), "-- entry point\n").unwrap();
+        let db = device_db(&install.join("settings")).await;
+
+        assert_eq!(statistics_db(mount.path()), Some(db.clone()));
+        assert_eq!(statistics_db(&install), Some(db.clone()));
+        assert_eq!(statistics_db(&db), Some(db));
+        assert_eq!(statistics_db(tempfile::tempdir().unwrap().path()), None);
+
+        let other = tempfile::tempdir().unwrap();
+        let stranger = other.path().join("other.sqlite3");
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(&stranger)
+                    .create_if_missing(true),
+            )
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE t (x)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        assert!(matches!(
+            import(&s, &stranger, false).await,
+            Err(EngineError::InvalidInput(_))
+        ));
+    }
+}
@@ -3,68 +3,110 @@
 //! The engine performs **no terminal I/O**: every user interaction lives in a
 //! frontend (CLI today, TUI later). Frontends drive it through [`Engine`].
 
+pub mod anki;
+pub mod author;
 pub mod book;
 pub mod calibre;
+pub mod citation;
 pub mod config;
 pub mod crash;
 pub mod device;
 pub mod diagnostic;
 pub mod epub;
 pub mod error;
+pub mod export;
 pub mod files;
 pub mod flashcards;
 pub mod goodreads;
 pub mod images;
+pub mod kindle;
+pub mod kobo;
+pub mod koplugin;
 pub mod koreader;
+pub mod koreader_push;
+pub mod koreader_stats;
 /// The one answer to "is this the book I already have". Internal: a frontend
 /// asks an import path, never the matcher.
 pub(crate) mod matching;
 pub mod notes;
+pub mod obsidian;
+pub mod pairing;
 pub mod partial_md5;
 pub mod providers;
+pub mod rename;
 pub mod search;
+pub mod series;
+pub mod site;
 pub mod storage;
+pub mod study;
+pub mod templates;
+pub mod vault;
 pub mod watch;
+pub mod work;
 
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
 use reqwest::Client;
 
+pub use author::{AuthorCorpus, CorpusBook, CorpusOrder};
 pub use book::{Book, isbn10_to_13, normalize_isbn};
 pub use calibre::{
     Calibre, CalibreBook, CalibreBookReport, CalibreMatch, CalibreReport,
     ImportOptions as CalibreImportOptions, UnmatchedCalibreBook,
 };
+pub use citation::{CitationFormat, QuoteStyle};
 pub use config::EngineConfig;
 pub use crash::CrashContext;
 pub use device::{
-    DeviceBook, DeviceScan, DeviceState, candidate_mounts, is_koreader_mount, koreader_dir,
-    mount_roots, offers_reader,
+    DeviceBook, DeviceScan, DeviceSource, DeviceState, candidate_mounts, is_koreader_mount,
+    koreader_dir, mount_roots, offers_reader,
 };
 pub use diagnostic::{Diagnostic, DiagnosticKind, ErrorClass, Severity};
 pub use error::{EngineError, Result};
+pub use export::ExportFormat;
 pub use files::{
     FileIdentity, FileImportReport, FileMatch, FileOutcome, ImportOptions as FileImportOptions,
 };
+pub use flashcards::CardFormat;
 pub use goodreads::{
     GoodreadsBookReport, GoodreadsMatch, GoodreadsReport, ImportOptions as GoodreadsImportOptions,
     TextOutcome, UnmatchedRow,
 };
+pub use kindle::{
+    ImportOptions as KindleImportOptions, KindleBookReport, KindleMatch, KindleReport,
+    UnmatchedClippings,
+};
+pub use koplugin::{PluginInstall, PluginRemoval, PluginState, PluginStatus};
 pub use koreader::{
     BookImportStats, ImportReport, KoStats, KoStatus, KoSummary, MatchCandidate, MatchMethod,
-    PullReport,
+    PullReport, SidecarPayload,
 };
-pub use notes::{CreatedNote, NewNoteInput, NoteKind};
+pub use koreader_push::{PushChange, PushReport};
+pub use koreader_stats::{ActivityReport, BookActivity, UnmatchedActivity};
+pub use notes::{CreatedNote, NewNoteInput, NoteKind, WikiLink};
+pub use obsidian::{AdoptMatch, AdoptPlan, AdoptProposal, AdoptReport, AdoptedNote};
+pub use pairing::{PairedDevice, PairingCode};
 pub use partial_md5::partial_md5;
 pub use providers::googlebooks::verify_key as verify_google_key;
 pub use providers::{ProviderId, SearchRequest};
 pub use search::{RankedResult, SearchOutcome};
+pub use series::{SeriesEntry, SeriesRun};
+pub use site::SiteReport;
 pub use storage::{
-    BookFile, BookSort, BookTag, FlashcardRow, Highlight, MergeReport, NewHighlight, NoteRecord,
-    NoteSearchHit, OutgoingLink, Rating, RatingScale, Reading, Storage,
+    Backlink, BookFile, BookFilter, BookPage, BookQuery, BookSort, BookStatus, BookTag, CardKind,
+    Device, FlashcardRow, Highlight, HighlightSearchHit, LibraryHit, MergeReport, NewHighlight,
+    NoteRecord, NoteSearchHit, OutgoingLink, PeriodStats, Promoted, Rating, RatingScale, Reading,
+    ReadingEvent, SeriesSummary, Shelf, ShelfRule, StatsGrain, StatsRange, Storage, StudyCard,
+    TagCandidate, Work, format_day, parse_day,
+};
+pub use study::{Grade, Schedule};
+pub use vault::{VaultFileIssue, VaultReindexReport};
+pub use watch::{
+    MOUNT_QUIET, MountEvent, MountStir, MountWatcher, VAULT_QUIET, VaultStir, VaultWatcher,
+    watch_mounts,
 };
-pub use watch::{MOUNT_QUIET, MountEvent, MountStir, MountWatcher, watch_mounts};
+pub use work::WorkHistory;
 
 use providers::googlebooks::GoogleBooksProvider;
 use providers::openlibrary::OpenLibraryProvider;
@@ -136,6 +178,12 @@ impl Engine {
             .user_agent(concat!("readingbuddy/", env!("CARGO_PKG_VERSION")))
             .build()?;
         let storage = Storage::connect(&config.db_url).await?;
+        // A rename the last run did not finish leaves links saying two names;
+        // finish it before anything reads them. A failure is logged, not fatal:
+        // the journal stays, and the next open tries again.
+        if let Err(e) = rename::recover(&storage, &config.vault_dir).await {
+            tracing::warn!(error = %e, "could not finish an interrupted note rename");
+        }
         let key = config.google_api_key.clone();
         let providers = build_providers(&client, key.clone());
         // Once, here — not once per book on a library import, which would be a
@@ -189,6 +237,10 @@ impl Engine {
         &self.config.log_dir
     }
 
+    pub fn templates_dir(&self) -> &Path {
+        &self.config.templates_dir
+    }
+
     /// The Google Books key in force **now**, which after a runtime change is
     /// not the one `EngineConfig` was built with.
     ///
@@ -277,6 +329,12 @@ impl Engine {
         self.storage.list_books(limit, sort).await
     }
 
+    /// The library filtered, one page of it, and how many books the filter
+    /// matches in all — the listing for a library too big to fetch whole.
+    pub async fn query_books(&self, query: &BookQuery) -> Result<BookPage> {
+        self.storage.query_books(query).await
+    }
+
     /// One book by its internal id. [`Engine::resolve_books`] is what a
     /// user-typed selector goes through; this is the id path.
     pub async fn get_book(&self, id: i64) -> Result<Option<Book>> {
@@ -284,9 +342,9 @@ impl Engine {
     }
 
     /// The shelf names another system minted for this book. **Inert
-    /// provenance** — nothing reads these to decide anything, and collections
-    /// are deliberately still deferred (`docs/decisions.md`); they are here so
-    /// that design can be made against real shelf names.
+    /// provenance** — nothing reads these to decide anything. Our own shelves
+    /// are separate; [`Engine::promote_tags`] copies a tag into one when the
+    /// user picks it.
     pub async fn book_tags(&self, book_id: i64) -> Result<Vec<BookTag>> {
         self.storage.book_tags(book_id).await
     }
@@ -419,6 +477,114 @@ impl Engine {
         self.storage.reread(book_id).await
     }
 
+    // ---- reading statistics ------------------------------------------------
+
+    /// Books finished, activity days, highlights, notes, links, pages and
+    /// minutes for every period of `grain` in `range` — empty periods
+    /// included. Pages and minutes are `None` for a period no source measured:
+    /// an unknown is never reported as a zero.
+    pub async fn reading_stats(
+        &self,
+        range: &StatsRange,
+        grain: StatsGrain,
+    ) -> Result<Vec<PeriodStats>> {
+        self.storage.reading_stats(range, grain).await
+    }
+
+    // ---- authors, series and works -----------------------------------------
+
+    /// Every book by one author — name variants pooled — with its readings,
+    /// highlights and notes. See [`author`].
+    pub async fn author_corpus(&self, name: &str, order: CorpusOrder) -> Result<AuthorCorpus> {
+        author::author_corpus(&self.storage, name, order).await
+    }
+
+    /// Every series with a book in it, and how many of those are finished.
+    pub async fn list_series(&self) -> Result<Vec<SeriesSummary>> {
+        self.storage.list_series().await
+    }
+
+    /// One series in order, each book with its reading state. A name that is
+    /// no series is [`EngineError::NotFound`].
+    pub async fn series(&self, name: &str) -> Result<SeriesRun> {
+        series::run(&self.storage, name).await
+    }
+
+    /// Make `book_id` another edition of the work `to` is in — creating that
+    /// work if `to` is in none. The two stay separate books. Returns the work.
+    pub async fn attach_edition(&self, book_id: i64, to: i64) -> Result<Work> {
+        self.storage.attach_edition(book_id, to).await?;
+        self.storage
+            .work_of(to)
+            .await?
+            .ok_or_else(|| EngineError::Other("an attached work vanished".into()))
+    }
+
+    /// Take `book_id` out of its work. False when it was in none.
+    pub async fn detach_edition(&self, book_id: i64) -> Result<bool> {
+        self.storage.detach_edition(book_id).await
+    }
+
+    /// Every edition of the work `book_id` is in, with their readings and
+    /// rating pooled. See [`work`].
+    pub async fn work_history(&self, book_id: i64) -> Result<WorkHistory> {
+        work::history(&self.storage, book_id).await
+    }
+
+    // ---- shelves -----------------------------------------------------------
+
+    /// Every shelf, by name, with how many books are on it now.
+    pub async fn list_shelves(&self) -> Result<Vec<Shelf>> {
+        self.storage.list_shelves().await
+    }
+
+    /// The shelf called `name`, case-insensitively.
+    pub async fn shelf(&self, name: &str) -> Result<Option<Shelf>> {
+        self.storage.shelf(name).await
+    }
+
+    /// Make a shelf: manual when `rule` is `None`, smart otherwise.
+    pub async fn create_shelf(&self, name: &str, rule: Option<&ShelfRule>) -> Result<Shelf> {
+        self.storage.create_shelf(name, rule).await
+    }
+
+    /// Delete a shelf, leaving its books. False when there was none.
+    pub async fn delete_shelf(&self, name: &str) -> Result<bool> {
+        self.storage.delete_shelf(name).await
+    }
+
+    /// The books on a shelf: a manual one in its order, a smart one by title.
+    pub async fn shelf_books(&self, name: &str) -> Result<Vec<Book>> {
+        self.storage.shelf_books(name).await
+    }
+
+    /// Put a book on a manual shelf at `position` (1-based) or the end;
+    /// one already there moves.
+    pub async fn shelve(&self, name: &str, book_id: i64, position: Option<i64>) -> Result<()> {
+        self.storage.shelve(name, book_id, position).await
+    }
+
+    /// Take a book off a manual shelf. False when it was not on it.
+    pub async fn unshelve(&self, name: &str, book_id: i64) -> Result<bool> {
+        self.storage.unshelve(name, book_id).await
+    }
+
+    /// The Goodreads shelves and calibre tags imports recorded, as candidates
+    /// for [`Engine::promote_tags`].
+    pub async fn tag_candidates(&self) -> Result<Vec<TagCandidate>> {
+        self.storage.tag_candidates().await
+    }
+
+    /// Copy each chosen tag into a manual shelf of the same name. Safe to run
+    /// again; see [`Storage::promote_tags`].
+    pub async fn promote_tags(
+        &self,
+        source: Option<&str>,
+        tags: &[String],
+    ) -> Result<Vec<Promoted>> {
+        self.storage.promote_tags(source, tags).await
+    }
+
     // ---- highlights --------------------------------------------------------
 
     /// This book's highlights, device-owned fields and all.
@@ -451,6 +617,22 @@ impl Engine {
         self.storage.set_annotation(highlight_id, annotation).await
     }
 
+    /// Highlights matching an FTS5 query, across every book: the passage, both
+    /// notes on it, and the chapter.
+    pub async fn search_highlights(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<HighlightSearchHit>> {
+        self.storage.search_highlights(query, limit).await
+    }
+
+    /// Notes and highlights together, ranked against each other — the search
+    /// over our own library text, where [`Engine::search`] is the providers'.
+    pub async fn search_library(&self, query: &str, limit: i64) -> Result<Vec<LibraryHit>> {
+        self.storage.search_library(query, limit).await
+    }
+
     // ---- epub import -------------------------------------------------------
 
     /// Import a local .epub: extract its ISBN, enrich via providers, extract
@@ -586,6 +768,53 @@ impl Engine {
         koreader::import_book_from_sidecar(&self.storage, sidecar).await
     }
 
+    /// The same pull, for a sidecar the reader sent over the network rather
+    /// than one on a mounted volume. `partial_md5` is the document's and is
+    /// required; `origin` is the document's path on the reader, if it said;
+    /// `device` is the paired reader that sent it, from
+    /// [`Engine::authenticate_device`].
+    #[tracing::instrument(skip(self, payload))]
+    pub async fn pull_sidecar_payload(
+        &self,
+        payload: &SidecarPayload,
+        partial_md5: &str,
+        origin: Option<&Path>,
+        device: Option<i64>,
+    ) -> Result<PullReport> {
+        koreader::pull_payload(&self.storage, payload, partial_md5, origin, device).await
+    }
+
+    /// Write the reader's own annotations into a sidecar's `note` fields, so
+    /// they are on the page next time. `path` is a `metadata.*.lua` or its
+    /// `.sdr`, already linked to a book here.
+    ///
+    /// Refused with [`EngineError::DeviceChanged`] when the file is not the
+    /// one the last pull saw; the original is backed up beside it before
+    /// anything is written. `dry_run` reports the notes it would change.
+    #[tracing::instrument(skip(self), fields(path = %path.display()))]
+    pub async fn push_annotations(&self, path: &Path, dry_run: bool) -> Result<PushReport> {
+        koreader_push::push(&self.storage, path, dry_run).await
+    }
+
+    /// Fold KOReader's `statistics.sqlite3` into the per-day activity log.
+    /// `path` is the database, a KOReader install, or a mount holding one.
+    /// Joins on the partial MD5 only: a device file not yet linked to a book
+    /// comes back in `unmatched`, and its days arrive with the next import
+    /// after `ko pull` or `ko link`.
+    #[tracing::instrument(skip(self), fields(path = %path.display()))]
+    pub async fn import_koreader_statistics(
+        &self,
+        path: &Path,
+        dry_run: bool,
+    ) -> Result<ActivityReport> {
+        koreader_stats::import(&self.storage, path, dry_run).await
+    }
+
+    /// A book's day-by-day reading activity, every source, oldest day first.
+    pub async fn reading_events(&self, book_id: i64) -> Result<Vec<ReadingEvent>> {
+        self.storage.list_reading_events(book_id).await
+    }
+
     // ---- device ------------------------------------------------------------
 
     /// Walk a mounted reader and report the state of every book on it.
@@ -603,6 +832,51 @@ impl Engine {
         device::sync_device(&self.storage, paths).await
     }
 
+    /// What readingbuddy's KOReader plugin is on this mount, against the
+    /// version this build would install.
+    pub fn plugin_status(&self, mount: &Path) -> Result<PluginStatus> {
+        koplugin::status(mount)
+    }
+
+    /// Install or upgrade the KOReader plugin on a mounted reader. Explicit
+    /// only — nothing calls this on a mount event.
+    pub fn install_plugin(&self, mount: &Path) -> Result<PluginInstall> {
+        koplugin::install(mount)
+    }
+
+    /// Remove exactly what [`Engine::install_plugin`] wrote.
+    pub fn uninstall_plugin(&self, mount: &Path) -> Result<PluginRemoval> {
+        koplugin::uninstall(mount)
+    }
+
+    /// Start pairing a reader for wireless sync: a short code for the user to
+    /// type into it, good for [`pairing::PAIRING_CODE_TTL`].
+    pub async fn mint_pairing_code(&self, name: &str) -> Result<PairingCode> {
+        pairing::mint_code(&self.storage, name).await
+    }
+
+    /// Trade a pairing code for the token the reader keeps. Once per code.
+    pub async fn pair_device(&self, code: &str) -> Result<PairedDevice> {
+        pairing::pair(&self.storage, code).await
+    }
+
+    /// The paired reader holding `token`, stamped as in contact now.
+    /// [`EngineError::NotPaired`] for a token that is wrong or revoked.
+    pub async fn authenticate_device(&self, token: &str) -> Result<Device> {
+        pairing::authenticate(&self.storage, token).await
+    }
+
+    /// Every reader ever paired or waiting to be, newest first, with the books
+    /// each has pushed.
+    pub async fn list_devices(&self) -> Result<Vec<Device>> {
+        self.storage.list_devices().await
+    }
+
+    /// Stop a reader's token working. What it pushed stays, still marked as its.
+    pub async fn revoke_device(&self, id: i64) -> Result<()> {
+        pairing::revoke(&self.storage, id).await
+    }
+
     /// Library books that look like this sidecar's book but not enough to link
     /// unasked.
     pub async fn sidecar_candidates(&self, sidecar: &Path) -> Result<Vec<MatchCandidate>> {
@@ -642,9 +916,61 @@ impl Engine {
             Some(id) => self.storage.get_book(id).await?,
             None => None,
         };
+        let input = self.templated(book.as_ref(), input).await?;
         notes::create_note(&self.storage, &self.config.vault_dir, book.as_ref(), input).await
     }
 
+    /// The names of the reader's note templates, for a frontend to offer.
+    pub fn templates(&self) -> Vec<String> {
+        templates::list(&self.config.templates_dir)
+    }
+
+    /// The body `input` would be created with: its template — the one it names,
+    /// or its kind's default when its body is empty — expanded. `None` when no
+    /// template applies. For a frontend that lets the reader edit it first.
+    pub async fn expand_template(&self, input: &NewNoteInput) -> Result<Option<String>> {
+        let book = match input.book_id {
+            Some(id) => self.storage.get_book(id).await?,
+            None => None,
+        };
+        self.render_template(book.as_ref(), input).await
+    }
+
+    async fn render_template(
+        &self,
+        book: Option<&Book>,
+        input: &NewNoteInput,
+    ) -> Result<Option<String>> {
+        let dir = &self.config.templates_dir;
+        let name = input.template.as_deref();
+        let Some(template) = templates::pick(&self.storage, dir, name, book, input).await? else {
+            return Ok(None);
+        };
+        let title = input
+            .title
+            .clone()
+            .unwrap_or_else(|| notes::derive_title(&input.body));
+        let context = templates::Context::gather(&self.storage, book, input, &title).await?;
+        Ok(Some(templates::render(&template, &context)))
+    }
+
+    /// `input` with its template expanded into the body. The title is settled
+    /// first, so a note without one is still named by its own words rather
+    /// than by the template's.
+    async fn templated(
+        &self,
+        book: Option<&Book>,
+        mut input: NewNoteInput,
+    ) -> Result<NewNoteInput> {
+        if let Some(body) = self.render_template(book, &input).await? {
+            if input.title.is_none() {
+                input.title = Some(notes::derive_title(&input.body));
+            }
+            input.body = body;
+        }
+        Ok(input)
+    }
+
     pub async fn list_notes(&self, book_id: Option<i64>) -> Result<Vec<NoteRecord>> {
         self.storage.list_notes(book_id).await
     }
@@ -691,7 +1017,10 @@ impl Engine {
     /// The facade had no link method at all before this pair: edges were
     /// written by `create_note` / `update_note_body` and read only inside
     /// `open_anchored`, so the graph could be built but never walked.
-    pub async fn backlinks(&self, note_id: i64) -> Result<Vec<NoteRecord>> {
+    ///
+    /// A link into a section carries it, so the reader can be taken there —
+    /// [`notes::anchor_line`] finds the line.
+    pub async fn backlinks(&self, note_id: i64) -> Result<Vec<Backlink>> {
         self.storage.backlinks(note_id).await
     }
 
@@ -717,12 +1046,29 @@ impl Engine {
         self.storage
             .refresh_note_body(note.id, &note.title, body)
             .await?;
-        let links = notes::extract_wikilinks(body);
+        let links = notes::extract_links(body);
         self.storage
-            .set_note_links(note.id, &note.title, &links)
+            .set_note_links(note.id, &note.title, &links, &notes::extract_anchors(body))
             .await
     }
 
+    /// Give a note a new title, and rewrite every `[[wikilink]]` to it —
+    /// `[[Old]]`, `[[Old|alias]]`, `[[Old#heading]]` — in the notes that link
+    /// to it. The file is renamed to match when its name followed the title.
+    /// Returns every file written, the renamed note's first.
+    ///
+    /// A crash part-way leaves a journal in the vault that the next
+    /// [`Engine::open`] finishes; see [`rename`].
+    #[tracing::instrument(skip(self))]
+    pub async fn rename_note(&self, note_id: i64, new_title: &str) -> Result<Vec<PathBuf>> {
+        let note = self
+            .storage
+            .get_note(note_id)
+            .await?
+            .ok_or_else(|| EngineError::NotFound(format!("note id {note_id}")))?;
+        rename::rename_note(&self.storage, &self.config.vault_dir, &note, new_title).await
+    }
+
     /// Delete a note: remove its markdown file from the vault, then its DB row
     /// and FTS entry. A missing file is not an error (the DB row still goes).
     pub async fn delete_note(&self, note: &NoteRecord) -> Result<()> {
@@ -746,12 +1092,50 @@ impl Engine {
             .await?;
         // An outside edit is exactly where a new [[wikilink]] appears, so the
         // graph has to follow the file here too.
-        let links = notes::extract_wikilinks(body);
+        let links = notes::extract_links(body);
         self.storage
-            .set_note_links(note.id, &note.title, &links)
+            .set_note_links(note.id, &note.title, &links, &notes::extract_anchors(body))
             .await
     }
 
+    /// Watch the vault for notes changed by anything else. Hand each batch it
+    /// settles to [`Engine::refresh_vault_paths`].
+    pub fn watch_vault(&self) -> Result<VaultWatcher> {
+        watch::watch_vault(&self.config.vault_dir)
+    }
+
+    /// Bring the index in line with whatever is now at `paths` — edited notes
+    /// re-read, new ones indexed, vanished ones tombstoned, moved ones followed
+    /// — and re-resolve the links left dangling. One [`Diagnostic`] per note
+    /// that changed; our own saves echoing back produce none.
+    pub async fn refresh_vault_paths(&self, paths: &[PathBuf]) -> Result<Vec<Diagnostic>> {
+        vault::refresh_paths(&self.storage, &self.config.vault_dir, paths).await
+    }
+
+    /// Rebuild notes, links and citations from the markdown in the vault, for
+    /// a database that was lost or corrupted. Files already indexed are kept;
+    /// the report lists the ones that could not be placed, and `dry_run`
+    /// writes nothing.
+    #[tracing::instrument(skip(self))]
+    pub async fn reindex_vault(&self, dry_run: bool) -> Result<VaultReindexReport> {
+        vault::reindex_vault(&self.storage, &self.config.vault_dir, dry_run).await
+    }
+
+    /// Read a foreign Obsidian vault and propose a book for each file in it.
+    /// Writes nothing; hand the plan — overruled where it guessed wrong — to
+    /// [`Engine::adopt_vault`].
+    #[tracing::instrument(skip(self))]
+    pub async fn plan_vault_adoption(&self, root: &Path) -> Result<AdoptPlan> {
+        obsidian::plan(&self.storage, root, &self.config.vault_dir).await
+    }
+
+    /// Copy the files of a planned adoption into the vault, under the books the
+    /// plan names, and resolve their wikilinks among everything already there.
+    #[tracing::instrument(skip(self, plan), fields(root = %plan.root.display()))]
+    pub async fn adopt_vault(&self, plan: &AdoptPlan) -> Result<AdoptReport> {
+        obsidian::adopt(&self.storage, &self.config.vault_dir, plan).await
+    }
+
     // ---- reflection + review -----------------------------------------------
 
     /// Open this reading's reflection, creating it on the first call and
@@ -773,8 +1157,10 @@ impl Engine {
     /// carries a rating.
     ///
     /// **Never derived from the reflection.** A review is a rewrite for a
-    /// different audience, not a subset of private thinking — no `public:`
-    /// frontmatter key, no divider, no shared body.
+    /// different audience, not a subset of private thinking — no divider
+    /// marking a public part of a reflection, no shared body. What publishes a
+    /// review is `public: true` in its own frontmatter
+    /// ([`Engine::set_review_public`]); a reflection is never published.
     pub async fn open_review(&self, book_id: i64, reading_id: Option<i64>) -> Result<CreatedNote> {
         self.open_anchored(book_id, reading_id, NoteKind::Review)
             .await
@@ -901,31 +1287,18 @@ impl Engine {
             .iter()
             .position(|r| r.id == reading_id)
             .map(|i| i + 1);
-        let label = match kind {
-            NoteKind::Review => "Review",
-            _ => "Reflection",
-        };
-        // The title is a wikilink target, so a reread's pair must not collide
-        // with the first reading's.
-        let title = match nth {
-            Some(n) if n > 1 => format!("{label}: {} ({n})", book.display_title()),
-            _ => format!("{label}: {}", book.display_title()),
+        let title = notes::anchored_title(kind, &book, nth);
+        let input = NewNoteInput {
+            book_id: Some(book_id),
+            reading_id: Some(reading_id),
+            kind,
+            title: Some(title),
+            body: String::new(),
+            ..Default::default()
         };
+        let input = self.templated(Some(&book), input).await?;
 
-        notes::create_note(
-            &self.storage,
-            &self.config.vault_dir,
-            Some(&book),
-            NewNoteInput {
-                book_id: Some(book_id),
-                reading_id: Some(reading_id),
-                kind,
-                title: Some(title),
-                body: String::new(),
-                ..Default::default()
-            },
-        )
-        .await
+        notes::create_note(&self.storage, &self.config.vault_dir, Some(&book), input).await
     }
 
     /// Rate a review on the active scale.
@@ -1052,6 +1425,95 @@ impl Engine {
         self.storage.citations_for(note_id).await
     }
 
+    /// Reference-manager entries for these books — the whole library when none
+    /// are named. See [`citation`].
+    pub async fn cite_books(&self, book_ids: &[i64], format: CitationFormat) -> Result<String> {
+        let books = if book_ids.is_empty() {
+            self.storage.list_books(i64::MAX, BookSort::Title).await?
+        } else {
+            let mut books = Vec::with_capacity(book_ids.len());
+            for &id in book_ids {
+                books.push(
+                    self.storage
+                        .get_book(id)
+                        .await?
+                        .ok_or_else(|| EngineError::NotFound(format!("book id {id}")))?,
+                );
+            }
+            books
+        };
+        Ok(citation::entries(&books, format))
+    }
+
+    /// A highlight quoted for writing elsewhere: the passage, its page and the
+    /// edition it is from.
+    pub async fn quote_highlight(&self, highlight_id: i64, style: QuoteStyle) -> Result<String> {
+        let h = self
+            .storage
+            .get_highlight(highlight_id)
+            .await?
+            .ok_or_else(|| EngineError::NotFound(format!("highlight id {highlight_id}")))?;
+        let book = self
+            .storage
+            .get_book(h.book_id)
+            .await?
+            .ok_or_else(|| EngineError::NotFound(format!("book id {}", h.book_id)))?;
+        Ok(citation::quotation(&book, &h, style))
+    }
+
+    /// A review as a page to publish: its body, the rating on its own scale,
+    /// the reading's dates, the cover and every cited passage. See
+    /// [`export`] for what each format carries.
+    ///
+    /// Only a review exports — a highlight note or a reflection is written to
+    /// the reader, not for anyone else.
+    pub async fn export_review(&self, note_id: i64, format: ExportFormat) -> Result<String> {
+        let note = self
+            .storage
+            .get_note(note_id)
+            .await?
+            .ok_or_else(|| EngineError::NotFound(format!("note id {note_id}")))?;
+        let body = self.note_body(&note)?;
+        let page = export::ReviewPage::gather(&self.storage, &note, body).await?;
+        Ok(page.render(format))
+    }
+
+    /// Mark a review for the site build, or take it back off. The flag is a
+    /// `public: true` line in the note's frontmatter ([`notes::is_public`]).
+    ///
+    /// A reflection cannot be published at all — that is the promise that
+    /// makes it safe to write one.
+    pub async fn set_review_public(&self, note_id: i64, public: bool) -> Result<()> {
+        let note = self
+            .storage
+            .get_note(note_id)
+            .await?
+            .ok_or_else(|| EngineError::NotFound(format!("note id {note_id}")))?;
+        if note.kind != NoteKind::Review.as_str() {
+            return Err(EngineError::InvalidInput(format!(
+                "note id {note_id} is a {}, and only a review is published",
+                note.kind
+            )));
+        }
+        let file = self.note_path(&note);
+        let content = std::fs::read_to_string(&file)?;
+        std::fs::write(&file, notes::with_public(&content, public))?;
+        Ok(())
+    }
+
+    /// Whether a note's file says it is public. Only a review's answer is ever
+    /// acted on.
+    pub fn note_is_public(&self, note: &NoteRecord) -> Result<bool> {
+        Ok(notes::is_public(&std::fs::read_to_string(
+            self.note_path(note),
+        )?))
+    }
+
+    /// Render the library as a static site into `out_dir`. See [`site`].
+    pub async fn build_site(&self, out_dir: &Path) -> Result<SiteReport> {
+        site::build(&self.storage, &self.config.vault_dir, out_dir).await
+    }
+
     // ---- goodreads ---------------------------------------------------------
 
     /// Import a Goodreads CSV export. `dry_run` reports what would change and
@@ -1178,6 +1640,36 @@ impl Engine {
             .await
     }
 
+    // ---- kindle ------------------------------------------------------------
+
+    /// Import a Kindle's `My Clippings.txt`. `path` is the file or a mounted
+    /// Kindle. `dry_run` reports what would change and writes nothing.
+    ///
+    /// Highlights only: the file says nothing about whether a book was
+    /// finished, so no reading is created or touched.
+    #[tracing::instrument(skip(self), fields(path = %path.display(), dry_run = opts.dry_run))]
+    pub async fn import_kindle(
+        &self,
+        path: &Path,
+        opts: kindle::ImportOptions,
+    ) -> Result<KindleReport> {
+        kindle::import(&self.storage, path, opts).await
+    }
+
+    // ---- kobo --------------------------------------------------------------
+
+    /// Import a stock Kobo's highlights, annotations and reading state from
+    /// its own `KoboReader.sqlite`. `path` is the database or the mount.
+    ///
+    /// Books that match one here are imported; the rest come back in
+    /// `unmatched` with their candidates, as with [`Engine::import_koreader`].
+    /// A single device row is pulled — created if need be — through
+    /// [`Engine::sync_device`].
+    #[tracing::instrument(skip(self), fields(path = %path.display()))]
+    pub async fn import_kobo(&self, path: &Path, dry_run: bool) -> Result<ImportReport> {
+        kobo::import(&self.storage, path, dry_run).await
+    }
+
     // ---- flashcards --------------------------------------------------------
 
     pub async fn list_flashcards(&self, include_exported: bool) -> Result<Vec<FlashcardRow>> {
@@ -1188,12 +1680,70 @@ impl Engine {
         self.storage.list_flashcards_for_book(book_id).await
     }
 
-    /// Build the Anki TSV and mark the exported cards. Returns (tsv, count).
-    pub async fn export_flashcards(&self, include_exported: bool) -> Result<(String, usize)> {
-        let cards = self.storage.list_flashcards(include_exported).await?;
-        let tsv = flashcards::export_tsv(&cards);
-        let ids: Vec<i64> = cards.iter().map(|c| c.id).collect();
+    /// Build the Anki export and mark the exported words. Returns the file's
+    /// bytes and how many notes are in it.
+    ///
+    /// [`CardFormat::Tsv`] is the words alone. [`CardFormat::Apkg`] is a whole
+    /// package: the words, every studied highlight as a cloze, the covers —
+    /// see [`anki`]. A highlight has no exported mark; it is in every package,
+    /// and its GUID makes the second import an update.
+    pub async fn export_flashcards(
+        &self,
+        include_exported: bool,
+        format: CardFormat,
+    ) -> Result<(Vec<u8>, usize)> {
+        let (bytes, ids, count) = match format {
+            CardFormat::Tsv => {
+                let cards = self.storage.list_flashcards(include_exported).await?;
+                let ids: Vec<i64> = cards.iter().map(|c| c.id).collect();
+                let count = ids.len();
+                (flashcards::export_tsv(&cards).into_bytes(), ids, count)
+            }
+            CardFormat::Apkg => {
+                let sources = self.storage.anki_sources(include_exported).await?;
+                if sources.is_empty() {
+                    return Ok((Vec::new(), 0));
+                }
+                let ids = sources
+                    .iter()
+                    .filter(|s| s.kind == CardKind::Word)
+                    .map(|s| s.id)
+                    .collect();
+                let apkg = anki::package(&sources, storage::now_unix()).await?;
+                (apkg, ids, sources.len())
+            }
+        };
         self.storage.mark_flashcards_exported(&ids).await?;
-        Ok((tsv, ids.len()))
+        Ok((bytes, count))
+    }
+
+    // ---- study -------------------------------------------------------------
+
+    /// Cards due now, most overdue first. Flashcards are enrolled on the way,
+    /// so a word imported a minute ago is already here.
+    pub async fn due_cards(&self, limit: usize) -> Result<Vec<StudyCard>> {
+        self.storage.due_cards(storage::now_unix(), limit).await
+    }
+
+    pub async fn study_card(&self, id: i64) -> Result<Option<StudyCard>> {
+        self.storage.study_card(id).await
+    }
+
+    /// Grade a card and reschedule it with [`Schedule::next`]. Returns the
+    /// card as it now stands, so a caller can say when it is back.
+    pub async fn grade_card(&self, id: i64, grade: Grade) -> Result<StudyCard> {
+        self.storage
+            .record_grade(id, grade, storage::now_unix())
+            .await
+    }
+
+    /// Opt a highlight into study, or out again (which forgets its
+    /// schedule). Returns whether anything changed.
+    pub async fn study_highlight(&self, highlight_id: i64, on: bool) -> Result<bool> {
+        self.storage.study_highlight(highlight_id, on).await
+    }
+
+    pub async fn studied_highlights(&self, book_id: i64) -> Result<Vec<i64>> {
+        self.storage.studied_highlights(book_id).await
     }
 }
@@ -197,7 +197,7 @@ fn content_tokens(normalized: &str) -> BTreeSet<String> {
 ///
 /// Not [`crate::search::normalize`]: that drops a leading article, and `A. N.
 /// Writer` would lose its first initial.
-fn author_key(name: &str) -> String {
+pub(crate) fn author_key(name: &str) -> String {
     let mut tokens: Vec<String> = name
         .chars()
         .map(|c| if c.is_alphanumeric() { c } else { ' ' })
@@ -75,6 +75,10 @@ pub struct NewNoteInput {
     pub kind: NoteKind,
     pub title: Option<String>,
     pub body: String,
+    /// A template to expand into the body, by name. `None` still takes the
+    /// default for the note's kind when the body is empty; see
+    /// [`crate::templates`].
+    pub template: Option<String>,
 }
 
 #[derive(Debug)]
@@ -110,30 +114,115 @@ pub fn slugify(s: &str) -> String {
     }
 }
 
-/// Extract [[wikilink]] targets, handling `[[target|alias]]` and
-/// `[[target#heading]]`; deduped, order preserved.
-pub fn extract_wikilinks(body: &str) -> Vec<String> {
-    let re = Regex::new(r"\[\[([^\]\|#]+)(?:#[^\]\|]*)?(?:\|[^\]]*)?\]\]").expect("static regex");
-    let mut seen = Vec::new();
+/// One `[[wikilink]]`: the note it names, and where in that note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    pub target: String,
+    /// The heading after `#` — the last one of a `#Part#Chapter` path, which
+    /// is the one it lands on — or `^id` for a block. `None` links the whole
+    /// note.
+    pub anchor: Option<String>,
+}
+
+/// Extract [[wikilinks]], handling `[[target|alias]]`, `[[target#heading]]`
+/// and `[[target#^block]]`; deduped on target and anchor, order preserved.
+pub fn extract_links(body: &str) -> Vec<WikiLink> {
+    let re = Regex::new(r"\[\[([^\]\|#]+)(#[^\]\|]*)?(?:\|[^\]]*)?\]\]").expect("static regex");
+    let mut seen: Vec<WikiLink> = Vec::new();
     for cap in re.captures_iter(body) {
         let target = cap[1].trim().to_string();
-        if !target.is_empty() && !seen.contains(&target) {
-            seen.push(target);
+        let anchor = cap.get(2).and_then(|m| {
+            m.as_str()
+                .split('#')
+                .map(str::trim)
+                .rfind(|s| !s.is_empty())
+                .map(str::to_string)
+        });
+        let link = WikiLink { target, anchor };
+        if !link.target.is_empty() && !seen.contains(&link) {
+            seen.push(link);
+        }
+    }
+    seen
+}
+
+/// Extract [[wikilink]] targets — the notes a body links to, whichever
+/// section of each; deduped, order preserved.
+pub fn extract_wikilinks(body: &str) -> Vec<String> {
+    let mut seen: Vec<String> = Vec::new();
+    for link in extract_links(body) {
+        if !seen.contains(&link.target) {
+            seen.push(link.target);
+        }
+    }
+    seen
+}
+
+/// Everything in a body a link can point into: each heading's text and each
+/// `^block` id (with its caret), in body order, deduped.
+pub fn extract_anchors(body: &str) -> Vec<String> {
+    let mut seen: Vec<String> = Vec::new();
+    for (_, anchor) in anchors_by_line(body) {
+        if !seen.iter().any(|a| a.eq_ignore_ascii_case(&anchor)) {
+            seen.push(anchor);
         }
     }
     seen
 }
 
-fn frontmatter(
+/// The line of `body` (0-based) an anchor names, compared the way a link
+/// resolves — case-insensitively. What opens a note at the cited section.
+pub fn anchor_line(body: &str, anchor: &str) -> Option<usize> {
+    anchors_by_line(body)
+        .into_iter()
+        .find(|(_, a)| a.eq_ignore_ascii_case(anchor.trim()))
+        .map(|(line, _)| line)
+}
+
+/// ATX headings (`## Origins`, closing hashes dropped) and blocks ending in
+/// ` ^id` or standing alone as `^id`. Fenced code is skipped: a `# comment`
+/// in a shell snippet is not a heading.
+fn anchors_by_line(body: &str) -> Vec<(usize, String)> {
+    let heading = Regex::new(r"^#{1,6}\s+(.*?)(?:\s+#+)?\s*$").expect("static regex");
+    let block = Regex::new(r"(?:^|\s)(\^[A-Za-z0-9-]+)\s*$").expect("static regex");
+    let mut out = Vec::new();
+    let mut fence: Option<&str> = None;
+    for (i, line) in body.lines().enumerate() {
+        let t = line.trim_start();
+        if let Some(f) = fence {
+            if t.starts_with(f) {
+                fence = None;
+            }
+            continue;
+        }
+        if t.starts_with("```") || t.starts_with("~~~") {
+            fence = Some(&t[..3]);
+            continue;
+        }
+        if let Some(c) = heading.captures(line)
+            && !c[1].is_empty()
+        {
+            out.push((i, c[1].to_string()));
+        } else if let Some(c) = block.captures(line) {
+            out.push((i, c[1].to_string()));
+        }
+    }
+    out
+}
+
+/// The header `create_note` writes. Everything in it is what
+/// [`crate::vault::reindex_vault`] reads back when the database is gone, so a
+/// key added here is a key that survives losing `database/`.
+pub(crate) fn frontmatter(
     book: Option<&Book>,
-    reading_id: Option<i64>,
-    highlight_id: Option<i64>,
-    page: Option<i64>,
-    location: Option<&str>,
-    kind: NoteKind,
+    input: &NewNoteInput,
+    title: &str,
     created: &str,
 ) -> String {
     let mut fm = String::from("---\n");
+    // The title is what every `[[wikilink]]` to this note says, and the file
+    // name only keeps its slug.
+    fm.push_str(&format!("title: \"{}\"\n", title.replace('"', "'")));
     if let Some(b) = book {
         let id = b
             .any_isbn()
@@ -148,19 +237,19 @@ fn frontmatter(
     }
     // Beside the book, because it is part of the same anchor: which book, and
     // which time through it.
-    if let Some(r) = reading_id {
+    if let Some(r) = input.reading_id {
         fm.push_str(&format!("reading: {r}\n"));
     }
-    if let Some(h) = highlight_id {
+    if let Some(h) = input.highlight_id {
         fm.push_str(&format!("highlight: {h}\n"));
     }
-    if let Some(p) = page {
+    if let Some(p) = input.page {
         fm.push_str(&format!("page: {p}\n"));
     }
-    if let Some(l) = location {
+    if let Some(l) = &input.location {
         fm.push_str(&format!("location: \"{}\"\n", l.replace('"', "'")));
     }
-    fm.push_str(&format!("kind: {}\n", kind.as_str()));
+    fm.push_str(&format!("kind: {}\n", input.kind.as_str()));
     fm.push_str(&format!("created: {created}\n"));
     fm.push_str("---\n\n");
     fm
@@ -232,7 +321,60 @@ pub fn parse_frontmatter(content: &str) -> (Vec<(String, String)>, &str) {
     (pairs, body)
 }
 
-fn derive_title(body: &str) -> String {
+/// True when a note file says `public: true` in its frontmatter — the one
+/// thing the site build publishes a review on. Absent is private.
+///
+/// The flag lives in the file and nowhere else: it is a decision about the
+/// prose, so it travels with the prose, survives losing `database/`, and can
+/// be flipped from Obsidian as well as from here.
+pub fn is_public(content: &str) -> bool {
+    parse_frontmatter(content)
+        .0
+        .iter()
+        .any(|(k, v)| k == "public" && matches!(v.to_lowercase().as_str(), "true" | "yes"))
+}
+
+/// `content` with its frontmatter saying `public: true`, or not saying it.
+/// Private is the absence of the key rather than `public: false`, so a file
+/// never published reads exactly as it was written.
+pub(crate) fn with_public(content: &str, public: bool) -> String {
+    let (header, body) = frontmatter_and_body(content);
+    if header.is_empty() {
+        return match public {
+            true => format!("---\npublic: true\n---\n\n{body}"),
+            false => content.to_string(),
+        };
+    }
+    let mut lines: Vec<&str> = header["---\n".len()..]
+        .split_inclusive('\n')
+        .filter(|l| !l.starts_with("public:"))
+        .collect();
+    if public {
+        // Just above the closing fence.
+        let close = lines
+            .iter()
+            .rposition(|l| l.trim_end() == "---")
+            .unwrap_or(lines.len());
+        lines.insert(close, "public: true\n");
+    }
+    format!("---\n{}{body}", lines.concat())
+}
+
+/// The title a reflection or a review is given: the book's, and which reading
+/// when it is not the first. The title is a wikilink target, so a reread's pair
+/// must not collide with the first reading's.
+pub fn anchored_title(kind: NoteKind, book: &Book, nth: Option<usize>) -> String {
+    let label = match kind {
+        NoteKind::Review => "Review",
+        _ => "Reflection",
+    };
+    match nth {
+        Some(n) if n > 1 => format!("{label}: {} ({n})", book.display_title()),
+        _ => format!("{label}: {}", book.display_title()),
+    }
+}
+
+pub(crate) fn derive_title(body: &str) -> String {
     let words: Vec<&str> = body.split_whitespace().take(6).collect();
     if words.is_empty() {
         "Untitled".to_string()
@@ -280,20 +422,12 @@ pub async fn create_note(
     let created_str = now.format(&time::format_description::well_known::Rfc3339)?;
     let content = format!(
         "{}{}\n",
-        frontmatter(
-            book,
-            input.reading_id,
-            input.highlight_id,
-            input.page,
-            input.location.as_deref(),
-            input.kind,
-            &created_str,
-        ),
+        frontmatter(book, &input, &title, &created_str),
         input.body.trim_end()
     );
     std::fs::write(&file, &content)?;
 
-    let links = extract_wikilinks(&input.body);
+    let links = extract_links(&input.body);
     let id = storage
         .insert_note(
             crate::storage::NewNoteMeta {
@@ -308,6 +442,7 @@ pub async fn create_note(
             },
             &input.body,
             &links,
+            &extract_anchors(&input.body),
         )
         .await?;
 
@@ -315,7 +450,7 @@ pub async fn create_note(
         id,
         title,
         file,
-        links,
+        links: extract_wikilinks(&input.body),
     })
 }
 
@@ -345,6 +480,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn links_keep_their_section_and_notes_list_theirs() {
+        let body = "[[Han#Origins]], [[Han#^grief]], [[Han#Part 1#Osaka|there]], [[Han]] \
+                    and [[Han#Origins]] again.";
+        let links = extract_links(body);
+        let anchors: Vec<Option<&str>> = links.iter().map(|l| l.anchor.as_deref()).collect();
+        assert_eq!(
+            anchors,
+            [Some("Origins"), Some("^grief"), Some("Osaka"), None]
+        );
+        assert!(links.iter().all(|l| l.target == "Han"));
+
+        let han = "# Han\n\n## Origins ##\nA grief with no bottom. ^grief\n\n\
+                   ```sh\n# not a heading\n```\n### Osaka\n^standalone\n";
+        assert_eq!(
+            extract_anchors(han),
+            ["Han", "Origins", "^grief", "Osaka", "^standalone"]
+        );
+        assert_eq!(anchor_line(han, "origins"), Some(2));
+        assert_eq!(anchor_line(han, "^grief"), Some(3));
+        assert_eq!(anchor_line(han, "not a heading"), None);
+    }
+
+    #[test]
+    fn publishing_is_one_frontmatter_line_and_unpublishing_removes_it() {
+        let private = "---\ntitle: \"Review: Pachinko\"\nkind: review\n---\n\nBody\n";
+        assert!(!is_public(private));
+        let public = with_public(private, true);
+        assert_eq!(
+            public,
+            "---\ntitle: \"Review: Pachinko\"\nkind: review\npublic: true\n---\n\nBody\n"
+        );
+        assert!(is_public(&public));
+        assert_eq!(with_public(&with_public(&public, true), false), private);
+        assert!(is_public(&with_public("Body\n", true)));
+        assert!(!is_public("---\npublic: no\n---\n\nBody\n"));
+    }
+
     #[test]
     fn frontmatter_roundtrip() {
         let book = Book {
@@ -353,13 +526,18 @@ mod tests {
             isbn_13: Some("9781455563937".into()),
             ..Default::default()
         };
+        let input = NewNoteInput {
+            reading_id: Some(11),
+            highlight_id: Some(3),
+            page: Some(42),
+            location: Some("Chapter 2".into()),
+            kind: NoteKind::Session,
+            ..Default::default()
+        };
         let fm = frontmatter(
             Some(&book),
-            Some(11),
-            Some(3),
-            Some(42),
-            Some("Chapter 2"),
-            NoteKind::Session,
+            &input,
+            "Sunja's \"choice\"",
             "2026-07-23T10:00:00Z",
         );
         let content = format!("{fm}The body text with [[Link]].\n");
@@ -370,6 +548,7 @@ mod tests {
                 .find(|(key, _)| key == k)
                 .map(|(_, v)| v.as_str())
         };
+        assert_eq!(get("title"), Some("Sunja's 'choice'"));
         assert_eq!(get("book"), Some("9781455563937"));
         assert_eq!(get("book-title"), Some("Pachinko"));
         assert_eq!(get("reading"), Some("11"));
@@ -0,0 +1,514 @@
+//! Adopting an Obsidian vault someone already keeps.
+//!
+//! Most readers arrive with years of book notes in Obsidian: in folders of
+//! their own devising, with whatever frontmatter a plugin or a habit left. This
+//! takes such a vault in two steps, because which book a file is about is a
+//! guess and a guess is shown before it is acted on.
+//!
+//! * [`plan`] reads every markdown file and proposes a book for it. The
+//!   evidence is what the file says — an `isbn`, a `title` and `author` in its
+//!   frontmatter — then its own name, then the folders it sits in, nearest
+//!   first. Matching is the shared [`crate::matching`] scorer through
+//!   [`koreader::scores_for`], with the bands every importer uses: a sure match
+//!   is proposed, a close one is offered as a candidate, and nothing is filed
+//!   under a book the scorer would not link on its own.
+//! * [`adopt`] takes the plan back — with whatever the reader changed in it —
+//!   and copies each file into `vault_dir/<book-slug>/`, under its own name.
+//!
+//! Three things are kept on purpose:
+//!
+//! * **The file name is the title.** It is what Obsidian calls a note and so
+//!   what every `[[wikilink]]` to it says; the links are resolved through
+//!   [`Storage::set_note_links`] once every file is in, so a link to a note
+//!   adopted later in the same run still finds it.
+//! * **Their frontmatter stays.** Ours is added at the top; a key of theirs we
+//!   also write is kept as `original-<key>` rather than dropped.
+//! * **The foreign vault is never written to.** It is copied from, and a file
+//!   already copied (same place, same words) is not copied twice.
+//!
+//! Attachments — an embedded `![[image.png]]` — are not copied. The note keeps
+//! the embed, which resolves again once the image is put beside it.
+
+use std::path::{Path, PathBuf};
+
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+
+use crate::book::{Book, normalize_isbn};
+use crate::diagnostic::Diagnostic;
+use crate::error::{EngineError, Result};
+use crate::koreader::{self, MatchCandidate};
+use crate::matching::Query;
+use crate::notes::{self, NewNoteInput, NoteKind, WikiLink};
+use crate::storage::{NewNoteMeta, Storage};
+use crate::vault;
+
+/// The frontmatter keys `create_note` writes. A foreign key with one of these
+/// names is kept under `original-<key>`, so neither reading is lost.
+const OUR_KEYS: &[&str] = &[
+    "title",
+    "book",
+    "book-title",
+    "reading",
+    "highlight",
+    "page",
+    "location",
+    "kind",
+    "created",
+];
+
+// ---- the plan --------------------------------------------------------------
+
+/// How a file's book was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdoptMatch {
+    /// Its frontmatter carried an ISBN we know.
+    Isbn,
+    /// Its frontmatter `title` (and `author`), or its own file name, was a
+    /// sure match.
+    Title,
+    /// A folder it sits in was named for the book.
+    Folder,
+    /// The reader chose it, over what was proposed.
+    Chosen,
+}
+
+impl std::fmt::Display for AdoptMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdoptMatch::Isbn => write!(f, "isbn"),
+            AdoptMatch::Title => write!(f, "title"),
+            AdoptMatch::Folder => write!(f, "folder"),
+            AdoptMatch::Chosen => write!(f, "chosen"),
+        }
+    }
+}
+
+/// One file, and the book proposed for it.
+#[derive(Debug, Clone)]
+pub struct AdoptProposal {
+    /// Relative to the vault being adopted.
+    pub path: PathBuf,
+    /// The note's title once adopted: its file name.
+    pub title: String,
+    /// The book it will be filed under. `None` adopts it as a free note.
+    /// A caller overruling the proposal sets this — to one of `candidates`,
+    /// or to any book — and [`AdoptProposal::choose`] is how.
+    pub book_id: Option<i64>,
+    pub book_title: Option<String>,
+    pub matched_by: Option<AdoptMatch>,
+    /// Books in the ambiguous band, best first, when nothing was sure.
+    pub candidates: Vec<MatchCandidate>,
+}
+
+impl AdoptProposal {
+    /// File it under `book` instead, or under none.
+    pub fn choose(&mut self, book: Option<&Book>) {
+        self.book_id = book.and_then(|b| b.id);
+        self.book_title = book.map(|b| b.display_title().to_string());
+        self.matched_by = book.map(|_| AdoptMatch::Chosen);
+    }
+}
+
+/// Every markdown file in a foreign vault, with a book proposed for each.
+#[derive(Debug, Clone)]
+pub struct AdoptPlan {
+    pub root: PathBuf,
+    pub files: Vec<AdoptProposal>,
+}
+
+/// What a foreign file's frontmatter says about its book, read loosely: a
+/// plain value, a `[a, b]` list, or a YAML list on the lines below the key.
+#[derive(Debug, Default, PartialEq)]
+struct ForeignMeta {
+    title: Option<String>,
+    authors: Vec<String>,
+    isbn: Option<String>,
+    created: Option<i64>,
+}
+
+fn foreign_meta(header: &str) -> ForeignMeta {
+    let mut fields: Vec<(String, Vec<String>)> = Vec::new();
+    for line in header.lines().filter(|l| *l != "---") {
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            // A list item belongs to the key above it.
+            if line.starts_with([' ', '\t', '-'])
+                && let Some((_, values)) = fields.last_mut()
+            {
+                values.push(clean(item));
+            }
+            continue;
+        }
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let values = match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            // `[[Min Jin Lee]]` is a wikilink, not a one-item list.
+            Some(inner) if !value.starts_with("[[") => inner.split(',').map(clean).collect(),
+            _ if value.is_empty() => Vec::new(),
+            _ => vec![clean(value)],
+        };
+        fields.push((key.trim().to_lowercase(), values));
+    }
+    let first = |keys: &[&str]| {
+        keys.iter().find_map(|k| {
+            fields
+                .iter()
+                .find(|(key, v)| key == k && !v.is_empty())
+                .map(|(_, v)| v.clone())
+        })
+    };
+    ForeignMeta {
+        title: first(&["title", "book-title", "book"]).and_then(|v| v.into_iter().next()),
+        authors: first(&["author", "authors", "creator"]).unwrap_or_default(),
+        isbn: first(&["isbn", "isbn13", "isbn-13", "isbn10", "isbn-10"])
+            .into_iter()
+            .flatten()
+            .find_map(|i| normalize_isbn(&i)),
+        created: first(&["created", "date", "created_at"])
+            .into_iter()
+            .flatten()
+            .find_map(|d| parse_date(&d)),
+    }
+}
+
+/// One frontmatter value, without its quotes or its `[[ ]]`.
+fn clean(value: &str) -> String {
+    let v = value.trim().trim_matches(['"', '\'']).trim();
+    let v = v
+        .strip_prefix("[[")
+        .and_then(|v| v.strip_suffix("]]"))
+        .unwrap_or(v);
+    // `[[Min Jin Lee|Lee]]` names the note, not the alias.
+    v.split('|').next().unwrap_or(v).trim().to_string()
+}
+
+/// An RFC 3339 instant, or a bare `YYYY-MM-DD` taken as that day's midnight,
+/// which is what most Obsidian templates write.
+fn parse_date(s: &str) -> Option<i64> {
+    if let Ok(t) = OffsetDateTime::parse(s, &Rfc3339) {
+        return Some(t.unix_timestamp());
+    }
+    let day = time::Date::parse(s.get(..10)?, format_description!("[year]-[month]-[day]")).ok()?;
+    Some(day.midnight().assume_utc().unix_timestamp())
+}
+
+/// Read a foreign vault and propose a book for every markdown file in it.
+/// Writes nothing.
+pub async fn plan(storage: &Storage, root: &Path, vault_dir: &Path) -> Result<AdoptPlan> {
+    if !root.is_dir() {
+        return Err(EngineError::NotFound(format!(
+            "no vault at {}",
+            root.display()
+        )));
+    }
+    let ours = vault_dir.canonicalize().ok();
+    let theirs = root.canonicalize()?;
+    if ours
+        .as_ref()
+        .is_some_and(|o| theirs.starts_with(o) || o.starts_with(&theirs))
+    {
+        return Err(EngineError::InvalidInput(format!(
+            "{} is readingbuddy's own vault, or holds it",
+            root.display()
+        )));
+    }
+
+    let mut files = Vec::new();
+    vault::collect_markdown(root, &mut files, 0);
+    files.sort();
+    let mut out = Vec::new();
+    for path in files {
+        let Ok(rel) = path.strip_prefix(root).map(Path::to_path_buf) else {
+            continue;
+        };
+        let title = rel
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        // An unreadable file is still proposed, unfiled: `adopt` reads it again
+        // and reports what it finds then.
+        let meta = std::fs::read_to_string(&path)
+            .map(|c| foreign_meta(notes::frontmatter_and_body(&c).0))
+            .unwrap_or_default();
+        let mut proposal = AdoptProposal {
+            path: rel.clone(),
+            title: title.clone(),
+            book_id: None,
+            book_title: None,
+            matched_by: None,
+            candidates: Vec::new(),
+        };
+        propose(storage, &mut proposal, &meta).await?;
+        out.push(proposal);
+    }
+    Ok(AdoptPlan {
+        root: root.to_path_buf(),
+        files: out,
+    })
+}
+
+/// The book for one file: its ISBN, then each title it offers in turn — the
+/// frontmatter's, its own name, its folders' — until one is a sure match. The
+/// candidates kept are the first title's that had any.
+async fn propose(storage: &Storage, p: &mut AdoptProposal, meta: &ForeignMeta) -> Result<()> {
+    if let Some(isbn) = &meta.isbn
+        && let Some(book) = storage.find_book_by_isbn(isbn).await?
+    {
+        p.choose(Some(&book));
+        p.matched_by = Some(AdoptMatch::Isbn);
+        return Ok(());
+    }
+    let mut titles: Vec<(String, AdoptMatch)> = Vec::new();
+    if let Some(t) = &meta.title {
+        titles.push((t.clone(), AdoptMatch::Title));
+    }
+    titles.push((p.title.clone(), AdoptMatch::Title));
+    let folders = p.path.parent().into_iter().flat_map(Path::ancestors);
+    for dir in folders {
+        if let Some(name) = dir.file_name() {
+            titles.push((name.to_string_lossy().into_owned(), AdoptMatch::Folder));
+        }
+    }
+    for (title, how) in titles {
+        let mut scored =
+            koreader::scores_for(storage, &Query::new(Some(&title), &meta.authors)).await?;
+        if scored.first().is_some_and(|s| s.can_auto) {
+            let book = scored.remove(0).book;
+            p.choose(Some(&book));
+            p.matched_by = Some(how);
+            p.candidates.clear();
+            return Ok(());
+        }
+        if p.candidates.is_empty() {
+            p.candidates = koreader::band(scored);
+        }
+    }
+    Ok(())
+}
+
+// ---- adopting --------------------------------------------------------------
+
+/// One file copied in.
+#[derive(Debug, Clone)]
+pub struct AdoptedNote {
+    pub note_id: i64,
+    /// Relative to the adopted vault.
+    pub from: PathBuf,
+    /// Relative to `vault_dir`.
+    pub to: PathBuf,
+    pub book_id: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct AdoptReport {
+    pub adopted: Vec<AdoptedNote>,
+    /// Copied by an earlier run, word for word, and left alone.
+    pub already: usize,
+    /// `[[wikilinks]]` in the adopted files that now resolve to a note.
+    pub links_resolved: usize,
+    /// And those that name no note yet — kept, as every forward reference is.
+    pub links_dangling: usize,
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// A file copied in, waiting for every other file before its links are.
+struct Written {
+    id: i64,
+    title: String,
+    links: Vec<WikiLink>,
+    anchors: Vec<String>,
+    created: i64,
+}
+
+/// Copy every file in `plan` into the vault under the book it now names, and
+/// index it.
+pub async fn adopt(storage: &Storage, vault_dir: &Path, plan: &AdoptPlan) -> Result<AdoptReport> {
+    let mut report = AdoptReport::default();
+    let mut written: Vec<Written> = Vec::new();
+    for p in &plan.files {
+        let content = match std::fs::read_to_string(plan.root.join(&p.path)) {
+            Ok(c) => c,
+            Err(e) => {
+                report
+                    .warnings
+                    .push(Diagnostic::note_unreadable(p.path.clone(), &e.into()));
+                continue;
+            }
+        };
+        let book = match p.book_id {
+            Some(id) => Some(
+                storage
+                    .get_book(id)
+                    .await?
+                    .ok_or_else(|| EngineError::NotFound(format!("book id {id}")))?,
+            ),
+            None => None,
+        };
+        let (header, body) = notes::frontmatter_and_body(&content);
+        let body = body.trim_end();
+        let meta = foreign_meta(header);
+        let created = match meta.created {
+            Some(at) => OffsetDateTime::from_unix_timestamp(at)
+                .map_err(|e| EngineError::InvalidInput(e.to_string()))?,
+            None => std::fs::metadata(plan.root.join(&p.path))
+                .and_then(|m| m.modified())
+                .map(OffsetDateTime::from)
+                .unwrap_or_else(|_| OffsetDateTime::now_utc()),
+        };
+
+        let dir_name = book
+            .as_ref()
+            .map(|b| notes::slugify(b.display_title()))
+            .unwrap_or_else(|| "unsorted".to_string());
+        let dir = vault_dir.join(&dir_name);
+        std::fs::create_dir_all(&dir)?;
+        let input = NewNoteInput {
+            book_id: book.as_ref().and_then(|b| b.id),
+            kind: NoteKind::Note,
+            ..Default::default()
+        };
+        let ours = notes::frontmatter(book.as_ref(), &input, &p.title, &created.format(&Rfc3339)?);
+        let file = format!("{}{body}\n", merged_header(&ours, header));
+
+        // Obsidian's own way with a taken name: `Han 1.md`, `Han 2.md`.
+        let mut name = format!("{}.md", p.title);
+        let mut n = 0;
+        let already = loop {
+            let at = dir.join(&name);
+            match std::fs::read_to_string(&at) {
+                Ok(there) if notes::frontmatter_and_body(&there).1.trim_end() == body => {
+                    break true;
+                }
+                Ok(_) => {
+                    n += 1;
+                    name = format!("{} {n}.md", p.title);
+                }
+                Err(_) => break false,
+            }
+        };
+        if already {
+            report.already += 1;
+            continue;
+        }
+        std::fs::write(dir.join(&name), &file)?;
+        let rel = format!("{dir_name}/{name}");
+        let note_id = storage
+            .insert_note(
+                NewNoteMeta {
+                    book_id: input.book_id,
+                    reading_id: None,
+                    highlight_id: None,
+                    page: None,
+                    location: None,
+                    file_path: &rel,
+                    title: &p.title,
+                    kind: NoteKind::Note.as_str(),
+                },
+                body,
+                &[],
+                &[],
+            )
+            .await?;
+        written.push(Written {
+            id: note_id,
+            title: p.title.clone(),
+            links: notes::extract_links(body),
+            anchors: notes::extract_anchors(body),
+            created: created.unix_timestamp(),
+        });
+        report.adopted.push(AdoptedNote {
+            note_id,
+            from: p.path.clone(),
+            to: PathBuf::from(rel),
+            book_id: input.book_id,
+        });
+    }
+
+    // Every file is in, so a link to one adopted after it resolves too.
+    // Dated after the links are in, so the edges take the note's date too.
+    for w in &written {
+        storage
+            .set_note_links(w.id, &w.title, &w.links, &w.anchors)
+            .await?;
+        storage.restore_note_created(w.id, w.created).await?;
+        for (_, to) in storage.note_links(w.id).await? {
+            match to {
+                Some(_) => report.links_resolved += 1,
+                None => report.links_dangling += 1,
+            }
+        }
+    }
+    tracing::info!(
+        root = %plan.root.display(),
+        adopted = report.adopted.len(),
+        already = report.already,
+        "vault adopted"
+    );
+    Ok(report)
+}
+
+/// Our header, then theirs: `ours` is a whole `---` block from
+/// [`notes::frontmatter`], `theirs` the raw header of the foreign file.
+fn merged_header(ours: &str, theirs: &str) -> String {
+    let mut out = ours.trim_end().trim_end_matches("---").to_string();
+    let mut lines = theirs.lines().filter(|l| l.trim() != "---");
+    for line in lines.by_ref() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let renamed = (!line.starts_with([' ', '\t', '-']))
+            .then(|| line.split_once(':'))
+            .flatten()
+            .filter(|(k, _)| OUR_KEYS.contains(&k.trim().to_lowercase().as_str()));
+        match renamed {
+            Some((key, value)) => out.push_str(&format!("original-{}:{value}\n", key.trim())),
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    out.push_str("---\n\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn foreign_frontmatter_is_read_in_the_shapes_plugins_write() {
+        let header = "---\n\
+                      title: \"Pachinko\"\n\
+                      author:\n  - \"[[Min Jin Lee]]\"\n\
+                      tags: [book, fiction]\n\
+                      isbn: 978-1-4555-6393-7\n\
+                      date: 2024-03-02\n\
+                      ---\n\n";
+        let meta = foreign_meta(header);
+        assert_eq!(meta.title.as_deref(), Some("Pachinko"));
+        assert_eq!(meta.authors, ["Min Jin Lee"]);
+        assert_eq!(meta.isbn.as_deref(), Some("9781455563937"));
+        assert_eq!(meta.created, Some(1_709_337_600));
+
+        let inline = foreign_meta("---\nauthors: [Lee, Min Jin, Other Person]\n---\n");
+        assert_eq!(inline.authors, ["Lee", "Min Jin", "Other Person"]);
+    }
+
+    #[test]
+    fn their_keys_stay_and_a_clash_is_kept_under_another_name() {
+        let ours = "---\ntitle: \"Han\"\nkind: note\ncreated: 2024-03-02T00:00:00Z\n---\n\n";
+        let theirs = "---\ntitle: Pachinko\ntags:\n  - book\n---\n\n";
+        assert_eq!(
+            merged_header(ours, theirs),
+            "---\ntitle: \"Han\"\nkind: note\ncreated: 2024-03-02T00:00:00Z\n\
+             original-title: Pachinko\ntags:\n  - book\n---\n\n"
+        );
+    }
+}
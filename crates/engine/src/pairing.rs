@@ -0,0 +1,187 @@
+//! Pairing a reader for wireless sync.
+//!
+//! Two secrets, with two jobs. A **pairing code** is short enough to type on an
+//! e-ink keyboard and lives for [`PAIRING_CODE_TTL`]; all it can do is be
+//! traded, once, for a **token**. The token is long, never expires, and is
+//! what the reader presents on every push until the user revokes it. Only its
+//! SHA-256 is stored — see migration `0015`.
+//!
+//! Both come from the operating system's generator. Nothing here is seeded,
+//! and nothing here is testable by predicting a value; the tests trade what
+//! they are handed.
+
+use sha2::{Digest, Sha256};
+
+use crate::error::{EngineError, Result};
+use crate::storage::{Device, Storage, now_unix};
+
+/// How long a code can wait to be typed in. Long enough to walk to the reader
+/// and find the menu, short enough that one read over a shoulder is stale by
+/// the time it could be used.
+pub const PAIRING_CODE_TTL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Uppercase letters and digits, less the ones an e-ink font makes ambiguous:
+/// no `0`/`O`, no `1`/`I`/`L`.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+/// Eight of them: about 39 bits, for a secret that lives ten minutes and is
+/// spent on first use.
+const CODE_LEN: usize = 8;
+
+/// A code, waiting to be typed into the reader called `name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairingCode {
+    pub device_id: i64,
+    pub name: String,
+    /// As shown to the user, `XXXX-XXXX`. [`pair`] ignores case, spaces and
+    /// the dash.
+    pub code: String,
+    /// Unix seconds.
+    pub expires_at: i64,
+}
+
+/// A reader that has just traded its code. `token` is shown this once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairedDevice {
+    pub device: Device,
+    pub token: String,
+}
+
+/// Start pairing a reader called `name`.
+pub async fn mint_code(storage: &Storage, name: &str) -> Result<PairingCode> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(EngineError::InvalidInput(
+            "a device needs a name to be told apart by".into(),
+        ));
+    }
+    let code = random_code()?;
+    let expires_at = now_unix() + PAIRING_CODE_TTL.as_secs() as i64;
+    let device_id = storage
+        .insert_pending_device(name, &code, expires_at)
+        .await?;
+    tracing::info!(device_id, "pairing code minted");
+    Ok(PairingCode {
+        device_id,
+        name: name.to_string(),
+        code: format!("{}-{}", &code[..CODE_LEN / 2], &code[CODE_LEN / 2..]),
+        expires_at,
+    })
+}
+
+/// Trade a code for a token.
+pub async fn pair(storage: &Storage, code: &str) -> Result<PairedDevice> {
+    let code: String = code
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let token = random_token()?;
+    let Some(id) = storage
+        .redeem_pairing_code(&code, &token_hash(&token))
+        .await?
+    else {
+        return Err(EngineError::NotPaired(
+            "that pairing code is wrong, used or expired".into(),
+        ));
+    };
+    let device = storage
+        .get_device(id)
+        .await?
+        .ok_or_else(|| EngineError::Other("a device vanished while pairing".into()))?;
+    tracing::info!(device_id = id, "device paired");
+    Ok(PairedDevice { device, token })
+}
+
+/// The paired device a token belongs to, stamped as in contact.
+pub async fn authenticate(storage: &Storage, token: &str) -> Result<Device> {
+    storage
+        .device_by_token_hash(&token_hash(token))
+        .await?
+        .ok_or_else(|| EngineError::NotPaired("that token is not a paired device's".into()))
+}
+
+/// Revoke a device. Its token stops working at once; what it pushed stays, and
+/// still says where it came from.
+pub async fn revoke(storage: &Storage, id: i64) -> Result<()> {
+    if !storage.revoke_device(id).await? {
+        return Err(EngineError::NotFound(format!("device {id}")));
+    }
+    tracing::info!(device_id = id, "device revoked");
+    Ok(())
+}
+
+/// Hex SHA-256. A token carries 256 bits from the OS generator, so nothing is
+/// gained by a slow hash: there is no dictionary to make expensive.
+fn token_hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn random_code() -> Result<String> {
+    let mut code = String::with_capacity(CODE_LEN);
+    let mut buf = [0u8; 32];
+    while code.len() < CODE_LEN {
+        fill(&mut buf)?;
+        // Rejection rather than `% len`, which would favour the first few
+        // letters: 248 is the largest multiple of 31 that fits in a byte.
+        let usable = 256 - 256 % CODE_ALPHABET.len();
+        for &b in buf.iter().filter(|&&b| (b as usize) < usable) {
+            if code.len() == CODE_LEN {
+                break;
+            }
+            code.push(CODE_ALPHABET[b as usize % CODE_ALPHABET.len()] as char);
+        }
+    }
+    Ok(code)
+}
+
+fn random_token() -> Result<String> {
+    let mut buf = [0u8; 32];
+    fill(&mut buf)?;
+    Ok(buf.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+fn fill(buf: &mut [u8]) -> Result<()> {
+    getrandom::fill(buf)
+        .map_err(|e| EngineError::Other(format!("the system random source failed: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_use_only_the_unambiguous_alphabet() {
+        for _ in 0..50 {
+            let code = random_code().unwrap();
+            assert_eq!(code.len(), CODE_LEN);
+            assert!(code.bytes().all(|b| CODE_ALPHABET.contains(&b)), "{code}");
+        }
+        assert_ne!(random_token().unwrap(), random_token().unwrap());
+    }
+
+    #[tokio::test]
+    async fn a_code_is_spent_by_the_first_trade() {
+        let storage = Storage::connect("sqlite::memory:").await.unwrap();
+        let minted = mint_code(&storage, "Kobo Libra").await.unwrap();
+        let typed = minted.code.to_lowercase().replace('-', " ");
+        let paired = pair(&storage, &typed).await.unwrap();
+        assert_eq!(paired.device.id, minted.device_id);
+        assert!(paired.device.is_paired());
+        assert!(matches!(
+            pair(&storage, &minted.code).await,
+            Err(EngineError::NotPaired(_))
+        ));
+        assert_eq!(
+            authenticate(&storage, &paired.token).await.unwrap().id,
+            minted.device_id
+        );
+        assert!(matches!(
+            authenticate(&storage, &minted.code).await,
+            Err(EngineError::NotPaired(_))
+        ));
+    }
+}
@@ -10,6 +10,7 @@ use super::{
 };
 use crate::book::{Book, normalize_isbn};
 use crate::error::{EngineError, Result};
+use crate::series::parse_label;
 
 pub struct OpenLibraryProvider {
     client: Client,
@@ -77,6 +78,8 @@ impl Works {
             isbn_10,
             isbn_13,
             openlibrary_key: self.key.clone(),
+            // A search hit *is* a work: its key is `/works/…`.
+            openlibrary_work: self.key.clone(),
             publisher: self.publisher.as_ref().and_then(|v| v.first().cloned()),
             page_count: self.number_of_pages_median,
             first_sentence: self
@@ -157,6 +160,10 @@ struct EditionJson {
     key: Option<String>,
     languages: Option<Vec<Key>>,
     covers: Option<Vec<i64>>,
+    /// Labels like `Mistborn ; 1`. An edition in several lists its main one
+    /// first.
+    series: Option<Vec<String>>,
+    works: Option<Vec<Key>>,
 }
 
 async fn author_of_key(key: &Key, client: &Client) -> Result<Option<String>> {
@@ -199,6 +206,13 @@ async fn edition_to_book(edition: EditionJson, client: &Client) -> Result<Book>
         .and_then(|k| k.key.as_deref())
         .and_then(|k| k.rsplit('/').next().map(normalize_language));
 
+    let (series, series_index) = edition
+        .series
+        .as_ref()
+        .and_then(|v| v.first())
+        .and_then(|l| parse_label(l))
+        .map_or((None, None), |(s, i)| (Some(s), i));
+
     Ok(Book {
         title: edition.title,
         authors,
@@ -214,6 +228,12 @@ async fn edition_to_book(edition: EditionJson, client: &Client) -> Result<Book>
             .map(|id| format!("https://covers.openlibrary.org/b/id/{id}-M.jpg")),
         isbn_10: first_valid(edition.isbn_10),
         isbn_13: first_valid(edition.isbn_13),
+        series,
+        series_index,
+        openlibrary_work: edition
+            .works
+            .and_then(|w| w.into_iter().next())
+            .and_then(|k| k.key),
         ..Default::default()
     })
 }
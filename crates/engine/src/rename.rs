@@ -0,0 +1,373 @@
+//! Renaming a note, and every `[[wikilink]]` that says its old name.
+//!
+//! A title is what links resolve by, so changing one is not a change to one
+//! note: every note linking to it has to be rewritten, or its edge goes
+//! dangling the moment the title moves. That is several files and a database
+//! transaction, and a crash between any two of them would leave a vault whose
+//! links say one name and whose index says another.
+//!
+//! So a rename is worked out in full before anything is touched — each file's
+//! new content, the note's new path — and written down as a journal in the
+//! vault. Applying it only ever writes what the journal says, each file
+//! through a temporary and a rename, so doing it twice is the same as doing
+//! it once. A journal still there when the engine opens is a rename a crash
+//! interrupted, and [`recover`] rolls it forward.
+//!
+//! Links are rewritten in the three shapes Obsidian writes them —
+//! `[[Old]]`, `[[Old|alias]]`, `[[Old#heading]]` — keeping the alias and the
+//! heading. The comparison is SQLite's `NOCASE`, which is what resolved them.
+
+use std::path::{Path, PathBuf};
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngineError, Result};
+use crate::notes::{self, slugify};
+use crate::storage::{Backlink, NoteRecord, Storage};
+
+/// In the vault, beside the notes it rewrites — and hidden, so neither the
+/// vault walk nor Obsidian takes it for one.
+const JOURNAL: &str = ".readingbuddy-rename.json";
+
+/// What a title must not contain to stay a link target: each of these ends or
+/// splits a `[[wikilink]]`.
+const NOT_IN_TITLE: &[char] = &['[', ']', '|', '#', '^', '\n'];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Journal {
+    note_id: i64,
+    title: String,
+    /// Vault-relative, as `notes.file_path` keeps them.
+    from: String,
+    to: String,
+    files: Vec<JournalFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalFile {
+    note_id: i64,
+    title: String,
+    path: String,
+    content: String,
+}
+
+/// Rename `note` to `title`: its file, its frontmatter, its row, and every
+/// link to it. Returns each file written, the renamed note's first.
+pub async fn rename_note(
+    storage: &Storage,
+    vault_dir: &Path,
+    note: &NoteRecord,
+    title: &str,
+) -> Result<Vec<PathBuf>> {
+    let Some(journal) = plan(storage, vault_dir, note, title).await? else {
+        return Ok(Vec::new());
+    };
+    write_file(&vault_dir.join(JOURNAL), &serde_json::to_string(&journal)?)?;
+    let touched = apply(storage, vault_dir, &journal).await?;
+    tracing::info!(
+        note = note.id,
+        from = %note.title,
+        to = %journal.title,
+        files = touched.len(),
+        "note renamed"
+    );
+    Ok(touched)
+}
+
+/// Everything the rename will write, read before any of it is. `None` when
+/// the title is already this one.
+async fn plan(
+    storage: &Storage,
+    vault_dir: &Path,
+    note: &NoteRecord,
+    title: &str,
+) -> Result<Option<Journal>> {
+    let title = title.trim();
+    if title.is_empty() || title.contains(NOT_IN_TITLE) {
+        return Err(EngineError::InvalidInput(format!(
+            "'{title}' cannot be a note title: a wikilink could not name it"
+        )));
+    }
+    if title == note.title {
+        return Ok(None);
+    }
+    if let Some(other) = storage.note_titled(title).await?
+        && other != note.id
+    {
+        return Err(EngineError::InvalidInput(format!(
+            "note {other} is already called '{title}'"
+        )));
+    }
+    if vault_dir.join(JOURNAL).exists() {
+        // Not ours to finish here: `Engine::open` rolls it forward, and a second
+        // rename planned over a half-done one would plan from stale files.
+        return Err(EngineError::Other(
+            "an earlier rename was interrupted; reopen to finish it".into(),
+        ));
+    }
+
+    let to = new_path(vault_dir, &note.file_path, &note.title, title);
+    let content = std::fs::read_to_string(vault_dir.join(&note.file_path))?;
+    let mut files = vec![JournalFile {
+        note_id: note.id,
+        title: title.to_string(),
+        path: to.clone(),
+        content: retitle(&relink(&content, &note.title, title), title),
+    }];
+    for Backlink { from, .. } in storage.backlinks(note.id).await? {
+        // Its own links were rewritten above, with its title; and `[[Han]]`
+        // beside `[[han|…]]` is two edges from one file.
+        if files.iter().any(|f| f.note_id == from.id) {
+            continue;
+        }
+        let content = std::fs::read_to_string(vault_dir.join(&from.file_path))?;
+        let rewritten = relink(&content, &note.title, title);
+        if rewritten != content {
+            files.push(JournalFile {
+                note_id: from.id,
+                title: from.title,
+                path: from.file_path,
+                content: rewritten,
+            });
+        }
+    }
+    Ok(Some(Journal {
+        note_id: note.id,
+        title: title.to_string(),
+        from: note.file_path.clone(),
+        to,
+        files,
+    }))
+}
+
+/// Finish a rename a crash interrupted, if there is one. `true` if there was.
+pub async fn recover(storage: &Storage, vault_dir: &Path) -> Result<bool> {
+    let path = vault_dir.join(JOURNAL);
+    let raw = match std::fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    let journal: Journal = serde_json::from_str(&raw)?;
+    apply(storage, vault_dir, &journal).await?;
+    tracing::warn!(
+        note = journal.note_id,
+        to = %journal.title,
+        "finished a note rename that was interrupted"
+    );
+    Ok(true)
+}
+
+/// Do what the journal says, then drop it. Every step writes an end state, not
+/// a change, so running this over a half-applied journal is what finishes it.
+async fn apply(storage: &Storage, vault_dir: &Path, journal: &Journal) -> Result<Vec<PathBuf>> {
+    let mut touched = Vec::new();
+    let mut bodies = Vec::new();
+    for f in &journal.files {
+        let path = vault_dir.join(&f.path);
+        write_file(&path, &f.content)?;
+        touched.push(path);
+        bodies.push((
+            f.note_id,
+            f.title.as_str(),
+            notes::frontmatter_and_body(&f.content).1.trim_end(),
+        ));
+    }
+    if journal.from != journal.to {
+        match std::fs::remove_file(vault_dir.join(&journal.from)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    storage
+        .apply_note_rename(journal.note_id, &journal.title, &journal.to, &bodies)
+        .await?;
+    std::fs::remove_file(vault_dir.join(JOURNAL))?;
+    Ok(touched)
+}
+
+/// Through a temporary and a rename, so a crash leaves the old file or the
+/// new one and never half of either.
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".readingbuddy-tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Where the renamed file goes: the same folder, named the way it was named.
+/// `create_note`'s `<stamp>-<slug>.md` keeps its stamp and takes the new slug;
+/// a file named for its title — an adopted Obsidian note — takes the new title;
+/// anything else keeps its name, since only the frontmatter says the title.
+fn new_path(vault_dir: &Path, from: &str, old: &str, new: &str) -> String {
+    let (dir, name) = from.rsplit_once('/').unwrap_or(("", from));
+    let stem = name.strip_suffix(".md").unwrap_or(name);
+    let stamped = stem
+        .split_once('-')
+        .filter(|(stamp, rest)| {
+            stamp.len() == 14
+                && stamp.bytes().all(|b| b.is_ascii_digit())
+                && rest.strip_prefix(&slugify(old)).is_some_and(|n| {
+                    n.is_empty()
+                        || n.strip_prefix('-')
+                            .is_some_and(|d| d.parse::<u32>().is_ok())
+                })
+        })
+        .map(|(stamp, _)| stamp);
+    let (base, sep) = match stamped {
+        Some(stamp) => (format!("{stamp}-{}", slugify(new)), "-"),
+        None if stem == old && !new.contains(['/', '\\', ':']) => (new.to_string(), " "),
+        None => return from.to_string(),
+    };
+    let join = |name: String| {
+        if dir.is_empty() {
+            name
+        } else {
+            format!("{dir}/{name}")
+        }
+    };
+    let mut candidate = join(format!("{base}.md"));
+    let mut n = 1;
+    while candidate != from && vault_dir.join(&candidate).exists() {
+        n += 1;
+        candidate = join(format!("{base}{sep}{n}.md"));
+    }
+    candidate
+}
+
+/// `content` with every link to `old` pointing at `new`, alias and heading
+/// kept.
+fn relink(content: &str, old: &str, new: &str) -> String {
+    let re = Regex::new(r"\[\[([^\]\|#]+)((?:#[^\]\|]*)?(?:\|[^\]]*)?)\]\]").expect("static regex");
+    re.replace_all(content, |c: &Captures| {
+        if c[1].trim().eq_ignore_ascii_case(old) {
+            format!("[[{new}{}]]", &c[2])
+        } else {
+            c[0].to_string()
+        }
+    })
+    .into_owned()
+}
+
+/// `content` with its frontmatter `title:` saying `title` — added, for a file
+/// from before the frontmatter carried one.
+fn retitle(content: &str, title: &str) -> String {
+    let line = format!("title: \"{}\"", title.replace('"', "'"));
+    let (header, body) = notes::frontmatter_and_body(content);
+    if header.is_empty() {
+        return format!("---\n{line}\n---\n\n{body}");
+    }
+    // First, where `create_note` writes it.
+    let rest: String = header["---\n".len()..]
+        .split_inclusive('\n')
+        .filter(|l| !l.starts_with("title:"))
+        .collect();
+    format!("---\n{line}\n{rest}{body}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::NewNoteInput;
+
+    #[test]
+    fn every_shape_of_link_is_rewritten_and_nothing_else() {
+        let body = "[[Han]], [[han|grief]], [[Han#Origins]], [[Han#Origins|it]], \
+                    [[Hansel]], [[Sunja]] and Han.";
+        assert_eq!(
+            relink(body, "Han", "Han (恨)"),
+            "[[Han (恨)]], [[Han (恨)|grief]], [[Han (恨)#Origins]], [[Han (恨)#Origins|it]], \
+             [[Hansel]], [[Sunja]] and Han."
+        );
+    }
+
+    #[test]
+    fn a_file_keeps_the_way_it_was_named() {
+        let vault = tempfile::tempdir().unwrap();
+        let v = vault.path();
+        assert_eq!(
+            new_path(v, "pachinko/20260101120000-han.md", "Han", "Grief"),
+            "pachinko/20260101120000-grief.md"
+        );
+        assert_eq!(
+            new_path(v, "pachinko/20260101120000-han-2.md", "Han", "Grief"),
+            "pachinko/20260101120000-grief.md"
+        );
+        assert_eq!(
+            new_path(v, "pachinko/Han.md", "Han", "Grief"),
+            "pachinko/Grief.md"
+        );
+        assert_eq!(
+            new_path(v, "inbox/idea.md", "Han", "Grief"),
+            "inbox/idea.md"
+        );
+
+        std::fs::create_dir_all(v.join("pachinko")).unwrap();
+        std::fs::write(v.join("pachinko/Grief.md"), "").unwrap();
+        assert_eq!(
+            new_path(v, "pachinko/Han.md", "Han", "Grief"),
+            "pachinko/Grief 2.md"
+        );
+    }
+
+    #[test]
+    fn the_title_line_is_replaced_or_added() {
+        assert_eq!(
+            retitle("---\ntitle: \"Han\"\nkind: note\n---\n\nBody\n", "Grief"),
+            "---\ntitle: \"Grief\"\nkind: note\n---\n\nBody\n"
+        );
+        assert_eq!(
+            retitle("---\nkind: note\ntitle: Han\n---\n\nBody\n", "Grief"),
+            "---\ntitle: \"Grief\"\nkind: note\n---\n\nBody\n"
+        );
+        assert_eq!(
+            retitle("Body\n", "Grief"),
+            "---\ntitle: \"Grief\"\n---\n\nBody\n"
+        );
+    }
+
+    /// A crash after the journal and the first file were written: the note is
+    /// renamed on disk, its backlinks are not, and the index knows neither.
+    /// Opening again finishes it, and finishing is done once.
+    #[tokio::test]
+    async fn an_interrupted_rename_is_finished_from_its_journal() {
+        let vault = tempfile::tempdir().unwrap();
+        let v = vault.path();
+        let storage = Storage::connect("sqlite::memory:").await.unwrap();
+        let note = |title: &str, body: &str| NewNoteInput {
+            title: Some(title.into()),
+            body: body.into(),
+            ..Default::default()
+        };
+        let han = notes::create_note(&storage, v, None, note("Han", "Grief."))
+            .await
+            .unwrap();
+        let sunja = notes::create_note(&storage, v, None, note("Sunja", "See [[Han|it]]."))
+            .await
+            .unwrap();
+        let record = storage.get_note(han.id).await.unwrap().unwrap();
+
+        let journal = plan(&storage, v, &record, "Grief").await.unwrap().unwrap();
+        write_file(&v.join(JOURNAL), &serde_json::to_string(&journal).unwrap()).unwrap();
+        write_file(&v.join(&journal.files[0].path), &journal.files[0].content).unwrap();
+
+        assert!(recover(&storage, v).await.unwrap());
+        assert!(!v.join(JOURNAL).exists());
+        assert!(!han.file.exists(), "the old name is gone");
+        let renamed = storage.get_note(han.id).await.unwrap().unwrap();
+        assert_eq!(renamed.title, "Grief");
+        assert!(v.join(&renamed.file_path).exists());
+        assert_eq!(
+            std::fs::read_to_string(&sunja.file).unwrap().lines().last(),
+            Some("See [[Grief|it]].")
+        );
+        assert_eq!(
+            storage.note_links(sunja.id).await.unwrap(),
+            [("Grief".to_string(), Some(han.id))]
+        );
+        assert!(!recover(&storage, v).await.unwrap());
+    }
+}
@@ -198,6 +198,7 @@ fn merge_into(a: &mut Book, b: &Book, b_provider: ProviderId) {
     fill(&mut a.first_sentence, &b.first_sentence);
     fill(&mut a.cover_url, &b.cover_url);
     fill(&mut a.openlibrary_key, &b.openlibrary_key);
+    fill(&mut a.openlibrary_work, &b.openlibrary_work);
     fill(&mut a.googlebooks_id, &b.googlebooks_id);
 }
 
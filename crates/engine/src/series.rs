@@ -0,0 +1,216 @@
+//! Series: the ways sources write one, and a run as the series view shows it.
+//!
+//! Three sources, three spellings of the same fact. Calibre hands over a name
+//! and a number in separate fields and needs nothing from here. Goodreads
+//! folds it into the title — `The Final Empire (Mistborn, #1)` — and an
+//! OpenLibrary edition lists labels like `Mistborn ; 1` or `Mistborn, #1`.
+//! [`split_title`] and [`parse_label`] turn both into the name and number
+//! [`Book::series`] and [`Book::series_index`] hold.
+//!
+//! The parse is conservative on purpose: a trailing number only counts as a
+//! position when something marks it as one (`#`, `;`, `, book 3`), so a series
+//! called "Area 51" is not the 51st of "Area".
+
+use crate::book::Book;
+use crate::error::{EngineError, Result};
+use crate::storage::{BookStatus, Storage};
+
+/// One series, in order, with how far through it the reader is.
+#[derive(Debug, Clone)]
+pub struct SeriesRun {
+    pub name: String,
+    pub books: Vec<SeriesEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SeriesEntry {
+    pub book: Book,
+    pub status: BookStatus,
+}
+
+impl SeriesRun {
+    /// The first book not finished, after the last one that was — where the
+    /// run picks up again. `None` when every book is read.
+    pub fn next_up(&self) -> Option<&SeriesEntry> {
+        let after = self
+            .books
+            .iter()
+            .rposition(|e| e.status == BookStatus::Finished)
+            .map_or(0, |i| i + 1);
+        self.books[after..]
+            .iter()
+            .find(|e| e.status != BookStatus::Finished)
+    }
+}
+
+pub(crate) async fn run(storage: &Storage, name: &str) -> Result<SeriesRun> {
+    let Some((name, books)) = storage.series_books(name).await? else {
+        return Err(EngineError::NotFound(format!("no series called '{name}'")));
+    };
+    Ok(SeriesRun {
+        name,
+        books: books
+            .into_iter()
+            .map(|(book, status)| SeriesEntry { book, status })
+            .collect(),
+    })
+}
+
+/// `#3`, or `#2.5` for the novella between — the way every source numbers one.
+pub fn format_index(index: f64) -> String {
+    if index.fract() == 0.0 {
+        format!("#{index:.0}")
+    } else {
+        format!("#{index}")
+    }
+}
+
+/// A Goodreads title, split into the title and the series it carries:
+/// `The Final Empire (Mistborn, #1)` is "The Final Empire" in Mistborn at 1.
+///
+/// Only a trailing bracket with a `#` in it is a series — `Saga (Volume One)`
+/// is a title. A book in two (`(Discworld, #1; Rincewind #1)`) takes the first,
+/// which is the one Goodreads lists the book under.
+pub fn split_title(title: &str) -> (String, Option<(String, Option<f64>)>) {
+    let trimmed = title.trim();
+    let whole = || (trimmed.to_string(), None);
+    let Some(open) = trimmed.strip_suffix(')').and_then(|t| t.rfind('(')) else {
+        return whole();
+    };
+    let inner = &trimmed[open + 1..trimmed.len() - 1];
+    let title = trimmed[..open].trim();
+    if !inner.contains('#') || title.is_empty() {
+        return whole();
+    }
+    match inner.split(';').next().and_then(parse_label) {
+        Some(series) => (title.to_string(), Some(series)),
+        None => whole(),
+    }
+}
+
+/// A series label as a catalogue writes it: `Mistborn ; 1`, `Mistborn, #1`,
+/// `Dune Chronicles #1`, `The Wheel of Time, book 1`, or just `Mistborn`.
+pub fn parse_label(label: &str) -> Option<(String, Option<f64>)> {
+    let label = label.trim();
+    let split = label
+        .rfind('#')
+        .map(|i| (i, true))
+        .or_else(|| label.rfind([';', ',']).map(|i| (i, false)));
+    let (name, index) = match split {
+        // After a `#` the number is the point; anywhere else it has to be there
+        // for the split to be one.
+        Some((i, hash)) => match position(&label[i + 1..]) {
+            Some(n) => (&label[..i], Some(n)),
+            None if hash => (&label[..i], None),
+            None => (label, None),
+        },
+        None => (label, None),
+    };
+    let name = name.trim_end_matches([',', ';', ' ', '\t']).trim();
+    (!name.is_empty()).then(|| (name.to_string(), index))
+}
+
+/// The number at the start of a label's tail, past a `book`/`vol.`-style word.
+/// `1-3` (an omnibus) is where it starts.
+fn position(tail: &str) -> Option<f64> {
+    let mut tail = tail.trim();
+    for word in ["volume", "vol.", "vol", "book", "bk.", "no.", "part"] {
+        if tail
+            .get(..word.len())
+            .is_some_and(|w| w.eq_ignore_ascii_case(word))
+        {
+            tail = tail[word.len()..].trim_start();
+            break;
+        }
+    }
+    let end = tail
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(tail.len());
+    tail[..end].trim_end_matches('.').parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_goodreads_title_gives_up_its_series() {
+        assert_eq!(
+            split_title("The Final Empire (Mistborn, #1)"),
+            (
+                "The Final Empire".to_string(),
+                Some(("Mistborn".to_string(), Some(1.0)))
+            )
+        );
+        assert_eq!(
+            split_title("Dune (Dune Chronicles #1)").1,
+            Some(("Dune Chronicles".to_string(), Some(1.0)))
+        );
+        assert_eq!(
+            split_title("The Colour of Magic (Discworld, #1; Rincewind #1)").1,
+            Some(("Discworld".to_string(), Some(1.0)))
+        );
+        assert_eq!(
+            split_title("Edgedancer (The Stormlight Archive, #2.5)").1,
+            Some(("The Stormlight Archive".to_string(), Some(2.5)))
+        );
+        assert_eq!(
+            split_title("The Mistborn Trilogy (Mistborn, #1-3)").1,
+            Some(("Mistborn".to_string(), Some(1.0)))
+        );
+        // No `#`, no series: the bracket is part of the title.
+        assert_eq!(split_title("Saga (Volume One)").1, None);
+        assert_eq!(split_title("Pachinko").0, "Pachinko");
+    }
+
+    #[test]
+    fn a_label_only_numbers_what_it_marks() {
+        assert_eq!(
+            parse_label("Mistborn ; 1"),
+            Some(("Mistborn".to_string(), Some(1.0)))
+        );
+        assert_eq!(
+            parse_label("The Wheel of Time, book 4"),
+            Some(("The Wheel of Time".to_string(), Some(4.0)))
+        );
+        assert_eq!(
+            parse_label("Mistborn"),
+            Some(("Mistborn".to_string(), None))
+        );
+        assert_eq!(parse_label("Area 51"), Some(("Area 51".to_string(), None)));
+        assert_eq!(
+            parse_label("Penguin Classics, Deluxe Edition"),
+            Some(("Penguin Classics, Deluxe Edition".to_string(), None))
+        );
+        assert_eq!(parse_label("  "), None);
+    }
+
+    #[test]
+    fn next_up_is_past_the_last_finished_book() {
+        let run = |statuses: &[BookStatus]| SeriesRun {
+            name: "Saga".into(),
+            books: statuses
+                .iter()
+                .enumerate()
+                .map(|(i, s)| SeriesEntry {
+                    book: Book {
+                        series_index: Some(i as f64 + 1.0),
+                        ..Default::default()
+                    },
+                    status: *s,
+                })
+                .collect(),
+        };
+        use BookStatus::*;
+        let r = run(&[Finished, Unstarted, Finished, Unstarted, Unstarted]);
+        assert_eq!(
+            r.next_up().and_then(|e| e.book.series_index),
+            Some(4.0),
+            "the fourth, not the skipped second"
+        );
+        let r = run(&[Finished, Finished]);
+        assert!(r.next_up().is_none());
+        assert_eq!(format_index(3.0), "#3");
+        assert_eq!(format_index(2.5), "#2.5");
+    }
+}
@@ -0,0 +1,413 @@
+//! The library as a static site: a shelf, a page per book, and the authors and
+//! tags between them — plain files, to be served by anything or opened from
+//! disk.
+//!
+//! ```text
+//! index.html            the shelf, by year finished, newest first
+//! books/<slug>.html     cover, rating, readings, and each published review
+//! authors/<slug>.html   that author's books
+//! tags/<slug>.html      the books filed under that shelf
+//! covers/<slug>.<ext>   copied from wherever the cover already is
+//! style.css
+//! ```
+//!
+//! **Nothing private leaves.** A review appears only when its file says
+//! `public: true` ([`notes::is_public`]); a reflection never does, whatever its
+//! frontmatter says, and neither does any other note. Highlights appear only
+//! as the passages a published review cites. The rating is not prose, and
+//! heads its book's page whether or not the review it was given in is public.
+//!
+//! URLs are [`notes::slugify`] of the title, author or tag, so a page keeps its
+//! address from one build to the next. Two books whose titles slug alike are
+//! told apart by id — the older keeps the bare slug — which is stable for as
+//! long as both are in the library. Authors and tags have no id, so two that
+//! slug alike are told apart by name order: the first keeps the bare slug,
+//! the next gets `-2`.
+//!
+//! A build writes over its own files and removes nothing, so `out_dir` can sit
+//! inside a repository with other things in it.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use time::OffsetDateTime;
+
+use crate::book::Book;
+use crate::error::Result;
+use crate::export::{ReviewPage, STYLE, escape, image_mime, reading_dates};
+use crate::notes::{self, NoteKind};
+use crate::storage::{BookSort, Rating, Reading, Storage};
+use crate::work;
+
+/// What a build wrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteReport {
+    pub books: usize,
+    /// Published reviews; every other review and note was left out.
+    pub reviews: usize,
+    pub authors: usize,
+    pub tags: usize,
+    /// The site's front page.
+    pub index: PathBuf,
+}
+
+/// One book, as the site shows it.
+struct Entry {
+    book: Book,
+    slug: String,
+    readings: Vec<Reading>,
+    /// The work's rating, shown whether or not the review it sits on is
+    /// published: a number is not the reader's prose.
+    rating: Option<Rating>,
+    reviews: Vec<ReviewPage>,
+    tags: Vec<String>,
+    /// The cover's file name under `covers/`.
+    cover: Option<String>,
+}
+
+/// The shelf an index lists under: a year, or not finished at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Shelf {
+    Year(std::cmp::Reverse<i32>),
+    Unfinished,
+}
+
+pub(crate) async fn build(storage: &Storage, vault_dir: &Path, out: &Path) -> Result<SiteReport> {
+    let mut books = storage.list_books(i64::MAX, BookSort::Title).await?;
+    let mut entries = Vec::with_capacity(books.len());
+    let slugs = book_slugs(&books);
+    for book in books.drain(..) {
+        let id = book.id.expect("stored book has id");
+        let readings = storage.list_readings(id).await?;
+        let rating = work::history(storage, id).await?.rating;
+        let mut reviews = Vec::new();
+        for note in storage.list_notes(Some(id)).await? {
+            if note.kind != NoteKind::Review.as_str() {
+                continue;
+            }
+            // A file that cannot be read cannot say it is public.
+            let Ok(content) = std::fs::read_to_string(vault_dir.join(&note.file_path)) else {
+                continue;
+            };
+            if !notes::is_public(&content) {
+                continue;
+            }
+            let body = notes::frontmatter_and_body(&content)
+                .1
+                .trim_end()
+                .to_string();
+            reviews.push(ReviewPage::gather(storage, &note, body).await?);
+        }
+        let mut tags: Vec<String> = storage
+            .book_tags(id)
+            .await?
+            .into_iter()
+            .map(|t| t.tag)
+            .collect();
+        tags.sort();
+        tags.dedup();
+        entries.push(Entry {
+            slug: slugs[&id].clone(),
+            cover: None,
+            book,
+            readings,
+            rating,
+            reviews,
+            tags,
+        });
+    }
+
+    for e in &mut entries {
+        e.cover = copy_cover(&e.book, &e.slug, &out.join("covers"))?;
+    }
+
+    let mut authors: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut tags: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, e) in entries.iter().enumerate() {
+        for a in &e.book.authors {
+            authors.entry(a).or_default().push(i);
+        }
+        for t in &e.tags {
+            tags.entry(t).or_default().push(i);
+        }
+    }
+    let slugs = Slugs {
+        authors: name_slugs(authors.keys().copied()),
+        tags: name_slugs(tags.keys().copied()),
+    };
+
+    write(&out.join("style.css"), &format!("{STYLE}{SITE_STYLE}"))?;
+    write(&out.join("index.html"), &index(&entries))?;
+    for e in &entries {
+        write(
+            &out.join("books").join(format!("{}.html", e.slug)),
+            &book_page(e, &slugs),
+        )?;
+    }
+    for (name, books) in &authors {
+        let slug = &slugs.authors[*name];
+        let body = format!(
+            "<h1>{}</h1>\n{}",
+            escape(name),
+            shelf_list(&entries, books, "../")
+        );
+        write(
+            &out.join("authors").join(format!("{slug}.html")),
+            &page(name, "../", &body),
+        )?;
+    }
+    for (name, books) in &tags {
+        let slug = &slugs.tags[*name];
+        let body = format!(
+            "<h1>{}</h1>\n{}",
+            escape(name),
+            shelf_list(&entries, books, "../")
+        );
+        write(
+            &out.join("tags").join(format!("{slug}.html")),
+            &page(name, "../", &body),
+        )?;
+    }
+
+    Ok(SiteReport {
+        books: entries.len(),
+        reviews: entries.iter().map(|e| e.reviews.len()).sum(),
+        authors: authors.len(),
+        tags: tags.len(),
+        index: out.join("index.html"),
+    })
+}
+
+/// Each book's slug, decided in id order so the older of two alike keeps the
+/// bare one. The younger takes `-{id}`, and that too may be taken — "Volume"
+/// with id 3 against a book titled "Volume 3" — so it counts on from there
+/// until a slug is free.
+fn book_slugs(books: &[Book]) -> BTreeMap<i64, String> {
+    let mut by_id: Vec<&Book> = books.iter().collect();
+    by_id.sort_by_key(|b| b.id);
+    let mut taken = HashSet::new();
+    let mut out = BTreeMap::new();
+    for b in by_id {
+        let id = b.id.expect("stored book has id");
+        let base = notes::slugify(b.display_title());
+        let mut slug = base.clone();
+        let mut n = 1;
+        while !taken.insert(slug.clone()) {
+            slug = match n {
+                1 => format!("{base}-{id}"),
+                n => format!("{base}-{id}-{n}"),
+            };
+            n += 1;
+        }
+        out.insert(id, slug);
+    }
+    out
+}
+
+/// The page name of every author and tag.
+struct Slugs {
+    authors: BTreeMap<String, String>,
+    tags: BTreeMap<String, String>,
+}
+
+/// Each name's slug. Two names that slug alike — "Sci-Fi" and "sci fi" — are
+/// two pages, not one under whichever came first: the later in sort order gets
+/// `-2`, then `-3`, which is stable for as long as the names are.
+fn name_slugs<'a>(names: impl Iterator<Item = &'a str>) -> BTreeMap<String, String> {
+    let mut names: Vec<&str> = names.collect();
+    names.sort_unstable();
+    names.dedup();
+    let mut taken = HashSet::new();
+    let mut out = BTreeMap::new();
+    for name in names {
+        let base = notes::slugify(name);
+        let mut slug = base.clone();
+        let mut n = 1;
+        while !taken.insert(slug.clone()) {
+            n += 1;
+            slug = format!("{base}-{n}");
+        }
+        out.insert(name.to_string(), slug);
+    }
+    out
+}
+
+/// Copy the cover in beside the pages, named for the book. Only a type a
+/// browser shows is copied; a cover gone from disk is left out.
+fn copy_cover(book: &Book, slug: &str, dir: &Path) -> Result<Option<String>> {
+    let Some(from) = book.cover_path.as_deref().map(Path::new) else {
+        return Ok(None);
+    };
+    let (Some(_), Some(ext)) = (image_mime(from), from.extension()) else {
+        return Ok(None);
+    };
+    if !from.is_file() {
+        return Ok(None);
+    }
+    let name = format!("{slug}.{}", ext.to_string_lossy().to_lowercase());
+    std::fs::create_dir_all(dir)?;
+    std::fs::copy(from, dir.join(&name))?;
+    Ok(Some(name))
+}
+
+fn index(entries: &[Entry]) -> String {
+    let mut shelves: BTreeMap<Shelf, Vec<(i64, usize)>> = BTreeMap::new();
+    for (i, e) in entries.iter().enumerate() {
+        let mut years = HashSet::new();
+        for r in &e.readings {
+            if let Some(at) = r.finished_at
+                && let Ok(t) = OffsetDateTime::from_unix_timestamp(at)
+                && years.insert(t.year())
+            {
+                shelves
+                    .entry(Shelf::Year(std::cmp::Reverse(t.year())))
+                    .or_default()
+                    .push((-at, i));
+            }
+        }
+        if years.is_empty() {
+            shelves.entry(Shelf::Unfinished).or_default().push((0, i));
+        }
+    }
+    let mut body = String::from("<h1>Shelf</h1>\n");
+    for (shelf, mut books) in shelves {
+        // Most recently finished first; the unfinished stay in title order.
+        books.sort_by_key(|(at, _)| *at);
+        let heading = match shelf {
+            Shelf::Year(std::cmp::Reverse(y)) => y.to_string(),
+            Shelf::Unfinished => "Not finished".to_string(),
+        };
+        let idxs: Vec<usize> = books.into_iter().map(|(_, i)| i).collect();
+        body.push_str(&format!(
+            "<h2>{heading}</h2>\n{}",
+            shelf_list(entries, &idxs, "")
+        ));
+    }
+    page("Shelf", "", &body)
+}
+
+fn book_page(e: &Entry, slugs: &Slugs) -> String {
+    let title = e.book.display_title();
+    let mut body = String::from("<header>\n");
+    if let Some(c) = &e.cover {
+        body.push_str(&format!(
+            "<img class=\"cover\" src=\"../covers/{c}\" alt=\"Cover of {}\">\n",
+            escape(title)
+        ));
+    }
+    body.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+    if !e.book.authors.is_empty() {
+        let by: Vec<String> = e
+            .book
+            .authors
+            .iter()
+            .map(|a| {
+                format!(
+                    "<a href=\"../authors/{}.html\">{}</a>",
+                    slugs.authors[a],
+                    escape(a)
+                )
+            })
+            .collect();
+        body.push_str(&format!("<p class=\"byline\">by {}</p>\n", by.join(", ")));
+    }
+    if !e.tags.is_empty() {
+        let tags: Vec<String> = e
+            .tags
+            .iter()
+            .map(|t| {
+                format!(
+                    "<a href=\"../tags/{}.html\">{}</a>",
+                    slugs.tags[t],
+                    escape(t)
+                )
+            })
+            .collect();
+        body.push_str(&format!("<p class=\"tags\">{}</p>\n", tags.join(" · ")));
+    }
+    if let Some(r) = &e.rating {
+        body.push_str(&format!(
+            "<p class=\"facts\"><span class=\"rating\">{} / {}</span></p>\n",
+            r.value, r.scale.max
+        ));
+    }
+    body.push_str("</header>\n");
+
+    let readings: Vec<String> = e
+        .readings
+        .iter()
+        .filter_map(|r| {
+            let dates = reading_dates(r)?;
+            Some(format!(
+                "<li>{} · {}</li>",
+                escape(&dates),
+                escape(&r.status)
+            ))
+        })
+        .collect();
+    if !readings.is_empty() {
+        body.push_str(&format!(
+            "<section class=\"readings\">\n<h2>Readings</h2>\n<ul>\n{}\n</ul>\n</section>\n",
+            readings.join("\n")
+        ));
+    }
+    for review in &e.reviews {
+        body.push_str(&format!(
+            "<article class=\"review\">\n<h2>Review</h2>\n{}{}</article>\n",
+            review.facts_html(),
+            review.review_html()
+        ));
+    }
+    page(title, "../", &body)
+}
+
+/// Books as a list of links, from a page `root` below the site's top.
+fn shelf_list(entries: &[Entry], idxs: &[usize], root: &str) -> String {
+    let items: Vec<String> = idxs
+        .iter()
+        .map(|&i| {
+            let e = &entries[i];
+            let by = match e.book.authors.is_empty() {
+                true => String::new(),
+                false => format!(
+                    " <span class=\"byline\">{}</span>",
+                    escape(&e.book.authors.join(", "))
+                ),
+            };
+            format!(
+                "<li><a href=\"{root}books/{}.html\">{}</a>{by}</li>",
+                e.slug,
+                escape(e.book.display_title())
+            )
+        })
+        .collect();
+    format!("<ul class=\"shelf\">\n{}\n</ul>\n", items.join("\n"))
+}
+
+fn page(title: &str, root: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<link rel=\"stylesheet\" href=\"{root}style.css\">\n</head>\n\
+         <body>\n<nav><a href=\"{root}index.html\">Shelf</a></nav>\n{body}</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+/// What the site adds to a review page's styling: the navigation and lists.
+const SITE_STYLE: &str = "
+nav { margin-bottom: 1.5rem; font-size: 0.9rem; }
+a { color: inherit; }
+.shelf { list-style: none; padding: 0; }
+.shelf li { margin: 0.3rem 0; }
+.shelf .byline, .tags { color: #555; }
+.review { border-top: 1px solid #ddd; margin-top: 2rem; }
+";
+
+fn write(path: &Path, content: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
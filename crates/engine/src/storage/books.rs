@@ -3,6 +3,7 @@ use sqlx::sqlite::SqliteRow;
 use time::OffsetDateTime;
 
 use super::highlights::identity_hash_of;
+use super::works::PRUNE_WORKS;
 use super::{Storage, now_unix};
 use crate::book::Book;
 use crate::error::{EngineError, Result};
@@ -12,6 +13,26 @@ pub enum BookSort {
     LastModified,
     Title,
     Progress,
+    /// By series name, then place in it; books in no series last, by title.
+    Series,
+}
+
+/// The `ORDER BY` for `sort`. Every order ends in `books.id`, so equal keys
+/// still come back in one order and an offset into them means one thing.
+pub(super) fn order_by(sort: BookSort) -> &'static str {
+    match sort {
+        BookSort::LastModified => "books.last_modified DESC, books.id DESC",
+        BookSort::Title => "books.title COLLATE NOCASE ASC, books.id ASC",
+        // The joined reading's page, not a `books` column any more.
+        BookSort::Progress => {
+            "CAST(cur.current_page AS REAL) / NULLIF(books.page_count, 0) DESC NULLS LAST, \
+             books.id ASC"
+        }
+        BookSort::Series => {
+            "series.name COLLATE NOCASE ASC NULLS LAST, bs.position ASC NULLS LAST, \
+             books.title COLLATE NOCASE ASC, books.id ASC"
+        }
+    }
 }
 
 /// What [`Storage::merge_books`] actually moved.
@@ -44,6 +65,10 @@ pub struct MergeReport {
     /// on the sha256 alone, so the same content cannot already be on both sides
     /// and a collision is not representable.
     pub files_moved: usize,
+    /// Days of reading activity repointed at `dst`. No `dropped` twin worth
+    /// reporting: a day collides only when one device file's statistics were
+    /// written under both books, and then `dst`'s copy is the newer import.
+    pub events_moved: usize,
     /// `src`'s cover file, when `dst` already had one of its own and therefore
     /// kept it. The file is now unreferenced; the caller deletes it, the same
     /// contract [`Storage::delete_book`] has.
@@ -67,6 +92,8 @@ pub(super) const BOOK_COLUMNS: &str = "books.id, books.title, books.sort_title,
      cur.current_page AS current_page, \
      CASE WHEN cur.status = 'finished' THEN 1 ELSE 0 END AS finished, \
      cur.started_at AS date_started, cur.finished_at AS date_finished, \
+     series.name AS series, bs.position AS series_index, \
+     books.work_id, works.openlibrary_key AS openlibrary_work, \
      books.created_at, books.last_modified";
 
 /// The join that resolves **the current reading**: the open one if there is
@@ -76,11 +103,17 @@ pub(super) const BOOK_COLUMNS: &str = "books.id, books.title, books.sort_title,
 /// "current" rather than a different one per column — a book whose `finished`
 /// came from its last reading while its `current_page` came from nowhere would
 /// render as a contradiction.
+///
+/// The series and work joins ride along for the same reason: each is keyed on
+/// the book, so they add columns and never a row.
 pub(super) const BOOK_FROM: &str = "FROM books LEFT JOIN readings cur ON cur.id = (
          SELECT r.id FROM readings r WHERE r.book_id = books.id
           ORDER BY (r.finished_at IS NULL) DESC,
                    COALESCE(r.started_at, r.created_at) DESC, r.id DESC
-          LIMIT 1)";
+          LIMIT 1)
+       LEFT JOIN book_series bs ON bs.book_id = books.id
+       LEFT JOIN series ON series.id = bs.series_id
+       LEFT JOIN works ON works.id = books.work_id";
 
 pub(super) fn row_to_book(row: &SqliteRow) -> Result<Book> {
     let authors: String = row.try_get("authors")?;
@@ -105,6 +138,10 @@ pub(super) fn row_to_book(row: &SqliteRow) -> Result<Book> {
         page_count: row.try_get("page_count")?,
         description: row.try_get("description")?,
         first_sentence: row.try_get("first_sentence")?,
+        series: row.try_get("series")?,
+        series_index: row.try_get("series_index")?,
+        work_id: row.try_get("work_id")?,
+        openlibrary_work: row.try_get("openlibrary_work")?,
         current_page: row.try_get("current_page")?,
         finished: row.try_get::<i64, _>("finished")? != 0,
         date_started: row.try_get("date_started")?,
@@ -253,7 +290,10 @@ impl Storage {
             .bind(now)
             .fetch_one(self.pool())
             .await?;
-        Ok(row.try_get("id")?)
+        let id = row.try_get("id")?;
+        self.merge_series(id, book).await?;
+        self.merge_work(id, book).await?;
+        Ok(id)
     }
 
     /// Merge a partial record into a book **we have already identified**.
@@ -280,6 +320,8 @@ impl Storage {
             .bind(book_id)
             .execute(self.pool())
             .await?;
+        self.merge_series(book_id, book).await?;
+        self.merge_work(book_id, book).await?;
         Ok(())
     }
 
@@ -316,16 +358,13 @@ impl Storage {
         rows.iter().map(row_to_book).collect()
     }
 
+    /// The first `limit` books. [`Storage::query_books`] is the same list
+    /// filtered, paged and counted.
     pub async fn list_books(&self, limit: i64, sort: BookSort) -> Result<Vec<Book>> {
-        let order = match sort {
-            BookSort::LastModified => "books.last_modified DESC",
-            BookSort::Title => "books.title COLLATE NOCASE ASC",
-            // The joined reading's page, not a `books` column any more.
-            BookSort::Progress => {
-                "CAST(cur.current_page AS REAL) / NULLIF(books.page_count, 0) DESC NULLS LAST"
-            }
-        };
-        let sql = format!("SELECT {BOOK_COLUMNS} {BOOK_FROM} ORDER BY {order} LIMIT ?");
+        let sql = format!(
+            "SELECT {BOOK_COLUMNS} {BOOK_FROM} ORDER BY {} LIMIT ?",
+            order_by(sort)
+        );
         let rows = sqlx::query(&sql).bind(limit).fetch_all(self.pool()).await?;
         rows.iter().map(row_to_book).collect()
     }
@@ -418,7 +457,9 @@ impl Storage {
                     // off the copy we are about to drop before dropping it:
                     // `flashcards.highlight_id` cascades on delete and
                     // `notes.highlight_id` nulls, so doing this in the other
-                    // order loses note anchors silently.
+                    // order loses note anchors silently. A study card moves
+                    // unless the survivor is studied already, and then the
+                    // cascade takes the copy's.
                     sqlx::query("UPDATE notes SET highlight_id = ? WHERE highlight_id = ?")
                         .bind(keep)
                         .bind(id)
@@ -429,6 +470,13 @@ impl Storage {
                         .bind(id)
                         .execute(&mut *tx)
                         .await?;
+                    sqlx::query(
+                        "UPDATE OR IGNORE study_cards SET highlight_id = ? WHERE highlight_id = ?",
+                    )
+                    .bind(keep)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
                     sqlx::query("DELETE FROM highlights WHERE id = ?")
                         .bind(id)
                         .execute(&mut *tx)
@@ -526,6 +574,17 @@ impl Storage {
             .await?
             .rows_affected() as usize;
 
+        // ---- reading events ------------------------------------------------
+        // `UPDATE OR IGNORE`, like the tags below: the key is `(book_id, day,
+        // source, source_key)`, and the losers go with the cascade.
+        report.events_moved =
+            sqlx::query("UPDATE OR IGNORE reading_events SET book_id = ? WHERE book_id = ?")
+                .bind(dst)
+                .bind(src)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as usize;
+
         // ---- provenance ----------------------------------------------------
         // `external_ids` and `book_tags` both cascade on `books`, so a merge
         // that left them alone would *delete* them with `src` — and losing
@@ -546,6 +605,27 @@ impl Storage {
             .bind(src)
             .execute(&mut *tx)
             .await?;
+        // A series is a field, so `dst` wins it like the columns below: `src`'s
+        // only moves across when `dst` has none.
+        sqlx::query("UPDATE OR IGNORE book_series SET book_id = ? WHERE book_id = ?")
+            .bind(dst)
+            .bind(src)
+            .execute(&mut *tx)
+            .await?;
+        // Shelves are the user's, so `src`'s memberships all survive; where
+        // both were on one shelf, `dst` keeps its place.
+        sqlx::query("UPDATE OR IGNORE shelf_books SET book_id = ? WHERE book_id = ?")
+            .bind(dst)
+            .bind(src)
+            .execute(&mut *tx)
+            .await?;
+        // And a work the same way. A merge folds two rows of one edition
+        // together, so whichever work either was in is the work of the one.
+        sqlx::query("UPDATE books SET work_id = COALESCE(work_id, ?2) WHERE id = ?1")
+            .bind(dst)
+            .bind(src_book.work_id)
+            .execute(&mut *tx)
+            .await?;
 
         // `src` goes before `dst` is updated: isbn_10 and isbn_13 are UNIQUE, so
         // handing `dst` an ISBN `src` still holds would fail the constraint.
@@ -602,6 +682,7 @@ impl Storage {
         .bind(now_unix())
         .execute(&mut *tx)
         .await?;
+        sqlx::query(PRUNE_WORKS).execute(&mut *tx).await?;
 
         tx.commit().await?;
         Ok(report)
@@ -950,8 +1031,8 @@ mod tests {
         );
     }
 
-    /// The dropped copy's note anchors and flashcards have to survive it.
-    /// `flashcards.highlight_id` cascades on delete and `notes.highlight_id`
+    /// The dropped copy's note anchors, flashcards and study card have to
+    /// survive it. `flashcards.highlight_id` cascades on delete and `notes.highlight_id`
     /// nulls, so deleting first and repointing after would lose both — silently,
     /// and only for books that happened to overlap.
     #[tokio::test]
@@ -966,9 +1047,16 @@ mod tests {
         s.insert_flashcard(src, Some(doomed), "shared", None)
             .await
             .unwrap();
+        s.study_highlight(doomed, true).await.unwrap();
 
         s.merge_books(src, dst).await.unwrap();
 
+        assert_eq!(
+            s.studied_highlights(dst).await.unwrap(),
+            vec![keep],
+            "the study card must follow the survivor"
+        );
+
         let anchored: Option<i64> =
             sqlx::query_scalar("SELECT highlight_id FROM flashcards WHERE word = ?")
                 .bind("shared")
@@ -0,0 +1,241 @@
+//! Paired readers (`devices`, migration `0015`) and the books each has pushed.
+//!
+//! Storage holds hashes and timestamps and nothing cleverer. Minting codes and
+//! tokens, and deciding what a stale code means, is [`crate::pairing`]'s.
+
+use sqlx::Row;
+
+use super::highlights::NewHighlight;
+use super::{Storage, now_unix};
+use crate::error::Result;
+
+/// One reader, pending, paired or revoked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    pub id: i64,
+    /// What the user called it when they paired it.
+    pub name: String,
+    pub created_at: i64,
+    /// `None` while the code is still waiting to be traded.
+    pub paired_at: Option<i64>,
+    /// The last time it presented its token, unix seconds.
+    pub last_contact: Option<i64>,
+    pub revoked_at: Option<i64>,
+    /// Books it has pushed, most recently reported first.
+    pub books: Vec<i64>,
+}
+
+impl Device {
+    /// Paired and not since revoked: the only state a token is good in.
+    pub fn is_paired(&self) -> bool {
+        self.paired_at.is_some() && self.revoked_at.is_none()
+    }
+}
+
+const DEVICE_COLUMNS: &str = "id, name, created_at, paired_at, last_contact, revoked_at";
+
+fn row_to_device(r: &sqlx::sqlite::SqliteRow) -> Device {
+    Device {
+        id: r.get("id"),
+        name: r.get("name"),
+        created_at: r.get("created_at"),
+        paired_at: r.get("paired_at"),
+        last_contact: r.get("last_contact"),
+        revoked_at: r.get("revoked_at"),
+        books: Vec::new(),
+    }
+}
+
+impl Storage {
+    /// A new pending device, waiting for `code` until `expires_at`.
+    ///
+    /// Pending rows whose code has already run out are dropped first: they
+    /// never paired, so nothing points at them, and a code nobody can trade is
+    /// a row nobody wants listed.
+    pub async fn insert_pending_device(
+        &self,
+        name: &str,
+        code: &str,
+        expires_at: i64,
+    ) -> Result<i64> {
+        let now = now_unix();
+        let mut tx = self.pool().begin().await?;
+        sqlx::query("DELETE FROM devices WHERE paired_at IS NULL AND code_expires_at <= ?")
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO devices (name, pairing_code, code_expires_at, created_at)
+             VALUES (?, ?, ?, ?) RETURNING id",
+        )
+        .bind(name)
+        .bind(code)
+        .bind(expires_at)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Trade a live code for a token, once. The code is cleared in the same
+    /// statement that checks it, so two readers racing with one code cannot
+    /// both win.
+    pub async fn redeem_pairing_code(&self, code: &str, token_hash: &str) -> Result<Option<i64>> {
+        let now = now_unix();
+        let id = sqlx::query_scalar(
+            "UPDATE devices
+             SET token_hash = ?, paired_at = ?, last_contact = ?,
+                 pairing_code = NULL, code_expires_at = NULL
+             WHERE pairing_code = ? AND code_expires_at > ? AND revoked_at IS NULL
+             RETURNING id",
+        )
+        .bind(token_hash)
+        .bind(now)
+        .bind(now)
+        .bind(code)
+        .bind(now)
+        .fetch_optional(self.pool())
+        .await?;
+        Ok(id)
+    }
+
+    /// The paired device holding this token, stamped as in contact now.
+    pub async fn device_by_token_hash(&self, token_hash: &str) -> Result<Option<Device>> {
+        let sql = format!(
+            "UPDATE devices SET last_contact = ?
+             WHERE token_hash = ? AND revoked_at IS NULL
+             RETURNING {DEVICE_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(now_unix())
+            .bind(token_hash)
+            .fetch_optional(self.pool())
+            .await?;
+        match row {
+            Some(r) => {
+                let mut device = row_to_device(&r);
+                device.books = self.books_reported(device.id).await?;
+                Ok(Some(device))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_device(&self, id: i64) -> Result<Option<Device>> {
+        let sql = format!("SELECT {DEVICE_COLUMNS} FROM devices WHERE id = ?");
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(self.pool())
+            .await?;
+        match row {
+            Some(r) => {
+                let mut device = row_to_device(&r);
+                device.books = self.books_reported(id).await?;
+                Ok(Some(device))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Every device, newest first, revoked ones included — the rows they made
+    /// still name them.
+    pub async fn list_devices(&self) -> Result<Vec<Device>> {
+        let sql = format!("SELECT {DEVICE_COLUMNS} FROM devices ORDER BY created_at DESC, id DESC");
+        let rows = sqlx::query(&sql).fetch_all(self.pool()).await?;
+        let mut devices: Vec<Device> = rows.iter().map(row_to_device).collect();
+        let reported = sqlx::query(
+            "SELECT device_id, book_id FROM device_books_reported
+             ORDER BY last_reported DESC, book_id",
+        )
+        .fetch_all(self.pool())
+        .await?;
+        for r in reported {
+            let device_id: i64 = r.get("device_id");
+            if let Some(d) = devices.iter_mut().find(|d| d.id == device_id) {
+                d.books.push(r.get("book_id"));
+            }
+        }
+        Ok(devices)
+    }
+
+    async fn books_reported(&self, device_id: i64) -> Result<Vec<i64>> {
+        let ids = sqlx::query_scalar(
+            "SELECT book_id FROM device_books_reported WHERE device_id = ?
+             ORDER BY last_reported DESC, book_id",
+        )
+        .bind(device_id)
+        .fetch_all(self.pool())
+        .await?;
+        Ok(ids)
+    }
+
+    /// Revoke a device: its token stops working and any code it still had is
+    /// void. `false` if there is no such device. Revoking twice keeps the
+    /// first date.
+    pub async fn revoke_device(&self, id: i64) -> Result<bool> {
+        let done = sqlx::query(
+            "UPDATE devices
+             SET revoked_at = COALESCE(revoked_at, ?), token_hash = NULL,
+                 pairing_code = NULL, code_expires_at = NULL
+             WHERE id = ?",
+        )
+        .bind(now_unix())
+        .bind(id)
+        .execute(self.pool())
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    /// Say that `device_id` pushed `book_id`, and put its name on what came
+    /// with it: each of `highlights` that no reader has claimed yet, and — when
+    /// the push carried device state — the reading that state was written to.
+    ///
+    /// One transaction, so a list of devices never shows a book whose
+    /// highlights say they came from somewhere else.
+    pub async fn record_device_report(
+        &self,
+        device_id: i64,
+        book_id: i64,
+        highlights: &[NewHighlight],
+        carried_state: bool,
+    ) -> Result<()> {
+        let mut tx = self.pool().begin().await?;
+        sqlx::query(
+            "INSERT INTO device_books_reported (device_id, book_id, last_reported)
+             VALUES (?, ?, ?)
+             ON CONFLICT(device_id, book_id) DO UPDATE SET last_reported = excluded.last_reported",
+        )
+        .bind(device_id)
+        .bind(book_id)
+        .bind(now_unix())
+        .execute(&mut *tx)
+        .await?;
+        for h in highlights {
+            sqlx::query(
+                "UPDATE highlights SET device_id = ?
+                 WHERE book_id = ? AND identity_hash = ? AND device_id IS NULL",
+            )
+            .bind(device_id)
+            .bind(book_id)
+            .bind(h.identity_hash(book_id))
+            .execute(&mut *tx)
+            .await?;
+        }
+        if carried_state {
+            // The same row `set_device_state` picks, by the same ordering.
+            sqlx::query(
+                "UPDATE readings SET device_id = ?1
+                 WHERE id = (SELECT id FROM readings WHERE book_id = ?2
+                             ORDER BY (finished_at IS NULL) DESC,
+                                      COALESCE(started_at, created_at) DESC, id DESC LIMIT 1)",
+            )
+            .bind(device_id)
+            .bind(book_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
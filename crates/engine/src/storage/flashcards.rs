@@ -1,6 +1,6 @@
 use sqlx::Row;
 
-use super::{Storage, now_unix};
+use super::{CardKind, Storage, now_unix};
 use crate::error::Result;
 
 #[derive(Debug, Clone)]
@@ -12,6 +12,41 @@ pub struct FlashcardRow {
     pub exported: bool,
 }
 
+/// One note on its way into an Anki package: the prompt and everything the
+/// note type has a field for.
+#[derive(Debug, Clone)]
+pub(crate) struct AnkiSource {
+    pub kind: CardKind,
+    /// The flashcard's id for a word, the highlight's for a passage.
+    pub id: i64,
+    pub front: String,
+    /// The sentence a word was in; the reader's note on a passage.
+    pub context: Option<String>,
+    /// What the source is, besides its book, for the note's GUID: the word
+    /// itself, or the highlight's device creation time and text. Never a row
+    /// id — those change under a merge or a rebuild.
+    pub anchor: String,
+    pub book_title: String,
+    pub authors: Vec<String>,
+    pub page: Option<i64>,
+    pub cover_path: Option<String>,
+}
+
+fn row_to_source(r: &sqlx::sqlite::SqliteRow, kind: CardKind) -> AnkiSource {
+    let authors: String = r.get("authors");
+    AnkiSource {
+        kind,
+        id: r.get("id"),
+        front: r.get("front"),
+        context: r.get("context"),
+        anchor: r.get("anchor"),
+        book_title: r.get("book_title"),
+        authors: serde_json::from_str(&authors).unwrap_or_default(),
+        page: r.get("page"),
+        cover_path: r.get("cover_path"),
+    }
+}
+
 impl Storage {
     /// Returns true if newly inserted (UNIQUE(book_id, word) dedupes).
     pub async fn insert_flashcard(
@@ -92,4 +127,48 @@ impl Storage {
         }
         Ok(())
     }
+
+    /// What an Anki package carries: the words (the unexported ones unless
+    /// `include_exported`), then every highlight being studied. A highlight
+    /// has no exported flag — its GUID is what keeps a second package from
+    /// duplicating it.
+    pub(crate) async fn anki_sources(&self, include_exported: bool) -> Result<Vec<AnkiSource>> {
+        let filter = if include_exported {
+            ""
+        } else {
+            "WHERE f.exported = 0"
+        };
+        let words = format!(
+            r#"SELECT f.id, f.word AS front, f.context, f.word AS anchor,
+                      COALESCE(b.title, '') AS book_title, b.authors,
+                      b.cover_path, h.page
+                 FROM flashcards f JOIN books b ON b.id = f.book_id
+                 LEFT JOIN highlights h ON h.id = f.highlight_id
+                 {filter} ORDER BY f.created_at ASC, f.id"#
+        );
+        let mut out: Vec<AnkiSource> = sqlx::query(&words)
+            .fetch_all(self.pool())
+            .await?
+            .iter()
+            .map(|r| row_to_source(r, CardKind::Word))
+            .collect();
+        let passages = sqlx::query(
+            r#"SELECT h.id, h.text AS front, COALESCE(h.annotation, h.ko_note) AS context,
+                      COALESCE(h.ko_datetime, '') || ':' || h.text AS anchor,
+                      COALESCE(b.title, '') AS book_title, b.authors,
+                      b.cover_path, h.page
+                 FROM study_cards sc
+                 JOIN highlights h ON h.id = sc.highlight_id
+                 JOIN books b ON b.id = h.book_id
+                ORDER BY sc.created_at ASC, sc.id"#,
+        )
+        .fetch_all(self.pool())
+        .await?;
+        out.extend(
+            passages
+                .iter()
+                .map(|r| row_to_source(r, CardKind::Highlight)),
+        );
+        Ok(out)
+    }
 }
@@ -1,6 +1,7 @@
 use sha2::{Digest, Sha256};
 use sqlx::Row;
 
+use super::notes::qualified;
 use super::{Storage, now_unix};
 use crate::error::Result;
 
@@ -183,6 +184,21 @@ pub struct Highlight {
     /// which is when the device captured it, and equally not something a
     /// refresh may move.
     pub created_at: i64,
+    /// The paired reader that first pushed it, if one did. `None` for a wired
+    /// pull and for every source that is not a paired reader.
+    pub device_id: Option<i64>,
+}
+
+/// One highlight a full-text query found.
+#[derive(Debug, Clone)]
+pub struct HighlightSearchHit {
+    pub highlight: Highlight,
+    /// The best-matching stretch of whichever column matched — the passage, the
+    /// annotation, the device note or the chapter — with the terms in `>>`/`<<`,
+    /// as [`super::NoteSearchHit`]'s are.
+    pub snippet: String,
+    /// FTS5's bm25 rank: lower is better, and only comparable within a query.
+    pub rank: f64,
 }
 
 /// Every column a [`Highlight`] is built from, and the mapper that builds it.
@@ -191,7 +207,7 @@ pub struct Highlight {
 /// returns highlights too, and a second hand-written projection is how the two
 /// drift into disagreeing about what a highlight is.
 pub(super) const HIGHLIGHT_COLUMNS: &str = "id, book_id, text, chapter, page, ko_note, annotation, ko_datetime, reading_id, \
-     source, created_at";
+     source, created_at, device_id";
 
 pub(super) fn row_to_highlight(r: &sqlx::sqlite::SqliteRow) -> Highlight {
     Highlight {
@@ -206,6 +222,7 @@ pub(super) fn row_to_highlight(r: &sqlx::sqlite::SqliteRow) -> Highlight {
         reading_id: r.get("reading_id"),
         source: r.get("source"),
         created_at: r.get("created_at"),
+        device_id: r.get("device_id"),
     }
 }
 
@@ -323,6 +340,58 @@ impl Storage {
         Ok(n > 0)
     }
 
+    /// Rewrite the stored copy of `old` as `new`, in place. Returns true if a
+    /// row was rewritten.
+    ///
+    /// For a device that records an edit as a *second* annotation rather than
+    /// changing the first — Kindle appends a new clipping when a highlight's
+    /// bounds are dragged, and leaves the old one in the file. The identity
+    /// changes (the text is part of it), so neither `insert_highlight` nor
+    /// `refresh_device_fields` can express it: one would add a duplicate, the
+    /// other would find nothing to refresh.
+    ///
+    /// In place for the reason `refresh_device_fields` gives — notes and
+    /// flashcards point at the row's id — and `annotation` is untouched. A no-op
+    /// when `new` is already stored, so a stale and a current copy can never
+    /// collapse into one row carrying the wrong id.
+    pub async fn supersede_highlight(
+        &self,
+        book_id: i64,
+        old: &NewHighlight,
+        new: &NewHighlight,
+    ) -> Result<bool> {
+        let done = sqlx::query(
+            r#"UPDATE highlights SET
+                   text              = ?3,
+                   pos0              = ?4,
+                   pos1              = ?5,
+                   page              = ?6,
+                   chapter           = ?7,
+                   ko_datetime       = ?8,
+                   color             = ?9,
+                   ko_note           = ?10,
+                   last_seen_ko_note = ?10,
+                   identity_hash     = ?11
+               WHERE book_id = ?1 AND identity_hash = ?2
+                 AND NOT EXISTS (SELECT 1 FROM highlights h
+                                 WHERE h.book_id = ?1 AND h.identity_hash = ?11)"#,
+        )
+        .bind(book_id)
+        .bind(old.identity_hash(book_id))
+        .bind(&new.text)
+        .bind(new.pos0.as_ref())
+        .bind(new.pos1.as_ref())
+        .bind(new.page)
+        .bind(new.chapter.as_ref())
+        .bind(new.ko_datetime.as_ref())
+        .bind(new.color.as_ref())
+        .bind(new.note.as_ref())
+        .bind(new.identity_hash(book_id))
+        .execute(self.pool())
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
     /// Set the reader's own annotation on a highlight. Ours; the device never
     /// sees it and import never overwrites it.
     pub async fn set_annotation(&self, highlight_id: i64, annotation: Option<&str>) -> Result<()> {
@@ -388,6 +457,49 @@ impl Storage {
         Ok(n > 0)
     }
 
+    /// The stored copy of a device highlight, with the device note as of the
+    /// last import — `last_seen_ko_note`, which is what a push compares the
+    /// file against before it writes.
+    pub async fn device_highlight(
+        &self,
+        book_id: i64,
+        h: &NewHighlight,
+    ) -> Result<Option<(Highlight, Option<String>)>> {
+        let sql = format!(
+            "SELECT {HIGHLIGHT_COLUMNS}, last_seen_ko_note FROM highlights
+             WHERE book_id = ? AND identity_hash = ?"
+        );
+        let row = sqlx::query(&sql)
+            .bind(book_id)
+            .bind(h.identity_hash(book_id))
+            .fetch_optional(self.pool())
+            .await?;
+        Ok(row.map(|r| (row_to_highlight(&r), r.get("last_seen_ko_note"))))
+    }
+
+    /// Record that `note` is now what the device holds for this highlight,
+    /// because we just wrote it there. Both columns, for the reason
+    /// [`Storage::insert_highlight`] seeds both: a row whose `ko_note` and
+    /// `last_seen_ko_note` disagree reads as an edit nobody made.
+    pub async fn record_pushed_note(&self, highlight_id: i64, note: &str) -> Result<()> {
+        sqlx::query("UPDATE highlights SET ko_note = ?1, last_seen_ko_note = ?1 WHERE id = ?2")
+            .bind(note)
+            .bind(highlight_id)
+            .execute(self.pool())
+            .await?;
+        Ok(())
+    }
+
+    /// One highlight by id.
+    pub async fn get_highlight(&self, id: i64) -> Result<Option<Highlight>> {
+        let sql = format!("SELECT {HIGHLIGHT_COLUMNS} FROM highlights WHERE id = ?");
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(self.pool())
+            .await?;
+        Ok(row.as_ref().map(row_to_highlight))
+    }
+
     pub async fn list_highlights(&self, book_id: i64) -> Result<Vec<Highlight>> {
         let sql = format!(
             "SELECT {HIGHLIGHT_COLUMNS} FROM highlights WHERE book_id = ?
@@ -400,6 +512,41 @@ impl Storage {
         Ok(rows.iter().map(row_to_highlight).collect())
     }
 
+    /// Highlights matching an FTS5 query, best first.
+    ///
+    /// Searches the passage, both notes on it and the chapter (migration
+    /// `0013`). The index is kept by triggers on `highlights` itself, so nothing
+    /// here or in the importers has to remember to update it.
+    pub async fn search_highlights(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<HighlightSearchHit>> {
+        let columns = qualified(HIGHLIGHT_COLUMNS, "h");
+        let sql = format!(
+            r#"SELECT {columns},
+                      snippet(highlights_fts, -1, '>>', '<<', '…', 12) AS snip,
+                      highlights_fts.rank AS rank
+               FROM highlights_fts
+               JOIN highlights h ON h.id = highlights_fts.rowid
+               WHERE highlights_fts MATCH ?
+               ORDER BY highlights_fts.rank LIMIT ?"#
+        );
+        let rows = sqlx::query(&sql)
+            .bind(query)
+            .bind(limit)
+            .fetch_all(self.pool())
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|r| HighlightSearchHit {
+                highlight: row_to_highlight(r),
+                snippet: r.get("snip"),
+                rank: r.get("rank"),
+            })
+            .collect())
+    }
+
     /// What was highlighted during one reading, in the same order
     /// [`Storage::list_highlights`] uses.
     ///
@@ -0,0 +1,581 @@
+//! The library as a filtered, counted, paged list.
+//!
+//! [`Storage::list_books`] answers "the first N books"; that was the whole
+//! shelf while the library was small enough for N to be all of it. Past that it
+//! is a silent truncation — nothing said the 201st book existed. A page here
+//! always comes with the total it was cut from, so a short page and a short
+//! library can be told apart.
+//!
+//! ## Offset pagination, for every sort
+//!
+//! Decided rather than defaulted to (spec item 18). Keyset pagination would
+//! only ever cover two of the three sorts: [`BookSort::Progress`] orders by a
+//! ratio computed from the *current reading*, which no index holds and no
+//! cursor can seek into without materialising it. Two pagination schemes
+//! behind one call is a worse API than one scheme a little slower than it
+//! could be, and the slowness is hypothetical — an offset into a few thousand
+//! rows is a scan SQLite finishes before the terminal redraws.
+//!
+//! What keyset would have bought is stability when the library changes between
+//! pages, and for the default sort it would not have bought it either: a book
+//! touched between two pages moves under `last_modified` whichever way the
+//! page boundary is expressed. Every order ends in `books.id`, so a library
+//! that does *not* change pages deterministically, ties included.
+//!
+//! ## One WHERE clause, two statements
+//!
+//! The count and the page run the same predicate text with the same binds, so
+//! "12 of 40" can never be the count of one filter and the rows of another.
+//! Each filter is written `(?N IS NULL OR …)` rather than assembled per call:
+//! the statement is one string that can be read top to bottom, and the filters
+//! a caller left unset cost a comparison against NULL.
+
+use sqlx::Row;
+
+use super::books::{BOOK_COLUMNS, BOOK_FROM, order_by, row_to_book};
+use super::{BookSort, Storage};
+use crate::book::Book;
+use crate::error::{EngineError, Result};
+
+/// A book's state as its work's current reading has it: the open reading of
+/// any edition if there is one, else the latest — `BOOK_FROM`'s rule, asked
+/// across the editions. Reading the translation is reading the original, so
+/// neither lists as unstarted. The row's own `finished` and `current_page`
+/// stay the edition's: a page number means nothing in another printing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookStatus {
+    /// No reading at all.
+    Unstarted,
+    Reading,
+    Finished,
+    Abandoned,
+}
+
+impl BookStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BookStatus::Unstarted => "unstarted",
+            BookStatus::Reading => super::STATUS_READING,
+            BookStatus::Finished => super::STATUS_FINISHED,
+            BookStatus::Abandoned => super::STATUS_ABANDONED,
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<BookStatus> {
+        [
+            BookStatus::Unstarted,
+            BookStatus::Reading,
+            BookStatus::Finished,
+            BookStatus::Abandoned,
+        ]
+        .into_iter()
+        .find(|st| st.as_str().eq_ignore_ascii_case(s.trim()))
+    }
+}
+
+/// What to narrow a listing to. Every field left `None` narrows nothing, so
+/// `BookFilter::default()` is the whole library.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookFilter {
+    pub status: Option<BookStatus>,
+    /// Part of any one author's name, case-insensitively — `"tokarczuk"`
+    /// finds "Olga Tokarczuk" without the reader knowing how it was stored.
+    pub author: Option<String>,
+    /// Inclusive. A book with no `publish_year` is outside every range.
+    pub year_from: Option<i64>,
+    pub year_to: Option<i64>,
+    /// As stored on the book (`en`, `fr`, …), case-insensitively.
+    pub language: Option<String>,
+    /// A shelf from `book_tags`, matched against our normalized tag or the
+    /// origin's own string, so both `to-read` and "To Read" work.
+    pub tag: Option<String>,
+    /// A cover on disk (`cover_path`). A `cover_url` not yet downloaded is
+    /// not a cover the shelf can show.
+    pub has_cover: Option<bool>,
+    /// An owned file in `book_files`.
+    pub has_file: Option<bool>,
+    /// A system that knows this book: an `external_ids` source (`goodreads`,
+    /// `calibre`), `koreader` for a book linked to a device file, or the
+    /// `source` of any of its readings (`manual`, `migrated`).
+    pub source: Option<String>,
+    /// Part of the series name, case-insensitively, so `"earthsea"` finds
+    /// "The Earthsea Cycle".
+    pub series: Option<String>,
+    /// At least this many Goodreads stars on some review, read through
+    /// `rating_map` — the user's own scale means nothing to a filter that
+    /// must work whatever scale a review was rated on.
+    pub min_stars: Option<u8>,
+    /// A reading finished in this year, local time, as the stats count it.
+    pub finished_year: Option<i64>,
+    /// A note of any kind on the book that is not tombstoned.
+    pub has_notes: Option<bool>,
+    /// The books on this shelf, by name, case-insensitively. An unknown shelf
+    /// is [`EngineError::NotFound`] rather than an empty page.
+    pub shelf: Option<String>,
+}
+
+/// One page request: which books, in what order, and which slice of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookQuery {
+    pub filter: BookFilter,
+    pub sort: BookSort,
+    /// `None` is every match — what a shelf that sorts and filters in memory
+    /// wants.
+    pub limit: Option<i64>,
+    pub offset: i64,
+}
+
+impl Default for BookQuery {
+    fn default() -> Self {
+        BookQuery {
+            filter: BookFilter::default(),
+            sort: BookSort::LastModified,
+            limit: None,
+            offset: 0,
+        }
+    }
+}
+
+/// What [`Storage::query_books`] found.
+#[derive(Debug, Clone)]
+pub struct BookPage {
+    pub books: Vec<Book>,
+    /// Every book the filter matches, not just this page's.
+    pub total: i64,
+    /// Where this page started, echoed so the page can say where it is.
+    pub offset: i64,
+}
+
+impl BookPage {
+    /// The offset of the page after this one, or `None` when this one ended
+    /// the list.
+    pub fn next_offset(&self) -> Option<i64> {
+        let next = self.offset + self.books.len() as i64;
+        (!self.books.is_empty() && next < self.total).then_some(next)
+    }
+}
+
+/// The filters, as `?1`–`?14` in the order [`bind_filter`] binds them. A shelf
+/// arrives as `?14`, the JSON array of its book ids: what is on a smart shelf
+/// is itself a filter, and resolving it first keeps this one statement.
+const FILTER_WHERE: &str = "WHERE (?1 IS NULL OR COALESCE(
+          (SELECT r.status FROM readings r JOIN books e ON e.id = r.book_id
+            WHERE e.id = books.id OR e.work_id = books.work_id
+            ORDER BY (r.finished_at IS NULL) DESC,
+                     COALESCE(r.started_at, r.created_at) DESC, r.id DESC
+            LIMIT 1),
+          'unstarted') = ?1)
+   AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(books.authors) a
+                              WHERE instr(lower(a.value), lower(?2)) > 0))
+   AND (?3 IS NULL OR books.publish_year >= ?3)
+   AND (?4 IS NULL OR books.publish_year <= ?4)
+   AND (?5 IS NULL OR books.language = ?5 COLLATE NOCASE)
+   AND (?6 IS NULL OR EXISTS (SELECT 1 FROM book_tags t WHERE t.book_id = books.id
+                               AND (t.tag = ?6 COLLATE NOCASE OR t.raw = ?6 COLLATE NOCASE)))
+   AND (?7 IS NULL OR (books.cover_path IS NOT NULL) = ?7)
+   AND (?8 IS NULL OR EXISTS (SELECT 1 FROM book_files f WHERE f.book_id = books.id) = ?8)
+   AND (?9 IS NULL
+        OR EXISTS (SELECT 1 FROM external_ids e WHERE e.book_id = books.id AND e.source = ?9)
+        OR (?9 = 'koreader'
+            AND EXISTS (SELECT 1 FROM device_books d WHERE d.book_id = books.id))
+        OR EXISTS (SELECT 1 FROM readings r WHERE r.book_id = books.id AND r.source = ?9))
+   AND (?10 IS NULL OR instr(lower(series.name), lower(?10)) > 0)
+   AND (?11 IS NULL OR EXISTS (SELECT 1 FROM notes n
+                                 JOIN review_ratings rr ON rr.note_id = n.id
+                                 JOIN rating_map m ON m.scale_id = rr.scale_id
+                                                  AND m.value = rr.value
+                                WHERE n.book_id = books.id AND n.kind = 'review'
+                                  AND n.missing_since IS NULL AND m.goodreads >= ?11))
+   AND (?12 IS NULL OR EXISTS (SELECT 1 FROM readings r
+                                WHERE r.book_id = books.id AND r.status = 'finished'
+                                  AND CAST(strftime('%Y', r.finished_at, 'unixepoch', 'localtime')
+                                           AS INTEGER) = ?12))
+   AND (?13 IS NULL OR EXISTS (SELECT 1 FROM notes n WHERE n.book_id = books.id
+                                 AND n.missing_since IS NULL) = ?13)
+   AND (?14 IS NULL OR books.id IN (SELECT value FROM json_each(?14)))";
+
+type Query<'q> = sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>;
+
+fn bind_filter<'q>(q: Query<'q>, f: &'q BookFilter, shelf: Option<&'q str>) -> Query<'q> {
+    q.bind(f.status.map(BookStatus::as_str))
+        .bind(f.author.as_deref().map(str::trim))
+        .bind(f.year_from)
+        .bind(f.year_to)
+        .bind(f.language.as_deref().map(str::trim))
+        .bind(f.tag.as_deref().map(str::trim))
+        .bind(f.has_cover)
+        .bind(f.has_file)
+        .bind(f.source.as_deref().map(str::trim))
+        .bind(f.series.as_deref().map(str::trim))
+        .bind(f.min_stars)
+        .bind(f.finished_year)
+        .bind(f.has_notes)
+        .bind(shelf)
+}
+
+impl Storage {
+    /// One page of the books `query` matches, and how many match in all.
+    ///
+    /// A negative offset, or a year range that ends before it starts, is
+    /// [`EngineError::InvalidInput`]: both are a caller's arithmetic gone
+    /// wrong, and an empty page would hide it.
+    pub async fn query_books(&self, query: &BookQuery) -> Result<BookPage> {
+        if query.offset < 0 {
+            return Err(EngineError::InvalidInput(format!(
+                "offset {} is negative",
+                query.offset
+            )));
+        }
+        let f = &query.filter;
+        if let (Some(from), Some(to)) = (f.year_from, f.year_to)
+            && from > to
+        {
+            return Err(EngineError::InvalidInput(format!(
+                "year range {from}–{to} is empty"
+            )));
+        }
+
+        let shelf = match f.shelf.as_deref() {
+            Some(name) => Some(self.shelf_ids(name).await?),
+            None => None,
+        };
+        let shelf = shelf.as_deref();
+
+        let count_sql = format!("SELECT count(*) {BOOK_FROM} {FILTER_WHERE}");
+        let total: i64 = bind_filter(sqlx::query(&count_sql), f, shelf)
+            .fetch_one(self.pool())
+            .await?
+            .try_get(0)?;
+
+        let page_sql = format!(
+            "SELECT {BOOK_COLUMNS} {BOOK_FROM} {FILTER_WHERE}
+             ORDER BY {} LIMIT ?15 OFFSET ?16",
+            order_by(query.sort)
+        );
+        // SQLite reads a negative LIMIT as none at all.
+        let rows = bind_filter(sqlx::query(&page_sql), f, shelf)
+            .bind(query.limit.unwrap_or(-1))
+            .bind(query.offset)
+            .fetch_all(self.pool())
+            .await?;
+        Ok(BookPage {
+            books: rows.iter().map(row_to_book).collect::<Result<_>>()?,
+            total,
+            offset: query.offset,
+        })
+    }
+
+    /// The ids a filter matches, in no particular order — what a smart shelf
+    /// is made of. A shelf in `f` is ignored: a rule cannot name one, and
+    /// this is what resolving one calls.
+    pub(super) async fn filtered_ids(&self, f: &BookFilter) -> Result<Vec<i64>> {
+        let sql = format!("SELECT books.id {BOOK_FROM} {FILTER_WHERE}");
+        let rows = bind_filter(sqlx::query(&sql), f, None)
+            .fetch_all(self.pool())
+            .await?;
+        rows.iter()
+            .map(|r| r.try_get(0).map_err(Into::into))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn book(s: &Storage, title: &str, authors: &[&str], year: i64, lang: &str) -> i64 {
+        s.upsert_book(&Book {
+            title: Some(title.into()),
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            publish_year: Some(year),
+            language: Some(lang.into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    }
+
+    fn titles(page: &BookPage) -> Vec<&str> {
+        page.books
+            .iter()
+            .map(|b| b.title.as_deref().unwrap_or(""))
+            .collect()
+    }
+
+    fn by(filter: BookFilter) -> BookQuery {
+        BookQuery {
+            filter,
+            sort: BookSort::Title,
+            ..Default::default()
+        }
+    }
+
+    /// Pages tile the list: no book twice, none skipped, and the total is the
+    /// whole match rather than the page.
+    #[tokio::test]
+    async fn pages_tile_the_library_and_carry_its_total() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        for i in 0..7 {
+            // Two books per title, so the tiebreak is what keeps pages apart.
+            book(&s, &format!("Volume {}", i / 2), &[], 2000, "en").await;
+        }
+
+        let mut seen = Vec::new();
+        let mut q = BookQuery {
+            sort: BookSort::Title,
+            limit: Some(3),
+            ..Default::default()
+        };
+        loop {
+            let page = s.query_books(&q).await.unwrap();
+            assert_eq!(page.total, 7);
+            seen.extend(page.books.iter().map(|b| b.id.unwrap()));
+            match page.next_offset() {
+                Some(next) => q.offset = next,
+                None => break,
+            }
+        }
+        let mut unique = seen.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(seen.len(), 7);
+        assert_eq!(unique.len(), 7);
+
+        // No limit is every match, which is what the TUI shelf asks for.
+        let all = s.query_books(&BookQuery::default()).await.unwrap();
+        assert_eq!(all.books.len(), 7);
+        assert_eq!(all.next_offset(), None);
+
+        assert!(matches!(
+            s.query_books(&BookQuery {
+                offset: -1,
+                ..Default::default()
+            })
+            .await,
+            Err(EngineError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn each_filter_narrows_the_page_and_the_count_alike() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let flights = book(&s, "Flights", &["Olga Tokarczuk"], 2007, "pl").await;
+        let drive = book(&s, "Drive Your Plow", &["Olga Tokarczuk"], 2009, "EN").await;
+        let kindred = book(&s, "Kindred", &["Octavia E. Butler"], 1979, "en").await;
+
+        s.update_progress(drive, Some(10), None).await.unwrap();
+        s.update_progress(kindred, None, Some(true)).await.unwrap();
+        s.add_book_tags(
+            kindred,
+            "goodreads",
+            &[("time-travel".into(), "Time Travel".into())],
+        )
+        .await
+        .unwrap();
+        s.link_external_id("calibre", "17", flights).await.unwrap();
+        s.enrich_book(
+            drive,
+            &Book {
+                series: Some("Tokarczuk in English".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let reading = s.list_readings(kindred).await.unwrap()[0].id;
+        let review = s
+            .insert_note(
+                crate::storage::NewNoteMeta {
+                    book_id: Some(kindred),
+                    reading_id: Some(reading),
+                    highlight_id: None,
+                    page: None,
+                    location: None,
+                    file_path: "kindred-review.md",
+                    title: "Kindred — review",
+                    kind: "review",
+                },
+                "",
+                &[],
+                &[],
+            )
+            .await
+            .unwrap();
+        let scale = s.rating_scale_by_name("goodreads").await.unwrap().unwrap();
+        s.set_review_rating(review, &scale, 4.0).await.unwrap();
+        let this_year: i64 =
+            sqlx::query_scalar("SELECT CAST(strftime('%Y', 'now', 'localtime') AS INTEGER)")
+                .fetch_one(s.pool())
+                .await
+                .unwrap();
+        s.create_shelf("Desk", None).await.unwrap();
+        s.shelve("desk", flights, None).await.unwrap();
+        sqlx::query("UPDATE books SET cover_path = 'covers/k.jpg' WHERE id = ?")
+            .bind(kindred)
+            .execute(s.pool())
+            .await
+            .unwrap();
+
+        let cases: Vec<(BookFilter, Vec<&str>)> = vec![
+            (
+                BookFilter {
+                    status: Some(BookStatus::Unstarted),
+                    ..Default::default()
+                },
+                vec!["Flights"],
+            ),
+            (
+                BookFilter {
+                    status: Some(BookStatus::Finished),
+                    ..Default::default()
+                },
+                vec!["Kindred"],
+            ),
+            (
+                BookFilter {
+                    author: Some("tokarczuk".into()),
+                    ..Default::default()
+                },
+                vec!["Drive Your Plow", "Flights"],
+            ),
+            (
+                BookFilter {
+                    year_from: Some(2000),
+                    year_to: Some(2008),
+                    ..Default::default()
+                },
+                vec!["Flights"],
+            ),
+            (
+                BookFilter {
+                    language: Some("en".into()),
+                    ..Default::default()
+                },
+                vec!["Drive Your Plow", "Kindred"],
+            ),
+            (
+                BookFilter {
+                    tag: Some("Time Travel".into()),
+                    ..Default::default()
+                },
+                vec!["Kindred"],
+            ),
+            (
+                BookFilter {
+                    series: Some("IN ENGLISH".into()),
+                    ..Default::default()
+                },
+                vec!["Drive Your Plow"],
+            ),
+            (
+                BookFilter {
+                    has_cover: Some(false),
+                    ..Default::default()
+                },
+                vec!["Drive Your Plow", "Flights"],
+            ),
+            (
+                BookFilter {
+                    has_file: Some(true),
+                    ..Default::default()
+                },
+                vec![],
+            ),
+            (
+                BookFilter {
+                    source: Some("calibre".into()),
+                    ..Default::default()
+                },
+                vec!["Flights"],
+            ),
+            (
+                BookFilter {
+                    source: Some("manual".into()),
+                    ..Default::default()
+                },
+                vec!["Drive Your Plow", "Kindred"],
+            ),
+            (
+                BookFilter {
+                    min_stars: Some(4),
+                    ..Default::default()
+                },
+                vec!["Kindred"],
+            ),
+            (
+                BookFilter {
+                    min_stars: Some(5),
+                    ..Default::default()
+                },
+                vec![],
+            ),
+            (
+                BookFilter {
+                    finished_year: Some(this_year),
+                    ..Default::default()
+                },
+                vec!["Kindred"],
+            ),
+            (
+                BookFilter {
+                    has_notes: Some(false),
+                    ..Default::default()
+                },
+                vec!["Drive Your Plow", "Flights"],
+            ),
+            (
+                BookFilter {
+                    shelf: Some("DESK".into()),
+                    ..Default::default()
+                },
+                vec!["Flights"],
+            ),
+            // Filters combine by AND.
+            (
+                BookFilter {
+                    author: Some("olga".into()),
+                    language: Some("en".into()),
+                    ..Default::default()
+                },
+                vec!["Drive Your Plow"],
+            ),
+        ];
+        for (filter, want) in cases {
+            let page = s.query_books(&by(filter.clone())).await.unwrap();
+            assert_eq!(titles(&page), want, "{filter:?}");
+            assert_eq!(page.total, want.len() as i64, "{filter:?}");
+        }
+
+        assert!(matches!(
+            s.query_books(&by(BookFilter {
+                year_from: Some(2010),
+                year_to: Some(2000),
+                ..Default::default()
+            }))
+            .await,
+            Err(EngineError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            s.query_books(&by(BookFilter {
+                shelf: Some("nowhere".into()),
+                ..Default::default()
+            }))
+            .await,
+            Err(EngineError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn a_status_parses_from_the_word_it_prints_as() {
+        for st in [
+            BookStatus::Unstarted,
+            BookStatus::Reading,
+            BookStatus::Finished,
+            BookStatus::Abandoned,
+        ] {
+            assert_eq!(BookStatus::parse(st.as_str()), Some(st));
+        }
+        assert_eq!(BookStatus::parse(" Finished "), Some(BookStatus::Finished));
+        assert_eq!(BookStatus::parse("complete"), None);
+    }
+}
@@ -1,27 +1,46 @@
 mod book_files;
 mod books;
 mod device_books;
+mod devices;
 mod flashcards;
 mod highlights;
+mod listing;
 mod notes;
 mod provenance;
 mod ratings;
+mod reading_events;
 mod readings;
+mod search;
+mod series;
+mod shelves;
 mod sidecar_seen;
+mod stats;
+mod study;
+mod works;
 
 pub use book_files::BookFile;
 pub use books::{BookSort, MergeReport};
 pub use device_books::LinkedBy;
+pub use devices::Device;
+pub(crate) use flashcards::AnkiSource;
 pub use flashcards::FlashcardRow;
 pub(crate) use highlights::DeviceDigest;
-pub use highlights::{Highlight, NewHighlight};
-pub use notes::{NewNoteMeta, NoteRecord, NoteSearchHit, OutgoingLink};
+pub use highlights::{Highlight, HighlightSearchHit, NewHighlight};
+pub use listing::{BookFilter, BookPage, BookQuery, BookStatus};
+pub use notes::{Backlink, NewNoteMeta, NoteRecord, NoteSearchHit, OutgoingLink};
 pub use provenance::BookTag;
 pub use ratings::{Rating, RatingScale};
+pub use reading_events::{CONFIDENCE_INFERRED, CONFIDENCE_MEASURED, NewReadingEvent, ReadingEvent};
 pub use readings::{
     Reading, STATUS_ABANDONED, STATUS_FINISHED, STATUS_READING, ko_datetime_to_unix,
 };
+pub use search::LibraryHit;
+pub use series::SeriesSummary;
+pub use shelves::{Promoted, Shelf, ShelfRule, TagCandidate};
 pub use sidecar_seen::SidecarFacts;
+pub use stats::{PeriodStats, StatsGrain, StatsRange, format_day, parse_day};
+pub use study::{CardKind, StudyCard};
+pub use works::Work;
 
 use std::str::FromStr;
 
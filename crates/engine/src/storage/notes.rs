@@ -4,6 +4,7 @@ use time::OffsetDateTime;
 use super::highlights::{HIGHLIGHT_COLUMNS, row_to_highlight};
 use super::{Highlight, Storage, now_unix};
 use crate::error::Result;
+use crate::notes::WikiLink;
 
 /// Metadata for a new note row (body is passed separately — it lives on
 /// disk and only enters the DB as the FTS cache).
@@ -42,6 +43,8 @@ const NOTE_COLUMNS: &str = "id, book_id, reading_id, highlight_id, page, locatio
 pub struct NoteSearchHit {
     pub note: NoteRecord,
     pub snippet: String,
+    /// FTS5's bm25 rank: lower is better, and only comparable within a query.
+    pub rank: f64,
 }
 
 /// One outgoing edge, read from the linking note's side.
@@ -50,18 +53,62 @@ pub struct NoteSearchHit {
 /// has not been written yet. That is an ordinary zettelkasten forward reference,
 /// not an error, which is why the raw text is carried beside the resolution
 /// rather than the row being dropped.
+///
+/// A link into a section is an edge of its own (migration `0017`), and it
+/// dangles too when the note is there but the heading or block is not.
 #[derive(Debug, Clone)]
 pub struct OutgoingLink {
     /// The wikilink target exactly as the body wrote it.
     pub target_title: String,
+    /// The heading, or `^block`, the link points into; `None` for the note.
+    pub anchor: Option<String>,
     /// The note it resolves to, when one exists.
     pub to: Option<NoteRecord>,
+    /// Whether `to` still has `anchor`. Always true of a whole-note link
+    /// that resolves.
+    pub anchor_found: bool,
+}
+
+impl OutgoingLink {
+    /// No note by that title, or no such section in it.
+    pub fn dangles(&self) -> bool {
+        self.to.is_none() || !self.anchor_found
+    }
+}
+
+/// One incoming edge, read from the linked note's side: the note that links
+/// here, and the section of this note it points into.
+#[derive(Debug, Clone)]
+pub struct Backlink {
+    pub from: NoteRecord,
+    /// The heading, or `^block`, of *this* note the link names; `None` for
+    /// a link to the whole note.
+    pub anchor: Option<String>,
+    /// False when this note no longer has that section. The link still
+    /// arrives here — it is the section that is gone, and a reader of the
+    /// pane should see the link and that it lands at the top.
+    pub anchor_found: bool,
+}
+
+/// `l.anchor` as a column a row decodes, and whether it still resolves: an
+/// empty anchor is the whole note, so only the note has to be there.
+const ANCHOR_COLUMNS: &str = "l.anchor AS anchor,
+       (l.to_note IS NOT NULL AND (l.anchor = '' OR EXISTS (
+            SELECT 1 FROM note_anchors a WHERE a.note_id = l.to_note AND a.anchor = l.anchor)))
+         AS anchor_found";
+
+fn row_anchor(r: &sqlx::sqlite::SqliteRow) -> (Option<String>, bool) {
+    let anchor: String = r.get("anchor");
+    (
+        Some(anchor).filter(|a| !a.is_empty()),
+        r.get("anchor_found"),
+    )
 }
 
 /// Prefix a canonical column list with a table alias, so a joined query can
 /// reuse `NOTE_COLUMNS` / `HIGHLIGHT_COLUMNS` instead of spelling out a second
 /// copy that can drift from it.
-fn qualified(columns: &str, alias: &str) -> String {
+pub(super) fn qualified(columns: &str, alias: &str) -> String {
     columns
         .split(", ")
         .map(|c| format!("{alias}.{c}"))
@@ -87,6 +134,8 @@ fn row_to_note(r: &sqlx::sqlite::SqliteRow) -> NoteRecord {
 /// Write a note's outgoing edges: each target resolved against existing note
 /// titles, kept as text when dangling (zettelkasten forward references), then
 /// any older dangling link pointing at *this* note's title back-resolved.
+/// `anchors` replace the note's own headings and blocks, the ends of links
+/// into it.
 ///
 /// Shared by the insert and the re-index so the two cannot disagree about what
 /// a link means.
@@ -94,21 +143,28 @@ async fn write_links(
     tx: &mut sqlx::SqliteConnection,
     note_id: i64,
     title: &str,
-    links: &[String],
+    links: &[WikiLink],
+    anchors: &[String],
 ) -> Result<()> {
-    for target in links {
-        let to_note: Option<i64> =
-            sqlx::query_scalar("SELECT id FROM notes WHERE title = ? COLLATE NOCASE LIMIT 1")
-                .bind(target)
-                .fetch_optional(&mut *tx)
-                .await?;
+    for link in links {
+        let to_note: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM notes WHERE title = ? COLLATE NOCASE AND missing_since IS NULL LIMIT 1",
+        )
+        .bind(&link.target)
+        .fetch_optional(&mut *tx)
+        .await?;
+        // `created_at` is left alone on conflict: an edge the body already had
+        // keeps the date it was first written (migration `0012`).
         sqlx::query(
-            r#"INSERT INTO note_links (from_note, to_note, target_title) VALUES (?, ?, ?)
-               ON CONFLICT(from_note, target_title) DO UPDATE SET to_note = excluded.to_note"#,
+            r#"INSERT INTO note_links (from_note, to_note, target_title, anchor, created_at)
+               VALUES (?, ?, ?, ?, ?)
+               ON CONFLICT(from_note, target_title, anchor) DO UPDATE SET to_note = excluded.to_note"#,
         )
         .bind(note_id)
         .bind(to_note)
-        .bind(target)
+        .bind(&link.target)
+        .bind(link.anchor.as_deref().unwrap_or(""))
+        .bind(now_unix())
         .execute(&mut *tx)
         .await?;
     }
@@ -121,20 +177,34 @@ async fn write_links(
     .bind(title)
     .execute(&mut *tx)
     .await?;
+
+    sqlx::query("DELETE FROM note_anchors WHERE note_id = ?")
+        .bind(note_id)
+        .execute(&mut *tx)
+        .await?;
+    for anchor in anchors {
+        sqlx::query("INSERT OR IGNORE INTO note_anchors (note_id, anchor) VALUES (?, ?)")
+            .bind(note_id)
+            .bind(anchor)
+            .execute(&mut *tx)
+            .await?;
+    }
     Ok(())
 }
 
 impl Storage {
     /// Insert note metadata + FTS row + wikilink edges in one transaction.
-    /// `links` are raw [[wikilink]] target titles; each is resolved against
-    /// existing note titles, kept as text when dangling (zettelkasten
+    /// `links` are the body's raw [[wikilinks]]; each target is resolved
+    /// against existing note titles, kept as text when dangling (zettelkasten
     /// forward references). Also back-resolves older dangling links that
-    /// pointed at this note's title.
+    /// pointed at this note's title. `anchors` are the body's headings and
+    /// `^block` ids, from [`crate::notes::extract_anchors`].
     pub async fn insert_note(
         &self,
         meta: NewNoteMeta<'_>,
         body: &str,
-        links: &[String],
+        links: &[WikiLink],
+        anchors: &[String],
     ) -> Result<i64> {
         let NewNoteMeta {
             book_id,
@@ -172,7 +242,7 @@ impl Storage {
             .execute(&mut *tx)
             .await?;
 
-        write_links(&mut tx, note_id, title, links).await?;
+        write_links(&mut tx, note_id, title, links, anchors).await?;
 
         tx.commit().await?;
         Ok(note_id)
@@ -183,19 +253,101 @@ impl Storage {
     /// Without this, a note's edges are whatever its *first* body said for ever
     /// — and a reflection is opened empty and written afterwards, so the hub of
     /// the graph would be the one note with no edges at all.
-    pub async fn set_note_links(&self, note_id: i64, title: &str, links: &[String]) -> Result<()> {
+    pub async fn set_note_links(
+        &self,
+        note_id: i64,
+        title: &str,
+        links: &[WikiLink],
+        anchors: &[String],
+    ) -> Result<()> {
+        let mut tx = self.pool().begin().await?;
+        // A link the user deleted from the body has to leave the graph too —
+        // but only that one. Deleting every edge and rewriting them would
+        // re-date each link to this save, and the statistics count links by
+        // when they were written.
+        let kept: Vec<(&str, &str)> = links
+            .iter()
+            .map(|l| (l.target.as_str(), l.anchor.as_deref().unwrap_or("")))
+            .collect();
+        sqlx::query(
+            "DELETE FROM note_links WHERE from_note = ?
+               AND NOT EXISTS (SELECT 1 FROM json_each(?) j
+                                WHERE json_extract(j.value, '$[0]') = target_title
+                                  AND json_extract(j.value, '$[1]') = anchor)",
+        )
+        .bind(note_id)
+        .bind(serde_json::to_string(&kept)?)
+        .execute(&mut *tx)
+        .await?;
+        write_links(&mut tx, note_id, title, links, anchors).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Give a note a new title and file, with everything that follows from it
+    /// in the same transaction: the edges pointing at it take the new
+    /// `target_title` — keeping the dates they were written — links already
+    /// waiting for that title resolve to it, and `bodies`, `(note_id, title,
+    /// body)` for each file the rename rewrote, are re-indexed.
+    ///
+    /// `OR REPLACE` because a note can hold both `[[Old]]` and a dangling
+    /// `[[New]]`; after the rename they are one edge, and the old one is the
+    /// one that keeps its date.
+    pub async fn apply_note_rename(
+        &self,
+        note_id: i64,
+        title: &str,
+        file_path: &str,
+        bodies: &[(i64, &str, &str)],
+    ) -> Result<()> {
         let mut tx = self.pool().begin().await?;
-        // Replaced, not merged: a link the user deleted from the body has to
-        // leave the graph too.
-        sqlx::query("DELETE FROM note_links WHERE from_note = ?")
+        sqlx::query("UPDATE notes SET title = ?, file_path = ?, last_modified = ? WHERE id = ?")
+            .bind(title)
+            .bind(file_path)
+            .bind(now_unix())
             .bind(note_id)
             .execute(&mut *tx)
             .await?;
-        write_links(&mut tx, note_id, title, links).await?;
+        sqlx::query("UPDATE OR REPLACE note_links SET target_title = ? WHERE to_note = ?")
+            .bind(title)
+            .bind(note_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "UPDATE note_links SET to_note = ? WHERE to_note IS NULL AND target_title = ? COLLATE NOCASE",
+        )
+        .bind(note_id)
+        .bind(title)
+        .execute(&mut *tx)
+        .await?;
+        for (id, title, body) in bodies {
+            sqlx::query("DELETE FROM notes_fts WHERE rowid = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("INSERT INTO notes_fts (rowid, title, body) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(title)
+                .bind(body)
+                .execute(&mut *tx)
+                .await?;
+        }
         tx.commit().await?;
         Ok(())
     }
 
+    /// The live note with this title, if there is one — `COLLATE NOCASE`, the
+    /// same comparison a wikilink resolves by.
+    pub async fn note_titled(&self, title: &str) -> Result<Option<i64>> {
+        Ok(sqlx::query_scalar(
+            "SELECT id FROM notes WHERE title = ? COLLATE NOCASE AND missing_since IS NULL
+              ORDER BY id LIMIT 1",
+        )
+        .bind(title)
+        .fetch_optional(self.pool())
+        .await?)
+    }
+
     /// Refresh a note body in the FTS index (delete + insert).
     pub async fn refresh_note_body(&self, note_id: i64, title: &str, body: &str) -> Result<()> {
         let mut tx = self.pool().begin().await?;
@@ -239,12 +391,16 @@ impl Storage {
         let rows = match book_id {
             Some(id) => {
                 let sql = format!(
-                    "SELECT {NOTE_COLUMNS} FROM notes WHERE book_id = ? ORDER BY created_at DESC"
+                    "SELECT {NOTE_COLUMNS} FROM notes WHERE book_id = ? AND missing_since IS NULL
+                     ORDER BY created_at DESC"
                 );
                 sqlx::query(&sql).bind(id).fetch_all(self.pool()).await?
             }
             None => {
-                let sql = format!("SELECT {NOTE_COLUMNS} FROM notes ORDER BY created_at DESC");
+                let sql = format!(
+                    "SELECT {NOTE_COLUMNS} FROM notes WHERE missing_since IS NULL
+                     ORDER BY created_at DESC"
+                );
                 sqlx::query(&sql).fetch_all(self.pool()).await?
             }
         };
@@ -283,7 +439,8 @@ impl Storage {
         let rows = sqlx::query(
             r#"SELECT n.id, n.book_id, n.reading_id, n.highlight_id, n.page, n.location,
                       n.file_path, n.title, n.kind, n.created_at,
-                      snippet(notes_fts, 1, '>>', '<<', '…', 12) AS snip
+                      snippet(notes_fts, 1, '>>', '<<', '…', 12) AS snip,
+                      notes_fts.rank AS rank
                FROM notes_fts
                JOIN notes n ON n.id = notes_fts.rowid
                WHERE notes_fts MATCH ?
@@ -298,6 +455,7 @@ impl Storage {
             .map(|r| NoteSearchHit {
                 note: row_to_note(r),
                 snippet: r.get("snip"),
+                rank: r.get("rank"),
             })
             .collect())
     }
@@ -344,16 +502,20 @@ impl Storage {
         Ok(rows.iter().map(row_to_highlight).collect())
     }
 
-    /// Outgoing links of a note: (target_title, resolved note id if any).
+    /// Outgoing links of a note: (target_title, resolved note id if any), one
+    /// per note linked, whichever sections of it.
     ///
     /// The cheap half of [`Storage::outgoing_links`], kept because
     /// `open_anchored` only ever wants the titles a body wrote and has no use
     /// for the target rows.
     pub async fn note_links(&self, note_id: i64) -> Result<Vec<(String, Option<i64>)>> {
-        let rows = sqlx::query("SELECT target_title, to_note FROM note_links WHERE from_note = ?")
-            .bind(note_id)
-            .fetch_all(self.pool())
-            .await?;
+        let rows = sqlx::query(
+            "SELECT target_title, to_note FROM note_links WHERE from_note = ?
+              GROUP BY target_title ORDER BY min(rowid)",
+        )
+        .bind(note_id)
+        .fetch_all(self.pool())
+        .await?;
         Ok(rows
             .into_iter()
             .map(|r| (r.get("target_title"), r.get("to_note")))
@@ -369,7 +531,7 @@ impl Storage {
     pub async fn outgoing_links(&self, note_id: i64) -> Result<Vec<OutgoingLink>> {
         let columns = qualified(NOTE_COLUMNS, "n");
         let sql = format!(
-            "SELECT l.target_title AS target_title, {columns}
+            "SELECT l.target_title AS target_title, {ANCHOR_COLUMNS}, {columns}
              FROM note_links l LEFT JOIN notes n ON n.id = l.to_note
              WHERE l.from_note = ? ORDER BY l.rowid"
         );
@@ -379,13 +541,18 @@ impl Storage {
             .await?;
         Ok(rows
             .iter()
-            .map(|r| OutgoingLink {
-                target_title: r.get("target_title"),
-                // The join missed, so every other `n.` column is NULL and
-                // `row_to_note` would fail decoding them. Reading `id` as an
-                // `Option` first is what keeps a dangling target a value rather
-                // than an error.
-                to: r.get::<Option<i64>, _>("id").map(|_| row_to_note(r)),
+            .map(|r| {
+                let (anchor, anchor_found) = row_anchor(r);
+                OutgoingLink {
+                    target_title: r.get("target_title"),
+                    anchor,
+                    // The join missed, so every other `n.` column is NULL and
+                    // `row_to_note` would fail decoding them. Reading `id` as an
+                    // `Option` first is what keeps a dangling target a value
+                    // rather than an error.
+                    to: r.get::<Option<i64>, _>("id").map(|_| row_to_note(r)),
+                    anchor_found,
+                }
             })
             .collect())
     }
@@ -406,24 +573,198 @@ impl Storage {
     ///
     /// The second reason is the one that survives the exception. `notes.title`
     /// is not unique, so an edge that resolved to one of two same-titled notes
-    /// dangles again if *that* one is deleted, and nothing re-resolves it toward
-    /// the survivor. Both sides then agree it dangles, which is worth more than
-    /// one side guessing —
+    /// dangles again if *that* one is deleted, and nothing on the write path
+    /// re-resolves it toward the survivor — only the vault watcher's
+    /// [`Storage::resolve_dangling_links`] does. Both sides then agree it
+    /// dangles, which is worth more than one side guessing —
     /// `a_title_shared_by_two_notes_is_where_back_resolution_stops`.
     ///
+    /// One row per edge, so a note linking two sections of this one appears
+    /// twice — each row is somewhere different to jump to. A link into a
+    /// section this note no longer has is still listed, with `anchor_found`
+    /// false: it is the section that dangles, and the note does link here.
+    ///
     /// Newest first, as `list_notes` orders — with `id` breaking the tie, since
-    /// `created_at` is only second-resolution.
-    pub async fn backlinks(&self, note_id: i64) -> Result<Vec<NoteRecord>> {
+    /// `created_at` is only second-resolution — and a note's links in the
+    /// order its body wrote them.
+    pub async fn backlinks(&self, note_id: i64) -> Result<Vec<Backlink>> {
         let columns = qualified(NOTE_COLUMNS, "n");
         let sql = format!(
-            "SELECT {columns} FROM note_links l JOIN notes n ON n.id = l.from_note
-             WHERE l.to_note = ? ORDER BY n.created_at DESC, n.id DESC"
+            "SELECT {ANCHOR_COLUMNS}, {columns} FROM note_links l JOIN notes n ON n.id = l.from_note
+             WHERE l.to_note = ? AND n.missing_since IS NULL
+             ORDER BY n.created_at DESC, n.id DESC, l.rowid"
         );
         let rows = sqlx::query(&sql)
             .bind(note_id)
             .fetch_all(self.pool())
             .await?;
-        Ok(rows.iter().map(row_to_note).collect())
+        Ok(rows
+            .iter()
+            .map(|r| {
+                let (anchor, anchor_found) = row_anchor(r);
+                Backlink {
+                    from: row_to_note(r),
+                    anchor,
+                    anchor_found,
+                }
+            })
+            .collect())
+    }
+
+    // ---- the vault, changed underneath us (migration `0016`) --------------
+
+    /// The note indexed at this vault-relative path, live or tombstoned, as
+    /// `(id, tombstoned)`. `file_path` is unique, so this is at most one row.
+    pub async fn note_at_path(&self, file_path: &str) -> Result<Option<(i64, bool)>> {
+        let row = sqlx::query("SELECT id, missing_since FROM notes WHERE file_path = ?")
+            .bind(file_path)
+            .fetch_optional(self.pool())
+            .await?;
+        Ok(row.map(|r| {
+            (
+                r.get("id"),
+                r.get::<Option<i64>, _>("missing_since").is_some(),
+            )
+        }))
+    }
+
+    /// The live notes filed anywhere under a vault-relative directory. What a
+    /// folder deleted or moved in Obsidian took with it — the platform reports
+    /// the folder, not the files inside it.
+    pub async fn note_paths_under(&self, dir: &str) -> Result<Vec<String>> {
+        let prefix = format!("{}/", dir.trim_end_matches('/'));
+        // `substr` rather than `LIKE`: a folder name may hold `%` or `_`.
+        let paths = sqlx::query_scalar(
+            "SELECT file_path FROM notes
+              WHERE missing_since IS NULL AND substr(file_path, 1, length(?1)) = ?1
+              ORDER BY file_path",
+        )
+        .bind(prefix)
+        .fetch_all(self.pool())
+        .await?;
+        Ok(paths)
+    }
+
+    /// The body as the search index last saw it — how the watcher tells an
+    /// outside edit from the echo of one of our own saves.
+    pub async fn indexed_note_body(&self, note_id: i64) -> Result<Option<String>> {
+        let body = sqlx::query_scalar("SELECT body FROM notes_fts WHERE rowid = ?")
+            .bind(note_id)
+            .fetch_optional(self.pool())
+            .await?;
+        Ok(body)
+    }
+
+    /// Take a note out of the index because its file left the vault. Returns
+    /// false when it was already out.
+    ///
+    /// Links *to* it dangle again rather than keep pointing at a file that is
+    /// not there; its own edges stay for the day it comes back.
+    pub async fn tombstone_note(&self, note_id: i64) -> Result<bool> {
+        let mut tx = self.pool().begin().await?;
+        let done = sqlx::query(
+            "UPDATE notes SET missing_since = ? WHERE id = ? AND missing_since IS NULL",
+        )
+        .bind(now_unix())
+        .bind(note_id)
+        .execute(&mut *tx)
+        .await?;
+        if done.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query("DELETE FROM notes_fts WHERE rowid = ?")
+            .bind(note_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE note_links SET to_note = NULL WHERE to_note = ?")
+            .bind(note_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// A tombstoned note created at this instant, if there is exactly one — the
+    /// other half of a move. Our frontmatter's `created:` is written once and
+    /// survives a rename, which makes it the one identity a moved file carries.
+    ///
+    /// Within a second either way: `create_note` stamps the frontmatter and
+    /// `insert_note` the row, and a second can turn over between the two.
+    pub async fn tombstone_created_at(&self, created_at: i64) -> Result<Option<i64>> {
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM notes
+              WHERE missing_since IS NOT NULL AND abs(created_at - ?) <= 1 LIMIT 2",
+        )
+        .bind(created_at)
+        .fetch_all(self.pool())
+        .await?;
+        // Two tombstones from the same second cannot be told apart, and guessing
+        // would hand one note's citations to the other.
+        Ok(match ids.as_slice() {
+            [id] => Some(*id),
+            _ => None,
+        })
+    }
+
+    /// Bring a tombstoned note back at `file_path`. The caller re-reads the file
+    /// straight after, which is what puts it back in the search index and lets
+    /// `write_links` re-resolve the links that dangled while it was away.
+    pub async fn revive_note(&self, note_id: i64, file_path: &str) -> Result<()> {
+        sqlx::query("UPDATE notes SET missing_since = NULL, file_path = ? WHERE id = ?")
+            .bind(file_path)
+            .bind(note_id)
+            .execute(self.pool())
+            .await?;
+        Ok(())
+    }
+
+    /// Point every dangling edge at a live note of its title, where one exists.
+    /// Returns how many edges now resolve.
+    ///
+    /// `write_links` only back-resolves toward the note being written. A file
+    /// tombstoned out from under an edge leaves it dangling even when a second
+    /// note of the same title is still there, and this is the pass that finds
+    /// the survivor — run by the vault watcher after each batch. The oldest
+    /// note wins a shared title, so the answer is the same every time.
+    pub async fn resolve_dangling_links(&self) -> Result<u64> {
+        let done = sqlx::query(
+            "UPDATE note_links SET to_note = (
+                 SELECT n.id FROM notes n
+                  WHERE n.title = note_links.target_title COLLATE NOCASE
+                    AND n.missing_since IS NULL
+                  ORDER BY n.id LIMIT 1)
+              WHERE to_note IS NULL
+                AND EXISTS (SELECT 1 FROM notes n
+                             WHERE n.title = note_links.target_title COLLATE NOCASE
+                               AND n.missing_since IS NULL)",
+        )
+        .execute(self.pool())
+        .await?;
+        Ok(done.rows_affected())
+    }
+
+    /// Re-date a note rebuilt from the vault to the `created:` its frontmatter
+    /// says, and its edges with it.
+    ///
+    /// `insert_note` stamps both with the moment of the rebuild, which would
+    /// put a whole vault's writing into the one day the database was lost.
+    /// The edges take the note's date for the reason migration `0012` gave
+    /// when it back-filled them: the earliest they can have been written, and
+    /// the only date there is.
+    pub async fn restore_note_created(&self, note_id: i64, created_at: i64) -> Result<()> {
+        let mut tx = self.pool().begin().await?;
+        sqlx::query("UPDATE notes SET created_at = ?1 WHERE id = ?2")
+            .bind(created_at)
+            .bind(note_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE note_links SET created_at = ?1 WHERE from_note = ?2")
+            .bind(created_at)
+            .bind(note_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
     }
 }
 
@@ -449,7 +790,8 @@ mod tests {
                     kind: "note",
                 },
                 "Reminds me of [[Han]] as a concept.",
-                &["Han".to_string()],
+                &crate::notes::extract_links("[[Han]]"),
+                &[],
             )
             .await
             .unwrap();
@@ -471,6 +813,7 @@ mod tests {
                 },
                 "Korean concept of grief.",
                 &[],
+                &[],
             )
             .await
             .unwrap();
@@ -490,4 +833,56 @@ mod tests {
         assert!(s.search_notes("grief", 10).await.unwrap().is_empty());
         assert_eq!(s.search_notes("resilience", 10).await.unwrap().len(), 1);
     }
+
+    /// Re-indexing a body keeps the date of every link it still writes, and
+    /// drops the links it no longer does.
+    #[tokio::test]
+    async fn a_rewritten_body_keeps_its_surviving_links_dates() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let n = s
+            .insert_note(
+                NewNoteMeta {
+                    book_id: None,
+                    reading_id: None,
+                    highlight_id: None,
+                    page: None,
+                    location: None,
+                    file_path: "unsorted/a.md",
+                    title: "First thought",
+                    kind: "note",
+                },
+                "[[Han]] and [[Jeong]]",
+                &crate::notes::extract_links("[[Han]] [[Jeong]]"),
+                &[],
+            )
+            .await
+            .unwrap();
+        sqlx::query("UPDATE note_links SET created_at = 1000 WHERE from_note = ?")
+            .bind(n)
+            .execute(s.pool())
+            .await
+            .unwrap();
+
+        s.set_note_links(
+            n,
+            "First thought",
+            &crate::notes::extract_links("[[Han]] [[Nunchi]]"),
+            &[],
+        )
+        .await
+        .unwrap();
+
+        let dated: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT target_title, created_at FROM note_links WHERE from_note = ?
+             ORDER BY target_title",
+        )
+        .bind(n)
+        .fetch_all(s.pool())
+        .await
+        .unwrap();
+        assert_eq!(dated.len(), 2, "Jeong left the body, so it left the graph");
+        assert_eq!(dated[0], ("Han".to_string(), 1000));
+        assert_eq!(dated[1].0, "Nunchi");
+        assert!(dated[1].1 > 1000, "a new link is dated now");
+    }
 }
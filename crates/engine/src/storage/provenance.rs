@@ -2,10 +2,11 @@
 //! it filed it under.
 //!
 //! Both tables (migration `0009`) are **inert provenance**. Nothing reads
-//! `book_tags` to decide anything, there is no UI for it, and there are no merge
-//! semantics — `docs/decisions.md` defers collections outright, because three
-//! systems minting them is a merge problem with no good default. Recording the
-//! raw value now is what lets that design be made later against real shelves.
+//! `book_tags` to decide anything — a library listing can be filtered by one,
+//! which is looking rather than deciding — and there are no merge semantics:
+//! three systems minting collections is a merge problem with no good default,
+//! so none is attempted. Shelves (`shelves.rs`) are ours; a tag becomes one
+//! only when the user promotes it, as a copy, and these rows stay as they were.
 //!
 //! `external_ids` is the working half: it is what makes re-importing the same
 //! Goodreads CSV find the same books instead of creating a second copy of every
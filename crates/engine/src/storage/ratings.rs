@@ -533,6 +533,7 @@ mod tests {
             },
             "body",
             &[],
+            &[],
         )
         .await
         .unwrap()
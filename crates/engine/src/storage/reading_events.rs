@@ -0,0 +1,353 @@
+//! The per-day activity log (`reading_events`, migration `0011`).
+//!
+//! One row per book, per day, per source. Every filler writes here in this
+//! shape and nothing downstream reads a source in its own — see the migration's
+//! header for why the log is source-agnostic, and why `minutes`/`pages` are
+//! nullable rather than zero.
+
+use sqlx::Row;
+use sqlx::sqlite::SqliteRow;
+
+use super::readings::READING_WINDOWS;
+use super::{Storage, now_unix};
+use crate::error::Result;
+
+/// Read off an instrument: the device counted the seconds.
+pub const CONFIDENCE_MEASURED: &str = "measured";
+/// Deduced from something else — a highlight's timestamp says you were in the
+/// book that day, not for how long.
+pub const CONFIDENCE_INFERRED: &str = "inferred";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadingEvent {
+    pub id: i64,
+    pub book_id: i64,
+    /// The reading the day falls in, attributed the way highlights are. `None`
+    /// when the book has no reading whose window covers the day.
+    pub reading_id: Option<i64>,
+    /// `YYYY-MM-DD`.
+    pub day: String,
+    /// `None` is "this source does not know", never zero.
+    pub minutes: Option<i64>,
+    pub pages: Option<i64>,
+    pub source: String,
+    /// Which instance of the source wrote the row — for KOReader, the device
+    /// file's `partial_md5`. `""` for a source that has only one.
+    pub source_key: String,
+    pub confidence: String,
+    pub created_at: i64,
+    pub last_modified: i64,
+}
+
+/// One day's worth of activity, as a filler reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewReadingEvent<'a> {
+    pub book_id: i64,
+    pub day: &'a str,
+    pub minutes: Option<i64>,
+    pub pages: Option<i64>,
+    pub source: &'a str,
+    pub source_key: &'a str,
+    pub confidence: &'a str,
+}
+
+const EVENT_COLUMNS: &str = "id, book_id, reading_id, day, minutes, pages, source, source_key, \
+     confidence, created_at, last_modified";
+
+fn row_to_event(row: &SqliteRow) -> Result<ReadingEvent> {
+    Ok(ReadingEvent {
+        id: row.try_get("id")?,
+        book_id: row.try_get("book_id")?,
+        reading_id: row.try_get("reading_id")?,
+        day: row.try_get("day")?,
+        minutes: row.try_get("minutes")?,
+        pages: row.try_get("pages")?,
+        source: row.try_get("source")?,
+        source_key: row.try_get("source_key")?,
+        confidence: row.try_get("confidence")?,
+        created_at: row.try_get("created_at")?,
+        last_modified: row.try_get("last_modified")?,
+    })
+}
+
+impl Storage {
+    /// The row a filler would write this event over, if there is one.
+    ///
+    /// Separate from [`Storage::put_reading_event`] so a dry run can classify
+    /// a day as new, changed or already known without writing anything.
+    pub async fn find_reading_event(
+        &self,
+        ev: &NewReadingEvent<'_>,
+    ) -> Result<Option<ReadingEvent>> {
+        let sql = format!(
+            "SELECT {EVENT_COLUMNS} FROM reading_events
+             WHERE book_id = ? AND day = ? AND source = ? AND source_key = ?"
+        );
+        let row = sqlx::query(&sql)
+            .bind(ev.book_id)
+            .bind(ev.day)
+            .bind(ev.source)
+            .bind(ev.source_key)
+            .fetch_optional(self.pool())
+            .await?;
+        row.as_ref().map(row_to_event).transpose()
+    }
+
+    /// Insert the day, or overwrite it.
+    ///
+    /// **Straight assignment**, like every device-owned field: a filler
+    /// recomputes a day from its whole source on every import, so what it
+    /// reports is the complete state of that day, not a delta to add. Summing
+    /// here would double a day each time the device was plugged in.
+    pub async fn put_reading_event(&self, ev: &NewReadingEvent<'_>) -> Result<()> {
+        let now = now_unix();
+        sqlx::query(
+            r#"INSERT INTO reading_events
+                   (book_id, day, minutes, pages, source, source_key, confidence,
+                    created_at, last_modified)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+               ON CONFLICT(book_id, day, source, source_key) DO UPDATE SET
+                   minutes       = excluded.minutes,
+                   pages         = excluded.pages,
+                   confidence    = excluded.confidence,
+                   last_modified = excluded.last_modified"#,
+        )
+        .bind(ev.book_id)
+        .bind(ev.day)
+        .bind(ev.minutes)
+        .bind(ev.pages)
+        .bind(ev.source)
+        .bind(ev.source_key)
+        .bind(ev.confidence)
+        .bind(now)
+        .bind(now)
+        .execute(self.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// Drop one source instance's rows from every book but `book_id`.
+    ///
+    /// A device file can be re-linked to a different book (`ko link` repoints
+    /// a bad automatic match). Its statistics follow it: the days it wrote under
+    /// the old book were never that book's, and leaving them would count the
+    /// same minutes twice, once on each.
+    pub async fn drop_reading_events_elsewhere(
+        &self,
+        source: &str,
+        source_key: &str,
+        book_id: i64,
+    ) -> Result<usize> {
+        Ok(sqlx::query(
+            "DELETE FROM reading_events WHERE source = ? AND source_key = ? AND book_id <> ?",
+        )
+        .bind(source)
+        .bind(source_key)
+        .bind(book_id)
+        .execute(self.pool())
+        .await?
+        .rows_affected() as usize)
+    }
+
+    /// A book's activity, oldest day first.
+    pub async fn list_reading_events(&self, book_id: i64) -> Result<Vec<ReadingEvent>> {
+        let sql = format!(
+            "SELECT {EVENT_COLUMNS} FROM reading_events WHERE book_id = ?
+             ORDER BY day, source, source_key"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(book_id)
+            .fetch_all(self.pool())
+            .await?;
+        rows.iter().map(row_to_event).collect()
+    }
+
+    /// Point each of a book's days at the reading it falls in.
+    ///
+    /// The windows are [`Storage::attribute_highlights`]'s, shared rather than
+    /// re-derived — a day of reading and a highlight made that day landing in
+    /// different readings would be a contradiction on the book screen. A day is
+    /// a span rather than an instant, so it belongs to a window it *overlaps*:
+    /// a reading started at ten in the morning owns that morning's minutes, and
+    /// the later window still wins a tie, as it does for highlights.
+    ///
+    /// The day is the device's *local* date (`koreader_stats` reads it with
+    /// `'localtime'`), so its bounds are that date's local midnights — `'utc'`
+    /// turns each into the instant a window is measured in. Read as UTC, an
+    /// evening start west of Greenwich would miss the day it began on.
+    pub async fn attribute_reading_events(&self, book_id: i64) -> Result<usize> {
+        let sql = format!(
+            r#"WITH {READING_WINDOWS}
+               UPDATE reading_events SET reading_id = (
+                   SELECT w.reading_id FROM windows w
+                    WHERE w.win_start <= CAST(strftime('%s', reading_events.day, '+1 day', 'utc') AS INTEGER) - 1
+                      AND w.win_end >= CAST(strftime('%s', reading_events.day, 'utc') AS INTEGER)
+                    ORDER BY w.win_start DESC, w.reading_id DESC
+                    LIMIT 1)
+               WHERE book_id = ?1"#
+        );
+        sqlx::query(&sql).bind(book_id).execute(self.pool()).await?;
+
+        let n: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM reading_events WHERE book_id = ? AND reading_id IS NOT NULL",
+        )
+        .bind(book_id)
+        .fetch_one(self.pool())
+        .await?;
+        Ok(n as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::Book;
+
+    async fn seeded() -> (Storage, i64) {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let id = s
+            .upsert_book(&Book {
+                title: Some("Pachinko".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        (s, id)
+    }
+
+    fn ko<'a>(book_id: i64, day: &'a str, minutes: i64, key: &'a str) -> NewReadingEvent<'a> {
+        NewReadingEvent {
+            book_id,
+            day,
+            minutes: Some(minutes),
+            pages: Some(minutes / 2),
+            source: "koreader",
+            source_key: key,
+            confidence: CONFIDENCE_MEASURED,
+        }
+    }
+
+    /// A local time as unix seconds, by SQLite's reckoning of the zone — the
+    /// one attribution uses.
+    async fn local(s: &Storage, at: &str) -> i64 {
+        sqlx::query_scalar("SELECT CAST(strftime('%s', ?, 'utc') AS INTEGER)")
+            .bind(at)
+            .fetch_one(s.pool())
+            .await
+            .unwrap()
+    }
+
+    /// Re-importing a day replaces it. The device recomputes the day from its
+    /// whole log, so adding would double it on every plug-in.
+    #[tokio::test]
+    async fn a_day_written_twice_is_replaced_not_summed() {
+        let (s, book) = seeded().await;
+        s.put_reading_event(&ko(book, "2026-03-01", 20, "aaa"))
+            .await
+            .unwrap();
+        s.put_reading_event(&ko(book, "2026-03-01", 35, "aaa"))
+            .await
+            .unwrap();
+
+        let events = s.list_reading_events(book).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].minutes, Some(35));
+    }
+
+    /// Two device files of one book each keep their own day: the key is the
+    /// source instance, not just the book.
+    #[tokio::test]
+    async fn two_device_files_of_one_book_stand_side_by_side() {
+        let (s, book) = seeded().await;
+        s.put_reading_event(&ko(book, "2026-03-01", 20, "aaa"))
+            .await
+            .unwrap();
+        s.put_reading_event(&ko(book, "2026-03-01", 10, "bbb"))
+            .await
+            .unwrap();
+
+        let total: i64 = s
+            .list_reading_events(book)
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|e| e.minutes)
+            .sum();
+        assert_eq!(total, 30);
+    }
+
+    /// A device file re-linked elsewhere takes its days with it.
+    #[tokio::test]
+    async fn relinking_a_device_file_drops_its_days_from_the_old_book() {
+        let (s, old) = seeded().await;
+        let new = s
+            .upsert_book(&Book {
+                title: Some("Free Food for Millionaires".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        s.put_reading_event(&ko(old, "2026-03-01", 20, "aaa"))
+            .await
+            .unwrap();
+        s.put_reading_event(&ko(old, "2026-03-01", 15, "bbb"))
+            .await
+            .unwrap();
+
+        let dropped = s
+            .drop_reading_events_elsewhere("koreader", "aaa", new)
+            .await
+            .unwrap();
+        assert_eq!(dropped, 1);
+        let left = s.list_reading_events(old).await.unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(
+            left[0].source_key, "bbb",
+            "another file's days are not touched"
+        );
+    }
+
+    /// A day overlapping two readings goes to the later one; a day before any
+    /// reading began goes to none.
+    #[tokio::test]
+    async fn days_are_attributed_to_the_reading_they_overlap() {
+        let (s, book) = seeded().await;
+        // Days are local, so the instants are too, whatever zone this runs in:
+        // 2026-03-01 to noon on the 10th, then from six that evening.
+        let (start, end, again) = (
+            local(&s, "2026-03-01 00:00").await,
+            local(&s, "2026-03-10 12:00").await,
+            local(&s, "2026-03-10 18:00").await,
+        );
+        let first = s
+            .record_reading(book, Some(start), Some(end), "finished", "manual")
+            .await
+            .unwrap();
+        let second = s
+            .record_reading(book, Some(again), None, "reading", "manual")
+            .await
+            .unwrap();
+        for day in ["2026-02-20", "2026-03-05", "2026-03-10", "2026-03-20"] {
+            s.put_reading_event(&ko(book, day, 5, "aaa")).await.unwrap();
+        }
+
+        let n = s.attribute_reading_events(book).await.unwrap();
+        assert_eq!(n, 3);
+        let by_day: Vec<(String, Option<i64>)> = s
+            .list_reading_events(book)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.day, e.reading_id))
+            .collect();
+        assert_eq!(
+            by_day,
+            vec![
+                ("2026-02-20".to_string(), None),
+                ("2026-03-05".to_string(), Some(first)),
+                ("2026-03-10".to_string(), Some(second)),
+                ("2026-03-20".to_string(), Some(second)),
+            ]
+        );
+    }
+}
@@ -41,16 +41,22 @@ pub struct Reading {
     pub ko_status: Option<String>,
     pub ko_percent: Option<f64>,
     pub ko_rating: Option<i64>,
+    /// When the device last had the book open, unix seconds. Only a reader that
+    /// keeps it says so — a stock Kobo does, a KOReader sidecar does not.
+    pub ko_last_read: Option<i64>,
+    /// The paired reader whose report the device mirror above is, if the last
+    /// one came over the network. Moves with the mirror.
+    pub device_id: Option<i64>,
     pub created_at: i64,
     pub last_modified: i64,
 }
 
 pub(super) const READING_COLUMNS: &str = "id, book_id, started_at, finished_at, status, source, current_page, \
-     ko_status, ko_percent, ko_rating, created_at, last_modified";
+     ko_status, ko_percent, ko_rating, ko_last_read, device_id, created_at, last_modified";
 
 /// What [`Storage::list_open_readings`] renames the reading's columns to.
 ///
-/// Six of the twelve — `id`, `book_id`, `current_page`, `status`, `created_at`,
+/// Six of the fourteen — `id`, `book_id`, `current_page`, `status`, `created_at`,
 /// `last_modified` — are names `BOOK_COLUMNS` also projects, so joining the two
 /// into one row means renaming one side of the collision.
 const JOINED_READING_PREFIX: &str = "r_";
@@ -91,6 +97,8 @@ fn row_to_reading_prefixed(row: &SqliteRow, prefix: &str) -> Result<Reading> {
         ko_status: row.try_get(col("ko_status").as_str())?,
         ko_percent: row.try_get(col("ko_percent").as_str())?,
         ko_rating: row.try_get(col("ko_rating").as_str())?,
+        ko_last_read: row.try_get(col("ko_last_read").as_str())?,
+        device_id: row.try_get(col("device_id").as_str())?,
         created_at: row.try_get(col("created_at").as_str())?,
         last_modified: row.try_get(col("last_modified").as_str())?,
     })
@@ -112,6 +120,29 @@ fn row_to_reading_prefixed(row: &SqliteRow, prefix: &str) -> Result<Reading> {
 const DEVICE_STATE_DIFFER: &str =
     "(ko_status IS NOT ?2 OR ko_percent IS NOT ?3 OR ko_rating IS NOT ?4)";
 
+/// Each reading of book `?1` as a `[win_start, win_end]` span of unix seconds,
+/// as a CTE named `windows`.
+///
+/// The derivation is documented on [`Storage::attribute_highlights`]. It is a
+/// constant because reading events are attributed against the same windows,
+/// and two definitions of "which reading was this" would drift.
+pub(super) const READING_WINDOWS: &str = r#"windows AS (
+       SELECT r.id AS reading_id,
+              COALESCE(
+                  r.started_at,
+                  (SELECT MAX(p.finished_at) + 1 FROM readings p
+                    WHERE p.book_id = r.book_id
+                      AND p.id <> r.id
+                      AND p.finished_at IS NOT NULL
+                      AND p.finished_at <=
+                          COALESCE(r.finished_at, 8640000000000)),
+                  -8640000000000
+              ) AS win_start,
+              COALESCE(r.finished_at, 8640000000000) AS win_end
+         FROM readings r
+        WHERE r.book_id = ?1
+   )"#;
+
 /// KOReader's `datetime` as unix seconds.
 ///
 /// The device writes `YYYY-MM-DD HH:MM:SS` with no zone, so it is read as UTC —
@@ -521,6 +552,44 @@ impl Storage {
         Ok(done.rows_affected() > 0)
     }
 
+    /// The device rating on the current reading — the one
+    /// [`Storage::set_device_state`] would overwrite. For a source that has no
+    /// rating of its own to mirror and must not clear another device's.
+    pub async fn device_rating(&self, book_id: i64) -> Result<Option<i64>> {
+        let rating: Option<Option<i64>> = sqlx::query_scalar(
+            "SELECT ko_rating FROM readings WHERE book_id = ?
+             ORDER BY (finished_at IS NULL) DESC, COALESCE(started_at, created_at) DESC, id DESC
+             LIMIT 1",
+        )
+        .bind(book_id)
+        .fetch_optional(self.pool())
+        .await?;
+        Ok(rating.flatten())
+    }
+
+    /// Mirror the device's last-read time onto the current reading. Returns
+    /// whether it changed.
+    ///
+    /// Separate from [`Storage::set_device_state`] because only some readers
+    /// keep it: folding it in would have every KOReader import assign NULL
+    /// over a time a Kobo reported for the same book. Straight assignment
+    /// otherwise, for the reason given there.
+    pub async fn set_device_last_read(&self, book_id: i64, at: Option<i64>) -> Result<bool> {
+        let done = sqlx::query(
+            "UPDATE readings SET ko_last_read = ?2, last_modified = ?3
+             WHERE id = (SELECT id FROM readings WHERE book_id = ?1
+                         ORDER BY (finished_at IS NULL) DESC,
+                                  COALESCE(started_at, created_at) DESC, id DESC LIMIT 1)
+               AND ko_last_read IS NOT ?2",
+        )
+        .bind(book_id)
+        .bind(at)
+        .bind(now_unix())
+        .execute(self.pool())
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
     /// Would [`Storage::set_device_state`] change anything? Read-only, for a
     /// dry-run preview.
     pub async fn device_state_differs(
@@ -598,34 +667,17 @@ impl Storage {
     /// two readings explicitly overlapping dates, and the later window is the
     /// better guess for a highlight that falls in both.
     pub async fn attribute_highlights(&self, book_id: i64) -> Result<usize> {
-        sqlx::query(
-            r#"WITH windows AS (
-                   SELECT r.id AS reading_id,
-                          COALESCE(
-                              r.started_at,
-                              (SELECT MAX(p.finished_at) + 1 FROM readings p
-                                WHERE p.book_id = r.book_id
-                                  AND p.id <> r.id
-                                  AND p.finished_at IS NOT NULL
-                                  AND p.finished_at <=
-                                      COALESCE(r.finished_at, 8640000000000)),
-                              -8640000000000
-                          ) AS win_start,
-                          COALESCE(r.finished_at, 8640000000000) AS win_end
-                     FROM readings r
-                    WHERE r.book_id = ?1
-               )
+        let sql = format!(
+            r#"WITH {READING_WINDOWS}
                UPDATE highlights SET reading_id = (
                    SELECT w.reading_id FROM windows w
                     WHERE CAST(strftime('%s', highlights.ko_datetime) AS INTEGER)
                           BETWEEN w.win_start AND w.win_end
                     ORDER BY w.win_start DESC, w.reading_id DESC
                     LIMIT 1)
-               WHERE book_id = ?1"#,
-        )
-        .bind(book_id)
-        .execute(self.pool())
-        .await?;
+               WHERE book_id = ?1"#
+        );
+        sqlx::query(&sql).bind(book_id).execute(self.pool()).await?;
 
         let n: i64 = sqlx::query_scalar(
             "SELECT count(*) FROM highlights WHERE book_id = ? AND reading_id IS NOT NULL",
@@ -0,0 +1,250 @@
+//! One search over everything the reader wrote or marked: notes and highlights
+//! together.
+//!
+//! The two indexes stay separate — `notes_fts` is fed by the notes engine from
+//! files on disk, `highlights_fts` by triggers on `highlights` — and are merged
+//! here, by rank. bm25 is computed per index, against that index's own term
+//! statistics, so a note and a highlight with the same rank are not *exactly*
+//! equally good answers. They are close enough to interleave, and interleaving
+//! is the point: a frontend that showed every note and then every highlight
+//! would bury the best passage under the weakest note.
+
+use super::{HighlightSearchHit, NoteSearchHit, Storage};
+use crate::error::{EngineError, Result};
+
+/// One result of [`Storage::search_library`].
+#[derive(Debug, Clone)]
+pub enum LibraryHit {
+    Note(NoteSearchHit),
+    Highlight(HighlightSearchHit),
+}
+
+impl LibraryHit {
+    pub fn rank(&self) -> f64 {
+        match self {
+            LibraryHit::Note(h) => h.rank,
+            LibraryHit::Highlight(h) => h.rank,
+        }
+    }
+
+    pub fn snippet(&self) -> &str {
+        match self {
+            LibraryHit::Note(h) => &h.snippet,
+            LibraryHit::Highlight(h) => &h.snippet,
+        }
+    }
+
+    /// The book the hit belongs to. `None` only for a note written about no
+    /// book; a highlight always has one.
+    pub fn book_id(&self) -> Option<i64> {
+        match self {
+            LibraryHit::Note(h) => h.note.book_id,
+            LibraryHit::Highlight(h) => Some(h.highlight.book_id),
+        }
+    }
+}
+
+/// Every term of `query` as a quoted phrase, so none of it is read as syntax.
+///
+/// `"` is FTS5's only escape inside a phrase, doubled.
+fn literal_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Storage {
+    /// Notes and highlights matching `query`, best first, at most `limit`.
+    ///
+    /// The query is FTS5 syntax — `grief*`, `"exact phrase"`, `han OR jeong` —
+    /// because that is what `search_notes` has always taken. **A query FTS5
+    /// cannot parse is searched for as plain words** rather than refused: the
+    /// people typing into a search box are looking for `don't` or `C++`, not
+    /// writing expressions, and "syntax error near '" is no answer to them. A
+    /// query that still fails after that is a real error and is returned.
+    pub async fn search_library(&self, query: &str, limit: i64) -> Result<Vec<LibraryHit>> {
+        if query.trim().is_empty() {
+            return Err(EngineError::InvalidInput("an empty search".into()));
+        }
+        match self.search_library_as(query, limit).await {
+            Err(EngineError::Db(sqlx::Error::Database(e))) => {
+                tracing::debug!(error = %e, "not an FTS5 query; searching its words");
+                self.search_library_as(&literal_query(query), limit).await
+            }
+            other => other,
+        }
+    }
+
+    async fn search_library_as(&self, query: &str, limit: i64) -> Result<Vec<LibraryHit>> {
+        // Each index is asked for the whole `limit`: the best `limit` of the
+        // union can all come from one side.
+        let notes = self.search_notes(query, limit).await?;
+        let highlights = self.search_highlights(query, limit).await?;
+
+        let mut hits: Vec<LibraryHit> = notes
+            .into_iter()
+            .map(LibraryHit::Note)
+            .chain(highlights.into_iter().map(LibraryHit::Highlight))
+            .collect();
+        // Stable, so a tie keeps notes ahead of highlights and each side's own
+        // order within it.
+        hits.sort_by(|a, b| a.rank().total_cmp(&b.rank()));
+        hits.truncate(limit.max(0) as usize);
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::Book;
+    use crate::storage::{NewHighlight, NewNoteMeta};
+
+    async fn seeded() -> (Storage, i64) {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let id = s
+            .upsert_book(&Book {
+                title: Some("The Remains of the Day".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        (s, id)
+    }
+
+    fn hl(text: &str, chapter: &str, note: Option<&str>) -> NewHighlight {
+        NewHighlight {
+            text: text.into(),
+            chapter: Some(chapter.into()),
+            page: Some(12),
+            pos0: Some(format!("/body/p[{}]", text.len())),
+            pos1: None,
+            ko_datetime: Some("2026-03-05 12:00:00".into()),
+            ko_datetime_updated: None,
+            color: None,
+            note: note.map(Into::into),
+            source: "koreader".into(),
+        }
+    }
+
+    async fn note(s: &Storage, book_id: i64, title: &str, body: &str) -> i64 {
+        s.insert_note(
+            NewNoteMeta {
+                book_id: Some(book_id),
+                reading_id: None,
+                highlight_id: None,
+                page: None,
+                location: None,
+                file_path: &format!("notes/{title}.md"),
+                title,
+                kind: "note",
+            },
+            body,
+            &[],
+            &[],
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Every way a highlight's words change reaches the index: the insert, the
+    /// device rewriting its note, and the reader's own annotation.
+    #[tokio::test]
+    async fn the_index_follows_every_write_to_a_highlight() {
+        let (s, book) = seeded().await;
+        let h = hl("the great butlers are great", "Day One", Some("dignity"));
+        let id = s.insert_highlight(book, &h).await.unwrap().unwrap();
+        assert_eq!(s.search_highlights("butlers", 10).await.unwrap().len(), 1);
+        assert_eq!(s.search_highlights("dignity", 10).await.unwrap().len(), 1);
+        assert_eq!(
+            s.search_highlights("\"day one\"", 10).await.unwrap().len(),
+            1
+        );
+
+        // The device edits its note: the old words leave the index with it.
+        let edited = NewHighlight {
+            note: Some("restraint".into()),
+            ..h
+        };
+        assert!(s.refresh_device_fields(book, &edited).await.unwrap());
+        assert!(s.search_highlights("dignity", 10).await.unwrap().is_empty());
+        assert_eq!(s.search_highlights("restraint", 10).await.unwrap().len(), 1);
+
+        s.set_annotation(id, Some("compare Stevens' father"))
+            .await
+            .unwrap();
+        let hits = s.search_highlights("father", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].highlight.id, id);
+        assert!(
+            hits[0].snippet.contains(">>father<<"),
+            "{}",
+            hits[0].snippet
+        );
+
+        s.set_annotation(id, None).await.unwrap();
+        assert!(s.search_highlights("father", 10).await.unwrap().is_empty());
+    }
+
+    /// A book deleted takes its highlights out of the index too — by
+    /// `ON DELETE CASCADE`, which no Rust method sees.
+    #[tokio::test]
+    async fn a_removed_book_leaves_nothing_behind_in_the_index() {
+        let (s, book) = seeded().await;
+        s.insert_highlight(book, &hl("bantering", "Day Three", None))
+            .await
+            .unwrap();
+        s.delete_book(book).await.unwrap();
+        assert!(
+            s.search_highlights("bantering", 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    /// Notes and highlights come back in one list, and a query that is not
+    /// FTS5 syntax is searched as the words it is.
+    #[tokio::test]
+    async fn notes_and_highlights_are_searched_together() {
+        let (s, book) = seeded().await;
+        let n = note(&s, book, "Stevens", "On dignity, and what it costs.").await;
+        let h = s
+            .insert_highlight(
+                book,
+                &hl("a dignity in keeping with his position", "Day One", None),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        s.insert_highlight(book, &hl("what's the use", "Day Six", None))
+            .await
+            .unwrap();
+
+        let hits = s.search_library("dignity", 10).await.unwrap();
+        let mut found: Vec<(&str, i64)> = hits
+            .iter()
+            .map(|hit| match hit {
+                LibraryHit::Note(x) => ("note", x.note.id),
+                LibraryHit::Highlight(x) => ("highlight", x.highlight.id),
+            })
+            .collect();
+        found.sort_unstable();
+        assert_eq!(found, vec![("highlight", h), ("note", n)]);
+        assert!(hits.windows(2).all(|w| w[0].rank() <= w[1].rank()));
+        assert!(hits.iter().all(|hit| hit.book_id() == Some(book)));
+
+        // An unbalanced quote is a syntax error to FTS5 and a word to a reader.
+        let hits = s.search_library("what's", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(matches!(hits[0], LibraryHit::Highlight(_)));
+
+        assert_eq!(s.search_library("dignity", 1).await.unwrap().len(), 1);
+        assert!(matches!(
+            s.search_library("  ", 10).await,
+            Err(EngineError::InvalidInput(_))
+        ));
+    }
+}
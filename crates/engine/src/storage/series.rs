@@ -0,0 +1,209 @@
+//! Series, and each book's place in one (migration `0018`).
+//!
+//! Written only through [`Storage::upsert_book`] and [`Storage::enrich_book`],
+//! from `Book::series`: a series is a field of the book as far as every
+//! importer is concerned, and routing it through the same two writers is what
+//! keeps it on the same merge rule as the columns beside it.
+
+use sqlx::Row;
+
+use super::books::{BOOK_COLUMNS, BOOK_FROM, row_to_book};
+use super::{BookStatus, Storage};
+use crate::book::Book;
+use crate::error::Result;
+
+/// One series, as the series list shows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeriesSummary {
+    pub name: String,
+    pub books: i64,
+    pub finished: i64,
+}
+
+impl Storage {
+    /// Put `book` in the series it names, if it names one.
+    ///
+    /// The position follows the same no-clobber rule as the columns, within one
+    /// series: a record that says "Saga" without a number keeps the number
+    /// already known. Moving to a *different* series takes the new position as
+    /// given, because the old one was a place in something else.
+    pub(super) async fn merge_series(&self, book_id: i64, book: &Book) -> Result<()> {
+        let Some(name) = book
+            .series
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+        else {
+            return Ok(());
+        };
+        sqlx::query("INSERT INTO series (name) VALUES (?) ON CONFLICT (name) DO NOTHING")
+            .bind(name)
+            .execute(self.pool())
+            .await?;
+        sqlx::query(
+            "INSERT INTO book_series (book_id, series_id, position)
+             VALUES (?1, (SELECT id FROM series WHERE name = ?2), ?3)
+             ON CONFLICT (book_id) DO UPDATE SET
+                position = CASE WHEN book_series.series_id = excluded.series_id
+                                THEN COALESCE(excluded.position, book_series.position)
+                                ELSE excluded.position END,
+                series_id = excluded.series_id",
+        )
+        .bind(book_id)
+        .bind(name)
+        .bind(book.series_index)
+        .execute(self.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// Every series with a book in it, by name.
+    pub async fn list_series(&self) -> Result<Vec<SeriesSummary>> {
+        let rows = sqlx::query(
+            "SELECT s.name,
+                    count(*) AS books,
+                    sum(EXISTS (SELECT 1 FROM readings r
+                                WHERE r.book_id = bs.book_id AND r.status = 'finished'))
+                        AS finished
+               FROM series s JOIN book_series bs ON bs.series_id = s.id
+              GROUP BY s.id
+              ORDER BY s.name COLLATE NOCASE",
+        )
+        .fetch_all(self.pool())
+        .await?;
+        rows.iter()
+            .map(|r| {
+                Ok(SeriesSummary {
+                    name: r.try_get("name")?,
+                    books: r.try_get("books")?,
+                    finished: r.try_get("finished")?,
+                })
+            })
+            .collect()
+    }
+
+    /// One series' books in order, each with its current reading's state.
+    /// `None` when no series is called `name` (case-insensitively).
+    pub async fn series_books(
+        &self,
+        name: &str,
+    ) -> Result<Option<(String, Vec<(Book, BookStatus)>)>> {
+        let canonical: Option<String> =
+            sqlx::query_scalar("SELECT name FROM series WHERE name = ?")
+                .bind(name.trim())
+                .fetch_optional(self.pool())
+                .await?;
+        let Some(canonical) = canonical else {
+            return Ok(None);
+        };
+        let sql = format!(
+            "SELECT {BOOK_COLUMNS}, COALESCE(cur.status, 'unstarted') AS status {BOOK_FROM}
+              WHERE series.name = ?
+              ORDER BY bs.position ASC NULLS LAST, books.title COLLATE NOCASE, books.id"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&canonical)
+            .fetch_all(self.pool())
+            .await?;
+        let books = rows
+            .iter()
+            .map(|r| {
+                let status: String = r.try_get("status")?;
+                Ok((
+                    row_to_book(r)?,
+                    BookStatus::parse(&status).unwrap_or(BookStatus::Unstarted),
+                ))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Some((canonical, books)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn saga(s: &Storage, title: &str, at: Option<f64>) -> i64 {
+        s.upsert_book(&Book {
+            title: Some(title.into()),
+            series: Some("Saga".into()),
+            series_index: at,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_record_without_a_position_keeps_the_known_one() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let id = saga(&s, "Volume Two", Some(2.0)).await;
+        s.enrich_book(
+            id,
+            &Book {
+                series: Some("saga".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let b = s.get_book(id).await.unwrap().unwrap();
+        assert_eq!(b.series.as_deref(), Some("Saga"), "one row, first spelling");
+        assert_eq!(b.series_index, Some(2.0));
+
+        // Naming no series at all leaves it where it was.
+        s.enrich_book(id, &Book::default()).await.unwrap();
+        assert_eq!(
+            s.get_book(id).await.unwrap().unwrap().series_index,
+            Some(2.0)
+        );
+
+        // A different series is a different place.
+        s.enrich_book(
+            id,
+            &Book {
+                series: Some("Omnibus".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let b = s.get_book(id).await.unwrap().unwrap();
+        assert_eq!(b.series.as_deref(), Some("Omnibus"));
+        assert_eq!(b.series_index, None);
+    }
+
+    #[tokio::test]
+    async fn a_run_comes_back_in_order_with_its_state() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let three = saga(&s, "Volume Three", Some(3.0)).await;
+        let one = saga(&s, "Volume One", Some(1.0)).await;
+        saga(&s, "A Side Story", None).await;
+        s.update_progress(one, None, Some(true)).await.unwrap();
+        s.update_progress(three, Some(10), None).await.unwrap();
+
+        let (name, run) = s.series_books("SAGA").await.unwrap().unwrap();
+        assert_eq!(name, "Saga");
+        let got: Vec<_> = run
+            .iter()
+            .map(|(b, st)| (b.display_title().to_string(), *st))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                ("Volume One".to_string(), BookStatus::Finished),
+                ("Volume Three".to_string(), BookStatus::Reading),
+                ("A Side Story".to_string(), BookStatus::Unstarted),
+            ]
+        );
+        assert_eq!(
+            s.list_series().await.unwrap(),
+            [SeriesSummary {
+                name: "Saga".into(),
+                books: 3,
+                finished: 1
+            }]
+        );
+        assert!(s.series_books("Sagas").await.unwrap().is_none());
+    }
+}
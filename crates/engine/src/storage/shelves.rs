@@ -0,0 +1,689 @@
+//! Shelves: the user's own collections (migration `0020`).
+//!
+//! A manual shelf is a list the user keeps in order; a smart shelf is a saved
+//! [`ShelfRule`], and what is on it is whatever the rule matches when it is
+//! asked. Either is "the shelf called X" to everything that reads one — the
+//! listing filter resolves both to the ids on them.
+//!
+//! Nothing here reads `book_tags` except [`Storage::promote_tags`], and that
+//! only when the user names the tags: a Goodreads shelf or a calibre tag
+//! becomes a shelf by being picked, once, as a copy. The provenance rows stay
+//! what they were, and a re-import never changes a shelf.
+
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use super::books::{BOOK_COLUMNS, BOOK_FROM, row_to_book};
+use super::listing::{BookFilter, BookStatus};
+use super::{Storage, now_unix};
+use crate::book::Book;
+use crate::error::{EngineError, Result};
+
+/// Renumber one manual shelf's positions 1..n, in the order they already
+/// have. Run before placing a book, so a gap a deleted book left behind never
+/// makes "third" mean something other than third.
+const RENUMBER: &str = "UPDATE shelf_books SET position = o.n
+      FROM (SELECT book_id, row_number() OVER (ORDER BY position, book_id) AS n
+              FROM shelf_books WHERE shelf_id = ?1) o
+     WHERE shelf_books.shelf_id = ?1 AND shelf_books.book_id = o.book_id";
+
+/// What a smart shelf holds: every field set must match, and a field left
+/// `None` matches everything. A subset of [`BookFilter`] — the parts that say
+/// something about the reader's relationship with a book rather than how the
+/// library happens to be arranged today.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShelfRule {
+    pub status: Option<BookStatus>,
+    pub tag: Option<String>,
+    pub author: Option<String>,
+    /// At least this many Goodreads stars, as [`BookFilter::min_stars`].
+    pub min_stars: Option<u8>,
+    pub finished_year: Option<i64>,
+    pub has_notes: Option<bool>,
+}
+
+impl ShelfRule {
+    /// A rule that sets nothing is the whole library, which is not a shelf.
+    pub fn is_empty(&self) -> bool {
+        *self == ShelfRule::default()
+    }
+
+    /// The listing filter this rule is. Never names a shelf, so evaluating a
+    /// rule can never need another one.
+    pub fn to_filter(&self) -> BookFilter {
+        BookFilter {
+            status: self.status,
+            tag: self.tag.clone(),
+            author: self.author.clone(),
+            min_stars: self.min_stars,
+            finished_year: self.finished_year,
+            has_notes: self.has_notes,
+            ..Default::default()
+        }
+    }
+}
+
+/// The stored form of a rule. Separate from [`ShelfRule`] so the JSON in the
+/// database is spelled by this file, and a status is the word the CLI prints.
+#[derive(Serialize, Deserialize, Default)]
+struct RuleJson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_stars: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    finished_year: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    has_notes: Option<bool>,
+}
+
+fn rule_to_json(rule: &ShelfRule) -> String {
+    serde_json::to_string(&RuleJson {
+        status: rule.status.map(|s| s.as_str().to_string()),
+        tag: rule.tag.clone(),
+        author: rule.author.clone(),
+        min_stars: rule.min_stars,
+        finished_year: rule.finished_year,
+        has_notes: rule.has_notes,
+    })
+    .expect("a rule serializes")
+}
+
+fn rule_from_json(json: &str) -> Result<ShelfRule> {
+    let r: RuleJson = serde_json::from_str(json)
+        .map_err(|e| EngineError::Other(format!("unreadable shelf rule {json:?}: {e}")))?;
+    Ok(ShelfRule {
+        status: r.status.as_deref().and_then(BookStatus::parse),
+        tag: r.tag,
+        author: r.author,
+        min_stars: r.min_stars,
+        finished_year: r.finished_year,
+        has_notes: r.has_notes,
+    })
+}
+
+/// One shelf, and how many books are on it now.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shelf {
+    pub id: i64,
+    pub name: String,
+    /// `Some` for a smart shelf.
+    pub rule: Option<ShelfRule>,
+    pub books: i64,
+}
+
+impl Shelf {
+    pub fn is_smart(&self) -> bool {
+        self.rule.is_some()
+    }
+}
+
+/// A tag some import recorded, offered for promotion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagCandidate {
+    pub source: String,
+    pub tag: String,
+    /// The origin's own spelling, which is what a promoted shelf is called.
+    pub raw: Option<String>,
+    pub books: i64,
+    /// A shelf by that name exists already, so promoting adds to it.
+    pub shelved: bool,
+}
+
+impl TagCandidate {
+    /// The name the shelf gets: theirs, not our normalization of it.
+    pub fn shelf_name(&self) -> &str {
+        self.raw.as_deref().unwrap_or(&self.tag)
+    }
+}
+
+/// What promoting one tag did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Promoted {
+    pub shelf: String,
+    /// The shelf did not exist before.
+    pub created: bool,
+    /// Books newly put on it; one already there is not counted again.
+    pub added: usize,
+}
+
+impl Storage {
+    /// Make a shelf: manual when `rule` is `None`, smart otherwise.
+    ///
+    /// A name already taken — case aside — is [`EngineError::InvalidInput`],
+    /// as is an empty rule.
+    pub async fn create_shelf(&self, name: &str, rule: Option<&ShelfRule>) -> Result<Shelf> {
+        if name.trim().is_empty() {
+            return Err(EngineError::InvalidInput("a shelf needs a name".into()));
+        }
+        if rule.is_some_and(ShelfRule::is_empty) {
+            return Err(EngineError::InvalidInput(
+                "a smart shelf needs at least one condition".into(),
+            ));
+        }
+        let name = name.trim();
+        if self.shelf(name).await?.is_some() {
+            return Err(EngineError::InvalidInput(format!(
+                "there is already a shelf called {name:?}"
+            )));
+        }
+        let now = now_unix();
+        sqlx::query(
+            "INSERT INTO shelves (name, kind, rule, created_at, last_modified)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(name)
+        .bind(if rule.is_some() { "smart" } else { "manual" })
+        .bind(rule.map(rule_to_json))
+        .bind(now)
+        .bind(now)
+        .execute(self.pool())
+        .await?;
+        Ok(self.shelf(name).await?.expect("just inserted"))
+    }
+
+    /// Delete a shelf. The books stay; only the list goes. False when there was
+    /// no such shelf.
+    pub async fn delete_shelf(&self, name: &str) -> Result<bool> {
+        Ok(sqlx::query("DELETE FROM shelves WHERE name = ?")
+            .bind(name.trim())
+            .execute(self.pool())
+            .await?
+            .rows_affected()
+            > 0)
+    }
+
+    /// The shelf called `name`, case-insensitively.
+    pub async fn shelf(&self, name: &str) -> Result<Option<Shelf>> {
+        let row = sqlx::query("SELECT id, name, rule FROM shelves WHERE name = ?")
+            .bind(name.trim())
+            .fetch_optional(self.pool())
+            .await?;
+        match row {
+            Some(r) => Ok(Some(self.shelf_from_row(&r).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Every shelf, by name.
+    pub async fn list_shelves(&self) -> Result<Vec<Shelf>> {
+        let rows = sqlx::query("SELECT id, name, rule FROM shelves ORDER BY name ASC")
+            .fetch_all(self.pool())
+            .await?;
+        let mut shelves = Vec::with_capacity(rows.len());
+        for r in &rows {
+            shelves.push(self.shelf_from_row(r).await?);
+        }
+        Ok(shelves)
+    }
+
+    async fn shelf_from_row(&self, r: &sqlx::sqlite::SqliteRow) -> Result<Shelf> {
+        let id: i64 = r.try_get("id")?;
+        let rule = match r.try_get::<Option<String>, _>("rule")? {
+            Some(json) => Some(rule_from_json(&json)?),
+            None => None,
+        };
+        let books = match &rule {
+            Some(rule) => self.filtered_ids(&rule.to_filter()).await?.len() as i64,
+            None => {
+                sqlx::query_scalar("SELECT count(*) FROM shelf_books WHERE shelf_id = ?")
+                    .bind(id)
+                    .fetch_one(self.pool())
+                    .await?
+            }
+        };
+        Ok(Shelf {
+            id,
+            name: r.try_get("name")?,
+            rule,
+            books,
+        })
+    }
+
+    async fn require_shelf(&self, name: &str) -> Result<Shelf> {
+        self.shelf(name)
+            .await?
+            .ok_or_else(|| EngineError::NotFound(format!("shelf {:?}", name.trim())))
+    }
+
+    async fn require_manual(&self, name: &str) -> Result<Shelf> {
+        let shelf = self.require_shelf(name).await?;
+        if shelf.is_smart() {
+            return Err(EngineError::InvalidInput(format!(
+                "{:?} is a smart shelf; its rule decides what is on it",
+                shelf.name
+            )));
+        }
+        Ok(shelf)
+    }
+
+    /// The ids on a shelf, as the JSON array the listing filter binds.
+    pub(super) async fn shelf_ids(&self, name: &str) -> Result<String> {
+        let shelf = self.require_shelf(name).await?;
+        let ids: Vec<i64> = match &shelf.rule {
+            Some(rule) => self.filtered_ids(&rule.to_filter()).await?,
+            None => {
+                sqlx::query_scalar("SELECT book_id FROM shelf_books WHERE shelf_id = ?")
+                    .bind(shelf.id)
+                    .fetch_all(self.pool())
+                    .await?
+            }
+        };
+        Ok(serde_json::to_string(&ids).expect("ids serialize"))
+    }
+
+    /// The books on a shelf: a manual one in its order, a smart one by title.
+    pub async fn shelf_books(&self, name: &str) -> Result<Vec<Book>> {
+        let shelf = self.require_shelf(name).await?;
+        if shelf.is_smart() {
+            let page = self
+                .query_books(&super::BookQuery {
+                    filter: BookFilter {
+                        shelf: Some(shelf.name),
+                        ..Default::default()
+                    },
+                    sort: super::BookSort::Title,
+                    ..Default::default()
+                })
+                .await?;
+            return Ok(page.books);
+        }
+        let sql = format!(
+            "SELECT {BOOK_COLUMNS} {BOOK_FROM}
+               JOIN shelf_books sb ON sb.book_id = books.id
+              WHERE sb.shelf_id = ?
+              ORDER BY sb.position ASC, books.id ASC"
+        );
+        sqlx::query(&sql)
+            .bind(shelf.id)
+            .fetch_all(self.pool())
+            .await?
+            .iter()
+            .map(row_to_book)
+            .collect()
+    }
+
+    /// Put a book on a manual shelf at `position` (1-based), or at the end.
+    /// A book already there moves; a position past the end is the end.
+    pub async fn shelve(&self, name: &str, book_id: i64, position: Option<i64>) -> Result<()> {
+        let shelf = self.require_manual(name).await?;
+        let mut tx = self.pool().begin().await?;
+        let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM books WHERE id = ?")
+            .bind(book_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if exists.is_none() {
+            return Err(EngineError::NotFound(format!("book id {book_id}")));
+        }
+        // Taken off first, so a move and an insert are the same arithmetic; it
+        // keeps the day it was first shelved.
+        let added_at: Option<i64> = sqlx::query_scalar(
+            "DELETE FROM shelf_books WHERE shelf_id = ? AND book_id = ? RETURNING added_at",
+        )
+        .bind(shelf.id)
+        .bind(book_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        sqlx::query(RENUMBER)
+            .bind(shelf.id)
+            .execute(&mut *tx)
+            .await?;
+        let len: i64 = sqlx::query_scalar("SELECT count(*) FROM shelf_books WHERE shelf_id = ?")
+            .bind(shelf.id)
+            .fetch_one(&mut *tx)
+            .await?;
+        let at = position.unwrap_or(len + 1).clamp(1, len + 1);
+        sqlx::query(
+            "UPDATE shelf_books SET position = position + 1 WHERE shelf_id = ? AND position >= ?",
+        )
+        .bind(shelf.id)
+        .bind(at)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO shelf_books (shelf_id, book_id, position, added_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(shelf.id)
+        .bind(book_id)
+        .bind(at)
+        .bind(added_at.unwrap_or_else(now_unix))
+        .execute(&mut *tx)
+        .await?;
+        touch(&mut tx, shelf.id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Take a book off a manual shelf. False when it was not on it.
+    pub async fn unshelve(&self, name: &str, book_id: i64) -> Result<bool> {
+        let shelf = self.require_manual(name).await?;
+        let mut tx = self.pool().begin().await?;
+        let removed = sqlx::query("DELETE FROM shelf_books WHERE shelf_id = ? AND book_id = ?")
+            .bind(shelf.id)
+            .bind(book_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+            > 0;
+        if removed {
+            sqlx::query(RENUMBER)
+                .bind(shelf.id)
+                .execute(&mut *tx)
+                .await?;
+            touch(&mut tx, shelf.id).await?;
+        }
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    /// Every tag an import recorded, with how many books carry it — the menu
+    /// [`Storage::promote_tags`] is chosen from.
+    pub async fn tag_candidates(&self) -> Result<Vec<TagCandidate>> {
+        let rows = sqlx::query(
+            "SELECT g.*, EXISTS (SELECT 1 FROM shelves s
+                                  WHERE s.name = COALESCE(g.raw, g.tag)) AS shelved
+               FROM (SELECT source, tag, max(raw) AS raw, count(DISTINCT book_id) AS books
+                       FROM book_tags GROUP BY source, tag) g
+              ORDER BY g.source ASC, g.tag ASC",
+        )
+        .fetch_all(self.pool())
+        .await?;
+        rows.iter()
+            .map(|r| {
+                Ok(TagCandidate {
+                    source: r.try_get("source")?,
+                    tag: r.try_get("tag")?,
+                    raw: r.try_get("raw")?,
+                    books: r.try_get("books")?,
+                    shelved: r.try_get("shelved")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Make a manual shelf of each chosen tag, holding every book it is on.
+    ///
+    /// A tag is matched as the listing filter matches one — ours or the
+    /// origin's spelling — and `source`, when given, keeps a Goodreads pick
+    /// from sweeping in a calibre tag of the same name. The shelf is named as
+    /// the origin spelt it. One that exists already is added to, at the end,
+    /// by title; running the same promotion twice adds nothing.
+    ///
+    /// All or nothing: a tag nothing carries is [`EngineError::NotFound`], and
+    /// a smart shelf in the way is [`EngineError::InvalidInput`], before any
+    /// shelf is touched.
+    pub async fn promote_tags(
+        &self,
+        source: Option<&str>,
+        tags: &[String],
+    ) -> Result<Vec<Promoted>> {
+        let mut plan = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = tag.trim();
+            let rows = sqlx::query(
+                "SELECT t.book_id, COALESCE(t.raw, t.tag) AS name FROM book_tags t
+                   JOIN books ON books.id = t.book_id
+                  WHERE (t.tag = ?1 COLLATE NOCASE OR t.raw = ?1 COLLATE NOCASE)
+                    AND (?2 IS NULL OR t.source = ?2)
+                  ORDER BY t.source ASC, books.title COLLATE NOCASE ASC, books.id ASC",
+            )
+            .bind(tag)
+            .bind(source)
+            .fetch_all(self.pool())
+            .await?;
+            let Some(first) = rows.first() else {
+                return Err(EngineError::NotFound(match source {
+                    Some(s) => format!("{s} tag {tag:?}"),
+                    None => format!("tag {tag:?}"),
+                }));
+            };
+            let name: String = first.try_get("name")?;
+            let existing = self.shelf(&name).await?;
+            if existing.as_ref().is_some_and(Shelf::is_smart) {
+                return Err(EngineError::InvalidInput(format!(
+                    "{name:?} is a smart shelf; a tag cannot be promoted onto it"
+                )));
+            }
+            let mut books = Vec::with_capacity(rows.len());
+            for r in &rows {
+                let id: i64 = r.try_get("book_id")?;
+                if !books.contains(&id) {
+                    books.push(id);
+                }
+            }
+            plan.push((name, existing.map(|s| s.id), books));
+        }
+
+        let mut tx = self.pool().begin().await?;
+        let mut done = Vec::with_capacity(plan.len());
+        let now = now_unix();
+        for (name, existing, books) in plan {
+            let (shelf_id, created) = match existing {
+                Some(id) => (id, false),
+                // Two picks can name one new shelf ("to-read" from both
+                // sources); the second finds the first's row.
+                None => {
+                    let id: Option<i64> =
+                        sqlx::query_scalar("SELECT id FROM shelves WHERE name = ?")
+                            .bind(&name)
+                            .fetch_optional(&mut *tx)
+                            .await?;
+                    match id {
+                        Some(id) => (id, false),
+                        None => {
+                            let id = sqlx::query_scalar(
+                                "INSERT INTO shelves (name, kind, created_at, last_modified)
+                                 VALUES (?, 'manual', ?, ?) RETURNING id",
+                            )
+                            .bind(&name)
+                            .bind(now)
+                            .bind(now)
+                            .fetch_one(&mut *tx)
+                            .await?;
+                            (id, true)
+                        }
+                    }
+                }
+            };
+            sqlx::query(RENUMBER)
+                .bind(shelf_id)
+                .execute(&mut *tx)
+                .await?;
+            let mut len: i64 =
+                sqlx::query_scalar("SELECT count(*) FROM shelf_books WHERE shelf_id = ?")
+                    .bind(shelf_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            let mut added = 0;
+            for book_id in books {
+                let inserted = sqlx::query(
+                    "INSERT INTO shelf_books (shelf_id, book_id, position, added_at)
+                     VALUES (?, ?, ?, ?)
+                     ON CONFLICT (shelf_id, book_id) DO NOTHING",
+                )
+                .bind(shelf_id)
+                .bind(book_id)
+                .bind(len + 1)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+                if inserted > 0 {
+                    len += 1;
+                    added += 1;
+                }
+            }
+            if added > 0 {
+                touch(&mut tx, shelf_id).await?;
+            }
+            if let Some(d) = done.iter_mut().find(|d: &&mut Promoted| d.shelf == name) {
+                d.added += added;
+            } else {
+                done.push(Promoted {
+                    shelf: name,
+                    created,
+                    added,
+                });
+            }
+        }
+        tx.commit().await?;
+        Ok(done)
+    }
+}
+
+async fn touch(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, shelf_id: i64) -> Result<()> {
+    sqlx::query("UPDATE shelves SET last_modified = ? WHERE id = ?")
+        .bind(now_unix())
+        .bind(shelf_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn book(s: &Storage, title: &str) -> i64 {
+        s.upsert_book(&Book {
+            title: Some(title.into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    }
+
+    fn titles(books: &[Book]) -> Vec<&str> {
+        books.iter().map(|b| b.display_title()).collect()
+    }
+
+    #[tokio::test]
+    async fn a_manual_shelf_keeps_the_order_it_is_given() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let a = book(&s, "Annihilation").await;
+        let b = book(&s, "Borne").await;
+        let c = book(&s, "Dead Astronauts").await;
+        s.create_shelf("Weird", None).await.unwrap();
+        assert!(matches!(
+            s.create_shelf("weird", None).await,
+            Err(EngineError::InvalidInput(_))
+        ));
+
+        s.shelve("weird", c, None).await.unwrap();
+        s.shelve("weird", a, None).await.unwrap();
+        s.shelve("weird", b, Some(1)).await.unwrap();
+        assert_eq!(
+            titles(&s.shelf_books("Weird").await.unwrap()),
+            ["Borne", "Dead Astronauts", "Annihilation"]
+        );
+        // Shelving again moves; past the end is the end.
+        s.shelve("weird", b, Some(99)).await.unwrap();
+        assert_eq!(
+            titles(&s.shelf_books("Weird").await.unwrap()),
+            ["Dead Astronauts", "Annihilation", "Borne"]
+        );
+
+        // A deleted book leaves a gap that "second" still sees past.
+        sqlx::query("DELETE FROM books WHERE id = ?")
+            .bind(c)
+            .execute(s.pool())
+            .await
+            .unwrap();
+        s.shelve("weird", b, Some(2)).await.unwrap();
+        assert_eq!(
+            titles(&s.shelf_books("Weird").await.unwrap()),
+            ["Annihilation", "Borne"]
+        );
+
+        assert!(s.unshelve("weird", a).await.unwrap());
+        assert!(!s.unshelve("weird", a).await.unwrap());
+        assert_eq!(s.shelf("WEIRD").await.unwrap().unwrap().books, 1);
+        assert!(matches!(
+            s.shelve("weird", 999, None).await,
+            Err(EngineError::NotFound(_))
+        ));
+        assert!(s.delete_shelf("Weird").await.unwrap());
+        assert!(s.get_book(b).await.unwrap().is_some(), "the books stay");
+    }
+
+    #[tokio::test]
+    async fn a_smart_shelf_is_its_rule_asked_now() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let a = book(&s, "Annihilation").await;
+        book(&s, "Borne").await;
+        let rule = ShelfRule {
+            status: Some(BookStatus::Finished),
+            ..Default::default()
+        };
+        let shelf = s.create_shelf("Done", Some(&rule)).await.unwrap();
+        assert_eq!(shelf.rule.as_ref(), Some(&rule), "the rule round-trips");
+        assert_eq!(shelf.books, 0);
+
+        s.update_progress(a, None, Some(true)).await.unwrap();
+        assert_eq!(
+            titles(&s.shelf_books("done").await.unwrap()),
+            ["Annihilation"]
+        );
+        assert!(matches!(
+            s.shelve("done", a, None).await,
+            Err(EngineError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            s.create_shelf("Everything", Some(&ShelfRule::default()))
+                .await,
+            Err(EngineError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn promoting_a_tag_copies_it_once() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let a = book(&s, "Pachinko").await;
+        let b = book(&s, "Minor Detail").await;
+        let korean = [("korean-lit".to_string(), "Korean Lit".to_string())];
+        s.add_book_tags(a, "goodreads", &korean).await.unwrap();
+        s.add_book_tags(b, "calibre", &korean).await.unwrap();
+
+        let offered = s.tag_candidates().await.unwrap();
+        assert_eq!(offered.len(), 2);
+        assert_eq!(offered[0].shelf_name(), "Korean Lit");
+        assert!(!offered[0].shelved);
+
+        let done = s
+            .promote_tags(Some("goodreads"), &["korean-lit".into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            done,
+            [Promoted {
+                shelf: "Korean Lit".into(),
+                created: true,
+                added: 1
+            }]
+        );
+        assert_eq!(
+            titles(&s.shelf_books("korean lit").await.unwrap()),
+            ["Pachinko"]
+        );
+
+        // Both sources, twice: the calibre book joins at the end, once.
+        for _ in 0..2 {
+            s.promote_tags(None, &["Korean Lit".into()]).await.unwrap();
+        }
+        assert_eq!(
+            titles(&s.shelf_books("Korean Lit").await.unwrap()),
+            ["Pachinko", "Minor Detail"]
+        );
+        assert!(s.tag_candidates().await.unwrap()[0].shelved);
+
+        assert!(matches!(
+            s.promote_tags(None, &["korean-lit".into(), "nope".into()])
+                .await,
+            Err(EngineError::NotFound(_))
+        ));
+    }
+}
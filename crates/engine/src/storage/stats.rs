@@ -0,0 +1,495 @@
+//! Reading statistics: the first aggregates in `Storage`.
+//!
+//! Everything else in this module's siblings lists rows. These count them, per
+//! period, over every source at once — `readings` for what was finished,
+//! `highlights` and `notes` for what was written, `note_links` for what was
+//! connected, and `reading_events` (migration `0011`) for time and pages.
+//!
+//! **Every aggregate has to be able to say it does not know.** Pages and
+//! minutes come only from a filler of `reading_events`, and a month no filler
+//! covered is `None`, not `0`: zero is a claim that you did not read, and a
+//! Goodreads-only library has made no such claim. The counts are different —
+//! highlights, notes and finished readings are rows we hold, so a zero there is
+//! true of the library as it stands.
+//!
+//! Days are **local** dates. Unix stamps go through SQLite's `'localtime'`,
+//! which is how `reading_events.day` is computed too, so one evening's notes
+//! and that evening's minutes land in the same day. `highlights.ko_datetime` is
+//! already the device's wall clock and is taken as written.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use time::{Date, Month};
+
+use super::Storage;
+use crate::error::{EngineError, Result};
+
+/// How wide each period is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatsGrain {
+    Day,
+    #[default]
+    Month,
+    Year,
+    /// The whole range as one period: a book's totals, or a year's.
+    All,
+}
+
+/// Which days to count, inclusive at both ends, and optionally for one book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsRange {
+    pub from: Date,
+    pub to: Date,
+    pub book_id: Option<i64>,
+}
+
+/// One period's numbers.
+///
+/// Every period in the range is returned, empty ones included: a month with
+/// nothing in it is an answer, and a caller drawing twelve bars should not have
+/// to work out which eleven it was given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeriodStats {
+    /// `2026-03-01`, `2026-03`, `2026` — or `from..to` for [`StatsGrain::All`].
+    pub label: String,
+    /// The period's first and last day, clipped to the range: a range starting
+    /// mid-March has a March that starts there too.
+    pub from: Date,
+    pub to: Date,
+    /// Readings finished in the period. A reread finished is counted again,
+    /// because it was read again.
+    pub books_finished: usize,
+    /// Days on which anything at all happened: a reading logged, a highlight
+    /// made, a note or a link written, a book finished.
+    pub activity_days: usize,
+    /// Highlights by the date the device stamped on them. One without a date
+    /// is counted nowhere rather than on the day it was imported.
+    pub highlights: usize,
+    pub notes: usize,
+    pub links: usize,
+    /// `None` when no source measured any pages in the period.
+    pub pages: Option<i64>,
+    /// `None` when no source measured any time in the period.
+    pub minutes: Option<i64>,
+    /// How many of the period's days `minutes` covers. A month with minutes for
+    /// three of its days is three days of evidence, not a month's.
+    pub timed_days: usize,
+}
+
+/// `YYYY-MM-DD`, the one date shape the statistics speak.
+pub fn parse_day(s: &str) -> Result<Date> {
+    let fmt = time::macros::format_description!("[year]-[month]-[day]");
+    Date::parse(s.trim(), fmt)
+        .map_err(|_| EngineError::InvalidInput(format!("'{s}' is not a YYYY-MM-DD date")))
+}
+
+/// The inverse of [`parse_day`].
+pub fn format_day(d: Date) -> String {
+    let fmt = time::macros::format_description!("[year]-[month]-[day]");
+    d.format(fmt).unwrap_or_default()
+}
+
+/// The first day of the period `d` falls in, before clipping.
+fn period_start(d: Date, grain: StatsGrain, range: &StatsRange) -> Date {
+    let start = match grain {
+        StatsGrain::Day => d,
+        StatsGrain::Month => d.replace_day(1).unwrap_or(d),
+        StatsGrain::Year => Date::from_calendar_date(d.year(), Month::January, 1).unwrap_or(d),
+        StatsGrain::All => range.from,
+    };
+    start.max(range.from)
+}
+
+/// The day after the period starting at `start` ends, before clipping.
+fn period_after(start: Date, grain: StatsGrain, range: &StatsRange) -> Option<Date> {
+    match grain {
+        StatsGrain::Day => start.next_day(),
+        StatsGrain::Month => {
+            let (y, m) = match start.month() {
+                Month::December => (start.year() + 1, Month::January),
+                m => (start.year(), m.next()),
+            };
+            Date::from_calendar_date(y, m, 1).ok()
+        }
+        StatsGrain::Year => Date::from_calendar_date(start.year() + 1, Month::January, 1).ok(),
+        StatsGrain::All => range.to.next_day(),
+    }
+}
+
+fn label(from: Date, to: Date, grain: StatsGrain) -> String {
+    match grain {
+        StatsGrain::Day => format_day(from),
+        StatsGrain::Month => format!("{}-{:02}", from.year(), from.month() as u8),
+        StatsGrain::Year => from.year().to_string(),
+        StatsGrain::All => format!("{}..{}", format_day(from), format_day(to)),
+    }
+}
+
+/// `(day, count)` for one source. `?1`/`?2` are the range, `?3` the optional
+/// book; the inner select names its day `d`.
+const FINISHED_BY_DAY: &str = "SELECT d, count(*) FROM (
+        SELECT date(finished_at, 'unixepoch', 'localtime') AS d FROM readings
+         WHERE status = 'finished' AND finished_at IS NOT NULL
+           AND (?3 IS NULL OR book_id = ?3))
+      WHERE d BETWEEN ?1 AND ?2 GROUP BY d";
+
+const HIGHLIGHTS_BY_DAY: &str = "SELECT d, count(*) FROM (
+        SELECT date(ko_datetime) AS d FROM highlights
+         WHERE ?3 IS NULL OR book_id = ?3)
+      WHERE d BETWEEN ?1 AND ?2 GROUP BY d";
+
+const NOTES_BY_DAY: &str = "SELECT d, count(*) FROM (
+        SELECT date(created_at, 'unixepoch', 'localtime') AS d FROM notes
+         WHERE missing_since IS NULL AND (?3 IS NULL OR book_id = ?3))
+      WHERE d BETWEEN ?1 AND ?2 GROUP BY d";
+
+/// A link belongs to the book its linking note is about.
+const LINKS_BY_DAY: &str = "SELECT d, count(*) FROM (
+        SELECT date(l.created_at, 'unixepoch', 'localtime') AS d
+          FROM note_links l JOIN notes n ON n.id = l.from_note
+         WHERE n.missing_since IS NULL AND (?3 IS NULL OR n.book_id = ?3))
+      WHERE d BETWEEN ?1 AND ?2 GROUP BY d";
+
+/// `SUM` over no non-NULL rows is NULL, which is exactly "unknown"; the
+/// `count`s say how many days each sum actually rests on.
+const EVENTS_BY_DAY: &str = "SELECT day, SUM(minutes), SUM(pages), count(minutes), count(pages)
+       FROM reading_events
+      WHERE day BETWEEN ?1 AND ?2 AND (?3 IS NULL OR book_id = ?3)
+      GROUP BY day";
+
+impl Storage {
+    /// Finished readings, activity days, highlights, notes, links, pages and
+    /// minutes for each period of `grain` in `range`.
+    pub async fn reading_stats(
+        &self,
+        range: &StatsRange,
+        grain: StatsGrain,
+    ) -> Result<Vec<PeriodStats>> {
+        if range.from > range.to {
+            return Err(EngineError::InvalidInput(format!(
+                "the range ends ({}) before it starts ({})",
+                format_day(range.to),
+                format_day(range.from)
+            )));
+        }
+
+        let mut periods: BTreeMap<Date, PeriodStats> = BTreeMap::new();
+        let mut start = range.from;
+        loop {
+            let to = period_after(start, grain, range)
+                .and_then(|d| d.previous_day())
+                .map_or(range.to, |d| d.min(range.to));
+            periods.insert(
+                start,
+                PeriodStats {
+                    label: label(start, to, grain),
+                    from: start,
+                    to,
+                    books_finished: 0,
+                    activity_days: 0,
+                    highlights: 0,
+                    notes: 0,
+                    links: 0,
+                    pages: None,
+                    minutes: None,
+                    timed_days: 0,
+                },
+            );
+            match to.next_day() {
+                Some(next) if next <= range.to => start = next,
+                _ => break,
+            }
+        }
+
+        let from = format_day(range.from);
+        let to = format_day(range.to);
+        let mut active: BTreeMap<Date, BTreeSet<Date>> = BTreeMap::new();
+
+        for (sql, field) in [
+            (FINISHED_BY_DAY, Field::Finished),
+            (HIGHLIGHTS_BY_DAY, Field::Highlights),
+            (NOTES_BY_DAY, Field::Notes),
+            (LINKS_BY_DAY, Field::Links),
+        ] {
+            let rows: Vec<(String, i64)> = sqlx::query_as(sql)
+                .bind(&from)
+                .bind(&to)
+                .bind(range.book_id)
+                .fetch_all(self.pool())
+                .await?;
+            for (day, n) in rows {
+                let Ok(day) = parse_day(&day) else { continue };
+                let key = period_start(day, grain, range);
+                let Some(p) = periods.get_mut(&key) else {
+                    continue;
+                };
+                let n = n as usize;
+                match field {
+                    Field::Finished => p.books_finished += n,
+                    Field::Highlights => p.highlights += n,
+                    Field::Notes => p.notes += n,
+                    Field::Links => p.links += n,
+                }
+                active.entry(key).or_default().insert(day);
+            }
+        }
+
+        let rows: Vec<EventDay> = sqlx::query_as(EVENTS_BY_DAY)
+            .bind(&from)
+            .bind(&to)
+            .bind(range.book_id)
+            .fetch_all(self.pool())
+            .await?;
+        for (day, minutes, pages, timed, paged) in rows {
+            let Ok(day) = parse_day(&day) else { continue };
+            let key = period_start(day, grain, range);
+            let Some(p) = periods.get_mut(&key) else {
+                continue;
+            };
+            if timed > 0 {
+                p.minutes = Some(p.minutes.unwrap_or(0) + minutes.unwrap_or(0));
+                p.timed_days += 1;
+            }
+            if paged > 0 {
+                p.pages = Some(p.pages.unwrap_or(0) + pages.unwrap_or(0));
+            }
+            active.entry(key).or_default().insert(day);
+        }
+
+        for (key, days) in active {
+            if let Some(p) = periods.get_mut(&key) {
+                p.activity_days = days.len();
+            }
+        }
+        Ok(periods.into_values().collect())
+    }
+}
+
+/// A row of [`EVENTS_BY_DAY`]: the day, its minutes and pages, and how many of
+/// its events measured each.
+type EventDay = (String, Option<i64>, Option<i64>, i64, i64);
+
+#[derive(Clone, Copy)]
+enum Field {
+    Finished,
+    Highlights,
+    Notes,
+    Links,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::Book;
+    use crate::storage::{CONFIDENCE_MEASURED, NewHighlight, NewNoteMeta, NewReadingEvent};
+
+    /// 2026-03-05 12:00:00 UTC — midday, so the local date is the 5th in any
+    /// timezone a test machine is plausibly in.
+    const MARCH_5_NOON: i64 = 1_772_712_000;
+
+    fn day(s: &str) -> Date {
+        parse_day(s).unwrap()
+    }
+
+    fn year_2026(book_id: Option<i64>) -> StatsRange {
+        StatsRange {
+            from: day("2026-01-01"),
+            to: day("2026-12-31"),
+            book_id,
+        }
+    }
+
+    async fn seeded() -> (Storage, i64) {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let id = s
+            .upsert_book(&Book {
+                title: Some("Pachinko".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        (s, id)
+    }
+
+    fn ko_day(book_id: i64, day: &str, minutes: i64) -> NewReadingEvent<'_> {
+        NewReadingEvent {
+            book_id,
+            day,
+            minutes: Some(minutes),
+            pages: Some(minutes / 2),
+            source: "koreader",
+            source_key: "aaaa",
+            confidence: CONFIDENCE_MEASURED,
+        }
+    }
+
+    /// Twelve months come back for a year, and a month no source measured says
+    /// so rather than claiming zero minutes.
+    #[tokio::test]
+    async fn a_month_without_device_data_is_unknown_not_zero() {
+        let (s, book) = seeded().await;
+        s.put_reading_event(&ko_day(book, "2026-03-01", 30))
+            .await
+            .unwrap();
+        s.put_reading_event(&ko_day(book, "2026-03-04", 20))
+            .await
+            .unwrap();
+
+        let months = s
+            .reading_stats(&year_2026(None), StatsGrain::Month)
+            .await
+            .unwrap();
+        assert_eq!(months.len(), 12);
+        let march = &months[2];
+        assert_eq!(march.label, "2026-03");
+        assert_eq!(
+            (march.from, march.to),
+            (day("2026-03-01"), day("2026-03-31"))
+        );
+        assert_eq!(march.minutes, Some(50));
+        assert_eq!(march.pages, Some(25));
+        assert_eq!((march.timed_days, march.activity_days), (2, 2));
+
+        let april = &months[3];
+        assert_eq!(april.minutes, None);
+        assert_eq!(april.pages, None);
+        assert_eq!(april.activity_days, 0);
+    }
+
+    /// Every source lands in the period it happened in, and a day with several
+    /// kinds of activity is still one activity day.
+    #[tokio::test]
+    async fn each_source_is_counted_once_in_its_own_period() {
+        let (s, book) = seeded().await;
+        s.record_reading(
+            book,
+            Some(MARCH_5_NOON - 30 * 86_400),
+            Some(MARCH_5_NOON),
+            "finished",
+            "manual",
+        )
+        .await
+        .unwrap();
+        s.insert_highlight(
+            book,
+            &NewHighlight {
+                text: "Family is everything.".into(),
+                chapter: None,
+                page: Some(12),
+                pos0: Some("/body/p[1]".into()),
+                pos1: None,
+                ko_datetime: Some("2026-03-05 21:10:00".into()),
+                ko_datetime_updated: None,
+                color: None,
+                note: None,
+                source: "koreader".into(),
+            },
+        )
+        .await
+        .unwrap();
+        let note = s
+            .insert_note(
+                NewNoteMeta {
+                    book_id: Some(book),
+                    reading_id: None,
+                    highlight_id: None,
+                    page: None,
+                    location: None,
+                    file_path: "books/pachinko/a.md",
+                    title: "Sunja",
+                    kind: "note",
+                },
+                "[[Han]]",
+                &crate::notes::extract_links("[[Han]]"),
+                &[],
+            )
+            .await
+            .unwrap();
+        for sql in [
+            "UPDATE notes SET created_at = ? WHERE id = ?",
+            "UPDATE note_links SET created_at = ? WHERE from_note = ?",
+        ] {
+            sqlx::query(sql)
+                .bind(MARCH_5_NOON)
+                .bind(note)
+                .execute(s.pool())
+                .await
+                .unwrap();
+        }
+        s.put_reading_event(&ko_day(book, "2026-03-05", 40))
+            .await
+            .unwrap();
+
+        let all = s
+            .reading_stats(&year_2026(Some(book)), StatsGrain::All)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 1);
+        let p = &all[0];
+        assert_eq!(p.label, "2026-01-01..2026-12-31");
+        assert_eq!(
+            (p.books_finished, p.highlights, p.notes, p.links),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(p.activity_days, 1, "five kinds of activity, one day");
+        assert_eq!(p.minutes, Some(40));
+
+        let other = s
+            .upsert_book(&Book {
+                title: Some("Kokoro".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let none = s
+            .reading_stats(&year_2026(Some(other)), StatsGrain::All)
+            .await
+            .unwrap();
+        assert_eq!(none[0].activity_days, 0);
+        assert_eq!(none[0].minutes, None);
+    }
+
+    /// A range that starts and ends mid-period clips its first and last
+    /// periods rather than reaching outside itself.
+    #[tokio::test]
+    async fn periods_are_clipped_to_the_range() {
+        let (s, book) = seeded().await;
+        s.put_reading_event(&ko_day(book, "2026-02-27", 10))
+            .await
+            .unwrap();
+        let range = StatsRange {
+            from: day("2026-02-28"),
+            to: day("2027-01-02"),
+            book_id: None,
+        };
+
+        let years = s.reading_stats(&range, StatsGrain::Year).await.unwrap();
+        assert_eq!(years.len(), 2);
+        assert_eq!(
+            (years[0].from, years[0].to),
+            (day("2026-02-28"), day("2026-12-31"))
+        );
+        assert_eq!(
+            (years[1].from, years[1].to),
+            (day("2027-01-01"), day("2027-01-02"))
+        );
+        assert_eq!(years[0].minutes, None, "the 27th is outside the range");
+
+        let days = s.reading_stats(&range, StatsGrain::Day).await.unwrap();
+        assert_eq!(days.len(), 309);
+        assert_eq!(days[0].label, "2026-02-28");
+
+        assert!(matches!(
+            s.reading_stats(
+                &StatsRange {
+                    from: day("2026-03-01"),
+                    to: day("2026-02-01"),
+                    book_id: None
+                },
+                StatsGrain::Month
+            )
+            .await,
+            Err(EngineError::InvalidInput(_))
+        ));
+    }
+}
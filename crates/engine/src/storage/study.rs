@@ -0,0 +1,317 @@
+//! Study cards: the schedule hung off a flashcard or a chosen highlight
+//! (migration `0021`).
+//!
+//! [`crate::study::Schedule`] decides; this keeps what it decided and reads
+//! cards back with everything a review screen shows around the prompt — the
+//! book, its authors, the chapter and page the passage came from.
+//!
+//! Flashcards are enrolled here rather than where they are made: the queue
+//! reads in any flashcard without a card first, so the importers that mint
+//! words never learn this table exists.
+
+use sqlx::Row;
+
+use super::{Storage, now_unix};
+use crate::error::{EngineError, Result};
+use crate::study::{Grade, Schedule};
+
+/// Which of the two things a card quizzes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardKind {
+    /// A word from the `flashcards` table; `context` is the sentence it was in.
+    Word,
+    /// A highlight the reader opted in; `context` is their annotation on it.
+    Highlight,
+}
+
+/// One card, ready to show.
+#[derive(Debug, Clone)]
+pub struct StudyCard {
+    pub id: i64,
+    pub kind: CardKind,
+    /// The prompt: the word, or the whole passage.
+    pub front: String,
+    pub context: Option<String>,
+    pub book_id: i64,
+    pub book_title: String,
+    pub authors: Vec<String>,
+    /// Where in the book, when the highlight behind the card knows. A word
+    /// card has them through the highlight it was lifted from.
+    pub chapter: Option<String>,
+    pub page: Option<i64>,
+    pub schedule: Schedule,
+    pub due_at: i64,
+    pub last_reviewed_at: Option<i64>,
+}
+
+const CARD_SELECT: &str = r#"
+    SELECT sc.id, sc.flashcard_id, sc.ease, sc.interval_days, sc.repetitions, sc.lapses,
+           sc.due_at, sc.last_reviewed_at,
+           CASE WHEN sc.flashcard_id IS NOT NULL THEN f.word ELSE h.text END AS front,
+           CASE WHEN sc.flashcard_id IS NOT NULL THEN f.context
+                ELSE COALESCE(h.annotation, h.ko_note) END AS context,
+           b.id AS book_id, COALESCE(b.title, '') AS book_title, b.authors,
+           h.chapter, h.page
+      FROM study_cards sc
+      LEFT JOIN flashcards f ON f.id = sc.flashcard_id
+      LEFT JOIN highlights h ON h.id = COALESCE(sc.highlight_id, f.highlight_id)
+      JOIN books b ON b.id = COALESCE(f.book_id, h.book_id)"#;
+
+fn row_to_card(r: &sqlx::sqlite::SqliteRow) -> StudyCard {
+    let flashcard_id: Option<i64> = r.get("flashcard_id");
+    let authors: String = r.get("authors");
+    StudyCard {
+        id: r.get("id"),
+        kind: if flashcard_id.is_some() {
+            CardKind::Word
+        } else {
+            CardKind::Highlight
+        },
+        front: r.get("front"),
+        context: r.get("context"),
+        book_id: r.get("book_id"),
+        book_title: r.get("book_title"),
+        authors: serde_json::from_str(&authors).unwrap_or_default(),
+        chapter: r.get("chapter"),
+        page: r.get("page"),
+        schedule: Schedule {
+            ease: r.get("ease"),
+            interval_days: r.get("interval_days"),
+            repetitions: r.get("repetitions"),
+            lapses: r.get("lapses"),
+        },
+        due_at: r.get("due_at"),
+        last_reviewed_at: r.get("last_reviewed_at"),
+    }
+}
+
+impl Storage {
+    /// Give every flashcard without a card one, due now. Returns how many were
+    /// made; run before anything counts or lists cards.
+    pub async fn enrol_flashcards(&self) -> Result<u64> {
+        let now = now_unix();
+        let res = sqlx::query(
+            "INSERT INTO study_cards (flashcard_id, due_at, created_at)
+             SELECT f.id, ?1, ?1 FROM flashcards f
+              WHERE NOT EXISTS (SELECT 1 FROM study_cards sc WHERE sc.flashcard_id = f.id)",
+        )
+        .bind(now)
+        .execute(self.pool())
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Opt a highlight in (`on`) or out of study. Opting out forgets its
+    /// schedule and log. Returns whether anything changed.
+    pub async fn study_highlight(&self, highlight_id: i64, on: bool) -> Result<bool> {
+        if !on {
+            let res = sqlx::query("DELETE FROM study_cards WHERE highlight_id = ?")
+                .bind(highlight_id)
+                .execute(self.pool())
+                .await?;
+            return Ok(res.rows_affected() > 0);
+        }
+        let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM highlights WHERE id = ?")
+            .bind(highlight_id)
+            .fetch_optional(self.pool())
+            .await?;
+        if exists.is_none() {
+            return Err(EngineError::NotFound(format!(
+                "highlight id {highlight_id}"
+            )));
+        }
+        let now = now_unix();
+        let res = sqlx::query(
+            "INSERT INTO study_cards (highlight_id, due_at, created_at) VALUES (?1, ?2, ?2)
+             ON CONFLICT(highlight_id) DO NOTHING",
+        )
+        .bind(highlight_id)
+        .bind(now)
+        .execute(self.pool())
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// The highlight ids of `book_id` that are being studied, for marking them
+    /// in a highlight list.
+    pub async fn studied_highlights(&self, book_id: i64) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar(
+            "SELECT sc.highlight_id FROM study_cards sc
+               JOIN highlights h ON h.id = sc.highlight_id
+              WHERE h.book_id = ? ORDER BY sc.highlight_id",
+        )
+        .bind(book_id)
+        .fetch_all(self.pool())
+        .await?)
+    }
+
+    /// Cards due at or before `now`, most overdue first, at most `limit`.
+    pub async fn due_cards(&self, now: i64, limit: usize) -> Result<Vec<StudyCard>> {
+        self.enrol_flashcards().await?;
+        let sql = format!("{CARD_SELECT} WHERE sc.due_at <= ? ORDER BY sc.due_at, sc.id LIMIT ?");
+        let rows = sqlx::query(&sql)
+            .bind(now)
+            .bind(limit as i64)
+            .fetch_all(self.pool())
+            .await?;
+        Ok(rows.iter().map(row_to_card).collect())
+    }
+
+    pub async fn study_card(&self, id: i64) -> Result<Option<StudyCard>> {
+        let sql = format!("{CARD_SELECT} WHERE sc.id = ?");
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(self.pool())
+            .await?;
+        Ok(row.as_ref().map(row_to_card))
+    }
+
+    /// Grade card `id` at `now`: move its schedule on and log the grade, in one
+    /// transaction so the log never disagrees with the card.
+    pub async fn record_grade(&self, id: i64, grade: Grade, now: i64) -> Result<StudyCard> {
+        let mut tx = self.pool().begin().await?;
+        let row = sqlx::query(
+            "SELECT ease, interval_days, repetitions, lapses FROM study_cards WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| EngineError::NotFound(format!("study card {id}")))?;
+        let next = Schedule {
+            ease: row.get("ease"),
+            interval_days: row.get("interval_days"),
+            repetitions: row.get("repetitions"),
+            lapses: row.get("lapses"),
+        }
+        .next(grade);
+        sqlx::query(
+            "UPDATE study_cards SET ease = ?, interval_days = ?, repetitions = ?, lapses = ?,
+                    due_at = ?, last_reviewed_at = ? WHERE id = ?",
+        )
+        .bind(next.ease)
+        .bind(next.interval_days)
+        .bind(next.repetitions)
+        .bind(next.lapses)
+        .bind(next.due_after(now))
+        .bind(now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO study_log (card_id, grade, reviewed_at, interval_days, ease)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(grade.as_str())
+        .bind(now)
+        .bind(next.interval_days)
+        .bind(next.ease)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        self.study_card(id)
+            .await?
+            .ok_or_else(|| EngineError::NotFound(format!("study card {id}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::Book;
+    use crate::storage::NewHighlight;
+
+    async fn seeded() -> (Storage, i64, i64) {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let book_id = s
+            .upsert_book(&Book {
+                title: Some("Flights".into()),
+                authors: vec!["Olga Tokarczuk".into()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let highlight_id = s
+            .insert_highlight(
+                book_id,
+                &NewHighlight {
+                    text: "Barbarians don't travel; they simply go to destinations.".into(),
+                    chapter: Some("Travel Psychology".into()),
+                    page: Some(77),
+                    pos0: None,
+                    pos1: None,
+                    ko_datetime: Some("2024-03-01 10:00:00".into()),
+                    ko_datetime_updated: None,
+                    color: None,
+                    note: None,
+                    source: "koreader".into(),
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        (s, book_id, highlight_id)
+    }
+
+    #[tokio::test]
+    async fn flashcards_enrol_themselves_and_highlights_opt_in() {
+        let (s, book_id, hid) = seeded().await;
+        s.insert_flashcard(book_id, Some(hid), "destinations", Some("they simply go"))
+            .await
+            .unwrap();
+        let now = now_unix();
+
+        let due = s.due_cards(now, 10).await.unwrap();
+        assert_eq!(due.len(), 1);
+        let word = &due[0];
+        assert_eq!(
+            (word.kind, word.front.as_str()),
+            (CardKind::Word, "destinations")
+        );
+        // The word card finds its chapter through the highlight it came from.
+        assert_eq!(word.chapter.as_deref(), Some("Travel Psychology"));
+        assert_eq!(word.authors, vec!["Olga Tokarczuk".to_string()]);
+
+        assert!(s.study_highlight(hid, true).await.unwrap());
+        assert!(!s.study_highlight(hid, true).await.unwrap());
+        assert_eq!(s.studied_highlights(book_id).await.unwrap(), vec![hid]);
+        let due = s.due_cards(now, 10).await.unwrap();
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[1].kind, CardKind::Highlight);
+        assert_eq!(due[1].page, Some(77));
+
+        assert!(s.study_highlight(hid, false).await.unwrap());
+        assert_eq!(s.due_cards(now, 10).await.unwrap().len(), 1);
+        assert!(matches!(
+            s.study_highlight(hid + 100, true).await,
+            Err(EngineError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_grade_moves_the_card_out_of_the_queue_and_is_logged() {
+        let (s, _, hid) = seeded().await;
+        s.study_highlight(hid, true).await.unwrap();
+        let now = now_unix();
+        let card = s.due_cards(now, 10).await.unwrap().remove(0);
+
+        let graded = s.record_grade(card.id, Grade::Good, now).await.unwrap();
+        assert_eq!(graded.schedule.interval_days, 1);
+        assert_eq!(graded.due_at, now + 86_400);
+        assert_eq!(graded.last_reviewed_at, Some(now));
+        assert!(s.due_cards(now, 10).await.unwrap().is_empty());
+        assert_eq!(s.due_cards(now + 86_400, 10).await.unwrap().len(), 1);
+
+        let logged: Vec<(String, i64)> =
+            sqlx::query_as("SELECT grade, interval_days FROM study_log WHERE card_id = ?")
+                .bind(card.id)
+                .fetch_all(s.pool())
+                .await
+                .unwrap();
+        assert_eq!(logged, vec![("good".to_string(), 1)]);
+        assert!(matches!(
+            s.record_grade(card.id + 100, Grade::Good, now).await,
+            Err(EngineError::NotFound(_))
+        ));
+    }
+}
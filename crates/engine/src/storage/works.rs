@@ -0,0 +1,290 @@
+//! Works, and which editions are one (migration `0019`).
+//!
+//! Two writers and no third. [`Storage::attach_edition`] and
+//! [`Storage::detach_edition`] are the user saying "these are the same book";
+//! [`Storage::merge_work`] is a provider saying so by key, through the same
+//! upsert/enrich path as every other field it brings. Neither ever moves a
+//! reading or a highlight — the editions stay the rows they were, and anything
+//! that wants the work asks for its editions and reads across them.
+
+use sqlx::Row;
+
+use super::books::{BOOK_COLUMNS, BOOK_FROM, row_to_book};
+use super::{Storage, now_unix};
+use crate::book::Book;
+use crate::error::{EngineError, Result};
+
+/// A work left with no editions is nothing, and one left with a single edition
+/// and no key is only that book again: every writer that can thin one runs
+/// this after. A keyed work keeps its last edition — the key is what the next
+/// edition a provider brings will be filed under. The lone book's `work_id`
+/// goes with the row, by `ON DELETE SET NULL`.
+pub(super) const PRUNE_WORKS: &str = "DELETE FROM works
+     WHERE (SELECT count(*) FROM books WHERE books.work_id = works.id)
+           < CASE WHEN works.openlibrary_key IS NULL THEN 2 ELSE 1 END";
+
+/// One work and every edition of it held here.
+#[derive(Debug, Clone)]
+pub struct Work {
+    pub id: i64,
+    /// The title of the edition the work was made from. Editions keep their
+    /// own — a translation's is usually different — and this is only what the
+    /// work is called when it is listed by itself.
+    pub title: String,
+    /// OpenLibrary's `/works/…` key, when a provider has given one.
+    pub openlibrary_key: Option<String>,
+    /// Oldest first, by publication year, so the original leads its
+    /// translations.
+    pub editions: Vec<Book>,
+}
+
+impl Storage {
+    /// File `book` under the work its OpenLibrary key names, if it names one.
+    ///
+    /// A book already in a work stays in it — the user may have put it there,
+    /// and a key is no reason to undo that. If that work has no key yet it
+    /// takes this one, unless another work already holds it.
+    pub(super) async fn merge_work(&self, book_id: i64, book: &Book) -> Result<()> {
+        let Some(key) = book
+            .openlibrary_work
+            .as_deref()
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+        else {
+            return Ok(());
+        };
+        sqlx::query(
+            "UPDATE works SET openlibrary_key = ?1
+              WHERE id = (SELECT work_id FROM books WHERE id = ?2)
+                AND openlibrary_key IS NULL
+                AND NOT EXISTS (SELECT 1 FROM works WHERE openlibrary_key = ?1)",
+        )
+        .bind(key)
+        .bind(book_id)
+        .execute(self.pool())
+        .await?;
+
+        let grouped: Option<i64> = sqlx::query_scalar("SELECT work_id FROM books WHERE id = ?")
+            .bind(book_id)
+            .fetch_optional(self.pool())
+            .await?
+            .flatten();
+        if grouped.is_some() {
+            return Ok(());
+        }
+        sqlx::query(
+            "INSERT INTO works (title, openlibrary_key, created_at)
+             SELECT title, ?2, ?3 FROM books WHERE id = ?1
+             ON CONFLICT (openlibrary_key) DO NOTHING",
+        )
+        .bind(book_id)
+        .bind(key)
+        .bind(now_unix())
+        .execute(self.pool())
+        .await?;
+        sqlx::query(
+            "UPDATE books SET work_id = (SELECT id FROM works WHERE openlibrary_key = ?2)
+              WHERE id = ?1",
+        )
+        .bind(book_id)
+        .bind(key)
+        .execute(self.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// Make `book_id` an edition of the work `to` belongs to, making that work
+    /// from `to` if it is in none. Returns the work's id.
+    ///
+    /// A book that was in another work leaves it; that work goes if this was
+    /// its last edition.
+    pub async fn attach_edition(&self, book_id: i64, to: i64) -> Result<i64> {
+        if book_id == to {
+            return Err(EngineError::InvalidInput(
+                "a book is already an edition of itself".into(),
+            ));
+        }
+        let mut tx = self.pool().begin().await?;
+        // Both must exist; the second is the one whose work counts.
+        let mut target = None;
+        for id in [book_id, to] {
+            let row: Option<(String, Option<i64>)> =
+                sqlx::query_as("SELECT title, work_id FROM books WHERE id = ?")
+                    .bind(id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            let Some(row) = row else {
+                return Err(EngineError::NotFound(format!("book id {id}")));
+            };
+            target = Some(row);
+        }
+        let (title, work_id) = target.expect("looked up");
+        let work_id = match work_id {
+            Some(w) => w,
+            None => {
+                let w: i64 = sqlx::query_scalar(
+                    "INSERT INTO works (title, created_at) VALUES (?, ?) RETURNING id",
+                )
+                .bind(&title)
+                .bind(now_unix())
+                .fetch_one(&mut *tx)
+                .await?;
+                sqlx::query("UPDATE books SET work_id = ? WHERE id = ?")
+                    .bind(w)
+                    .bind(to)
+                    .execute(&mut *tx)
+                    .await?;
+                w
+            }
+        };
+        sqlx::query("UPDATE books SET work_id = ? WHERE id = ?")
+            .bind(work_id)
+            .bind(book_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(PRUNE_WORKS).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(work_id)
+    }
+
+    /// Take `book_id` out of its work, back to a book of its own. False when
+    /// it was in none.
+    pub async fn detach_edition(&self, book_id: i64) -> Result<bool> {
+        let mut tx = self.pool().begin().await?;
+        let detached =
+            sqlx::query("UPDATE books SET work_id = NULL WHERE id = ? AND work_id IS NOT NULL")
+                .bind(book_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+                > 0;
+        sqlx::query(PRUNE_WORKS).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(detached)
+    }
+
+    /// The work `book_id` is an edition of, with every edition. `None` for a
+    /// book in no work.
+    pub async fn work_of(&self, book_id: i64) -> Result<Option<Work>> {
+        let row = sqlx::query(
+            "SELECT works.id, works.title, works.openlibrary_key
+               FROM books JOIN works ON works.id = books.work_id
+              WHERE books.id = ?",
+        )
+        .bind(book_id)
+        .fetch_optional(self.pool())
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let id: i64 = row.try_get("id")?;
+        let sql = format!(
+            "SELECT {BOOK_COLUMNS} {BOOK_FROM} WHERE books.work_id = ?
+              ORDER BY books.publish_year ASC NULLS LAST, books.id ASC"
+        );
+        let editions = sqlx::query(&sql)
+            .bind(id)
+            .fetch_all(self.pool())
+            .await?
+            .iter()
+            .map(row_to_book)
+            .collect::<Result<_>>()?;
+        Ok(Some(Work {
+            id,
+            title: row.try_get("title")?,
+            openlibrary_key: row.try_get("openlibrary_key")?,
+            editions,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn edition(s: &Storage, title: &str, year: i64) -> i64 {
+        s.upsert_book(&Book {
+            title: Some(title.into()),
+            publish_year: Some(year),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn attaching_and_detaching_keeps_the_editions_apart() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let original = edition(&s, "Die Verwandlung", 1915).await;
+        let english = edition(&s, "The Metamorphosis", 1972).await;
+        let french = edition(&s, "La Métamorphose", 1938).await;
+
+        let work = s.attach_edition(english, original).await.unwrap();
+        assert_eq!(s.attach_edition(french, english).await.unwrap(), work);
+        let w = s.work_of(french).await.unwrap().unwrap();
+        assert_eq!(w.title, "Die Verwandlung");
+        let titles: Vec<_> = w.editions.iter().map(|b| b.display_title()).collect();
+        assert_eq!(
+            titles,
+            ["Die Verwandlung", "La Métamorphose", "The Metamorphosis"]
+        );
+
+        assert!(s.detach_edition(french).await.unwrap());
+        assert!(!s.detach_edition(french).await.unwrap());
+        assert!(s.work_of(french).await.unwrap().is_none());
+        assert_eq!(
+            s.work_of(original).await.unwrap().unwrap().editions.len(),
+            2
+        );
+
+        // One out of two leaves no work behind: a work of one is just a book.
+        s.detach_edition(original).await.unwrap();
+        assert_eq!(s.get_book(english).await.unwrap().unwrap().work_id, None);
+        let left: i64 = sqlx::query_scalar("SELECT count(*) FROM works")
+            .fetch_one(s.pool())
+            .await
+            .unwrap();
+        assert_eq!(left, 0);
+
+        assert!(matches!(
+            s.attach_edition(original, original).await,
+            Err(EngineError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            s.attach_edition(original, 999).await,
+            Err(EngineError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_work_key_groups_editions_without_moving_a_grouped_one() {
+        let s = Storage::connect("sqlite::memory:").await.unwrap();
+        let keyed = |title: &str, isbn: &str| Book {
+            title: Some(title.into()),
+            isbn_13: Some(isbn.into()),
+            openlibrary_work: Some("/works/OL1W".into()),
+            ..Default::default()
+        };
+        let a = s
+            .upsert_book(&keyed("Pachinko", "9781455563937"))
+            .await
+            .unwrap();
+        let b = s
+            .upsert_book(&keyed("Pachinko (Paperback)", "9781455563920"))
+            .await
+            .unwrap();
+        let w = s.work_of(a).await.unwrap().unwrap();
+        assert_eq!(w.openlibrary_key.as_deref(), Some("/works/OL1W"));
+        assert_eq!(w.editions.len(), 2);
+        assert_eq!(s.get_book(b).await.unwrap().unwrap().work_id, Some(w.id));
+
+        // Put by hand into another work, a book stays there when a provider
+        // names the first one again.
+        let other = edition(&s, "Something Else", 2000).await;
+        let moved = s.attach_edition(b, other).await.unwrap();
+        s.enrich_book(b, &keyed("Pachinko (Paperback)", "9781455563920"))
+            .await
+            .unwrap();
+        assert_eq!(s.get_book(b).await.unwrap().unwrap().work_id, Some(moved));
+    }
+}
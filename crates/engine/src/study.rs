@@ -0,0 +1,170 @@
+//! Study: spaced repetition over flashcards and chosen highlights.
+//!
+//! The scheduler is SM-2, the algorithm Anki grew out of, graded the way Anki
+//! grades — four buttons rather than SM-2's six qualities, because nobody can
+//! tell a 0 from a 1 at the end of a long review. [`Schedule::next`] is the
+//! whole of it and is pure; the storage side (`storage/study.rs`) only keeps
+//! the result per card and logs each grade.
+//!
+//! SM-2 and not FSRS: FSRS wants a few hundred reviews per user before its
+//! fitted weights beat SM-2's constants, and a reading log's card count is
+//! small. `study_log` keeps every grade with the schedule it produced, so a
+//! later scheduler can be fitted from it without a migration.
+
+/// How well the answer came, in Anki's four words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    /// Forgotten — the card starts over, due again tomorrow.
+    Again,
+    /// Recalled, with effort. Passes, but the card gets harder.
+    Hard,
+    Good,
+    /// Recalled without thinking. Passes, and the card gets easier.
+    Easy,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 4] = [Grade::Again, Grade::Hard, Grade::Good, Grade::Easy];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Grade::Again => "again",
+            Grade::Hard => "hard",
+            Grade::Good => "good",
+            Grade::Easy => "easy",
+        }
+    }
+
+    /// The name, or the 1–4 a review screen puts on the keys.
+    pub fn parse(s: &str) -> Option<Grade> {
+        let s = s.trim();
+        Grade::ALL
+            .into_iter()
+            .find(|g| g.as_str().eq_ignore_ascii_case(s) || s == (*g as usize + 1).to_string())
+    }
+
+    /// SM-2's 0–5 response quality. Again is a 1 rather than a 0 — both fail,
+    /// and the ease penalty for a 0 punishes a card for the reader's bad day.
+    fn quality(self) -> f64 {
+        match self {
+            Grade::Again => 1.0,
+            Grade::Hard => 3.0,
+            Grade::Good => 4.0,
+            Grade::Easy => 5.0,
+        }
+    }
+}
+
+/// SM-2's floor: below this a card comes back so often it is a chore, and
+/// raising the ease again takes a run of Easy the reader will not give it.
+pub const MIN_EASE: f64 = 1.3;
+
+/// One card's place in the schedule. A new card is [`Schedule::default`] and is
+/// due at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub ease: f64,
+    /// Days until it is due again, as the last grade decided.
+    pub interval_days: i64,
+    /// Passing grades in a row; a lapse sets it back to nought.
+    pub repetitions: i64,
+    /// Times forgotten after having been learnt.
+    pub lapses: i64,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule {
+            ease: 2.5,
+            interval_days: 0,
+            repetitions: 0,
+            lapses: 0,
+        }
+    }
+}
+
+impl Schedule {
+    /// The schedule after grading. A failing grade relearns from a one-day
+    /// interval; a pass steps 1 day, 6 days, then the last interval times the
+    /// ease. The ease moves on every grade, failing ones included.
+    pub fn next(&self, grade: Grade) -> Schedule {
+        let q = grade.quality();
+        let ease = (self.ease + (0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02))).max(MIN_EASE);
+        if grade == Grade::Again {
+            return Schedule {
+                ease,
+                interval_days: 1,
+                repetitions: 0,
+                lapses: self.lapses + i64::from(self.repetitions > 0),
+            };
+        }
+        let interval_days = match self.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => ((self.interval_days as f64) * ease).round().max(1.0) as i64,
+        };
+        Schedule {
+            ease,
+            interval_days,
+            repetitions: self.repetitions + 1,
+            lapses: self.lapses,
+        }
+    }
+
+    /// When a card graded at `now` with this schedule is next due.
+    pub fn due_after(&self, now: i64) -> i64 {
+        now + self.interval_days * 86_400
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_new_card_steps_one_day_six_days_then_by_ease() {
+        let first = Schedule::default().next(Grade::Good);
+        assert_eq!((first.interval_days, first.repetitions), (1, 1));
+        let second = first.next(Grade::Good);
+        assert_eq!(second.interval_days, 6);
+        let third = second.next(Grade::Good);
+        // Good leaves the ease where it was: 6 × 2.5.
+        assert_eq!(third.interval_days, 15);
+        assert!((third.ease - 2.5).abs() < 1e-9);
+        assert_eq!(third.due_after(1_000), 1_000 + 15 * 86_400);
+    }
+
+    #[test]
+    fn again_relearns_and_counts_a_lapse_only_once_learnt() {
+        let learnt = Schedule::default().next(Grade::Good).next(Grade::Good);
+        let lapsed = learnt.next(Grade::Again);
+        assert_eq!(
+            (lapsed.interval_days, lapsed.repetitions, lapsed.lapses),
+            (1, 0, 1)
+        );
+        assert!(lapsed.ease < learnt.ease);
+        // Failing a card never passed is not forgetting it.
+        assert_eq!(Schedule::default().next(Grade::Again).lapses, 0);
+    }
+
+    #[test]
+    fn hard_and_easy_move_the_ease_and_it_never_drops_below_the_floor() {
+        let s = Schedule::default();
+        assert!(s.next(Grade::Hard).ease < s.ease);
+        assert!(s.next(Grade::Easy).ease > s.ease);
+        let mut worn = s;
+        for _ in 0..20 {
+            worn = worn.next(Grade::Again);
+        }
+        assert_eq!(worn.ease, MIN_EASE);
+    }
+
+    #[test]
+    fn grades_parse_by_name_or_key() {
+        assert_eq!(Grade::parse("Good"), Some(Grade::Good));
+        assert_eq!(Grade::parse("1"), Some(Grade::Again));
+        assert_eq!(Grade::parse("4"), Some(Grade::Easy));
+        assert_eq!(Grade::parse("5"), None);
+        assert_eq!(Grade::parse("meh"), None);
+    }
+}
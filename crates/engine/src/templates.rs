@@ -0,0 +1,348 @@
+//! Note templates: markdown the reader keeps in `templates_dir`, expanded when
+//! a note is created.
+//!
+//! A template is an ordinary `.md` file, named by its path under the
+//! directory without the extension. Two kinds of name are looked for on their
+//! own:
+//!
+//! * `<kind>.md` — `note`, `session`, `reflection`, `review` — for every note
+//!   of that kind;
+//! * `<tag>/<kind>.md`, for a book carrying that tag, ahead of the plain one.
+//!   The tag is slugged the way the vault slugs folder names.
+//!
+//! Any other file is chosen by name, from the CLI's `note --template` or the
+//! TUI editor. A default applies only to a note created empty — a reflection
+//! or review being opened, a note composed from nothing — because a note that
+//! arrives with its words has already been written; a template chosen by name
+//! always applies, with the words at `{{body}}` or, without one, below it.
+//!
+//! Placeholders are `{{name}}`, spaces inside the braces allowed:
+//!
+//! | placeholder      | expands to                                        |
+//! |------------------|---------------------------------------------------|
+//! | `{{title}}`      | the note's title                                  |
+//! | `{{book}}`       | the book's title                                  |
+//! | `{{authors}}`    | its authors, comma-separated                      |
+//! | `{{started}}`    | the reading's start, `YYYY-MM-DD`                 |
+//! | `{{finished}}`   | its finish, empty while it is open                |
+//! | `{{rating}}`     | the reading's review rating, `4.5/5`              |
+//! | `{{page}}`       | the note's page, else the reading's current one   |
+//! | `{{highlights}}` | the reading's highlights, one blockquote each     |
+//! | `{{date}}`       | today, `YYYY-MM-DD`                               |
+//! | `{{body}}`       | what the note was created with                    |
+//!
+//! A value there is none of expands to nothing. A name not in the table is
+//! left exactly as written, so a template shared with Obsidian's Templater
+//! keeps the `{{…}}` that are Templater's.
+
+use std::path::{Path, PathBuf};
+
+use regex::{Captures, Regex};
+use time::OffsetDateTime;
+
+use crate::book::Book;
+use crate::error::{EngineError, Result};
+use crate::notes::{NewNoteInput, NoteKind, slugify};
+use crate::storage::{Highlight, Reading, Storage, format_day};
+
+/// How deep a template name may nest: `<tag>/<kind>` and a folder of one's
+/// own, and no further walk than that.
+const MAX_DEPTH: usize = 3;
+
+/// Every template under `dir`, by name, sorted.
+pub fn list(dir: &Path) -> Vec<String> {
+    let mut out = Vec::new();
+    collect(dir, dir, 0, &mut out);
+    out.sort();
+    out
+}
+
+fn collect(root: &Path, dir: &Path, depth: usize, out: &mut Vec<String>) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let p = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if p.is_dir() {
+            collect(root, &p, depth + 1, out);
+        } else if p.extension().is_some_and(|e| e == "md")
+            && let Ok(rel) = p.with_extension("").strip_prefix(root)
+        {
+            let parts: Vec<String> = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            out.push(parts.join("/"));
+        }
+    }
+}
+
+/// The file a name stands for. A name is a path under `dir`, never out of it.
+fn path_of(dir: &Path, name: &str) -> Result<PathBuf> {
+    let name = name.trim().trim_end_matches(".md");
+    if name.is_empty()
+        || name
+            .split(['/', '\\'])
+            .any(|p| p.is_empty() || p.starts_with('.'))
+    {
+        return Err(EngineError::InvalidInput(format!(
+            "'{name}' is not a template name"
+        )));
+    }
+    Ok(dir.join(format!("{name}.md")))
+}
+
+/// The template a note gets: the one named, or — for a note created empty —
+/// the default for its kind and its book's tags. `None` when there is none.
+pub(crate) async fn pick(
+    storage: &Storage,
+    dir: &Path,
+    name: Option<&str>,
+    book: Option<&Book>,
+    input: &NewNoteInput,
+) -> Result<Option<String>> {
+    if let Some(name) = name {
+        return match std::fs::read_to_string(path_of(dir, name)?) {
+            Ok(t) => Ok(Some(t)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let known = list(dir);
+                Err(EngineError::NotFound(if known.is_empty() {
+                    format!("no template '{name}': {} has none", dir.display())
+                } else {
+                    format!("no template '{name}' (there are: {})", known.join(", "))
+                }))
+            }
+            Err(e) => Err(e.into()),
+        };
+    }
+    if !input.body.trim().is_empty() {
+        return Ok(None);
+    }
+    let kind = input.kind.as_str();
+    let mut candidates = Vec::new();
+    if let Some(id) = book.and_then(|b| b.id) {
+        for t in storage.book_tags(id).await? {
+            candidates.push(dir.join(slugify(&t.tag)).join(format!("{kind}.md")));
+        }
+    }
+    candidates.push(dir.join(format!("{kind}.md")));
+    for path in candidates {
+        match std::fs::read_to_string(&path) {
+            Ok(t) => return Ok(Some(t)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(None)
+}
+
+/// What the placeholders stand for, gathered once per note.
+#[derive(Debug, Default)]
+pub(crate) struct Context {
+    title: String,
+    book: Option<Book>,
+    reading: Option<Reading>,
+    rating: Option<String>,
+    page: Option<i64>,
+    highlights: Vec<Highlight>,
+    body: String,
+}
+
+impl Context {
+    /// The reading a note is about: the one it is filed under, else the book's
+    /// open one, else its latest.
+    pub(crate) async fn gather(
+        storage: &Storage,
+        book: Option<&Book>,
+        input: &NewNoteInput,
+        title: &str,
+    ) -> Result<Context> {
+        let reading = match (input.reading_id, book.and_then(|b| b.id)) {
+            (Some(id), _) => storage.get_reading(id).await?,
+            (None, Some(book_id)) => match storage.active_reading(book_id).await? {
+                Some(r) => Some(r),
+                None => storage.list_readings(book_id).await?.pop(),
+            },
+            (None, None) => None,
+        };
+        let (rating, highlights) = match &reading {
+            Some(r) => {
+                let review = storage
+                    .note_for_reading(r.id, NoteKind::Review.as_str())
+                    .await?;
+                let rating = match review {
+                    Some(n) => storage
+                        .review_rating(n.id)
+                        .await?
+                        .map(|r| format!("{}/{}", r.value, r.scale.max)),
+                    None => None,
+                };
+                (rating, storage.highlights_for_reading(r.id).await?)
+            }
+            None => (None, Vec::new()),
+        };
+        let page = input
+            .page
+            .or(reading.as_ref().and_then(|r| r.current_page))
+            .or(book.and_then(|b| b.current_page));
+        Ok(Context {
+            title: title.to_string(),
+            book: book.cloned(),
+            reading,
+            rating,
+            page,
+            highlights,
+            body: input.body.trim_end().to_string(),
+        })
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        let day = |at: Option<i64>| {
+            at.and_then(|t| OffsetDateTime::from_unix_timestamp(t).ok())
+                .map(|t| format_day(t.date()))
+                .unwrap_or_default()
+        };
+        Some(match name {
+            "title" => self.title.clone(),
+            "book" => self
+                .book
+                .as_ref()
+                .map(|b| b.display_title().to_string())
+                .unwrap_or_default(),
+            "authors" => self
+                .book
+                .as_ref()
+                .map(|b| b.authors.join(", "))
+                .unwrap_or_default(),
+            "started" => day(self.reading.as_ref().and_then(|r| r.started_at)),
+            "finished" => day(self.reading.as_ref().and_then(|r| r.finished_at)),
+            "rating" => self.rating.clone().unwrap_or_default(),
+            "page" => self.page.map(|p| p.to_string()).unwrap_or_default(),
+            "highlights" => quoted(&self.highlights),
+            "date" => format_day(OffsetDateTime::now_utc().date()),
+            "body" => self.body.clone(),
+            _ => return None,
+        })
+    }
+}
+
+/// One blockquote per highlight, its page under it.
+fn quoted(highlights: &[Highlight]) -> String {
+    highlights
+        .iter()
+        .map(|h| {
+            let mut q: String = h.text.trim().lines().map(|l| format!("> {l}\n")).collect();
+            if let Some(p) = h.page {
+                q.push_str(&format!("> — p. {p}\n"));
+            }
+            q
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+/// `template` with its placeholders filled. The note's own words go at
+/// `{{body}}`, or after the template when it has no place for them.
+pub(crate) fn render(template: &str, ctx: &Context) -> String {
+    let re = Regex::new(r"\{\{\s*([a-z]+)\s*\}\}").expect("static regex");
+    let mut placed = false;
+    let out = re.replace_all(template, |c: &Captures| {
+        placed |= &c[1] == "body";
+        ctx.value(&c[1]).unwrap_or_else(|| c[0].to_string())
+    });
+    let out = out.trim_end();
+    if placed || ctx.body.is_empty() {
+        format!("{out}\n")
+    } else {
+        format!("{out}\n\n{}\n", ctx.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> Context {
+        Context {
+            title: "Reflection: Pachinko".into(),
+            book: Some(Book {
+                title: Some("Pachinko".into()),
+                authors: vec!["Min Jin Lee".into()],
+                ..Default::default()
+            }),
+            rating: Some("4.5/5".into()),
+            page: Some(40),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn placeholders_fill_and_strangers_are_left_alone() {
+        let t = "# {{title}}\n{{ book }} by {{authors}}, p. {{page}} — {{rating}}\n\
+                 started {{started}}; {{tp.date.now()}} {{unknown}}\n";
+        assert_eq!(
+            render(t, &ctx()),
+            "# Reflection: Pachinko\nPachinko by Min Jin Lee, p. 40 — 4.5/5\n\
+             started ; {{tp.date.now()}} {{unknown}}\n"
+        );
+    }
+
+    #[test]
+    fn the_body_goes_where_the_template_says_or_after_it() {
+        let with = Context {
+            body: "Han.".into(),
+            ..ctx()
+        };
+        assert_eq!(
+            render("## Notes\n{{body}}\n## End\n", &with),
+            "## Notes\nHan.\n## End\n"
+        );
+        assert_eq!(render("## Notes\n", &with), "## Notes\n\nHan.\n");
+    }
+
+    #[test]
+    fn highlights_are_quoted_with_their_pages() {
+        let h = |text: &str, page| Highlight {
+            id: 1,
+            book_id: 1,
+            text: text.into(),
+            chapter: None,
+            page,
+            ko_note: None,
+            annotation: None,
+            ko_datetime: None,
+            reading_id: None,
+            source: "koreader".into(),
+            created_at: 0,
+            device_id: None,
+        };
+        assert_eq!(
+            quoted(&[
+                h("History has failed us,\nbut no matter.", Some(40)),
+                h("Han.", None)
+            ]),
+            "> History has failed us,\n> but no matter.\n> — p. 40\n\n> Han."
+        );
+    }
+
+    #[test]
+    fn names_stay_inside_the_directory() {
+        let dir = Path::new("/t");
+        assert_eq!(
+            path_of(dir, "fiction/review").unwrap(),
+            dir.join("fiction/review.md")
+        );
+        assert_eq!(path_of(dir, "log.md").unwrap(), dir.join("log.md"));
+        for bad in ["", "../x", "a/../../b", "/abs", ".hidden"] {
+            assert!(path_of(dir, bad).is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,754 @@
+//! The vault, changed by someone other than us.
+//!
+//! The notes' home is the markdown in `vault_dir`; `notes`, `notes_fts` and
+//! `note_links` are an index of it. Every write readingbuddy makes keeps the two
+//! in step, but Obsidian, a sync client and `git pull` write too, and until now
+//! only an explicit `refresh_note_from_disk` would notice.
+//!
+//! [`refresh_paths`] is what a [`crate::watch::VaultWatcher`] batch is handed
+//! to. It decides what each settled path was — an edit, a new file, a file that
+//! left, a folder that moved — and brings the index back in line, returning one
+//! [`Diagnostic`] per note it changed. A path it has nothing to say about (a
+//! pasted image, our own save echoing back) produces nothing, which is what
+//! lets a frontend print every diagnostic it gets.
+//!
+//! [`reindex_vault`] is the same idea run over the whole vault at once, for the
+//! day `database/` is lost: every file's frontmatter is read back, the note is
+//! filed under its book again, and notes, links and citations are rebuilt.
+//!
+//! **Nothing here writes to the vault.** A file that appeared is indexed as it
+//! stands; frontmatter is read, never added.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::book::{Book, isbn10_to_13, normalize_isbn};
+use crate::diagnostic::Diagnostic;
+use crate::error::{EngineError, Result};
+use crate::notes::{self, NoteKind};
+use crate::storage::{Highlight, NewNoteMeta, Storage};
+
+/// How deep a folder that appeared in one piece is walked. A vault is a few
+/// levels deep; the cap is what a symlink loop runs into.
+const MAX_VAULT_DEPTH: usize = 16;
+
+/// Bring the index in line with whatever is now at `paths`.
+///
+/// Paths outside `vault_dir` are ignored. Vanished paths are handled before
+/// present ones, so a move whose two halves settle together finds the
+/// tombstone it is about to claim.
+pub async fn refresh_paths(
+    storage: &Storage,
+    vault_dir: &Path,
+    paths: &[PathBuf],
+) -> Result<Vec<Diagnostic>> {
+    let mut present = Vec::new();
+    let mut vanished = Vec::new();
+    for path in paths {
+        let path = if path.is_absolute() {
+            path.clone()
+        } else {
+            vault_dir.join(path)
+        };
+        let Some(rel) = relative(&path, vault_dir) else {
+            continue;
+        };
+        if path.is_dir() {
+            collect_markdown(&path, &mut present, 0);
+        } else if path.is_file() {
+            if is_markdown(&path) {
+                present.push(path);
+            }
+        } else {
+            // Gone — and nothing says whether it was a file or a folder, so it
+            // is asked both ways. A folder takes every note filed under it.
+            if is_markdown(&path) {
+                vanished.push(rel.clone());
+            }
+            vanished.extend(storage.note_paths_under(&rel).await?);
+        }
+    }
+    present.sort();
+    present.dedup();
+    vanished.sort();
+    vanished.dedup();
+
+    // Held back until the end: a tombstone claimed by a file in this same batch
+    // is reported once, as the move it was, rather than as a delete and a move.
+    let mut tombstoned: HashMap<i64, String> = HashMap::new();
+    for rel in vanished {
+        if let Some((id, false)) = storage.note_at_path(&rel).await?
+            && storage.tombstone_note(id).await?
+        {
+            tombstoned.insert(id, rel);
+        }
+    }
+
+    let mut out = Vec::new();
+    for path in present {
+        let Some(rel) = relative(&path, vault_dir) else {
+            continue;
+        };
+        let content = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                out.push(Diagnostic::note_unreadable(
+                    PathBuf::from(&rel),
+                    &EngineError::from(e),
+                ));
+                continue;
+            }
+        };
+        let (pairs, body) = notes::parse_frontmatter(&content);
+        let field = |k: &str| pairs.iter().find(|(key, _)| key == k).map(|(_, v)| v);
+
+        if let Some((id, was_tombstoned)) = storage.note_at_path(&rel).await? {
+            if was_tombstoned {
+                storage.revive_note(id, &rel).await?;
+            } else if storage
+                .indexed_note_body(id)
+                .await?
+                .is_some_and(|indexed| indexed.trim_end() == body.trim_end())
+            {
+                // Our own save, or a touch: the index already says this.
+                continue;
+            }
+            reindex(storage, id, body).await?;
+            // Left and came back at the same path within one batch is an
+            // editor's atomic save, and an edit is all it gets reported as.
+            tombstoned.remove(&id);
+            out.push(Diagnostic::note_refreshed(PathBuf::from(&rel)));
+            continue;
+        }
+
+        let created = field("created")
+            .and_then(|c| OffsetDateTime::parse(c, &Rfc3339).ok())
+            .map(|t| t.unix_timestamp());
+        let moved = match created {
+            Some(at) => storage.tombstone_created_at(at).await?,
+            None => None,
+        };
+        if let Some(id) = moved {
+            let from = match tombstoned.remove(&id) {
+                Some(from) => from,
+                None => storage
+                    .get_note(id)
+                    .await?
+                    .map(|n| n.file_path)
+                    .unwrap_or_default(),
+            };
+            storage.revive_note(id, &rel).await?;
+            reindex(storage, id, body).await?;
+            out.push(Diagnostic::note_moved(
+                PathBuf::from(from),
+                PathBuf::from(&rel),
+            ));
+            continue;
+        }
+
+        // A stranger. Its title is the one our frontmatter gave it, else its
+        // file name, which is what Obsidian calls it and therefore what a
+        // `[[wikilink]]` to it says.
+        let title = field("title").cloned().unwrap_or_else(|| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| rel.clone())
+        });
+        // Only the unanchored kinds: a reflection or a review belongs to a
+        // reading, and a file claiming to be one has not said which.
+        let kind = field("kind")
+            .and_then(|k| k.parse::<NoteKind>().ok())
+            .filter(|k| !k.is_anchored())
+            .unwrap_or_default();
+        let links = notes::extract_links(body);
+        storage
+            .insert_note(
+                NewNoteMeta {
+                    book_id: None,
+                    reading_id: None,
+                    highlight_id: None,
+                    page: field("page").and_then(|p| p.parse().ok()),
+                    location: field("location").map(String::as_str),
+                    file_path: &rel,
+                    title: &title,
+                    kind: kind.as_str(),
+                },
+                body,
+                &links,
+                &notes::extract_anchors(body),
+            )
+            .await?;
+        out.push(Diagnostic::note_indexed(PathBuf::from(&rel)));
+    }
+
+    let mut gone: Vec<(i64, String)> = tombstoned.into_iter().collect();
+    gone.sort();
+    out.extend(
+        gone.into_iter()
+            .map(|(_, rel)| Diagnostic::note_tombstoned(PathBuf::from(rel))),
+    );
+
+    let resolved = storage.resolve_dangling_links().await?;
+    if !out.is_empty() || resolved > 0 {
+        tracing::info!(changed = out.len(), resolved, "vault re-indexed");
+    }
+    Ok(out)
+}
+
+// ---- rebuilding the index from the vault ----------------------------------
+
+/// What [`reindex_vault`] did, or in a dry run would have done.
+#[derive(Debug, Default)]
+pub struct VaultReindexReport {
+    pub dry_run: bool,
+    /// Markdown files found, hidden folders aside.
+    pub files: usize,
+    /// Already in the index. Their search text and links are re-read anyway,
+    /// since a database that lost some rows may have lost those too.
+    pub kept: usize,
+    /// Written back into the index.
+    pub indexed: usize,
+    /// Of `indexed`, filed under a book again.
+    pub on_books: usize,
+    /// Citations written: a note's highlight anchor, and any highlight its body
+    /// quotes.
+    pub citations: usize,
+    /// Left out of the index. Running the rebuild again once the book is back
+    /// (a device sync, an import) places them.
+    pub unplaced: Vec<VaultFileIssue>,
+    /// Indexed, but without something their frontmatter named.
+    pub detached: Vec<VaultFileIssue>,
+}
+
+/// One file the rebuild has something to say about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultFileIssue {
+    /// Vault-relative.
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl VaultFileIssue {
+    fn new(rel: &str, reason: impl Into<String>) -> Self {
+        VaultFileIssue {
+            path: PathBuf::from(rel),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// The reading an anchored note will be filed under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Slot {
+    Reading(i64),
+    /// The book has no reading at all, so one is opened for it.
+    NewFor(i64),
+}
+
+/// Where one file goes: everything `create_note` wrote, read back.
+struct Placement {
+    book: Option<Book>,
+    slot: Option<Slot>,
+    /// Which time through the book, for a reflection's or a review's title.
+    nth: Option<usize>,
+    highlight_id: Option<i64>,
+    kind: NoteKind,
+}
+
+/// Rebuild `notes`, `note_links` and `citations` from the markdown in
+/// `vault_dir`: disaster recovery, for a database that was lost or corrupted.
+///
+/// Each file is filed under the book its frontmatter names — by ISBN, or by
+/// id when the book had none and the title beside it still agrees — and
+/// under its reading and highlight where those still exist. A file already
+/// indexed is kept and re-read. `dry_run` writes nothing and reports the same.
+///
+/// Citations live only in the database, so what comes back is what the files
+/// still show: a note anchored to a highlight cites it, and a `>` quote of one
+/// of the book's highlights cites that. One cited and never quoted is gone.
+pub async fn reindex_vault(
+    storage: &Storage,
+    vault_dir: &Path,
+    dry_run: bool,
+) -> Result<VaultReindexReport> {
+    let mut files = Vec::new();
+    collect_markdown(vault_dir, &mut files, 0);
+    files.sort();
+    let mut report = VaultReindexReport {
+        dry_run,
+        files: files.len(),
+        ..Default::default()
+    };
+
+    // Everything is read before anything is placed: a title lost with the
+    // database is partly recovered from what other notes link to it as.
+    let mut read = Vec::new();
+    for path in &files {
+        let Some(rel) = relative(path, vault_dir) else {
+            continue;
+        };
+        match std::fs::read_to_string(path) {
+            Ok(content) => read.push((rel, content)),
+            Err(e) => report
+                .unplaced
+                .push(VaultFileIssue::new(&rel, format!("unreadable: {e}"))),
+        }
+    }
+    let targets: HashSet<String> = read
+        .iter()
+        .flat_map(|(_, c)| notes::extract_wikilinks(notes::frontmatter_and_body(c).1))
+        .collect();
+    let mut targets: Vec<String> = targets.into_iter().collect();
+    targets.sort();
+
+    let mut highlights: HashMap<i64, Vec<Highlight>> = HashMap::new();
+    let mut claimed: HashMap<(Slot, &str), String> = HashMap::new();
+    for (rel, content) in &read {
+        let (pairs, body) = notes::parse_frontmatter(content);
+        let field = |k: &str| pairs.iter().find(|(key, _)| key == k).map(|(_, v)| v);
+
+        if let Some((id, tombstoned)) = storage.note_at_path(rel).await? {
+            report.kept += 1;
+            if !dry_run {
+                if tombstoned {
+                    storage.revive_note(id, rel).await?;
+                }
+                reindex(storage, id, body).await?;
+            }
+            if let Some(note) = storage.get_note(id).await?
+                && let Some(book_id) = note.book_id
+            {
+                let known = book_highlights(storage, &mut highlights, book_id).await?;
+                let have: Vec<i64> = storage
+                    .citations_for(id)
+                    .await?
+                    .iter()
+                    .map(|h| h.id)
+                    .collect();
+                for h in cited(note.highlight_id, body, known) {
+                    if !have.contains(&h) {
+                        if !dry_run {
+                            storage.add_citation(id, h).await?;
+                        }
+                        report.citations += 1;
+                    }
+                }
+            }
+            continue;
+        }
+
+        let placement = match place(storage, rel, &pairs, &mut highlights, &mut report).await? {
+            Ok(p) => p,
+            Err(reason) => {
+                report.unplaced.push(VaultFileIssue::new(rel, reason));
+                continue;
+            }
+        };
+        if let Some(slot) = placement.slot.filter(|_| placement.kind.is_anchored()) {
+            if let Some(other) = claimed.get(&(slot, placement.kind.as_str())) {
+                report.unplaced.push(VaultFileIssue::new(
+                    rel,
+                    format!(
+                        "its reading already has a {}: {other}",
+                        placement.kind.as_str()
+                    ),
+                ));
+                continue;
+            }
+            claimed.insert((slot, placement.kind.as_str()), rel.clone());
+        }
+
+        let title = recover_title(rel, field("title"), body, &placement, &targets);
+        let created = field("created")
+            .and_then(|c| OffsetDateTime::parse(c, &Rfc3339).ok())
+            .map(|t| t.unix_timestamp());
+        let quoted = match &placement.book {
+            Some(b) => {
+                let id = b.id.unwrap_or_default();
+                cited(
+                    placement.highlight_id,
+                    body,
+                    book_highlights(storage, &mut highlights, id).await?,
+                )
+            }
+            None => Vec::new(),
+        };
+        report.indexed += 1;
+        report.on_books += usize::from(placement.book.is_some());
+        report.citations += quoted.len();
+        if dry_run {
+            continue;
+        }
+
+        let reading_id = match placement.slot {
+            Some(Slot::Reading(id)) => Some(id),
+            Some(Slot::NewFor(book_id)) => {
+                Some(storage.ensure_reading(book_id, created, "manual").await?)
+            }
+            None => None,
+        };
+        let links = notes::extract_links(body);
+        let id = storage
+            .insert_note(
+                NewNoteMeta {
+                    book_id: placement.book.as_ref().and_then(|b| b.id),
+                    reading_id,
+                    highlight_id: placement.highlight_id,
+                    page: field("page").and_then(|p| p.parse().ok()),
+                    location: field("location").map(String::as_str),
+                    file_path: rel,
+                    title: &title,
+                    kind: placement.kind.as_str(),
+                },
+                body,
+                &links,
+                &notes::extract_anchors(body),
+            )
+            .await?;
+        if let Some(at) = created {
+            storage.restore_note_created(id, at).await?;
+        }
+        for h in quoted {
+            storage.add_citation(id, h).await?;
+        }
+    }
+
+    if !dry_run {
+        storage.resolve_dangling_links().await?;
+    }
+    tracing::info!(
+        dry_run,
+        files = report.files,
+        kept = report.kept,
+        indexed = report.indexed,
+        unplaced = report.unplaced.len(),
+        "vault reindexed"
+    );
+    Ok(report)
+}
+
+/// File one note: its book, reading and highlight, as far as they still exist.
+/// `Err` is the reason it cannot be filed at all; an anchor that is merely gone
+/// is dropped and reported in `report.detached`.
+async fn place(
+    storage: &Storage,
+    rel: &str,
+    pairs: &[(String, String)],
+    highlights: &mut HashMap<i64, Vec<Highlight>>,
+    report: &mut VaultReindexReport,
+) -> Result<std::result::Result<Placement, String>> {
+    let field = |k: &str| pairs.iter().find(|(key, _)| key == k).map(|(_, v)| v);
+    let kind = match field("kind") {
+        Some(k) => match k.parse::<NoteKind>() {
+            Ok(kind) => kind,
+            Err(_) => {
+                report.detached.push(VaultFileIssue::new(
+                    rel,
+                    format!("unknown kind {k}; filed as a note"),
+                ));
+                NoteKind::Note
+            }
+        },
+        None => NoteKind::Note,
+    };
+    let book = match field("book") {
+        Some(key) => match find_book(storage, key, field("book-title")).await? {
+            Ok(book) => Some(book),
+            Err(reason) => return Ok(Err(reason)),
+        },
+        None => None,
+    };
+    let Some(book) = book else {
+        if kind.is_anchored() {
+            return Ok(Err(format!(
+                "a {} belongs to a book, and this one names none",
+                kind.as_str()
+            )));
+        }
+        return Ok(Ok(Placement {
+            book: None,
+            slot: None,
+            nth: None,
+            highlight_id: None,
+            kind,
+        }));
+    };
+    let book_id = book.id.unwrap_or_default();
+
+    let readings = storage.list_readings(book_id).await?;
+    let named = field("reading").and_then(|r| r.parse::<i64>().ok());
+    let mut slot = named
+        .filter(|id| readings.iter().any(|r| r.id == *id))
+        .map(Slot::Reading);
+    if let (Some(id), None) = (named, slot) {
+        report
+            .detached
+            .push(VaultFileIssue::new(rel, format!("reading {id} is gone")));
+    }
+    if slot.is_none() && kind.is_anchored() {
+        // A reflection has to be filed under *some* reading: the one that was
+        // under way when it was written, else the book's latest.
+        let created = field("created")
+            .and_then(|c| OffsetDateTime::parse(c, &Rfc3339).ok())
+            .map(|t| t.unix_timestamp());
+        let during = readings
+            .iter()
+            .filter(|r| match (created, r.started_at) {
+                (Some(at), Some(start)) => start <= at,
+                _ => true,
+            })
+            .max_by_key(|r| (r.started_at, r.id))
+            .or_else(|| readings.iter().max_by_key(|r| (r.started_at, r.id)));
+        slot = Some(match during {
+            Some(r) => Slot::Reading(r.id),
+            None => Slot::NewFor(book_id),
+        });
+    }
+    if let Some(Slot::Reading(id)) = slot
+        && kind.is_anchored()
+        && let Some(existing) = storage.note_for_reading(id, kind.as_str()).await?
+    {
+        return Ok(Err(format!(
+            "its reading already has a {}: {}",
+            kind.as_str(),
+            existing.file_path
+        )));
+    }
+    let nth = match slot {
+        Some(Slot::Reading(id)) => readings.iter().position(|r| r.id == id).map(|i| i + 1),
+        _ => None,
+    };
+
+    // A note written from a highlight took its page and chapter, which is what
+    // tells the highlight it named from another that now has its id.
+    let named = field("highlight").and_then(|h| h.parse::<i64>().ok());
+    let page = field("page").and_then(|p| p.parse::<i64>().ok());
+    let location = field("location");
+    let agrees = |h: &&Highlight| {
+        page.is_none_or(|p| h.page == Some(p))
+            && location.is_none_or(|l| h.chapter.as_deref() == Some(l.as_str()))
+    };
+    let highlight_id = match named {
+        Some(id) => {
+            let known = book_highlights(storage, highlights, book_id).await?;
+            if known.iter().filter(agrees).any(|h| h.id == id) {
+                Some(id)
+            } else {
+                // Numbered afresh with the library, most likely: the one
+                // highlight on its page is the one it was written from.
+                let mut here = known.iter().filter(agrees);
+                match (here.next(), here.next()) {
+                    (Some(h), None) if page.is_some() || location.is_some() => Some(h.id),
+                    _ => {
+                        report
+                            .detached
+                            .push(VaultFileIssue::new(rel, format!("highlight {id} is gone")));
+                        None
+                    }
+                }
+            }
+        }
+        None => None,
+    };
+
+    Ok(Ok(Placement {
+        book: Some(book),
+        slot,
+        nth,
+        highlight_id,
+        kind,
+    }))
+}
+
+/// The book a frontmatter `book:` names. `create_note` writes the ISBN when
+/// the book has one and the id when it does not; an id is only trusted when
+/// the `book-title` beside it agrees, because a rebuilt library numbers its
+/// books afresh.
+async fn find_book(
+    storage: &Storage,
+    key: &str,
+    title: Option<&String>,
+) -> Result<std::result::Result<Book, String>> {
+    let named = match title {
+        Some(t) => format!("book {key} (\"{t}\")"),
+        None => format!("book {key}"),
+    };
+    if let Some(isbn) = normalize_isbn(key) {
+        if let Some(book) = storage.find_book_by_isbn(&isbn).await? {
+            return Ok(Ok(book));
+        }
+        if let Some(book) = match isbn10_to_13(&isbn) {
+            Some(isbn13) => storage.find_book_by_isbn(&isbn13).await?,
+            None => None,
+        } {
+            return Ok(Ok(book));
+        }
+        return Ok(Err(format!("{named} is not in the library")));
+    }
+    let Ok(id) = key.parse::<i64>() else {
+        return Ok(Err(format!("{named} is neither an ISBN nor a book id")));
+    };
+    match storage.get_book(id).await? {
+        Some(book)
+            if title.is_none_or(|t| {
+                t.eq_ignore_ascii_case(&book.display_title().replace('"', "'"))
+            }) =>
+        {
+            Ok(Ok(book))
+        }
+        Some(book) => Ok(Err(format!(
+            "{named}: book {id} is now \"{}\"",
+            book.display_title()
+        ))),
+        None => Ok(Err(format!("{named} is not in the library"))),
+    }
+}
+
+/// The title a note had. The frontmatter says it in files written since it
+/// was added there; before that, a reflection's follows from its book, and any
+/// other note's is whichever of its leading words or the links naming it
+/// slugify back to its file name. The slug itself is the last resort.
+fn recover_title(
+    rel: &str,
+    title: Option<&String>,
+    body: &str,
+    placement: &Placement,
+    targets: &[String],
+) -> String {
+    if let Some(t) = title.filter(|t| !t.is_empty()) {
+        return t.clone();
+    }
+    if let (true, Some(book)) = (placement.kind.is_anchored(), &placement.book) {
+        return notes::anchored_title(placement.kind, book, placement.nth);
+    }
+    let stem = Path::new(rel)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    // `create_note` names a file `<yyyymmddhhmmss>-<slug>[-n].md`. Anything
+    // else was named by hand, and its name is its title.
+    let Some(slug) = stem
+        .split_once('-')
+        .filter(|(stamp, _)| stamp.len() == 14 && stamp.bytes().all(|b| b.is_ascii_digit()))
+        .map(|(_, slug)| slug)
+    else {
+        return stem;
+    };
+    let unnumbered = slug
+        .rsplit_once('-')
+        .filter(|(_, n)| n.parse::<u32>().is_ok())
+        .map(|(base, _)| base);
+    let fits = |t: &str| {
+        let s = notes::slugify(t);
+        s == slug || Some(s.as_str()) == unnumbered
+    };
+    let derived = notes::derive_title(body);
+    if fits(&derived) {
+        return derived;
+    }
+    if let Some(t) = targets.iter().find(|t| fits(t)) {
+        return t.clone();
+    }
+    slug.replace('-', " ")
+}
+
+/// The highlights a note cites, as far as its file shows: its anchor, and each
+/// `>` quote whose text is one of the book's highlights, whitespace aside.
+fn cited(anchor: Option<i64>, body: &str, highlights: &[Highlight]) -> Vec<i64> {
+    let mut out: Vec<i64> = anchor.into_iter().collect();
+    let mut quote: Vec<&str> = Vec::new();
+    let mut quotes = Vec::new();
+    // The empty line chained on the end closes a quote the body ends in.
+    for line in body.lines().chain(std::iter::once("")) {
+        if let Some(rest) = line.trim_start().strip_prefix('>') {
+            let rest = rest.trim_start_matches(['>', ' ']);
+            // A callout's first line (`> [!quote]`) is its label, not text.
+            if !rest.starts_with("[!") {
+                quote.push(rest);
+            }
+        } else if !quote.is_empty() {
+            quotes.push(normalized(&quote.join(" ")));
+            quote.clear();
+        }
+    }
+    for h in highlights {
+        if !out.contains(&h.id) && quotes.contains(&normalized(&h.text)) {
+            out.push(h.id);
+        }
+    }
+    out
+}
+
+fn normalized(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// One book's highlights, read once per rebuild.
+async fn book_highlights<'a>(
+    storage: &Storage,
+    cache: &'a mut HashMap<i64, Vec<Highlight>>,
+    book_id: i64,
+) -> Result<&'a [Highlight]> {
+    Ok(match cache.entry(book_id) {
+        Entry::Occupied(e) => e.into_mut(),
+        Entry::Vacant(e) => e.insert(storage.list_highlights(book_id).await?),
+    })
+}
+
+/// Re-read one note's body into the search index and its edges into the graph.
+async fn reindex(storage: &Storage, id: i64, body: &str) -> Result<()> {
+    let Some(note) = storage.get_note(id).await? else {
+        return Ok(());
+    };
+    storage.refresh_note_body(id, &note.title, body).await?;
+    let links = notes::extract_links(body);
+    storage
+        .set_note_links(id, &note.title, &links, &notes::extract_anchors(body))
+        .await
+}
+
+/// `notes.file_path` for an absolute path: vault-relative, `/`-separated, as
+/// `create_note` writes it.
+pub(crate) fn relative(path: &Path, vault_dir: &Path) -> Option<String> {
+    let rest = path.strip_prefix(vault_dir).ok()?;
+    let parts: Vec<String> = rest
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("md"))
+}
+
+/// Every markdown file under `dir`, skipping hidden directories (`.obsidian`,
+/// `.trash`) and never following a symlinked one.
+pub(crate) fn collect_markdown(dir: &Path, out: &mut Vec<PathBuf>, depth: usize) {
+    if depth >= MAX_VAULT_DEPTH {
+        tracing::warn!(path = %dir.display(), depth, "vault walk hit its depth cap");
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let p = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let is_link = entry.file_type().is_ok_and(|t| t.is_symlink());
+        if p.is_dir() {
+            if !is_link {
+                collect_markdown(&p, out, depth + 1);
+            }
+        } else if is_markdown(&p) {
+            out.push(p);
+        }
+    }
+}
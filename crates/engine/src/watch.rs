@@ -1,4 +1,11 @@
-//! Noticing a reader without being asked.
+//! Noticing a reader — or an edited note — without being asked.
+//!
+//! Two watchers share one debounce ([`Debounce`]): [`MountWatcher`] for readers
+//! arriving and leaving, and [`VaultWatcher`] for markdown files changed in the
+//! vault by something other than us (Obsidian, a sync client, `git pull`). Both
+//! announce settled paths and nothing more; what a settled vault path *means*
+//! is `Engine::refresh_vault_paths`' business, for the reason the last
+//! paragraph below gives.
 //!
 //! Everything a mounted device needs is already here — [`crate::device`] has
 //! `scan_device`, `sync_device`, `candidate_mounts`, `is_koreader_mount` and the
@@ -21,8 +28,10 @@
 //! [`crate::storage::Storage`] at all: it announces arrivals and departures, and
 //! what the frontend does about one is the frontend's decision. Nothing here can
 //! write to a device or to the library, by construction rather than by rule.
+//! The vault watcher keeps to the same line: it holds no `Storage` either.
 
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
@@ -44,6 +53,14 @@ use crate::error::{EngineError, Result};
 /// could differ.
 pub const MOUNT_QUIET: Duration = Duration::from_secs(2);
 
+/// How long a vault file has to hold still before it is re-read.
+///
+/// Shorter than [`MOUNT_QUIET`]: a note is one file, not a volume flushing a
+/// library, and the user who just saved in Obsidian is waiting to see it
+/// searchable. Long enough that an editor's write-to-temp-then-rename lands as
+/// one change rather than three.
+pub const VAULT_QUIET: Duration = Duration::from_secs(1);
+
 /// How many raw stirs may queue before the oldest are dropped.
 ///
 /// Dropping is safe here and nowhere else in the codebase: every stir means only
@@ -58,7 +75,7 @@ const STIR_CAPACITY: usize = 256;
 /// themselves (`/Volumes`) — never a file deep inside a volume. Normalizing to
 /// that is the adapter's job, because the adapter is what knows which roots it
 /// asked to be told about.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct MountStir(pub PathBuf);
 
 /// What the watcher announces, once a volume has settled and been checked.
@@ -78,6 +95,89 @@ impl MountEvent {
     }
 }
 
+/// Per-key quiet periods over a channel of stirs: the half of a watcher that
+/// has nothing to do with what is being watched.
+///
+/// Each key's burst is timed on its own, so a reader still flushing does not
+/// hold back one that has finished, and a note being typed into does not hold
+/// back the one saved beside it.
+struct Debounce<T> {
+    stirs: mpsc::Receiver<T>,
+    quiet: Duration,
+    /// Keys whose burst has not finished, when it will have, and the stir
+    /// that last armed it.
+    settling: HashMap<T, (Instant, u64)>,
+    stirred: u64,
+}
+
+impl<T: Hash + Eq + Clone> Debounce<T> {
+    fn new(stirs: mpsc::Receiver<T>, quiet: Duration) -> Self {
+        Debounce {
+            stirs,
+            quiet,
+            settling: HashMap::new(),
+            stirred: 0,
+        }
+    }
+
+    /// The next keys to have held still, in the order their bursts finished,
+    /// or `None` once the source is gone and nothing is left settling.
+    ///
+    /// Cancel-safe: every deadline lives in `self`, and the batch is taken out
+    /// of `settling` only in the same synchronous step that returns it.
+    async fn next(&mut self) -> Option<Vec<T>> {
+        loop {
+            let Some(deadline) = self.settling.values().map(|(at, _)| *at).min() else {
+                // Nothing is settling, so there is nothing to wake up for.
+                let stir = self.stirs.recv().await?;
+                self.stir(stir);
+                continue;
+            };
+            // `timeout_at` rather than a `select!`: the deadline is a property of
+            // the watcher, not of this call, so there is nothing to race that a
+            // cancelled call would take with it.
+            match tokio::time::timeout_at(deadline, self.stirs.recv()).await {
+                Ok(Some(stir)) => self.stir(stir),
+                // The source is gone, but a key stirred a moment before it died
+                // still stirred. Wait the burst out and decide it; the empty
+                // `settling` on a later pass is what returns `None`.
+                Ok(None) => {
+                    tokio::time::sleep_until(deadline).await;
+                    return Some(self.due());
+                }
+                Err(_) => return Some(self.due()),
+            }
+        }
+    }
+
+    /// Arm — or re-arm — the quiet period for a key.
+    fn stir(&mut self, key: T) {
+        self.stirred += 1;
+        self.settling
+            .insert(key, (Instant::now() + self.quiet, self.stirred));
+    }
+
+    /// Take every key whose quiet period has run out.
+    fn due(&mut self) -> Vec<T> {
+        let now = Instant::now();
+        // Ordered by when each burst finished, so two devices plugged in one
+        // after the other are announced in that order rather than in whatever
+        // order the map iterated. Ties — two stirs in one instant, which is
+        // what a rename is — break on the order they were stirred in.
+        let mut due: Vec<((Instant, u64), T)> = self
+            .settling
+            .iter()
+            .filter(|(_, (at, _))| *at <= now)
+            .map(|(key, when)| (*when, key.clone()))
+            .collect();
+        due.sort_by_key(|(when, _)| *when);
+        for (_, key) in &due {
+            self.settling.remove(key);
+        }
+        due.into_iter().map(|(_, key)| key).collect()
+    }
+}
+
 /// Debounces raw filesystem stirs into readers arriving and leaving.
 ///
 /// Cancel-safe: [`MountWatcher::next`] holds no state of its own, so a
@@ -85,10 +185,7 @@ impl MountEvent {
 /// same already-decided verdicts on the next call. It is dropped that way on
 /// every keypress in the TUI's event loop, so this is not a theoretical claim.
 pub struct MountWatcher {
-    stirs: mpsc::Receiver<MountStir>,
-    quiet: Duration,
-    /// Paths whose burst has not finished, and when it will have.
-    settling: HashMap<PathBuf, Instant>,
+    debounce: Debounce<MountStir>,
     /// Mounts already announced. This is what makes a second stir about a volume
     /// that is still plugged in cost nothing — one arrival per arrival, however
     /// many events the platform decided to send.
@@ -110,9 +207,7 @@ impl MountWatcher {
     /// A watcher driven by a channel — the seam every test uses.
     pub fn from_stirs(stirs: mpsc::Receiver<MountStir>) -> Self {
         MountWatcher {
-            stirs,
-            quiet: MOUNT_QUIET,
-            settling: HashMap::new(),
+            debounce: Debounce::new(stirs, MOUNT_QUIET),
             present: HashSet::new(),
             decided: VecDeque::new(),
             roots: Vec::new(),
@@ -140,7 +235,7 @@ impl MountWatcher {
 
     /// Shorten the quiet period. Tests only — see [`MOUNT_QUIET`].
     pub fn quiet_for(mut self, quiet: Duration) -> Self {
-        self.quiet = quiet;
+        self.debounce.quiet = quiet;
         self
     }
 
@@ -156,51 +251,14 @@ impl MountWatcher {
             if let Some(event) = self.decided.pop_front() {
                 return Some(event);
             }
-            let Some(deadline) = self.settling.values().min().copied() else {
-                // Nothing is settling, so there is nothing to wake up for.
-                let stir = self.stirs.recv().await?;
-                self.stir(stir);
-                continue;
-            };
-            // `timeout_at` rather than a `select!`: the deadline is a property of
-            // the watcher, not of this call, so there is nothing to race that a
-            // cancelled call would take with it.
-            match tokio::time::timeout_at(deadline, self.stirs.recv()).await {
-                Ok(Some(stir)) => self.stir(stir),
-                // The source is gone, but a volume that arrived a moment before
-                // it died still arrived. Wait the burst out and decide it; the
-                // empty `settling` on the next pass is what returns `None`.
-                Ok(None) => {
-                    tokio::time::sleep_until(deadline).await;
-                    self.settle();
-                }
-                Err(_) => self.settle(),
-            }
+            let due = self.debounce.next().await?;
+            self.settle(due);
         }
     }
 
-    /// Arm — or re-arm — the quiet period for a path.
-    fn stir(&mut self, MountStir(path): MountStir) {
-        self.settling.insert(path, Instant::now() + self.quiet);
-    }
-
     /// Decide every path whose quiet period has run out.
-    fn settle(&mut self) {
-        let now = Instant::now();
-        // Ordered by when each burst finished, so two devices plugged in one
-        // after the other are announced in that order rather than in whatever
-        // order the map iterated. Ties break on the path, which is arbitrary but
-        // at least the same arbitrary every time.
-        let mut due: Vec<(Instant, PathBuf)> = self
-            .settling
-            .iter()
-            .filter(|(_, at)| **at <= now)
-            .map(|(path, at)| (*at, path.clone()))
-            .collect();
-        due.sort();
-
-        for (_, path) in due {
-            self.settling.remove(&path);
+    fn settle(&mut self, due: Vec<MountStir>) {
+        for MountStir(path) in due {
             if self.roots.contains(&path) {
                 for volume in self.volumes_under(&path) {
                     self.decide(volume);
@@ -325,6 +383,109 @@ fn normalize(path: &Path, roots: &[PathBuf]) -> Option<PathBuf> {
     None
 }
 
+/// A raw "something happened at this path" inside the vault. Absolute, and
+/// never under a hidden directory — [`watch_vault`] filters those out.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VaultStir(pub PathBuf);
+
+/// Debounces edits in the vault into batches of paths that have settled.
+///
+/// A batch is paths, not verdicts: a path may be a note that changed, a note
+/// that vanished, a folder that moved, or a picture pasted beside a note.
+/// Telling those apart needs the index, which this does not hold — see the
+/// module doc. Cancel-safe for the same reason [`MountWatcher`] is: the
+/// deadlines live in the shared [`Debounce`].
+pub struct VaultWatcher {
+    debounce: Debounce<VaultStir>,
+    /// Keeps the platform watcher alive; see [`MountWatcher`].
+    _source: Option<notify::RecommendedWatcher>,
+}
+
+impl VaultWatcher {
+    /// A watcher driven by a channel — the seam every test uses.
+    pub fn from_stirs(stirs: mpsc::Receiver<VaultStir>) -> Self {
+        VaultWatcher {
+            debounce: Debounce::new(stirs, VAULT_QUIET),
+            _source: None,
+        }
+    }
+
+    /// Shorten the quiet period. Tests only — see [`VAULT_QUIET`].
+    pub fn quiet_for(mut self, quiet: Duration) -> Self {
+        self.debounce.quiet = quiet;
+        self
+    }
+
+    /// The next paths to have held still, in the order they settled, or `None`
+    /// once the source is gone.
+    ///
+    /// The order matters for a move: its two halves are stirred old path first,
+    /// so the vanished file is seen no later than the one that appeared, and
+    /// the tombstone is there for the arrival to claim.
+    pub async fn next(&mut self) -> Option<Vec<PathBuf>> {
+        let due = self.debounce.next().await?;
+        Some(due.into_iter().map(|VaultStir(path)| path).collect())
+    }
+}
+
+/// Watch `vault_dir`, recursively, for files changed by anything at all.
+///
+/// Our own saves are seen too, and that is harmless rather than wasted: the
+/// engine compares what is on disk with what it last indexed and reports
+/// nothing when they agree. Fails when the platform cannot watch — which, as
+/// with [`watch_mounts`], a caller degrades around.
+pub fn watch_vault(vault_dir: &Path) -> Result<VaultWatcher> {
+    std::fs::create_dir_all(vault_dir)?;
+    let root = vault_dir.to_path_buf();
+    let (tx, rx) = mpsc::channel(STIR_CAPACITY);
+    let watched = root.clone();
+
+    let mut source = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            // As for mounts: the next stir re-reads the file regardless.
+            return;
+        };
+        // Access events change nothing, and an editor that merely opens a note
+        // produces a stream of them.
+        if matches!(event.kind, notify::EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            if !in_vault(&path, &watched) {
+                continue;
+            }
+            // Full means a burst is already queued; the path will be stirred
+            // again by the rest of it, or re-read by the next reindex.
+            let _ = tx.try_send(VaultStir(path));
+        }
+    })
+    .map_err(|e| EngineError::Watch(e.to_string()))?;
+    notify::Watcher::watch(&mut source, &root, notify::RecursiveMode::Recursive)
+        .map_err(|e| EngineError::Watch(format!("{}: {e}", root.display())))?;
+
+    tracing::info!(vault = %root.display(), "watching the vault");
+    Ok(VaultWatcher {
+        _source: Some(source),
+        ..VaultWatcher::from_stirs(rx)
+    })
+}
+
+/// Is this path one of the vault's own, rather than the vault root or
+/// something under a hidden directory?
+///
+/// Hidden is `.obsidian/` (which Obsidian rewrites whenever a pane moves),
+/// `.trash/` and `.git/`. A note moved into `.trash` is still seen leaving —
+/// the old path is not hidden — which is exactly what a delete should look like.
+fn in_vault(path: &Path, root: &Path) -> bool {
+    let Ok(rest) = path.strip_prefix(root) else {
+        return false;
+    };
+    !rest.as_os_str().is_empty()
+        && rest
+            .components()
+            .all(|c| !c.as_os_str().to_string_lossy().starts_with('.'))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -671,6 +832,55 @@ mod tests {
         );
     }
 
+    /// A save is a burst too — an editor writes a temp file and renames it over
+    /// the note — and the two halves of a move settle in the order they were
+    /// stirred, old path first.
+    #[tokio::test(start_paused = true)]
+    async fn vault_edits_settle_as_one_batch_in_the_order_they_were_made() {
+        let (tx, rx) = mpsc::channel(16);
+        let mut watcher = VaultWatcher::from_stirs(rx).quiet_for(QUIET);
+        let note = PathBuf::from("/vault/unsorted/han.md");
+        for _ in 0..4 {
+            tx.send(VaultStir(note.clone())).await.unwrap();
+        }
+        assert_eq!(
+            tokio::time::timeout(QUIET * 2, watcher.next())
+                .await
+                .unwrap(),
+            Some(vec![note.clone()]),
+        );
+
+        let moved = PathBuf::from("/vault/pachinko/han.md");
+        tx.send(VaultStir(note.clone())).await.unwrap();
+        tx.send(VaultStir(moved.clone())).await.unwrap();
+        let mut seen = Vec::new();
+        while seen.len() < 2 {
+            let batch = tokio::time::timeout(QUIET * 2, watcher.next())
+                .await
+                .unwrap()
+                .unwrap();
+            seen.extend(batch);
+        }
+        assert_eq!(seen, [note, moved]);
+
+        drop(tx);
+        assert_eq!(watcher.next().await, None);
+    }
+
+    #[test]
+    fn hidden_directories_and_the_root_are_not_the_vault() {
+        let root = Path::new("/vault");
+        assert!(in_vault(Path::new("/vault/pachinko/han.md"), root));
+        assert!(in_vault(Path::new("/vault/Vol. 2"), root));
+        assert!(!in_vault(Path::new("/vault"), root));
+        assert!(!in_vault(
+            Path::new("/vault/.obsidian/workspace.json"),
+            root
+        ));
+        assert!(!in_vault(Path::new("/vault/.trash/han.md"), root));
+        assert!(!in_vault(Path::new("/elsewhere/han.md"), root));
+    }
+
     /// A page turn rewrites a sidecar deep inside the volume. Reducing that to
     /// the volume is what stops a reader being rescanned on every page turn —
     /// and a path outside the roots is not ours at all.
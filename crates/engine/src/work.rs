@@ -0,0 +1,84 @@
+//! A book read as a work: every edition's readings, and the rating, in one.
+//!
+//! Editions stay separate rows — the ISBN, the page count and the page a
+//! highlight sits on all belong to one printing — so nothing here is stored.
+//! "Have I read this?" is asked of the work: reading the translation counts for
+//! the original, and the paperback's rating is the ebook's too.
+
+use crate::book::Book;
+use crate::error::{EngineError, Result};
+use crate::notes::NoteKind;
+use crate::storage::{Rating, Reading, STATUS_FINISHED, Storage, Work};
+
+/// One book and everything read of it in any edition.
+#[derive(Debug, Clone)]
+pub struct WorkHistory {
+    /// The work, or `None` for a book in no work — which is a work of one.
+    pub work: Option<Work>,
+    /// Every edition, the asked-about one included. Just that one for a book
+    /// in no work.
+    pub editions: Vec<Book>,
+    /// Every edition's readings, oldest first — `list_readings`' order, across
+    /// the editions.
+    pub readings: Vec<Reading>,
+    /// The rating on the latest rated reading, in whichever edition.
+    pub rating: Option<Rating>,
+}
+
+impl WorkHistory {
+    /// Whether any edition has been read to the end.
+    pub fn has_read(&self) -> bool {
+        self.last_finished().is_some()
+    }
+
+    /// The edition most recently finished, if any was.
+    pub fn read_as(&self) -> Option<&Book> {
+        let r = self.last_finished()?;
+        self.editions.iter().find(|b| b.id == Some(r.book_id))
+    }
+
+    fn last_finished(&self) -> Option<&Reading> {
+        self.readings
+            .iter()
+            .filter(|r| r.status == STATUS_FINISHED)
+            .max_by_key(|r| (r.finished_at, r.id))
+    }
+}
+
+pub(crate) async fn history(storage: &Storage, book_id: i64) -> Result<WorkHistory> {
+    let Some(book) = storage.get_book(book_id).await? else {
+        return Err(EngineError::NotFound(format!("book id {book_id}")));
+    };
+    let work = storage.work_of(book_id).await?;
+    let editions = match &work {
+        Some(w) => w.editions.clone(),
+        None => vec![book],
+    };
+
+    let mut readings = Vec::new();
+    for id in editions.iter().filter_map(|b| b.id) {
+        readings.extend(storage.list_readings(id).await?);
+    }
+    readings.sort_by_key(|r| (r.started_at.unwrap_or(r.created_at), r.id));
+
+    let mut rating = None;
+    for r in readings.iter().rev() {
+        let Some(note) = storage
+            .note_for_reading(r.id, NoteKind::Review.as_str())
+            .await?
+        else {
+            continue;
+        };
+        if let Some(found) = storage.review_rating(note.id).await? {
+            rating = Some(found);
+            break;
+        }
+    }
+
+    Ok(WorkHistory {
+        work,
+        editions,
+        readings,
+        rating,
+    })
+}
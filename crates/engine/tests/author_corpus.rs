@@ -0,0 +1,127 @@
+//! The author corpus through the facade: one person's books under every
+//! spelling, with what was kept from each.
+
+use readingbuddy::{Book, CorpusOrder, EngineError, NewNoteInput, NoteKind};
+
+mod common;
+use common::{engine, highlight};
+
+fn by(title: &str, author: &str, year: i64) -> Book {
+    Book {
+        title: Some(title.into()),
+        authors: vec![author.into()],
+        publish_year: Some(year),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn an_author_pools_her_spellings_and_orders_either_way() {
+    let (_tmp, engine) = engine().await;
+    let mut ids = Vec::new();
+    for b in [
+        by("Pachinko", "Min Jin Lee", 2017),
+        by("Free Food for Millionaires", "Lee, Min Jin", 2007),
+        by("American Hippo", "Min Jin Lee", 2030),
+        by("The Lowland", "Jhumpa Lahiri", 2013),
+        by("Native Speaker", "Chang-rae Lee", 1995),
+    ] {
+        ids.push(engine.save_book(&b).await.unwrap().id.unwrap());
+    }
+    let (pachinko, free_food) = (ids[0], ids[1]);
+    engine
+        .storage()
+        .record_reading(pachinko, Some(1_000), Some(2_000), "finished", "manual")
+        .await
+        .unwrap();
+    engine
+        .storage()
+        .record_reading(free_food, Some(5_000), None, "reading", "manual")
+        .await
+        .unwrap();
+    engine
+        .storage()
+        .insert_highlight(
+            free_food,
+            &highlight("Casey had always been good", "2026-01-05 21:14:08"),
+        )
+        .await
+        .unwrap();
+    engine
+        .create_note(NewNoteInput {
+            book_id: Some(pachinko),
+            kind: NoteKind::Note,
+            title: Some("On Sunja".into()),
+            body: "She keeps going.".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let corpus = engine
+        .author_corpus("min jin lee", CorpusOrder::Published)
+        .await
+        .unwrap();
+    assert_eq!(corpus.name, "Min Jin Lee", "the spelling most books use");
+    assert_eq!(corpus.variants, vec!["Lee, Min Jin".to_string()]);
+    let titles: Vec<_> = corpus
+        .books
+        .iter()
+        .map(|b| b.book.title.clone().unwrap())
+        .collect();
+    assert_eq!(
+        titles,
+        ["Free Food for Millionaires", "Pachinko", "American Hippo"]
+    );
+    assert_eq!(corpus.highlight_count(), 1);
+    assert_eq!(corpus.note_count(), 1);
+
+    // Read order: met first, first; never read, last.
+    let corpus = engine
+        .author_corpus("Lee, Min Jin", CorpusOrder::Read)
+        .await
+        .unwrap();
+    let titles: Vec<_> = corpus
+        .books
+        .iter()
+        .map(|b| b.book.title.clone().unwrap())
+        .collect();
+    assert_eq!(
+        titles,
+        ["Pachinko", "Free Food for Millionaires", "American Hippo"]
+    );
+}
+
+#[tokio::test]
+async fn part_of_a_name_finds_one_person_or_says_who_it_could_be() {
+    let (_tmp, engine) = engine().await;
+    for b in [
+        by("Pachinko", "Min Jin Lee", 2017),
+        by("Native Speaker", "Chang-rae Lee", 1995),
+        by("The Lowland", "Jhumpa Lahiri", 2013),
+    ] {
+        engine.save_book(&b).await.unwrap();
+    }
+
+    let corpus = engine
+        .author_corpus("lahiri", CorpusOrder::default())
+        .await
+        .unwrap();
+    assert_eq!(corpus.name, "Jhumpa Lahiri");
+
+    match engine.author_corpus("lee", CorpusOrder::default()).await {
+        Err(EngineError::InvalidInput(msg)) => {
+            assert!(
+                msg.contains("Min Jin Lee") && msg.contains("Chang-rae Lee"),
+                "{msg}"
+            )
+        }
+        other => panic!("expected an ambiguity, got {other:?}"),
+    }
+    assert!(matches!(
+        engine
+            .author_corpus("Toni Morrison", CorpusOrder::default())
+            .await,
+        Err(EngineError::NotFound(_))
+    ));
+}
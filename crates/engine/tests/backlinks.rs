@@ -19,8 +19,8 @@ use readingbuddy::{NewNoteInput, NoteKind};
 /// note that links to itself appears once on each side rather than twice on
 /// either.
 ///
-/// The self-link is not a curiosity. `note_links` is keyed
-/// `(from_note, target_title)`, so the row exists exactly once, and both queries
+/// The self-link is not a curiosity. `note_links` is keyed `(from_note,
+/// target_title, anchor)`, so the row exists exactly once, and both queries
 /// select it — a `JOIN` written carelessly (or a union with the dangling case)
 /// is how it would come back doubled.
 #[tokio::test]
@@ -68,7 +68,7 @@ async fn inbound_and_outbound_are_different_sets_and_a_self_link_counts_once() {
         .await
         .unwrap()
         .into_iter()
-        .map(|n| n.id)
+        .map(|n| n.from.id)
         .collect();
     assert_eq!(inbound.len(), 2, "B links here, and so does A itself");
     assert!(inbound.contains(&b.id));
@@ -127,7 +127,7 @@ async fn a_forward_reference_resolves_when_its_target_is_written() {
         .await
         .unwrap()
         .into_iter()
-        .map(|n| n.id)
+        .map(|n| n.from.id)
         .collect();
     assert_eq!(
         inbound,
@@ -198,7 +198,7 @@ async fn deleting_the_target_leaves_the_link_as_text() {
         .await
         .unwrap()
         .into_iter()
-        .map(|n| n.id)
+        .map(|n| n.from.id)
         .collect();
     assert_eq!(inbound, vec![source.id]);
 }
@@ -304,3 +304,96 @@ async fn a_rewritten_body_drops_the_backlink_it_no_longer_writes() {
     );
     assert!(engine.outgoing_links(a.id).await.unwrap().is_empty());
 }
+
+/// A link into a heading or a `^block` is an edge of its own, read the same
+/// from both ends — and it dangles when the target loses the section, then
+/// resolves again when the section comes back, without the linking note
+/// being touched.
+#[tokio::test]
+async fn a_link_into_a_section_dangles_with_the_section() {
+    let (_tmp, engine) = engine().await;
+    let book = seed_book(&engine, "Pachinko").await;
+    let note = async |title: &str, body: &str| {
+        engine
+            .create_note(NewNoteInput {
+                book_id: Some(book),
+                title: Some(title.into()),
+                body: body.into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .id
+    };
+    let han = note(
+        "Han",
+        "## Origins\nOsaka.\n\nA grief with no bottom. ^grief",
+    )
+    .await;
+    let sunja = note(
+        "Sunja",
+        "[[Han]], [[Han#Origins]], [[Han#^grief|that line]] and [[Han#Ending]].",
+    )
+    .await;
+
+    let out = engine.outgoing_links(sunja).await.unwrap();
+    let seen: Vec<(Option<&str>, bool)> = out
+        .iter()
+        .map(|l| (l.anchor.as_deref(), l.dangles()))
+        .collect();
+    assert_eq!(
+        seen,
+        [
+            (None, false),
+            (Some("Origins"), false),
+            (Some("^grief"), false),
+            (Some("Ending"), true),
+        ]
+    );
+    assert!(out.iter().all(|l| l.to.as_ref().map(|n| n.id) == Some(han)));
+    let back = engine.backlinks(han).await.unwrap();
+    assert_eq!(back.len(), 4, "one row per section cited");
+    assert!(back.iter().all(|l| l.from.id == sunja));
+
+    // The heading goes, and the link into it dangles from both ends.
+    let han_rec = engine.get_note(han).await.unwrap().unwrap();
+    engine
+        .update_note_body(
+            &han_rec,
+            "Osaka.\n\n## Ending\nA grief with no bottom. ^grief",
+        )
+        .await
+        .unwrap();
+    let found = |links: Vec<(Option<String>, bool)>| {
+        links
+            .into_iter()
+            .filter_map(|(a, f)| a.map(|a| (a, f)))
+            .collect::<Vec<_>>()
+    };
+    let out = engine.outgoing_links(sunja).await.unwrap();
+    assert_eq!(
+        found(
+            out.into_iter()
+                .map(|l| (l.anchor, l.anchor_found))
+                .collect()
+        ),
+        [
+            ("Origins".to_string(), false),
+            ("^grief".to_string(), true),
+            ("Ending".to_string(), true),
+        ]
+    );
+    let back = engine.backlinks(han).await.unwrap();
+    assert_eq!(
+        found(
+            back.into_iter()
+                .map(|l| (l.anchor, l.anchor_found))
+                .collect()
+        ),
+        [
+            ("Origins".to_string(), false),
+            ("^grief".to_string(), true),
+            ("Ending".to_string(), true),
+        ]
+    );
+}
@@ -0,0 +1,54 @@
+//! Citation export through the facade: books from the library, passages from
+//! their highlights.
+
+use readingbuddy::{CitationFormat, EngineError, QuoteStyle};
+
+mod common;
+use common::{book, engine, highlight, seed_book};
+
+#[tokio::test]
+async fn the_library_exports_as_entries_and_a_highlight_as_a_quotation() {
+    let (_tmp, engine) = engine().await;
+    let mut pachinko = book("Pachinko");
+    pachinko.publish_year = Some(2017);
+    pachinko.isbn_13 = Some("9781455563937".into());
+    let pachinko = engine.save_book(&pachinko).await.unwrap().id.unwrap();
+    let other = seed_book(&engine, "Free Food for Millionaires").await;
+    let hid = engine
+        .storage()
+        .insert_highlight(
+            pachinko,
+            &highlight("History has failed us", "2026-01-05 21:14:08"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+    let all = engine
+        .cite_books(&[], CitationFormat::Bibtex)
+        .await
+        .unwrap();
+    assert_eq!(all.matches("@book{").count(), 2, "{all}");
+    let one = engine
+        .cite_books(&[other], CitationFormat::Ris)
+        .await
+        .unwrap();
+    assert!(one.contains("TI  - Free Food for Millionaires"), "{one}");
+    assert!(!one.contains("Pachinko"), "{one}");
+
+    let quote = engine
+        .quote_highlight(hid, QuoteStyle::Chicago)
+        .await
+        .unwrap();
+    assert!(quote.starts_with("“History has failed us” Min Jin Lee, *Pachinko* (2017), 12."));
+    assert!(quote.ends_with("ISBN 9781455563937."), "{quote}");
+
+    assert!(matches!(
+        engine.quote_highlight(9999, QuoteStyle::Apa).await,
+        Err(EngineError::NotFound(_))
+    ));
+    assert!(matches!(
+        engine.cite_books(&[9999], CitationFormat::CslJson).await,
+        Err(EngineError::NotFound(_))
+    ));
+}
@@ -48,6 +48,7 @@ pub async fn engine_with_calibre(bin_dir: Option<PathBuf>) -> (tempfile::TempDir
         files_dir: tmp.path().join("database/files"),
         vault_dir: tmp.path().join("vault"),
         log_dir: tmp.path().join("logs"),
+        templates_dir: tmp.path().join("templates"),
         google_api_key: None,
         calibre_bin_dir: bin_dir,
     };
@@ -0,0 +1,135 @@
+//! Paired readers, through the facade: pairing, attribution, revocation.
+//!
+//! Two readers in one household, the case the registry exists for. Codes and
+//! tokens are random, so nothing here predicts one — every test trades what it
+//! was handed.
+
+mod common;
+
+use readingbuddy::{EngineError, SidecarPayload};
+
+const MD5: &str = "5f1e0c2b9a8d7e6f5a4b3c2d1e0f9a8b";
+
+/// A sidecar with these highlights and, when `status` is given, device state.
+fn sidecar(highlights: &[(&str, &str)], status: Option<&str>) -> SidecarPayload {
+    let mut src = String::from("return {\n  [\"doc_props\"] = { [\"title\"] = \"Kindred\" },\n");
+    src.push_str("  [\"annotations\"] = {\n");
+    for (i, (text, when)) in highlights.iter().enumerate() {
+        src.push_str(&format!(
+            "    [{}] = {{ [\"text\"] = {text:?}, [\"pos0\"] = \"/body/p[{}]/text().0\", [\"datetime\"] = {when:?} }},\n",
+            i + 1,
+            i + 1
+        ));
+    }
+    src.push_str("  },\n");
+    if let Some(status) = status {
+        src.push_str(&format!(
+            "  [\"summary\"] = {{ [\"status\"] = {status:?} }},\n  [\"percent_finished\"] = 0.5,\n"
+        ));
+    }
+    src.push('}');
+    SidecarPayload::Lua(src)
+}
+
+/// Each reader's highlights carry its name, the reading carries whoever spoke
+/// last, and the list says which books each has pushed.
+#[tokio::test]
+async fn two_readers_are_told_apart() {
+    let (_dir, engine) = common::engine().await;
+    let libra = engine.mint_pairing_code("Libra").await.unwrap();
+    let libra = engine.pair_device(&libra.code).await.unwrap();
+    let clara = engine.mint_pairing_code("Clara").await.unwrap();
+    let clara = engine.pair_device(&clara.code).await.unwrap();
+    assert_ne!(libra.token, clara.token);
+
+    let first = ("I lost an arm", "2026-07-01 10:00:00");
+    let second = ("on my last trip home", "2026-07-03 21:15:00");
+    let libra_id = engine.authenticate_device(&libra.token).await.unwrap().id;
+    let pulled = engine
+        .pull_sidecar_payload(
+            &sidecar(&[first], Some("reading")),
+            MD5,
+            None,
+            Some(libra_id),
+        )
+        .await
+        .unwrap();
+    let book = pulled.stats.book_id;
+    let clara_id = engine.authenticate_device(&clara.token).await.unwrap().id;
+    engine
+        .pull_sidecar_payload(
+            &sidecar(&[first, second], Some("reading")),
+            MD5,
+            None,
+            Some(clara_id),
+        )
+        .await
+        .unwrap();
+
+    let by_text: Vec<(String, Option<i64>)> = engine
+        .list_highlights(book)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|h| (h.text, h.device_id))
+        .collect();
+    assert!(
+        by_text.contains(&(first.0.into(), Some(libra_id))),
+        "{by_text:?}"
+    );
+    assert!(
+        by_text.contains(&(second.0.into(), Some(clara_id))),
+        "{by_text:?}"
+    );
+    let reading = engine.active_reading(book).await.unwrap().unwrap();
+    assert_eq!(reading.device_id, Some(clara_id), "the last to report");
+
+    let devices = engine.list_devices().await.unwrap();
+    assert_eq!(
+        devices.iter().map(|d| d.name.as_str()).collect::<Vec<_>>(),
+        ["Clara", "Libra"]
+    );
+    assert!(devices.iter().all(|d| d.books == [book]));
+    assert!(devices.iter().all(|d| d.last_contact.is_some()));
+
+    // A wired pull names nobody.
+    let (_other, wired) = common::engine().await;
+    let report = wired
+        .pull_sidecar_payload(&sidecar(&[first], None), MD5, None, None)
+        .await
+        .unwrap();
+    let hs = wired.list_highlights(report.stats.book_id).await.unwrap();
+    assert_eq!(hs[0].device_id, None);
+}
+
+/// A revoked token stops working at once, and what it pushed still says so.
+#[tokio::test]
+async fn revoking_stops_the_token_and_keeps_the_history() {
+    let (_dir, engine) = common::engine().await;
+    let code = engine.mint_pairing_code("Libra").await.unwrap();
+    assert!(!engine.list_devices().await.unwrap()[0].is_paired());
+    let paired = engine.pair_device(&code.code).await.unwrap();
+    let id = paired.device.id;
+    let pulled = engine
+        .pull_sidecar_payload(
+            &sidecar(&[("I lost an arm", "2026-07-01 10:00:00")], None),
+            MD5,
+            None,
+            Some(id),
+        )
+        .await
+        .unwrap();
+
+    engine.revoke_device(id).await.unwrap();
+    let err = engine.authenticate_device(&paired.token).await.unwrap_err();
+    assert!(matches!(err, EngineError::NotPaired(_)), "{err:?}");
+    let listed = &engine.list_devices().await.unwrap()[0];
+    assert!(listed.revoked_at.is_some() && !listed.is_paired());
+    let hs = engine.list_highlights(pulled.stats.book_id).await.unwrap();
+    assert_eq!(hs[0].device_id, Some(id));
+
+    let err = engine.revoke_device(id + 100).await.unwrap_err();
+    assert!(matches!(err, EngineError::NotFound(_)), "{err:?}");
+    let err = engine.mint_pairing_code("  ").await.unwrap_err();
+    assert!(matches!(err, EngineError::InvalidInput(_)), "{err:?}");
+}
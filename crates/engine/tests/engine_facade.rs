@@ -6,7 +6,7 @@
 
 use std::path::PathBuf;
 
-use readingbuddy::{Book, Engine, NewNoteInput, NoteKind};
+use readingbuddy::{Book, CardFormat, Engine, NewNoteInput, NoteKind};
 
 mod common;
 use common::{book, engine, highlight, seed_book, write_isbnless_epub};
@@ -259,7 +259,11 @@ async fn export_flashcards_marks_what_it_exported() {
     assert_eq!(engine.list_flashcards(false).await.unwrap().len(), 2);
     assert_eq!(engine.list_flashcards_for_book(id).await.unwrap().len(), 2);
 
-    let (tsv, count) = engine.export_flashcards(false).await.unwrap();
+    let (tsv, count) = engine
+        .export_flashcards(false, CardFormat::Tsv)
+        .await
+        .unwrap();
+    let tsv = String::from_utf8(tsv).unwrap();
     assert_eq!(count, 2);
     assert!(tsv.starts_with("#separator:tab"));
     assert!(tsv.contains("pachinko"));
@@ -271,7 +275,123 @@ async fn export_flashcards_marks_what_it_exported() {
         "cards were not marked exported"
     );
     assert_eq!(engine.list_flashcards(true).await.unwrap().len(), 2);
-    assert_eq!(engine.export_flashcards(false).await.unwrap().1, 0);
+    assert_eq!(
+        engine
+            .export_flashcards(false, CardFormat::Tsv)
+            .await
+            .unwrap()
+            .1,
+        0
+    );
+}
+
+/// The GUIDs in an `.apkg`, read back the way Anki would: unzip, open the
+/// collection, list the notes.
+async fn package_guids(apkg: &[u8], dir: &std::path::Path) -> Vec<(String, String)> {
+    use sqlx::ConnectOptions;
+    use std::io::Read;
+
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(apkg)).unwrap();
+    let mut collection = Vec::new();
+    zip.by_name("collection.anki2")
+        .unwrap()
+        .read_to_end(&mut collection)
+        .unwrap();
+    let path = dir.join("collection.anki2");
+    std::fs::write(&path, collection).unwrap();
+    let mut conn = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(&path)
+        .connect()
+        .await
+        .unwrap();
+    sqlx::query_as("SELECT guid, sfld FROM notes ORDER BY sfld")
+        .fetch_all(&mut conn)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn an_anki_package_carries_studied_passages_and_keeps_its_guids() {
+    let (tmp, engine) = engine().await;
+    let id = seed_book(&engine, "Pachinko").await;
+    engine
+        .storage()
+        .insert_flashcard(id, None, "pachinko", Some("a game"))
+        .await
+        .unwrap();
+    let hid = engine
+        .storage()
+        .insert_highlight(
+            id,
+            &highlight("History has failed us. No matter.", "2024-01-01 10:00:00"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    engine.study_highlight(hid, true).await.unwrap();
+
+    let (first, count) = engine
+        .export_flashcards(false, CardFormat::Apkg)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+    let guids = package_guids(&first, tmp.path()).await;
+    assert_eq!(guids.len(), 2);
+    assert_eq!(guids[0].1, "History has failed us. No matter.");
+    assert_eq!(guids[1].1, "pachinko");
+
+    // The word is marked exported, the passage has no such mark: a second
+    // package without `include_exported` is the passage alone, under the
+    // GUID it had — which is what makes Anki update rather than add.
+    let (second, count) = engine
+        .export_flashcards(false, CardFormat::Apkg)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(package_guids(&second, tmp.path()).await, guids[..1]);
+    let (all, _) = engine
+        .export_flashcards(true, CardFormat::Apkg)
+        .await
+        .unwrap();
+    assert_eq!(package_guids(&all, tmp.path()).await, guids);
+}
+
+#[tokio::test]
+async fn a_merged_book_keeps_its_cards_guids() {
+    let (tmp, engine) = engine().await;
+    let copy = seed_book(&engine, "Pachinko").await;
+    let kept = engine
+        .save_book(&Book {
+            isbn_13: Some("9781784161880".into()),
+            ..book("Pachinko")
+        })
+        .await
+        .unwrap()
+        .id
+        .unwrap();
+    // The same word in both copies: the merge keeps the other book's row.
+    for id in [copy, kept] {
+        engine
+            .storage()
+            .insert_flashcard(id, None, "pachinko", Some("a game"))
+            .await
+            .unwrap();
+    }
+    let (before, count) = engine
+        .export_flashcards(true, CardFormat::Apkg)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+    let guids = package_guids(&before, tmp.path()).await;
+    assert_eq!(guids.len(), 1);
+
+    engine.merge_books(copy, kept).await.unwrap();
+    let (after, count) = engine
+        .export_flashcards(true, CardFormat::Apkg)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(package_guids(&after, tmp.path()).await, guids);
 }
 
 // ---- input validation ------------------------------------------------------
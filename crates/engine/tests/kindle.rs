@@ -0,0 +1,253 @@
+//! Kindle's `My Clippings.txt`, through the facade.
+//!
+//! One recorded file (see `tests/fixtures/kindle/README.md` for why it is
+//! hand-authored and what each entry is for), imported whole, in part, twice,
+//! and dry. Offline throughout.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use readingbuddy::kindle::ImportOptions;
+use readingbuddy::{Book, DiagnosticKind, Engine, KindleMatch, KindleReport};
+
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/kindle/recorded/My Clippings.txt"
+);
+
+fn recorded() -> PathBuf {
+    PathBuf::from(FIXTURE)
+}
+
+fn dry() -> ImportOptions {
+    ImportOptions {
+        dry_run: true,
+        ..Default::default()
+    }
+}
+
+fn book<'a>(report: &'a KindleReport, title: &str) -> &'a readingbuddy::KindleBookReport {
+    report
+        .books
+        .iter()
+        .find(|b| b.title == title)
+        .unwrap_or_else(|| panic!("no {title} in {report:#?}"))
+}
+
+/// The first `entries` entries of the recorded file, written where the engine
+/// can read them — the file as it stood earlier in the reader's week.
+fn truncated(dir: &Path, entries: usize) -> PathBuf {
+    let src = std::fs::read_to_string(recorded()).expect("read fixture");
+    let head: String = src
+        .split_inclusive("==========\r\n")
+        .take(entries)
+        .collect();
+    let path = dir.join("My Clippings.txt");
+    std::fs::write(&path, head).expect("write clippings");
+    path
+}
+
+async fn texts(engine: &Engine, book_id: i64) -> Vec<String> {
+    let mut t: Vec<String> = engine
+        .list_highlights(book_id)
+        .await
+        .expect("highlights")
+        .into_iter()
+        .map(|h| h.text)
+        .collect();
+    t.sort();
+    t
+}
+
+#[tokio::test]
+async fn the_recorded_file_imports_one_highlight_per_highlight() {
+    let (_dir, engine) = common::engine().await;
+    let report = engine
+        .import_kindle(&recorded(), ImportOptions::default())
+        .await
+        .expect("import");
+
+    assert_eq!(report.entries, 10);
+    assert_eq!(report.created(), 4, "{report:#?}");
+    assert!(report.unmatched.is_empty());
+    assert_eq!(
+        report
+            .warnings
+            .iter()
+            .map(|w| w.kind.clone())
+            .collect::<Vec<_>>(),
+        vec![DiagnosticKind::KindleEntrySkipped { entry: 5 }],
+        "only the clipping-limit placeholder"
+    );
+
+    let kindred = book(&report, "Kindred");
+    assert_eq!((kindred.inserted, kindred.bookmarks), (1, 1));
+    let id = kindred.book_id.expect("created");
+    let stored = engine.list_highlights(id).await.expect("highlights");
+    assert_eq!(stored.len(), 1, "the edit replaced the first version");
+    assert_eq!(
+        stored[0].text,
+        "I lost an arm on my last trip home. My left arm."
+    );
+    assert_eq!(
+        stored[0].ko_note.as_deref(),
+        Some("the first line, and it tells you everything")
+    );
+    assert_eq!(
+        stored[0].ko_datetime.as_deref(),
+        Some("2018-03-05 22:41:58")
+    );
+    let created = engine.get_book(id).await.unwrap().unwrap();
+    assert_eq!(created.authors, vec!["Octavia E. Butler".to_string()]);
+
+    let kafka = book(&report, "Der Process");
+    assert_eq!((kafka.inserted, kafka.flashcards), (2, 1));
+    let kafka_id = kafka.book_id.unwrap();
+    let cards = engine.list_flashcards_for_book(kafka_id).await.unwrap();
+    assert_eq!(cards.len(), 1);
+    assert_eq!(cards[0].word, "Sehnsucht");
+
+    assert_eq!(book(&report, "雪国").inserted, 1);
+
+    let le_guin = book(&report, "The Left Hand of Darkness");
+    assert_eq!(le_guin.inserted, 2, "the highlight and the note on its own");
+    let le_guin_id = le_guin.book_id.unwrap();
+    assert_eq!(
+        texts(&engine, le_guin_id).await,
+        vec![
+            String::new(),
+            "Light is the left hand of darkness\nand darkness the right hand of light.".to_string(),
+        ]
+    );
+    let author = engine.get_book(le_guin_id).await.unwrap().unwrap().authors;
+    assert_eq!(author, vec!["Ursula K. Le Guin".to_string()]);
+}
+
+/// The file only grows. Importing it again adds nothing, creates no second
+/// book, and leaves the reader's own annotation where it was.
+#[tokio::test]
+async fn a_second_import_adds_nothing_and_keeps_the_annotation() {
+    let (_dir, engine) = common::engine().await;
+    let first = engine
+        .import_kindle(&recorded(), ImportOptions::default())
+        .await
+        .unwrap();
+    let id = book(&first, "Kindred").book_id.unwrap();
+    let h = &engine.list_highlights(id).await.unwrap()[0];
+    engine.set_annotation(h.id, Some("mine")).await.unwrap();
+
+    let again = engine
+        .import_kindle(&recorded(), ImportOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(again.created(), 0);
+    for b in &again.books {
+        assert_eq!((b.inserted, b.updated), (0, 0), "{b:#?}");
+        assert_eq!(b.matched_by, KindleMatch::Title);
+    }
+    let kept = &engine.list_highlights(id).await.unwrap()[0];
+    assert_eq!((kept.id, kept.annotation.as_deref()), (h.id, Some("mine")));
+}
+
+/// Imported before the edit, then after: the stored row becomes the edit — the
+/// same row, so the annotation on it survives — and the note arrives as an
+/// update.
+#[tokio::test]
+async fn an_edit_after_an_import_rewrites_the_stored_highlight() {
+    let (dir, engine) = common::engine().await;
+    let early = truncated(dir.path(), 1);
+    let first = engine
+        .import_kindle(&early, ImportOptions::default())
+        .await
+        .unwrap();
+    let id = book(&first, "Kindred").book_id.unwrap();
+    let before = engine.list_highlights(id).await.unwrap();
+    assert_eq!(before[0].text, "I lost an arm on my last trip home.");
+    engine
+        .set_annotation(before[0].id, Some("mine"))
+        .await
+        .unwrap();
+
+    let preview = engine.import_kindle(&recorded(), dry()).await.unwrap();
+    let kindred = book(&preview, "Kindred");
+    assert_eq!((kindred.inserted, kindred.updated), (0, 1));
+
+    let report = engine
+        .import_kindle(&recorded(), ImportOptions::default())
+        .await
+        .unwrap();
+    let kindred = book(&report, "Kindred");
+    assert_eq!((kindred.inserted, kindred.updated), (0, 1));
+    let after = engine.list_highlights(id).await.unwrap();
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0].id, before[0].id);
+    assert_eq!(
+        after[0].text,
+        "I lost an arm on my last trip home. My left arm."
+    );
+    assert_eq!(after[0].annotation.as_deref(), Some("mine"));
+    assert!(after[0].ko_note.is_some());
+}
+
+/// A dry run says what the real run will do, and does none of it.
+#[tokio::test]
+async fn a_dry_run_writes_nothing_and_predicts_the_real_one() {
+    let (_dir, engine) = common::engine().await;
+    let preview = engine.import_kindle(&recorded(), dry()).await.unwrap();
+    assert!(preview.dry_run);
+    assert!(preview.books.iter().all(|b| b.book_id.is_none()));
+    assert!(
+        engine
+            .query_books(&Default::default())
+            .await
+            .unwrap()
+            .books
+            .is_empty()
+    );
+
+    let real = engine
+        .import_kindle(&recorded(), ImportOptions::default())
+        .await
+        .unwrap();
+    let counts = |r: &KindleReport| {
+        r.books
+            .iter()
+            .map(|b| (b.title.clone(), b.inserted, b.updated, b.flashcards))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(counts(&preview), counts(&real));
+}
+
+/// A book already here is found by title and author, however the Kindle
+/// spelled the author, and a mount is as good as the file.
+#[tokio::test]
+async fn clippings_find_the_book_already_here_through_a_mount() {
+    let (dir, engine) = common::engine().await;
+    let kafka = engine
+        .save_book(&Book {
+            title: Some("Der Process".into()),
+            authors: vec!["Franz Kafka".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let mount = dir.path().join("Kindle");
+    std::fs::create_dir_all(mount.join("documents")).unwrap();
+    std::fs::copy(recorded(), mount.join(readingbuddy::kindle::CLIPPINGS_FILE)).unwrap();
+
+    let report = engine
+        .import_kindle(&mount, ImportOptions::default())
+        .await
+        .unwrap();
+    let found = book(&report, "Der Process");
+    assert_eq!(found.matched_by, KindleMatch::Title);
+    assert_eq!(found.book_id, kafka.id);
+    assert_eq!(report.created(), 3);
+
+    let nowhere = engine
+        .import_kindle(&dir.path().join("nothing"), ImportOptions::default())
+        .await;
+    assert!(nowhere.is_err());
+}
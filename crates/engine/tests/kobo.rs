@@ -0,0 +1,313 @@
+//! A stock Kobo's `KoboReader.sqlite`, through the facade.
+//!
+//! The database is built here, table by table, with only the columns the
+//! importer reads — Nickel's real schema is some two hundred columns wide and
+//! changes between firmware releases, and a committed copy of one would be a
+//! binary nobody can review. The mount around it is a tempdir with one
+//! sideloaded book on it, so that book has a `partial_md5` and the store book
+//! beside it does not. Offline throughout.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use readingbuddy::kobo::KOBO_DB;
+use readingbuddy::{
+    Book, DeviceBook, DeviceSource, DeviceState, DiagnosticKind, Engine, MatchMethod,
+};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+const KINDRED: &str = "file:///mnt/onboard/Books/Kindred.kepub.epub";
+const PROCESS: &str = "5c3e1b2a-8f4d-4e7a-9b61-0d2c7a9e4f13";
+const SAMPLE: &str = "9a1f0e6c-2b7d-4c38-a5e2-7f1b3d8c6a40";
+
+/// A Kobo holding three books: *Kindred*, sideloaded, finished, with two
+/// highlights (one annotated), a dog-ear and a deleted highlight; *Der
+/// Process*, bought from the store, half read, one highlight; and a sample the
+/// store pushed and nobody opened.
+async fn kobo(root: &Path) -> PathBuf {
+    let mount = root.join("KOBOeReader");
+    std::fs::create_dir_all(mount.join(".kobo")).unwrap();
+    std::fs::create_dir_all(mount.join("Books")).unwrap();
+    std::fs::write(
+        mount.join("Books/Kindred.kepub.epub"),
+        "Kindred, sideloaded. ".repeat(200),
+    )
+    .unwrap();
+
+    let opts = SqliteConnectOptions::new()
+        .filename(mount.join(KOBO_DB))
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await
+        .unwrap();
+    for stmt in [
+        "CREATE TABLE content (
+            ContentID TEXT PRIMARY KEY, ContentType TEXT, Title TEXT, Attribution TEXT,
+            ISBN TEXT, Language TEXT, ReadStatus INTEGER, ___PercentRead INTEGER,
+            DateLastRead TEXT)",
+        "CREATE TABLE Bookmark (
+            BookmarkID TEXT PRIMARY KEY, VolumeID TEXT, ContentID TEXT, Text TEXT,
+            Annotation TEXT, StartContainerPath TEXT, EndContainerPath TEXT,
+            DateCreated TEXT, DateModified TEXT, Hidden TEXT)",
+    ] {
+        sqlx::query(stmt).execute(&pool).await.unwrap();
+    }
+    for (id, kind, title, by, status, percent, last_read) in [
+        (
+            KINDRED,
+            "6",
+            "Kindred",
+            "Octavia E. Butler",
+            2,
+            0,
+            "2018-03-09T21:04:00Z",
+        ),
+        (
+            PROCESS,
+            "6",
+            "Der Process",
+            "Franz Kafka",
+            1,
+            47,
+            "2024-11-02T08:15:30.000",
+        ),
+        (SAMPLE, "6", "A Sample", "Somebody", 0, 0, ""),
+        (
+            "file:///mnt/onboard/Books/Kindred.kepub.epub!OEBPS!ch01.xhtml",
+            "9",
+            "The River",
+            "",
+            0,
+            0,
+            "",
+        ),
+    ] {
+        sqlx::query(
+            "INSERT INTO content (ContentID, ContentType, Title, Attribution, ReadStatus,
+                                  ___PercentRead, DateLastRead)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )
+        .bind(id)
+        .bind(kind)
+        .bind(title)
+        .bind(by)
+        .bind(status)
+        .bind(percent)
+        .bind(last_read)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    let chapter = "file:///mnt/onboard/Books/Kindred.kepub.epub!OEBPS!ch01.xhtml";
+    for (id, volume, text, note, created, hidden) in [
+        (
+            "b1",
+            KINDRED,
+            "I lost an arm on my last trip home.",
+            "the first line",
+            "2018-03-05T22:41:11Z",
+            "false",
+        ),
+        (
+            "b2",
+            KINDRED,
+            "My left arm.",
+            "",
+            "2018-03-05T22:41:58Z",
+            "false",
+        ),
+        ("b3", KINDRED, "", "", "2018-03-06T07:00:00Z", "false"),
+        (
+            "b4",
+            KINDRED,
+            "Deleted on the device.",
+            "",
+            "2018-03-06T07:01:00Z",
+            "true",
+        ),
+        (
+            "b5",
+            PROCESS,
+            "Jemand mußte Josef K. verleumdet haben.",
+            "",
+            "2024-11-01T20:00:00Z",
+            "false",
+        ),
+    ] {
+        sqlx::query(
+            "INSERT INTO Bookmark (BookmarkID, VolumeID, ContentID, Text, Annotation,
+                                   StartContainerPath, EndContainerPath, DateCreated,
+                                   DateModified, Hidden)
+             VALUES (?1, ?2, ?3, ?4, ?5, 'span#kobo\\.1\\.1', 'span#kobo\\.1\\.9', ?6, ?6, ?7)",
+        )
+        .bind(id)
+        .bind(volume)
+        .bind(chapter)
+        .bind(text)
+        .bind(note)
+        .bind(created)
+        .bind(hidden)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    pool.close().await;
+    mount
+}
+
+async fn kindred(engine: &Engine) -> i64 {
+    engine
+        .save_book(&Book {
+            title: Some("Kindred".into()),
+            authors: vec!["Octavia E. Butler".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .id
+        .unwrap()
+}
+
+fn titled<'a>(books: &'a [DeviceBook], title: &str) -> &'a DeviceBook {
+    books
+        .iter()
+        .find(|b| b.title.as_deref() == Some(title))
+        .unwrap_or_else(|| panic!("no {title} in {books:#?}"))
+}
+
+/// Highlights, annotations, the percentage and the last-read time all land;
+/// a finished book's reading is closed; the book nothing here matches is
+/// reported, not created.
+#[tokio::test]
+async fn an_import_brings_highlights_and_reading_state_across() {
+    let (dir, engine) = common::engine().await;
+    let mount = kobo(dir.path()).await;
+    let id = kindred(&engine).await;
+
+    let report = engine.import_kobo(&mount, false).await.unwrap();
+    assert_eq!(report.imported.len(), 1, "{report:#?}");
+    let stats = &report.imported[0];
+    assert_eq!((stats.book_id, stats.inserted), (id, 2));
+    assert_eq!(stats.matched_by, MatchMethod::Title);
+    assert_eq!(report.unmatched.len(), 1);
+    assert_eq!(report.unmatched[0].title.as_deref(), Some("Der Process"));
+
+    let hs = engine.list_highlights(id).await.unwrap();
+    assert_eq!(hs.len(), 2, "no dog-ear, nothing deleted: {hs:#?}");
+    let first = hs
+        .iter()
+        .find(|h| h.text.starts_with("I lost"))
+        .expect("first highlight");
+    assert_eq!(first.ko_note.as_deref(), Some("the first line"));
+    assert_eq!(first.ko_datetime.as_deref(), Some("2018-03-05 22:41:11"));
+    assert_eq!(first.chapter.as_deref(), Some("The River"));
+
+    let readings = engine.list_readings(id).await.unwrap();
+    assert_eq!(readings.len(), 1);
+    let r = &readings[0];
+    assert_eq!(r.source, "kobo");
+    assert_eq!(r.ko_percent, Some(1.0), "finished reads as 100%");
+    assert_eq!(r.ko_last_read, Some(1_520_629_440));
+    assert!(r.finished_at.is_some());
+
+    // The sideloaded file is now linked, so the next import finds it by MD5.
+    let again = engine.import_kobo(&mount, false).await.unwrap();
+    let stats = &again.imported[0];
+    assert_eq!((stats.inserted, stats.updated, stats.skipped), (0, 0, 2));
+    assert_eq!(stats.matched_by, MatchMethod::Md5);
+    assert_eq!(engine.list_readings(id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn a_dry_run_writes_nothing() {
+    let (dir, engine) = common::engine().await;
+    let mount = kobo(dir.path()).await;
+    let id = kindred(&engine).await;
+
+    let db = mount.join(KOBO_DB);
+    let report = engine.import_kobo(&db, true).await.unwrap();
+    assert_eq!(report.imported[0].inserted, 2);
+    assert!(engine.list_highlights(id).await.unwrap().is_empty());
+    assert!(engine.list_readings(id).await.unwrap().is_empty());
+}
+
+/// The Device screen lists a Kobo's books beside any KOReader ones, in the
+/// same states, and a row's path is enough for a sync to pull the book in.
+#[tokio::test]
+async fn kobo_books_list_on_the_device_and_sync_by_row() {
+    let (dir, engine) = common::engine().await;
+    let mount = kobo(dir.path()).await;
+    kindred(&engine).await;
+
+    let scan = engine.scan_device(&mount).await.unwrap();
+    assert!(scan.warnings.is_empty(), "{:#?}", scan.warnings);
+    assert_eq!(scan.books.len(), 2, "the unopened sample is not listed");
+    assert!(scan.books.iter().all(|b| b.source == DeviceSource::Kobo));
+
+    let kindred_row = titled(&scan.books, "Kindred");
+    assert_eq!(
+        kindred_row.state,
+        DeviceState::Updated {
+            new_highlights: 2,
+            refreshed: 0
+        }
+    );
+    assert_eq!(kindred_row.ko_percent, Some(1.0));
+    assert!(kindred_row.partial_md5.is_some());
+    let process = titled(&scan.books, "Der Process");
+    assert!(matches!(process.state, DeviceState::New { .. }));
+    assert_eq!(
+        process.partial_md5, None,
+        "a store book has no file to hash"
+    );
+    assert_eq!(process.authors.as_deref(), Some("Franz Kafka"));
+
+    let paths: Vec<PathBuf> = scan.books.iter().map(|b| b.path.clone()).collect();
+    let pulled = engine.sync_device(&paths).await.unwrap();
+    assert_eq!(pulled.len(), 2);
+    let created = pulled
+        .iter()
+        .find(|p| p.stats.book_title == "Der Process")
+        .expect("pulled");
+    assert_eq!(created.stats.matched_by, MatchMethod::New);
+    assert_eq!(created.stats.inserted, 1);
+    assert!(
+        created
+            .warnings
+            .iter()
+            .any(|w| matches!(w.kind, DiagnosticKind::KoboBookNotIdentified { .. }))
+    );
+    let readings = engine.list_readings(created.stats.book_id).await.unwrap();
+    assert_eq!(readings[0].ko_percent, Some(0.47));
+    assert!(readings[0].finished_at.is_none());
+
+    let rescan = engine.scan_device(&mount).await.unwrap();
+    assert!(
+        rescan
+            .books
+            .iter()
+            .all(|b| b.state == DeviceState::Unchanged),
+        "{:#?}",
+        rescan.books
+    );
+}
+
+/// A database that will not open is a warning on the scan, not a failed scan.
+#[tokio::test]
+async fn an_unreadable_database_is_a_warning() {
+    let (dir, engine) = common::engine().await;
+    let mount = dir.path().join("KOBOeReader");
+    std::fs::create_dir_all(mount.join(".kobo")).unwrap();
+    std::fs::write(mount.join(KOBO_DB), b"not a database").unwrap();
+
+    let scan = engine.scan_device(&mount).await.unwrap();
+    assert!(scan.books.is_empty());
+    assert!(matches!(
+        scan.warnings[..],
+        [ref w] if matches!(w.kind, DiagnosticKind::KoboDatabaseUnreadable { .. })
+    ));
+    assert!(engine.import_kobo(&mount, false).await.is_err());
+}
@@ -0,0 +1,178 @@
+//! Installing the KOReader plugin, against a fake mount tree.
+//!
+//! `docs/spec-11-16.md` makes the installer's predicates and refusals the part
+//! of item 15 that can be tested off the device, and this is that part. The
+//! plugin itself needs hardware.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use readingbuddy::koplugin::{PLUGIN_DIR, PLUGIN_VERSION};
+use readingbuddy::{EngineError, PluginState};
+
+/// A Kobo with KOReader installed under `.adds/`, and one plugin of its own.
+fn reader(root: &Path) -> PathBuf {
+    let ko = root.join(".adds/koreader");
+    std::fs::create_dir_all(ko.join("frontend")).unwrap();
+    std::fs::create_dir_all(ko.join("plugins/statistics.koplugin")).unwrap();
+    std::fs::write(
+        ko.join("plugins/statistics.koplugin/main.lua"),
+        "-- theirs\n",
+    )
+    .unwrap();
+    std::fs::write(ko.join("reader.lua"), "-- entry point\n").unwrap();
+    ko.join("plugins").join(PLUGIN_DIR)
+}
+
+/// Every file under `root`, relative to it, sorted.
+fn tree(root: &Path) -> Vec<String> {
+    fn walk(dir: &Path, root: &Path, out: &mut Vec<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                walk(&path, root, out);
+            } else {
+                out.push(path.strip_prefix(root).unwrap().display().to_string());
+            }
+        }
+    }
+    let mut out = Vec::new();
+    walk(root, root, &mut out);
+    out.sort();
+    out
+}
+
+/// Install writes our three files and nothing else; a second install is a
+/// no-op; remove leaves the mount exactly as it found it.
+#[tokio::test]
+async fn install_then_remove_leaves_the_reader_as_it_was() {
+    let (_dir, engine) = common::engine().await;
+    let mount = tempfile::tempdir().unwrap();
+    let dir = reader(mount.path());
+    let before = tree(mount.path());
+
+    let status = engine.plugin_status(mount.path()).unwrap();
+    assert_eq!(status.plugin_dir, dir);
+    assert_eq!(status.state, PluginState::NotInstalled);
+
+    let installed = engine.install_plugin(mount.path()).unwrap();
+    assert_eq!(installed.version, PLUGIN_VERSION);
+    assert_eq!(installed.replaced, None);
+    assert_eq!(
+        installed.written,
+        ["_meta.lua", "main.lua", "readingbuddy.manifest"].map(|n| dir.join(n))
+    );
+    let mut after = before.clone();
+    after.extend(
+        installed
+            .written
+            .iter()
+            .map(|p| p.strip_prefix(mount.path()).unwrap().display().to_string()),
+    );
+    after.sort();
+    assert_eq!(tree(mount.path()), after, "only inside our directory");
+    assert_eq!(
+        engine.plugin_status(mount.path()).unwrap().state,
+        PluginState::Current
+    );
+
+    let again = engine.install_plugin(mount.path()).unwrap();
+    assert!(again.written.is_empty());
+
+    // A file of ours edited on the reader is put back, and only that one.
+    std::fs::write(dir.join("main.lua"), "-- edited\n").unwrap();
+    let repaired = engine.install_plugin(mount.path()).unwrap();
+    assert_eq!(repaired.written, [dir.join("main.lua")]);
+
+    let removed = engine.uninstall_plugin(mount.path()).unwrap();
+    assert_eq!(removed.version, Some(PLUGIN_VERSION));
+    assert_eq!(removed.removed.len(), 3);
+    assert!(removed.kept.is_empty());
+    assert!(!dir.exists());
+    assert_eq!(tree(mount.path()), before);
+
+    let nothing = engine.uninstall_plugin(mount.path()).unwrap();
+    assert_eq!(nothing.version, None);
+}
+
+/// An older install is upgraded in place, and a file only it had goes with
+/// it; a file the user put there stays, through the upgrade and the removal.
+#[tokio::test]
+async fn an_upgrade_replaces_only_what_the_old_version_wrote() {
+    let (_dir, engine) = common::engine().await;
+    let mount = tempfile::tempdir().unwrap();
+    let dir = reader(mount.path());
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.lua"), "-- version 0\n").unwrap();
+    std::fs::write(dir.join("old.lua"), "-- dropped in 1\n").unwrap();
+    std::fs::write(dir.join("settings.lua"), "-- the user's\n").unwrap();
+    std::fs::write(
+        dir.join("readingbuddy.manifest"),
+        "version 0\nmain.lua\nold.lua\n",
+    )
+    .unwrap();
+    assert_eq!(
+        engine.plugin_status(mount.path()).unwrap().state,
+        PluginState::Older { installed: 0 }
+    );
+
+    let upgraded = engine.install_plugin(mount.path()).unwrap();
+    assert_eq!(upgraded.replaced, Some(0));
+    assert_eq!(upgraded.removed, [dir.join("old.lua")]);
+    assert_ne!(
+        std::fs::read_to_string(dir.join("main.lua")).unwrap(),
+        "-- version 0\n"
+    );
+
+    let removed = engine.uninstall_plugin(mount.path()).unwrap();
+    assert_eq!(removed.kept, [dir.join("settings.lua")]);
+    assert_eq!(
+        std::fs::read_dir(&dir).unwrap().count(),
+        1,
+        "the directory stays for the file that is not ours"
+    );
+}
+
+/// A newer version, a directory we did not write, and a volume that is not a
+/// KOReader install are all refused, and nothing on them changes.
+#[tokio::test]
+async fn refusals_write_nothing() {
+    let (_dir, engine) = common::engine().await;
+
+    let newer = tempfile::tempdir().unwrap();
+    let dir = reader(newer.path());
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("readingbuddy.manifest"), "version 99\nmain.lua\n").unwrap();
+    let before = tree(newer.path());
+    assert_eq!(
+        engine.plugin_status(newer.path()).unwrap().state,
+        PluginState::Newer { installed: 99 }
+    );
+    let err = engine.install_plugin(newer.path()).unwrap_err();
+    assert!(matches!(err, EngineError::PluginRefused { .. }), "{err:?}");
+    assert_eq!(tree(newer.path()), before);
+
+    let foreign = tempfile::tempdir().unwrap();
+    let dir = reader(foreign.path());
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.lua"), "-- somebody else's\n").unwrap();
+    let before = tree(foreign.path());
+    assert!(matches!(
+        engine.plugin_status(foreign.path()).unwrap().state,
+        PluginState::Foreign { .. }
+    ));
+    for err in [
+        engine.install_plugin(foreign.path()).unwrap_err(),
+        engine.uninstall_plugin(foreign.path()).unwrap_err(),
+    ] {
+        assert!(matches!(err, EngineError::PluginRefused { .. }), "{err:?}");
+    }
+    assert_eq!(tree(foreign.path()), before);
+
+    let plain = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(plain.path().join("koreader")).unwrap();
+    let err = engine.install_plugin(plain.path()).unwrap_err();
+    assert!(matches!(err, EngineError::InvalidInput(_)), "{err:?}");
+    assert_eq!(tree(plain.path()), Vec::<String>::new());
+}
@@ -0,0 +1,159 @@
+//! Pushing annotations back into a sidecar, through the facade.
+//!
+//! Against a copy of the synthetic `Pachinko.sdr` in a tempdir — a push writes
+//! to the device, and the committed fixture is not one. Offline throughout.
+
+mod common;
+
+use std::path::Path;
+
+use readingbuddy::device::DeviceState;
+use readingbuddy::koreader::parse_sidecar;
+use readingbuddy::{Engine, EngineError};
+
+use common::{place, rewrite_sidecar};
+
+const FIRST: &str = "History has failed us, but no matter.";
+
+/// Pull Pachinko off a fresh "device" and annotate its first highlight here.
+async fn pulled_and_annotated(engine: &Engine, root: &Path) -> (std::path::PathBuf, i64) {
+    let sidecar = place(root, "Pachinko.sdr", "Pachinko.sdr");
+    let pulled = engine.pull_book_from_sidecar(&sidecar).await.unwrap();
+    let book_id = pulled.stats.book_id;
+    let first = engine
+        .list_highlights(book_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|h| h.text == FIRST)
+        .unwrap();
+    engine
+        .set_annotation(first.id, Some("Written at the desk."))
+        .await
+        .unwrap();
+    (sidecar, book_id)
+}
+
+fn backups(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|n| n.ends_with(".bak"))
+        .collect()
+}
+
+/// The dry run shows the one note that would change and writes nothing; the
+/// real push writes it, keeps the original, and leaves the device screen
+/// calling the book unchanged.
+#[tokio::test]
+async fn a_push_writes_the_annotation_and_backs_up_the_original() {
+    let (dir, engine) = common::engine().await;
+    let (sidecar, book_id) = pulled_and_annotated(&engine, dir.path()).await;
+    let original = std::fs::read(&sidecar).unwrap();
+
+    let preview = engine.push_annotations(&sidecar, true).await.unwrap();
+    assert!(preview.dry_run);
+    assert_eq!(preview.changes.len(), 1, "{preview:#?}");
+    let change = &preview.changes[0];
+    assert_eq!(change.index, 1);
+    assert_eq!(change.text, FIRST);
+    assert_eq!(
+        change.before.as_deref(),
+        Some("Opening line - sets the whole register.")
+    );
+    assert_eq!(change.after, "Written at the desk.");
+    assert_eq!(std::fs::read(&sidecar).unwrap(), original);
+    assert!(backups(sidecar.parent().unwrap()).is_empty());
+
+    let pushed = engine
+        .push_annotations(sidecar.parent().unwrap(), false)
+        .await
+        .unwrap();
+    assert_eq!(pushed.changes, preview.changes);
+    let backup = pushed.backup.expect("backed up");
+    assert_eq!(std::fs::read(&backup).unwrap(), original);
+
+    let src = std::fs::read_to_string(&sidecar).unwrap();
+    assert!(src.contains("[\"annotations_externally_modified\"] = true,"));
+    let sc = parse_sidecar(&src).unwrap();
+    let notes: Vec<Option<&str>> = sc.highlights.iter().map(|h| h.note.as_deref()).collect();
+    assert_eq!(notes, vec![Some("Written at the desk."), None]);
+    assert_eq!(
+        sc.partial_md5.as_deref(),
+        Some("0d6ba6c47caf63b8b3d1a2b3c4d5e6f7")
+    );
+    assert_eq!(sc.title.as_deref(), Some("Pachinko"));
+
+    let scan = engine.scan_device(dir.path()).await.unwrap();
+    assert_eq!(scan.books[0].state, DeviceState::Unchanged);
+    let again = engine.push_annotations(&sidecar, false).await.unwrap();
+    assert!(again.changes.is_empty());
+    assert_eq!(again.backup, None);
+    assert_eq!(backups(sidecar.parent().unwrap()).len(), 1);
+
+    let stored = engine.list_highlights(book_id).await.unwrap();
+    let first = stored.iter().find(|h| h.text == FIRST).unwrap();
+    assert_eq!(first.ko_note.as_deref(), Some("Written at the desk."));
+    assert_eq!(first.annotation.as_deref(), Some("Written at the desk."));
+}
+
+/// A note edited on the device since the last pull is the reader's, and is
+/// not ours to overwrite: the push is refused and the file left as it is.
+#[tokio::test]
+async fn a_sidecar_changed_on_the_device_is_refused_not_clobbered() {
+    let (dir, engine) = common::engine().await;
+    let (sidecar, _) = pulled_and_annotated(&engine, dir.path()).await;
+    rewrite_sidecar(&sidecar, |s| {
+        s.replace("sets the whole register", "edited on the reader")
+    });
+    let edited = std::fs::read(&sidecar).unwrap();
+
+    for dry_run in [true, false] {
+        let err = engine
+            .push_annotations(&sidecar, dry_run)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineError::DeviceChanged { .. }), "{err:?}");
+    }
+    assert_eq!(std::fs::read(&sidecar).unwrap(), edited);
+    assert!(backups(sidecar.parent().unwrap()).is_empty());
+
+    // Pulling first is the way through.
+    engine.pull_book_from_sidecar(&sidecar).await.unwrap();
+    let pushed = engine.push_annotations(&sidecar, false).await.unwrap();
+    assert_eq!(
+        pushed.changes[0].before.as_deref(),
+        Some("Opening line - edited on the reader.")
+    );
+}
+
+/// The reading state is checked as well as the notes: a book read further on
+/// the device is a file the last pull did not see.
+#[tokio::test]
+async fn reading_on_after_the_pull_is_a_change_too() {
+    let (dir, engine) = common::engine().await;
+    let sidecar = place(dir.path(), "Gen-Summary.sdr", "Summary.sdr");
+    rewrite_sidecar(&sidecar, |s| {
+        s.replace(
+            "[\"doc_props\"]",
+            "[\"partial_md5_checksum\"] = \"5f1e0c2b9a8d7e6f5a4b3c2d1e0f9a8b\",\n    [\"doc_props\"]",
+        )
+    });
+    engine.pull_book_from_sidecar(&sidecar).await.unwrap();
+    rewrite_sidecar(&sidecar, |s| {
+        s.replace(
+            "[\"percent_finished\"] = ",
+            "[\"percent_finished\"] = 0.01 + ",
+        )
+    });
+    let err = engine.push_annotations(&sidecar, true).await.unwrap_err();
+    assert!(matches!(err, EngineError::DeviceChanged { .. }), "{err:?}");
+}
+
+#[tokio::test]
+async fn a_sidecar_never_pulled_has_nowhere_to_push_from() {
+    let (dir, engine) = common::engine().await;
+    let sidecar = place(dir.path(), "Pachinko.sdr", "Pachinko.sdr");
+    let err = engine.push_annotations(&sidecar, true).await.unwrap_err();
+    assert!(matches!(err, EngineError::NotFound(_)), "{err:?}");
+}
@@ -0,0 +1,57 @@
+//! Reading days are local dates, and attribution has to read them as such.
+//!
+//! A binary of its own because the zone is the process's: `TZ` is set before
+//! anything asks for local time, and no other test shares the process to be
+//! surprised by it. `EST5` is a POSIX zone — five hours west, no daylight
+//! saving, and no tzdata needed to resolve it.
+
+mod common;
+
+use common::{engine, seed_book};
+use readingbuddy::storage::{CONFIDENCE_MEASURED, NewReadingEvent};
+
+/// A reading begun at nine in the evening owns that evening's reading day,
+/// though in UTC it began the morning after.
+#[tokio::test]
+async fn a_reading_begun_in_the_evening_owns_that_local_day() {
+    // SAFETY: the only test in this binary, and nothing has read the zone yet.
+    unsafe { std::env::set_var("TZ", "EST5") };
+    let (_tmp, engine) = engine().await;
+    let book = seed_book(&engine, "Pachinko").await;
+    let storage = engine.storage();
+    // 2026-03-10 21:00 local is 2026-03-11 02:00 UTC.
+    let reading = storage
+        .record_reading(book, Some(1_773_194_400), None, "reading", "manual")
+        .await
+        .unwrap();
+    for day in ["2026-03-09", "2026-03-10"] {
+        storage
+            .put_reading_event(&NewReadingEvent {
+                book_id: book,
+                day,
+                minutes: Some(30),
+                pages: Some(12),
+                source: "koreader",
+                source_key: "aaa",
+                confidence: CONFIDENCE_MEASURED,
+            })
+            .await
+            .unwrap();
+    }
+
+    storage.attribute_reading_events(book).await.unwrap();
+    let by_day: Vec<(String, Option<i64>)> = storage
+        .list_reading_events(book)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|e| (e.day, e.reading_id))
+        .collect();
+    assert_eq!(
+        by_day,
+        vec![
+            ("2026-03-09".to_string(), None),
+            ("2026-03-10".to_string(), Some(reading)),
+        ]
+    );
+}
@@ -17,6 +17,7 @@ const READINGS: i64 = 5;
 const REFLECTION: i64 = 7;
 const NOTE_LINK_INDEX: i64 = 8;
 const GOODREADS: i64 = 9;
+const HIGHLIGHTS_FTS: i64 = 13;
 
 /// A connection migrated up to (but not including) `version`.
 async fn migrated_below(version: i64) -> SqliteConnection {
@@ -442,6 +443,45 @@ async fn a_second_default_scale_is_refused() {
     );
 }
 
+/// `0013`'s index is built over the highlights a library already holds, not
+/// only the ones imported after it.
+///
+/// The triggers only see writes made after they exist, so without the
+/// `'rebuild'` every highlight from before the upgrade would be unsearchable
+/// until the device happened to rewrite it — which for most of them is never.
+#[tokio::test]
+async fn highlights_from_before_the_index_are_searchable_after_it() {
+    let mut conn = migrated_below(HIGHLIGHTS_FTS).await;
+    let book: i64 = sqlx::query(
+        "INSERT INTO books (title, created_at, last_modified) VALUES ('Kindred', 0, 0)
+         RETURNING id",
+    )
+    .fetch_one(&mut conn)
+    .await
+    .expect("insert book")
+    .get("id");
+    sqlx::query(
+        "INSERT INTO highlights (book_id, text, annotation, identity_hash, created_at)
+         VALUES (?, 'I lost an arm on my last trip home.', 'the opening line', 'h1', 0)",
+    )
+    .bind(book)
+    .execute(&mut conn)
+    .await
+    .expect("insert highlight");
+
+    apply(&mut conn, HIGHLIGHTS_FTS).await;
+
+    for query in ["trip", "opening"] {
+        let n: i64 =
+            sqlx::query_scalar("SELECT count(*) FROM highlights_fts WHERE highlights_fts MATCH ?")
+                .bind(query)
+                .fetch_one(&mut conn)
+                .await
+                .expect("search");
+        assert_eq!(n, 1, "'{query}' should find the old highlight");
+    }
+}
+
 mod props {
     use super::*;
     use proptest::prelude::*;
@@ -0,0 +1,93 @@
+//! Renaming a note without stranding the links to it.
+
+mod common;
+
+use common::{engine, seed_book};
+use readingbuddy::{Engine, NewNoteInput};
+
+async fn note(engine: &Engine, book_id: Option<i64>, title: &str, body: &str) -> i64 {
+    engine
+        .create_note(NewNoteInput {
+            book_id,
+            title: Some(title.into()),
+            body: body.into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .id
+}
+
+/// Every shape of link to the old title is rewritten on disk and in the index,
+/// a link that was waiting for the new title finds the note, and a link that
+/// only looks similar is left alone.
+#[tokio::test]
+async fn a_rename_rewrites_every_link_to_the_note() {
+    let (_tmp, engine) = engine().await;
+    let book = seed_book(&engine, "Pachinko").await;
+    let han = note(&engine, Some(book), "Han", "A grief with no bottom.").await;
+    let sunja = note(
+        &engine,
+        Some(book),
+        "Sunja",
+        "Her life is [[Han]], [[han|grief]] and [[Han#Origins]].",
+    )
+    .await;
+    let waiting = note(&engine, None, "Kyunghee", "Some day, [[Sorrow]].").await;
+    let other = note(&engine, None, "Hansu", "Not [[Hansel]].").await;
+    let before = engine.get_note(han).await.unwrap().unwrap();
+
+    let touched = engine.rename_note(han, "Sorrow").await.unwrap();
+    let after = engine.get_note(han).await.unwrap().unwrap();
+    assert_eq!(after.title, "Sorrow");
+    assert!(
+        after.file_path.ends_with("-sorrow.md"),
+        "{}",
+        after.file_path
+    );
+    assert!(!engine.note_path(&before).exists());
+    assert_eq!(touched.len(), 2, "{touched:?}");
+    assert_eq!(touched[0], engine.note_path(&after));
+
+    let file = std::fs::read_to_string(engine.note_path(&after)).unwrap();
+    assert!(file.starts_with("---\ntitle: \"Sorrow\"\n"), "{file}");
+    let sunja_rec = engine.get_note(sunja).await.unwrap().unwrap();
+    assert_eq!(
+        engine.note_body(&sunja_rec).unwrap(),
+        "Her life is [[Sorrow]], [[Sorrow|grief]] and [[Sorrow#Origins]]."
+    );
+    let other_rec = engine.get_note(other).await.unwrap().unwrap();
+    assert_eq!(engine.note_body(&other_rec).unwrap(), "Not [[Hansel]].");
+
+    let mut back: Vec<i64> = engine
+        .backlinks(han)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|n| n.from.id)
+        .collect();
+    back.sort();
+    // Sunja twice: once to the note, once to its `#Origins`.
+    assert_eq!(back, [sunja, sunja, waiting]);
+    assert_eq!(engine.search_notes("Sorrow", 10).await.unwrap().len(), 3);
+
+    // Renaming back takes the file back to its old name.
+    engine.rename_note(han, "Han").await.unwrap();
+    let again = engine.get_note(han).await.unwrap().unwrap();
+    assert_eq!(again.file_path, before.file_path);
+}
+
+/// A title a wikilink could not name, or one another note already has, is
+/// refused before anything is written.
+#[tokio::test]
+async fn a_title_that_would_break_links_is_refused() {
+    let (_tmp, engine) = engine().await;
+    let han = note(&engine, None, "Han", "Grief.").await;
+    note(&engine, None, "Sunja", "Endures.").await;
+
+    for bad in ["Sunja", "sunja", "A|B", "[[x]]", "  "] {
+        assert!(engine.rename_note(han, bad).await.is_err(), "{bad}");
+    }
+    assert_eq!(engine.get_note(han).await.unwrap().unwrap().title, "Han");
+    assert!(engine.rename_note(han, "Han").await.unwrap().is_empty());
+}
@@ -0,0 +1,134 @@
+//! Adopting a reader's existing Obsidian vault.
+
+mod common;
+
+use std::path::Path;
+
+use common::{book, engine, seed_book};
+use readingbuddy::{AdoptMatch, Book};
+
+fn write(root: &Path, rel: &str, content: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+/// Each file is matched on the strongest evidence it has — an ISBN, its
+/// frontmatter title, the folder it sits in — and an unknown one is proposed
+/// as a free note. Adopting copies them under their books with our header
+/// added, keeps theirs, and resolves the wikilinks among them.
+#[tokio::test]
+async fn a_foreign_vault_is_matched_copied_and_linked() {
+    let (tmp, engine) = engine().await;
+    let pachinko = engine
+        .save_book(&Book {
+            isbn_13: Some("9781455563937".into()),
+            ..book("Pachinko")
+        })
+        .await
+        .unwrap()
+        .id
+        .unwrap();
+    let free_food = seed_book(&engine, "Free Food for Millionaires").await;
+
+    let theirs = tmp.path().join("obsidian");
+    write(
+        &theirs,
+        "Books/2024/Sunja.md",
+        "---\nisbn: 978-1-4555-6393-7\ntitle: Sunja the character\ntags: [fiction]\n---\n\nShe endures. See [[Han]].\n",
+    );
+    write(
+        &theirs,
+        "Reading/Han.md",
+        "---\nbook-title: \"[[Pachinko]]\"\nauthor:\n  - Min Jin Lee\ndate: 2024-03-02\n---\n\nHan, and [[Nowhere Yet]].\n",
+    );
+    write(
+        &theirs,
+        "Free Food for Millionaires/Casey.md",
+        "Restless, in [[Sunja]]'s shadow.\n",
+    );
+    write(&theirs, "Inbox/Shopping.md", "Milk.\n");
+    write(&theirs, ".obsidian/workspace.md", "not a note\n");
+
+    let plan = engine.plan_vault_adoption(&theirs).await.unwrap();
+    let by_title = |t: &str| plan.files.iter().find(|p| p.title == t).unwrap();
+    assert_eq!(plan.files.len(), 4, "{plan:?}");
+    assert_eq!(by_title("Sunja").book_id, Some(pachinko));
+    assert_eq!(by_title("Sunja").matched_by, Some(AdoptMatch::Isbn));
+    assert_eq!(by_title("Han").book_id, Some(pachinko));
+    assert_eq!(by_title("Han").matched_by, Some(AdoptMatch::Title));
+    assert_eq!(by_title("Casey").book_id, Some(free_food));
+    assert_eq!(by_title("Casey").matched_by, Some(AdoptMatch::Folder));
+    assert_eq!(by_title("Shopping").book_id, None);
+    assert!(
+        engine.list_notes(None).await.unwrap().is_empty(),
+        "a plan writes nothing"
+    );
+
+    let report = engine.adopt_vault(&plan).await.unwrap();
+    assert_eq!(report.adopted.len(), 4, "{report:?}");
+    assert_eq!((report.links_resolved, report.links_dangling), (2, 1));
+
+    let vault = tmp.path().join("vault");
+    let han = std::fs::read_to_string(vault.join("pachinko/Han.md")).unwrap();
+    assert!(han.starts_with("---\ntitle: \"Han\"\n"), "{han}");
+    assert!(han.contains("book: 9781455563937\n"), "{han}");
+    assert!(
+        han.contains("original-book-title: \"[[Pachinko]]\"\n"),
+        "{han}"
+    );
+    assert!(han.contains("date: 2024-03-02\n"), "{han}");
+    assert!(han.ends_with("---\n\nHan, and [[Nowhere Yet]].\n"), "{han}");
+    assert!(vault.join("unsorted/Shopping.md").exists());
+
+    let notes = engine.list_notes(Some(pachinko)).await.unwrap();
+    let han = notes.iter().find(|n| n.title == "Han").unwrap();
+    assert_eq!(
+        han.created_at.map(|t| t.unix_timestamp()),
+        Some(1_709_337_600),
+        "dated as written"
+    );
+    let sunja = notes.iter().find(|n| n.title == "Sunja").unwrap();
+    let back: Vec<String> = engine
+        .backlinks(sunja.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|n| n.from.title)
+        .collect();
+    assert_eq!(back, ["Casey"]);
+    assert_eq!(engine.search_notes("endures", 10).await.unwrap().len(), 1);
+
+    // Adopting the same vault again copies nothing twice.
+    let again = engine.adopt_vault(&plan).await.unwrap();
+    assert_eq!((again.adopted.len(), again.already), (0, 4));
+}
+
+/// A proposal the reader overrules is filed where they said; and our own vault
+/// is not something to adopt.
+#[tokio::test]
+async fn a_chosen_book_wins_and_our_own_vault_is_refused() {
+    let (tmp, engine) = engine().await;
+    let id = seed_book(&engine, "Pachinko").await;
+    let theirs = tmp.path().join("obsidian");
+    write(&theirs, "Misc/Thoughts.md", "On Sunja.\n");
+
+    let mut plan = engine.plan_vault_adoption(&theirs).await.unwrap();
+    assert_eq!(plan.files[0].book_id, None);
+    let chosen = engine.get_book(id).await.unwrap().unwrap();
+    plan.files[0].choose(Some(&chosen));
+    let report = engine.adopt_vault(&plan).await.unwrap();
+    assert_eq!(report.adopted[0].book_id, Some(id));
+    assert_eq!(
+        report.adopted[0].to,
+        Path::new("pachinko").join("Thoughts.md")
+    );
+
+    std::fs::create_dir_all(tmp.path().join("vault")).unwrap();
+    assert!(
+        engine
+            .plan_vault_adoption(&tmp.path().join("vault"))
+            .await
+            .is_err()
+    );
+}
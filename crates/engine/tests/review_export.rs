@@ -0,0 +1,87 @@
+//! A review exported as a page: what it carries, and that it is read live.
+
+use readingbuddy::{EngineError, ExportFormat};
+
+mod common;
+use common::{book, engine, highlight};
+
+/// The page is put together at export time — a highlight edited after it was
+/// cited exports edited, and a cover is inlined rather than linked.
+#[tokio::test]
+async fn a_review_exports_its_rating_dates_cover_and_live_citations() {
+    let (tmp, engine) = engine().await;
+    let cover = tmp.path().join("pachinko.png");
+    std::fs::write(&cover, b"\x89PNG not really").unwrap();
+    let mut b = book("Pachinko");
+    b.cover_path = Some(cover.display().to_string());
+    let book_id = engine.save_book(&b).await.unwrap().id.unwrap();
+    engine
+        .storage()
+        .update_progress(book_id, Some(490), Some(true))
+        .await
+        .unwrap();
+    let hid = engine
+        .storage()
+        .insert_highlight(
+            book_id,
+            &highlight("History has failed us", "2026-01-05 21:14:08"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+    let review = engine.open_review(book_id, None).await.unwrap();
+    let record = engine.storage().get_note(review.id).await.unwrap().unwrap();
+    engine
+        .update_note_body(&record, "Sunja, like [[Han|her son]], **endures**.")
+        .await
+        .unwrap();
+    engine.set_rating(review.id, 4.5).await.unwrap();
+    engine.cite(review.id, hid).await.unwrap();
+
+    let md = engine
+        .export_review(review.id, ExportFormat::Markdown)
+        .await
+        .unwrap();
+    assert!(md.starts_with("# Pachinko\n"), "{md}");
+    assert!(md.contains("*by Min Jin Lee*"), "{md}");
+    assert!(md.contains("**Rating:** 4.5 / 5"), "{md}");
+    assert!(md.contains("**Read:** "), "{md}");
+    assert!(md.contains("Sunja, like her son, **endures**."), "{md}");
+    assert!(
+        md.contains("> History has failed us\n>\n> — p. 12 · Ch 1"),
+        "{md}"
+    );
+    assert!(md.contains("pachinko.png"), "{md}");
+
+    let html = engine
+        .export_review(review.id, ExportFormat::Html)
+        .await
+        .unwrap();
+    assert!(html.contains("src=\"data:image/png;base64,"), "{html}");
+    assert!(
+        !html.contains("pachinko.png"),
+        "the page must not point at the disk"
+    );
+    assert!(html.contains("<strong>endures</strong>"), "{html}");
+    assert!(html.contains("<footer>p. 12 · Ch 1</footer>"), "{html}");
+    assert!(!html.contains("[["), "wikilinks mean nothing off the vault");
+}
+
+/// A reflection is the reader's own; only a review is for someone else.
+#[tokio::test]
+async fn only_a_review_exports() {
+    let (_tmp, engine) = engine().await;
+    let book_id = common::seed_book(&engine, "Pachinko").await;
+    let reflection = engine.open_reflection(book_id, None).await.unwrap();
+    assert!(matches!(
+        engine
+            .export_review(reflection.id, ExportFormat::Html)
+            .await,
+        Err(EngineError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        engine.export_review(9999, ExportFormat::Html).await,
+        Err(EngineError::NotFound(_))
+    ));
+}
@@ -0,0 +1,133 @@
+//! A sidecar sent by the reader over the network, through the facade.
+//!
+//! The wireless path has to land exactly where the wired one does, so every
+//! test here pulls a committed fixture both ways — or in both of its forms,
+//! the Lua and the JSON KOReader's `json.encode` would make of it — and
+//! compares. Offline throughout.
+
+mod common;
+
+use std::path::Path;
+
+use readingbuddy::{EngineError, MatchMethod, SidecarPayload};
+
+const PACHINKO_MD5: &str = "0d6ba6c47caf63b8b3d1a2b3c4d5e6f7";
+
+fn fixture(name: &str) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::read_to_string(common::place(dir.path(), name, name)).unwrap()
+}
+
+/// The table `json.encode` would send: the fixture evaluated, then serialised.
+fn as_json(src: &str) -> serde_json::Value {
+    let lua = mlua::Lua::new();
+    let value: mlua::Value = lua.load(src).eval().unwrap();
+    serde_json::to_value(&value).unwrap()
+}
+
+/// A pushed sidecar becomes the book a wired pull would have made, and a
+/// second push of the same file is the idempotent no-op the wired one is.
+#[tokio::test]
+async fn a_pushed_sidecar_lands_where_a_wired_pull_would() {
+    let (wired_dir, wired) = common::engine().await;
+    let sidecar = common::place(wired_dir.path(), "Pachinko.sdr", "Pachinko.sdr");
+    let by_wire = wired.pull_book_from_sidecar(&sidecar).await.unwrap();
+
+    let (_dir, engine) = common::engine().await;
+    let payload = SidecarPayload::Lua(fixture("Pachinko.sdr"));
+    let origin = Path::new("/mnt/onboard/Books/Pachinko.epub");
+    let pushed = engine
+        .pull_sidecar_payload(&payload, PACHINKO_MD5, Some(origin), None)
+        .await
+        .unwrap();
+    assert_eq!(pushed.stats.book_title, by_wire.stats.book_title);
+    assert_eq!(pushed.stats.inserted, by_wire.stats.inserted);
+    assert_eq!(pushed.stats.matched_by, MatchMethod::New);
+    let texts = |hs: Vec<readingbuddy::Highlight>| -> Vec<String> {
+        hs.into_iter().map(|h| h.text).collect()
+    };
+    assert_eq!(
+        texts(engine.list_highlights(pushed.stats.book_id).await.unwrap()),
+        texts(wired.list_highlights(by_wire.stats.book_id).await.unwrap())
+    );
+
+    let again = engine
+        .pull_sidecar_payload(&payload, &PACHINKO_MD5.to_uppercase(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(again.stats.book_id, pushed.stats.book_id);
+    assert_eq!(again.stats.matched_by, MatchMethod::Md5);
+    assert_eq!((again.stats.inserted, again.stats.updated), (0, 0));
+}
+
+/// The JSON form of a sidecar imports exactly what its Lua does — the modern
+/// layout, the legacy one, and a summary block.
+#[tokio::test]
+async fn the_json_form_reads_the_same_as_the_lua() {
+    for (name, md5) in [
+        ("Pachinko.sdr", PACHINKO_MD5),
+        ("Gen-Summary-Legacy.sdr", "11111111111111111111111111111111"),
+        ("Gen-Summary.sdr", "22222222222222222222222222222222"),
+    ] {
+        let src = fixture(name);
+        let (_a, from_lua) = common::engine().await;
+        let (_b, from_json) = common::engine().await;
+        let lua = from_lua
+            .pull_sidecar_payload(&SidecarPayload::Lua(src.clone()), md5, None, None)
+            .await
+            .unwrap();
+        let json = from_json
+            .pull_sidecar_payload(&SidecarPayload::Json(as_json(&src)), md5, None, None)
+            .await
+            .unwrap();
+        assert_eq!(json.stats.inserted, lua.stats.inserted, "{name}");
+        assert_eq!(json.stats.status, lua.stats.status, "{name}");
+        assert_eq!(json.stats.rating, lua.stats.rating, "{name}");
+        assert_eq!(
+            json.stats.percent_finished, lua.stats.percent_finished,
+            "{name}"
+        );
+        let notes = |hs: Vec<readingbuddy::Highlight>| -> Vec<(String, Option<String>)> {
+            hs.into_iter().map(|h| (h.text, h.ko_note)).collect()
+        };
+        assert_eq!(
+            notes(from_json.list_highlights(json.stats.book_id).await.unwrap()),
+            notes(from_lua.list_highlights(lua.stats.book_id).await.unwrap()),
+            "{name}"
+        );
+    }
+}
+
+/// The checksum the reader sends is the book's identity from then on: a
+/// sidecar without one takes it, a sidecar with a different one is refused,
+/// and something that is not a checksum at all is refused before anything is
+/// parsed.
+#[tokio::test]
+async fn the_checksum_is_required_and_must_agree() {
+    let (_dir, engine) = common::engine().await;
+
+    let summary = SidecarPayload::Lua(fixture("Gen-Summary.sdr"));
+    let md5 = "5f1e0c2b9a8d7e6f5a4b3c2d1e0f9a8b";
+    let pulled = engine
+        .pull_sidecar_payload(&summary, md5, None, None)
+        .await
+        .unwrap();
+    assert!(pulled.warnings.is_empty(), "{:#?}", pulled.warnings);
+    let again = engine
+        .pull_sidecar_payload(&summary, md5, None, None)
+        .await
+        .unwrap();
+    assert_eq!(again.stats.matched_by, MatchMethod::Md5);
+
+    let pachinko = SidecarPayload::Lua(fixture("Pachinko.sdr"));
+    for bad in [md5, "not-a-checksum", ""] {
+        let err = engine
+            .pull_sidecar_payload(&pachinko, bad, None, None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, EngineError::InvalidInput(_)),
+            "{bad}: {err:?}"
+        );
+    }
+}
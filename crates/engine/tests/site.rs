@@ -0,0 +1,192 @@
+//! `build_site`: what is published, what never is, and where it lands.
+
+use readingbuddy::EngineError;
+
+mod common;
+use common::{book, engine, seed_book};
+
+fn read(path: &std::path::Path) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+/// The review marked public is on its book's page; the private one, and a
+/// reflection that *claims* to be public, are nowhere in the site.
+#[tokio::test]
+async fn only_a_published_review_leaves_and_a_reflection_never_does() {
+    let (tmp, engine) = engine().await;
+    let cover = tmp.path().join("cover.jpg");
+    std::fs::write(&cover, b"jpeg-ish").unwrap();
+    let mut pachinko = book("Pachinko");
+    pachinko.cover_path = Some(cover.display().to_string());
+    let pachinko = engine.save_book(&pachinko).await.unwrap().id.unwrap();
+    let free_food = seed_book(&engine, "Free Food for Millionaires").await;
+    engine
+        .storage()
+        .add_book_tags(
+            pachinko,
+            "goodreads",
+            &[("fiction".into(), "Fiction".into())],
+        )
+        .await
+        .unwrap();
+    engine
+        .storage()
+        .update_progress(pachinko, Some(490), Some(true))
+        .await
+        .unwrap();
+
+    let published = engine.open_review(pachinko, None).await.unwrap();
+    let record = engine.get_note(published.id).await.unwrap().unwrap();
+    engine
+        .update_note_body(&record, "A family saga told **patiently**.")
+        .await
+        .unwrap();
+    engine.set_rating(published.id, 4.0).await.unwrap();
+    engine.set_review_public(published.id, true).await.unwrap();
+    assert!(engine.note_is_public(&record).unwrap());
+
+    let unpublished = engine.open_review(free_food, None).await.unwrap();
+    let record = engine.get_note(unpublished.id).await.unwrap().unwrap();
+    engine
+        .update_note_body(&record, "Not ready for anyone yet.")
+        .await
+        .unwrap();
+    engine.set_rating(unpublished.id, 3.0).await.unwrap();
+
+    // A reflection refuses the flag, and one written into its file by hand is
+    // ignored all the same.
+    let reflection = engine.open_reflection(pachinko, None).await.unwrap();
+    assert!(matches!(
+        engine.set_review_public(reflection.id, true).await,
+        Err(EngineError::InvalidInput(_))
+    ));
+    let content = read(&reflection.file).replacen("kind:", "public: true\nkind:", 1);
+    std::fs::write(&reflection.file, content).unwrap();
+    let record = engine.get_note(reflection.id).await.unwrap().unwrap();
+    engine
+        .update_note_body(&record, "Private grief about my own family.")
+        .await
+        .unwrap();
+
+    let out = tmp.path().join("site");
+    let report = engine.build_site(&out).await.unwrap();
+    assert_eq!((report.books, report.reviews), (2, 1));
+    assert_eq!((report.authors, report.tags), (1, 1));
+
+    let page = read(&out.join("books/pachinko.html"));
+    assert!(page.contains("<strong>patiently</strong>"), "{page}");
+    assert!(page.contains("4 / 5"), "{page}");
+    assert!(page.contains("src=\"../covers/pachinko.jpg\""), "{page}");
+    assert!(
+        page.contains("href=\"../authors/min-jin-lee.html\""),
+        "{page}"
+    );
+    assert!(page.contains("href=\"../tags/fiction.html\""), "{page}");
+    assert!(out.join("covers/pachinko.jpg").is_file());
+    assert!(out.join("tags/fiction.html").is_file());
+    assert!(
+        read(&out.join("authors/min-jin-lee.html"))
+            .contains("books/free-food-for-millionaires.html")
+    );
+    // The rating is on the page even though the review it was given in is not.
+    let unpublished_page = read(&out.join("books/free-food-for-millionaires.html"));
+    assert!(unpublished_page.contains("3 / 5"), "{unpublished_page}");
+
+    let index = read(&out.join("index.html"));
+    assert!(index.contains("Not finished"), "{index}");
+    assert!(index.contains("href=\"books/pachinko.html\""), "{index}");
+
+    for dir in ["", "books", "authors", "tags"] {
+        for f in std::fs::read_dir(out.join(dir)).unwrap() {
+            let path = f.unwrap().path();
+            if path.extension().is_some_and(|e| e == "html") {
+                let html = read(&path);
+                assert!(!html.contains("Private grief"), "{}", path.display());
+                assert!(!html.contains("Not ready"), "{}", path.display());
+            }
+        }
+    }
+
+    // Unpublishing takes it back off at the next build.
+    engine.set_review_public(published.id, false).await.unwrap();
+    let report = engine.build_site(&out).await.unwrap();
+    assert_eq!(report.reviews, 0);
+    assert!(!read(&out.join("books/pachinko.html")).contains("patiently"));
+}
+
+/// Two books whose titles slug alike each get a page, and the older keeps the
+/// bare address.
+#[tokio::test]
+async fn alike_titles_get_their_own_stable_pages() {
+    let (tmp, engine) = engine().await;
+    let first = seed_book(&engine, "Han").await;
+    let mut second = book("HAN!");
+    second.authors = vec!["Someone Else".into()];
+    let second = engine.save_book(&second).await.unwrap().id.unwrap();
+    assert!(first < second);
+
+    let out = tmp.path().join("site");
+    engine.build_site(&out).await.unwrap();
+    assert!(out.join("books/han.html").is_file());
+    assert!(out.join(format!("books/han-{second}.html")).is_file());
+    assert!(read(&out.join("books/han.html")).contains("<h1>Han</h1>"));
+}
+
+/// A book's `-{id}` fallback can be another book's own slug: "Volume" with
+/// id 3 against "Volume 3". Neither page may be written over the other.
+#[tokio::test]
+async fn a_fallback_slug_does_not_take_another_books_page() {
+    let (tmp, engine) = engine().await;
+    let first = seed_book(&engine, "Volume").await;
+    // The title names the id the next book will get.
+    let mut numbered = book(&format!("Volume {}", first + 2));
+    numbered.authors = vec!["Someone Else".into()];
+    engine.save_book(&numbered).await.unwrap();
+    let mut again = book("VOLUME");
+    again.authors = vec!["Another Person".into()];
+    let again = engine.save_book(&again).await.unwrap().id.unwrap();
+    assert_eq!(again, first + 2);
+
+    let out = tmp.path().join("site");
+    let report = engine.build_site(&out).await.unwrap();
+    assert_eq!(report.books, 3);
+    assert!(read(&out.join("books/volume.html")).contains("<h1>Volume</h1>"));
+    let numbered = read(&out.join(format!("books/volume-{again}.html")));
+    assert!(
+        numbered.contains(&format!("<h1>Volume {again}</h1>")),
+        "{numbered}"
+    );
+    assert!(read(&out.join(format!("books/volume-{again}-2.html"))).contains("<h1>VOLUME</h1>"));
+}
+
+/// Two tags, or two authors, that slug alike are two pages rather than one
+/// under whichever name came first.
+#[tokio::test]
+async fn alike_tags_and_authors_are_not_merged() {
+    let (tmp, engine) = engine().await;
+    let first = seed_book(&engine, "Dune").await;
+    let mut other = book("Hyperion");
+    other.authors = vec!["MIN JIN LEE".into()];
+    let second = engine.save_book(&other).await.unwrap().id.unwrap();
+    for (id, tag) in [(first, "Sci-Fi"), (second, "sci fi")] {
+        engine
+            .storage()
+            .add_book_tags(id, "goodreads", &[(tag.into(), tag.into())])
+            .await
+            .unwrap();
+    }
+
+    let out = tmp.path().join("site");
+    let report = engine.build_site(&out).await.unwrap();
+    assert_eq!((report.authors, report.tags), (2, 2));
+    // "MIN JIN LEE" sorts before "Min Jin Lee", and so keeps the bare slug.
+    assert!(read(&out.join("authors/min-jin-lee.html")).contains("Hyperion"));
+    let later = read(&out.join("authors/min-jin-lee-2.html"));
+    assert!(
+        later.contains("Dune") && !later.contains("Hyperion"),
+        "{later}"
+    );
+    assert!(read(&out.join("tags/sci-fi.html")).contains("<h1>Sci-Fi</h1>"));
+    assert!(read(&out.join("tags/sci-fi-2.html")).contains("<h1>sci fi</h1>"));
+    assert!(read(&out.join("books/dune.html")).contains("href=\"../authors/min-jin-lee-2.html\""));
+}
@@ -0,0 +1,132 @@
+//! Note templates from the data root, expanded as notes are created.
+
+mod common;
+
+use std::path::Path;
+
+use common::{engine, highlight, seed_book};
+use readingbuddy::{NewHighlight, NewNoteInput, NoteKind};
+
+fn template(dir: &Path, name: &str, body: &str) {
+    let path = dir.join(format!("{name}.md"));
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, body).unwrap();
+}
+
+/// A reflection opens on its kind's template, filled from the book and the
+/// reading — and a book's tag picks its own template over the plain one.
+#[tokio::test]
+async fn an_opened_reflection_starts_from_its_template() {
+    let (tmp, engine) = engine().await;
+    let dir = tmp.path().join("templates");
+    template(
+        &dir,
+        "reflection",
+        "# {{book}}\nby {{authors}}, started {{started}}\n\n{{highlights}}\n",
+    );
+    template(&dir, "fiction/reflection", "Fiction: {{book}}\n");
+    let book = seed_book(&engine, "Pachinko").await;
+    let other = seed_book(&engine, "Free Food for Millionaires").await;
+    engine
+        .storage()
+        .add_book_tags(other, "goodreads", &[("fiction".into(), "Fiction".into())])
+        .await
+        .unwrap();
+    // A reading from New Year, and a highlight made during it.
+    let s = engine.storage();
+    s.ensure_reading(book, Some(1_767_225_600), "manual")
+        .await
+        .unwrap();
+    s.insert_highlight(
+        book,
+        &NewHighlight {
+            page: Some(40),
+            ..highlight(
+                "History has failed us,\nbut no matter.",
+                "2026-01-04 10:00:00",
+            )
+        },
+    )
+    .await
+    .unwrap();
+    s.attribute_highlights(book).await.unwrap();
+
+    let reflection = engine.open_reflection(book, None).await.unwrap();
+    let record = engine.get_note(reflection.id).await.unwrap().unwrap();
+    assert_eq!(
+        engine.note_body(&record).unwrap(),
+        "# Pachinko\nby Min Jin Lee, started 2026-01-01\n\n\
+         > History has failed us,\n> but no matter.\n> — p. 40"
+    );
+    assert_eq!(
+        record.title, "Reflection: Pachinko",
+        "the title is not the template's"
+    );
+
+    let tagged = engine.open_reflection(other, None).await.unwrap();
+    let record = engine.get_note(tagged.id).await.unwrap().unwrap();
+    assert_eq!(
+        engine.note_body(&record).unwrap(),
+        "Fiction: Free Food for Millionaires"
+    );
+}
+
+/// A default is for a note created empty; a note that arrives with its words
+/// is left alone unless a template is named, which then takes the words at
+/// `{{body}}`. A name that is not there is an error, not a silent blank.
+#[tokio::test]
+async fn a_named_template_wraps_the_words_and_a_default_does_not() {
+    let (tmp, engine) = engine().await;
+    let dir = tmp.path().join("templates");
+    template(&dir, "note", "DEFAULT\n");
+    template(&dir, "quote", "> {{body}}\n\np. {{page}} of {{book}}\n");
+    let book = seed_book(&engine, "Pachinko").await;
+    assert_eq!(engine.templates(), ["note", "quote"]);
+
+    let plain = engine
+        .create_note(NewNoteInput {
+            book_id: Some(book),
+            body: "Han.".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let quoted = engine
+        .create_note(NewNoteInput {
+            book_id: Some(book),
+            page: Some(40),
+            body: "History has failed us".into(),
+            template: Some("quote".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let plain = engine.get_note(plain.id).await.unwrap().unwrap();
+    assert_eq!(engine.note_body(&plain).unwrap(), "Han.");
+    let quoted = engine.get_note(quoted.id).await.unwrap().unwrap();
+    assert_eq!(quoted.title, "History has failed us", "named by its words");
+    assert_eq!(
+        engine.note_body(&quoted).unwrap(),
+        "> History has failed us\n\np. 40 of Pachinko"
+    );
+
+    let preview = engine
+        .expand_template(&NewNoteInput {
+            kind: NoteKind::Note,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(preview.as_deref(), Some("DEFAULT\n"));
+
+    let missing = engine
+        .create_note(NewNoteInput {
+            body: "x".into(),
+            template: Some("nope".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(missing.contains("note, quote"), "{missing}");
+}
@@ -0,0 +1,205 @@
+//! Losing `database/` and getting the notes back from the vault.
+//!
+//! Each test writes notes through one engine, then opens a second engine on a
+//! fresh database over the *same* vault: what the first one knew and the files
+//! do not say is exactly what a real loss would take with it.
+
+mod common;
+
+use std::path::Path;
+
+use common::{book, engine, highlight};
+use readingbuddy::{Book, Engine, EngineConfig, NewHighlight, NewNoteInput, NoteKind};
+
+/// A second engine, on an empty database, over an existing vault.
+async fn engine_over(vault: &Path) -> (tempfile::TempDir, Engine) {
+    let tmp = tempfile::tempdir().expect("tempdir");
+    let config = EngineConfig {
+        db_url: "sqlite::memory:".into(),
+        images_dir: tmp.path().join("database/images"),
+        files_dir: tmp.path().join("database/files"),
+        vault_dir: vault.to_path_buf(),
+        log_dir: tmp.path().join("logs"),
+        templates_dir: tmp.path().join("templates"),
+        google_api_key: None,
+        calibre_bin_dir: None,
+    };
+    (tmp, Engine::open(config).await.expect("engine opens"))
+}
+
+/// On its own page, so a note written from it can be told apart.
+fn history() -> NewHighlight {
+    NewHighlight {
+        page: Some(40),
+        ..highlight("History has failed us", "2026-01-04 10:00:00")
+    }
+}
+
+fn pachinko() -> Book {
+    Book {
+        isbn_13: Some("9781455563937".into()),
+        ..book("Pachinko")
+    }
+}
+
+/// Notes, their links, the reflection and the citations all come back on the
+/// library rebuilt by a re-import; the note on a book not re-imported is
+/// reported rather than guessed at, and a dry run writes nothing.
+#[tokio::test]
+async fn a_lost_database_is_rebuilt_from_the_vault() {
+    let (tmp, before) = engine().await;
+    let book_id = before.save_book(&pachinko()).await.unwrap().id.unwrap();
+    let stray = before
+        .save_book(&book("Free Food for Millionaires"))
+        .await
+        .unwrap()
+        .id
+        .unwrap();
+    let s = before.storage();
+    s.insert_highlight(
+        book_id,
+        &highlight("Wealth is a kind of weather", "2026-01-03 10:00:00"),
+    )
+    .await
+    .unwrap();
+    let hid = s
+        .insert_highlight(book_id, &history())
+        .await
+        .unwrap()
+        .unwrap();
+    let han = before
+        .create_note(NewNoteInput {
+            book_id: Some(book_id),
+            highlight_id: Some(hid),
+            page: Some(40),
+            title: Some("Han".into()),
+            body: "See [[Sunja]].\n\n> Wealth is a kind of\n> weather\n".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    before
+        .create_note(NewNoteInput {
+            book_id: Some(book_id),
+            title: Some("Sunja".into()),
+            body: "She endures.".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let reflection = before.open_reflection(book_id, None).await.unwrap();
+    before
+        .create_note(NewNoteInput {
+            book_id: Some(stray),
+            title: Some("Casey".into()),
+            body: "Restless.".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let han_created = before.get_note(han.id).await.unwrap().unwrap().created_at;
+
+    // The library comes back from a re-import, numbered afresh; the book with
+    // no ISBN does not come back at all.
+    let (_db, after) = engine_over(&tmp.path().join("vault")).await;
+    after.save_book(&book("Something Else")).await.unwrap();
+    let new_id = after.save_book(&pachinko()).await.unwrap().id.unwrap();
+    let s = after.storage();
+    s.insert_highlight(new_id, &history()).await.unwrap();
+    s.insert_highlight(
+        new_id,
+        &highlight("Wealth is a kind of weather", "2026-01-03 10:00:00"),
+    )
+    .await
+    .unwrap();
+
+    let dry = after.reindex_vault(true).await.unwrap();
+    assert_eq!((dry.files, dry.indexed, dry.on_books), (4, 3, 3), "{dry:?}");
+    assert_eq!(dry.unplaced.len(), 1, "{dry:?}");
+    assert!(dry.unplaced[0].reason.contains("Free Food"), "{dry:?}");
+    assert!(after.list_notes(None).await.unwrap().is_empty());
+
+    let report = after.reindex_vault(false).await.unwrap();
+    assert_eq!(report.indexed, 3);
+    assert_eq!(report.unplaced, dry.unplaced);
+    // Readings come back from a device, not from a re-import of the book: the
+    // reflection's is gone, and it is filed under a new one.
+    assert_eq!(report.detached.len(), 1, "{report:?}");
+    assert_eq!(report.detached[0].reason, "reading 1 is gone");
+    assert_eq!(report.citations, 2, "the anchor and the quote");
+
+    let notes = after.list_notes(Some(new_id)).await.unwrap();
+    let han = notes
+        .iter()
+        .find(|n| n.title == "Han")
+        .expect("Han is back");
+    assert_eq!(han.page, Some(40));
+    assert_eq!(
+        han.created_at, han_created,
+        "dated as written, not as rebuilt"
+    );
+    let out = after.outgoing_links(han.id).await.unwrap();
+    assert_eq!(out[0].to.as_ref().map(|n| n.title.as_str()), Some("Sunja"));
+    // The old anchor's id now belongs to the other highlight; the page is what
+    // finds the one it was written from.
+    assert_ne!(han.highlight_id, Some(hid));
+    let mut cited: Vec<String> = after
+        .citations_for(han.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|h| h.text)
+        .collect();
+    cited.sort();
+    assert_eq!(
+        cited,
+        ["History has failed us", "Wealth is a kind of weather"]
+    );
+
+    let refl = after
+        .note_for_reading(
+            after.storage().list_readings(new_id).await.unwrap()[0].id,
+            NoteKind::Reflection.as_str(),
+        )
+        .await
+        .unwrap()
+        .expect("the reflection is filed under a reading again");
+    assert_eq!(refl.title, reflection.title);
+    assert_eq!(after.search_notes("endures", 10).await.unwrap().len(), 1);
+
+    // Idempotent: a second run finds everything already there.
+    let again = after.reindex_vault(false).await.unwrap();
+    assert_eq!((again.kept, again.indexed, again.citations), (3, 0, 0));
+}
+
+/// A file from before the frontmatter carried a title gets the one the links
+/// to it use, when that slugifies back to its file name.
+#[tokio::test]
+async fn an_old_file_without_a_title_takes_the_one_its_links_use() {
+    let (tmp, engine) = engine().await;
+    let dir = tmp.path().join("vault/unsorted");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("20250301120000-sunja-s-choice.md"),
+        "---\nkind: note\ncreated: 2025-03-01T12:00:00Z\n---\n\nShe stays.\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("20250302120000-han.md"),
+        "---\nkind: note\ncreated: 2025-03-02T12:00:00Z\n---\n\nHan, and [[Sunja's Choice]].\n",
+    )
+    .unwrap();
+
+    let report = engine.reindex_vault(false).await.unwrap();
+    assert_eq!(report.indexed, 2, "{report:?}");
+    let mut titles: Vec<String> = engine
+        .list_notes(None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|n| n.title)
+        .collect();
+    titles.sort();
+    // Nothing names the other one, so its slug is all there is.
+    assert_eq!(titles, ["Sunja's Choice", "han"]);
+}
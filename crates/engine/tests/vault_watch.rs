@@ -0,0 +1,174 @@
+//! The vault changed by someone else, and the index following it.
+//!
+//! These drive `Engine::refresh_vault_paths` with the paths a `VaultWatcher`
+//! would have settled on, rather than a real watcher: the debounce has its own
+//! tests in `watch.rs`, and what is under test here is what a settled path
+//! *means* — an edit, a stranger, a move, a delete.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::engine;
+use readingbuddy::{DiagnosticKind, NewNoteInput, Severity};
+
+fn note(title: &str, body: &str) -> NewNoteInput {
+    NewNoteInput {
+        title: Some(title.into()),
+        body: body.into(),
+        ..Default::default()
+    }
+}
+
+/// An edit made in Obsidian is searchable and linked once it settles; our own
+/// save echoing back through the watcher is not news.
+#[tokio::test]
+async fn an_outside_edit_is_reindexed_and_our_own_save_is_not_reported() {
+    let (_tmp, engine) = engine().await;
+    let han = engine
+        .create_note(note("Han", "Grief, mostly."))
+        .await
+        .unwrap();
+    assert!(
+        engine
+            .refresh_vault_paths(std::slice::from_ref(&han.file))
+            .await
+            .unwrap()
+            .is_empty(),
+        "the create echoing back"
+    );
+
+    let content = std::fs::read_to_string(&han.file).unwrap();
+    std::fs::write(
+        &han.file,
+        content.replace("Grief, mostly.", "Grief and [[Sunja]]'s endurance."),
+    )
+    .unwrap();
+    let changed = engine
+        .refresh_vault_paths(std::slice::from_ref(&han.file))
+        .await
+        .unwrap();
+    assert_eq!(changed.len(), 1, "{changed:?}");
+    assert!(matches!(
+        changed[0].kind,
+        DiagnosticKind::NoteRefreshed { .. }
+    ));
+    assert_eq!(changed[0].severity, Severity::Info);
+    assert_eq!(engine.search_notes("endurance", 10).await.unwrap().len(), 1);
+    let out = engine.outgoing_links(han.id).await.unwrap();
+    assert_eq!(out[0].target_title, "Sunja");
+
+    let rec = engine.get_note(han.id).await.unwrap().unwrap();
+    engine
+        .update_note_body(&rec, "Grief and [[Sunja]]'s endurance, again.")
+        .await
+        .unwrap();
+    assert!(
+        engine
+            .refresh_vault_paths(&[han.file])
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+/// A file written straight into the vault is indexed under its file name, and
+/// the forward reference that was waiting for that name resolves to it.
+#[tokio::test]
+async fn a_new_file_is_indexed_and_resolves_what_was_waiting_for_it() {
+    let (tmp, engine) = engine().await;
+    let han = engine
+        .create_note(note("Han", "See [[Sunja]]."))
+        .await
+        .unwrap();
+    let dir = tmp.path().join("vault/characters");
+    std::fs::create_dir_all(&dir).unwrap();
+    let sunja = dir.join("Sunja.md");
+    std::fs::write(&sunja, "---\nkind: session\n---\n\nShe endures.\n").unwrap();
+
+    // The folder, as a platform that reports only the directory would.
+    let changed = engine.refresh_vault_paths(&[dir]).await.unwrap();
+    assert_eq!(changed.len(), 1, "{changed:?}");
+    assert_eq!(
+        changed[0].kind,
+        DiagnosticKind::NoteIndexed {
+            path: PathBuf::from("characters/Sunja.md")
+        }
+    );
+    let out = engine.outgoing_links(han.id).await.unwrap();
+    let to = out[0].to.as_ref().expect("resolved");
+    assert_eq!(to.title, "Sunja");
+    assert_eq!(to.kind, "session");
+    assert_eq!(to.file_path, "characters/Sunja.md");
+}
+
+/// A move keeps the row — so what links to it still does — and a delete takes
+/// it out of the index without losing it, until a move brings it back.
+#[tokio::test]
+async fn a_move_keeps_the_note_and_a_delete_tombstones_it() {
+    let (tmp, engine) = engine().await;
+    let sunja = engine
+        .create_note(note("Sunja", "She endures."))
+        .await
+        .unwrap();
+    let han = engine
+        .create_note(note("Han", "See [[Sunja]]."))
+        .await
+        .unwrap();
+    let old_rel = engine.get_note(sunja.id).await.unwrap().unwrap().file_path;
+
+    let moved = tmp.path().join("vault/characters/sunja.md");
+    std::fs::create_dir_all(moved.parent().unwrap()).unwrap();
+    std::fs::rename(&sunja.file, &moved).unwrap();
+    let changed = engine
+        .refresh_vault_paths(&[sunja.file.clone(), moved.clone()])
+        .await
+        .unwrap();
+    assert_eq!(
+        changed.iter().map(|d| &d.kind).collect::<Vec<_>>(),
+        [&DiagnosticKind::NoteMoved {
+            from: PathBuf::from(&old_rel),
+            to: PathBuf::from("characters/sunja.md"),
+        }]
+    );
+    let out = engine.outgoing_links(han.id).await.unwrap();
+    assert_eq!(out[0].to.as_ref().map(|n| n.id), Some(sunja.id));
+
+    let kept = std::fs::read_to_string(&moved).unwrap();
+    std::fs::remove_file(&moved).unwrap();
+    let changed = engine
+        .refresh_vault_paths(std::slice::from_ref(&moved))
+        .await
+        .unwrap();
+    assert!(matches!(
+        changed[0].kind,
+        DiagnosticKind::NoteTombstoned { .. }
+    ));
+    assert!(
+        engine
+            .list_notes(None)
+            .await
+            .unwrap()
+            .iter()
+            .all(|n| n.id != sunja.id)
+    );
+    assert!(engine.search_notes("endures", 10).await.unwrap().is_empty());
+    let out = engine.outgoing_links(han.id).await.unwrap();
+    assert!(out[0].to.is_none(), "a link to a missing file dangles");
+
+    // Restored from Obsidian's trash, somewhere else again: the same note, and
+    // the link that dangled while it was away resolves to it once more.
+    let back = tmp.path().join("vault/sunja.md");
+    std::fs::write(&back, kept).unwrap();
+    let changed = engine
+        .refresh_vault_paths(std::slice::from_ref(&back))
+        .await
+        .unwrap();
+    assert!(
+        matches!(changed[0].kind, DiagnosticKind::NoteMoved { .. }),
+        "{changed:?}"
+    );
+    assert_eq!(engine.search_notes("endures", 10).await.unwrap().len(), 1);
+    let out = engine.outgoing_links(han.id).await.unwrap();
+    assert_eq!(out[0].to.as_ref().map(|n| n.id), Some(sunja.id));
+}
@@ -152,6 +152,7 @@ async fn a_device_note_edit_reaches_us_through_the_cache_and_spares_everything_o
             page: None,
             location: None,
             highlight_id: Some(before_id),
+            template: None,
         })
         .await
         .unwrap();
@@ -0,0 +1,162 @@
+//! Editions grouped into a work through the facade: they stay separate books,
+//! and what was read, rated and kept in one answers for all of them.
+
+use std::collections::BTreeSet;
+
+use readingbuddy::{Book, BookFilter, BookQuery, BookStatus, CorpusOrder};
+
+mod common;
+use common::{engine, highlight};
+
+fn edition(title: &str, isbn: &str, year: i64) -> Book {
+    Book {
+        title: Some(title.into()),
+        authors: vec!["Han Kang".into()],
+        isbn_13: Some(isbn.into()),
+        publish_year: Some(year),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn reading_one_edition_answers_for_the_work() {
+    let (_tmp, engine) = engine().await;
+    let korean = engine
+        .save_book(&edition("채식주의자", "9788936433598", 2007))
+        .await
+        .unwrap()
+        .id
+        .unwrap();
+    let english = engine
+        .save_book(&edition("The Vegetarian", "9780553448184", 2015))
+        .await
+        .unwrap()
+        .id
+        .unwrap();
+
+    let storage = engine.storage();
+    let reading = storage
+        .record_reading(english, Some(1_000), Some(2_000), "finished", "manual")
+        .await
+        .unwrap();
+    let review = engine.open_review(english, Some(reading)).await.unwrap();
+    let scale = storage
+        .rating_scale_by_name("goodreads")
+        .await
+        .unwrap()
+        .expect("the seeded scale");
+    storage
+        .set_review_rating(review.id, &scale, 4.0)
+        .await
+        .unwrap();
+    storage
+        .insert_highlight(
+            english,
+            &highlight("Before the dream", "2026-01-05 21:14:08"),
+        )
+        .await
+        .unwrap();
+
+    // Apart, the original knows nothing of the translation.
+    let alone = engine.work_history(korean).await.unwrap();
+    assert!(alone.work.is_none());
+    assert!(!alone.has_read());
+    assert!(alone.rating.is_none());
+
+    let work = engine.attach_edition(english, korean).await.unwrap();
+    assert_eq!(work.title, "채식주의자");
+    assert_eq!(work.editions.len(), 2);
+
+    let h = engine.work_history(korean).await.unwrap();
+    assert!(h.has_read(), "the translation was read");
+    assert_eq!(h.read_as().and_then(|b| b.id), Some(english));
+    assert_eq!(h.rating.map(|r| r.value), Some(4.0));
+    // Still two books, each with its own ISBN and readings.
+    assert!(storage.list_readings(korean).await.unwrap().is_empty());
+    assert_eq!(
+        storage
+            .get_book(english)
+            .await
+            .unwrap()
+            .unwrap()
+            .isbn_13
+            .as_deref(),
+        Some("9780553448184")
+    );
+
+    // The author corpus lists the work once, with the translation's highlight.
+    let corpus = engine
+        .author_corpus("Han Kang", CorpusOrder::Published)
+        .await
+        .unwrap();
+    assert_eq!(corpus.books.len(), 1);
+    assert_eq!(corpus.books[0].book.id, Some(korean));
+    assert_eq!(corpus.books[0].editions.len(), 1);
+    assert_eq!(corpus.highlight_count(), 1);
+    assert_eq!(corpus.books[0].readings.len(), 1);
+
+    assert!(engine.detach_edition(english).await.unwrap());
+    assert!(!engine.work_history(korean).await.unwrap().has_read());
+    assert_eq!(
+        engine
+            .author_corpus("Han Kang", CorpusOrder::Published)
+            .await
+            .unwrap()
+            .books
+            .len(),
+        2
+    );
+}
+
+/// The status filter asks the work: the original of a translation read to the
+/// end lists as finished, not as a book never started.
+#[tokio::test]
+async fn a_status_filter_answers_for_the_work() {
+    let (_tmp, engine) = engine().await;
+    let korean = engine
+        .save_book(&edition("채식주의자", "9788936433598", 2007))
+        .await
+        .unwrap()
+        .id
+        .unwrap();
+    let english = engine
+        .save_book(&edition("The Vegetarian", "9780553448184", 2015))
+        .await
+        .unwrap()
+        .id
+        .unwrap();
+    engine
+        .storage()
+        .record_reading(english, Some(1_000), Some(2_000), "finished", "manual")
+        .await
+        .unwrap();
+
+    let titles = |status| {
+        let engine = &engine;
+        async move {
+            let query = BookQuery {
+                filter: BookFilter {
+                    status: Some(status),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let page = engine.query_books(&query).await.unwrap();
+            page.books
+                .into_iter()
+                .filter_map(|b| b.title)
+                .collect::<BTreeSet<_>>()
+        }
+    };
+    assert_eq!(
+        titles(BookStatus::Unstarted).await,
+        BTreeSet::from(["채식주의자".to_string()])
+    );
+
+    engine.attach_edition(english, korean).await.unwrap();
+    assert!(titles(BookStatus::Unstarted).await.is_empty());
+    assert_eq!(titles(BookStatus::Finished).await.len(), 2);
+    // The row is still the edition's: nothing was read in Korean.
+    let original = engine.storage().get_book(korean).await.unwrap().unwrap();
+    assert!(!original.finished);
+}
@@ -12,9 +12,11 @@ use ratatui::backend::Backend;
 use ratatui::layout::Position;
 use ratatui::widgets::ListState;
 use readingbuddy::{
-    Book, BookSort, DeviceBook, DeviceState, Diagnostic, Engine, EngineError, FlashcardRow,
-    Highlight, MatchCandidate, MountEvent, MountWatcher, NewNoteInput, NoteKind, NoteRecord,
-    RankedResult, Reading, SearchRequest,
+    AuthorCorpus, Backlink, Book, BookFilter, BookQuery, CardFormat, CorpusOrder, DeviceBook,
+    DeviceState, Diagnostic, Engine, EngineError, ExportFormat, FlashcardRow, Highlight,
+    LibraryHit, MatchCandidate, MountEvent, MountWatcher, NewNoteInput, NoteKind, NoteRecord,
+    PluginState, QuoteStyle, RankedResult, Reading, SearchRequest, StudyCard, VaultWatcher,
+    WorkHistory,
 };
 
 use crossterm::event::KeyModifiers;
@@ -126,6 +128,34 @@ pub enum Screen {
     /// of it lands or none does, because the engine matches a row against the
     /// library rather than the other way round.
     Goodreads,
+    /// Every book by one author, and what was kept from each. Reached from a
+    /// book's Info section, where the author rows are the way in.
+    Author,
+    /// The cards that are due, one at a time: a word or a passage, where it
+    /// came from, then — once it is shown — a grade. Reached from the menu.
+    Study,
+}
+
+/// What the search screen is searching.
+///
+/// Two searches behind one screen rather than a second screen: the question is
+/// the same — "where was that?" — and only the answer's origin differs. Tab
+/// swaps between them and asks the last query again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchScope {
+    /// OpenLibrary and Google Books: books to add.
+    #[default]
+    Online,
+    /// Our own notes and highlights: things already written.
+    Library,
+}
+
+/// A library search hit, with the book it belongs to already loaded — the row
+/// shows the title, and a hit is opened into that book.
+#[derive(Debug, Clone)]
+pub struct LibraryRow {
+    pub hit: LibraryHit,
+    pub book: Option<Book>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -194,8 +224,8 @@ pub const MENU: [(MenuItem, &str, &str); 12] = [
     ),
     (
         MenuItem::Cards,
-        "Flashcards",
-        "count the cards waiting for export",
+        "Review cards",
+        "the words and passages that are due",
     ),
     (
         MenuItem::Settings,
@@ -237,12 +267,20 @@ pub struct BookView {
     /// list is tiny (one row per read) and it is fetched on the same pass as the
     /// other three, so it costs a query rather than a design.
     pub readings: Vec<Reading>,
+    /// Ids of the highlights in the study deck, for the list's mark and for
+    /// `c` to know which way it toggles.
+    pub studied: Vec<i64>,
+    /// The book as a work: its other editions, and whether one of them was
+    /// the one read. `readings` above stays this edition's — the gutter
+    /// numbers this edition's highlights, which no other edition's read holds.
+    pub work: Option<WorkHistory>,
 }
 
 impl BookView {
     pub fn tab_len(&self, tab: BookTab) -> usize {
         match tab {
-            BookTab::Info => 0,
+            // The author rows are Info's list: Enter on one opens the author.
+            BookTab::Info => self.book.authors.len(),
             BookTab::Notes => self.notes.len(),
             BookTab::Highlights => self.highlights.len(),
             BookTab::Cards => self.cards.len(),
@@ -386,6 +424,69 @@ impl CalibreRowState {
     }
 }
 
+/// One author's corpus, flattened into the rows the author screen lists.
+pub struct AuthorView {
+    pub corpus: AuthorCorpus,
+    pub order: CorpusOrder,
+    pub rows: Vec<CorpusRow>,
+    pub state: ListState,
+}
+
+/// A row of the author screen, by index into [`AuthorCorpus::books`] and then
+/// into that book's highlights or notes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorpusRow {
+    Book(usize),
+    Highlight(usize, usize),
+    Note(usize, usize),
+}
+
+impl AuthorView {
+    pub fn new(corpus: AuthorCorpus, order: CorpusOrder) -> Self {
+        let mut rows = Vec::new();
+        for (b, entry) in corpus.books.iter().enumerate() {
+            rows.push(CorpusRow::Book(b));
+            rows.extend((0..entry.highlights.len()).map(|i| CorpusRow::Highlight(b, i)));
+            rows.extend((0..entry.notes.len()).map(|i| CorpusRow::Note(b, i)));
+        }
+        let mut state = ListState::default();
+        if !rows.is_empty() {
+            state.select(Some(0));
+        }
+        AuthorView {
+            corpus,
+            order,
+            rows,
+            state,
+        }
+    }
+
+    /// The book the selected row belongs to.
+    pub fn selected_book(&self) -> Option<&Book> {
+        let b = match self.rows.get(self.state.selected()?)? {
+            CorpusRow::Book(b) | CorpusRow::Highlight(b, _) | CorpusRow::Note(b, _) => *b,
+        };
+        Some(&self.corpus.books[b].book)
+    }
+}
+
+/// A review session: the cards that were due when it opened, worked through in
+/// order. Read once rather than re-asked after every grade, so a card failed
+/// here comes back tomorrow as the schedule says, not again in five seconds.
+pub struct StudyView {
+    pub queue: Vec<StudyCard>,
+    /// Index of the card on screen; past the end is "nothing left".
+    pub pos: usize,
+    /// Has the back of the current card been shown? A grade waits for it.
+    pub revealed: bool,
+}
+
+impl StudyView {
+    pub fn current(&self) -> Option<&StudyCard> {
+        self.queue.get(self.pos)
+    }
+}
+
 /// A Goodreads CSV as the dry run described it, before anything is written.
 ///
 /// Held whole rather than flattened into the status line because that is the
@@ -493,6 +594,17 @@ pub enum LinkTarget {
     Goodreads { external_id: String },
 }
 
+/// The paired readers, opened over the device shelf by `w`.
+///
+/// A list of the registry as `Engine::list_devices` returns it — pending codes,
+/// paired readers and revoked ones alike. Revoked rows stay because the engine
+/// keeps them: what a revoked reader pushed still names it, and a list that
+/// forgot it would leave those names unexplained.
+pub struct ReadersPanel {
+    pub devices: Vec<readingbuddy::Device>,
+    pub state: ListState,
+}
+
 /// What an open text input is collecting, so `commit` knows what to do.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InputContext {
@@ -529,6 +641,12 @@ pub enum InputContext {
     /// as a note's page anchor.
     ConvertInput,
     ConvertOutput,
+    /// Where to write the selected review as a page. The extension picks the
+    /// format — `.md` for Markdown, anything else one self-contained HTML file.
+    ReviewOut,
+    /// The name of a reader about to be paired. Asked first, because the name
+    /// is how its highlights will be told apart from everyone else's.
+    PairingName,
 }
 
 /// One edge of the note graph, as one row of the links pane.
@@ -549,9 +667,13 @@ pub enum LinkRow {
         /// wikilink text exactly as the body wrote it.
         title: String,
         to: Option<NoteRecord>,
+        /// The heading or `^block` the link points into, if it names one.
+        anchor: Option<String>,
+        /// False when `to` has no such section (any more).
+        anchor_found: bool,
     },
-    /// Another note links here.
-    In(NoteRecord),
+    /// Another note links here — to the whole note, or to one section of it.
+    In(Backlink),
 }
 
 /// The graph around one note: what it links to, and what links back.
@@ -748,6 +870,23 @@ pub enum Confirm {
         input: PathBuf,
         output: PathBuf,
     },
+    /// Put the KOReader plugin on the device, or upgrade the one there. Asked
+    /// with the directory named, because installing is never a side effect.
+    InstallPlugin {
+        mount: PathBuf,
+        plugin_dir: PathBuf,
+    },
+    /// Take it off again.
+    RemovePlugin {
+        mount: PathBuf,
+        plugin_dir: PathBuf,
+    },
+    /// Stop accepting a paired reader's token. Asked, because the reader then
+    /// has to be paired again from scratch.
+    RevokeDevice {
+        id: i64,
+        name: String,
+    },
 }
 
 /// What an open in-house editor will do on save.
@@ -765,9 +904,29 @@ pub enum NoteTarget {
 pub struct NoteDraft {
     pub target: NoteTarget,
     pub editor: TextEditor,
+    /// The template the buffer was filled from, on a new note.
+    pub template: Option<DraftTemplate>,
+}
+
+/// What a template put in the editor: its name (`None` for the kind's
+/// default), and the text, to tell an untouched buffer from a written one.
+pub struct DraftTemplate {
+    pub name: Option<String>,
+    pub text: String,
 }
 
 impl NoteDraft {
+    /// Nothing written yet: blank, or exactly what a template filled in. Such a
+    /// draft is dropped without asking, and may be refilled from another
+    /// template.
+    pub fn untouched(&self) -> bool {
+        self.editor.is_blank()
+            || self
+                .template
+                .as_ref()
+                .is_some_and(|t| self.editor.text().trim_end() == t.text.trim_end())
+    }
+
     /// What the editor's border calls this. A reflection and a review are named
     /// rather than both reading "edit note": they are the two notes you open by
     /// a key rather than pick off a list, so the border is the only thing that
@@ -801,6 +960,10 @@ pub struct App {
     /// standing on, and a refresh underneath a filtered list cannot silently
     /// widen it back to the whole library.
     pub library_filter: Option<String>,
+    /// The shelf the library list is narrowed to, by name, if any. Applied in
+    /// [`App::refresh_library`] by the engine's listing filter, before the
+    /// words — a shelf is a set of books, and the words search within it.
+    pub library_shelf: Option<String>,
     /// What the library list is ordered by. Applied in [`App::refresh_library`]
     /// beside the filter and for the same reason: `self.library` *is* what is on
     /// screen, so every index into it — the selection, remove, open — keeps
@@ -879,6 +1042,11 @@ pub struct App {
     pub pending_rating: Option<i64>,
     pub search_results: Vec<RankedResult>,
     pub search_state: ListState,
+    pub search_scope: SearchScope,
+    /// The library search's answer; `search_results` is the online one's.
+    pub library_hits: Vec<LibraryRow>,
+    /// The last query asked, so switching scope can ask it again.
+    pub search_query: Option<String>,
     /// The mounted reader's books, as the last scan found them.
     pub device: Vec<DeviceRow>,
     pub device_state: ListState,
@@ -891,6 +1059,8 @@ pub struct App {
     /// screens that can offer one. Shared rather than one field per screen: only
     /// one can be open at a time, and the handler is the same.
     pub link_picker: Option<LinkPicker>,
+    /// The paired-readers list, while `w` has it open on the device screen.
+    pub readers: Option<ReadersPanel>,
     /// The calibre library's books, as the last scan found them.
     pub calibre: Vec<CalibreRow>,
     pub calibre_state: ListState,
@@ -905,8 +1075,18 @@ pub struct App {
     pub calibre_scanned: bool,
     /// The Goodreads CSV as the dry run described it, when one has been read.
     pub goodreads: Option<GoodreadsPreview>,
+    /// The author screen's corpus, when one has been opened.
+    pub author: Option<AuthorView>,
+    /// The study screen's session, when one has been opened.
+    pub study: Option<StudyView>,
     /// A conversion's input path, held while its output path is typed.
     pub pending_convert: Option<PathBuf>,
+    /// The review being exported, held while its file name is typed.
+    pub pending_export: Option<NoteRecord>,
+    /// The style `y` quotes a highlight in: the last one used.
+    pub quote_style: QuoteStyle,
+    /// The highlight last copied, and how — a second `y` on it steps the style.
+    pub copied: Option<(i64, QuoteStyle)>,
     /// A device root awaiting its (blocking) walk. Drained by the event loop
     /// *after* it has drawn the frame that says "scanning…" — the same
     /// deferred-work shape as `pending_verify`.
@@ -952,6 +1132,7 @@ impl App {
             library: Vec::new(),
             library_state: ListState::default(),
             library_filter: None,
+            library_shelf: None,
             library_sort: crate::ui::library::Sort::default(),
             reading: Vec::new(),
             reading_state: ListState::default(),
@@ -984,18 +1165,27 @@ impl App {
             pending_rating: None,
             search_results: Vec::new(),
             search_state: ListState::default(),
+            search_scope: SearchScope::default(),
+            library_hits: Vec::new(),
+            search_query: None,
             device: Vec::new(),
             device_state: ListState::default(),
             device_marks: HashSet::new(),
             device_root: None,
             link_picker: None,
+            readers: None,
             calibre: Vec::new(),
             calibre_state: ListState::default(),
             calibre_marks: HashSet::new(),
             calibre_library: None,
             calibre_scanned: false,
             goodreads: None,
+            author: None,
+            study: None,
             pending_convert: None,
+            pending_export: None,
+            quote_style: QuoteStyle::default(),
+            copied: None,
             pending_scan: None,
             pending_pull: None,
             pending_calibre: None,
@@ -1031,11 +1221,28 @@ impl App {
     }
 
     pub async fn refresh_library(&mut self) -> Result<()> {
-        // `BookSort::LastModified` regardless of `library_sort`, and the
-        // ordering is applied below. A SQL `ORDER BY … LIMIT 200` would make the
-        // sort key decide *which* 200 books are on screen, so pressing `s` would
-        // swap the contents of the list rather than reorder it.
-        self.library = self.engine.list_books(200, BookSort::LastModified).await?;
+        // The whole library, unpaged, and ordered below by `library_sort`. A
+        // SQL `ORDER BY … LIMIT` would make the sort key decide *which* books
+        // are on screen, so pressing `s` would swap the contents of the list
+        // rather than reorder it — and any cap at all is a shelf that silently
+        // stops short of the library.
+        let query = BookQuery {
+            filter: BookFilter {
+                shelf: self.library_shelf.clone(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        self.library = match self.engine.query_books(&query).await {
+            Ok(page) => page.books,
+            // Deleted from the CLI while it was on screen: the whole library
+            // again, rather than an error every refresh from now on.
+            Err(EngineError::NotFound(_)) if self.library_shelf.is_some() => {
+                self.library_shelf = None;
+                self.engine.query_books(&BookQuery::default()).await?.books
+            }
+            Err(e) => return Err(e.into()),
+        };
         if let Some(q) = self.library_filter.clone() {
             self.library.retain(|b| matches_book(b, &q));
         }
@@ -1098,14 +1305,23 @@ impl App {
     }
 
     async fn load_view(&self, book: Book) -> Result<BookView> {
-        let (notes, highlights, cards, readings) = match book.id {
+        let (notes, highlights, cards, readings, studied, work) = match book.id {
             Some(id) => (
                 self.engine.list_notes(Some(id)).await?,
                 self.engine.list_highlights(id).await?,
                 self.engine.list_flashcards_for_book(id).await?,
                 self.engine.list_readings(id).await?,
+                self.engine.studied_highlights(id).await?,
+                Some(self.engine.work_history(id).await?),
+            ),
+            None => (
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                None,
             ),
-            None => (Vec::new(), Vec::new(), Vec::new(), Vec::new()),
         };
         Ok(BookView {
             book,
@@ -1113,6 +1329,8 @@ impl App {
             highlights,
             cards,
             readings,
+            studied,
+            work,
         })
     }
 
@@ -1242,6 +1460,7 @@ impl App {
             // The candidate chooser is a modal like the others: while a decision
             // is open the background stops drifting behind it.
             && self.link_picker.is_none()
+            && self.readers.is_none()
             && !self.help
     }
 
@@ -1386,13 +1605,14 @@ impl App {
             // state the user can see, and backing out of the screen while it is
             // still set would hide books behind a word typed on another screen.
             (Screen::Library, Action::Back) => {
-                if self.library_filter.is_some() {
+                if self.library_filter.is_some() || self.library_shelf.is_some() {
                     self.clear_library_filter().await?;
                 } else {
                     self.back();
                 }
             }
             (Screen::Library, Action::CycleSort) => self.cycle_library_sort().await?,
+            (Screen::Library, Action::CycleShelf) => self.cycle_library_shelf().await?,
             (Screen::Library, Action::Delete) => self.ask_remove_selected(),
             (Screen::Library, Action::Select) => {
                 if let Some(book) = self
@@ -1412,7 +1632,11 @@ impl App {
             (Screen::Search, Action::Query) => {
                 self.start_input(InputContext::SearchQuery, "search", "")
             }
-            (Screen::Search, Action::Select) => self.add_search_result().await?,
+            (Screen::Search, Action::TogglePanel) => self.toggle_search_scope().await?,
+            (Screen::Search, Action::Select) => match self.search_scope {
+                SearchScope::Online => self.add_search_result().await?,
+                SearchScope::Library => self.open_library_hit().await?,
+            },
             (Screen::Search, Action::Back) => self.back(),
 
             (Screen::Settings, Action::Select | Action::ToggleSpin) => self.toggle_glyphs(),
@@ -1430,6 +1654,8 @@ impl App {
             (Screen::Device, action) => self.handle_device(action).await?,
             (Screen::Calibre, action) => self.handle_calibre(action).await?,
             (Screen::Goodreads, action) => self.handle_goodreads(action).await?,
+            (Screen::Author, action) => self.handle_author(action).await?,
+            (Screen::Study, action) => self.handle_study(action).await?,
 
             (Screen::Book, action) => self.handle_book(action).await?,
 
@@ -1495,7 +1721,7 @@ impl App {
             Action::ToggleRenderer => self.toggle_renderer(),
             Action::GrowBook => self.slide_divider(crate::ui::DIVIDER_STEP),
             Action::ShrinkBook => self.slide_divider(-crate::ui::DIVIDER_STEP),
-            Action::NewNote => self.new_note(false),
+            Action::NewNote => self.new_note(false).await,
             // The book view's half of item 7. `reading_id: None` is the engine's
             // "the current reading", which opens one when the book has none —
             // a reflection is written mid-book, and that is the normal case.
@@ -1515,7 +1741,12 @@ impl App {
             Action::Delete => self.ask_delete_selected_note(),
             Action::EditProgress => self.start_input(InputContext::ProgressPage, "page", ""),
             Action::ToggleFinished => self.toggle_finished().await?,
-            Action::Export => self.export_cards().await?,
+            Action::Copy => self.copy_quotation().await?,
+            Action::Study => self.toggle_study().await?,
+            Action::Export => match self.selected_review() {
+                Some(review) => self.ask_export_review(review),
+                None => self.export_cards().await?,
+            },
 
             // Back / left: leave the section, or leave the book — and leaving
             // the book means back where the book was opened from. This used to
@@ -1651,6 +1882,7 @@ impl App {
         self.note_editor = Some(NoteDraft {
             target: NoteTarget::Edit(record),
             editor: TextEditor::new(&body),
+            template: None,
         });
         // The note exists from the moment it is opened, not from the moment it
         // is saved, so the Notes list behind the editor is already out of date.
@@ -1687,9 +1919,34 @@ impl App {
         Ok(())
     }
 
-    /// Widen the library list back to the whole library.
+    /// Narrow the library list to the next shelf by name, and past the last
+    /// one back to the whole library. The selection resets, as it does for a
+    /// find: the rows are a different set.
+    async fn cycle_library_shelf(&mut self) -> Result<()> {
+        let shelves = self.engine.list_shelves().await?;
+        if shelves.is_empty() {
+            self.status = Some("no shelves yet — `readingbuddy shelf new <name>` makes one".into());
+            return Ok(());
+        }
+        let at = self.library_shelf.as_deref().and_then(|cur| {
+            shelves
+                .iter()
+                .position(|s| s.name.eq_ignore_ascii_case(cur))
+        });
+        self.library_shelf = match at {
+            Some(i) => shelves.get(i + 1).map(|s| s.name.clone()),
+            None => Some(shelves[0].name.clone()),
+        };
+        self.library_state.select(None);
+        self.refresh_library().await?;
+        self.status = None;
+        Ok(())
+    }
+
+    /// Widen the library list back to the whole library: no words, no shelf.
     async fn clear_library_filter(&mut self) -> Result<()> {
         self.library_filter = None;
+        self.library_shelf = None;
         self.library_state.select(None);
         self.refresh_library().await?;
         self.status = None;
@@ -1699,8 +1956,7 @@ impl App {
     /// Open the search screen onto a fresh query box.
     fn open_search(&mut self) {
         self.go(Screen::Search);
-        self.search_results.clear();
-        self.search_state.select(None);
+        self.clear_search();
         self.start_input(InputContext::SearchQuery, "search", "");
     }
 
@@ -1743,12 +1999,7 @@ impl App {
             MenuItem::Device => self.open_device(),
             MenuItem::Calibre => self.open_calibre(),
             MenuItem::Goodreads => self.open_goodreads(),
-            MenuItem::Cards => {
-                let n = self.engine.list_flashcards(false).await?.len();
-                self.status = Some(format!(
-                    "{n} flashcard candidate(s) pending — open a book's Cards tab to export"
-                ));
-            }
+            MenuItem::Cards => self.open_study().await?,
             MenuItem::Settings => self.go(Screen::Settings),
             MenuItem::Quit => self.quit = true,
         }
@@ -1768,6 +2019,7 @@ impl App {
     fn open_device(&mut self) {
         self.go(Screen::Device);
         self.link_picker = None;
+        self.readers = None;
         let mut mounts = readingbuddy::candidate_mounts();
         match mounts.len() {
             1 => self.start_scan(mounts.remove(0)),
@@ -1854,6 +2106,34 @@ impl App {
         }
     }
 
+    /// Notes changed in the vault by something else: bring the index in line,
+    /// and say what moved in the status line.
+    ///
+    /// Never a screen change, for the reason a mount is not one — the user is
+    /// in Obsidian, or was a moment ago, and the book they left open here should
+    /// still be where they left it. Only its lists are re-read.
+    pub async fn on_vault_changes(&mut self, paths: Vec<PathBuf>) -> Result<()> {
+        let changed = match self.engine.refresh_vault_paths(&paths).await {
+            Ok(changed) => changed,
+            Err(e) => {
+                self.status = Some(format!("vault: {e}"));
+                self.dirty = true;
+                return Ok(());
+            }
+        };
+        // Our own saves come back through the watcher too, and report nothing.
+        let Some(first) = changed.first() else {
+            return Ok(());
+        };
+        self.status = Some(match changed.len() {
+            1 => first.to_string(),
+            n => format!("{n} notes changed in the vault — {first}, …"),
+        });
+        self.reload_view().await?;
+        self.dirty = true;
+        Ok(())
+    }
+
     /// Queue a walk of `root`. The work itself happens in [`App::finish_scan`],
     /// after the loop has drawn the frame this status line belongs to.
     fn start_scan(&mut self, root: PathBuf) {
@@ -1908,12 +2188,15 @@ impl App {
             .select(Some(m.land(cur, self.device.len())));
     }
 
-    /// Device-screen keys. The candidate chooser, when open, takes the
-    /// directions and Enter for itself; everything else is the list.
+    /// Device-screen keys. The candidate chooser and the readers list, when
+    /// open, take the keys for themselves; everything else is the list.
     async fn handle_device(&mut self, action: Action) -> Result<()> {
         if self.link_picker.is_some() {
             return self.handle_link_picker(action).await;
         }
+        if self.readers.is_some() {
+            return self.handle_readers(action);
+        }
         match action {
             Action::Up => self.step_device(Move::Row(-1)),
             Action::Down => self.step_device(Move::Row(1)),
@@ -1924,6 +2207,8 @@ impl App {
             Action::Mark => self.toggle_mark(),
             Action::Sync => self.sync_marked(),
             Action::Link => self.open_link_picker(),
+            Action::Plugin => self.offer_plugin(),
+            Action::Readers => self.open_readers().await?,
             Action::Rescan => match self.device_root.clone() {
                 Some(root) => self.start_scan(root),
                 None => self.start_input(InputContext::DevicePath, "device path", ""),
@@ -1942,6 +2227,118 @@ impl App {
         Ok(())
     }
 
+    /// Ask to install the plugin, or to remove it when this build's version is
+    /// already there. A newer plugin, or a directory that is not ours, is only
+    /// reported: there is no question the user could answer that would make
+    /// touching it right.
+    fn offer_plugin(&mut self) {
+        let Some(mount) = self.device_root.clone() else {
+            self.status = Some("scan a reader first".into());
+            return;
+        };
+        let status = match self.engine.plugin_status(&mount) {
+            Ok(status) => status,
+            Err(e) => {
+                self.status = Some(format!("no plugin here: {e}"));
+                return;
+            }
+        };
+        let plugin_dir = status.plugin_dir;
+        self.confirm = match status.state {
+            PluginState::NotInstalled | PluginState::Older { .. } => {
+                Some(Confirm::InstallPlugin { mount, plugin_dir })
+            }
+            PluginState::Current => Some(Confirm::RemovePlugin { mount, plugin_dir }),
+            PluginState::Newer { installed } => {
+                self.status = Some(format!(
+                    "plugin version {installed} is newer than this build's {} — left as it is",
+                    status.bundled
+                ));
+                None
+            }
+            PluginState::Foreign { reason } => {
+                self.status = Some(format!("{} is not ours: {reason}", plugin_dir.display()));
+                None
+            }
+        };
+    }
+
+    /// Open the readers list, or refresh it in place when it is already open
+    /// — after a mint or a revoke, so the row just changed is still selected.
+    async fn open_readers(&mut self) -> Result<()> {
+        let devices = self.engine.list_devices().await?;
+        let cur = self
+            .readers
+            .as_ref()
+            .and_then(|r| r.state.selected())
+            .unwrap_or(0);
+        let mut state = ListState::default();
+        if !devices.is_empty() {
+            state.select(Some(cur.min(devices.len() - 1)));
+        }
+        self.readers = Some(ReadersPanel { devices, state });
+        Ok(())
+    }
+
+    /// `n` mints a code, `d` revokes, Esc closes. Both verbs are the global
+    /// keys for "new" and "delete", so nothing here has to be learnt twice.
+    fn handle_readers(&mut self, action: Action) -> Result<()> {
+        let Some(panel) = self.readers.as_mut() else {
+            return Ok(());
+        };
+        match action {
+            Action::Up | Action::Down | Action::PageUp | Action::PageDown => {
+                let len = panel.devices.len();
+                if len == 0 {
+                    return Ok(());
+                }
+                let m = match action {
+                    Action::Up => Move::Row(-1),
+                    Action::Down => Move::Row(1),
+                    Action::PageUp => Move::Page(-1),
+                    _ => Move::Page(1),
+                };
+                let cur = panel.state.selected().unwrap_or(0);
+                panel.state.select(Some(m.land(cur, len)));
+            }
+            Action::NewNote => self.start_input(InputContext::PairingName, "reader name", ""),
+            Action::Delete => {
+                let Some(device) = panel.state.selected().and_then(|i| panel.devices.get(i)) else {
+                    return Ok(());
+                };
+                if device.revoked_at.is_some() {
+                    self.status = Some(format!("{} is already revoked", device.name));
+                    return Ok(());
+                }
+                self.confirm = Some(Confirm::RevokeDevice {
+                    id: device.id,
+                    name: device.name.clone(),
+                });
+            }
+            Action::Back | Action::Left | Action::Readers => self.readers = None,
+            _ => self.dirty = false,
+        }
+        Ok(())
+    }
+
+    /// Mint a code for a reader called `name`, and show it where it will be
+    /// read off: the status line, with how long it has.
+    async fn mint_pairing_code(&mut self, name: String) -> Result<()> {
+        match self.engine.mint_pairing_code(&name).await {
+            Ok(code) => {
+                self.status = Some(format!(
+                    "type {} into {} within {} minutes",
+                    code.code,
+                    code.name,
+                    readingbuddy::pairing::PAIRING_CODE_TTL.as_secs() / 60
+                ));
+                self.open_readers().await?;
+            }
+            Err(e) => self.status = Some(format!("no code minted: {e}")),
+        }
+        Ok(())
+    }
+
     fn selected_row(&self) -> Option<&DeviceRow> {
         self.device_state
             .selected()
@@ -2835,6 +3232,142 @@ impl App {
         Ok(())
     }
 
+    /// Read an author's corpus and show it. A name that will not resolve is a
+    /// status line rather than an empty screen.
+    async fn open_author(&mut self, name: &str, order: CorpusOrder) {
+        match self.engine.author_corpus(name, order).await {
+            Ok(corpus) => {
+                self.author = Some(AuthorView::new(corpus, order));
+                self.go(Screen::Author);
+            }
+            Err(e) => self.status = Some(format!("could not read {name}: {e}")),
+        }
+    }
+
+    async fn handle_author(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Up => self.step_author(Move::Row(-1)),
+            Action::Down => self.step_author(Move::Row(1)),
+            Action::PageUp => self.step_author(Move::Page(-1)),
+            Action::PageDown => self.step_author(Move::Page(1)),
+            Action::Back => self.back(),
+            Action::Select => {
+                let book = self
+                    .author
+                    .as_ref()
+                    .and_then(|v| v.selected_book())
+                    .cloned();
+                if let Some(book) = book {
+                    self.open_book(book).await?;
+                }
+            }
+            // Re-read rather than resort: the engine owns the order.
+            Action::CycleSort => {
+                if let Some(v) = &self.author {
+                    let order = match v.order {
+                        CorpusOrder::Published => CorpusOrder::Read,
+                        CorpusOrder::Read => CorpusOrder::Published,
+                    };
+                    let name = v.corpus.name.clone();
+                    self.open_author(&name, order).await;
+                }
+            }
+            _ => self.dirty = false,
+        }
+        Ok(())
+    }
+
+    fn step_author(&mut self, m: Move) {
+        let Some(v) = &mut self.author else {
+            return;
+        };
+        if v.rows.is_empty() {
+            return;
+        }
+        let cur = v.state.selected().unwrap_or(0);
+        v.state.select(Some(m.land(cur, v.rows.len())));
+    }
+
+    // ---- the study screen ---------------------------------------------------
+
+    /// How many cards one sitting holds. A backlog after a month away is not
+    /// a session anybody finishes, and what is left stays due for the next.
+    const STUDY_SITTING: usize = 100;
+
+    async fn open_study(&mut self) -> Result<()> {
+        let queue = self.engine.due_cards(Self::STUDY_SITTING).await?;
+        self.study = Some(StudyView {
+            queue,
+            pos: 0,
+            revealed: false,
+        });
+        self.go(Screen::Study);
+        self.status = None;
+        Ok(())
+    }
+
+    async fn handle_study(&mut self, action: Action) -> Result<()> {
+        let Some(v) = &mut self.study else {
+            self.dirty = false;
+            return Ok(());
+        };
+        match action {
+            Action::Back => self.back(),
+            Action::Select | Action::ToggleSpin if v.current().is_some() => v.revealed = true,
+            Action::Grade(_) if !v.revealed && v.current().is_some() => {
+                self.status = Some("enter shows the rest first".into());
+            }
+            Action::Grade(grade) => {
+                let Some(id) = v.current().map(|c| c.id) else {
+                    self.dirty = false;
+                    return Ok(());
+                };
+                let graded = self.engine.grade_card(id, grade).await?;
+                let days = graded.schedule.interval_days;
+                self.status = Some(format!(
+                    "{}: back in {days} day{}",
+                    grade.as_str(),
+                    if days == 1 { "" } else { "s" }
+                ));
+                if let Some(v) = &mut self.study {
+                    v.pos += 1;
+                    v.revealed = false;
+                }
+            }
+            _ => self.dirty = false,
+        }
+        Ok(())
+    }
+
+    /// `c` on the highlights list: the highlight under the cursor into the
+    /// study deck, or back out of it.
+    async fn toggle_study(&mut self) -> Result<()> {
+        if self.links.is_some() || self.book_tab != BookTab::Highlights || !self.in_section {
+            self.dirty = false;
+            return Ok(());
+        }
+        let Some((id, studied)) = self
+            .tab_state
+            .selected()
+            .and_then(|i| {
+                self.view
+                    .as_ref()
+                    .and_then(|v| Some((v, v.highlights.get(i)?)))
+            })
+            .map(|(v, h)| (h.id, v.studied.contains(&h.id)))
+        else {
+            self.status = Some("no highlight selected".into());
+            return Ok(());
+        };
+        self.engine.study_highlight(id, !studied).await?;
+        self.status = Some(if studied {
+            "taken out of the study deck".into()
+        } else {
+            "in the study deck — due now; review it from the menu".into()
+        });
+        self.reload_view().await
+    }
+
     fn step_goodreads(&mut self, m: Move) {
         let Some(preview) = &mut self.goodreads else {
             return;
@@ -2931,7 +3464,17 @@ impl App {
     async fn activate_tab_row(&mut self) -> Result<()> {
         match self.book_tab {
             BookTab::Notes => self.edit_selected_note().await?,
-            BookTab::Highlights => self.new_note(true),
+            BookTab::Highlights => self.new_note(true).await,
+            BookTab::Info => {
+                let name = self.tab_state.selected().and_then(|i| {
+                    self.view
+                        .as_ref()
+                        .and_then(|v| v.book.authors.get(i).cloned())
+                });
+                if let Some(name) = name {
+                    self.open_author(&name, CorpusOrder::default()).await;
+                }
+            }
             _ => self.dirty = false,
         }
         Ok(())
@@ -2940,7 +3483,7 @@ impl App {
     /// Open the in-house editor on a fresh note. When `from_highlight`, anchor
     /// it to the selected highlight (inheriting its page/chapter); otherwise
     /// anchor to the book's current reading page.
-    fn new_note(&mut self, from_highlight: bool) {
+    async fn new_note(&mut self, from_highlight: bool) {
         let Some(view) = &self.view else { return };
         let target = if from_highlight {
             let Some(h) = self
@@ -2965,13 +3508,84 @@ impl App {
                 highlight_id: None,
             }
         };
+        // The reader's default note template, if they keep one, is where the
+        // writing starts. One that will not expand says so and starts blank.
+        let template = match self
+            .engine
+            .expand_template(&draft_input(&target, None))
+            .await
+        {
+            Ok(text) => text.map(|text| DraftTemplate { name: None, text }),
+            Err(e) => {
+                self.status = Some(format!("template: {e}"));
+                None
+            }
+        };
+        let text = template.as_ref().map_or("", |t| t.text.trim_end());
         self.note_editor = Some(NoteDraft {
             target,
-            editor: TextEditor::new(""),
+            editor: TextEditor::new(text),
+            template,
         });
         self.dirty = true;
     }
 
+    /// Ctrl+T on a new note: refill it from the next template, in name order,
+    /// and back to blank after the last. Only over a draft nothing has been
+    /// written in — a keypress must never throw writing away.
+    async fn next_template(&mut self) -> Result<()> {
+        let Some(draft) = self.note_editor.as_ref() else {
+            return Ok(());
+        };
+        if !matches!(draft.target, NoteTarget::New { .. }) {
+            self.status = Some("a template starts a new note".into());
+            return Ok(());
+        }
+        if !draft.untouched() {
+            self.status = Some("clear the note to switch templates".into());
+            return Ok(());
+        }
+        let names = self.engine.templates();
+        if names.is_empty() {
+            self.status = Some(format!(
+                "no note templates yet — they go in {}",
+                self.engine.templates_dir().display()
+            ));
+            return Ok(());
+        }
+        let current = draft.template.as_ref().and_then(|t| t.name.as_deref());
+        let next = match current {
+            None => names.first(),
+            Some(c) => names.iter().skip_while(|n| n.as_str() != c).nth(1),
+        }
+        .cloned();
+        let template = match &next {
+            Some(name) => {
+                let input = draft_input(&draft.target, Some(name.clone()));
+                match self.engine.expand_template(&input).await {
+                    Ok(text) => Some(DraftTemplate {
+                        name: next.clone(),
+                        text: text.unwrap_or_default(),
+                    }),
+                    Err(e) => {
+                        self.status = Some(format!("template {name}: {e}"));
+                        return Ok(());
+                    }
+                }
+            }
+            None => None,
+        };
+        self.status = Some(match &next {
+            Some(name) => format!("template: {name}"),
+            None => "no template".into(),
+        });
+        if let Some(draft) = self.note_editor.as_mut() {
+            draft.editor = TextEditor::new(template.as_ref().map_or("", |t| t.text.trim_end()));
+            draft.template = template;
+        }
+        Ok(())
+    }
+
     // ---- the links pane ----------------------------------------------------
 
     /// Open the graph around the selected note.
@@ -3024,6 +3638,8 @@ impl App {
                     .as_ref()
                     .map_or_else(|| l.target_title.clone(), |n| n.title.clone()),
                 to: l.to,
+                anchor: l.anchor,
+                anchor_found: l.anchor_found,
             })
             .collect();
         rows.extend(inbound.into_iter().map(LinkRow::In));
@@ -3072,22 +3688,59 @@ impl App {
     /// A dangling target is not a dead end and is not an error — it is the note
     /// worth writing next. Saying so is the whole difference between a forward
     /// reference and a broken link.
+    ///
+    /// A link into a section lands there: the walk continues as for any other
+    /// link, and the target opens in the editor at the cited heading or block,
+    /// which is the only view of a body this app has.
     async fn follow_link(&mut self) -> Result<()> {
         let Some(row) = self.links.as_ref().and_then(|p| p.selected()).cloned() else {
             return Ok(());
         };
         match row {
-            LinkRow::Out { title, to: None } => {
+            LinkRow::Out {
+                title, to: None, ..
+            } => {
                 self.status = Some(format!(
                     "“{title}” has no note yet — write one and this link resolves itself"
                 ));
                 self.dirty = true;
                 Ok(())
             }
-            LinkRow::Out { to: Some(note), .. } | LinkRow::In(note) => self.goto_note(note).await,
+            LinkRow::Out {
+                to: Some(note),
+                anchor: Some(anchor),
+                anchor_found,
+                ..
+            } => {
+                self.goto_note(note.clone()).await?;
+                if anchor_found {
+                    self.open_note_at(note, &anchor);
+                } else {
+                    self.status = Some(format!(
+                        "“{}” has no section “{anchor}” any more — the link lands at the top",
+                        note.title
+                    ));
+                }
+                Ok(())
+            }
+            LinkRow::Out { to: Some(note), .. } => self.goto_note(note).await,
+            LinkRow::In(link) => self.goto_note(link.from).await,
         }
     }
 
+    /// Open `note` in the editor with the cursor on `anchor`'s line.
+    fn open_note_at(&mut self, note: NoteRecord, anchor: &str) {
+        let body = self.engine.note_body(&note).unwrap_or_default();
+        let line = readingbuddy::notes::anchor_line(&body, anchor).unwrap_or(0);
+        self.note_editor = Some(NoteDraft {
+            target: NoteTarget::Edit(note),
+            editor: TextEditor::at_line(&body, line),
+            template: None,
+        });
+        self.status = Some(format!("at #{anchor}"));
+        self.dirty = true;
+    }
+
     /// Follow the graph to `note`: its book, its row in the Notes list, and the
     /// pane re-centred on it so the walk can continue.
     ///
@@ -3163,6 +3816,7 @@ impl App {
         self.note_editor = Some(NoteDraft {
             target: NoteTarget::Edit(note),
             editor: TextEditor::new(&body),
+            template: None,
         });
         self.dirty = true;
         Ok(())
@@ -3172,6 +3826,9 @@ impl App {
     /// newline chords (Shift/Alt+Enter, Ctrl+J) add a line, the rest edits.
     async fn on_editor_key(&mut self, key: KeyEvent) -> Result<()> {
         self.dirty = true;
+        if key.code == KeyCode::Char('t') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return self.next_template().await;
+        }
         let Some(draft) = self.note_editor.as_mut() else {
             return Ok(());
         };
@@ -3186,7 +3843,7 @@ impl App {
                     // note is left as-is. Deletion is only ever via `d`.
                     self.note_editor = None;
                     self.status = Some("edit cancelled".into());
-                } else if draft.editor.is_blank() {
+                } else if draft.untouched() {
                     // A brand-new note with nothing written — no need to ask.
                     self.note_editor = None;
                     self.status = Some("note discarded".into());
@@ -3285,6 +3942,8 @@ impl App {
                 kind: NoteKind::Note,
                 title: None,
                 body,
+                // Already expanded into the editor, and edited since.
+                template: None,
             })
             .await?;
         self.status = Some(format!("saved note “{}”", created.title));
@@ -3326,15 +3985,100 @@ impl App {
         Ok(())
     }
 
-    async fn export_cards(&mut self) -> Result<()> {
-        let (tsv, count) = self.engine.export_flashcards(false).await?;
-        if count == 0 {
-            self.status = Some("no unexported cards".into());
+    /// Copy the selected highlight as a quotation with its page and edition.
+    /// `y` again on the same highlight steps to the next style, so all three
+    /// are one key away without a picker.
+    async fn copy_quotation(&mut self) -> Result<()> {
+        if self.links.is_some() || self.book_tab != BookTab::Highlights || !self.in_section {
+            self.dirty = false;
             return Ok(());
         }
-        let vault = self.engine.vault_dir();
-        let dir = vault.parent().unwrap_or(vault).to_path_buf();
-        let out = dir.join("flashcards.tsv");
+        let Some(id) = self
+            .tab_state
+            .selected()
+            .and_then(|i| self.view.as_ref().and_then(|v| v.highlights.get(i)))
+            .map(|h| h.id)
+        else {
+            self.status = Some("no highlight selected".into());
+            return Ok(());
+        };
+        let style = match self.copied {
+            Some((last, s)) if last == id => s.next(),
+            _ => self.quote_style,
+        };
+        let quote = self.engine.quote_highlight(id, style).await?;
+        self.quote_style = style;
+        self.copied = Some((id, style));
+        self.status = Some(match crate::clipboard::write(&quote) {
+            Ok(()) => format!(
+                "copied, {} style — y again for {}",
+                style.as_str(),
+                style.next().as_str()
+            ),
+            Err(e) => format!("couldn't reach the clipboard: {e}"),
+        });
+        Ok(())
+    }
+
+    /// The review under the cursor in the Notes list — what `x` exports
+    /// instead of the cards. The same visibility rule as delete: with the links
+    /// pane open the list is not on screen.
+    fn selected_review(&self) -> Option<NoteRecord> {
+        if self.links.is_some() || self.book_tab != BookTab::Notes || !self.in_section {
+            return None;
+        }
+        self.selected_note()
+            .filter(|n| n.kind == NoteKind::Review.as_str())
+    }
+
+    /// Ask where the review goes, beside the vault by default — the same place
+    /// the cards are written.
+    fn ask_export_review(&mut self, review: NoteRecord) {
+        let vault = self.engine.vault_dir();
+        let dir = vault.parent().unwrap_or(vault);
+        let title = self
+            .view
+            .as_ref()
+            .map_or(review.title.as_str(), |v| v.book.display_title());
+        let out = dir.join(format!(
+            "{}-review.html",
+            readingbuddy::notes::slugify(title)
+        ));
+        self.pending_export = Some(review);
+        self.start_input(
+            InputContext::ReviewOut,
+            "write review to (.md for Markdown)",
+            &out.display().to_string(),
+        );
+    }
+
+    async fn export_review(&mut self, review: NoteRecord, out: PathBuf) {
+        let format = ExportFormat::for_path(&out);
+        let page = match self.engine.export_review(review.id, format).await {
+            Ok(page) => page,
+            Err(e) => {
+                self.status = Some(format!("couldn't build the page: {e}"));
+                return;
+            }
+        };
+        self.status = Some(match std::fs::write(&out, page) {
+            Ok(()) => format!("review → {} ({})", out.display(), format.as_str()),
+            Err(e) => format!("couldn't write {}: {e}", out.display()),
+        });
+    }
+
+    async fn export_cards(&mut self) -> Result<()> {
+        let (tsv, count) = self
+            .engine
+            .export_flashcards(false, CardFormat::Tsv)
+            .await?;
+        if count == 0 {
+            self.status = Some("no unexported cards".into());
+            return Ok(());
+        }
+        let vault = self.engine.vault_dir();
+        let dir = vault.parent().unwrap_or(vault).to_path_buf();
+        let out = dir.join("flashcards.tsv");
         std::fs::write(&out, tsv)?;
         self.status = Some(format!("exported {count} cards -> {}", out.display()));
         self.reload_view().await?;
@@ -3600,8 +4344,9 @@ impl App {
             // app forgetting what the question it just asked was about.
             Some(Confirm::SearchOnline(query)) if yes => {
                 self.go(Screen::Search);
-                self.search_results.clear();
-                self.search_state.select(None);
+                self.clear_search();
+                // Online is what was asked, whichever scope was left selected.
+                self.search_scope = SearchScope::Online;
                 self.run_search(query).await?;
             }
             // Declining leaves the shelf as it was, with the words still named
@@ -3620,6 +4365,37 @@ impl App {
             Some(Confirm::OverwriteConversion { output, .. }) => {
                 self.status = Some(format!("kept {}", output.display()));
             }
+            Some(Confirm::InstallPlugin { mount, .. }) if yes => {
+                self.status = Some(match self.engine.install_plugin(&mount) {
+                    Ok(r) => format!(
+                        "plugin version {} installed: {} file(s) written — restart KOReader to load it",
+                        r.version,
+                        r.written.len()
+                    ),
+                    Err(e) => format!("plugin not installed: {e}"),
+                });
+            }
+            Some(Confirm::RevokeDevice { id, name }) if yes => {
+                self.engine.revoke_device(id).await?;
+                self.status = Some(format!("{name} revoked — pair it again to sync"));
+                if self.readers.is_some() {
+                    self.open_readers().await?;
+                }
+            }
+            Some(Confirm::RemovePlugin { mount, .. }) if yes => {
+                self.status = Some(match self.engine.uninstall_plugin(&mount) {
+                    Ok(r) if r.kept.is_empty() => {
+                        format!("plugin removed: {} file(s)", r.removed.len())
+                    }
+                    Ok(r) => format!(
+                        "plugin removed: {} file(s); {} not ours left in {}",
+                        r.removed.len(),
+                        r.kept.len(),
+                        r.plugin_dir.display()
+                    ),
+                    Err(e) => format!("plugin not removed: {e}"),
+                });
+            }
             // Any other decline just keeps things as they were.
             Some(_) => self.status = Some("kept.".into()),
             None => {}
@@ -3653,7 +4429,7 @@ impl App {
                 } else if context == InputContext::ReviewRating {
                     // The review is already saved; the rating was optional.
                     self.pending_rating = None;
-                } else if self.screen == Screen::Search && self.search_results.is_empty() {
+                } else if self.screen == Screen::Search && self.search_is_empty() {
                     // A cancelled search query with no results leaves a screen
                     // with nothing on it, so it backs out the way Esc would if
                     // the box had never been open.
@@ -3696,7 +4472,7 @@ impl App {
             return Ok(());
         }
         if text.is_empty() {
-            if context == InputContext::SearchQuery && self.search_results.is_empty() {
+            if context == InputContext::SearchQuery && self.search_is_empty() {
                 self.back();
             }
             // An emptied find box is how the filter comes off from the library
@@ -3716,6 +4492,7 @@ impl App {
                 self.go(Screen::Device);
                 self.start_scan(PathBuf::from(text));
             }
+            InputContext::PairingName => self.mint_pairing_code(text).await?,
             InputContext::ProgressPage => self.commit_progress(text).await?,
             InputContext::AccentHex => match theme::parse_hex(&text) {
                 Some(rgb) => self.set_accent_rgb(rgb),
@@ -3751,6 +4528,11 @@ impl App {
                     self.run_convert(input, PathBuf::from(text), false).await;
                 }
             }
+            InputContext::ReviewOut => {
+                if let Some(review) = self.pending_export.take() {
+                    self.export_review(review, PathBuf::from(text)).await;
+                }
+            }
             InputContext::NotePage | InputContext::ReviewRating | InputContext::CalibreLibrary => {
                 unreachable!("handled above")
             }
@@ -3815,6 +4597,10 @@ impl App {
     // ---- global actions ----------------------------------------------------
 
     async fn run_search(&mut self, query: String) -> Result<()> {
+        self.search_query = Some(query.clone());
+        if self.search_scope == SearchScope::Library {
+            return self.run_library_search(query).await;
+        }
         self.status = Some(format!("searching “{query}”…"));
         let req = SearchRequest {
             query: Some(query),
@@ -3917,12 +4703,142 @@ impl App {
     }
 
     fn step_search(&mut self, m: Move) {
-        if self.search_results.is_empty() {
+        let len = self.search_len();
+        if len == 0 {
             return;
         }
         let cur = self.search_state.selected().unwrap_or(0);
+        self.search_state.select(Some(m.land(cur, len)));
+    }
+
+    /// How many rows the search screen is showing, in whichever scope.
+    pub fn search_len(&self) -> usize {
+        match self.search_scope {
+            SearchScope::Online => self.search_results.len(),
+            SearchScope::Library => self.library_hits.len(),
+        }
+    }
+
+    fn search_is_empty(&self) -> bool {
+        self.search_len() == 0
+    }
+
+    /// Forget both scopes' answers and the query that produced them.
+    fn clear_search(&mut self) {
+        self.search_results.clear();
+        self.library_hits.clear();
+        self.search_query = None;
+        self.search_state.select(None);
+    }
+
+    /// Tab on the search screen: the other search, asked the same question.
+    ///
+    /// Re-asking rather than keeping both answers around: the other scope's
+    /// list, if there is one, answered an older query, and showing it under the
+    /// new one would be a list that looks current and is not.
+    async fn toggle_search_scope(&mut self) -> Result<()> {
+        self.search_scope = match self.search_scope {
+            SearchScope::Online => SearchScope::Library,
+            SearchScope::Library => SearchScope::Online,
+        };
+        self.search_results.clear();
+        self.library_hits.clear();
+        self.search_state.select(None);
+        match self.search_query.clone() {
+            Some(query) => self.run_search(query).await?,
+            None => {
+                self.status = Some(match self.search_scope {
+                    SearchScope::Online => "searching online — / to ask".into(),
+                    SearchScope::Library => "searching your notes and highlights — / to ask".into(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The library scope: notes and highlights, ranked together by the engine.
+    async fn run_library_search(&mut self, query: String) -> Result<()> {
+        let hits = match self.engine.search_library(&query, 50).await {
+            Ok(hits) => hits,
+            Err(e) => {
+                self.status = Some(format!("search failed: {e}"));
+                return Ok(());
+            }
+        };
+        let mut rows = Vec::with_capacity(hits.len());
+        for hit in hits {
+            // The library list is already in memory, and is every book a hit
+            // can belong to; the engine is only asked when it is not there.
+            let book = match hit.book_id() {
+                Some(id) => match self.library.iter().find(|b| b.id == Some(id)) {
+                    Some(b) => Some(b.clone()),
+                    None => self.engine.get_book(id).await?,
+                },
+                None => None,
+            };
+            rows.push(LibraryRow { hit, book });
+        }
+        self.library_hits = rows;
         self.search_state
-            .select(Some(m.land(cur, self.search_results.len())));
+            .select((!self.library_hits.is_empty()).then_some(0));
+        self.status = Some(if self.library_hits.is_empty() {
+            format!("nothing in your notes or highlights matching “{query}”")
+        } else {
+            format!(
+                "{} in your notes and highlights — enter to open, tab to search online",
+                self.library_hits.len()
+            )
+        });
+        Ok(())
+    }
+
+    /// Enter on a library hit: open its book on the row it found.
+    ///
+    /// A note about no book has no book to open onto, so it opens straight into
+    /// the editor — which is where Enter on a note leads from the book view too.
+    async fn open_library_hit(&mut self) -> Result<()> {
+        let Some(row) = self
+            .search_state
+            .selected()
+            .and_then(|i| self.library_hits.get(i))
+            .cloned()
+        else {
+            return Ok(());
+        };
+        let Some(book) = row.book else {
+            if let LibraryHit::Note(n) = row.hit {
+                let body = self.engine.note_body(&n.note).unwrap_or_default();
+                self.note_editor = Some(NoteDraft {
+                    target: NoteTarget::Edit(n.note),
+                    editor: TextEditor::new(&body),
+                    template: None,
+                });
+                self.dirty = true;
+            }
+            return Ok(());
+        };
+        self.open_book(book).await?;
+        let Some(view) = self.view.as_ref() else {
+            return Ok(());
+        };
+        let (tab, at) = match &row.hit {
+            LibraryHit::Note(n) => (
+                BookTab::Notes,
+                view.notes.iter().position(|x| x.id == n.note.id),
+            ),
+            LibraryHit::Highlight(h) => (
+                BookTab::Highlights,
+                view.highlights.iter().position(|x| x.id == h.highlight.id),
+            ),
+        };
+        // The row is the point of arriving here, so the pane it is in has to
+        // be showing.
+        self.ensure_panel();
+        self.book_tab = tab;
+        self.in_section = true;
+        self.tab_state.select(at);
+        self.clamp_tab_selection();
+        Ok(())
     }
 }
 
@@ -3935,6 +4851,27 @@ impl App {
 ///
 /// `to_lowercase` rather than `eq_ignore_ascii_case` because a library is not
 /// ASCII: `Ä` must find `ä`.
+/// The note a new draft will become, as far as a template needs to know.
+fn draft_input(target: &NoteTarget, template: Option<String>) -> NewNoteInput {
+    let NoteTarget::New {
+        book_id,
+        page,
+        location,
+        highlight_id,
+    } = target
+    else {
+        return NewNoteInput::default();
+    };
+    NewNoteInput {
+        book_id: *book_id,
+        page: *page,
+        location: location.clone(),
+        highlight_id: *highlight_id,
+        template,
+        ..NewNoteInput::default()
+    }
+}
+
 fn matches_book(b: &Book, query: &str) -> bool {
     let q = query.trim().to_lowercase();
     if q.is_empty() {
@@ -3971,15 +4908,27 @@ async fn next_mount(watcher: &mut Option<MountWatcher>) -> MountEvent {
     }
 }
 
-/// The event loop: crossterm events, a 20fps animation tick, and the mount
-/// watcher — redrawing only when something actually changed.
+/// The next batch of settled vault paths, or never — see [`next_mount`].
+async fn next_vault(watcher: &mut Option<VaultWatcher>) -> Vec<PathBuf> {
+    match watcher {
+        Some(w) => match w.next().await {
+            Some(paths) => paths,
+            None => std::future::pending().await,
+        },
+        None => std::future::pending().await,
+    }
+}
+
+/// The event loop: crossterm events, a 20fps animation tick, and the mount and
+/// vault watchers — redrawing only when something actually changed.
 ///
-/// `mounts` is an `Option` because not every machine can watch, and one that
+/// The watchers are `Option`s because not every machine can watch, and one that
 /// cannot is a machine that still runs the app.
 pub async fn run<B: Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
     mut mounts: Option<MountWatcher>,
+    mut vault: Option<VaultWatcher>,
 ) -> Result<()> {
     let mut events = EventStream::new();
     let mut ticker = tokio::time::interval(TICK);
@@ -4013,6 +4962,7 @@ pub async fn run<B: Backend>(
             // handler only queues work — the walk itself goes through
             // `pending_scan` like every other one.
             event = next_mount(&mut mounts) => app.on_mount_event(event),
+            paths = next_vault(&mut vault) => app.on_vault_changes(paths).await?,
             _ = std::future::ready(()), if app.has_deferred() => {}
         }
 
@@ -4159,6 +5109,7 @@ mod tests {
             files_dir: tmp.join("files"),
             vault_dir: tmp.join("vault"),
             log_dir: tmp.join("logs"),
+            templates_dir: tmp.join("templates"),
             google_api_key: None,
             calibre_bin_dir,
         };
@@ -4255,6 +5206,8 @@ mod tests {
             ko_status: None,
             ko_percent,
             ko_rating: None,
+            ko_last_read: None,
+            device_id: None,
             created_at: 0,
             last_modified: 0,
         }
@@ -4838,6 +5791,7 @@ mod tests {
                     highlight_id: None,
                 },
                 editor: TextEditor::new("a line\nanother line"),
+                template: None,
             });
             terminal.draw(|f| ui::draw(f, app)).expect("draw editor");
             app.note_editor = None;
@@ -4853,12 +5807,20 @@ mod tests {
                     LinkRow::Out {
                         title: "Symphony".into(),
                         to: Some(sample_note(2, "Symphony")),
+                        anchor: Some("Act II".into()),
+                        anchor_found: true,
                     },
                     LinkRow::Out {
                         title: "Nowhere".into(),
                         to: None,
+                        anchor: None,
+                        anchor_found: false,
                     },
-                    LinkRow::In(sample_note(3, "Doctor Eleven")),
+                    LinkRow::In(Backlink {
+                        from: sample_note(3, "Doctor Eleven"),
+                        anchor: None,
+                        anchor_found: true,
+                    }),
                 ],
                 Vec::new(),
             ] {
@@ -5372,6 +6334,35 @@ mod tests {
         assert_eq!(draft.editor.text(), "a\tb\nc");
     }
 
+    #[tokio::test]
+    async fn ctrl_t_cycles_templates_into_an_untouched_draft() {
+        let mut app = test_app().await;
+        let dir = app.engine.templates_dir().to_path_buf();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("quote.md"), "from {{book}}, p. {{page}}\n").unwrap();
+        let book = app.library.first().cloned().expect("seeded book");
+        app.open_book(book).await.expect("open");
+
+        app.handle(Action::NewNote).await.expect("open editor");
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        app.on_editor_key(ctrl_t).await.expect("template");
+        let draft = app.note_editor.as_ref().expect("editor still open");
+        assert_eq!(draft.editor.text(), "from Station Eleven, p. 120");
+        // A template alone is nothing written: Esc discards without asking.
+        app.on_editor_key(KeyEvent::from(KeyCode::Esc))
+            .await
+            .expect("esc");
+        assert!(app.note_editor.is_none() && app.confirm.is_none());
+
+        // Past the last template, back to none.
+        app.handle(Action::NewNote).await.expect("open editor");
+        app.on_editor_key(ctrl_t).await.expect("template");
+        app.on_editor_key(ctrl_t).await.expect("no template");
+        let draft = app.note_editor.as_ref().expect("editor still open");
+        assert_eq!(draft.editor.text(), "");
+        assert_eq!(app.status.as_deref(), Some("no template"));
+    }
+
     #[tokio::test]
     async fn empty_page_prompt_saves_without_an_anchor() {
         let mut app = test_app().await;
@@ -5402,6 +6393,27 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn an_edit_made_in_obsidian_lands_on_the_status_line() {
+        let mut app = test_app().await;
+        let book = app.library.first().cloned().expect("seeded book");
+        app.open_book(book).await.expect("open");
+        let note = app.view.as_ref().unwrap().notes[0].clone();
+        let path = app.engine.vault_dir().join(&note.file_path);
+
+        // Our own file, unchanged: the echo of a save says nothing.
+        app.status = None;
+        app.on_vault_changes(vec![path.clone()]).await.unwrap();
+        assert!(app.status.is_none());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, format!("{content}\nAdded elsewhere.\n")).unwrap();
+        app.on_vault_changes(vec![path]).await.unwrap();
+        let status = app.status.clone().expect("reported");
+        assert!(status.starts_with(&note.file_path), "{status}");
+        assert!(status.contains("re-indexed"), "{status}");
+    }
+
     #[tokio::test]
     async fn editor_updates_a_body_preserving_frontmatter() {
         let mut app = test_app().await;
@@ -5437,7 +6449,9 @@ mod tests {
         let updated = std::fs::read_to_string(&path).unwrap();
         assert!(updated.contains("page: 120"), "frontmatter kept");
         assert!(updated.contains("rewritten body"));
-        assert!(!updated.contains("Symphony"), "old body gone");
+        // The frontmatter keeps the title the old body gave it; the body is new.
+        let (_, body) = readingbuddy::notes::frontmatter_and_body(&updated);
+        assert!(!body.contains("Symphony"), "old body gone");
     }
 
     #[tokio::test]
@@ -5502,6 +6516,44 @@ mod tests {
         assert!(!path.exists(), "the note file was left behind");
     }
 
+    /// On a review, `x` is the review as a page rather than the cards, and the
+    /// file name it is given picks the format.
+    #[tokio::test]
+    async fn x_on_a_review_exports_it_where_it_is_told() {
+        let mut app = test_app().await;
+        let book = app.library.first().cloned().expect("seeded book");
+        let review = app
+            .engine
+            .open_review(book.id.unwrap(), None)
+            .await
+            .expect("review");
+        app.open_book(book).await.expect("open");
+        app.book_tab = BookTab::Notes;
+        app.in_section = true;
+        let row = app
+            .view
+            .as_ref()
+            .unwrap()
+            .notes
+            .iter()
+            .position(|n| n.id == review.id);
+        app.tab_state.select(row);
+
+        app.handle(Action::Export).await.expect("x");
+        assert_eq!(
+            app.input.as_ref().map(|i| i.context),
+            Some(InputContext::ReviewOut)
+        );
+        let out = app.engine.vault_dir().join("../review.md");
+        app.commit_input(InputContext::ReviewOut, out.display().to_string())
+            .await
+            .expect("write");
+        let said = app.status.clone().unwrap_or_default();
+        assert!(said.contains("(md)"), "{said}");
+        assert!(std::fs::read_to_string(&out).unwrap().starts_with("# "));
+        assert!(app.pending_export.is_none());
+    }
+
     #[tokio::test]
     async fn deleting_a_note_only_works_in_the_open_notes_section() {
         let mut app = test_app().await;
@@ -5520,6 +6572,153 @@ mod tests {
         assert!(app.confirm.is_none());
     }
 
+    /// Enter on an author row in Info opens everything of theirs, spellings
+    /// pooled; `s` re-reads it in the other order, and Enter on a row opens
+    /// the book it belongs to.
+    #[tokio::test]
+    async fn an_author_row_opens_their_corpus() {
+        let mut app = test_app().await;
+        app.engine
+            .save_book(&Book {
+                title: Some("Sea of Tranquility".into()),
+                authors: vec!["Mandel, Emily St. John".into()],
+                publish_year: Some(2022),
+                ..Book::default()
+            })
+            .await
+            .expect("save");
+        let book = app
+            .library
+            .iter()
+            .find(|b| b.title.as_deref() == Some("Station Eleven"))
+            .cloned()
+            .expect("seeded book");
+        app.open_book(book).await.expect("open");
+
+        app.book_tab = BookTab::Info;
+        app.enter_section();
+        assert_eq!(app.tab_state.selected(), Some(0), "the author row");
+        app.handle(Action::Select).await.expect("enter");
+        assert_eq!(app.screen, Screen::Author);
+        let view = app.author.as_ref().expect("a corpus");
+        assert_eq!(view.corpus.books.len(), 2);
+        assert_eq!(view.corpus.highlight_count(), 1);
+        assert_eq!(view.order, CorpusOrder::Published);
+
+        app.handle(Action::CycleSort).await.expect("s");
+        assert_eq!(app.author.as_ref().unwrap().order, CorpusOrder::Read);
+        assert_eq!(app.screen, Screen::Author);
+
+        // Read order puts the book being read first; its title is the row.
+        app.handle(Action::Select).await.expect("enter on a row");
+        assert_eq!(app.screen, Screen::Book);
+        let title = app.view.as_ref().and_then(|v| v.book.title.clone());
+        assert_eq!(title.as_deref(), Some("Station Eleven"));
+        app.handle(Action::Back).await.expect("back");
+        assert_eq!(app.screen, Screen::Author);
+    }
+
+    /// `y` quotes the highlight under the cursor, and `y` again on it moves to
+    /// the next style. The clipboard may not exist where tests run, so what is
+    /// asserted is what was quoted, not that it arrived.
+    #[tokio::test]
+    async fn y_quotes_a_highlight_and_again_steps_the_style() {
+        let mut app = test_app().await;
+        let book = app.library.first().cloned().expect("seeded book");
+        app.open_book(book).await.expect("open");
+        app.book_tab = BookTab::Highlights;
+        app.in_section = true;
+        app.clamp_tab_selection();
+        let id = app.view.as_ref().unwrap().highlights[0].id;
+
+        app.handle(Action::Copy).await.expect("y");
+        assert_eq!(app.copied, Some((id, QuoteStyle::Chicago)));
+        assert!(app.status.is_some());
+        app.handle(Action::Copy).await.expect("y again");
+        assert_eq!(app.copied, Some((id, QuoteStyle::Mla)));
+
+        // Off the highlights list it does nothing at all.
+        app.book_tab = BookTab::Notes;
+        app.handle(Action::Copy).await.expect("y on notes");
+        assert_eq!(app.copied, Some((id, QuoteStyle::Mla)));
+    }
+
+    /// The menu's card row opens the due cards. The back of one stays hidden
+    /// until enter, a grade before then is refused, and a graded card leaves
+    /// the session — and the queue — for as long as the schedule says.
+    #[tokio::test]
+    async fn review_shows_a_due_card_and_grades_it_only_once_it_is_shown() {
+        let mut app = test_app().await;
+        app.status = None;
+        app.menu_index = menu_row(MenuItem::Cards);
+        app.handle(Action::Select).await.expect("open");
+        assert_eq!(app.screen, Screen::Study);
+        let front = app
+            .study
+            .as_ref()
+            .and_then(|v| v.current())
+            .map(|c| c.front.clone());
+        assert_eq!(front.as_deref(), Some("insufficient"));
+        let text = screen_text(&mut app, 90, 30);
+        assert!(
+            text.contains("Station Eleven — Emily St. John Mandel"),
+            "{text}"
+        );
+        assert!(!text.contains("ch1"), "the back showed early:\n{text}");
+
+        dispatch_key(&mut app, KeyEvent::from(KeyCode::Char('3')))
+            .await
+            .expect("early grade");
+        assert_eq!(app.study.as_ref().unwrap().pos, 0);
+        dispatch_key(&mut app, KeyEvent::from(KeyCode::Enter))
+            .await
+            .expect("reveal");
+        assert!(screen_text(&mut app, 90, 30).contains("ch1"));
+        dispatch_key(&mut app, KeyEvent::from(KeyCode::Char('3')))
+            .await
+            .expect("grade");
+        assert!(app.study.as_ref().unwrap().current().is_none());
+        assert!(app.status.as_deref().unwrap().contains("back in 1 day"));
+        assert!(app.engine.due_cards(10).await.unwrap().is_empty());
+        assert!(screen_text(&mut app, 90, 30).contains("nothing due"));
+
+        app.handle(Action::Back).await.expect("back");
+        assert_eq!(app.screen, Screen::Menu);
+    }
+
+    /// `c` on a highlight puts it in the deck, with its chapter, and `c` again
+    /// takes it out. Off the highlights list it does nothing.
+    #[tokio::test]
+    async fn c_studies_the_highlight_under_the_cursor_and_again_stops() {
+        let mut app = test_app().await;
+        let book = app.library.first().cloned().expect("seeded book");
+        app.open_book(book).await.expect("open");
+        app.book_tab = BookTab::Highlights;
+        app.in_section = true;
+        app.clamp_tab_selection();
+        let id = app.view.as_ref().unwrap().highlights[0].id;
+
+        dispatch_key(&mut app, KeyEvent::from(KeyCode::Char('c')))
+            .await
+            .expect("c");
+        assert_eq!(app.view.as_ref().unwrap().studied, [id]);
+        assert!(app.status.as_deref().unwrap().contains("in the study deck"));
+        let due = app.engine.due_cards(10).await.unwrap();
+        assert!(due.iter().any(|c| c.front == "survival is insufficient"
+            && c.chapter.as_deref() == Some("1")));
+
+        dispatch_key(&mut app, KeyEvent::from(KeyCode::Char('c')))
+            .await
+            .expect("c again");
+        assert!(app.view.as_ref().unwrap().studied.is_empty());
+
+        app.book_tab = BookTab::Notes;
+        dispatch_key(&mut app, KeyEvent::from(KeyCode::Char('c')))
+            .await
+            .expect("c on notes");
+        assert!(app.view.as_ref().unwrap().studied.is_empty());
+    }
+
     // ---- the links pane ----------------------------------------------------
 
     /// A small graph in the open book, built through the real engine so the
@@ -5583,18 +6782,22 @@ mod tests {
 
         // Outbound first, in the order the body wrote them.
         match &pane.rows[0] {
-            LinkRow::Out { title, to: Some(n) } => {
+            LinkRow::Out {
+                title, to: Some(n), ..
+            } => {
                 assert_eq!(title, "Symphony");
                 assert_eq!(n.id, symphony);
             }
             other => panic!("first row is the resolved outbound link, got {other:?}"),
         }
         match &pane.rows[1] {
-            LinkRow::Out { title, to: None } => assert_eq!(title, "Nowhere"),
+            LinkRow::Out {
+                title, to: None, ..
+            } => assert_eq!(title, "Nowhere"),
             other => panic!("the dangling target is kept as text, got {other:?}"),
         }
         match &pane.rows[2] {
-            LinkRow::In(n) => assert_eq!(n.id, inbound),
+            LinkRow::In(n) => assert_eq!(n.from.id, inbound),
             other => panic!("inbound comes after outbound, got {other:?}"),
         }
     }
@@ -5650,7 +6853,7 @@ mod tests {
             .rows
             .iter()
             .filter_map(|r| match r {
-                LinkRow::In(n) => Some(n.id),
+                LinkRow::In(n) => Some(n.from.id),
                 _ => None,
             })
             .collect();
@@ -5658,6 +6861,77 @@ mod tests {
         assert!(!back.contains(&inbound));
     }
 
+    /// A link into a section lands on it: the walk moves as for any link, and
+    /// the target opens with the cursor on the cited heading. A section the
+    /// target has lost says so, and the walk still moves.
+    #[tokio::test]
+    async fn following_a_link_into_a_section_opens_the_note_there() {
+        let mut app = test_app().await;
+        let book = app.library.first().cloned().expect("seeded book");
+        app.open_book(book).await.expect("open");
+        let book_id = app.view.as_ref().and_then(|v| v.book.id);
+        let make = async |title: &str, body: &str| {
+            app.engine
+                .create_note(NewNoteInput {
+                    book_id,
+                    title: Some(title.into()),
+                    body: body.into(),
+                    ..NewNoteInput::default()
+                })
+                .await
+                .expect("note")
+                .id
+        };
+        let symphony = make("Symphony", "# Symphony\nActors.\n\n## Act II\nThe road.").await;
+        let hub = make("Hub", "[[Symphony#Act II]], [[Symphony#Act III]]").await;
+        app.reload_view().await.expect("reload");
+        app.book_tab = BookTab::Notes;
+        app.in_section = true;
+        let i = app
+            .view
+            .as_ref()
+            .unwrap()
+            .notes
+            .iter()
+            .position(|n| n.id == hub);
+        app.tab_state.select(i);
+        app.handle(Action::Links).await.expect("open links");
+
+        app.handle(Action::Select)
+            .await
+            .expect("follow into the section");
+        assert_eq!(app.links.as_ref().expect("pane").note.id, symphony);
+        app.on_editor_key(KeyEvent::from(KeyCode::Char('>')))
+            .await
+            .expect("type at the cursor");
+        let draft = app.note_editor.as_ref().expect("opened in the editor");
+        assert!(matches!(&draft.target, NoteTarget::Edit(n) if n.id == symphony));
+        assert_eq!(
+            draft.editor.text(),
+            "# Symphony\nActors.\n\n>## Act II\nThe road."
+        );
+        app.on_editor_key(KeyEvent::from(KeyCode::Esc))
+            .await
+            .expect("close");
+
+        // Back on the hub, the second link names a section there is none of.
+        let i = app
+            .view
+            .as_ref()
+            .unwrap()
+            .notes
+            .iter()
+            .position(|n| n.id == hub);
+        app.tab_state.select(i);
+        app.links = None;
+        app.handle(Action::Links).await.expect("open links");
+        app.handle(Action::Down).await.expect("down");
+        app.handle(Action::Select).await.expect("follow");
+        assert!(app.note_editor.is_none());
+        let status = app.status.clone().expect("said something");
+        assert!(status.contains("no section “Act III”"), "{status}");
+    }
+
     /// Esc backs out one level — into the note list the pane replaced, not out
     /// of the section and not out of the book.
     #[tokio::test]
@@ -5853,6 +7127,50 @@ mod tests {
         );
     }
 
+    /// A book read in another edition says so, in its header and on its Info
+    /// tab: "have I read this?" is the work's question, as `rb show` answers it.
+    #[tokio::test]
+    async fn the_info_tab_names_the_edition_that_was_read() {
+        let mut app = test_app().await;
+        let edition = |title: &str, year| Book {
+            title: Some(title.into()),
+            authors: vec!["Han Kang".into()],
+            publish_year: Some(year),
+            ..Book::default()
+        };
+        let original = app
+            .engine
+            .save_book(&edition("Chaesikjuuija", 2007))
+            .await
+            .expect("save");
+        let translation = app
+            .engine
+            .save_book(&edition("The Vegetarian", 2015))
+            .await
+            .expect("save");
+        let (oid, tid) = (original.id.expect("id"), translation.id.expect("id"));
+        app.engine
+            .storage()
+            .record_reading(tid, Some(1_000), Some(2_000), "finished", "manual")
+            .await
+            .expect("reading");
+
+        app.open_book(original.clone()).await.expect("open");
+        assert!(screen_text(&mut app, 100, 24).contains("not started"));
+
+        app.engine.attach_edition(tid, oid).await.expect("attach");
+        app.open_book(original).await.expect("open");
+        // The header, over the object, before any section is open.
+        let text = screen_text(&mut app, 100, 24);
+        assert!(text.contains("read as The Vegetarian"), "{text}");
+        app.in_section = true;
+        let text = screen_text(&mut app, 100, 24);
+        assert!(text.contains("editions  2"), "{text}");
+        // The pane is narrow and wraps; the edition's year is on the next row.
+        assert!(text.contains("read as   The"), "{text}");
+        assert!(text.contains("Vegetarian (2015)"), "{text}");
+    }
+
     /// Once a book has been read twice, each highlight says which read it came
     /// from — and one that belongs to neither read says *that*, rather than
     /// going blank and looking like a misaligned row.
@@ -6129,6 +7447,42 @@ mod tests {
             .expect("save");
     }
 
+    /// `S` walks the shelves and back out to the whole library, and esc drops
+    /// a shelf the way it drops a find.
+    #[tokio::test]
+    async fn shift_s_narrows_the_library_to_each_shelf_in_turn() {
+        let mut app = test_app().await;
+        seed_second_book(&app).await;
+        app.refresh_library().await.expect("refresh");
+        let pachinko = app
+            .library
+            .iter()
+            .find(|b| b.display_title() == "Pachinko")
+            .and_then(|b| b.id)
+            .expect("seeded");
+        app.engine.create_shelf("Desk", None).await.expect("shelf");
+        app.engine
+            .shelve("Desk", pachinko, None)
+            .await
+            .expect("shelve");
+        app.go(Screen::Library);
+
+        app.handle(Action::CycleShelf).await.expect("S");
+        assert_eq!(app.library_shelf.as_deref(), Some("Desk"));
+        assert_eq!(app.library.len(), 1);
+        assert_eq!(app.library[0].id, Some(pachinko));
+
+        app.handle(Action::CycleShelf).await.expect("S again");
+        assert!(app.library_shelf.is_none(), "past the last shelf is none");
+        assert_eq!(app.library.len(), 2);
+
+        app.handle(Action::CycleShelf).await.expect("S");
+        app.handle(Action::Back).await.expect("esc");
+        assert!(app.library_shelf.is_none());
+        assert_eq!(app.screen, Screen::Library, "esc widened before leaving");
+        assert_eq!(app.library.len(), 2);
+    }
+
     /// The whole point of the key: a book you already own is found in the
     /// library, and no provider is asked. Asserted by where it lands — the
     /// search screen is the only place a network call is made from, and this
@@ -6188,6 +7542,53 @@ mod tests {
         );
     }
 
+    /// Tab on the search screen turns it on our own text, and enter on a hit
+    /// lands on that highlight in its book — not on the book's info page, which
+    /// would leave the reader to find the row the search already found.
+    ///
+    /// Offline throughout: the scope is switched before anything is asked, so
+    /// no provider is ever reached.
+    #[tokio::test]
+    async fn a_library_search_opens_the_book_on_the_row_it_found() {
+        let mut app = home_app().await;
+        app.open_search();
+        app.input = None;
+        app.handle(Action::TogglePanel).await.expect("tab");
+        assert_eq!(app.search_scope, SearchScope::Library);
+
+        app.commit_input(InputContext::SearchQuery, "insufficient".into())
+            .await
+            .expect("commit");
+        assert!(app.search_results.is_empty(), "a provider was asked");
+        assert_eq!(app.library_hits.len(), 1);
+        assert!(matches!(app.library_hits[0].hit, LibraryHit::Highlight(_)));
+        assert_eq!(
+            app.library_hits[0]
+                .book
+                .as_ref()
+                .map(|b| b.display_title().to_string())
+                .as_deref(),
+            Some("Station Eleven")
+        );
+
+        // The row draws, at a size that has to cut it short.
+        let mut t = ratatui::Terminal::new(TestBackend::new(30, 8)).expect("terminal");
+        t.draw(|f| ui::draw(f, &mut app)).expect("draw");
+
+        app.handle(Action::Select).await.expect("open");
+        assert_eq!(app.screen, Screen::Book);
+        assert_eq!(app.book_tab, BookTab::Highlights);
+        assert!(app.in_section && app.layout.panel);
+        assert_eq!(app.tab_state.selected(), Some(0));
+
+        // And back — out of the section first, as always — is the search,
+        // still holding its answer.
+        app.handle(Action::Back).await.expect("back");
+        app.handle(Action::Back).await.expect("back");
+        assert_eq!(app.screen, Screen::Search);
+        assert_eq!(app.library_hits.len(), 1);
+    }
+
     /// Esc off a narrowed library widens it rather than leaving: the two
     /// meanings are one keypress apart, and leaving with the filter still set
     /// is how books appear to have gone missing later.
@@ -6508,6 +7909,88 @@ mod tests {
         assert_eq!(app.library.len(), 2);
     }
 
+    /// `p` asks before it installs, naming the directory; `y` installs; the
+    /// next `p` offers the removal, and declining it leaves the plugin there.
+    #[tokio::test]
+    async fn p_installs_and_removes_the_plugin_only_when_asked() {
+        let mut app = test_app().await;
+        app.screen = Screen::Device;
+        app.handle(Action::Plugin).await.expect("plugin");
+        assert!(app.confirm.is_none(), "the fixture tree is not a KOReader");
+        assert!(app.status.as_deref().unwrap().starts_with("no plugin here"));
+
+        let root = app.device_root.clone().expect("scanned");
+        let ko = root.join("koreader");
+        std::fs::create_dir_all(ko.join("frontend")).unwrap();
+        std::fs::create_dir_all(ko.join("plugins")).unwrap();
+        std::fs::write(ko.join("reader.lua"), "-- entry point\n").unwrap();
+        let dir = ko.join("plugins/readingbuddy.koplugin");
+
+        app.handle(Action::Plugin).await.expect("plugin");
+        match &app.confirm {
+            Some(Confirm::InstallPlugin { plugin_dir, .. }) => assert_eq!(plugin_dir, &dir),
+            other => panic!("expected the install question, got {other:?}"),
+        }
+        assert!(!dir.exists(), "asking writes nothing");
+        app.resolve_confirm(true).await.expect("yes");
+        assert!(dir.join("main.lua").is_file());
+
+        app.handle(Action::Plugin).await.expect("plugin");
+        assert!(matches!(app.confirm, Some(Confirm::RemovePlugin { .. })));
+        app.resolve_confirm(false).await.expect("no");
+        assert!(dir.join("main.lua").is_file());
+
+        app.handle(Action::Plugin).await.expect("plugin");
+        app.resolve_confirm(true).await.expect("yes");
+        assert!(!dir.exists());
+    }
+
+    /// `w` lists the paired readers; `n` asks for a name and shows the code,
+    /// and `d` revokes only once the question is answered.
+    #[tokio::test]
+    async fn w_pairs_a_reader_and_revokes_it_when_asked() {
+        let mut app = test_app().await;
+        app.screen = Screen::Device;
+        app.handle(Action::Readers).await.expect("readers");
+        assert!(app.readers.as_ref().expect("open").devices.is_empty());
+
+        app.handle(Action::NewNote).await.expect("new");
+        assert_eq!(
+            app.input.as_ref().map(|i| i.context),
+            Some(InputContext::PairingName)
+        );
+        app.input = None;
+        app.commit_input(InputContext::PairingName, "Libra".into())
+            .await
+            .expect("mint");
+        let status = app.status.clone().unwrap();
+        assert!(status.contains("into Libra"), "{status}");
+        let code = status.split_whitespace().nth(1).unwrap().to_string();
+        let paired = app.engine.pair_device(&code).await.expect("pair");
+
+        // The pairing happened on the reader: reopening is what shows it.
+        app.handle(Action::Back).await.expect("close");
+        app.handle(Action::Readers).await.expect("reopen");
+        let listed = &app.readers.as_ref().unwrap().devices;
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].is_paired());
+
+        app.handle(Action::Delete).await.expect("delete");
+        assert!(matches!(app.confirm, Some(Confirm::RevokeDevice { .. })));
+        app.resolve_confirm(false).await.expect("no");
+        assert!(app.engine.authenticate_device(&paired.token).await.is_ok());
+
+        app.handle(Action::Delete).await.expect("delete");
+        app.resolve_confirm(true).await.expect("yes");
+        assert!(app.engine.authenticate_device(&paired.token).await.is_err());
+        let listed = &app.readers.as_ref().expect("still open").devices;
+        assert!(listed[0].revoked_at.is_some());
+
+        app.handle(Action::Back).await.expect("close");
+        assert!(app.readers.is_none());
+        assert_eq!(app.screen, Screen::Device);
+    }
+
     /// With nothing marked, `s` takes every row there is something to do about
     /// — and never the unreadable one, which a sync would error on.
     #[tokio::test]
@@ -6871,7 +8354,7 @@ mod tests {
     /// one sweep and not the other. The length is written out, so growing it is a
     /// deliberate edit — though what really stops a screen shipping unswept is
     /// `ui::help::page`, which is exhaustive on [`Screen`].
-    const ALL_SCREENS: [Screen; 9] = [
+    const ALL_SCREENS: [Screen; 11] = [
         Screen::Home,
         Screen::Menu,
         Screen::Library,
@@ -6881,6 +8364,8 @@ mod tests {
         Screen::Device,
         Screen::Calibre,
         Screen::Goodreads,
+        Screen::Author,
+        Screen::Study,
     ];
 
     /// The front door is the menu with nothing behind it, which is what makes
@@ -7036,6 +8521,8 @@ mod tests {
             Screen::Device,
             Screen::Calibre,
             Screen::Goodreads,
+            Screen::Author,
+            Screen::Study,
         ] {
             app.screen = screen;
             let mut t = ratatui::Terminal::new(TestBackend::new(w, h)).unwrap();
@@ -7126,6 +8613,8 @@ mod tests {
             (Screen::Device, true, false),
             (Screen::Calibre, false, false),
             (Screen::Goodreads, false, false),
+            (Screen::Author, false, false),
+            (Screen::Study, false, false),
         ] {
             app.screen = screen;
             let held = empty.then(|| std::mem::take(&mut app.reading));
@@ -8064,6 +9553,8 @@ mod tests {
             (Screen::Device, "device"),
             (Screen::Calibre, "calibre"),
             (Screen::Goodreads, "goodreads"),
+            (Screen::Author, "an author"),
+            (Screen::Study, "review"),
         ] {
             app.screen = screen;
             dispatch_key(&mut app, KeyEvent::from(KeyCode::Char('?')))
@@ -1,4 +1,5 @@
-//! System clipboard access, used by the API-key box to paste a copied key.
+//! System clipboard access, used by the API-key box to paste a copied key and
+//! by the highlights list to copy a passage out as a quotation.
 //!
 //! We read the OS clipboard directly (via `arboard`) rather than relying on the
 //! terminal's bracketed-paste: it works the same on every terminal and lets the
@@ -12,3 +13,20 @@ pub fn read() -> Result<String> {
     let text = clipboard.get_text().context("read clipboard")?;
     Ok(text.trim().to_string())
 }
+
+/// Put `text` on the clipboard.
+#[cfg(not(test))]
+pub fn write(text: &str) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new().context("open clipboard")?;
+    clipboard
+        .set_text(text.to_string())
+        .context("write clipboard")?;
+    Ok(())
+}
+
+/// Tests copy too, and must not overwrite what the person running them had on
+/// their clipboard.
+#[cfg(test)]
+pub fn write(_text: &str) -> Result<()> {
+    Ok(())
+}
@@ -50,6 +50,8 @@ pub enum Action {
     ToggleFinished,
     /// Export flashcards.
     Export,
+    /// Copy the selected highlight out, quoted with its page and edition.
+    Copy,
     /// Remove the selected library book.
     Delete,
     /// Open / reopen a search query.
@@ -60,6 +62,9 @@ pub enum Action {
     CycleAmbient,
     /// Cycle the library list's order (library screen).
     CycleSort,
+    /// Narrow the library list to the next shelf, then back to none (library
+    /// screen).
+    CycleShelf,
     /// Mark / unmark the selected device row.
     Mark,
     /// Bring the marked device rows across (or every syncable one).
@@ -68,6 +73,12 @@ pub enum Action {
     Link,
     /// Walk the device again.
     Rescan,
+    /// Install readingbuddy's KOReader plugin on the device, or remove it.
+    /// Always asked, never done: it puts code on somebody's reader.
+    Plugin,
+    /// Open the paired readers: the ones that push over the network rather
+    /// than being plugged in.
+    Readers,
     /// Bring a row in as a **new** book even though it looks like one already
     /// here — the `--new` escape hatch `ko pull`, `goodreads import` and
     /// `calibre import` all carry, as a key.
@@ -78,6 +89,11 @@ pub enum Action {
     CreateAnyway,
     /// Convert a book between formats through calibre.
     Convert,
+    /// Grade the card on the study screen. Its own keys, `1`–`4`, claimed
+    /// there and nowhere else.
+    Grade(readingbuddy::Grade),
+    /// Put the selected highlight in the study deck, or take it out.
+    Study,
     /// Show the current screen's help page.
     ///
     /// Global, and deliberately so: the page is per-screen but the *key* is not,
@@ -106,6 +122,7 @@ pub fn map_key_on(screen: crate::app::Screen, key: KeyEvent) -> Option<Action> {
         Screen::Device => map_device_key(key),
         Screen::Calibre => map_calibre_key(key),
         Screen::Goodreads => map_goodreads_key(key),
+        Screen::Study => map_study_key(key),
         _ => None,
     };
     claimed.or_else(|| map_key(key))
@@ -131,6 +148,12 @@ fn map_device_key(key: KeyEvent) -> Option<Action> {
         KeyCode::Char('s') => Some(Action::Sync),
         KeyCode::Char('l') => Some(Action::Link),
         KeyCode::Char('r') => Some(Action::Rescan),
+        // `p` is the global progress key, and a device row has no progress of
+        // ours to edit — the device owns it.
+        KeyCode::Char('p') => Some(Action::Plugin),
+        // `w` is the global review key; a device row is not a reading to
+        // review, and "wireless" is the mnemonic.
+        KeyCode::Char('w') => Some(Action::Readers),
         _ => None,
     }
 }
@@ -177,6 +200,20 @@ fn map_goodreads_key(key: KeyEvent) -> Option<Action> {
     }
 }
 
+/// The study screen's bindings: the four grades, on the digits Anki puts them
+/// on. No other screen spends a digit, so claiming them here shadows nothing.
+fn map_study_key(key: KeyEvent) -> Option<Action> {
+    if !claimable(key) {
+        return None;
+    }
+    match key.code {
+        KeyCode::Char(c @ '1'..='4') => {
+            readingbuddy::Grade::parse(&c.to_string()).map(Action::Grade)
+        }
+        _ => None,
+    }
+}
+
 pub fn map_key(key: KeyEvent) -> Option<Action> {
     // Windows delivers press *and* release; only act on press.
     if key.kind == KeyEventKind::Release {
@@ -204,6 +241,13 @@ pub fn map_key(key: KeyEvent) -> Option<Action> {
     {
         return Some(Action::Links);
     }
+    // Shift-S the same way: the shelf is the library's other arrangement, so it
+    // sits on the capital of the sort key.
+    if matches!(key.code, KeyCode::Char('S'))
+        || (matches!(key.code, KeyCode::Char('s')) && key.modifiers.contains(KeyModifiers::SHIFT))
+    {
+        return Some(Action::CycleShelf);
+    }
     match key.code {
         KeyCode::Char('q') => Some(Action::Quit),
         KeyCode::Char('m') => Some(Action::Menu),
@@ -250,6 +294,12 @@ pub fn map_key(key: KeyEvent) -> Option<Action> {
         KeyCode::Char('p') => Some(Action::EditProgress),
         KeyCode::Char('f') => Some(Action::ToggleFinished),
         KeyCode::Char('x') => Some(Action::Export),
+        // vim's yank.
+        KeyCode::Char('y') => Some(Action::Copy),
+        // A card out of the highlight under the cursor. Global for the same
+        // reason `y` is, and free because the one screen that spends `c` — the
+        // calibre shelf, on a conversion — claims it in its own map first.
+        KeyCode::Char('c') => Some(Action::Study),
         KeyCode::Char('d') => Some(Action::Delete),
         KeyCode::Char('/') => Some(Action::Query),
         KeyCode::Char('g') => Some(Action::EditApiKey),
@@ -333,6 +383,8 @@ mod tests {
             (KeyCode::Char('s'), Action::Sync),
             (KeyCode::Char('l'), Action::Link),
             (KeyCode::Char('r'), Action::Rescan),
+            (KeyCode::Char('p'), Action::Plugin),
+            (KeyCode::Char('w'), Action::Readers),
         ] {
             assert_eq!(map_key_on(Screen::Device, press(code)), Some(want));
             assert_eq!(
@@ -421,12 +473,52 @@ mod tests {
         );
     }
 
+    /// The grades live on the study screen's digits; every other screen leaves
+    /// the digits alone, and the study screen keeps Enter to reveal.
+    #[test]
+    fn the_study_screen_grades_on_the_digits_and_only_there() {
+        use crate::app::Screen;
+        use readingbuddy::Grade;
+        for (c, grade) in [
+            ('1', Grade::Again),
+            ('2', Grade::Hard),
+            ('3', Grade::Good),
+            ('4', Grade::Easy),
+        ] {
+            assert_eq!(
+                map_key_on(Screen::Study, press(KeyCode::Char(c))),
+                Some(Action::Grade(grade))
+            );
+            assert_eq!(map_key_on(Screen::Book, press(KeyCode::Char(c))), None);
+        }
+        assert_eq!(map_key_on(Screen::Study, press(KeyCode::Char('5'))), None);
+        // Enter still reveals, `c` still makes a card, and `c` on the calibre
+        // shelf still converts.
+        assert_eq!(
+            map_key_on(Screen::Study, press(KeyCode::Enter)),
+            Some(Action::Select)
+        );
+        assert_eq!(
+            map_key_on(Screen::Book, press(KeyCode::Char('c'))),
+            Some(Action::Study)
+        );
+        assert_eq!(
+            map_key_on(Screen::Calibre, press(KeyCode::Char('c'))),
+            Some(Action::Convert)
+        );
+    }
+
     /// A screen may not shadow `ctrl-c`. The claim maps run before the global one,
     /// so this is the guard that they never see a modified key at all.
     #[test]
     fn no_screen_can_shadow_ctrl_c() {
         use crate::app::Screen;
-        for screen in [Screen::Device, Screen::Calibre, Screen::Goodreads] {
+        for screen in [
+            Screen::Device,
+            Screen::Calibre,
+            Screen::Goodreads,
+            Screen::Study,
+        ] {
             let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
             assert_eq!(map_key_on(screen, ctrl_c), Some(Action::Quit));
             // And `s`/`n` with control held reach nothing rather than the shelf.
@@ -465,6 +557,16 @@ mod tests {
 
     /// `L` is the links pane, and taking the capital must not have disturbed
     /// the lowercase `l` every list uses to step right.
+    #[test]
+    fn shift_s_cycles_the_shelf_and_plain_s_still_sorts() {
+        assert_eq!(map_key(press(KeyCode::Char('S'))), Some(Action::CycleShelf));
+        assert_eq!(
+            map_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::SHIFT)),
+            Some(Action::CycleShelf)
+        );
+        assert_eq!(map_key(press(KeyCode::Char('s'))), Some(Action::CycleSort));
+    }
+
     #[test]
     fn shift_l_opens_the_links_pane_and_plain_l_still_moves_right() {
         assert_eq!(map_key(press(KeyCode::Char('L'))), Some(Action::Links));
@@ -349,7 +349,16 @@ async fn main() -> Result<()> {
             None
         }
     };
-    let result = app::run(&mut terminal, &mut app, mounts).await;
+    // The same rule for the vault: without a watcher, an Obsidian edit is
+    // searchable after the next explicit refresh instead of straight away.
+    let vault = match app.engine.watch_vault() {
+        Ok(w) => Some(w),
+        Err(e) => {
+            tracing::warn!(error = %e, "not watching the vault");
+            None
+        }
+    };
+    let result = app::run(&mut terminal, &mut app, mounts, vault).await;
     restore_terminal();
     result
 }
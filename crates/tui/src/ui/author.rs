@@ -0,0 +1,122 @@
+//! One author across their books: each book, then what was highlighted and
+//! noted in it.
+//!
+//! Drawn in the shelf frame the device and calibre screens use, because it is a
+//! shelf — one row per book — with the book's own rows indented under it. The
+//! corpus is read whole when the screen opens; `s` re-reads it in the other
+//! order rather than resorting in place, so the two orders can never disagree
+//! with the engine about where a book goes.
+
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem};
+use readingbuddy::{AuthorCorpus, CorpusOrder};
+
+use crate::app::{App, CorpusRow};
+use crate::theme;
+
+const HINT: &str = "nothing by this author yet";
+
+pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
+    let (title, rows) = match &app.author {
+        None => (" author ".to_string(), Vec::new()),
+        Some(v) => {
+            let rows: Vec<Line> = v.rows.iter().map(|r| row(&v.corpus, *r)).collect();
+            (title_for(&v.corpus, v.order), rows)
+        }
+    };
+
+    let Some((area, block)) = super::shelf_frame(f, area, title, key_bar(), &rows, HINT) else {
+        return;
+    };
+
+    let items: Vec<ListItem> = rows.into_iter().map(ListItem::new).collect();
+    let list = List::new(items).block(block).highlight_symbol("› ");
+    if let Some(v) = &mut app.author {
+        f.render_stateful_widget(list, area, &mut v.state);
+    }
+}
+
+/// The name, the other spellings folded into it, and the order.
+fn title_for(c: &AuthorCorpus, order: CorpusOrder) -> String {
+    let by = match order {
+        CorpusOrder::Published => "by year",
+        CorpusOrder::Read => "as read",
+    };
+    if c.variants.is_empty() {
+        format!(" {} · {by} ", c.name)
+    } else {
+        format!(" {} (also {}) · {by} ", c.name, c.variants.join("; "))
+    }
+}
+
+fn row(c: &AuthorCorpus, r: CorpusRow) -> Line<'static> {
+    match r {
+        CorpusRow::Book(b) => {
+            let entry = &c.books[b];
+            let mut spans = vec![Span::styled(
+                entry.book.display_title().to_string(),
+                theme::title(),
+            )];
+            if let Some(y) = entry.book.publish_year {
+                spans.push(Span::styled(format!("  {y}"), theme::dim()));
+            }
+            if !entry.editions.is_empty() {
+                spans.push(Span::styled(
+                    format!("  +{} edition(s)", entry.editions.len()),
+                    theme::dim(),
+                ));
+            }
+            spans.push(Span::styled(
+                format!(
+                    "  {} highlights · {} notes",
+                    entry.highlights.len(),
+                    entry.notes.len()
+                ),
+                theme::dim(),
+            ));
+            Line::from(spans)
+        }
+        CorpusRow::Highlight(b, i) => {
+            let h = &c.books[b].highlights[i];
+            let page = h.page.map(|p| format!("p.{p} ")).unwrap_or_default();
+            let mut spans = vec![
+                Span::styled(format!("    {page}"), theme::dim()),
+                Span::styled(
+                    format!(
+                        "“{}”",
+                        super::clip(h.text.trim().to_string(), super::DETAIL_MAX)
+                    ),
+                    theme::primary(),
+                ),
+            ];
+            if let Some(a) = h.annotation.as_deref().or(h.ko_note.as_deref()) {
+                spans.push(Span::styled(
+                    format!("  — {}", super::clip(a.to_string(), super::DETAIL_MAX)),
+                    theme::dim(),
+                ));
+            }
+            Line::from(spans)
+        }
+        CorpusRow::Note(b, i) => {
+            let n = &c.books[b].notes[i];
+            Line::from(vec![
+                Span::styled("    ✎ ", theme::accent()),
+                Span::styled(n.title.clone(), theme::primary()),
+                Span::styled(format!("  {}", n.kind), theme::dim()),
+            ])
+        }
+    }
+}
+
+fn key_bar() -> Line<'static> {
+    Line::from(vec![
+        Span::styled(" enter", theme::key()),
+        Span::styled(" open book  ", theme::dim()),
+        Span::styled("s", theme::key()),
+        Span::styled(" order  ", theme::dim()),
+        Span::styled("m", theme::key()),
+        Span::styled(" menu ", theme::dim()),
+    ])
+}
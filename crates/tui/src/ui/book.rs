@@ -125,14 +125,19 @@ fn draw_header(f: &mut Frame, view: &BookView, object: Rect) {
         title,
     );
     f.render_widget(
-        Paragraph::new(Line::from(Span::styled(progress_text(b), theme::accent())))
-            .alignment(Alignment::Center),
+        Paragraph::new(Line::from(Span::styled(
+            progress_text(view),
+            theme::accent(),
+        )))
+        .alignment(Alignment::Center),
         prog,
     );
 }
 
-/// Progress as a compact one-liner for the floating header.
-fn progress_text(b: &Book) -> String {
+/// Progress as a compact one-liner for the floating header. An edition never
+/// opened whose work was read in another says which, not "not started".
+fn progress_text(view: &BookView) -> String {
+    let b = &view.book;
     match (b.finished, b.current_page, b.page_count) {
         (true, _, _) => "finished".to_string(),
         (_, Some(p), Some(t)) if t > 0 => {
@@ -140,10 +145,21 @@ fn progress_text(b: &Book) -> String {
             format!("{p} / {t} · {pct}%")
         }
         (_, Some(p), _) => format!("page {p}"),
-        _ => "not started".to_string(),
+        _ => match read_elsewhere(view) {
+            Some(read) => format!("read as {}", read.display_title()),
+            None => "not started".to_string(),
+        },
     }
 }
 
+/// The edition of this book's work last finished, when that is another one.
+fn read_elsewhere(view: &BookView) -> Option<&Book> {
+    view.work
+        .as_ref()?
+        .read_as()
+        .filter(|r| r.id != view.book.id)
+}
+
 /// The section pane: the section menu, or an open section's content. Separated
 /// from the object by a rule — on the left in Split, on top in Stacked.
 fn draw_panel(f: &mut Frame, app: &mut App, area: Rect, border: Borders) {
@@ -238,7 +254,7 @@ fn draw_section(f: &mut Frame, app: &mut App, area: Rect) {
     match app.book_tab {
         BookTab::Info => {
             let view = app.view.as_ref().expect("checked");
-            draw_info(f, view, content);
+            draw_info(f, view, app.tab_state.selected(), content);
         }
         BookTab::Notes if app.links.is_some() => {
             draw_links(f, app.links.as_mut().expect("checked"), content);
@@ -266,7 +282,13 @@ fn draw_section(f: &mut Frame, app: &mut App, area: Rect) {
             let items: Vec<ListItem> = view
                 .highlights
                 .iter()
-                .map(|h| ListItem::new(highlight_line(h, gutter.then(|| view.read_number(h)))))
+                .map(|h| {
+                    ListItem::new(highlight_line(
+                        h,
+                        gutter.then(|| view.read_number(h)),
+                        view.studied.contains(&h.id),
+                    ))
+                })
                 .collect();
             draw_list(
                 f,
@@ -358,12 +380,37 @@ fn draw_links(f: &mut Frame, pane: &mut crate::app::LinksPane, area: Rect) {
 
 /// One edge as a row. Homogeneous colour, like `note_line`, so a `REVERSED`
 /// selection inverts the whole row uniformly.
+///
+/// A section rides in wikilink form: `→ Han#Origins` is where this note points,
+/// `← Sunja  (cites #Origins)` where another one points into this.
 fn link_line(row: &crate::app::LinkRow) -> Line<'static> {
     use crate::app::LinkRow;
     let (arrow, title, tail) = match row {
-        LinkRow::Out { title, to: None } => ("→ ", title.clone(), "  (no note yet)"),
-        LinkRow::Out { title, .. } => ("→ ", title.clone(), ""),
-        LinkRow::In(n) => ("← ", n.title.clone(), ""),
+        LinkRow::Out {
+            title,
+            to,
+            anchor,
+            anchor_found,
+        } => {
+            let title = match anchor {
+                Some(a) => format!("{title}#{a}"),
+                None => title.clone(),
+            };
+            let tail = match (to, anchor_found) {
+                (None, _) => "  (no note yet)".to_string(),
+                (Some(_), false) => "  (no such section)".to_string(),
+                (Some(_), true) => String::new(),
+            };
+            ("→ ", title, tail)
+        }
+        LinkRow::In(l) => {
+            let tail = match (&l.anchor, l.anchor_found) {
+                (Some(a), true) => format!("  (cites #{a})"),
+                (Some(a), false) => format!("  (cites #{a}, gone)"),
+                (None, _) => String::new(),
+            };
+            ("← ", l.from.title.clone(), tail)
+        }
     };
     Line::from(vec![
         Span::styled(arrow, theme::primary()),
@@ -437,7 +484,7 @@ fn anchor_tag(n: &NoteRecord) -> String {
 /// Same shape as the note list's `◆`/`◇` kind gutter, and for the same reason: a
 /// word would repeat what the row already says, and one dim cell in front of
 /// every row keeps the text column aligned.
-fn highlight_line(h: &Highlight, read: Option<Option<usize>>) -> Line<'static> {
+fn highlight_line(h: &Highlight, read: Option<Option<usize>>, studied: bool) -> Line<'static> {
     let mut spans = Vec::new();
     if let Some(n) = read {
         // Two-digit reads are not a thing anyone will hit, but a `10` must not
@@ -451,6 +498,9 @@ fn highlight_line(h: &Highlight, read: Option<Option<usize>>) -> Line<'static> {
         spans.push(Span::styled(" · ", theme::primary()));
     }
     spans.push(Span::styled(h.text.clone(), theme::primary()));
+    if studied {
+        spans.push(Span::styled("  · studying", theme::dim()));
+    }
     Line::from(spans)
 }
 
@@ -467,12 +517,24 @@ fn card_line(c: &FlashcardRow) -> Line<'static> {
     Line::from(spans)
 }
 
-/// The Info section: the facts (title/author/progress live in the header now),
+/// The Info section: the authors — the section's list, Enter on one opens
+/// everything of theirs — then the facts (title/progress live in the header),
 /// then the highlight/note counts.
-fn draw_info(f: &mut Frame, view: &BookView, area: Rect) {
+fn draw_info(f: &mut Frame, view: &BookView, selected: Option<usize>, area: Rect) {
     let b = &view.book;
     let mut lines = Vec::new();
-    for (label, value) in facts(b) {
+    for (i, name) in b.authors.iter().enumerate() {
+        let (mark, style) = if selected == Some(i) {
+            ("› ", theme::selected())
+        } else {
+            ("  ", theme::primary())
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<10}", "author"), theme::dim()),
+            Span::styled(format!("{mark}{name}"), style),
+        ]));
+    }
+    for (label, value) in facts(b).into_iter().chain(work_facts(view)) {
         lines.push(Line::from(vec![
             Span::styled(format!("{label:<10}"), theme::dim()),
             Span::styled(value, theme::primary()),
@@ -509,6 +571,24 @@ fn facts(b: &Book) -> Vec<(&'static str, String)> {
     out
 }
 
+/// What the work adds: how many editions there are, and which was read when it
+/// was not this one — "have I read this?" is the work's question, as `rb show`
+/// answers it.
+fn work_facts(view: &BookView) -> Vec<(&'static str, String)> {
+    let Some(h) = view.work.as_ref().filter(|h| h.editions.len() > 1) else {
+        return Vec::new();
+    };
+    let mut out = vec![("editions", h.editions.len().to_string())];
+    if let Some(read) = read_elsewhere(view) {
+        let year = read
+            .publish_year
+            .map(|y| format!(" ({y})"))
+            .unwrap_or_default();
+        out.push(("read as", format!("{}{year}", read.display_title())));
+    }
+    out
+}
+
 /// The key bar. Collapsed it advertises the way out and the way to more;
 /// expanded it lists everything the view responds to. Pairs are dropped from
 /// the right when the pane is too narrow, so it never wraps.
@@ -689,6 +769,7 @@ mod tests {
     #[test]
     fn a_link_row_carries_its_direction_and_says_when_it_dangles() {
         use crate::app::LinkRow;
+        use readingbuddy::Backlink;
         let flat = |l: Line<'static>| {
             l.spans
                 .iter()
@@ -711,6 +792,8 @@ mod tests {
             flat(link_line(&LinkRow::Out {
                 title: "Symphony".into(),
                 to: Some(target.clone()),
+                anchor: None,
+                anchor_found: true,
             })),
             "→ Symphony"
         );
@@ -718,10 +801,36 @@ mod tests {
             flat(link_line(&LinkRow::Out {
                 title: "Nowhere".into(),
                 to: None,
+                anchor: None,
+                anchor_found: false,
             })),
             "→ Nowhere  (no note yet)"
         );
-        assert_eq!(flat(link_line(&LinkRow::In(target))), "← Symphony");
+        assert_eq!(
+            flat(link_line(&LinkRow::Out {
+                title: "Symphony".into(),
+                to: Some(target.clone()),
+                anchor: Some("Act II".into()),
+                anchor_found: false,
+            })),
+            "→ Symphony#Act II  (no such section)"
+        );
+        assert_eq!(
+            flat(link_line(&LinkRow::In(Backlink {
+                from: target.clone(),
+                anchor: None,
+                anchor_found: true,
+            }))),
+            "← Symphony"
+        );
+        assert_eq!(
+            flat(link_line(&LinkRow::In(Backlink {
+                from: target,
+                anchor: Some("^coda".into()),
+                anchor_found: true,
+            }))),
+            "← Symphony  (cites #^coda)"
+        );
     }
 
     #[test]
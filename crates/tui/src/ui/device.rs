@@ -9,10 +9,10 @@
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{List, ListItem};
-use readingbuddy::DeviceState;
+use ratatui::widgets::{Block, Borders, List, ListItem, Padding};
+use readingbuddy::{Device, DeviceSource, DeviceState};
 
-use crate::app::{App, DeviceRow};
+use crate::app::{App, DeviceRow, ReadersPanel};
 use crate::theme;
 
 const HINT: &str = "press r to look again, / for another path";
@@ -54,6 +54,104 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
     if let Some(picker) = &mut app.link_picker {
         super::link_picker(f, picker, area);
     }
+    if let Some(panel) = &mut app.readers {
+        readers(f, panel, area);
+    }
+}
+
+/// The paired readers, over the shelf: `Libra  paired  seen 3h ago  12 books`.
+fn readers(f: &mut Frame, panel: &mut ReadersPanel, area: Rect) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let selected = panel.state.selected();
+    let mut rows: Vec<Line> = panel
+        .devices
+        .iter()
+        .enumerate()
+        .map(|(i, d)| reader_row(d, Some(i) == selected, now))
+        .collect();
+    if rows.is_empty() {
+        rows.push(Line::from(Span::styled(
+            "nothing paired yet — n for a code",
+            theme::dim(),
+        )));
+    }
+    let title = " paired readers ";
+    let widest = rows.iter().map(|r| r.width() as u16).max().unwrap_or(20);
+    let width = widest
+        .max(title.len() as u16)
+        .saturating_add(super::LIST_CHROME);
+    let box_area = super::centered(area, width, rows.len() as u16 + 2);
+    f.render_widget(ratatui::widgets::Clear, box_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme::accent())
+        .padding(Padding::horizontal(1))
+        .title(Span::styled(title, theme::accent()))
+        .title_bottom(
+            Line::from(vec![
+                Span::styled(" n", theme::key()),
+                Span::styled(" pair one  ", theme::dim()),
+                Span::styled("d", theme::key()),
+                Span::styled(" revoke  ", theme::dim()),
+                Span::styled("esc", theme::key()),
+                Span::styled(" close ", theme::dim()),
+            ])
+            .centered(),
+        );
+    let items: Vec<ListItem> = rows.into_iter().map(ListItem::new).collect();
+    let list = List::new(items).block(block).highlight_symbol("› ");
+    f.render_stateful_widget(list, box_area, &mut panel.state);
+}
+
+/// One reader. The state word is the one thing that decides whether a push
+/// from it is taken, so it leads, in the accent while it is.
+fn reader_row(d: &Device, selected: bool, now: i64) -> Line<'static> {
+    let name = if selected {
+        theme::title().patch(theme::selected())
+    } else {
+        theme::title()
+    };
+    let (label, style) = if d.revoked_at.is_some() {
+        ("revoked", theme::dim())
+    } else if d.is_paired() {
+        ("paired", theme::accent())
+    } else {
+        ("waiting", theme::primary())
+    };
+    let mut spans = vec![
+        Span::styled(format!("{label:<9}"), style),
+        Span::styled(d.name.clone(), name),
+    ];
+    if let Some(seen) = d.last_contact {
+        spans.push(Span::styled(
+            format!("  seen {}", ago(now - seen)),
+            theme::dim(),
+        ));
+    }
+    if !d.books.is_empty() {
+        let n = d.books.len();
+        spans.push(Span::styled(
+            format!("  {n} book{}", if n == 1 { "" } else { "s" }),
+            theme::dim(),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// `42s ago`, `3h ago` — the largest unit only. "When did this reader last
+/// push" wants an order of magnitude, not a timestamp.
+fn ago(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3_600 => format!("{}m ago", secs / 60),
+        3_600..86_400 => format!("{}h ago", secs / 3_600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
 }
 
 /// The volume's own name rather than its whole path: the shelf *is* the device,
@@ -65,7 +163,9 @@ fn short_root(root: &std::path::Path) -> String {
         .unwrap_or_else(|| root.display().to_string())
 }
 
-/// One shelf row: `✓ updated  Title — Authors  [42%]  2 new, 1 edited here`.
+/// One shelf row: `✓ updated  Title — Authors  [42%]  2 new, 1 edited here`,
+/// with a dim `kobo` after the percentage when the row came from a stock Kobo
+/// rather than a KOReader sidecar.
 fn row(r: &DeviceRow, marked: bool, selected: bool) -> Line<'static> {
     let title_style = if selected {
         theme::title().patch(theme::selected())
@@ -90,6 +190,12 @@ fn row(r: &DeviceRow, marked: bool, selected: bool) -> Line<'static> {
             theme::accent(),
         ));
     }
+    if b.source == DeviceSource::Kobo {
+        spans.push(Span::styled(
+            format!("  {}", b.source.label()),
+            theme::dim(),
+        ));
+    }
     let detail = super::clip(detail(r), super::DETAIL_MAX);
     if !detail.is_empty() {
         spans.push(Span::styled(format!("  {detail}"), theme::dim()));
@@ -153,6 +259,10 @@ fn key_bar() -> Line<'static> {
         Span::styled(" link  ", theme::dim()),
         Span::styled("r", theme::key()),
         Span::styled(" rescan  ", theme::dim()),
+        Span::styled("p", theme::key()),
+        Span::styled(" plugin  ", theme::dim()),
+        Span::styled("w", theme::key()),
+        Span::styled(" readers  ", theme::dim()),
         Span::styled("m", theme::key()),
         Span::styled(" menu ", theme::dim()),
     ])
@@ -167,6 +277,7 @@ mod tests {
     fn device_row(state: DeviceState) -> DeviceRow {
         DeviceRow::new(DeviceBook {
             path: std::path::PathBuf::from("/mnt/Pachinko.sdr/metadata.epub.lua"),
+            source: DeviceSource::Koreader,
             title: Some("Pachinko".into()),
             authors: Some("Min Jin Lee".into()),
             partial_md5: None,
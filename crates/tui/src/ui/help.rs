@@ -122,6 +122,11 @@ pub fn page(screen: Screen) -> Help {
                 "border always names the one in force, the book you were",
                 "standing on stays under the cursor, and the order is",
                 "remembered for next time.",
+                "",
+                "`S` steps through your shelves — manual and smart alike, made",
+                "with `readingbuddy shelf` — and past the last one back to the",
+                "whole library. `/` then narrows within the shelf; esc drops",
+                "both.",
             ],
             sections: &[Section {
                 heading: None,
@@ -129,6 +134,7 @@ pub fn page(screen: Screen) -> Help {
                     ("enter", "open the book"),
                     ("/", "narrow the list"),
                     ("s", "cycle the order"),
+                    ("S", "narrow to the next shelf"),
                     ("d", "remove the book from the library"),
                 ],
             }],
@@ -145,6 +151,7 @@ pub fn page(screen: Screen) -> Help {
                 "Notes carry a marker: ◆ is the reading's reflection, ◇ its",
                 "review. Standing on a note, L shows what links to it and what",
                 "it links out to, including targets nobody has written yet.",
+                "Enter on an author in Info opens everything of theirs.",
             ],
             sections: &[
                 Section {
@@ -158,6 +165,9 @@ pub fn page(screen: Screen) -> Help {
                         ("p", "set the page you are on"),
                         ("f", "mark it finished, or unfinish it"),
                         ("x", "export this book's flashcards"),
+                        ("x", "on a review in Notes: export it as a page"),
+                        ("y", "on a highlight: copy it quoted (again: next style)"),
+                        ("c", "on a highlight: study it, or stop"),
                     ],
                 },
                 Section {
@@ -183,12 +193,16 @@ pub fn page(screen: Screen) -> Help {
                 "out becomes a warning rather than an empty screen.",
                 "",
                 "enter adds the highlighted result to the library, cover and all.",
+                "",
+                "tab turns the same question on your own notes and highlights,",
+                "ranked together; enter there opens the book on that row.",
             ],
             sections: &[Section {
                 heading: None,
                 keys: &[
                     ("/", "ask something else"),
-                    ("enter", "add this result to the library"),
+                    ("enter", "add this result, or open this hit"),
+                    ("tab", "search online / your library"),
                 ],
             }],
         },
@@ -236,6 +250,8 @@ pub fn page(screen: Screen) -> Help {
                     ("s", "sync the marked rows, or every syncable one"),
                     ("l", "link the row to a book already here"),
                     ("r", "walk the device again"),
+                    ("p", "install, upgrade or remove the KOReader plugin"),
+                    ("w", "paired readers: n mints a code, d revokes one"),
                     ("/", "scan a different path"),
                 ],
             }],
@@ -291,6 +307,41 @@ pub fn page(screen: Screen) -> Help {
                 ],
             }],
         },
+
+        Screen::Author => Help {
+            title: " an author ",
+            about: &[
+                "Every book by one author, each with what you highlighted and",
+                "noted in it. Spellings of the same name are pooled: \"Lee, Min",
+                "Jin\" and \"Min Jin Lee\" are one person here. Enter on any",
+                "row opens the book it came from.",
+            ],
+            sections: &[Section {
+                heading: None,
+                keys: &[("s", "order by publication, or by when you read them")],
+            }],
+        },
+
+        Screen::Study => Help {
+            title: " review ",
+            about: &[
+                "The words and passages that are due, one at a time, with the",
+                "book and chapter each came from. Enter shows the rest of the",
+                "card; then grade how well it came back, and the card goes away",
+                "for as long as that earns it. Words arrive on their own from",
+                "single-word highlights; c on a highlight adds the passage.",
+            ],
+            sections: &[Section {
+                heading: None,
+                keys: &[
+                    ("enter", "show the rest of the card"),
+                    ("1", "again: forgotten, back tomorrow"),
+                    ("2", "hard: recalled with effort"),
+                    ("3", "good"),
+                    ("4", "easy: longer until it is back"),
+                ],
+            }],
+        },
     }
 }
 
@@ -370,7 +421,7 @@ pub fn render(f: &mut Frame, area: Rect, screen: Screen) {
 mod tests {
     use super::*;
 
-    const SCREENS: [Screen; 9] = [
+    const SCREENS: [Screen; 11] = [
         Screen::Home,
         Screen::Menu,
         Screen::Library,
@@ -380,6 +431,8 @@ mod tests {
         Screen::Device,
         Screen::Calibre,
         Screen::Goodreads,
+        Screen::Author,
+        Screen::Study,
     ];
 
     /// Every screen has a page, and every page says something and lists
@@ -168,6 +168,8 @@ mod tests {
             ko_status: None,
             ko_percent,
             ko_rating: None,
+            ko_last_read: None,
+            device_id: None,
             created_at: 0,
             last_modified: 0,
         }
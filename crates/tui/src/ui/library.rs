@@ -193,14 +193,19 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
     // persists and is visible": a list ordered by author with nothing saying so
     // is a library that has quietly rearranged itself, and the one moment the
     // user needs to be told is the one after they pressed the key.
+    let shelf = app
+        .library_shelf
+        .as_deref()
+        .map(|name| format!("shelf {name} · "))
+        .unwrap_or_default();
     let title = match &app.library_filter {
         Some(q) => format!(
-            " library · “{q}” · by {} · {} ",
+            " library · {shelf}“{q}” · by {} · {} ",
             app.library_sort.label(),
             app.library.len()
         ),
         None => format!(
-            " library · by {} · {} ",
+            " library · {shelf}by {} · {} ",
             app.library_sort.label(),
             app.library.len()
         ),
@@ -211,7 +216,7 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
     // what the box has to be wide enough for. `Clear` because a `Block` styles
     // the cells it doesn't draw but never blanks them, so the ambient layer
     // would otherwise show through between the rows.
-    let keys = key_bar(app.library_filter.is_some());
+    let keys = key_bar(app.library_filter.is_some() || app.library_shelf.is_some());
     let widest = rows.iter().map(|l| l.width() as u16).max().unwrap_or(0);
     let area = super::list_box(
         area,
@@ -270,6 +275,8 @@ fn key_bar(filtered: bool) -> Line<'static> {
         Span::styled(" find  ", theme::dim()),
         Span::styled("s", theme::key()),
         Span::styled(" sort  ", theme::dim()),
+        Span::styled("S", theme::key()),
+        Span::styled(" shelf  ", theme::dim()),
         Span::styled("d", theme::key()),
         Span::styled(" remove  ", theme::dim()),
         Span::styled("esc", theme::key()),
@@ -1,6 +1,7 @@
 //! Screen drawing and the responsive breakpoints.
 
 pub mod apikey;
+pub mod author;
 pub mod book;
 pub mod calibre;
 pub mod device;
@@ -12,6 +13,7 @@ pub mod library;
 pub mod menu;
 pub mod search;
 pub mod settings;
+pub mod study;
 pub mod textedit;
 
 use ratatui::Frame;
@@ -542,6 +544,8 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         Screen::Device => device::draw(f, app, body),
         Screen::Calibre => calibre::draw(f, app, body),
         Screen::Goodreads => goodreads::draw(f, app, body),
+        Screen::Author => author::draw(f, app, body),
+        Screen::Study => study::draw(f, app, body),
     }
 
     // The help page floats over the screen it describes — over the screen and
@@ -599,6 +603,18 @@ fn confirm_prompt(app: &App) -> Option<String> {
         crate::app::Confirm::OverwriteConversion { output, .. } => {
             format!("{} already exists — overwrite it?  y / n", output.display())
         }
+        // The directory, in full: the rule is that installing shows the path.
+        crate::app::Confirm::InstallPlugin { plugin_dir, .. } => format!(
+            "install the readingbuddy plugin into {}?  y / n",
+            plugin_dir.display()
+        ),
+        crate::app::Confirm::RemovePlugin { plugin_dir, .. } => format!(
+            "remove the readingbuddy plugin from {}?  y / n",
+            plugin_dir.display()
+        ),
+        crate::app::Confirm::RevokeDevice { name, .. } => {
+            format!("revoke {name}? it will have to pair again  y / n")
+        }
     })
 }
 
@@ -1,27 +1,42 @@
-//! The search screen: a results list over a federated book search. The query
-//! itself is entered through the shared input overlay (see `super::input`); this
-//! draws whatever results the last query produced.
+//! The search screen: a results list over a federated book search, or — tab —
+//! over our own notes and highlights. The query itself is entered through the
+//! shared input overlay (see `super::input`); this draws whatever results the
+//! last query produced, in whichever scope is selected.
 
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, Padding};
-use readingbuddy::RankedResult;
+use readingbuddy::{LibraryHit, RankedResult};
 
-use crate::app::App;
+use crate::app::{App, LibraryRow, SearchScope};
 use crate::theme;
 
 pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
     const HINT: &str = "press / to search — enter a title, author, or ISBN";
+    const LIBRARY_HINT: &str = "press / to search your notes and highlights";
 
     let selected = app.search_state.selected();
-    let rows: Vec<Line> = app
-        .search_results
-        .iter()
-        .enumerate()
-        .map(|(i, r)| row(r, Some(i) == selected))
-        .collect();
-    let title = format!(" search · {} ", app.search_results.len());
+    let (rows, title, hint): (Vec<Line>, String, &str) = match app.search_scope {
+        SearchScope::Online => (
+            app.search_results
+                .iter()
+                .enumerate()
+                .map(|(i, r)| row(r, Some(i) == selected))
+                .collect(),
+            format!(" search · {} ", app.search_results.len()),
+            HINT,
+        ),
+        SearchScope::Library => (
+            app.library_hits
+                .iter()
+                .enumerate()
+                .map(|(i, r)| library_row(r, Some(i) == selected))
+                .collect(),
+            format!(" search · your library · {} ", app.library_hits.len()),
+            LIBRARY_HINT,
+        ),
+    };
 
     // Shrink-wrapped and centred — see `library::draw`. With no results the box
     // still has to hold the hint, which is wider than any of the chrome.
@@ -29,7 +44,7 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
         .iter()
         .map(|l| l.width() as u16)
         .max()
-        .unwrap_or(HINT.chars().count() as u16);
+        .unwrap_or(hint.chars().count() as u16);
     let area = super::list_box(
         area,
         widest.max(title.chars().count() as u16),
@@ -47,7 +62,7 @@ pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
         let inner = block.inner(area);
         f.render_widget(block, area);
         f.render_widget(
-            ratatui::widgets::Paragraph::new(HINT).style(theme::dim()),
+            ratatui::widgets::Paragraph::new(hint).style(theme::dim()),
             inner,
         );
         return;
@@ -85,6 +100,42 @@ fn row(r: &RankedResult, selected: bool) -> Line<'static> {
     Line::from(spans)
 }
 
+/// A library hit: what kind it is, the matching words, and whose book. The
+/// reverse goes on the snippet, which is this row's title.
+fn library_row(r: &LibraryRow, selected: bool) -> Line<'static> {
+    let kind = match r.hit {
+        LibraryHit::Note(_) => "note",
+        LibraryHit::Highlight(_) => "highlight",
+    };
+    let snippet_style = if selected {
+        theme::primary().patch(theme::selected())
+    } else {
+        theme::primary()
+    };
+    // The engine marks the terms with `>>`/`<<` for a terminal that cannot
+    // style; this one can, so the markers go and the whole snippet carries the
+    // row's weight instead. A note's snippet can span lines, and a row cannot.
+    let snippet = r
+        .hit
+        .snippet()
+        .replace(">>", "")
+        .replace("<<", "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut spans = vec![
+        Span::styled(format!("{kind:<9} "), theme::dim()),
+        Span::styled(snippet, snippet_style),
+    ];
+    if let Some(b) = &r.book {
+        spans.push(Span::styled(
+            format!("  {}", b.display_title()),
+            theme::dim(),
+        ));
+    }
+    Line::from(spans)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,163 @@
+//! The study screen: the due cards, one at a time.
+//!
+//! Not a shelf — a card. The front is the word or the passage, with the book,
+//! chapter and page it came from under it; Enter shows the rest (the sentence a
+//! word was in, the note on a passage), and only then do `1`–`4` grade it.
+//! Nothing on it is counted: no "3 of 20", no tally at the end — see "No
+//! task-completion framing" in `docs/decisions.md`. What is left stays due.
+
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Padding, Paragraph};
+use readingbuddy::{CardKind, StudyCard};
+
+use crate::app::App;
+use crate::theme;
+
+const HINT: &str = "nothing due. Words come from single-word highlights; c on a highlight in a \
+                    book adds the passage.";
+
+pub fn draw(f: &mut Frame, app: &mut App, area: Rect) {
+    let (lines, keys) = match app
+        .study
+        .as_ref()
+        .and_then(|v| Some((v.current()?, v.revealed)))
+    {
+        Some((card, revealed)) => (card_lines(card, revealed), key_bar(Some(revealed))),
+        None => (
+            wrap(HINT, super::DETAIL_MAX)
+                .into_iter()
+                .map(|l| Line::from(Span::styled(l, theme::dim())))
+                .collect(),
+            key_bar(None),
+        ),
+    };
+
+    let widest = lines
+        .iter()
+        .map(|l| l.width() as u16)
+        .max()
+        .unwrap_or(0)
+        .max(keys.width() as u16);
+    let area = super::list_box(area, widest, lines.len() as u16);
+    f.render_widget(ratatui::widgets::Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme::dim())
+        .padding(Padding::horizontal(1))
+        .title(Span::styled(" review ", theme::accent()))
+        .title_bottom(keys.centered());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    // The same guard `shelf_frame` has: a 1×1 pane has no inside at all.
+    if inner.width > 0 && inner.height > 0 {
+        f.render_widget(Paragraph::new(lines), inner);
+    }
+}
+
+/// The front, where it is from, and — once shown — the back.
+fn card_lines(card: &StudyCard, revealed: bool) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line> = match card.kind {
+        CardKind::Word => vec![Line::from(Span::styled(card.front.clone(), theme::title()))],
+        CardKind::Highlight => wrap(&format!("“{}”", card.front.trim()), super::DETAIL_MAX)
+            .into_iter()
+            .map(|l| Line::from(Span::styled(l, theme::primary())))
+            .collect(),
+    };
+    lines.push(Line::from(Span::styled(
+        super::clip(provenance(card), super::DETAIL_MAX),
+        theme::dim(),
+    )));
+    if revealed {
+        lines.push(Line::default());
+        match card.context.as_deref().filter(|c| !c.trim().is_empty()) {
+            Some(ctx) => lines.extend(
+                wrap(ctx, super::DETAIL_MAX)
+                    .into_iter()
+                    .map(|l| Line::from(Span::styled(l, theme::primary()))),
+            ),
+            None => lines.push(Line::from(Span::styled(
+                "nothing more on this card",
+                theme::dim(),
+            ))),
+        }
+    }
+    lines
+}
+
+/// Book — authors · chapter · p.N, leaving out what is not known.
+fn provenance(card: &StudyCard) -> String {
+    let mut line = card.book_title.clone();
+    if !card.authors.is_empty() {
+        line.push_str(&format!(" — {}", card.authors.join(", ")));
+    }
+    if let Some(ch) = &card.chapter {
+        line.push_str(&format!(" · {ch}"));
+    }
+    if let Some(p) = card.page {
+        line.push_str(&format!(" · p.{p}"));
+    }
+    line
+}
+
+/// Greedy word wrap at `width` columns. A word longer than the line keeps a
+/// line to itself rather than being broken.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let len = line.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > width {
+            out.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() || out.is_empty() {
+        out.push(line);
+    }
+    out
+}
+
+/// `None` is the empty session; otherwise whether the back is showing.
+fn key_bar(revealed: Option<bool>) -> Line<'static> {
+    let pairs: &[(&str, &str)] = match revealed {
+        Some(false) => &[(" enter", " show  "), ("b", " stop ")],
+        Some(true) => &[
+            (" 1", " again  "),
+            ("2", " hard  "),
+            ("3", " good  "),
+            ("4", " easy "),
+        ],
+        None => &[(" b", " back  "), ("m", " menu ")],
+    };
+    Line::from(
+        pairs
+            .iter()
+            .flat_map(|(k, what)| {
+                [
+                    Span::styled(*k, theme::key()),
+                    Span::styled(*what, theme::dim()),
+                ]
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_breaks_between_words_and_keeps_a_long_one_whole() {
+        assert_eq!(wrap("one two three", 7), ["one two", "three"]);
+        assert_eq!(
+            wrap("a supercalifragilistic b", 5),
+            ["a", "supercalifragilistic", "b"]
+        );
+        assert_eq!(wrap("", 10), [""]);
+    }
+}
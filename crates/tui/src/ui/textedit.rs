@@ -29,6 +29,15 @@ impl TextEditor {
         TextEditor { lines, row, col }
     }
 
+    /// The same, with the cursor at the start of line `row` rather than at
+    /// the end — opening a note at the section a link cited.
+    pub fn at_line(initial: &str, row: usize) -> Self {
+        let mut ed = Self::new(initial);
+        ed.row = row.min(ed.lines.len() - 1);
+        ed.col = 0;
+        ed
+    }
+
     pub fn text(&self) -> String {
         self.lines.join("\n")
     }
@@ -143,7 +152,9 @@ pub fn render(f: &mut Frame, area: Rect, title: &str, ed: &TextEditor) {
             Span::styled(" ⌥/⇧↵ ", theme::key()),
             Span::styled("newline  ", theme::dim()),
             Span::styled(" ⇥ ", theme::key()),
-            Span::styled("tab", theme::dim()),
+            Span::styled("tab  ", theme::dim()),
+            Span::styled(" ^T ", theme::key()),
+            Span::styled("template", theme::dim()),
         ])),
         hint,
     );
@@ -232,6 +243,14 @@ fn wrapped_lines(ed: &TextEditor, width: usize, height: usize) -> Vec<Line<'stat
 mod tests {
     use super::*;
 
+    #[test]
+    fn at_line_starts_on_that_line_clamped_to_the_text() {
+        let ed = TextEditor::at_line("a\n## b\nc", 1);
+        assert_eq!((ed.row, ed.col), (1, 0));
+        let ed = TextEditor::at_line("a", 9);
+        assert_eq!((ed.row, ed.col), (0, 0));
+    }
+
     #[test]
     fn types_and_deletes_across_lines() {
         let mut ed = TextEditor::new("");